@@ -0,0 +1,110 @@
+/*!
+# Usage analytics
+
+Hooks for researchers studying how students learn logic, like which rules they
+reach for and which mistakes they keep making. GUIs report what happens with
+`record()`, which does nothing until a program opts in by installing a `Sink`
+with `set_sink()`. Aris itself never sends events anywhere: where they end up,
+and whether the user agreed to that, is up to the sink.
+
+Events name rules by their serialized names and errors by their stable codes,
+so they can be compared across versions of Aris and courses' rule names, and
+they hold nothing of what the student typed.
+
+```
+use aris::analytics::{self, Event, Sink};
+use std::sync::{Arc, Mutex};
+
+struct Log(Arc<Mutex<Vec<Event>>>);
+
+impl Sink for Log {
+    fn record(&self, event: &Event) {
+        self.0.lock().unwrap().push(event.clone());
+    }
+}
+
+let events = Arc::new(Mutex::new(vec![]));
+analytics::set_sink(Box::new(Log(events.clone())));
+analytics::record(&Event::RuleSelected { rule: "MODUS_PONENS".into() });
+analytics::clear_sink();
+analytics::record(&Event::HintRequested);
+assert_eq!(*events.lock().unwrap(), vec![Event::RuleSelected { rule: "MODUS_PONENS".into() }]);
+```
+*/
+
+use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Something a student did that's of interest to researchers
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event {
+    /// A rule was chosen for a step, named as in `RuleM::to_serialized_name()`
+    RuleSelected { rule: String },
+    /// A step was found to be wrong, with the code from
+    /// `ProofCheckError::code()`
+    ErrorHit { code: String },
+    /// The student asked for more help than the feedback shown up front, like
+    /// the details of an error
+    HintRequested,
+}
+
+impl Event {
+    /// Write the event as JSON, like
+    /// `{"event":"rule-selected","rule":"MODUS_PONENS"}`
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("events are always serializable")
+    }
+}
+
+/// Where recorded events go
+pub trait Sink: Send + Sync {
+    fn record(&self, event: &Event);
+}
+
+lazy_static! {
+    static ref SINK: RwLock<Option<Arc<dyn Sink>>> = RwLock::new(None);
+}
+
+/// Send every event recorded from now on to `sink`, instead of the sink
+/// installed before, if any
+pub fn set_sink(sink: Box<dyn Sink>) {
+    *SINK.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::from(sink));
+}
+
+/// Go back to dropping events, as before any sink was installed
+pub fn clear_sink() {
+    *SINK.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Whether a sink is installed, so callers can skip the work of finding out
+/// about events that would be dropped
+pub fn is_enabled() -> bool {
+    SINK.read().unwrap_or_else(PoisonError::into_inner).is_some()
+}
+
+/// Pass an event to the installed sink, if there is one
+pub fn record(event: &Event) {
+    // The lock isn't held while the sink runs, so sinks can replace themselves
+    let sink = SINK.read().unwrap_or_else(PoisonError::into_inner).clone();
+    if let Some(sink) = sink {
+        sink.record(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_json() {
+        assert_eq!(Event::RuleSelected { rule: "MODUS_PONENS".into() }.to_json(), r#"{"event":"rule-selected","rule":"MODUS_PONENS"}"#);
+        assert_eq!(Event::ErrorHit { code: "does-not-occur".into() }.to_json(), r#"{"event":"error-hit","code":"does-not-occur"}"#);
+        assert_eq!(Event::HintRequested.to_json(), r#"{"event":"hint-requested"}"#);
+        assert_eq!(serde_json::from_str::<Event>(r#"{"event":"error-hit","code":"does-not-occur"}"#).unwrap(), Event::ErrorHit { code: "does-not-occur".into() });
+    }
+}
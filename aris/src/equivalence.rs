@@ -0,0 +1,132 @@
+/*!
+# Deciding whether formulas are equivalent
+
+Propositional formulas are compared by asking a SAT solver for a valuation
+where one is true and the other false, which decides equivalence outright.
+First-order formulas, and formulas with arithmetic, can't be decided that way,
+so they're compared by searching the small models `crate::soundness` tries; not
+finding one that tells them apart is evidence, but no proof, that they're
+equivalent.
+
+```
+use aris::equivalence::Equivalence;
+use aris::parser::parse_unwrap as p;
+
+assert_eq!(aris::are_equivalent(&p("~(A & B)"), &p("~A | ~B")), Equivalence::Equivalent);
+assert!(matches!(aris::are_equivalent(&p("A -> B"), &p("B -> A")), Equivalence::Inequivalent { .. }));
+assert_eq!(aris::are_equivalent(&p("~forall x P(x)"), &p("exists x ~P(x)")), Equivalence::NoCounterexample);
+```
+*/
+
+use crate::expr::Expr;
+use crate::expr::Op;
+use crate::soundness;
+use crate::soundness::Config;
+use crate::soundness::Countermodel;
+
+use std::collections::BTreeMap;
+
+/// Most CNF clauses to hand the SAT solver. Formulas needing more are compared
+/// by trying valuations instead.
+const MAX_CLAUSES: u64 = 1 << 12;
+
+/// Whether two formulas are true in the same models
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Equivalence {
+    /// The formulas are propositional and true in the same valuations
+    Equivalent,
+    /// No model tried tells the formulas apart, but they're first-order or
+    /// use arithmetic, so a larger model still might. Propositional formulas
+    /// too large for the SAT solver are also only compared in the valuations
+    /// `soundness::countermodel` tries.
+    NoCounterexample,
+    /// The formulas differ in this model, where the first is true if
+    /// `first_holds`, and the second is otherwise
+    Inequivalent { countermodel: Countermodel, first_holds: bool },
+    /// The formulas couldn't be compared, for this reason
+    Unknown(String),
+}
+
+impl Equivalence {
+    /// Whether the formulas are equivalent, or at least no model tried tells
+    /// them apart
+    pub fn is_equivalent(&self) -> bool {
+        matches!(self, Equivalence::Equivalent | Equivalence::NoCounterexample)
+    }
+}
+
+/// Decide whether two propositional formulas are equivalent with a SAT solver,
+/// or `None` if they aren't propositional or are too large to convert to CNF
+pub fn are_propositionally_equivalent(a: &Expr, b: &Expr) -> Option<bool> {
+    satisfy(!Expr::assoc(Op::Bicon, &[a.clone(), b.clone()])).map(|valuation| valuation.is_none())
+}
+
+/// A valuation making a propositional formula true, found by a SAT solver, or
+/// `None` inside if there isn't one. Returns `None` if the formula isn't
+/// propositional or is too large to convert to CNF.
+fn satisfy(e: Expr) -> Option<Option<BTreeMap<String, bool>>> {
+    let nnf = e.into_nnf()?;
+    if nnf.cnf_clause_count() > MAX_CLAUSES {
+        return None;
+    }
+    let (sat, vars) = nnf.into_cnf().to_varisat();
+    let mut solver = varisat::Solver::new();
+    solver.add_formula(&sat);
+    // Does not panic on the default config
+    solver.solve().expect("varisat error");
+    Some(solver.model().map(|model| model.into_iter().filter_map(|lit| Some((vars.get(&lit.var())?.clone(), lit.is_positive()))).collect()))
+}
+
+/// Search for a case where `premise` is true and `conclusion` is false,
+/// deciding it with a SAT solver for propositional formulas, and trying the
+/// models `config` allows otherwise, as `soundness::countermodel` does
+pub fn counterexample(premise: &Expr, conclusion: &Expr, config: &Config) -> Result<Option<Countermodel>, String> {
+    match satisfy(Expr::assoc(Op::And, &[premise.clone(), !conclusion.clone()])) {
+        Some(valuation) => Ok(valuation.map(|valuation| Countermodel::Valuation(valuation.into_iter().map(|(name, value)| (name, vec![value])).collect()))),
+        None => soundness::countermodel(std::slice::from_ref(premise), conclusion, config),
+    }
+}
+
+/// Whether two formulas are equivalent, trying the default models for
+/// formulas that aren't propositional
+pub fn are_equivalent(a: &Expr, b: &Expr) -> Equivalence {
+    are_equivalent_within(a, b, &Config::default())
+}
+
+/// Whether two formulas are equivalent, trying the models `config` allows for
+/// formulas that aren't propositional
+pub fn are_equivalent_within(a: &Expr, b: &Expr, config: &Config) -> Equivalence {
+    if are_propositionally_equivalent(a, b) == Some(true) {
+        return Equivalence::Equivalent;
+    }
+    for (first, second, first_holds) in [(a, b, true), (b, a, false)] {
+        match counterexample(first, second, config) {
+            Ok(None) => {}
+            Ok(Some(countermodel)) => return Equivalence::Inequivalent { countermodel, first_holds },
+            Err(reason) => return Equivalence::Unknown(reason),
+        }
+    }
+    Equivalence::NoCounterexample
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+
+    #[test]
+    fn test_equivalence() {
+        assert_eq!(are_propositionally_equivalent(&p("A -> B"), &p("~A | B")), Some(true));
+        assert_eq!(are_propositionally_equivalent(&p("A"), &p("A & B")), Some(false));
+        assert_eq!(are_propositionally_equivalent(&p("P(a)"), &p("P(a)")), None);
+
+        let countermodel = Countermodel::Valuation([("A".to_string(), vec![true]), ("B".to_string(), vec![false])].into_iter().collect());
+        assert_eq!(counterexample(&p("A"), &p("A & B"), &Config::default()), Ok(Some(countermodel.clone())));
+        assert_eq!(are_equivalent(&p("A & B"), &p("A")), Equivalence::Inequivalent { countermodel, first_holds: false });
+        assert_eq!(counterexample(&p("A & B"), &p("A"), &Config::default()), Ok(None));
+
+        assert!(are_equivalent(&p("forall x (P(x) & Q(x))"), &p("forall x P(x) & forall y Q(y)")).is_equivalent());
+        assert!(matches!(are_equivalent(&p("exists x P(x)"), &p("forall x P(x)")), Equivalence::Inequivalent { first_holds: true, .. }));
+        assert!(matches!(are_equivalent(&p("forall x P(f(x))"), &p("forall y P(f(y))")), Equivalence::Unknown(_)));
+    }
+}
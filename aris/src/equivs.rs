@@ -1,6 +1,7 @@
 //! Patterns for rewriting equivalences (a specific type of rule).
 
 use crate::rewrite_rules::RewriteRule;
+use crate::rewrite_rules::SideCondition;
 
 /// Defines literal data used for a rewrite rule.
 ///
@@ -13,7 +14,23 @@ use crate::rewrite_rules::RewriteRule;
 ///     ]
 /// }
 /// ```
+///
+/// Reductions with side conditions (see `ConditionalReduction`) are defined with `conditional:`
+///
+/// ```ignore
+/// define_rewrite_rule! {
+///     NAME_OF_RULE,
+///     conditional: &[
+///         ("pattern", "replacement", &[SideCondition::not_free("x", "phi")]),
+///     ]
+/// }
+/// ```
 macro_rules! define_rewrite_rule {
+    ($name:ident, conditional: $rules:expr) => {
+        lazy_static! {
+            pub static ref $name: RewriteRule = RewriteRule::from_conditional_patterns($rules);
+        }
+    };
     ($name:ident, $rules:expr) => {
         lazy_static! {
             pub static ref $name: RewriteRule = RewriteRule::from_patterns($rules);
@@ -192,6 +209,28 @@ define_rewrite_rule! {
     ]
 }
 
+// Quantifier Equivalences
+define_rewrite_rule! {
+    NULL_QUANTIFICATION,
+    conditional: &[
+        ("forall x phi", "phi", &[SideCondition::not_free("x", "phi")]),
+        ("exists x phi", "phi", &[SideCondition::not_free("x", "phi")]),
+    ]
+}
+define_rewrite_rule! {
+    QUANTIFIER_DISTRIBUTION,
+    conditional: &[
+        ("forall x (phi & psi)", "(forall x phi) & (forall x psi)", &[]),
+        ("exists x (phi | psi)", "(exists x phi) | (exists x psi)", &[]),
+    ]
+}
+define_rewrite_rule! {
+    SWAPPING_QUANTIFIERS,
+    conditional: &[
+        ("forall x forall y phi", "forall y forall x phi", &[SideCondition::sorts_after("x", "y")]),
+        ("exists x exists y phi", "exists y exists x phi", &[SideCondition::sorts_after("x", "y")]),
+    ]
+}
 define_rewrite_rule! {
     CONJUNCTION,
     &[
@@ -239,49 +278,41 @@ define_rewrite_rule! {
 mod tests {
     use super::*;
 
-    use crate::expr::free_vars;
-
-    fn for_each_truthtable<F>(n: usize, mut f: F)
-    where
-        F: FnMut(&[bool]),
-    {
-        let mut table = vec![false; n];
-        for x in 0..(2usize.pow(n as _)) {
-            for (i, value) in table.iter_mut().enumerate() {
-                *value = (x & (1 << i)) != 0;
-            }
-            f(&table[..]);
-        }
-    }
+    use crate::soundness;
+    use crate::soundness::Config;
 
     /// Test function to verify the logical equivalence of rewrite rules using brute-force truth tables.
     #[test]
     fn bruteforce_equivalence_truthtables() {
-        use std::collections::HashMap;
         let rules: Vec<&RewriteRule> = vec![&*DOUBLE_NEGATION, &*DISTRIBUTION, &*IDENTITY, &*ANNIHILATION, &*INVERSE, &*CONDITIONAL_ABSORPTION, &*CONDITIONAL_ANNIHILATION, &*CONDITIONAL_IMPLICATION, &*CONDITIONAL_CONTRAPOSITION, &*CONDITIONAL_EXPORTATION, &*CONDITIONAL_COMPLEMENT, &*CONDITIONAL_IDENTITY, &*CONDITIONAL_DISTRIBUTION, &*CONDITIONAL_REDUCTION, &*KNIGHTS_AND_KNAVES, &*CONDITIONAL_IDEMPOTENCE, &*BICONDITIONAL_ASSOCIATION, &*BICONDITIONAL_COMMUTATION, &*BICONDITIONAL_REDUCTION, &*BICONDITIONAL_COMPLEMENT, &*BICONDITIONAL_IDENTITY, &*BICONDITIONAL_EQUIVALENCE, &*BICONDITIONAL_NEGATION, &*BICONDITIONAL_SUBSTITUTION];
         for rule in rules {
             for (lhs, rhs) in rule.reductions.iter() {
-                println!("Testing {lhs} -> {rhs}");
-                let mut fvs: Vec<String> = free_vars(lhs).union(&free_vars(rhs)).cloned().collect();
-                fvs.sort();
-                let mut arities = HashMap::new();
-                lhs.infer_arities(&mut arities);
-                rhs.infer_arities(&mut arities);
-                println!("Inferred arities: {arities:?}");
-                let total_arity = arities.values().map(|v| 2usize.pow(*v as _)).sum();
-                for_each_truthtable(total_arity, |table| {
-                    let mut env = HashMap::new();
-                    let mut i = 0;
-                    for fv in fvs.iter().cloned() {
-                        let n = 2usize.pow(arities[&fv] as _);
-                        env.insert(fv, table[i..i + n].to_vec());
-                        i += n;
-                    }
-                    println!("{table:?} {env:?}");
-                    assert_eq!(lhs.eval(&env), rhs.eval(&env));
-                });
-                println!("-----");
+                if let Err(err) = soundness::check_equivalence(lhs, rhs, &Config::default()) {
+                    panic!("{lhs} -> {rhs}: {err}");
+                }
+            }
+        }
+    }
+
+    /// Test the quantifier equivalences in every model with up to two elements. Their patterns are instantiated
+    /// with sample formulas, and every instance that the side conditions allow must preserve truth.
+    #[test]
+    fn quantifier_equivalences_hold_in_finite_models() {
+        use crate::parser::parse_unwrap as p;
+
+        let config = Config { max_domain: 2, ..Config::default() };
+        let formulas = ["A", "P(x)", "P(y)", "R(x, y)", "P(x) | R(y, y)", "forall x R(x, y)", "~exists y R(y, x)"].map(p);
+        let rules: Vec<&RewriteRule> = vec![&*NULL_QUANTIFICATION, &*QUANTIFIER_DISTRIBUTION, &*SWAPPING_QUANTIFIERS];
+        for rule in rules {
+            for reduction in &rule.conditional_reductions {
+                match soundness::check_conditional_reduction(reduction, &formulas, &config) {
+                    Ok(applied) => assert!(applied > 0, "{} -> {} never applied", reduction.pattern, reduction.replacement),
+                    Err(err) => panic!("{} -> {}: {err}", reduction.pattern, reduction.replacement),
+                }
             }
         }
+        // The side conditions rule out unsound instances
+        assert_eq!(NULL_QUANTIFICATION.reduce(p("forall x P(x)")), p("forall x P(x)"));
+        assert_eq!(SWAPPING_QUANTIFIERS.reduce(p("forall y exists x R(x, y)")), p("forall y exists x R(x, y)"));
     }
 }
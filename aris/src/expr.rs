@@ -26,9 +26,13 @@ assert_eq!(&handle_user_input("bad(missing, paren"), "unsuccessful parse");
 ```
 */
 
+use crate::parser::LogicMode;
+
 use std::collections::BTreeSet;
 use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::ops::Not;
+use std::rc::Rc;
 use std::{fmt, mem};
 
 use itertools::Itertools;
@@ -50,10 +54,104 @@ pub enum Op {
     Equiv,
     /// Arithmetic addition `+`
     Add,
-    /// Arithmetic multiplication `*`
+    /// Arithmetic multiplication `×`
     Mult,
 }
 
+/// Name of the predicate `<` on natural numbers. Like numerals, it's interpreted rather than being an arbitrary
+/// symbol, but it's represented the same way: `x < y` is an `Apply` of the variable `<` to `x` and `y`.
+pub const LESS: &str = "<";
+
+/// Name of the predicate `≤` on natural numbers, see `LESS`
+pub const LESS_EQ: &str = "≤";
+
+/// Name of the equality predicate `=`, see `LESS`
+pub const EQUALS: &str = "=";
+
+/// Name of the membership predicate `∈` of sets, see `LESS`
+pub const ELEMENT: &str = "∈";
+
+/// Name of the subset predicate `⊆`, see `LESS`
+pub const SUBSET: &str = "⊆";
+
+/// Name of the union function `∪` of sets. Like the relations, `A ∪ B` is an `Apply` of the variable `∪` to `A` and `B`.
+pub const UNION: &str = "∪";
+
+/// Name of the intersection function `∩` of sets, see `UNION`
+pub const INTERSECTION: &str = "∩";
+
+/// Name of the empty set `∅`, which is a variable like numerals are
+pub const EMPTY_SET: &str = "∅";
+
+/// Name of the temporal operator `G φ`, that `φ` is true from now on, which is applied to formulas like a predicate
+/// in temporal logic
+pub const ALWAYS: &str = "G";
+
+/// Name of the temporal operator `F φ`, that `φ` is true now or at some point later, see `ALWAYS`
+pub const EVENTUALLY: &str = "F";
+
+/// Name of the temporal operator `X φ`, that `φ` is true at the next point in time, see `ALWAYS`
+pub const NEXT: &str = "X";
+
+/// Name of the temporal operator `φ U ψ`, that `ψ` is true at some point, and `φ` is until then, see `ALWAYS`
+pub const UNTIL: &str = "U";
+
+/// Name of a Hoare triple `{P} S {Q}`, that running the program `S` from a state where `P` is true ends, if it does,
+/// in one where `Q` is. It's an application of this name to `P`, `S`, and `Q`, where the program is an expression
+/// built from `SKIP`, `ASSIGN`, `SEQUENCE`, `IF`, and `WHILE`.
+pub const HOARE_TRIPLE: &str = "hoare";
+
+/// Name of the program `skip`, which does nothing
+pub const SKIP: &str = "skip";
+
+/// Name of the assignment `x := e`, applied to `x` and `e`
+pub const ASSIGN: &str = "assign";
+
+/// Name of the sequence `S; T`, applied to `S` and `T`
+pub const SEQUENCE: &str = "seq";
+
+/// Name of the conditional `if b then S else T end`, applied to `b`, `S`, and `T`
+pub const IF: &str = "if";
+
+/// Name of the loop `while b do S end`, applied to `b` and `S`
+pub const WHILE: &str = "while";
+
+/// Names of the relations and functions written between their two arguments
+const INFIX: [&str; 7] = [LESS, LESS_EQ, EQUALS, ELEMENT, SUBSET, UNION, INTERSECTION];
+
+/// A statement of a program in a Hoare triple, with the expressions it's built from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Statement<'a> {
+    /// `skip`
+    Skip,
+    /// `x := e`
+    Assign(&'a str, &'a Expr),
+    /// `S; T`
+    Sequence(&'a Expr, &'a Expr),
+    /// `if b then S else T end`
+    If(&'a Expr, &'a Expr, &'a Expr),
+    /// `while b do S end`
+    While(&'a Expr, &'a Expr),
+}
+
+/// A program displayed the way it's parsed, rather than as the expression it's represented by
+struct ProgramDisplay<'a>(&'a Expr);
+
+impl fmt::Display for ProgramDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.as_statement() {
+            Some(Statement::Skip) => write!(f, "{SKIP}"),
+            Some(Statement::Assign(name, value)) => write!(f, "{} := {value}", display_name(name)),
+            // Sequences are parsed grouped to the right
+            Some(Statement::Sequence(first, second)) if matches!(first.as_statement(), Some(Statement::Sequence(..))) => write!(f, "({}); {}", ProgramDisplay(first), ProgramDisplay(second)),
+            Some(Statement::Sequence(first, second)) => write!(f, "{}; {}", ProgramDisplay(first), ProgramDisplay(second)),
+            Some(Statement::If(cond, then, otherwise)) => write!(f, "if {cond} then {} else {} end", ProgramDisplay(then), ProgramDisplay(otherwise)),
+            Some(Statement::While(cond, body)) => write!(f, "while {cond} do {} end", ProgramDisplay(body)),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
 /// Kinds of quantifiers
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 #[repr(C)]
@@ -62,10 +160,36 @@ pub enum QuantKind {
     Forall,
     /// Existential quantifier `∃`
     Exists,
+    /// Set builder `{x : φ(x)}`, the set of everything that satisfies `φ`. It binds its variable like a quantifier,
+    /// but it makes a term rather than a formula, so quantifier equivalences don't apply to it.
+    SetBuilder,
+    /// Lambda abstraction `λx φ(x)`, the function taking `x` to `φ(x)`, in higher-order logic. Like a set builder, it
+    /// binds its variable without quantifying over it.
+    Lambda,
+}
+
+impl QuantKind {
+    /// Whether this is a quantifier `∀` or `∃`, as opposed to another kind of binder
+    pub fn is_quantifier(self) -> bool {
+        matches!(self, QuantKind::Forall | QuantKind::Exists)
+    }
+}
+
+/// Order in which `Expr::visit`, `Expr::visit_mut`, and `Expr::fold` reach the nodes of an expression
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TraversalOrder {
+    /// A node is reached before its subexpressions
+    PreOrder,
+    /// A node is reached after its subexpressions
+    PostOrder,
 }
 
 /// A logical expression
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+///
+/// Cloning, comparing, hashing, and showing an expression, like the traversals below, use explicit stacks instead
+/// of recursion, so they work however deeply it's nested. Dropping and ordering one still recurse, which is why
+/// the parser doesn't build expressions nested more than `parser::ParseLimits::max_depth` levels deep.
+#[derive(Debug, Eq, Ord, PartialOrd, Serialize, Deserialize)]
 #[repr(C)]
 pub enum Expr {
     /// Contradiction `⊥`
@@ -181,7 +305,7 @@ impl fmt::Display for Op {
             Op::Bicon => write!(f, "↔"),
             Op::Equiv => write!(f, "≡"),
             Op::Add => write!(f, "+"),
-            Op::Mult => write!(f, "*"),
+            Op::Mult => write!(f, "×"),
         }
     }
 }
@@ -191,10 +315,39 @@ impl fmt::Display for QuantKind {
         match self {
             QuantKind::Forall => write!(f, "∀"),
             QuantKind::Exists => write!(f, "∃"),
+            QuantKind::SetBuilder => write!(f, "{{:}}"),
+            QuantKind::Lambda => write!(f, "λ"),
         }
     }
 }
 
+/// A name with its numbered subscript and primes typeset, like `x₁′` for `x_1'` or `P₂` for `P_2`, the way expressions
+/// are displayed. The parser reads both spellings as the same name, so displayed expressions still parse back to
+/// themselves. Other names are left alone.
+///
+/// ```rust
+/// use aris::expr::display_name;
+///
+/// assert_eq!(display_name("x_12"), "x₁₂");
+/// assert_eq!(display_name("f''"), "f′′");
+/// assert_eq!(display_name("n_1'"), "n₁′");
+/// assert_eq!(display_name("x_a"), "x_a");
+/// assert_eq!(display_name("_1"), "_1");
+/// ```
+pub fn display_name(name: &str) -> std::borrow::Cow<'_, str> {
+    let unprimed = name.trim_end_matches('\'');
+    let subscripted = unprimed.rsplit_once('_').filter(|(base, digits)| !base.is_empty() && !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()));
+    if subscripted.is_none() && unprimed.len() == name.len() {
+        return name.into();
+    }
+    let mut ret = match subscripted {
+        Some((base, digits)) => base.to_owned() + &digits.chars().map(|d| char::from_u32('₀' as u32 + (d as u32 - '0' as u32)).unwrap_or(d)).collect::<String>(),
+        None => unprimed.to_owned(),
+    };
+    ret.push_str(&"′".repeat(name.len() - unprimed.len()));
+    ret.into()
+}
+
 /// Format associative operator expression
 ///
 /// ## Parameters
@@ -211,15 +364,312 @@ where
 
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Pieces still to be written, last first, so deeply nested expressions are written without recursion
+        enum Piece<'a> {
+            Expr(&'a Expr),
+            Text(String),
+        }
+        let text = |s: &str| Piece::Text(s.to_owned());
+        let mut stack = vec![Piece::Expr(self)];
+        while let Some(piece) = stack.pop() {
+            let e = match piece {
+                Piece::Expr(e) => e,
+                Piece::Text(s) => {
+                    f.write_str(&s)?;
+                    continue;
+                }
+            };
+            let pieces = if let (Some((pre, program, post)), LogicMode::Program) = (e.as_triple(), crate::parser::logic_mode()) {
+                vec![Piece::Text(format!("{{{pre}}} {} {{{post}}}", ProgramDisplay(program)))]
+            } else if let Some((symbol, operand)) = e.as_temporal_prefix() {
+                vec![Piece::Text(format!("{symbol} ")), Piece::Expr(operand)]
+            } else if let Some((symbol, left, right)) = e.as_infix() {
+                vec![text("("), Piece::Expr(left), Piece::Text(format!(" {symbol} ")), Piece::Expr(right), text(")")]
+            } else {
+                match e {
+                    Expr::Contra => vec![text("⊥")],
+                    Expr::Taut => vec![text("⊤")],
+                    Expr::Var { name } => vec![text(&display_name(name))],
+                    Expr::Apply { func, args } => {
+                        let args = args.iter().enumerate().flat_map(|(i, arg)| [(i > 0).then(|| text(", ")), Some(Piece::Expr(arg))]).flatten();
+                        std::iter::once(Piece::Expr(func)).chain(std::iter::once(text("("))).chain(args).chain(std::iter::once(text(")"))).collect()
+                    }
+                    Expr::Not { operand } => vec![text("¬"), Piece::Expr(operand)],
+                    Expr::Impl { left, right } => vec![text("("), Piece::Expr(left), text(" → "), Piece::Expr(right), text(")")],
+                    Expr::Assoc { op, exprs } => {
+                        let operands = exprs.iter().enumerate().flat_map(|(i, e)| [(i > 0).then(|| Piece::Text(format!(" {op} "))), Some(Piece::Expr(e))]).flatten();
+                        std::iter::once(text("(")).chain(operands).chain(std::iter::once(text(")"))).collect()
+                    }
+                    Expr::Quant { kind: QuantKind::SetBuilder, name, body } => vec![Piece::Text(format!("{{{} : ", display_name(name))), Piece::Expr(body), text("}")],
+                    Expr::Quant { kind, name, body } => vec![Piece::Text(format!("({kind} {} ", display_name(name))), Piece::Expr(body), text(")")],
+                }
+            };
+            stack.extend(pieces.into_iter().rev());
+        }
+        Ok(())
+    }
+}
+
+impl Clone for Expr {
+    fn clone(&self) -> Expr {
+        // Copies are built from the leaves up, with the subexpressions already copied on `built`
+        let mut stack = vec![(self, false)];
+        let mut built = vec![];
+        while let Some((e, children_built)) = stack.pop() {
+            if children_built {
+                let children = built.split_off(built.len() - e.children().len());
+                built.push(e.with_children(children));
+            } else {
+                stack.push((e, true));
+                stack.extend(e.children().into_iter().rev().map(|child| (child, false)));
+            }
+        }
+        built.pop().expect("the copy of the whole expression is built last")
+    }
+}
+
+impl PartialEq for Expr {
+    fn eq(&self, other: &Expr) -> bool {
+        let mut stack = vec![(self, other)];
+        while let Some((a, b)) = stack.pop() {
+            let same_node = match (a, b) {
+                (Expr::Contra, Expr::Contra) | (Expr::Taut, Expr::Taut) | (Expr::Not { .. }, Expr::Not { .. }) | (Expr::Impl { .. }, Expr::Impl { .. }) => true,
+                (Expr::Var { name: x }, Expr::Var { name: y }) => x == y,
+                (Expr::Apply { args: xs, .. }, Expr::Apply { args: ys, .. }) => xs.len() == ys.len(),
+                (Expr::Assoc { op: x, exprs: xs }, Expr::Assoc { op: y, exprs: ys }) => x == y && xs.len() == ys.len(),
+                (Expr::Quant { kind: k1, name: x, .. }, Expr::Quant { kind: k2, name: y, .. }) => k1 == k2 && x == y,
+                _ => false,
+            };
+            if !same_node {
+                return false;
+            }
+            stack.extend(a.children().into_iter().zip(b.children()));
+        }
+        true
+    }
+}
+
+impl Hash for Expr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Each node is hashed along with how many subexpressions it has, so the order they're hashed in determines
+        // the expression
+        let mut stack = vec![self];
+        while let Some(e) = stack.pop() {
+            mem::discriminant(e).hash(state);
+            match e {
+                Expr::Var { name } => name.hash(state),
+                Expr::Apply { args, .. } => args.len().hash(state),
+                Expr::Assoc { op, exprs } => (op, exprs.len()).hash(state),
+                Expr::Quant { kind, name, .. } => (kind, name).hash(state),
+                Expr::Contra | Expr::Taut | Expr::Not { .. } | Expr::Impl { .. } => {}
+            }
+            stack.extend(e.children().into_iter().rev());
+        }
+    }
+}
+
+/// How many parentheses a formula is written with, since textbooks differ in which ones they leave out
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Parenthesization {
+    /// Every binary connective, comparison, and quantifier in parentheses of its own, like `((A ∧ B) → C)`, the way
+    /// expressions are displayed by default
+    #[default]
+    Full,
+    /// Like `Full`, but without the outermost parentheses, like `(A ∧ B) → C`. The parser reads this too.
+    Conventional,
+    /// Only the parentheses needed by the usual precedence, where `¬` and the quantifiers bind tightest, then terms
+    /// and comparisons, then `∧`, `∨`, `→`, and `↔` and `≡`, like `A ∧ B → C`. Nested implications are still
+    /// parenthesized rather than grouped to the right. The parser doesn't read formulas written this way, so it's
+    /// only for showing them.
+    Minimal,
+}
+
+impl Parenthesization {
+    /// Name of the parenthesization, used for saving it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Parenthesization::Full => "full",
+            Parenthesization::Conventional => "conventional",
+            Parenthesization::Minimal => "minimal",
+        }
+    }
+}
+
+impl std::str::FromStr for Parenthesization {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Parenthesization::Full),
+            "conventional" => Ok(Parenthesization::Conventional),
+            "minimal" => Ok(Parenthesization::Minimal),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Whether the whole of `s` is in a pair of parentheses, rather than it starting with a parenthesized part
+pub(crate) fn enclosed_in_parens(s: &str) -> bool {
+    if !s.starts_with('(') || !s.ends_with(')') {
+        return false;
+    }
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return i == s.len() - 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// An expression displayed with a `Parenthesization`, as given by `Expr::display_with()`
+pub struct ParenthesizedDisplay<'a> {
+    expr: &'a Expr,
+    parens: Parenthesization,
+}
+
+/// How tightly the outermost operator of an expression binds its operands in `Parenthesization::Minimal`, from
+/// loosest to tightest, following the parser's precedence of terms. Operands are parenthesized unless their level is
+/// higher than the operator's, or at least `Prefix` for a prefix operator.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    Bicon,
+    Impl,
+    Or,
+    And,
+    Comparison,
+    Declared,
+    SetOp,
+    Add,
+    Mult,
+    Prefix,
+    Atom,
+}
+
+impl Level {
+    fn of(e: &Expr) -> Level {
+        if e.as_temporal_prefix().is_some() {
+            return Level::Prefix;
+        }
+        if let Some((symbol, _, _)) = e.as_infix() {
+            return match symbol {
+                UNION | INTERSECTION => Level::SetOp,
+                _ if crate::parser::is_declared_operator(symbol) => Level::Declared,
+                _ => Level::Comparison,
+            };
+        }
+        match e {
+            Expr::Contra | Expr::Taut | Expr::Var { .. } | Expr::Apply { .. } | Expr::Quant { kind: QuantKind::SetBuilder, .. } => Level::Atom,
+            Expr::Not { .. } | Expr::Quant { .. } => Level::Prefix,
+            Expr::Impl { .. } => Level::Impl,
+            Expr::Assoc { op: Op::And, .. } => Level::And,
+            Expr::Assoc { op: Op::Or, .. } => Level::Or,
+            Expr::Assoc { op: Op::Bicon | Op::Equiv, .. } => Level::Bicon,
+            Expr::Assoc { op: Op::Add, .. } => Level::Add,
+            Expr::Assoc { op: Op::Mult, .. } => Level::Mult,
+        }
+    }
+
+    /// The level operands of a binary operator at this level need, so that
+    /// ones with the same operator are parenthesized, since they're grouped
+    /// differently
+    fn operand(self) -> Level {
         match self {
-            Expr::Contra => write!(f, "⊥"),
-            Expr::Taut => write!(f, "⊤"),
-            Expr::Var { name } => write!(f, "{name}"),
-            Expr::Apply { func, args } => write!(f, "{}({})", func, args.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", ")),
-            Expr::Not { operand } => write!(f, "¬{operand}"),
-            Expr::Impl { left, right } => write!(f, "({left} → {right})"),
-            Expr::Assoc { op, exprs } => assoc_display_helper(f, op, exprs),
-            Expr::Quant { kind, name, body } => write!(f, "({kind} {name} {body})"),
+            Level::Bicon => Level::Impl,
+            Level::Impl => Level::Or,
+            Level::Or => Level::And,
+            Level::And => Level::Comparison,
+            Level::Comparison => Level::Declared,
+            Level::Declared => Level::SetOp,
+            Level::SetOp => Level::Add,
+            Level::Add => Level::Mult,
+            Level::Mult | Level::Prefix | Level::Atom => Level::Prefix,
+        }
+    }
+}
+
+/// Write `e` with as few parentheses as it needs to be an operand of an operator that binds its operands at `level`
+fn write_minimal(f: &mut fmt::Formatter, e: &Expr, level: Level) -> fmt::Result {
+    if Level::of(e) < level {
+        write!(f, "(")?;
+        write_minimal(f, e, Level::Bicon)?;
+        return write!(f, ")");
+    }
+    if let (Some(_), LogicMode::Program) = (e.as_triple(), crate::parser::logic_mode()) {
+        return write!(f, "{e}");
+    }
+    if let Some((symbol, operand)) = e.as_temporal_prefix() {
+        write!(f, "{symbol} ")?;
+        return write_minimal(f, operand, Level::Prefix);
+    }
+    if let Some((symbol, left, right)) = e.as_infix() {
+        write_minimal(f, left, Level::of(e).operand())?;
+        write!(f, " {symbol} ")?;
+        return write_minimal(f, right, Level::of(e).operand());
+    }
+    match e {
+        Expr::Contra | Expr::Taut | Expr::Var { .. } => write!(f, "{e}"),
+        Expr::Apply { func, args } => {
+            write_minimal(f, func, Level::Atom)?;
+            write!(f, "(")?;
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write_minimal(f, arg, Level::Bicon)?;
+            }
+            write!(f, ")")
+        }
+        Expr::Not { operand } => {
+            write!(f, "¬")?;
+            write_minimal(f, operand, Level::Prefix)
+        }
+        Expr::Impl { left, right } => {
+            write_minimal(f, left, Level::Impl.operand())?;
+            write!(f, " → ")?;
+            write_minimal(f, right, Level::Impl.operand())
+        }
+        Expr::Assoc { op, exprs } => {
+            for (i, operand) in exprs.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {op} ")?;
+                }
+                write_minimal(f, operand, Level::of(e).operand())?;
+            }
+            Ok(())
+        }
+        Expr::Quant { kind: QuantKind::SetBuilder, name, body } => {
+            write!(f, "{{{} : ", display_name(name))?;
+            write_minimal(f, body, Level::Bicon)?;
+            write!(f, "}}")
+        }
+        Expr::Quant { kind, name, body } => {
+            write!(f, "{kind} {} ", display_name(name))?;
+            write_minimal(f, body, Level::Prefix)
+        }
+    }
+}
+
+impl fmt::Display for ParenthesizedDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.parens {
+            Parenthesization::Full => write!(f, "{}", self.expr),
+            Parenthesization::Conventional => {
+                let full = self.expr.to_string();
+                if enclosed_in_parens(&full) {
+                    write!(f, "{}", &full[1..full.len() - 1])
+                } else {
+                    write!(f, "{full}")
+                }
+            }
+            Parenthesization::Minimal => write_minimal(f, self.expr, Level::Bicon),
         }
     }
 }
@@ -241,25 +691,40 @@ impl fmt::Display for NnfExpr {
 ///
 /// 1: https://en.wikipedia.org/wiki/Free_variables_and_bound_variables
 pub fn free_vars(expr: &Expr) -> HashSet<String> {
-    match expr {
-        Expr::Contra => hashset![],
-        Expr::Taut => hashset![],
-        Expr::Var { name } => hashset![name.clone()],
-        Expr::Apply { func, args } => {
-            // Iterator over free vars in arguments
-            let arg_free_vars = args.iter().flat_map(free_vars);
-
-            free_vars(func).into_iter().chain(arg_free_vars).collect()
-        }
-        Expr::Not { operand } => free_vars(operand),
-        Expr::Impl { left, right, .. } => &free_vars(left) | &free_vars(right),
-        Expr::Assoc { exprs, .. } => exprs.iter().flat_map(free_vars).collect(),
-        Expr::Quant { name, body, .. } => {
-            let mut ret = free_vars(body);
-            ret.remove(name);
-            ret
+    // Walk the expression with an explicit stack instead of recursion, so a deeply nested
+    // expression can't overflow the call stack. `Unbind` marks the end of a quantifier's body.
+    enum Frame<'a> {
+        Visit(&'a Expr),
+        Unbind(&'a str),
+    }
+    let mut ret = HashSet::new();
+    // How many quantifiers around the current subexpression bind each name
+    let mut bound: HashMap<&str, usize> = HashMap::new();
+    let mut stack = vec![Frame::Visit(expr)];
+    while let Some(frame) = stack.pop() {
+        match frame {
+            Frame::Visit(Expr::Var { name }) => {
+                if !bound.contains_key(name.as_str()) {
+                    ret.insert(name.clone());
+                }
+            }
+            Frame::Visit(Expr::Quant { name, body, .. }) => {
+                *bound.entry(name).or_insert(0) += 1;
+                stack.push(Frame::Unbind(name));
+                stack.push(Frame::Visit(body));
+            }
+            Frame::Visit(e) => stack.extend(e.children().into_iter().map(Frame::Visit)),
+            Frame::Unbind(name) => {
+                if let Some(count) = bound.get_mut(name) {
+                    *count -= 1;
+                    if *count == 0 {
+                        bound.remove(name);
+                    }
+                }
+            }
         }
     }
+    ret
 }
 
 /// Generate a variable name that doesn't exist in a set.
@@ -294,45 +759,107 @@ pub fn gen_var(prefix: &str, avoid: &HashSet<String>) -> String {
 ///   * `expr` - expression to check for matching free variables
 ///   * `var_to_replace` - variable name to be replaced
 ///   * `replacement` - expression that replaces the variable
-pub fn subst(expr: Expr, var_to_replace: &str, replacement: Expr) -> Expr {
-    match expr {
-        Expr::Contra => Expr::Contra,
-        Expr::Taut => Expr::Taut,
-        Expr::Var { name } => {
-            if name == var_to_replace {
-                replacement
-            } else {
-                Expr::Var { name }
+pub fn subst(mut expr: Expr, var_to_replace: &str, replacement: Expr) -> Expr {
+    /// A substitution still to be done on a subexpression
+    struct Pending {
+        var: String,
+        replacement: Expr,
+        /// Free variables of `replacement`, which quantifiers mustn't capture
+        free: HashSet<String>,
+    }
+
+    // Renaming a quantified variable is a substitution on the quantifier's body that has to happen
+    // before the ones already pending there, so each subexpression carries the list of
+    // substitutions to do on it in turn. The expression is walked with an explicit stack instead
+    // of recursion, so a deeply nested expression can't overflow the call stack.
+    let free = free_vars(&replacement);
+    let mut stack = vec![(&mut expr, vec![Rc::new(Pending { var: var_to_replace.to_owned(), replacement, free })])];
+    while let Some((e, mut pending)) = stack.pop() {
+        // The substitutions after the one replacing a variable apply to its replacement
+        while let Expr::Var { name } = e {
+            match pending.iter().position(|s| s.var == *name) {
+                Some(i) => {
+                    *e = pending[i].replacement.clone();
+                    pending.drain(..=i);
+                }
+                None => break,
             }
         }
-        Expr::Apply { func, args } => Expr::Apply { func: Box::new(subst(*func, var_to_replace, replacement.clone())), args: args.into_iter().map(|expr| subst(expr, var_to_replace, replacement.clone())).collect() },
-        Expr::Not { operand } => Expr::Not { operand: Box::new(subst(*operand, var_to_replace, replacement)) },
-        Expr::Impl { left, right } => Expr::Impl { left: Box::new(subst(*left, var_to_replace, replacement.clone())), right: Box::new(subst(*right, var_to_replace, replacement)) },
-        Expr::Assoc { op, exprs } => Expr::Assoc { op, exprs: exprs.into_iter().map(|expr| subst(expr, var_to_replace, replacement.clone())).collect() },
-        Expr::Quant { kind, name, body } => {
-            if name == var_to_replace {
-                // Variable is bound here, so can stop replacement
-                Expr::Quant { kind, name, body }
-            } else {
-                // Capture-avoidance behavior, rename the quantified variable if
-                // it collides with free variables in the replacement.
-
-                let old_name = name;
-
-                // New quantifier variable name
-                let name = gen_var(&old_name, &free_vars(&replacement));
+        if pending.is_empty() {
+            continue;
+        }
+        match e {
+            Expr::Quant { name, body, .. } => {
+                let mut body_pending = vec![];
+                for s in pending {
+                    if s.var == *name {
+                        // Variable is bound here, so this substitution stops
+                        continue;
+                    }
+                    // Capture-avoidance behavior, rename the quantified variable if it collides
+                    // with free variables in the replacement
+                    let new_name = gen_var(name.as_str(), &s.free);
+                    if new_name != *name {
+                        let old_name = mem::replace(name, new_name.clone());
+                        body_pending.push(Rc::new(Pending { var: old_name, replacement: Expr::var(&new_name), free: hashset![new_name] }));
+                    }
+                    body_pending.push(s);
+                }
+                stack.push((body, body_pending));
+            }
+            e => stack.extend(e.children_mut().into_iter().map(|child| (child, pending.clone()))),
+        }
+    }
+    expr
+}
 
-                // Change quantified variable
-                let body = subst(*body, &old_name, Expr::var(&name));
+/// Replace every free occurrence of the subexpression `target` with `replacement`. This generalizes
+/// `subst()` from variables to any term or formula, e.g. for renaming a predicate or replacing a
+/// term throughout a proof. An occurrence of `target` inside a quantifier that binds one of
+/// `target`'s free variables is a different subexpression, so it's left alone. Like `subst()`, this
+/// is capture-avoiding.
+///
+/// Occurrences are found by structural equality, so `A ∧ B` isn't found inside `A ∧ B ∧ C`.
+///
+/// ```
+/// use aris::expr::subst_expr;
+/// use aris::parser::parse_unwrap as p;
+///
+/// assert_eq!(subst_expr(p("P(a) & forall x (P(x) -> P(a))"), &p("P"), p("Q")), p("Q(a) & forall x (Q(x) -> Q(a))"));
+/// assert_eq!(subst_expr(p("R(f(x), f(x)) | forall x R(f(x), c)"), &p("f(x)"), p("c")), p("R(c, c) | forall x R(f(x), c)"));
+/// ```
+pub fn subst_expr(expr: Expr, target: &Expr, replacement: Expr) -> Expr {
+    if let Expr::Var { name } = target {
+        return subst(expr, name, replacement);
+    }
 
-                // Now it's safe to continue substitution
-                let body = subst(body, var_to_replace, replacement);
+    // Swap the occurrences for a placeholder variable, then substitute for that. The placeholder
+    // mustn't be the name of any variable in `expr`, bound ones included, or it could be captured.
+    let mut names = &free_vars(target) | &free_vars(&replacement);
+    expr.visit(TraversalOrder::PreOrder, &mut |e| match e {
+        Expr::Var { name } | Expr::Quant { name, .. } => {
+            names.insert(name.clone());
+        }
+        _ => {}
+    });
+    let placeholder = gen_var("__target", &names);
 
-                let body = Box::new(body);
-                Expr::Quant { kind, name, body }
+    fn aux(mut e: Expr, target: &Expr, target_free: &HashSet<String>, placeholder: &str) -> Expr {
+        if &e == target {
+            return Expr::var(placeholder);
+        }
+        if let Expr::Quant { name, .. } = &e {
+            if target_free.contains(name) {
+                return e;
             }
         }
+        for child in e.children_mut() {
+            *child = aux(mem::replace(child, Expr::Contra), target, target_free, placeholder);
+        }
+        e
     }
+    let expr = aux(expr, target, &free_vars(target), &placeholder);
+    subst(expr, &placeholder, replacement)
 }
 
 /// Constraints that should hold for a substitution, maintained in a set during unification
@@ -356,54 +883,57 @@ impl Substitution {
 /// Unifies a set of equality constraints on expressions, giving a list of substitutions that make constrained expressions equal.
 /// a == b -> unify(HashSet::from_iter(vec![Equal(a, b)])) == Some(vec![])
 pub fn unify(mut c: HashSet<Constraint>) -> Option<Substitution> {
-    // inspired by TAPL 22.4
-    //println!("\t{:?}", c);
-    let mut c_ = c.clone();
-    let Constraint::Equal(left, right) = if let Some(x) = c_.drain().next() {
+    // inspired by TAPL 22.4, with the recursion on the remaining constraints turned into a loop
+    let subst_set = |x: &str, e1: Expr, set: HashSet<_>| set.into_iter().map(|Constraint::Equal(e2, e3)| Constraint::Equal(subst(e2, x, e1.clone()), subst(e3, x, e1.clone()))).collect::<_>();
+    // Pairs in the order they're found. Each one is applied after the ones found after it, since
+    // those were found in constraints it had already been substituted into.
+    let mut found = vec![];
+    // Fresh constants standing for quantified variables, with the number of pairs found before
+    // each one. If a constant escapes into a later pair, then a free variable in one formula
+    // unified with a captured variable in the other, so the values don't unify.
+    let mut fresh = vec![];
+    while let Some(x) = c.iter().next().cloned() {
         c.remove(&x);
-        x
-    } else {
-        return Some(Substitution(vec![]));
-    };
-    let subst_set = |x, e1: Expr, set: HashSet<_>| set.into_iter().map(|Constraint::Equal(e2, e3)| Constraint::Equal(subst(e2, x, e1.clone()), subst(e3, x, e1.clone()))).collect::<_>();
-    let (fvs, fvt) = (free_vars(&left), free_vars(&right));
-    match (left, right) {
-        (left, right) if left == right => unify(c),
-        (Expr::Var { name: sname }, right) if !fvt.contains(&sname) => unify(subst_set(&sname, right.clone(), c)).map(|mut x| {
-            x.0.push((sname.clone(), right.clone()));
-            x
-        }),
-        (left, Expr::Var { name: tname }) if !fvs.contains(&tname) => unify(subst_set(&tname, left.clone(), c)).map(|mut x| {
-            x.0.push((tname.clone(), left.clone()));
-            x
-        }),
-        (Expr::Not { operand: s }, Expr::Not { operand: t }) => {
-            c.insert(Constraint::Equal(*s, *t));
-            unify(c)
-        }
-        (Expr::Impl { left: sl, right: sr }, Expr::Impl { left: tl, right: tr }) => {
-            c.insert(Constraint::Equal(*sl, *tl));
-            c.insert(Constraint::Equal(*sr, *tr));
-            unify(c)
-        }
-        (Expr::Apply { func: sf, args: sa }, Expr::Apply { func: tf, args: ta }) if sa.len() == ta.len() => {
-            c.insert(Constraint::Equal(*sf, *tf));
-            c.extend(sa.into_iter().zip(ta).map(|(x, y)| Constraint::Equal(x, y)));
-            unify(c)
-        }
-        (Expr::Assoc { op: so, exprs: se }, Expr::Assoc { op: to, exprs: te }) if so == to && se.len() == te.len() => {
-            c.extend(se.iter().zip(te.iter()).map(|(x, y)| Constraint::Equal(x.clone(), y.clone())));
-            unify(c)
-        }
-        (Expr::Quant { kind: sk, name: sn, body: sb }, Expr::Quant { kind: tk, name: tn, body: tb }) if sk == tk => {
-            let uv = gen_var("__unification_var", &fvs.union(&fvt).cloned().collect());
-            // require that the bodies of the quantifiers are alpha-equal by substituting a fresh constant
-            c.insert(Constraint::Equal(subst(*sb, &sn, Expr::var(&uv)), subst(*tb, &tn, Expr::var(&uv))));
-            // if the constant escapes, then a free variable in one formula unified with a captured variable in the other, so the values don't unify
-            unify(c).and_then(|sub| if sub.0.iter().any(|(x, y)| x == &uv || free_vars(y).contains(&uv)) { None } else { Some(sub) })
-        }
-        _ => None,
+        let Constraint::Equal(left, right) = x;
+        let (fvs, fvt) = (free_vars(&left), free_vars(&right));
+        match (left, right) {
+            (left, right) if left == right => {}
+            (Expr::Var { name: sname }, right) if !fvt.contains(&sname) => {
+                c = subst_set(&sname, right.clone(), c);
+                found.push((sname, right));
+            }
+            (left, Expr::Var { name: tname }) if !fvs.contains(&tname) => {
+                c = subst_set(&tname, left.clone(), c);
+                found.push((tname, left));
+            }
+            (Expr::Not { operand: s }, Expr::Not { operand: t }) => {
+                c.insert(Constraint::Equal(*s, *t));
+            }
+            (Expr::Impl { left: sl, right: sr }, Expr::Impl { left: tl, right: tr }) => {
+                c.insert(Constraint::Equal(*sl, *tl));
+                c.insert(Constraint::Equal(*sr, *tr));
+            }
+            (Expr::Apply { func: sf, args: sa }, Expr::Apply { func: tf, args: ta }) if sa.len() == ta.len() => {
+                c.insert(Constraint::Equal(*sf, *tf));
+                c.extend(sa.into_iter().zip(ta).map(|(x, y)| Constraint::Equal(x, y)));
+            }
+            (Expr::Assoc { op: so, exprs: se }, Expr::Assoc { op: to, exprs: te }) if so == to && se.len() == te.len() => {
+                c.extend(se.into_iter().zip(te).map(|(x, y)| Constraint::Equal(x, y)));
+            }
+            (Expr::Quant { kind: sk, name: sn, body: sb }, Expr::Quant { kind: tk, name: tn, body: tb }) if sk == tk => {
+                let uv = gen_var("__unification_var", &fvs.union(&fvt).cloned().collect());
+                // require that the bodies of the quantifiers are alpha-equal by substituting a fresh constant
+                c.insert(Constraint::Equal(subst(*sb, &sn, Expr::var(&uv)), subst(*tb, &tn, Expr::var(&uv))));
+                fresh.push((uv, found.len()));
+            }
+            _ => return None,
+        }
+    }
+    if fresh.iter().any(|(uv, start)| found[*start..].iter().any(|(x, y): &(String, Expr)| x == uv || free_vars(y).contains(uv))) {
+        return None;
     }
+    found.reverse();
+    Some(Substitution(found))
 }
 
 /*
@@ -441,6 +971,238 @@ impl Expr {
     pub fn apply(func: Expr, args: &[Expr]) -> Expr {
         Expr::Apply { func: Box::new(func), args: args.to_vec() }
     }
+    /// Helper for constructing numeric literals, which are variables named by a decimal numeral
+    pub fn num(n: u64) -> Expr {
+        Expr::var(&n.to_string())
+    }
+    /// The value of a numeric literal. Numerals with leading zeros aren't literals, so each number has one.
+    ///
+    /// ```
+    /// use aris::expr::Expr;
+    ///
+    /// assert_eq!(Expr::var("42").as_num(), Some(42));
+    /// assert_eq!(Expr::var("042").as_num(), None);
+    /// assert_eq!(Expr::var("x").as_num(), None);
+    /// ```
+    pub fn as_num(&self) -> Option<u64> {
+        match self {
+            Expr::Var { name } => name.parse::<u64>().ok().filter(|n| n.to_string() == *name),
+            _ => None,
+        }
+    }
+    /// Helper for constructing comparisons `left < right`
+    pub fn less(left: Expr, right: Expr) -> Expr {
+        Expr::apply(Expr::var(LESS), &[left, right])
+    }
+    /// Helper for constructing comparisons `left ≤ right`
+    pub fn less_eq(left: Expr, right: Expr) -> Expr {
+        Expr::apply(Expr::var(LESS_EQ), &[left, right])
+    }
+    /// The relation (`LESS` or `LESS_EQ`) and sides of a comparison
+    pub fn as_comparison(&self) -> Option<(&str, &Expr, &Expr)> {
+        match self {
+            Expr::Apply { func, args } => match (&**func, args.as_slice()) {
+                (Expr::Var { name }, [left, right]) if name == LESS || name == LESS_EQ => Some((name, left, right)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    /// The symbol and arguments of an application of a relation or function that's written between its arguments,
+    /// like `<`, `∈`, or `∪`, or an operator declared in the `parser::Syntax` set on the current thread
+    pub fn as_infix(&self) -> Option<(&str, &Expr, &Expr)> {
+        match self {
+            Expr::Apply { func, args } => match (&**func, args.as_slice()) {
+                (Expr::Var { name }, [left, right]) if INFIX.contains(&name.as_str()) || crate::parser::is_declared_operator(name) || (name == UNTIL && crate::parser::logic_mode() == LogicMode::Temporal) => Some((name, left, right)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    /// Helper for constructing equalities `left = right`
+    pub fn equals(left: Expr, right: Expr) -> Expr {
+        Expr::apply(Expr::var(EQUALS), &[left, right])
+    }
+    /// Helper for constructing memberships `element ∈ set`
+    pub fn element(element: Expr, set: Expr) -> Expr {
+        Expr::apply(Expr::var(ELEMENT), &[element, set])
+    }
+    /// Helper for constructing `left ⊆ right`
+    pub fn subset(left: Expr, right: Expr) -> Expr {
+        Expr::apply(Expr::var(SUBSET), &[left, right])
+    }
+    /// Helper for constructing `left ∪ right`
+    pub fn union(left: Expr, right: Expr) -> Expr {
+        Expr::apply(Expr::var(UNION), &[left, right])
+    }
+    /// Helper for constructing `left ∩ right`
+    pub fn intersection(left: Expr, right: Expr) -> Expr {
+        Expr::apply(Expr::var(INTERSECTION), &[left, right])
+    }
+    /// Helper for constructing set builders `{name : body}`
+    pub fn set_builder(name: &str, body: Expr) -> Expr {
+        Expr::Quant { kind: QuantKind::SetBuilder, name: name.into(), body: Box::new(body) }
+    }
+    /// The symbol and operand of an application of `G`, `F`, or `X`, which is written before its operand in temporal logic
+    pub fn as_temporal_prefix(&self) -> Option<(&str, &Expr)> {
+        match self {
+            Expr::Apply { func, args } => match (&**func, args.as_slice()) {
+                (Expr::Var { name }, [operand]) if [ALWAYS, EVENTUALLY, NEXT].contains(&name.as_str()) && crate::parser::logic_mode() == LogicMode::Temporal => Some((name, operand)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    /// Helper for constructing `G φ`
+    pub fn always(e: Expr) -> Expr {
+        Expr::apply(Expr::var(ALWAYS), &[e])
+    }
+    /// Helper for constructing `F φ`
+    pub fn eventually(e: Expr) -> Expr {
+        Expr::apply(Expr::var(EVENTUALLY), &[e])
+    }
+    /// Helper for constructing `X φ`
+    pub fn next(e: Expr) -> Expr {
+        Expr::apply(Expr::var(NEXT), &[e])
+    }
+    /// Helper for constructing `φ U ψ`
+    pub fn until(left: Expr, right: Expr) -> Expr {
+        Expr::apply(Expr::var(UNTIL), &[left, right])
+    }
+    /// The sides of `φ U ψ`, whatever the logic mode
+    pub fn as_until(&self) -> Option<(&Expr, &Expr)> {
+        match self {
+            Expr::Apply { func, args } => match (&**func, args.as_slice()) {
+                (Expr::Var { name }, [left, right]) if name == UNTIL => Some((left, right)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    /// Display the expression with the parentheses `parens` calls for, rather than with every one of them as its
+    /// `Display` does
+    ///
+    /// ```rust
+    /// use aris::expr::Parenthesization;
+    /// use aris::parser::parse_unwrap as p;
+    ///
+    /// let e = p("(A & ~(B | C)) -> forall x (P(x) & Q(x))");
+    /// assert_eq!(e.display_with(Parenthesization::Full).to_string(), "((A ∧ ¬(B ∨ C)) → (∀ x (P(x) ∧ Q(x))))");
+    /// assert_eq!(e.display_with(Parenthesization::Conventional).to_string(), "(A ∧ ¬(B ∨ C)) → (∀ x (P(x) ∧ Q(x)))");
+    /// assert_eq!(e.display_with(Parenthesization::Minimal).to_string(), "A ∧ ¬(B ∨ C) → ∀ x (P(x) ∧ Q(x))");
+    /// ```
+    pub fn display_with(&self, parens: Parenthesization) -> ParenthesizedDisplay<'_> {
+        ParenthesizedDisplay { expr: self, parens }
+    }
+    /// Helper for constructing Hoare triples `{pre} program {post}`
+    pub fn triple(pre: Expr, program: Expr, post: Expr) -> Expr {
+        Expr::apply(Expr::var(HOARE_TRIPLE), &[pre, program, post])
+    }
+    /// The precondition, program, and postcondition of a Hoare triple
+    pub fn as_triple(&self) -> Option<(&Expr, &Expr, &Expr)> {
+        match self {
+            Expr::Apply { func, args } => match (&**func, args.as_slice()) {
+                (Expr::Var { name }, [pre, program, post]) if name == HOARE_TRIPLE => Some((pre, program, post)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    /// Helper for constructing the program `skip`
+    pub fn skip() -> Expr {
+        Expr::var(SKIP)
+    }
+    /// Helper for constructing assignments `name := value`
+    pub fn assign(name: &str, value: Expr) -> Expr {
+        Expr::apply(Expr::var(ASSIGN), &[Expr::var(name), value])
+    }
+    /// Helper for constructing sequences `first; second`
+    pub fn sequence(first: Expr, second: Expr) -> Expr {
+        Expr::apply(Expr::var(SEQUENCE), &[first, second])
+    }
+    /// Helper for constructing conditionals `if cond then then else otherwise end`
+    pub fn if_then_else(cond: Expr, then: Expr, otherwise: Expr) -> Expr {
+        Expr::apply(Expr::var(IF), &[cond, then, otherwise])
+    }
+    /// Helper for constructing loops `while cond do body end`
+    pub fn while_loop(cond: Expr, body: Expr) -> Expr {
+        Expr::apply(Expr::var(WHILE), &[cond, body])
+    }
+    /// The statement this is, if it's part of a program
+    pub fn as_statement(&self) -> Option<Statement<'_>> {
+        match self {
+            Expr::Var { name } if name == SKIP => Some(Statement::Skip),
+            Expr::Apply { func, args } => match (&**func, args.as_slice()) {
+                (Expr::Var { name }, [Expr::Var { name: var }, value]) if name == ASSIGN => Some(Statement::Assign(var, value)),
+                (Expr::Var { name }, [first, second]) if name == SEQUENCE => Some(Statement::Sequence(first, second)),
+                (Expr::Var { name }, [cond, then, otherwise]) if name == IF => Some(Statement::If(cond, then, otherwise)),
+                (Expr::Var { name }, [cond, body]) if name == WHILE => Some(Statement::While(cond, body)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    /// The statements of a program run one after another, however its sequences are grouped
+    pub fn statements(&self) -> Vec<&Expr> {
+        match self.as_statement() {
+            Some(Statement::Sequence(first, second)) => first.statements().into_iter().chain(second.statements()).collect(),
+            _ => vec![self],
+        }
+    }
+    /// Helper for constructing lambda abstractions `λname body`
+    pub fn lambda(name: &str, body: Expr) -> Expr {
+        Expr::Quant { kind: QuantKind::Lambda, name: name.into(), body: Box::new(body) }
+    }
+    /// Beta-reduce an expression to its normal form, where no lambda abstraction is applied to arguments, in at most
+    /// `max_steps` reductions. Redexes are reduced outermost first, which finds the normal form whenever there is one,
+    /// but some expressions, like `(λx x(x))(λx x(x))`, reduce forever, so this gives `None` if the steps run out.
+    pub fn beta_reduce(self, max_steps: usize) -> Option<Expr> {
+        /// Reduce the head of an expression until it's no longer an applied lambda abstraction
+        fn reduce_head(mut expr: Expr, steps_left: &mut usize) -> Option<Expr> {
+            loop {
+                let (func, mut args) = match expr {
+                    Expr::Apply { func, args } if !args.is_empty() => (reduce_head(*func, steps_left)?, args),
+                    _ => return Some(expr),
+                };
+                expr = match func {
+                    Expr::Quant { kind: QuantKind::Lambda, name, body } => {
+                        *steps_left = steps_left.checked_sub(1)?;
+                        let reduced = subst(*body, &name, args.remove(0));
+                        // A lambda abstraction takes one argument, and the rest are applied to what it gives
+                        if args.is_empty() {
+                            reduced
+                        } else {
+                            Expr::Apply { func: Box::new(reduced), args }
+                        }
+                    }
+                    func => return Some(Expr::Apply { func: Box::new(func), args }),
+                };
+            }
+        }
+        fn reduce(expr: Expr, steps_left: &mut usize) -> Option<Expr> {
+            let mut expr = reduce_head(expr, steps_left)?;
+            for child in expr.children_mut() {
+                *child = reduce(std::mem::replace(child, Expr::Contra), steps_left)?;
+            }
+            Some(expr)
+        }
+        let mut steps_left = max_steps;
+        reduce(self, &mut steps_left)
+    }
+    /// The element and set of a membership `element ∈ set`
+    pub fn as_element(&self) -> Option<(&Expr, &Expr)> {
+        match self.as_infix() {
+            Some((ELEMENT, element, set)) => Some((element, set)),
+            _ => None,
+        }
+    }
+    /// The sides of a subset relation `left ⊆ right`
+    pub fn as_subset(&self) -> Option<(&Expr, &Expr)> {
+        match self.as_infix() {
+            Some((SUBSET, left, right)) => Some((left, right)),
+            _ => None,
+        }
+    }
     /// Helper for constructing `Not` nodes
     pub fn not_place_holder() -> Expr {
         Expr::Not { operand: Box::new(Expr::var("_")) }
@@ -473,6 +1235,141 @@ impl Expr {
     pub fn exists(name: &str, body: Expr) -> Expr {
         Expr::Quant { kind: QuantKind::Exists, name: name.into(), body: Box::new(body) }
     }
+    /// The immediate subexpressions of an expression, from left to right.
+    /// For `Apply`, the function comes before its arguments.
+    pub fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Contra | Expr::Taut | Expr::Var { .. } => vec![],
+            Expr::Apply { func, args } => std::iter::once(&**func).chain(args.iter()).collect(),
+            Expr::Not { operand } => vec![&**operand],
+            Expr::Impl { left, right } => vec![&**left, &**right],
+            Expr::Assoc { exprs, .. } => exprs.iter().collect(),
+            Expr::Quant { body, .. } => vec![&**body],
+        }
+    }
+    /// Mutable version of `children()`
+    pub fn children_mut(&mut self) -> Vec<&mut Expr> {
+        match self {
+            Expr::Contra | Expr::Taut | Expr::Var { .. } => vec![],
+            Expr::Apply { func, args } => std::iter::once(&mut **func).chain(args.iter_mut()).collect(),
+            Expr::Not { operand } => vec![&mut **operand],
+            Expr::Impl { left, right } => vec![&mut **left, &mut **right],
+            Expr::Assoc { exprs, .. } => exprs.iter_mut().collect(),
+            Expr::Quant { body, .. } => vec![&mut **body],
+        }
+    }
+    /// A copy of this node with `children` in place of its subexpressions, which must be as many as it has
+    fn with_children(&self, children: Vec<Expr>) -> Expr {
+        let mut children = children.into_iter();
+        let mut next = || Box::new(children.next().expect("a subexpression for each one replaced"));
+        match self {
+            Expr::Contra => Expr::Contra,
+            Expr::Taut => Expr::Taut,
+            Expr::Var { name } => Expr::Var { name: name.clone() },
+            Expr::Apply { args, .. } => Expr::Apply { func: next(), args: args.iter().map(|_| *next()).collect() },
+            Expr::Not { .. } => Expr::Not { operand: next() },
+            Expr::Impl { .. } => Expr::Impl { left: next(), right: next() },
+            Expr::Assoc { op, exprs } => Expr::Assoc { op: *op, exprs: exprs.iter().map(|_| *next()).collect() },
+            Expr::Quant { kind, name, .. } => Expr::Quant { kind: *kind, name: name.clone(), body: next() },
+        }
+    }
+    /// The subexpression reached by repeatedly indexing into `children()` along `path`.
+    /// The empty path refers to the whole expression.
+    pub fn subexpr_at(&self, path: &[usize]) -> Option<&Expr> {
+        path.iter().try_fold(self, |e, &i| e.children().get(i).copied())
+    }
+    /// Mutable version of `subexpr_at()`
+    pub fn subexpr_at_mut(&mut self, path: &[usize]) -> Option<&mut Expr> {
+        let mut e = self;
+        for &i in path {
+            e = e.children_mut().into_iter().nth(i)?;
+        }
+        Some(e)
+    }
+    /// Call `f` on every node of an expression, in the given order.
+    /// Binding structure is not taken into account, so `f` sees bound and free variables alike.
+    ///
+    /// ```
+    /// use aris::expr::{Expr, TraversalOrder};
+    /// use aris::parser::parse_unwrap as p;
+    ///
+    /// let mut names = vec![];
+    /// p("A & ~B").visit(TraversalOrder::PostOrder, &mut |e| if let Expr::Var { name } = e { names.push(name.clone()) });
+    /// assert_eq!(names, vec!["A", "B"]);
+    /// ```
+    pub fn visit<F: FnMut(&Expr)>(&self, order: TraversalOrder, f: &mut F) {
+        self.fold(order, (), &mut |(), e| f(e))
+    }
+    /// Call `f` on every node of an expression, allowing it to replace nodes in place.
+    /// In `PreOrder`, the traversal continues into whatever `f` left in place of a node, so `f`
+    /// must not keep growing the expression it is given. In `PostOrder`, `f` sees each node after
+    /// its subexpressions have already been visited.
+    pub fn visit_mut<F: FnMut(&mut Expr)>(&mut self, order: TraversalOrder, f: &mut F) {
+        // The traversals use explicit stacks instead of recursion, so a deeply nested expression
+        // can't overflow the call stack
+        match order {
+            TraversalOrder::PreOrder => {
+                let mut stack = vec![self];
+                while let Some(e) = stack.pop() {
+                    f(e);
+                    stack.extend(e.children_mut().into_iter().rev());
+                }
+            }
+            TraversalOrder::PostOrder => {
+                // A node can't be borrowed while its children are, so each node's children are
+                // moved out while they're visited, and put back before the node is
+                enum Frame {
+                    Enter(Expr),
+                    Exit(Expr, usize),
+                }
+                let mut stack = vec![Frame::Enter(mem::replace(self, Expr::Contra))];
+                let mut done = vec![];
+                while let Some(frame) = stack.pop() {
+                    match frame {
+                        Frame::Enter(mut e) => {
+                            let children = e.children_mut().into_iter().map(|child| mem::replace(child, Expr::Contra)).collect::<Vec<_>>();
+                            stack.push(Frame::Exit(e, children.len()));
+                            stack.extend(children.into_iter().rev().map(Frame::Enter));
+                        }
+                        Frame::Exit(mut e, n) => {
+                            for (child, visited) in e.children_mut().into_iter().zip(done.drain(done.len() - n..)) {
+                                *child = visited;
+                            }
+                            f(&mut e);
+                            done.push(e);
+                        }
+                    }
+                }
+                *self = done.pop().expect("the root is visited last");
+            }
+        }
+    }
+    /// Thread an accumulator through every node of an expression, in the given order
+    ///
+    /// ```
+    /// use aris::expr::{Expr, TraversalOrder};
+    /// use aris::parser::parse_unwrap as p;
+    ///
+    /// let size = p("(A -> B) | C").fold(TraversalOrder::PreOrder, 0, &mut |n, _| n + 1);
+    /// assert_eq!(size, 5);
+    /// ```
+    pub fn fold<A, F: FnMut(A, &Expr) -> A>(&self, order: TraversalOrder, init: A, f: &mut F) -> A {
+        // Each node is on the stack with whether its children have been pushed yet
+        let mut acc = init;
+        let mut stack = vec![(self, false)];
+        while let Some((e, expanded)) = stack.pop() {
+            if expanded {
+                acc = f(acc, e);
+                continue;
+            }
+            match order {
+                TraversalOrder::PreOrder => acc = f(acc, e),
+                TraversalOrder::PostOrder => stack.push((e, true)),
+            }
+            stack.extend(e.children().into_iter().rev().map(|child| (child, false)));
+        }
+        acc
+    }
     /// Infer arities (number of arguments) for each variable that occurs free in an expression
     pub fn infer_arities(&self, arities: &mut HashMap<String, usize>) {
         match self {
@@ -606,55 +1503,24 @@ impl Expr {
     /// Helper function for `tranform()`; use the `trans` function to transform
     /// `expr`, yielding a tuple of the transformed expression and a `bool`
     /// indicating whether the expression can be transformed again.
-    fn transform_expr_inner<Trans>(expr: Expr, trans: &Trans) -> (Expr, bool)
+    fn transform_expr_inner<Trans>(mut expr: Expr, trans: &Trans) -> (Expr, bool)
     where
         Trans: Fn(Expr) -> (Expr, bool),
     {
-        let (result, status) = trans(expr);
-        let (result, status2) = match result {
-            // Base cases: these just got transformed above so no need to recurse them
-            e @ Expr::Contra => (e, false),
-            e @ Expr::Taut => (e, false),
-            e @ Expr::Var { .. } => (e, false),
-
-            // Recursive cases: transform each of the sub-expressions of the various compound expressions
-            // and then construct a new instance of that compound expression with their transformed results.
-            // If any transformation is successful, we return success
-            Expr::Apply { func, args } => {
-                let (func, fs) = Self::transform_expr_inner(*func, trans);
-                let func = Box::new(func);
-                // Fancy iterator hackery to transform each sub expr and then collect all their results
-                let (args, stats): (Vec<_>, Vec<_>) = args.into_iter().map(move |expr| Self::transform_expr_inner(expr, trans)).unzip();
-                let success = fs || stats.into_iter().any(|x| x);
-                (Expr::Apply { func, args }, success)
-            }
-            Expr::Not { operand } => {
-                let (operand, success) = Self::transform_expr_inner(*operand, trans);
-                let operand = Box::new(operand);
-                (Expr::Not { operand }, success)
-            }
-            Expr::Impl { left, right } => {
-                let (left, ls) = Self::transform_expr_inner(*left, trans);
-                let (right, rs) = Self::transform_expr_inner(*right, trans);
-                let left = Box::new(left);
-                let right = Box::new(right);
-                let success = ls || rs;
-                (Expr::Impl { left, right }, success)
-            }
-            Expr::Assoc { op, exprs } => {
-                let (exprs, stats): (Vec<_>, Vec<_>) = exprs.into_iter().map(move |expr| Self::transform_expr_inner(expr, trans)).unzip();
-                let success = stats.into_iter().any(|x| x);
-                (Expr::Assoc { op, exprs }, success)
-            }
-            Expr::Quant { kind, name, body } => {
-                let (body, success) = Self::transform_expr_inner(*body, trans);
-                let body = Box::new(body);
-                (Expr::Quant { kind, name, body }, success)
-            }
-        };
+        // Transform each node, then the sub-expressions of whatever it was transformed into. If any
+        // transformation is successful, we return success. This uses an explicit stack instead of
+        // recursion, so a deeply nested expression can't overflow the call stack.
+        let mut status = false;
+        let mut stack = vec![&mut expr];
+        while let Some(e) = stack.pop() {
+            let (result, success) = trans(mem::replace(e, Expr::Contra));
+            *e = result;
+            status |= success;
+            stack.extend(e.children_mut().into_iter().rev());
+        }
         // The key to this function is that it returns true if ANYTHING was transformed. That means
         // if either the whole expression or any of the inner expressions, we should re-run on everything.
-        (result, status || status2)
+        (expr, status)
     }
 
     /// Recursive transforming visitor over an expression
@@ -936,13 +1802,9 @@ impl Expr {
                     Self::collect_unique_exprs_absorption(sub_expr, op, unique_exprs);
                 }
             }
-            Expr::Assoc { op: inner_op, exprs } if (op == Op::Or && *inner_op == Op::And) || (op == Op::And && *inner_op == Op::Or) => {
-                if exprs.iter().any(|e| unique_exprs.contains(e)) {
-                    if let Some(e) = exprs.iter().find(|e| unique_exprs.contains(e)) {
-                        unique_exprs.insert(e.clone());
-                    }
-                } else {
-                    unique_exprs.insert(expr.clone());
+            Expr::Assoc { op: inner_op, exprs } if ((op == Op::Or && *inner_op == Op::And) || (op == Op::And && *inner_op == Op::Or)) && exprs.iter().any(|e| unique_exprs.contains(e)) => {
+                if let Some(e) = exprs.iter().find(|e| unique_exprs.contains(e)) {
+                    unique_exprs.insert(e.clone());
                 }
             }
             _ => {
@@ -1181,7 +2043,14 @@ impl Expr {
     /// Removes complements from the unique expressions set when found
     fn collect_unique_exprs_complement(expr: &Expr, op: Op, unique_exprs: &mut HashSet<Expr>) -> bool {
         match expr {
-            Expr::Assoc { op: expr_op, exprs } if *expr_op == op => exprs.iter().fold(false, |found_complement, sub_expr| Expr::collect_unique_exprs_complement(sub_expr, op, unique_exprs) || found_complement),
+            Expr::Assoc { op: expr_op, exprs } if *expr_op == op => {
+                // Every operand is collected, even after a complement is found
+                let mut found_complement = false;
+                for sub_expr in exprs {
+                    found_complement |= Expr::collect_unique_exprs_complement(sub_expr, op, unique_exprs);
+                }
+                found_complement
+            }
             Expr::Not { operand } => {
                 if operand.is_universal_tautology() {
                     return true;
@@ -1287,6 +2156,7 @@ impl Expr {
                     Expr::Quant { kind, name, body } => match kind {
                         QuantKind::Exists => (gen_opposite(QuantKind::Forall, name, *body), true),
                         QuantKind::Forall => (gen_opposite(QuantKind::Exists, name, *body), true),
+                        QuantKind::SetBuilder | QuantKind::Lambda => (Expr::not(Expr::Quant { kind, name, body }), false),
                     },
                     _ => (Expr::not(*operand), false),
                 },
@@ -1299,7 +2169,7 @@ impl Expr {
         self.transform(&|expr| {
             match expr {
                 Expr::Quant { kind, name, body } => {
-                    if free_vars(&body).contains(&name) {
+                    if !kind.is_quantifier() || free_vars(&body).contains(&name) {
                         (Expr::Quant { kind, name, body }, false)
                     } else {
                         // if name is not free in body, then the quantifier isn't binding anything and can be removed
@@ -1363,6 +2233,73 @@ impl Expr {
         }
         ret
     }
+    /// A canonical form of the expression, so expressions that only differ in the grouping and order of the
+    /// operands of associative operators, or in the names of their bound variables, have the same one. Chains are
+    /// flattened and their operands sorted, and bound variables are named after how many quantifiers they're
+    /// inside, with names the parser doesn't accept, so they can't clash with free variables. It's for comparing
+    /// and hashing expressions, rather than showing them.
+    ///
+    /// ```rust
+    /// use aris::parser::parse_unwrap as p;
+    ///
+    /// assert_eq!(p("(C | B) & A").canonicalize(), p("A & (B | C)").canonicalize());
+    /// assert_eq!(p("forall x (P(x) & (Q & R))").canonicalize(), p("forall y ((R & P(y)) & Q)").canonicalize());
+    /// assert_ne!(p("forall x P(x, y)").canonicalize(), p("forall y P(y, x)").canonicalize());
+    /// ```
+    pub fn canonicalize(&self) -> Expr {
+        /// A quantifier around a subexpression, with the variable it binds and what that's renamed to
+        struct Binder {
+            from: String,
+            to: String,
+            outer: Option<Rc<Binder>>,
+        }
+
+        let mut ret = self.clone();
+        let mut stack: Vec<(&mut Expr, Option<Rc<Binder>>, usize)> = vec![(&mut ret, None, 0)];
+        while let Some((e, binder, level)) = stack.pop() {
+            match e {
+                Expr::Var { name } => {
+                    let mut binder = binder.as_deref();
+                    while let Some(b) = binder {
+                        if b.from == *name {
+                            name.clone_from(&b.to);
+                            break;
+                        }
+                        binder = b.outer.as_deref();
+                    }
+                }
+                Expr::Quant { name, body, .. } => {
+                    let to = format!("#{level}");
+                    let binder = Binder { from: mem::replace(name, to.clone()), to, outer: binder };
+                    stack.push((body, Some(Rc::new(binder)), level + 1));
+                }
+                e => stack.extend(e.children_mut().into_iter().map(|child| (child, binder.clone(), level))),
+            }
+        }
+        // Variables are named by depth rather than by order, so sorting can't change their names
+        ret.normalize_ac(&|_| true)
+    }
+    /// Flatten and sort the chains of the operators `ops` accepts, in one pass from the leaves up. This is what
+    /// `combine_associative_ops` followed by `sort_commutative_ops` do, without repeating until nothing changes.
+    pub fn normalize_ac(mut self, ops: &dyn Fn(Op) -> bool) -> Expr {
+        // The operands of a chain are normalized before the chain itself, so chains inside them are already flat
+        self.visit_mut(TraversalOrder::PostOrder, &mut |e| {
+            if let Expr::Assoc { op, exprs } = e {
+                if ops(*op) {
+                    let mut flattened = vec![];
+                    for e in mem::take(exprs) {
+                        match e {
+                            Expr::Assoc { op: inner, exprs } if inner == *op => flattened.extend(exprs),
+                            e => flattened.push(e),
+                        }
+                    }
+                    flattened.sort();
+                    *exprs = flattened;
+                }
+            }
+        });
+        self
+    }
     /// Sort the names of quantified variables within runs of quantifiers of the same kind
     pub fn swap_quantifiers(self) -> Expr {
         // check for quantifier,
@@ -1377,7 +2314,7 @@ impl Expr {
             let mut last_quantifier = None;
 
             while let Expr::Quant { kind, name, body } = mod_expr {
-                if last_quantifier.is_none() || last_quantifier == Some(kind) {
+                if kind.is_quantifier() && (last_quantifier.is_none() || last_quantifier == Some(kind)) {
                     last_quantifier = Some(kind);
                     stack.push(name);
                     mod_expr = *body;
@@ -1422,7 +2359,7 @@ impl Expr {
             for expr in exprs.into_iter() {
                 match expr {
                     Expr::Quant { kind, name, body } => {
-                        if found.is_none() && !all_free.contains(&name) {
+                        if found.is_none() && kind.is_quantifier() && !all_free.contains(&name) {
                             found = Some((kind, name));
                             others.push(*body);
                         } else {
@@ -1464,6 +2401,7 @@ impl Expr {
                                 QuantKind::Exists => {
                                     return reconstruct_7cd(QuantKind::Forall, name, body, right);
                                 }
+                                QuantKind::SetBuilder | QuantKind::Lambda => Box::new(Expr::Quant { kind, name, body }),
                             }
                         }
                         left => Box::new(left),
@@ -1479,6 +2417,7 @@ impl Expr {
                                 QuantKind::Exists => {
                                     return reconstruct_7cd(QuantKind::Exists, name, left, body);
                                 }
+                                QuantKind::SetBuilder | QuantKind::Lambda => Box::new(Expr::Quant { kind, name, body }),
                             }
                         }
                         right => Box::new(right),
@@ -1497,6 +2436,7 @@ impl Expr {
             let gen_opposite = |kind, name, body| match kind {
                 QuantKind::Exists => (Expr::Quant { kind: QuantKind::Forall, name, body }, true),
                 QuantKind::Forall => (Expr::Quant { kind: QuantKind::Exists, name, body }, true),
+                QuantKind::SetBuilder | QuantKind::Lambda => unreachable!("only quantifiers are negated"),
             };
 
             let orig_expr = expr.clone();
@@ -1504,7 +2444,7 @@ impl Expr {
                 // find unop quantifier on the left
                 Expr::Not { operand } => {
                     match *operand {
-                        Expr::Quant { kind, name, body } => {
+                        Expr::Quant { kind, name, body } if kind.is_quantifier() => {
                             match *body {
                                 // find implies, turn into associative binop
                                 Expr::Impl { left, right } => {
@@ -1582,6 +2522,7 @@ impl Expr {
                 *iter = match kind {
                     QuantKind::Exists => Expr::exists(qname.as_str(), tmp),
                     QuantKind::Forall => Expr::forall(qname.as_str(), tmp),
+                    QuantKind::SetBuilder | QuantKind::Lambda => unreachable!("only ∀ and ∃ are distributed"),
                 };
             });
         };
@@ -1752,6 +2693,26 @@ impl NnfExpr {
         Self::And { exprs: vec![a, b] }
     }
 
+    /// Number of clauses [`NnfExpr::into_cnf()`](NnfExpr::into_cnf) makes,
+    /// without making them. Distributing ORs can make exponentially many, so
+    /// this is for checking the conversion is affordable first. Saturates at
+    /// `u64::MAX`.
+    ///
+    /// ```rust
+    /// use aris::parser::parse_unwrap as p;
+    ///
+    /// let nnf = p("(A & B) | (C & D & E)").into_nnf().unwrap();
+    /// assert_eq!(nnf.cnf_clause_count(), 6);
+    /// assert_eq!(nnf.into_cnf().to_varisat().0.len(), 6);
+    /// ```
+    pub fn cnf_clause_count(&self) -> u64 {
+        match self {
+            NnfExpr::Lit { .. } => 1,
+            NnfExpr::And { exprs } => exprs.iter().map(NnfExpr::cnf_clause_count).fold(0, u64::saturating_add),
+            NnfExpr::Or { exprs } => exprs.iter().map(NnfExpr::cnf_clause_count).fold(1, u64::saturating_mul),
+        }
+    }
+
     /// Convert from [`NnfExpr`](NnfExpr) into [`CnfExpr`](CnfExpr) by distributing ORs.
     ///
     /// ```rust
@@ -1883,18 +2844,26 @@ impl CnfExpr {
     /// let a = CnfExpr::var("A");
     /// let b = CnfExpr::var("B");
     ///
-    /// assert_eq!(p("A | B").into_cnf(), Some(CnfExpr::or(vec![a, b])));
+    /// assert_eq!(p("A | B").into_cnf(), Some(CnfExpr::or(vec![a.clone(), b])));
+    /// assert_eq!(p("A | ⊤").into_cnf(), Some(CnfExpr::taut()));
+    /// assert_eq!(CnfExpr::or(vec![a, CnfExpr::taut()]), CnfExpr::taut());
+    /// assert_eq!(CnfExpr::or(vec![]), CnfExpr::contra());
     /// ```
     pub fn or<I>(exprs: I) -> Self
     where
         I: IntoIterator<Item = CnfExpr>,
     {
-        let clauses = exprs.into_iter().map(|expr| expr.0).multi_cartesian_product().map(|clauses| clauses.concat()).collect::<Vec<Vec<(bool, String)>>>();
-        if clauses.is_empty() {
-            CnfExpr::contra()
-        } else {
-            CnfExpr(clauses)
+        let exprs = exprs.into_iter().map(|expr| expr.0).collect::<Vec<_>>();
+        if exprs.is_empty() {
+            // OR() ≡ ⊥
+            return CnfExpr::contra();
+        }
+        if exprs.iter().any(Vec::is_empty) {
+            // An operand without clauses is true, which makes the whole OR
+            // true, rather than leaving no clauses to combine
+            return CnfExpr::taut();
         }
+        CnfExpr(exprs.into_iter().multi_cartesian_product().map(|clauses| clauses.concat()).collect())
     }
 
     /// Use CNF expression to create a [`varisat::CnfFormula`][cnfformula]
@@ -2001,6 +2970,33 @@ mod tests {
         assert_eq!(subst(p("forall f f(x) & g(y, z)"), "g", p("f")), p("forall f0 f0(x) & f(y, z)"));
     }
 
+    #[test]
+    fn test_subst_expr() {
+        use crate::parser::parse_unwrap as p;
+        assert_eq!(subst_expr(p("P(f(a)) -> Q(f(a), b)"), &p("f(a)"), p("c")), p("P(c) -> Q(c, b)"));
+        // Only free occurrences are replaced, and bound variables are renamed to keep the replacement's variables free
+        assert_eq!(subst_expr(p("g(y) & (forall y g(y)) & (forall x g(y))"), &p("g(y)"), p("h(x)")), p("h(x) & (forall y g(y)) & (forall x0 h(x))"));
+        assert_eq!(subst_expr(p("(A & B) | (A & B & C)"), &p("A & B"), p("D")), p("D | (A & B & C)"));
+    }
+
+    #[test]
+    fn test_beta_reduce() {
+        use crate::parser::{parse_unwrap as p, with_syntax, LogicMode, Syntax};
+        with_syntax(&Syntax { logic_mode: LogicMode::HigherOrder, ..Syntax::default() }, || {
+            assert_eq!(p("(λx P(x, x))(a)").beta_reduce(10), Some(p("P(a, a)")));
+            // Each argument is passed in turn, and substitution avoids capturing free variables
+            assert_eq!(p("(λx λy R(x, y))(y, b)").beta_reduce(10), Some(p("R(y, b)")));
+            assert_eq!(p("(λx λy R(x, y))(y)").beta_reduce(10), Some(p("λy0 R(y, y0)")));
+            assert_eq!(p("forall z ((λf f(z))(λx Q(x)) & (λx x)(A))").beta_reduce(10), Some(p("forall z (Q(z) & A)")));
+            // Outermost first, so an argument that's thrown away doesn't have to be reduced
+            let omega = "(λx x(x))(λx x(x))";
+            assert_eq!(p(omega).beta_reduce(1000), None);
+            assert_eq!(p(&format!("(λx λy y)({omega}, B)")).beta_reduce(10), Some(p("B")));
+            assert_eq!(p("(λx P(x))(a)").beta_reduce(0), None);
+            assert_eq!(p("f(a)(b)").beta_reduce(0), Some(p("f(a)(b)")));
+        });
+    }
+
     #[test]
     fn test_unify() {
         use crate::parser::parse_unwrap as p;
@@ -2040,6 +3036,96 @@ mod tests {
         f("(a & (b & c)) | (q | r)");
     }
 
+    #[test]
+    fn test_traversal_order() {
+        use crate::parser::parse_unwrap as p;
+        let e = p("P(a) -> ~Q");
+        let mut pre = vec![];
+        e.visit(TraversalOrder::PreOrder, &mut |e| pre.push(e.to_string()));
+        assert_eq!(pre, vec!["(P(a) → ¬Q)", "P(a)", "P", "a", "¬Q", "Q"]);
+        let post = e.fold(TraversalOrder::PostOrder, vec![], &mut |mut acc, e| {
+            acc.push(e.to_string());
+            acc
+        });
+        assert_eq!(post, vec!["P", "a", "P(a)", "Q", "¬Q", "(P(a) → ¬Q)"]);
+
+        let mut e = p("~~A & (B | ~~C)");
+        e.visit_mut(TraversalOrder::PostOrder, &mut |e| {
+            if let Expr::Not { operand } = e {
+                if let Expr::Not { operand: inner } = &mut **operand {
+                    *e = mem::replace(&mut **inner, Expr::Contra);
+                }
+            }
+        });
+        assert_eq!(e, p("A & (B | C)"));
+    }
+
+    #[test]
+    fn test_deep_expressions() {
+        // `A → (A → (... → ∀x (A → ...)))`, far deeper than the call stack allows recursing into
+        let mut e = Expr::var("P");
+        for i in 0..10_000 {
+            e = if i % 3 == 0 { Expr::forall("x", e) } else { Expr::implies(Expr::var("A"), e) };
+        }
+        assert_eq!(free_vars(&e), hashset!["A".to_owned(), "P".to_owned()]);
+        let mut e = subst(e, "P", Expr::var("x"));
+        assert_eq!(free_vars(&e), hashset!["A".to_owned(), "x".to_owned()]);
+        e.visit_mut(TraversalOrder::PostOrder, &mut |e| {
+            if let Expr::Var { name } = e {
+                if name == "A" {
+                    *e = Expr::Taut;
+                }
+            }
+        });
+        e.visit_mut(TraversalOrder::PreOrder, &mut |e| {
+            if let Expr::Quant { name, .. } = e {
+                *name = "y".into();
+            }
+        });
+        assert_eq!(e.fold(TraversalOrder::PreOrder, 0, &mut |n, e| n + matches!(e, Expr::Taut) as usize), 6_666);
+        assert_eq!(free_vars(&e), hashset!["x".to_owned()]);
+        let e = e.transform(&|e| match e {
+            Expr::Impl { left, right } if *left == Expr::Taut => (*right, true),
+            e => (e, false),
+        });
+        assert_eq!(e.fold(TraversalOrder::PostOrder, 0, &mut |n, _| n + 1), 3_335);
+        assert_eq!(e.to_string(), format!("{}x{}", "(∀ y ".repeat(3_334), ")".repeat(3_334)));
+        let copy = e.clone();
+        assert_eq!(copy, e);
+        assert_eq!(hashset![copy], hashset![e.clone()]);
+        assert_ne!(Expr::forall("y", e.clone()), e);
+        let canonical = e.canonicalize();
+        assert_eq!(canonical.fold(TraversalOrder::PreOrder, 0, &mut |n, e| n + matches!(e, Expr::Quant { name, .. } if name.starts_with('#')) as usize), 3_334);
+        let renamed = (0..3_334).fold(Expr::var("x"), |body, _| Expr::forall("z", body));
+        assert_eq!(canonical, renamed.canonicalize());
+    }
+
+    #[test]
+    fn test_display_with() {
+        use crate::parser::parse_unwrap as p;
+        let minimal = |input: &str| p(input).display_with(Parenthesization::Minimal).to_string();
+        assert_eq!(minimal("(A & B) | C"), "A ∧ B ∨ C");
+        assert_eq!(minimal("A & (B | C)"), "A ∧ (B ∨ C)");
+        assert_eq!(minimal("(A & B) & C"), "(A ∧ B) ∧ C");
+        assert_eq!(minimal("(A -> B) -> C"), "(A → B) → C");
+        assert_eq!(minimal("A -> (B -> C)"), "A → (B → C)");
+        assert_eq!(minimal("(A <-> B) <-> (C -> D)"), "(A ↔ B) ↔ C → D");
+        assert_eq!(minimal("~(A & B)"), "¬(A ∧ B)");
+        assert_eq!(minimal("(forall x P(x)) & Q"), "∀ x P(x) ∧ Q");
+        assert_eq!(minimal("forall x (P(x) & Q)"), "∀ x (P(x) ∧ Q)");
+        assert_eq!(minimal("~(x < y + 1) & x * (y + 1) = z"), "¬(x < y + 1) ∧ x × (y + 1) = z");
+        assert_eq!(minimal("{x : x ∈ A & x ∈ B} = A ∩ B"), "{x : x ∈ A ∧ x ∈ B} = A ∩ B");
+        for input in ["P(x)", "~A", "(A & B) -> ~C", "forall x (P(x) -> Q(x))", "x < y + 1"] {
+            let e = p(input);
+            assert_eq!(e.display_with(Parenthesization::Full).to_string(), e.to_string());
+            assert_eq!(p(&e.display_with(Parenthesization::Conventional).to_string()), e);
+        }
+        assert_eq!(p("P(x)").display_with(Parenthesization::Conventional).to_string(), "P(x)");
+        assert_eq!(p("(A | B) & (C | D)").display_with(Parenthesization::Conventional).to_string(), "(A ∨ B) ∧ (C ∨ D)");
+        assert_eq!("conventional".parse(), Ok(Parenthesization::Conventional));
+        assert_eq!(Parenthesization::Minimal.as_str(), "minimal");
+    }
+
     #[test]
     fn test_expressions_for_depth() {
         use std::iter::FromIterator;
@@ -0,0 +1,376 @@
+/*!
+# First-order consequence by Herbrand expansion
+
+A conclusion follows from premises exactly when the premises and the negation
+of the conclusion have no model together. Skolemizing them replaces each
+existential quantifier with a new function of the universally quantified
+variables around it, and by Herbrand's theorem the universal formulas left have
+no model exactly when some finite set of their instances is contradictory,
+using only terms built from their constants and functions. This module tries
+the instances with terms nested up to each depth in turn, asking the SAT solver
+whether they're contradictory.
+
+That finds every consequence, given deep enough terms, but it can't tell that a
+conclusion doesn't follow unless there are only finitely many terms, which is
+when the formulas have no functions left after Skolemizing. Otherwise the
+search stops at `Config::max_depth`, or when the instances would take more
+clauses than `Config::max_clauses`, and the result says how deep it got. It
+also spends the budget of the line being checked (see `crate::limits`), and
+reports running out of it rather than failing.
+
+```
+use aris::herbrand::{self, Config, Consequence};
+use aris::parser::parse_unwrap as p;
+
+let config = Config::default();
+assert_eq!(herbrand::consequence(&[p("forall x (P(x) -> Q(x))"), p("P(a)")], &p("Q(a)"), &config), Ok(Consequence::Follows { depth: 0 }));
+assert_eq!(herbrand::consequence(&[p("exists x P(x)")], &p("forall x P(x)"), &config), Ok(Consequence::DoesNotFollow));
+// Every number having a successor doesn't mean one of them is its own
+assert!(matches!(herbrand::consequence(&[p("forall x exists y S(x, y)")], &p("exists x S(x, x)"), &config), Ok(Consequence::Undecided { .. })));
+```
+
+Predicates are arbitrary relations, even `=`, so consequences that depend on
+what it means aren't found, and formulas with arithmetic, sets, or lambdas
+can't be checked.
+*/
+
+use crate::expr::gen_var;
+use crate::expr::Expr;
+use crate::expr::Op;
+use crate::expr::QuantKind;
+use crate::expr::TraversalOrder;
+use crate::limits;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::iter;
+
+/// How far to search for a contradiction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// Deepest nesting of functions in the terms tried; constants have depth 0
+    pub max_depth: usize,
+    /// Most clauses to hand the SAT solver for the instances at one depth
+    pub max_clauses: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { max_depth: 2, max_clauses: 1 << 14 }
+    }
+}
+
+/// Whether a conclusion follows from premises, as far as the search got
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Consequence {
+    /// The conclusion follows, which instances with terms up to `depth` show
+    Follows { depth: usize },
+    /// The conclusion doesn't follow: there are only finitely many terms, and
+    /// the instances with all of them aren't contradictory
+    DoesNotFollow,
+    /// The instances with terms up to `depth` aren't contradictory, and
+    /// deeper terms are beyond what `Config` allows. `depth` is `None` when
+    /// even the constants would take too many clauses.
+    Undecided { depth: Option<usize> },
+    /// The budget of the line being checked ran out, after trying terms up to
+    /// `depth`, if any
+    TimedOut { depth: Option<usize> },
+}
+
+/// Replaces quantifiers with variables and Skolem functions
+struct Skolemizer {
+    /// Names in use, which new variables and functions have to avoid
+    names: HashSet<String>,
+    /// Variables standing for every term
+    universals: HashSet<String>,
+}
+
+impl Skolemizer {
+    fn fresh(&mut self, prefix: &str) -> String {
+        let name = gen_var(prefix, &self.names);
+        self.names.insert(name.clone());
+        name
+    }
+
+    /// `e` in negation normal form if `positive`, and its negation otherwise, without quantifiers: universally
+    /// quantified variables become fresh variables, and existentially quantified ones Skolem functions of the
+    /// universally quantified variables they're in the scope of. `bound` is what each bound name was replaced with.
+    fn skolemize(&mut self, e: &Expr, positive: bool, bound: &mut Vec<(String, Expr)>, scope: &mut Vec<Expr>) -> Result<Expr, String> {
+        match e {
+            Expr::Contra | Expr::Taut => Ok(if (*e == Expr::Taut) == positive { Expr::Taut } else { Expr::Contra }),
+            Expr::Var { .. } | Expr::Apply { .. } => {
+                let atom = atom(e, bound)?;
+                Ok(if positive { atom } else { !atom })
+            }
+            Expr::Not { operand } => self.skolemize(operand, !positive, bound, scope),
+            Expr::Impl { left, right } => {
+                let left = self.skolemize(left, !positive, bound, scope)?;
+                let right = self.skolemize(right, positive, bound, scope)?;
+                Ok(Expr::assoc(if positive { Op::Or } else { Op::And }, &[left, right]))
+            }
+            Expr::Assoc { op: op @ (Op::And | Op::Or), exprs } => {
+                let op = if (*op == Op::And) == positive { Op::And } else { Op::Or };
+                let exprs = exprs.iter().map(|e| self.skolemize(e, positive, bound, scope)).collect::<Result<Vec<_>, _>>()?;
+                Ok(Expr::assoc(op, &exprs))
+            }
+            Expr::Assoc { op: op @ (Op::Bicon | Op::Equiv), exprs } => match exprs.as_slice() {
+                [] => self.skolemize(&Expr::Taut, positive, bound, scope),
+                [e] => self.skolemize(e, positive, bound, scope),
+                // A chain is the biconditional of its first formula with the rest of it
+                [first, rest @ ..] => {
+                    let rest = if rest.len() == 1 { rest[0].clone() } else { Expr::assoc(*op, rest) };
+                    let conditionals = Expr::assoc(Op::And, &[Expr::implies(first.clone(), rest.clone()), Expr::implies(rest, first.clone())]);
+                    self.skolemize(&conditionals, positive, bound, scope)
+                }
+            },
+            Expr::Assoc { op: Op::Add | Op::Mult, .. } => Err(format!("{e} uses arithmetic")),
+            Expr::Quant { kind: QuantKind::SetBuilder, .. } => Err(format!("{e} is a set")),
+            Expr::Quant { kind: QuantKind::Lambda, .. } => Err(format!("{e} is a function")),
+            Expr::Quant { kind, name, body } => {
+                let universal = (*kind == QuantKind::Forall) == positive;
+                let term = if universal {
+                    let var = self.fresh(name);
+                    self.universals.insert(var.clone());
+                    Expr::var(&var)
+                } else {
+                    let function = Expr::var(&self.fresh("sk"));
+                    if scope.is_empty() {
+                        function
+                    } else {
+                        Expr::apply(function, scope)
+                    }
+                };
+                bound.push((name.clone(), term.clone()));
+                if universal {
+                    scope.push(term);
+                }
+                let body = self.skolemize(body, positive, bound, scope);
+                bound.pop();
+                if universal {
+                    scope.pop();
+                }
+                body
+            }
+        }
+    }
+}
+
+/// An atomic formula with its bound variables replaced
+fn atom(e: &Expr, bound: &[(String, Expr)]) -> Result<Expr, String> {
+    let is_bound = |name: &String| bound.iter().any(|(bound, _)| bound == name);
+    match e {
+        Expr::Var { name } if is_bound(name) => Err(format!("{name} is used both as a term and as a formula")),
+        Expr::Apply { func, args } => match &**func {
+            Expr::Var { name } if is_bound(name) => Err(format!("{name} is quantified over, but it's applied like a predicate")),
+            Expr::Var { .. } => Ok(Expr::Apply { func: func.clone(), args: args.iter().map(|arg| term(arg, bound)).collect::<Result<_, _>>()? }),
+            _ => Err(format!("{func} isn't the name of a predicate")),
+        },
+        _ => Ok(e.clone()),
+    }
+}
+
+/// A term with its bound variables replaced
+fn term(e: &Expr, bound: &[(String, Expr)]) -> Result<Expr, String> {
+    match e {
+        Expr::Var { name } => Ok(bound.iter().rev().find(|(bound, _)| bound == name).map_or_else(|| e.clone(), |(_, term)| term.clone())),
+        Expr::Apply { func, args } if matches!(**func, Expr::Var { .. }) => Ok(Expr::Apply { func: func.clone(), args: args.iter().map(|arg| term(arg, bound)).collect::<Result<_, _>>()? }),
+        _ => Err(format!("{e} is a term, but only variables, constants, and functions applied to them are supported")),
+    }
+}
+
+/// Split a formula into its conjuncts, however deeply they're nested
+fn conjuncts(e: Expr, out: &mut Vec<Expr>) {
+    match e {
+        Expr::Taut => {}
+        Expr::Assoc { op: Op::And, exprs } => exprs.into_iter().for_each(|e| conjuncts(e, out)),
+        e => out.push(e),
+    }
+}
+
+/// The constants and the functions, with their arities, of the terms in a quantifier-free formula, leaving out
+/// `universals`
+fn add_symbols(e: &Expr, universals: &HashSet<String>, constants: &mut BTreeSet<String>, functions: &mut BTreeMap<String, usize>) {
+    fn add_term(e: &Expr, universals: &HashSet<String>, constants: &mut BTreeSet<String>, functions: &mut BTreeMap<String, usize>) {
+        match e {
+            Expr::Var { name } if !universals.contains(name) => {
+                constants.insert(name.clone());
+            }
+            Expr::Apply { func, args } => {
+                if let Expr::Var { name } = &**func {
+                    functions.insert(name.clone(), args.len());
+                }
+                args.iter().for_each(|arg| add_term(arg, universals, constants, functions));
+            }
+            _ => {}
+        }
+    }
+    // Only the outermost applications are predicates; their arguments are terms
+    match e {
+        Expr::Apply { args, .. } => args.iter().for_each(|arg| add_term(arg, universals, constants, functions)),
+        Expr::Var { .. } | Expr::Contra | Expr::Taut => {}
+        _ => e.children().into_iter().for_each(|child| add_symbols(child, universals, constants, functions)),
+    }
+}
+
+/// Every sequence of `len` items
+fn tuples(items: &[Expr], len: usize) -> Vec<Vec<Expr>> {
+    (0..len).fold(vec![vec![]], |tuples, _| {
+        tuples
+            .into_iter()
+            .flat_map(|tuple| {
+                items.iter().map(move |item| {
+                    let mut tuple = tuple.clone();
+                    tuple.push(item.clone());
+                    tuple
+                })
+            })
+            .collect()
+    })
+}
+
+/// A quantifier-free formula with ground atoms replaced by propositional variables named after them
+fn abstract_atoms(e: &Expr) -> Expr {
+    match e {
+        Expr::Apply { .. } => Expr::var(&e.to_string()),
+        Expr::Not { operand } => !abstract_atoms(operand),
+        Expr::Assoc { op, exprs } => Expr::Assoc { op: *op, exprs: exprs.iter().map(abstract_atoms).collect() },
+        _ => e.clone(),
+    }
+}
+
+/// Whether the conclusion follows from the premises, searching instances of them with terms up to
+/// `config.max_depth`. The error says why the formulas couldn't be checked.
+pub fn consequence(premises: &[Expr], conclusion: &Expr, config: &Config) -> Result<Consequence, String> {
+    let formulas = premises.iter().cloned().chain(iter::once(!conclusion.clone())).collect::<Vec<_>>();
+    let mut names = HashSet::new();
+    for e in &formulas {
+        e.visit(TraversalOrder::PreOrder, &mut |e| {
+            if let Expr::Var { name } = e {
+                names.insert(name.clone());
+            }
+        });
+    }
+    let mut skolemizer = Skolemizer { names, universals: HashSet::new() };
+    let mut clauses = vec![];
+    for e in &formulas {
+        conjuncts(skolemizer.skolemize(e, true, &mut vec![], &mut vec![])?, &mut clauses);
+    }
+
+    // Each conjunct is instantiated separately, with just the variables it has
+    let mut constants = BTreeSet::new();
+    let mut functions = BTreeMap::new();
+    let clauses = clauses
+        .into_iter()
+        .map(|clause| {
+            add_symbols(&clause, &skolemizer.universals, &mut constants, &mut functions);
+            let mut vars = BTreeSet::new();
+            clause.visit(TraversalOrder::PreOrder, &mut |e| {
+                if let Expr::Var { name } = e {
+                    if skolemizer.universals.contains(name) {
+                        vars.insert(name.clone());
+                    }
+                }
+            });
+            (clause, vars.into_iter().collect::<Vec<_>>())
+        })
+        .collect::<Vec<_>>();
+    if constants.is_empty() {
+        constants.insert(skolemizer.fresh("c"));
+    }
+    let constants = constants.iter().map(|name| Expr::var(name)).collect::<Vec<_>>();
+
+    let mut terms = constants.clone();
+    let mut searched = None;
+    for depth in 0..=config.max_depth {
+        if depth > 0 {
+            let count = functions.values().try_fold(constants.len() as u64, |count, &arity| count.checked_add((terms.len() as u64).checked_pow(arity as u32)?));
+            if count.filter(|&count| count <= config.max_clauses).is_none() {
+                return Ok(Consequence::Undecided { depth: searched });
+            }
+            terms = constants.iter().cloned().chain(functions.iter().flat_map(|(name, &arity)| tuples(&terms, arity).into_iter().map(|args| Expr::apply(Expr::var(name), &args)))).collect();
+        }
+
+        let count = clauses.iter().try_fold(0u64, |count, (_, vars)| count.checked_add((terms.len() as u64).checked_pow(vars.len() as u32)?));
+        if count.filter(|&count| count <= config.max_clauses).is_none() {
+            return Ok(Consequence::Undecided { depth: searched });
+        }
+        let mut instances = vec![];
+        for (clause, vars) in &clauses {
+            for args in tuples(&terms, vars.len()) {
+                let binding = vars.iter().map(String::as_str).zip(args).collect::<HashMap<_, _>>();
+                let mut instance = clause.clone();
+                instance.visit_mut(TraversalOrder::PreOrder, &mut |e| {
+                    if let Expr::Var { name } = e {
+                        if let Some(term) = binding.get(name.as_str()) {
+                            *e = term.clone();
+                        }
+                    }
+                });
+                instances.push(abstract_atoms(&instance));
+            }
+        }
+
+        // The instances are only connectives over atoms, so they convert
+        let nnf = Expr::assoc(Op::And, &instances).into_nnf().expect("instances are propositional");
+        let clause_count = nnf.cnf_clause_count();
+        if clause_count > config.max_clauses {
+            return Ok(Consequence::Undecided { depth: searched });
+        }
+        if limits::spend(clause_count).is_err() {
+            return Ok(Consequence::TimedOut { depth: searched });
+        }
+        let (sat, _) = nnf.into_cnf().to_varisat();
+        let mut solver = varisat::Solver::new();
+        solver.add_formula(&sat);
+        // Does not panic on the default config
+        solver.solve().expect("varisat error");
+        if solver.model().is_none() {
+            return Ok(Consequence::Follows { depth });
+        }
+        if functions.is_empty() {
+            return Ok(Consequence::DoesNotFollow);
+        }
+        searched = Some(depth);
+    }
+    Ok(Consequence::Undecided { depth: searched })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+
+    #[test]
+    fn test_consequence() {
+        let config = Config::default();
+        let follows = |premises: &[&str], conclusion: &str| consequence(&premises.iter().map(|e| p(e)).collect::<Vec<_>>(), &p(conclusion), &config);
+
+        assert_eq!(follows(&["A -> B", "A"], "B"), Ok(Consequence::Follows { depth: 0 }));
+        assert_eq!(follows(&["A -> B", "B"], "A"), Ok(Consequence::DoesNotFollow));
+        assert_eq!(follows(&["exists x forall y R(x, y)"], "forall y exists x R(x, y)"), Ok(Consequence::Follows { depth: 0 }));
+        assert_eq!(follows(&["exists x P(x)", "exists x Q(x)"], "exists x (P(x) & Q(x))"), Ok(Consequence::DoesNotFollow));
+        assert_eq!(follows(&["forall y exists x R(x, y)"], "exists x forall y R(x, y)"), Ok(Consequence::Undecided { depth: Some(2) }));
+        assert_eq!(follows(&["forall x (P(x) <-> ~Q(x))", "~Q(a)"], "exists x P(x)"), Ok(Consequence::Follows { depth: 0 }));
+        assert_eq!(follows(&[], "forall x P(x) | exists x ~P(x)"), Ok(Consequence::Follows { depth: 0 }));
+
+        // Functions, including Skolem functions, need deeper terms
+        assert_eq!(follows(&["forall x (P(x) -> P(f(x)))", "P(a)"], "P(f(f(a)))"), Ok(Consequence::Follows { depth: 1 }));
+        assert_eq!(follows(&["forall x exists y R(x, y)", "forall x forall y (R(x, y) -> R(y, x))"], "forall x exists y (R(x, y) & R(y, x))"), Ok(Consequence::Follows { depth: 1 }));
+        assert_eq!(follows(&["forall x (P(x) -> P(f(x)))"], "P(f(a))"), Ok(Consequence::Undecided { depth: Some(2) }));
+        assert_eq!(consequence(&[p("forall x (P(x) -> P(f(x)))")], &p("P(f(a))"), &Config { max_depth: 5, max_clauses: 3 }), Ok(Consequence::Undecided { depth: Some(1) }));
+
+        assert!(follows(&["x < y"], "x < y + 1").is_err());
+        assert!(follows(&["forall x x"], "A").is_err());
+    }
+
+    #[test]
+    fn test_consequence_times_out() {
+        use crate::limits::CheckLimits;
+        limits::set_limits(CheckLimits { max_steps: 4, timeout: None });
+        let result = limits::with_budget(|| consequence(&[p("forall x (P(x) -> P(f(x)))")], &p("P(f(a))"), &Config::default()));
+        limits::set_limits(CheckLimits::default());
+        assert_eq!(result, Ok(Consequence::TimedOut { depth: Some(0) }));
+    }
+}
@@ -0,0 +1,230 @@
+/*!
+Hash-consed expressions
+
+An `ExprArena` stores every distinct subexpression exactly once and hands out
+`ExprId` handles to it. Two ids from the same arena are equal exactly when the
+expressions they denote are structurally equal, so comparing or hashing an
+interned expression is O(1) no matter how deeply it is nested, and repeated
+subterms are shared instead of cloned. Interning uses an explicit stack instead
+of recursion, like the traversals in `expr`.
+
+The rewrite engine keeps an arena while it normalizes an expression, to
+remember by id the subexpressions none of its patterns apply to, so they
+aren't matched against every pattern again when they turn up elsewhere in the
+expression or in a later pass.
+
+# Usage
+
+```
+use aris::interned::ExprArena;
+use aris::parser::parse_unwrap as p;
+
+let mut arena = ExprArena::new();
+let a = arena.intern(&p("(A & B) -> (A & B)"));
+let b = arena.intern(&p("(A & B) -> (A & B)"));
+assert_eq!(a, b);
+
+// `A`, `B`, `A & B`, and the implication itself
+assert_eq!(arena.len(), 4);
+assert_eq!(arena.to_expr(a), p("(A & B) -> (A & B)"));
+```
+
+Ids are only meaningful for the arena that created them; mixing ids from
+different arenas gives nonsensical (but memory safe) results.
+*/
+
+use crate::expr::Expr;
+use crate::expr::Op;
+use crate::expr::QuantKind;
+
+use std::collections::HashMap;
+
+/// Handle to an expression stored in an `ExprArena`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct ExprId(usize);
+
+/// One level of an interned expression. This mirrors `Expr`, except that
+/// subexpressions are `ExprId`s into the owning arena instead of owned trees.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ExprNode {
+    Contra,
+    Taut,
+    Var { name: String },
+    Apply { func: ExprId, args: Vec<ExprId> },
+    Not { operand: ExprId },
+    Impl { left: ExprId, right: ExprId },
+    Assoc { op: Op, exprs: Vec<ExprId> },
+    Quant { kind: QuantKind, name: String, body: ExprId },
+}
+
+/// Arena of hash-consed expressions. See the module documentation for details.
+#[derive(Clone, Debug, Default)]
+pub struct ExprArena {
+    nodes: Vec<ExprNode>,
+    ids: HashMap<ExprNode, ExprId>,
+}
+
+impl ExprArena {
+    /// Create an empty arena
+    pub fn new() -> Self {
+        ExprArena::default()
+    }
+
+    /// Number of distinct subexpressions stored in the arena
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the arena has no expressions in it
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Look up the node that an id refers to
+    pub fn node(&self, id: ExprId) -> &ExprNode {
+        &self.nodes[id.0]
+    }
+
+    /// Intern a single node whose children are already in the arena, returning
+    /// the existing id if an equal node was interned before
+    pub fn intern_node(&mut self, node: ExprNode) -> ExprId {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        let id = ExprId(self.nodes.len());
+        self.nodes.push(node.clone());
+        self.ids.insert(node, id);
+        id
+    }
+
+    /// Intern an expression and all of its subexpressions
+    pub fn intern(&mut self, expr: &Expr) -> ExprId {
+        ids_bottom_up(expr, |node| Some(self.intern_node(node))).expect("every node is interned")
+    }
+
+    /// Find the id of an expression without adding anything to the arena.
+    /// Returns `None` if the expression was never interned.
+    pub fn lookup(&self, expr: &Expr) -> Option<ExprId> {
+        ids_bottom_up(expr, |node| self.ids.get(&node).copied())
+    }
+
+    /// Rebuild the owned `Expr` tree that an id refers to
+    pub fn to_expr(&self, id: ExprId) -> Expr {
+        // Built from the leaves up, with the subexpressions already built on `built`
+        let mut stack = vec![(id, false)];
+        let mut built = vec![];
+        while let Some((id, children_built)) = stack.pop() {
+            let node = self.node(id);
+            if !children_built {
+                stack.push((id, true));
+                stack.extend(node.children().into_iter().rev().map(|child| (child, false)));
+                continue;
+            }
+            let mut children = built.split_off(built.len() - node.children().len()).into_iter();
+            let mut next = || Box::new(children.next().expect("a subexpression for each child"));
+            built.push(match node {
+                ExprNode::Contra => Expr::Contra,
+                ExprNode::Taut => Expr::Taut,
+                ExprNode::Var { name } => Expr::Var { name: name.clone() },
+                ExprNode::Apply { args, .. } => Expr::Apply { func: next(), args: args.iter().map(|_| *next()).collect() },
+                ExprNode::Not { .. } => Expr::Not { operand: next() },
+                ExprNode::Impl { .. } => Expr::Impl { left: next(), right: next() },
+                ExprNode::Assoc { op, exprs } => Expr::Assoc { op: *op, exprs: exprs.iter().map(|_| *next()).collect() },
+                ExprNode::Quant { kind, name, .. } => Expr::Quant { kind: *kind, name: name.clone(), body: next() },
+            });
+        }
+        built.pop().expect("the whole expression is built last")
+    }
+}
+
+impl ExprNode {
+    /// The ids of the node's subexpressions, in the order of `Expr::children()`
+    fn children(&self) -> Vec<ExprId> {
+        match self {
+            ExprNode::Contra | ExprNode::Taut | ExprNode::Var { .. } => vec![],
+            ExprNode::Apply { func, args } => std::iter::once(*func).chain(args.iter().copied()).collect(),
+            ExprNode::Not { operand } => vec![*operand],
+            ExprNode::Impl { left, right } => vec![*left, *right],
+            ExprNode::Assoc { exprs, .. } => exprs.clone(),
+            ExprNode::Quant { body, .. } => vec![*body],
+        }
+    }
+}
+
+/// The id of `expr`, found from the leaves up by `id_of`, which is given each node once its subexpressions have
+/// ids, and can give up by returning `None`. An explicit stack is used instead of recursion, so deeply nested
+/// expressions can't overflow the call stack.
+fn ids_bottom_up(expr: &Expr, mut id_of: impl FnMut(ExprNode) -> Option<ExprId>) -> Option<ExprId> {
+    let mut stack = vec![(expr, false)];
+    let mut ids = vec![];
+    while let Some((e, children_done)) = stack.pop() {
+        if !children_done {
+            stack.push((e, true));
+            stack.extend(e.children().into_iter().rev().map(|child| (child, false)));
+            continue;
+        }
+        let mut children = ids.split_off(ids.len() - e.children().len()).into_iter();
+        let mut next = || children.next().expect("an id for each subexpression");
+        let node = match e {
+            Expr::Contra => ExprNode::Contra,
+            Expr::Taut => ExprNode::Taut,
+            Expr::Var { name } => ExprNode::Var { name: name.clone() },
+            Expr::Apply { args, .. } => ExprNode::Apply { func: next(), args: args.iter().map(|_| next()).collect() },
+            Expr::Not { .. } => ExprNode::Not { operand: next() },
+            Expr::Impl { .. } => ExprNode::Impl { left: next(), right: next() },
+            Expr::Assoc { op, exprs } => ExprNode::Assoc { op: *op, exprs: exprs.iter().map(|_| next()).collect() },
+            Expr::Quant { kind, name, .. } => ExprNode::Quant { kind: *kind, name: name.clone(), body: next() },
+        };
+        ids.push(id_of(node)?);
+    }
+    ids.pop()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::parser::parse_unwrap as p;
+
+    #[test]
+    fn test_interning_shares_subterms() {
+        let mut arena = ExprArena::new();
+        let e = p("forall x (P(x) & P(x)) | ~(P(x) & P(x))");
+        let id = arena.intern(&e);
+        // P, x, P(x), P(x) & P(x), the quantifier, the negation, and the disjunction
+        assert_eq!(arena.len(), 7);
+        assert_eq!(arena.to_expr(id), e);
+
+        let sub = arena.lookup(&p("P(x) & P(x)")).unwrap();
+        match arena.node(id) {
+            ExprNode::Assoc { op: Op::Or, exprs } => {
+                assert_eq!(arena.node(exprs[1]), &ExprNode::Not { operand: sub });
+            }
+            node => panic!("unexpected node {node:?}"),
+        }
+    }
+
+    #[test]
+    fn test_interned_equality_is_structural() {
+        let mut arena = ExprArena::new();
+        let exprs = ["A & B", "B & A", "A -> B", "A & B", "exists y Q(y)", "exists z Q(z)"].iter().map(|s| p(s)).collect::<Vec<_>>();
+        for e1 in &exprs {
+            for e2 in &exprs {
+                assert_eq!(arena.intern(e1) == arena.intern(e2), e1 == e2);
+            }
+        }
+        assert_eq!(arena.lookup(&p("C")), None);
+    }
+
+    #[test]
+    fn test_interning_deep_expressions() {
+        let mut arena = ExprArena::new();
+        let e = (0..10_000).fold(Expr::var("A"), |e, i| if i % 2 == 0 { !e } else { Expr::forall("x", e) });
+        let id = arena.intern(&e);
+        assert_eq!(arena.len(), 10_001);
+        assert_eq!(arena.lookup(&e), Some(id));
+        assert_eq!(arena.lookup(&!e.clone()), None);
+        assert_eq!(arena.to_expr(id), e);
+    }
+}
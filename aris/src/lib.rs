@@ -1,11 +1,28 @@
 #[macro_use]
 extern crate lazy_static;
 
+pub mod analytics;
+pub mod equivalence;
 mod equivs;
 pub mod expr;
+pub mod herbrand;
+pub mod interned;
+pub mod limits;
 pub mod macros;
 pub mod parser;
+pub mod plugins;
 pub mod proofs;
-mod rewrite_rules;
+pub mod rewrite_rules;
+pub mod rule_examples;
+pub mod rule_names;
 pub mod rules;
+pub mod soundness;
+#[cfg(any(test, feature = "proptest"))]
+pub mod strategies;
+pub mod symbolization;
+pub mod temporal;
+pub mod truth_table;
+pub mod workspace;
 mod zipper_vec;
+
+pub use equivalence::are_equivalent;
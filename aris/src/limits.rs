@@ -0,0 +1,162 @@
+/*!
+# Limits on checking lines
+
+Some rules search for their justification, like truth-functional consequence,
+which converts its dependencies to CNF, and rules that try their dependencies
+in every order. A line written to make that search blow up would otherwise
+check forever, locking up the editor it's in.
+
+Checking a line gets a budget of steps, which the expensive checkers use up as
+they go, and on native builds a time limit. When either runs out, the check
+stops with `ProofCheckError::TimedOut`. WebAssembly has no clock without
+JavaScript, so only the step budget applies there; it's cooperative either
+way, since checkers only stop where they spend steps.
+
+```
+use aris::limits::{self, CheckLimits};
+
+limits::set_limits(CheckLimits { max_steps: 10_000, timeout: None });
+assert_eq!(limits::limits().max_steps, 10_000);
+```
+*/
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::time::Duration;
+
+/// Steps a line may take to check by default
+pub const DEFAULT_MAX_STEPS: u64 = 1_000_000;
+
+/// Time a line may take to check by default, on native builds
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How much work checking a line may take
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CheckLimits {
+    /// Steps of work allowed, like clauses of a CNF conversion or orders of
+    /// dependencies tried
+    pub max_steps: u64,
+    /// Time allowed, if it's limited. This is only enforced on native builds.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for CheckLimits {
+    fn default() -> Self {
+        CheckLimits { max_steps: DEFAULT_MAX_STEPS, timeout: Some(DEFAULT_TIMEOUT) }
+    }
+}
+
+/// A check ran out of its budget
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimedOut;
+
+/// What's left for the check in progress
+struct Budget {
+    steps_left: u64,
+    #[cfg(not(target_arch = "wasm32"))]
+    deadline: Option<std::time::Instant>,
+}
+
+thread_local! {
+    static LIMITS: Cell<CheckLimits> = Cell::new(CheckLimits::default());
+    static BUDGET: RefCell<Option<Budget>> = const { RefCell::new(None) };
+}
+
+/// Set the limits of checks on the current thread
+pub fn set_limits(limits: CheckLimits) {
+    LIMITS.with(|current| current.set(limits));
+}
+
+/// The limits of checks on the current thread
+pub fn limits() -> CheckLimits {
+    LIMITS.with(Cell::get)
+}
+
+/// Ends the check in progress when dropped, even if the check panics
+struct BudgetGuard;
+
+impl Drop for BudgetGuard {
+    fn drop(&mut self) {
+        BUDGET.with(|budget| *budget.borrow_mut() = None);
+    }
+}
+
+/// Run a check with a fresh budget. Checks run as part of another check, like
+/// rules checking with other rules, share its budget.
+pub(crate) fn with_budget<T>(check: impl FnOnce() -> T) -> T {
+    let started = BUDGET.with(|budget| {
+        let mut budget = budget.borrow_mut();
+        if budget.is_some() {
+            return false;
+        }
+        let limits = limits();
+        #[cfg(not(target_arch = "wasm32"))]
+        let deadline = limits.timeout.map(|timeout| std::time::Instant::now() + timeout);
+        *budget = Some(Budget {
+            steps_left: limits.max_steps,
+            #[cfg(not(target_arch = "wasm32"))]
+            deadline,
+        });
+        true
+    });
+    // Only the outermost check ends the budget
+    let _guard = if started { Some(BudgetGuard) } else { None };
+    check()
+}
+
+/// Use up `steps` of the budget of the check in progress, failing if it runs
+/// out. Work done outside of a check, like calling a checker directly, is
+/// unlimited.
+pub(crate) fn spend(steps: u64) -> Result<(), TimedOut> {
+    BUDGET.with(|budget| {
+        let mut budget = budget.borrow_mut();
+        let budget = match &mut *budget {
+            Some(budget) => budget,
+            None => return Ok(()),
+        };
+        budget.steps_left = match budget.steps_left.checked_sub(steps) {
+            Some(left) => left,
+            None => {
+                budget.steps_left = 0;
+                return Err(TimedOut);
+            }
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        if budget.deadline.map(|deadline| std::time::Instant::now() > deadline).unwrap_or(false) {
+            return Err(TimedOut);
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spend() {
+        set_limits(CheckLimits { max_steps: 10, timeout: None });
+        // Outside of a check, nothing is counted
+        assert_eq!(spend(100), Ok(()));
+        with_budget(|| {
+            assert_eq!(spend(6), Ok(()));
+            // Nested checks share the budget
+            with_budget(|| assert_eq!(spend(4), Ok(())));
+            assert_eq!(spend(1), Err(TimedOut));
+            assert_eq!(spend(0), Ok(()));
+        });
+        // The next check gets a fresh budget
+        with_budget(|| assert_eq!(spend(10), Ok(())));
+        set_limits(CheckLimits::default());
+    }
+
+    #[test]
+    fn test_timeout() {
+        set_limits(CheckLimits { max_steps: u64::MAX, timeout: Some(Duration::ZERO) });
+        with_budget(|| {
+            std::thread::sleep(Duration::from_millis(1));
+            assert_eq!(spend(1), Err(TimedOut));
+        });
+        set_limits(CheckLimits::default());
+    }
+}
@@ -2,7 +2,7 @@
 
 /// Table of ASCII characters, macros, and their corresponding logic symbols.
 /// The format of each row is `(symbol, macros)`.
-pub static TABLE: [(&str, &[&str]); 10] = [("⊥", &[".con", "^"]), ("⊤", &[".taut", "!"]), ("¬", &[".not", "~"]), ("∀", &["forall", "@"]), ("∃", &["exists", "?"]), ("∧", &[".and", "&", r#"/\"#]), ("∨", &[".or", "|", r#"\/"#]), ("↔", &[".bicon", "%", "<->"]), ("→", &[".impl", "$", "->"]), ("≡", &[".equiv", "==="])];
+pub static TABLE: [(&str, &[&str]); 17] = [("⊥", &[".con", "^"]), ("⊤", &[".taut", "!"]), ("¬", &[".not", "~"]), ("∀", &["forall", "@"]), ("∃", &["exists", "?"]), ("∧", &[".and", "&", r#"/\"#]), ("∨", &[".or", "|", r#"\/"#]), ("↔", &[".bicon", "%", "<->"]), ("→", &[".impl", "$", "->"]), ("≡", &[".equiv", "==="]), ("×", &[".times"]), ("≤", &[".leq", "<="]), ("∈", &[".in"]), ("⊆", &[".sub"]), ("∪", &[".cup"]), ("∩", &[".cap"]), ("∅", &[".empty"])];
 
 /// Convert ASCII characters and macros to logic symbols.
 ///
@@ -13,9 +13,9 @@ pub static TABLE: [(&str, &[&str]); 10] = [("⊥", &[".con", "^"]), ("⊤", &[".
 /// );
 /// assert_eq!(
 ///     aris::macros::expand(
-///         r#".con ^ .taut .not ~ forall @ exists ? .and & /\ .or | \/ .bicon % <-> .impl $ -> .equiv ==="#
+///         r#".con ^ .taut .not ~ forall @ exists ? .and & /\ .or | \/ .bicon % <-> .impl $ -> .equiv === .times .leq <= .in .sub .cup .cap .empty"#
 ///     ),
-///     "⊥ ⊥ ⊤ ¬ ¬ ∀ ∀ ∃ ∃ ∧ ∧ ∧ ∨ ∨ ∨ ↔ ↔ ↔ → → → ≡ ≡"
+///     "⊥ ⊥ ⊤ ¬ ¬ ∀ ∀ ∃ ∃ ∧ ∧ ∧ ∨ ∨ ∨ ↔ ↔ ↔ → → → ≡ ≡ × ≤ ≤ ∈ ⊆ ∪ ∩ ∅"
 /// );
 /// ```
 pub fn expand(s: &str) -> String {
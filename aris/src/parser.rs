@@ -8,17 +8,63 @@
 //! ## Main Functions
 //! - 'parse': Converts a logical expression string into an AST ('Expr') or returns 'None' if parsing fails.
 //! - 'parse_unwrap': Like 'parse', but panics on failure. Primarily used for testing.
+//! - 'try_parse': Like 'parse', but says why parsing failed, telling apart inputs that are too large.
+//! - 'parse_with' and 'try_parse_with': Like 'parse' and 'try_parse', but in a given `Syntax`.
+//!
+//! ## Limits
+//! Formulas longer than `ParseLimits::max_len` characters, or nested more than `ParseLimits::max_depth` levels deep
+//! in parentheses, negations, and quantifiers, are rejected with `ParseError::TooLong` and `ParseError::TooDeep`,
+//! before they can overflow the stack or keep the parser busy. The limits are set per thread with `set_limits`.
+//!
+//! ## Syntax
+//! Which logic formulas are in, and which operators they declare, make up their `Syntax`. `parse` uses the syntax set
+//! on the current thread, only ever while a closure runs with `with_syntax`, which is also how expressions are printed,
+//! and `parse_with` takes the syntax to parse in instead, leaving the current thread's alone.
 //!
 //! ## Grammar and Parsing Notes
 //! - The parser handles infix logical expressions with support for parentheses, quantifiers, and operators.
 //! - Functions are modular and correspond to specific grammar productions in Extended Backus-Naur Form (EBNF).
 //! - The parser includes support for Unicode symbols (e.g., '∀', '∃', '∧', '∨').
+//! - Names can end in a numbered subscript and primes, written 'x_1' and "x'" or 'x₁' and 'x′', which are the same
+//!   names. Expressions are printed with the subscripts and primes typeset, as `expr::display_name` does.
+//! - Numerals, sums ('+'), products ('*' or '×'), and comparisons ('<', '<=' or '≤') are terms and formulas about natural
+//!   numbers, e.g. 'forall n (n < n + 1)'. A comparison is parsed as an application of the predicate `expr::LESS` or
+//!   `expr::LESS_EQ`.
+//! - Sets are written with '∅', unions ('∪'), intersections ('∩'), and set builders ('{x : P(x)}' or '{x | P(x)}'), and
+//!   compared with '∈', '⊆', and '=' at the same level as numbers, e.g. 'x ∈ A ∪ B -> x ∈ B ∪ A'.
+//!
+//! ## Higher-Order Logic
+//! With the logic mode set to `LogicMode::HigherOrder` by `with_syntax`, lambda abstractions are written with 'λ' or
+//! 'lambda' like quantifiers, e.g. 'λx (P(x) & Q(x))', and anything can be applied to arguments, e.g. '(λx P(x))(a)' or
+//! 'f(x)(y)'. In first-order logic, which is the default, only names are applied to arguments, and 'lambda' is a name.
+//!
+//! ## Temporal Logic
+//! With the logic mode set to `LogicMode::Temporal`, 'G' (always), 'F' (eventually), and 'X' (next) are written before
+//! formulas like negations, e.g. 'G (p -> F q)', and 'U' (until) between them like comparisons, e.g. 'p U q'. They're
+//! parsed as applications of `expr::ALWAYS`, `expr::EVENTUALLY`, `expr::NEXT`, and `expr::UNTIL`, and those letters
+//! aren't names of variables.
+//!
+//! ## Hoare Logic
+//! With the logic mode set to `LogicMode::Program`, a line can also be a Hoare triple '{P} S {Q}' about a program 'S',
+//! e.g. '{x = 0} x := x + 1; y := x {y = 1}'. Programs are sequences of statements separated by ';', which are 'skip',
+//! assignments of terms to variables ('x := e'), conditionals ('if b then S else T end'), and loops
+//! ('while b do S end'), and can be parenthesized. Since ';' separates statements, it doesn't start a comment, and the
+//! words of statements aren't names of variables. Triples are parsed with `Expr::triple`, and programs with the other
+//! helpers that `Expr::as_statement` takes apart.
+//!
+//! ## Declared Operators
+//! A proof can declare its own infix operators, like a relation 'R' written 'x R y' or a function '⊕', in the
+//! `operators` of its `Syntax`. They're parsed as applications of the symbol to both sides, and printed back the same way. Declared
+//! operators bind looser than sums and unions, and tighter than the built-in comparisons and logical operators; among
+//! themselves, ones with higher precedence bind tighter, and ones with the same precedence group to the left. Operators
+//! spelled with letters have to be separated from their operands by spaces.
 
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::newline;
 use nom::character::complete::one_of;
 use nom::combinator::map;
+use nom::combinator::not;
 use nom::combinator::peek;
 use nom::combinator::recognize;
 use nom::combinator::value;
@@ -26,28 +72,249 @@ use nom::combinator::verify;
 use nom::multi::many0;
 use nom::multi::many1;
 use nom::multi::separated_list0;
+use nom::multi::separated_list1;
 use nom::sequence::delimited;
 use nom::sequence::pair;
 use nom::sequence::preceded;
-use nom::sequence::separated_pair;
 use nom::sequence::terminated;
 use nom::sequence::tuple;
 use nom::IResult;
 
+use crate::expr::enclosed_in_parens;
 use crate::expr::Expr;
 use crate::expr::Op;
 use crate::expr::QuantKind;
+use crate::expr::ALWAYS;
+use crate::expr::ASSIGN;
+use crate::expr::ELEMENT;
+use crate::expr::EMPTY_SET;
+use crate::expr::EQUALS;
+use crate::expr::EVENTUALLY;
+use crate::expr::HOARE_TRIPLE;
+use crate::expr::IF;
+use crate::expr::INTERSECTION;
+use crate::expr::LESS;
+use crate::expr::LESS_EQ;
+use crate::expr::NEXT;
+use crate::expr::SEQUENCE;
+use crate::expr::SKIP;
+use crate::expr::SUBSET;
+use crate::expr::UNION;
+use crate::expr::UNTIL;
+use crate::expr::WHILE;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+
+/// Longest formula accepted by default, in characters
+pub const DEFAULT_MAX_LEN: usize = 10_000;
+
+/// Deepest nesting of parentheses, negations, and quantifiers accepted by default
+pub const DEFAULT_MAX_DEPTH: usize = 100;
+
+/// How large a formula the parser accepts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Characters allowed, including comments
+    pub max_len: usize,
+    /// Levels of nesting allowed, where each parenthesized subformula, negation, quantifier, and operand of an
+    /// implication or associative operator is a level
+    pub max_depth: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits { max_len: DEFAULT_MAX_LEN, max_depth: DEFAULT_MAX_DEPTH }
+    }
+}
+
+thread_local! {
+    static LIMITS: Cell<ParseLimits> = Cell::new(ParseLimits::default());
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Set the limits of parsing on the current thread
+pub fn set_limits(limits: ParseLimits) {
+    LIMITS.with(|current| current.set(limits));
+}
+
+/// The limits of parsing on the current thread
+pub fn limits() -> ParseLimits {
+    LIMITS.with(Cell::get)
+}
+
+/// Symbols of the built-in operators and constants, which can't be declared again
+const BUILTIN_SYMBOLS: [&str; 33] = ["&", "∧", "/\\", "|", "∨", "\\/", "<->", "↔", "===", "≡", "->", "→", "+", "*", "×", "<", "<=", "≤", "=", "∈", "⊆", "∪", "∩", "∅", "~", "¬", "⊥", "⊤", "_|_", "^|^", "∀", "∃", ":"];
+
+/// An infix operator declared by a proof, like a relation `R` written `x R y`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InfixOperator {
+    /// How the operator is written, which is also the name of the function or predicate it applies
+    pub symbol: String,
+    /// How tightly the operator binds, relative to other declared operators
+    pub precedence: u32,
+}
+
+impl InfixOperator {
+    /// Declare an operator, checking that its symbol can be parsed unambiguously
+    pub fn new(symbol: &str, precedence: u32) -> Result<InfixOperator, String> {
+        let is_word = symbol.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if symbol.is_empty() || symbol.chars().any(|c| c.is_whitespace() || "(){},;".contains(c)) {
+            return Err(format!("{symbol:?} can't be used as an operator"));
+        }
+        if BUILTIN_SYMBOLS.contains(&symbol) || keyword(symbol).is_ok() {
+            return Err(format!("{symbol:?} is already a built-in symbol"));
+        }
+        if !is_word && symbol.chars().any(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("{symbol:?} mixes letters and symbols, so it can't be told apart from its operands"));
+        }
+        Ok(InfixOperator { symbol: symbol.to_owned(), precedence })
+    }
+}
+
+/// Which logic formulas are parsed in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogicMode {
+    /// First-order logic, where only names are applied to arguments
+    #[default]
+    FirstOrder,
+    /// Higher-order logic, which adds lambda abstractions and applications of any term
+    HigherOrder,
+    /// Linear temporal logic, which adds the temporal operators 'G', 'F', 'X', and 'U'
+    Temporal,
+    /// First-order logic with Hoare triples about programs
+    Program,
+}
+
+thread_local! {
+    static LOGIC_MODE: Cell<LogicMode> = const { Cell::new(LogicMode::FirstOrder) };
+}
+
+/// Set the logic parsed on the current thread
+fn set_logic_mode(mode: LogicMode) {
+    LOGIC_MODE.with(|current| current.set(mode));
+}
+
+/// The logic parsed on the current thread
+pub fn logic_mode() -> LogicMode {
+    LOGIC_MODE.with(Cell::get)
+}
+
+thread_local! {
+    static OPERATORS: RefCell<Vec<InfixOperator>> = const { RefCell::new(vec![]) };
+}
+
+/// Declare the infix operators understood on the current thread, replacing any declared before
+fn set_operators(operators: Vec<InfixOperator>) {
+    OPERATORS.with(|current| *current.borrow_mut() = operators);
+}
+
+/// The infix operators declared on the current thread
+pub fn operators() -> Vec<InfixOperator> {
+    OPERATORS.with(|current| current.borrow().clone())
+}
+
+/// Whether `symbol` is a declared infix operator on the current thread
+pub fn is_declared_operator(symbol: &str) -> bool {
+    OPERATORS.with(|current| current.borrow().iter().any(|op| op.symbol == symbol))
+}
+
+/// The syntax formulas are written in, which is the logic they're in and the infix operators declared for them
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Syntax {
+    /// The logic
+    pub logic_mode: LogicMode,
+    /// The declared infix operators
+    pub operators: Vec<InfixOperator>,
+}
+
+impl Syntax {
+    /// The syntax set on the current thread, which `parse` and printing expressions use
+    pub fn current() -> Syntax {
+        Syntax { logic_mode: logic_mode(), operators: operators() }
+    }
+}
+
+/// Run `f` with `syntax` set on the current thread, and restore the syntax set before afterwards, even if `f` panics
+pub fn with_syntax<T>(syntax: &Syntax, f: impl FnOnce() -> T) -> T {
+    struct Restore(Syntax);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_logic_mode(self.0.logic_mode);
+            set_operators(std::mem::take(&mut self.0.operators));
+        }
+    }
+    let _restore = Restore(Syntax::current());
+    set_logic_mode(syntax.logic_mode);
+    set_operators(syntax.operators.clone());
+    f()
+}
+
+/// Why an input couldn't be parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input isn't a well-formed formula
+    Syntax,
+    /// The input has `len` characters, more than the limit of `max_len`
+    TooLong { len: usize, max_len: usize },
+    /// The input nests deeper than the limit of `max_depth` levels
+    TooDeep { max_depth: usize },
+}
+
+impl ParseError {
+    /// Whether the input was rejected for its size, rather than its syntax
+    pub fn is_too_large(&self) -> bool {
+        matches!(self, ParseError::TooLong { .. } | ParseError::TooDeep { .. })
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseError::Syntax => write!(f, "Parse error"),
+            ParseError::TooLong { len, max_len } => write!(f, "Expression too large: it has {len} characters, and at most {max_len} are allowed"),
+            ParseError::TooDeep { max_depth } => write!(f, "Expression too large: it nests more than {max_depth} levels deep"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
 
 /// parser::parse parses a string slice into an Expr AST, returning None if there's an error
 pub fn parse(input: &str) -> Option<Expr> {
-    let no_comments: String = input
-        .lines()
-        .map(|line| line.split(';').next().unwrap_or("").trim()) // Remove everything after ';' and trim
-        .collect::<Vec<_>>()
-        .join("\n"); // Rejoin the cleaned lines
+    try_parse(input).ok()
+}
+
+/// Like `parse`, but in `syntax` rather than the syntax set on the current thread
+pub fn parse_with(syntax: &Syntax, input: &str) -> Option<Expr> {
+    try_parse_with(syntax, input).ok()
+}
+
+/// Like `try_parse`, but in `syntax` rather than the syntax set on the current thread
+pub fn try_parse_with(syntax: &Syntax, input: &str) -> Result<Expr, ParseError> {
+    with_syntax(syntax, || try_parse(input))
+}
+
+/// parser::try_parse parses a string slice into an Expr AST, returning why it couldn't if there's an error
+pub fn try_parse(input: &str) -> Result<Expr, ParseError> {
+    let limits = limits();
+    let len = input.chars().count();
+    if len > limits.max_len {
+        return Err(ParseError::TooLong { len, max_len: limits.max_len });
+    }
 
-    let newlined = format!("{no_comments}\n");
-    main(&newlined).map(|(_, expr)| expr).ok()
+    let newlined = format!("{}\n", without_comments(input));
+    match main(&newlined) {
+        Ok((_, expr)) => Ok(expr),
+        Err(nom::Err::Failure(err)) if err.code == nom::error::ErrorKind::TooLarge => Err(ParseError::TooDeep { max_depth: limits.max_depth }),
+        Err(_) => Err(ParseError::Syntax),
+    }
+}
+
+/// The input without its comments, which run from ';' to the end of a line outside of programs, and with each line
+/// trimmed
+fn without_comments(input: &str) -> String {
+    input.lines().map(|line| if logic_mode() == LogicMode::Program { line.trim() } else { line.split(';').next().unwrap_or("").trim() }).collect::<Vec<_>>().join("\n")
 }
 
 /// parser::parse_unwrap is a convenience function used in the tests, and panics if the input doesn't parse
@@ -56,16 +323,79 @@ pub fn parse_unwrap(input: &str) -> Expr {
     parse(input).unwrap_or_else(|| panic!("failed parsing: {input}"))
 }
 
-/// Custom error helper function for parser failure
-fn custom_error<A, B>(a: A) -> nom::IResult<A, B> {
-    Err(nom::Err::Error(nom::error::Error { input: a, code: nom::error::ErrorKind::Fail }))
+/// Respell a formula the way it's displayed, with the same symbols, spacing, and parentheses as every other formula, or
+/// `None` if it doesn't parse or its displayed form wouldn't parse back to the same formula
+///
+/// ```
+/// use aris::parser::reformat;
+///
+/// assert_eq!(reformat("(A&B) -> ~C").as_deref(), Some("((A ∧ B) → ¬C)"));
+/// assert_eq!(reformat("a & ("), None);
+/// ```
+pub fn reformat(input: &str) -> Option<String> {
+    let e = parse(input)?;
+    let formatted = e.to_string();
+    (parse(&formatted)? == e).then_some(formatted)
+}
+
+/// Whether every binary connective, comparison, and quantifier of a formula is in parentheses of its own, the way
+/// `Parenthesization::Full` writes it, for courses that require formulas to be written that way. Parentheses around
+/// an atom or a negation, or around other parentheses, are allowed but don't count. Formulas that don't parse aren't
+/// fully parenthesized.
+///
+/// ```
+/// use aris::parser::is_fully_parenthesized;
+///
+/// assert!(is_fully_parenthesized("((A & B) -> ~C)"));
+/// assert!(is_fully_parenthesized("~((A))"));
+/// assert!(!is_fully_parenthesized("(A & B) -> ~C"));
+/// assert!(!is_fully_parenthesized("(forall x P(x) & Q)"));
+/// ```
+pub fn is_fully_parenthesized(input: &str) -> bool {
+    match parse(input) {
+        Some(e) => grouping_parens(&without_comments(input)) == grouping_parens(&e.to_string()),
+        None => false,
+    }
+}
+
+/// Number of pairs of parentheses in `s` around a formula that `Parenthesization::Full` parenthesizes, other than
+/// pairs directly around another pair. Since each such pair is around a different subformula, a formula is fully
+/// parenthesized when it has as many of them as its displayed form.
+fn grouping_parens(s: &str) -> usize {
+    let mut open = vec![];
+    let mut count = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => open.push(i),
+            ')' => {
+                if let Some(start) = open.pop() {
+                    // Argument lists don't parse, and atoms aren't displayed in parentheses
+                    let inside = s[start + 1..i].trim();
+                    if !enclosed_in_parens(inside) && parse(inside).is_some_and(|e| enclosed_in_parens(&e.to_string())) {
+                        count += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    count
+}
+
+/// Run `f` with the default syntax, first-order logic without declared operators, and restore the syntax of the current
+/// thread afterwards
+pub(crate) fn with_default_syntax<T>(f: impl FnOnce() -> T) -> T {
+    with_syntax(&Syntax::default(), f)
 }
 
 /// Parses a variable, ensuring it is not a reserved keyword
 fn variable(input: &str) -> nom::IResult<&str, String> {
-    verify(variable_, |v| keyword(v).is_err())(input)
+    verify(variable_, |v| keyword(v).is_err() && !(logic_mode() == LogicMode::Temporal && [ALWAYS, EVENTUALLY, NEXT, UNTIL].contains(&v)) && !(logic_mode() == LogicMode::Program && PROGRAM_WORDS.contains(&v)))(input)
 }
 
+/// Words of programs, and names of what they're parsed as, which aren't names of variables in Hoare logic
+const PROGRAM_WORDS: [&str; 10] = [SKIP, IF, "then", "else", WHILE, "do", "end", HOARE_TRIPLE, ASSIGN, SEQUENCE];
+
 // All the functions below can be thought of as grammar productions interleaved with code that constructs the AST value associated with each production.
 // `alt` corresponds to alternation/choice in an EBNF grammar
 // `tag` is used for literal string values, and supports unicode
@@ -75,14 +405,37 @@ fn space(input: &str) -> IResult<&str, ()> {
     value((), many0(one_of(" \t")))(input)
 }
 
-/// Matches variable-like identifiers (alphanumeric or underscores)
+/// Matches variable-like identifiers (alphanumeric or underscores), which can end in subscript digits and primes. Those are
+/// read as an underscore and digits and as apostrophes, so `x₁′` is the same name as `x_1'`, which is how
+/// `expr::display_name` writes it.
 fn variable_(input: &str) -> IResult<&str, String> {
-    map(recognize(many1(one_of("abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_"))), |v: &str| v.to_owned())(input)
+    let name = recognize(tuple((many1(one_of("abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_")), many0(one_of("₀₁₂₃₄₅₆₇₈₉")), many0(one_of("'′")))));
+    map(name, |v: &str| {
+        let mut name = String::with_capacity(v.len());
+        let mut subscripted = false;
+        for c in v.chars() {
+            match c {
+                '₀'..='₉' => {
+                    if !subscripted {
+                        name.push('_');
+                        subscripted = true;
+                    }
+                    name.push(char::from(b'0' + (c as u32 - '₀' as u32) as u8));
+                }
+                '′' => name.push('\''),
+                c => name.push(c),
+            }
+        }
+        name
+    })(input)
 }
 
-/// Matches logical keywords ('forall' or 'exists')
+/// Matches logical keywords ('forall' or 'exists', and 'lambda' in higher-order logic)
 fn keyword(input: &str) -> IResult<&str, &str> {
-    alt((tag("forall"), tag("exists")))(input)
+    match logic_mode() {
+        LogicMode::FirstOrder | LogicMode::Temporal | LogicMode::Program => alt((tag("forall"), tag("exists")))(input),
+        LogicMode::HigherOrder => alt((tag("forall"), tag("exists"), tag("lambda")))(input),
+    }
 }
 
 /// Parses a logical contradiction (e.g., '_⊥_')
@@ -95,14 +448,47 @@ fn tautology(input: &str) -> IResult<&str, Expr> {
     value(Expr::Taut, alt((tag("^|^"), tag("⊤"))))(input)
 }
 
+/// Parses the empty set ('∅')
+fn empty_set(input: &str) -> IResult<&str, Expr> {
+    value(Expr::var(EMPTY_SET), delimited(space, tag("∅"), space))(input)
+}
+
+/// Parses a set builder (e.g., '{x : P(x)}' or '{x | P(x)}')
+fn set_builder(input: &str) -> IResult<&str, Expr> {
+    map(delimited(tuple((space, tag("{"), space)), pair(variable, preceded(tuple((space, alt((tag(":"), tag("|"))), space)), expr)), tuple((space, tag("}"), space))), |(name, body)| Expr::set_builder(&name, body))(input)
+}
+
 /// Parses a negation term (e.g., '¬A')
 fn notterm(input: &str) -> IResult<&str, Expr> {
     map(preceded(alt((tag("~"), tag("¬"))), paren_expr), |e| Expr::Not { operand: Box::new(e) })(input)
 }
 
+/// Parses the arguments of an application (e.g., '(a, b)')
+fn arguments(input: &str) -> IResult<&str, Vec<Expr>> {
+    delimited(tag("("), separated_list0(tuple((space, tag(","), space)), expr), tag(")"))(input)
+}
+
+/// Parses a temporal operator ('G', 'F', or 'X') and the term it applies to (e.g., 'G p'), in temporal logic
+fn temporal_term(input: &str) -> IResult<&str, Expr> {
+    if logic_mode() != LogicMode::Temporal {
+        return Err(nom::Err::Error(nom::error::Error { input, code: nom::error::ErrorKind::Tag }));
+    }
+    let operator = alt((value(ALWAYS, tag("G")), value(EVENTUALLY, tag("F")), value(NEXT, tag("X"))));
+    // The letter can't be the start of a longer name
+    map(pair(terminated(operator, pair(not(variable_), space)), paren_expr), |(op, e)| Expr::apply(Expr::var(op), &[e]))(input)
+}
+
+/// Parses an until operator ('U'), giving its name, in temporal logic
+fn untilrepr(input: &str) -> IResult<&str, &'static str> {
+    if logic_mode() != LogicMode::Temporal {
+        return Err(nom::Err::Error(nom::error::Error { input, code: nom::error::ErrorKind::Tag }));
+    }
+    value(UNTIL, terminated(tag("U"), not(variable_)))(input)
+}
+
 /// Parses a predicate or variable term
 fn predicate(input: &str) -> IResult<&str, Expr> {
-    alt((map(pair(delimited(space, variable, space), delimited(tag("("), separated_list0(tuple((space, tag(","), space)), expr), tag(")"))), |(name, args)| Expr::Apply { func: Box::new(Expr::Var { name }), args }), map(delimited(space, variable, space), |name| Expr::Var { name })))(input)
+    alt((map(pair(delimited(space, variable, space), arguments), |(name, args)| Expr::Apply { func: Box::new(Expr::Var { name }), args }), map(delimited(space, variable, space), |name| Expr::Var { name })))(input)
 }
 
 /// Parses a universal quantifier ('∀') and associates it with an expression
@@ -115,9 +501,17 @@ fn exists_quantifier(input: &str) -> IResult<&str, QuantKind> {
     value(QuantKind::Exists, alt((tag("exists "), tag("∃"))))(input)
 }
 
-/// Parses any quantifier ('∀' or '∃')
+/// Parses a lambda ('λ'), which binds a variable like a quantifier, in higher-order logic
+fn lambda(input: &str) -> IResult<&str, QuantKind> {
+    match logic_mode() {
+        LogicMode::FirstOrder | LogicMode::Temporal | LogicMode::Program => Err(nom::Err::Error(nom::error::Error { input, code: nom::error::ErrorKind::Tag })),
+        LogicMode::HigherOrder => value(QuantKind::Lambda, alt((tag("lambda "), tag("λ"))))(input),
+    }
+}
+
+/// Parses any quantifier ('∀' or '∃'), or a lambda
 fn quantifier(input: &str) -> IResult<&str, QuantKind> {
-    alt((forall_quantifier, exists_quantifier))(input)
+    alt((forall_quantifier, exists_quantifier, lambda))(input)
 }
 
 /// Matches whitespace characters after quantifier
@@ -141,23 +535,16 @@ fn binder(input: &str) -> IResult<&str, Expr> {
         tuple((
             preceded(space, quantifier),
             preceded(space, variable),
-            preceded(
-                conditional_space,
-                alt((
-                    // Parse multiple terms enclosed in parentheses
-                    delimited(tuple((space, tag("("), space)), expr, tuple((space, tag(")"), space))),
-                    // Parse a single term without parentheses
-                    paren_expr,
-                )),
-            ),
+            // A body of several terms has to be parenthesized, which `paren_expr` handles too
+            preceded(conditional_space, paren_expr),
         )),
         |(kind, name, body)| Expr::Quant { kind, name, body: Box::new(body) },
     )(input)
 }
 
-/// Parses an implication term (e.g., 'A -> B' or 'A → B')
-fn impl_term(input: &str) -> IResult<&str, Expr> {
-    map(separated_pair(paren_expr, tuple((space, alt((tag("->"), tag("→"))), space)), paren_expr), |(left, right)| Expr::Impl { left: Box::new(left), right: Box::new(right) })(input)
+/// Parses the rest of an implication term after its left side (e.g., '-> B' or '→ B')
+fn impl_tail(input: &str) -> IResult<&str, Expr> {
+    preceded(tuple((space, alt((tag("->"), tag("→"))), space)), comparison)(input)
 }
 
 /// Parses an AND operator (e.g., '&', '∧', or '/\')
@@ -185,50 +572,196 @@ fn plusrepr(input: &str) -> IResult<&str, Op> {
     value(Op::Add, tag("+"))(input)
 }
 
-/// Parses a multiplication operator ('*')
+/// Parses a multiplication operator ('*' or '×')
 fn multrepr(input: &str) -> IResult<&str, Op> {
-    value(Op::Mult, tag("*"))(input)
+    value(Op::Mult, alt((tag("*"), tag("×"))))(input)
+}
+
+/// Parses a comparison of natural numbers (e.g., '<', '<=', or '≤'), equality ('='), or relation of sets ('∈' or
+/// '⊆'), giving the name of the relation
+fn comparisonrepr(input: &str) -> IResult<&str, &'static str> {
+    alt((value(LESS_EQ, alt((tag("<="), tag("≤")))), value(LESS, terminated(tag("<"), not(tag("->")))), value(EQUALS, terminated(tag("="), not(tag("=")))), value(ELEMENT, tag("∈")), value(SUBSET, tag("⊆"))))(input)
 }
 
-/// Parses a sequence of associative terms and their operators
-fn assoc_term_aux(input: &str) -> IResult<&str, (Vec<Expr>, Vec<Op>)> {
-    alt((
-        map(tuple((paren_expr, delimited(space, alt((andrepr, orrepr, biconrepr, equivrepr, plusrepr, multrepr)), space), assoc_term_aux)), |(e, sym, (mut es, mut syms))| {
-            es.push(e);
-            syms.push(sym);
-            (es, syms)
-        }),
-        map(paren_expr, |e| (vec![e], vec![])),
-    ))(input)
+/// Parses a union or intersection operator ('∪' or '∩'), giving the name of the function
+fn setoprepr(input: &str) -> IResult<&str, &'static str> {
+    alt((value(UNION, tag("∪")), value(INTERSECTION, tag("∩"))))(input)
 }
 
-/// Enforce that all symbols are the same.
-/// This check is what rules out `(a /\ b \/ c)` without further parenthesization.
-fn assoc_term(s: &str) -> nom::IResult<&str, Expr> {
-    let (rest, (mut exprs, syms)) = assoc_term_aux(s)?;
-    assert_eq!(exprs.len(), syms.len() + 1);
-    if exprs.len() == 1 {
-        return custom_error(rest);
+/// Parses a term or a sum or product of terms (e.g., 'x + 1'), which may be a side of a comparison
+fn arith_term(input: &str) -> IResult<&str, Expr> {
+    let (rest, first) = paren_expr(input)?;
+    let (after_tail, tail) = many0(pair(delimited(space, alt((plusrepr, multrepr)), space), paren_expr))(rest)?;
+    match tail.first() {
+        // Sums of products have to be parenthesized, like other mixed operators
+        Some((op, _)) if tail.iter().all(|(sym, _)| sym == op) => Ok((after_tail, Expr::Assoc { op: *op, exprs: std::iter::once(first).chain(tail.into_iter().map(|(_, e)| e)).collect() })),
+        _ => Ok((rest, first)),
     }
-    let op = syms[0];
-    if !syms.iter().all(|x| x == &op) {
-        return custom_error(rest);
+}
+
+/// Parses a term or a union or intersection of terms (e.g., 'A ∪ B'), which may be a side of a comparison. Unions and
+/// intersections group to the left, and mixing them has to be parenthesized.
+fn set_term(input: &str) -> IResult<&str, Expr> {
+    let (rest, first) = arith_term(input)?;
+    let (after_tail, tail) = many0(pair(delimited(space, setoprepr, space), arith_term))(rest)?;
+    match tail.first() {
+        Some((op, _)) if tail.iter().all(|(sym, _)| sym == op) => Ok((after_tail, tail.into_iter().fold(first, |left, (op, right)| Expr::apply(Expr::var(op), &[left, right])))),
+        _ => Ok((rest, first)),
+    }
+}
+
+/// Parses a declared operator with at least the precedence `min_precedence`, preferring the longest symbol that matches
+fn declared_operator(input: &str, min_precedence: u32) -> IResult<&str, InfixOperator> {
+    let matched = OPERATORS.with(|operators| {
+        operators
+            .borrow()
+            .iter()
+            .filter(|op| op.precedence >= min_precedence && input.starts_with(&op.symbol))
+            // Operators spelled with letters can't run into the operand after them
+            .filter(|op| !op.symbol.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_') || !input[op.symbol.len()..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_'))
+            .max_by_key(|op| op.symbol.len())
+            .cloned()
+    });
+    match matched {
+        Some(op) => Ok((&input[op.symbol.len()..], op)),
+        None => Err(nom::Err::Error(nom::error::Error { input, code: nom::error::ErrorKind::Tag })),
     }
-    exprs.reverse();
-    Ok((rest, Expr::Assoc { op, exprs }))
 }
 
-// paren_expr is a factoring of expr that eliminates left-recursion, which parser combinators have trouble with
+/// Parses a term or an application of declared operators to terms (e.g., 'x R y'), by precedence climbing: the
+/// operators with at least the precedence `min_precedence` are applied, and the right operand of each is parsed with
+/// a higher minimum, so tighter operators are applied first and ones of equal precedence group to the left.
+fn declared_term(input: &str, min_precedence: u32) -> IResult<&str, Expr> {
+    let (mut rest, mut left) = set_term(input)?;
+    loop {
+        let (after_op, op) = match delimited(space, |i| declared_operator(i, min_precedence), space)(rest) {
+            Ok(parsed) => parsed,
+            Err(nom::Err::Error(_)) => return Ok((rest, left)),
+            Err(err) => return Err(err),
+        };
+        let (after_right, right) = match declared_term(after_op, op.precedence.saturating_add(1)) {
+            Ok(parsed) => parsed,
+            Err(nom::Err::Error(_)) => return Ok((rest, left)),
+            Err(err) => return Err(err),
+        };
+        left = Expr::apply(Expr::var(&op.symbol), &[left, right]);
+        rest = after_right;
+    }
+}
+
+/// Parses a comparison (e.g., 'x < y + 1' or 'x ∈ A ∪ B'), or a term that isn't compared. Terms bind tighter than the
+/// logical operators, so a comparison can be the operand of one without being parenthesized.
+fn comparison(input: &str) -> IResult<&str, Expr> {
+    let (rest, left) = declared_term(input, 0)?;
+    match pair(delimited(space, alt((comparisonrepr, untilrepr)), space), |i| declared_term(i, 0))(rest) {
+        Ok((rest, (relation, right))) => Ok((rest, Expr::apply(Expr::var(relation), &[left, right]))),
+        Err(nom::Err::Error(_)) => Ok((rest, left)),
+        Err(err) => Err(err),
+    }
+}
+
+/// Parses the rest of a sequence of associative terms after the first one, along with their operators. The terms
+/// are parsed in a loop rather than by recursion, so long chains like `A & B & ... & Z` don't use up the stack.
+fn assoc_tail(input: &str) -> IResult<&str, Vec<(Op, Expr)>> {
+    many0(pair(delimited(space, alt((andrepr, orrepr, biconrepr, equivrepr)), space), comparison))(input)
+}
+
+/// A level of nesting of the formula being parsed, counted for as long as it's alive
+struct Nesting;
+
+impl Nesting {
+    /// Enter a level of nesting, failing if that's deeper than the limit
+    fn enter(input: &str) -> Result<Nesting, nom::Err<nom::error::Error<&str>>> {
+        DEPTH.with(|depth| {
+            if depth.get() >= limits().max_depth {
+                // A failure, rather than an error, stops `alt` from trying the other alternatives
+                return Err(nom::Err::Failure(nom::error::Error { input, code: nom::error::ErrorKind::TooLarge }));
+            }
+            depth.set(depth.get() + 1);
+            Ok(Nesting)
+        })
+    }
+}
+
+impl Drop for Nesting {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+// paren_expr is a factoring of expr that eliminates left-recursion, which parser combinators have trouble with.
+// Every nested subformula is parsed through it, so it's where nesting is counted.
 fn paren_expr(input: &str) -> IResult<&str, Expr> {
-    alt((contradiction, tautology, predicate, notterm, binder, delimited(tuple((space, tag("("), space)), expr, tuple((space, tag(")"), space)))))(input)
+    let _nesting = Nesting::enter(input)?;
+    let (rest, term) = alt((contradiction, tautology, empty_set, set_builder, temporal_term, predicate, notterm, binder, delimited(tuple((space, tag("("), space)), expr, tuple((space, tag(")"), space)))))(input)?;
+    match logic_mode() {
+        LogicMode::FirstOrder | LogicMode::Temporal | LogicMode::Program => Ok((rest, term)),
+        // Any term can be applied in higher-order logic, and applications group to the left, like 'f(x)(y)'
+        LogicMode::HigherOrder => {
+            let (rest, applications) = many0(terminated(arguments, space))(rest)?;
+            Ok((rest, applications.into_iter().fold(term, |func, args| Expr::Apply { func: Box::new(func), args })))
+        }
+    }
 }
 
+/// Parses a term, which may be the left side of an implication or the first of a sequence of associative terms.
+/// The first term is parsed once and shared by the alternatives, since parsing it again for each one would parse
+/// nested subformulas exponentially many times.
 fn expr(input: &str) -> IResult<&str, Expr> {
-    alt((assoc_term, impl_term, paren_expr))(input)
+    let (rest, first) = comparison(input)?;
+    match impl_tail(rest) {
+        Ok((rest, right)) => return Ok((rest, Expr::Impl { left: Box::new(first), right: Box::new(right) })),
+        Err(nom::Err::Error(_)) => {}
+        Err(err) => return Err(err),
+    }
+    let (after_tail, tail) = assoc_tail(rest)?;
+    let op = match tail.first() {
+        Some((op, _)) => *op,
+        None => return Ok((rest, first)),
+    };
+    // Enforce that all symbols are the same.
+    // This check is what rules out `(a /\ b \/ c)` without further parenthesization.
+    if !tail.iter().all(|(sym, _)| *sym == op) {
+        return Ok((rest, first));
+    }
+    let exprs = std::iter::once(first).chain(tail.into_iter().map(|(_, e)| e)).collect();
+    Ok((after_tail, Expr::Assoc { op, exprs }))
+}
+
+/// Parses a word of a program (e.g., 'while'), which can't be the start of a longer name
+fn program_word<'a>(word: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    delimited(space, terminated(tag(word), not(variable_)), space)
+}
+
+/// Parses a statement of a program (e.g., 'x := x + 1' or 'while x < 10 do x := x + 1 end')
+fn statement(input: &str) -> IResult<&str, Expr> {
+    let _nesting = Nesting::enter(input)?;
+    let skip = value(Expr::skip(), program_word(SKIP));
+    let conditional = map(tuple((preceded(program_word(IF), expr), preceded(program_word("then"), program), delimited(program_word("else"), program, program_word("end")))), |(cond, then, otherwise)| Expr::if_then_else(cond, then, otherwise));
+    let while_loop = map(pair(preceded(program_word(WHILE), expr), delimited(program_word("do"), program, program_word("end"))), |(cond, body)| Expr::while_loop(cond, body));
+    // Numerals can't be assigned to
+    let target = verify(variable, |name: &str| Expr::var(name).as_num().is_none());
+    let assignment = map(pair(delimited(space, target, tuple((space, tag(":="), space))), |i| declared_term(i, 0)), |(name, value)| Expr::assign(&name, value));
+    alt((skip, conditional, while_loop, assignment, delimited(tuple((space, tag("("), space)), program, tuple((space, tag(")"), space)))))(input)
+}
+
+/// Parses a program, a sequence of statements separated by ';' (e.g., 'x := 1; y := x'). Sequences group to the right.
+fn program(input: &str) -> IResult<&str, Expr> {
+    let (rest, statements) = separated_list1(tag(";"), statement)(input)?;
+    Ok((rest, statements.into_iter().rev().reduce(|second, first| Expr::sequence(first, second)).expect("there's at least one statement")))
+}
+
+/// Parses a Hoare triple (e.g., '{x = 0} x := x + 1 {x = 1}'), in Hoare logic
+fn triple(input: &str) -> IResult<&str, Expr> {
+    if logic_mode() != LogicMode::Program {
+        return Err(nom::Err::Error(nom::error::Error { input, code: nom::error::ErrorKind::Tag }));
+    }
+    let condition = || delimited(pair(space, tag("{")), expr, pair(tag("}"), space));
+    map(tuple((condition(), program, condition())), |(pre, program, post)| Expr::triple(pre, program, post))(input)
 }
 
 fn main(input: &str) -> IResult<&str, Expr> {
-    terminated(expr, newline)(input)
+    terminated(alt((triple, expr)), newline)(input)
 }
 
 #[test]
@@ -250,3 +783,220 @@ fn test_parser() {
     }
     println!("{:?}", f("aa\n"));
 }
+
+#[test]
+fn test_subscripts_and_primes() {
+    assert_eq!(parse_unwrap("P_2(x_1, x')"), Expr::apply(Expr::var("P_2"), &[Expr::var("x_1"), Expr::var("x'")]));
+    assert_eq!(parse_unwrap("P₂(x₁, x′)"), parse_unwrap("P_2(x_1, x')"));
+    assert_eq!(parse_unwrap("n₁₀′′ = s(n_9)"), parse_unwrap("n_10'' = s(n_9)"));
+    for input in ["forall n_1 (P(n_1) -> P(n_1'))", "{x_0 : x_0 ∈ A_1}", "a_b_1 & _1 & x__2"] {
+        let e = parse_unwrap(input);
+        assert_eq!(parse_unwrap(&e.to_string()), e, "{e} doesn't parse back to itself");
+    }
+    assert_eq!(parse_unwrap("forall n_1 P(n_1')").to_string(), "(∀ n₁ P(n₁′))");
+}
+
+#[test]
+fn test_parse_with() {
+    let syntax = Syntax { logic_mode: LogicMode::Temporal, operators: vec![InfixOperator::new("R", 0).unwrap()] };
+    let (x, y) = (Expr::var("x"), Expr::var("y"));
+    assert_eq!(parse_with(&syntax, "G (x R y)"), Some(Expr::apply(Expr::var(ALWAYS), &[Expr::apply(Expr::var("R"), &[x, y])])));
+    // The syntax set on the thread is left alone, even if what's run with another one panics
+    assert_eq!(Syntax::current(), Syntax::default());
+    assert_eq!(parse("x R y"), None);
+    assert!(std::panic::catch_unwind(|| with_syntax(&syntax, || panic!("in another syntax"))).is_err());
+    assert_eq!(Syntax::current(), Syntax::default());
+}
+
+#[test]
+fn test_parse_limits() {
+    // Long chains are parsed in a loop, so only their length is limited
+    let chain = vec!["A"; 2000].join(" & ");
+    assert_eq!(try_parse(&chain).map(|e| matches!(e, Expr::Assoc { exprs, .. } if exprs.len() == 2000)), Ok(true));
+    let too_long = vec!["A"; DEFAULT_MAX_LEN].join(" & ");
+    assert_eq!(try_parse(&too_long), Err(ParseError::TooLong { len: 4 * DEFAULT_MAX_LEN - 3, max_len: DEFAULT_MAX_LEN }));
+
+    let nested = |depth: usize| format!("{}A{}", "(".repeat(depth), ")".repeat(depth));
+    assert!(try_parse(&nested(DEFAULT_MAX_DEPTH - 1)).is_ok());
+    assert_eq!(try_parse(&nested(DEFAULT_MAX_DEPTH)), Err(ParseError::TooDeep { max_depth: DEFAULT_MAX_DEPTH }));
+    assert!(try_parse(&format!("{}A", "~".repeat(100_000))).unwrap_err().is_too_large());
+    assert!(try_parse(&format!("{}A", "~".repeat(DEFAULT_MAX_LEN - 1))).unwrap_err().is_too_large());
+    assert_eq!(try_parse("A &"), Err(ParseError::Syntax));
+
+    set_limits(ParseLimits { max_len: 5, max_depth: 2 });
+    assert!(try_parse("~~A").unwrap_err().is_too_large());
+    assert_eq!(try_parse("~A"), Ok(parse_unwrap("~A")));
+    assert_eq!(try_parse("A & B & C"), Err(ParseError::TooLong { len: 9, max_len: 5 }));
+    set_limits(ParseLimits::default());
+    // Failing for depth leaves nothing behind for the next parse
+    assert_eq!(try_parse("~~A"), Ok(Expr::Not { operand: Box::new(Expr::Not { operand: Box::new(Expr::var("A")) }) }));
+}
+
+#[test]
+fn test_arithmetic() {
+    let (x, y) = (Expr::var("x"), Expr::var("y"));
+    assert_eq!(parse_unwrap("x + 1 < y"), Expr::less(Expr::assoc(Op::Add, &[x.clone(), Expr::num(1)]), y.clone()));
+    assert_eq!(parse_unwrap("2 × x ≤ x * 2"), Expr::less_eq(Expr::assoc(Op::Mult, &[Expr::num(2), x.clone()]), Expr::assoc(Op::Mult, &[x.clone(), Expr::num(2)])));
+    assert_eq!(parse_unwrap("x <= y"), parse_unwrap("x ≤ y"));
+    assert_eq!(parse_unwrap("forall x (0 < s(x))"), Expr::forall("x", Expr::less(Expr::num(0), Expr::apply(Expr::var("s"), &[Expr::var("x")]))));
+    // Comparisons bind tighter than logical operators
+    assert_eq!(parse_unwrap("x < y & y < x + x"), Expr::assoc(Op::And, &[Expr::less(x.clone(), y.clone()), Expr::less(y.clone(), Expr::assoc(Op::Add, &[x.clone(), x.clone()]))]));
+    assert_eq!(parse_unwrap("x < y -> P(x)"), Expr::implies(Expr::less(x.clone(), y.clone()), Expr::apply(Expr::var("P"), &[Expr::var("x")])));
+    assert_eq!(parse_unwrap("A <-> B"), Expr::assoc(Op::Bicon, &[Expr::var("A"), Expr::var("B")]));
+    // Mixed arithmetic operators have to be parenthesized, on either side of a comparison
+    assert_eq!(parse_unwrap("(x + y) * 2 < x * (y + 1)").to_string(), "(((x + y) × 2) < (x × (y + 1)))");
+    for input in ["x + y * 2 < x", "x < y + x * 2", "x < y < x"] {
+        assert_eq!(parse(input), None, "{input}");
+    }
+    let e = parse_unwrap("forall x exists y (x + 1 ≤ y × 2)");
+    assert_eq!(parse_unwrap(&e.to_string()), e);
+}
+
+#[test]
+fn test_sets() {
+    let (x, a, b) = (Expr::var("x"), Expr::var("A"), Expr::var("B"));
+    assert_eq!(parse_unwrap("x ∈ A ∪ B"), Expr::element(x.clone(), Expr::union(a.clone(), b.clone())));
+    assert_eq!(parse_unwrap("A ∩ B ⊆ A"), Expr::subset(Expr::intersection(a.clone(), b.clone()), a.clone()));
+    assert_eq!(parse_unwrap("A ∪ B ∪ ∅ = B ∪ A"), Expr::equals(Expr::union(Expr::union(a.clone(), b.clone()), Expr::var("∅")), Expr::union(b.clone(), a.clone())));
+    let builder = Expr::set_builder("x", Expr::apply(Expr::var("P"), &[Expr::var("x")]) | Expr::var("Q"));
+    assert_eq!(parse_unwrap("{x : P(x) | Q}"), builder);
+    assert_eq!(parse_unwrap("{x | P(x) | Q}"), builder);
+    assert_eq!(parse_unwrap("forall y (y ∈ A -> y ∈ {x : x ∈ A})"), Expr::forall("y", Expr::implies(Expr::element(Expr::var("y"), a.clone()), Expr::element(Expr::var("y"), Expr::set_builder("x", Expr::element(x.clone(), a.clone()))))));
+    // `=` isn't confused with `===`
+    assert_eq!(parse_unwrap("A === B"), Expr::assoc(Op::Equiv, &[a.clone(), b.clone()]));
+    for input in ["A ∪ B ∩ A", "x ∈ A ∈ B", "{x P(x)}", "{P(x) : x}"] {
+        assert_eq!(parse(input), None, "{input}");
+    }
+    for input in ["(A ∪ B) ∩ A = ∅", "x ∈ {y : y ⊆ A & ~(y = ∅)}", "forall x (x ∈ A ∩ B -> x ∈ A)"] {
+        let e = parse_unwrap(input);
+        assert_eq!(parse_unwrap(&e.to_string()), e, "{input}");
+    }
+}
+
+#[test]
+fn test_declared_operators() {
+    let (x, y, z) = (Expr::var("x"), Expr::var("y"), Expr::var("z"));
+    let r = |left: Expr, right: Expr| Expr::apply(Expr::var("R"), &[left, right]);
+    let oplus = |left: Expr, right: Expr| Expr::apply(Expr::var("⊕"), &[left, right]);
+    assert_eq!(parse("x R y"), None);
+    assert_eq!(parse("x ⊕ y"), None);
+
+    with_syntax(&Syntax { operators: vec![InfixOperator::new("R", 1).unwrap(), InfixOperator::new("⊕", 2).unwrap()], ..Syntax::default() }, || {
+        assert_eq!(parse_unwrap("x R y"), r(x.clone(), y.clone()));
+        // Higher precedence binds tighter, and equal precedence groups to the left
+        assert_eq!(parse_unwrap("x ⊕ y R z"), r(oplus(x.clone(), y.clone()), z.clone()));
+        assert_eq!(parse_unwrap("x R y ⊕ z"), r(x.clone(), oplus(y.clone(), z.clone())));
+        assert_eq!(parse_unwrap("x ⊕ y ⊕ z"), oplus(oplus(x.clone(), y.clone()), z.clone()));
+        // Declared operators bind looser than sums, and tighter than comparisons and logical operators
+        assert_eq!(parse_unwrap("x + 1 ⊕ y = z"), Expr::equals(oplus(Expr::assoc(Op::Add, &[x.clone(), Expr::num(1)]), y.clone()), z.clone()));
+        assert_eq!(parse_unwrap("(x R y & y R x) -> x = y"), Expr::implies(Expr::assoc(Op::And, &[r(x.clone(), y.clone()), r(y.clone(), x.clone())]), Expr::equals(x.clone(), y.clone())));
+        // Letter operators have to be set apart from their operands, and other names are still variables
+        assert_eq!(parse_unwrap("Rain R xRy"), r(Expr::var("Rain"), Expr::var("xRy")));
+        assert_eq!(parse_unwrap("forall x (x R x)").to_string(), "(∀ x (x R x))");
+        for input in ["x R y ⊕ z", "forall x exists y (x ⊕ y R y & R(x, y, z))"] {
+            let e = parse_unwrap(input);
+            assert_eq!(parse_unwrap(&e.to_string()), e, "{input}");
+        }
+    });
+    assert_eq!(r(x.clone(), y.clone()).to_string(), "R(x, y)");
+
+    for symbol in ["", "->", "∈", "a b", "(", "R⊕", "forall"] {
+        assert!(InfixOperator::new(symbol, 0).is_err(), "{symbol:?}");
+    }
+}
+
+#[test]
+fn test_higher_order() {
+    let (x, a) = (Expr::var("x"), Expr::var("a"));
+    let p = |arg: Expr| Expr::apply(Expr::var("P"), &[arg]);
+    assert_eq!(parse("(λx P(x))(a)"), None);
+    assert_eq!(parse_unwrap("lambda"), Expr::var("lambda"));
+
+    with_syntax(&Syntax { logic_mode: LogicMode::HigherOrder, ..Syntax::default() }, || {
+        assert_eq!(parse_unwrap("λx P(x)"), Expr::lambda("x", p(x.clone())));
+        assert_eq!(parse_unwrap("lambda x (P(x) & Q)"), Expr::lambda("x", Expr::assoc(Op::And, &[p(x.clone()), Expr::var("Q")])));
+        assert_eq!(parse_unwrap("(λx P(x))(a)"), Expr::apply(Expr::lambda("x", p(x.clone())), &[Expr::var("a")]));
+        assert_eq!(parse_unwrap("f(x)(a) -> P(a)"), Expr::implies(Expr::apply(Expr::apply(Expr::var("f"), &[Expr::var("x")]), &[Expr::var("a")]), p(a.clone())));
+        assert_eq!(parse_unwrap("forall P (P(a) | ~P(a))"), Expr::forall("P", p(a.clone()) | !p(a.clone())));
+        for input in ["(λx λy R(y, x))(a)(b)", "forall F exists G ((λx F(x))(a) <-> G(a))", "{x : (λy P(y))(x)}"] {
+            let e = parse_unwrap(input);
+            assert_eq!(parse_unwrap(&e.to_string()), e, "{input}");
+        }
+        assert_eq!(parse("lambda"), None);
+    });
+}
+
+#[test]
+fn test_temporal() {
+    let (p, q) = (Expr::var("p"), Expr::var("q"));
+    assert_eq!(parse_unwrap("G(p)"), Expr::apply(Expr::var("G"), std::slice::from_ref(&p)));
+    assert_eq!(parse("p U q"), None);
+
+    with_syntax(&Syntax { logic_mode: LogicMode::Temporal, ..Syntax::default() }, || {
+        assert_eq!(parse_unwrap("G(p)"), Expr::always(p.clone()));
+        assert_eq!(parse_unwrap("G (p -> F q)"), Expr::always(Expr::implies(p.clone(), Expr::eventually(q.clone()))));
+        assert_eq!(parse_unwrap("~X p & p U q"), Expr::assoc(Op::And, &[!Expr::next(p.clone()), Expr::until(p.clone(), q.clone())]));
+        assert_eq!(parse_unwrap("G ~p"), Expr::always(!p.clone()));
+        // Longer names that start with the letters are still names
+        assert_eq!(parse_unwrap("Fx U Go"), Expr::until(Expr::var("Fx"), Expr::var("Go")));
+        for input in ["G", "p U", "F(p, q)", "p U q U p"] {
+            assert_eq!(parse(input), None, "{input}");
+        }
+        for input in ["G (p -> F q)", "~X p & (p U q)", "F G p <-> ~G F ~p", "G (p U X q)"] {
+            let e = parse_unwrap(input);
+            assert_eq!(parse_unwrap(&e.to_string()), e, "{input}");
+        }
+        assert_eq!(Expr::always(Expr::until(p.clone(), q.clone())).to_string(), "G (p U q)");
+    });
+    assert_eq!(Expr::always(Expr::until(p, q)).to_string(), "G(U(p, q))");
+}
+
+#[test]
+fn test_hoare() {
+    let (x, y) = (Expr::var("x"), Expr::var("y"));
+    assert_eq!(parse("{x = 0} x := 1 {x = 1}"), None);
+    // ';' starts a comment outside of Hoare logic
+    assert_eq!(parse_unwrap("x = 0; y = 0"), Expr::equals(x.clone(), Expr::num(0)));
+
+    with_syntax(&Syntax { logic_mode: LogicMode::Program, ..Syntax::default() }, || {
+        assert_eq!(parse_unwrap("{x = 0} x := x + 1 {x = 1}"), Expr::triple(Expr::equals(x.clone(), Expr::num(0)), Expr::assign("x", Expr::assoc(Op::Add, &[x.clone(), Expr::num(1)])), Expr::equals(x.clone(), Expr::num(1))));
+        assert_eq!(parse_unwrap("{⊤} x := 1; skip; y := x {y = 1}").as_triple().map(|(_, program, _)| program.clone()), Some(Expr::sequence(Expr::assign("x", Expr::num(1)), Expr::sequence(Expr::skip(), Expr::assign("y", x.clone())))));
+        assert_eq!(parse_unwrap("{P(x)} while x < 10 do x := x + 1 end {P(x) & ~(x < 10)}").as_triple().and_then(|(_, program, _)| program.as_statement()), Some(crate::expr::Statement::While(&Expr::less(x.clone(), Expr::num(10)), &Expr::assign("x", Expr::assoc(Op::Add, &[x.clone(), Expr::num(1)])))));
+        // Formulas are still lines on their own, and the words of programs aren't names
+        assert_eq!(parse_unwrap("x < y -> y = x + 1"), Expr::implies(Expr::less(x.clone(), y.clone()), Expr::equals(y, Expr::assoc(Op::Add, &[x, Expr::num(1)]))));
+        for input in ["{x = 0} x := 1", "{⊤} x := 1; {⊤}", "{⊤} if x < 1 then skip end {⊤}", "{⊤} while ⊤ do skip {⊤}", "{⊤} 1 := x {⊤}", "P(skip)", "{⊤} skip {⊤} & P"] {
+            assert_eq!(parse(input), None, "{input}");
+        }
+        for input in ["{x = 0} x := x + 1 {x = 1}", "{⊤} x := 1; (y := 2; skip) {⊤}", "{⊤} (x := 1; y := 2); skip {⊤}", "{n = 0} while n < 3 do if n = 1 then skip else n := n + 1 end end {n = 3}"] {
+            let e = parse_unwrap(input);
+            assert_eq!(parse_unwrap(&e.to_string()), e, "{input}");
+        }
+        assert_eq!(parse_unwrap("{⊤} while ⊤ do x := x × 2 end {⊥}").to_string(), "{⊤} while ⊤ do x := (x × 2) end {⊥}");
+    });
+    assert_eq!(Expr::triple(Expr::Taut, Expr::skip(), Expr::Taut).to_string(), "hoare(⊤, skip, ⊤)");
+}
+
+#[test]
+fn test_reformat() {
+    assert_eq!(reformat("forall x (P(x) -> Q(x))").as_deref(), Some("(∀ x (P(x) → Q(x)))"));
+    assert_eq!(reformat("(∀ x (P(x) → Q(x)))").as_deref(), Some("(∀ x (P(x) → Q(x)))"));
+    assert_eq!(reformat("A | B | C").as_deref(), Some("(A ∨ B ∨ C)"));
+    assert_eq!(reformat("").as_deref(), None);
+}
+
+#[test]
+fn test_is_fully_parenthesized() {
+    assert!(is_fully_parenthesized("P(x, y)"));
+    assert!(is_fully_parenthesized("(∀ x (P(x) → Q(x)))"));
+    assert!(is_fully_parenthesized("((A ∧ B) ∨ C) ; a comment, which isn't (parenthesized"));
+    assert!(is_fully_parenthesized("(((x + 1) < y) ∧ (x ∈ (A ∪ B)))"));
+    assert!(!is_fully_parenthesized("((x + 1 < y) ∧ (x ∈ (A ∪ B)))"));
+    assert!(!is_fully_parenthesized("(∀ x P(x) → Q)"));
+    assert!(!is_fully_parenthesized("((A)) ∧ B"));
+    assert!(!is_fully_parenthesized("(A ∧"));
+    for input in ["A -> (B -> C)", "(A & B) | C", "forall x (P(x) -> exists y R(x, y))"] {
+        let full = parse_unwrap(input).to_string();
+        assert!(!is_fully_parenthesized(input));
+        assert!(is_fully_parenthesized(&full));
+    }
+}
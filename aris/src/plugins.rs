@@ -0,0 +1,308 @@
+/*!
+# Rule plugins
+
+Departments teaching with their own rules can add them without forking Aris.
+A rule pack registers each of its rules at startup with `register()`, and the
+rules then behave like built-in ones: they're listed under their
+classifications in `RuleClassification::rules()`, which the GUI's menus are
+built from, they're saved in proof files by their serialized names, and steps
+using them are checked with the rule's `check` function.
+
+Native programs implement `RulePlugin` for their rules. Plugins written in other
+languages, like the web app's WebAssembly rule packs, describe their rules in
+JSON, which `RuleDescription` reads, and check steps with formulas written out
+as text.
+
+```
+use aris::expr::Expr;
+use aris::plugins::{self, PluginSubproof, RulePlugin};
+use aris::rules::{RuleClassification, RuleM, RuleT};
+use std::collections::HashSet;
+
+struct SelfImplication;
+
+impl RulePlugin for SelfImplication {
+    fn name(&self) -> String {
+        "Self Implication".into()
+    }
+    fn serialized_name(&self) -> String {
+        "EXAMPLE_SELF_IMPLICATION".into()
+    }
+    fn classifications(&self) -> HashSet<RuleClassification> {
+        [RuleClassification::Special].into_iter().collect()
+    }
+    fn num_deps(&self) -> Option<usize> {
+        Some(0)
+    }
+    fn num_subdeps(&self) -> Option<usize> {
+        Some(0)
+    }
+    fn check(&self, conclusion: &Expr, _: &[Expr], _: &[PluginSubproof]) -> Result<(), String> {
+        match conclusion {
+            Expr::Impl { left, right } if left == right => Ok(()),
+            _ => Err("The conclusion should be a formula implying itself".into()),
+        }
+    }
+}
+
+let rule = plugins::register(Box::new(SelfImplication)).unwrap();
+assert_eq!(rule.get_name(), "Self Implication");
+assert_eq!(RuleM::from_serialized_name("EXAMPLE_SELF_IMPLICATION"), Some(rule));
+assert!(RuleClassification::Special.rules().any(|special| special == rule));
+```
+*/
+
+use crate::expr::Expr;
+use crate::parser;
+use crate::rules::PluginRule;
+use crate::rules::Rule;
+use crate::rules::RuleClassification;
+use crate::rules::RuleM;
+use crate::rules::RuleSchema;
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+
+/// A subproof cited by a step using a plugin rule
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PluginSubproof {
+    pub premises: Vec<Expr>,
+    /// Formulas of the subproof's steps, not including nested subproofs
+    pub lines: Vec<Expr>,
+}
+
+/// A rule from outside Aris. The metadata is the same as `RuleT`'s, but
+/// checking only gets the formulas involved, rather than the whole proof.
+pub trait RulePlugin: Send + Sync {
+    /// Name of the rule for display in the GUI
+    fn name(&self) -> String;
+    /// Name of the rule in proof files, which has to differ from every other
+    /// rule's, like `"MYCOURSE_TRANSITIVITY"`
+    fn serialized_name(&self) -> String;
+    /// One-sentence explanation of the rule for the GUI's rule reference
+    fn description(&self) -> String {
+        String::new()
+    }
+    /// Typical use of the rule, for the rule reference
+    fn schema(&self) -> Option<RuleSchema> {
+        None
+    }
+    /// Menus to list the rule in
+    fn classifications(&self) -> HashSet<RuleClassification>;
+    /// Number of lines the rule cites, or `None` if it varies
+    fn num_deps(&self) -> Option<usize> {
+        None
+    }
+    /// Number of subproofs the rule cites, or `None` if it varies
+    fn num_subdeps(&self) -> Option<usize> {
+        None
+    }
+    /// Check that `conclusion` follows by the rule from the cited lines and
+    /// subproofs, returning an error message to show if it doesn't
+    fn check(&self, conclusion: &Expr, deps: &[Expr], subproofs: &[PluginSubproof]) -> Result<(), String>;
+}
+
+/// A registered plugin rule, with its serialized name kept for as long as the
+/// program runs, like the built-in rules' names
+struct Registered {
+    plugin: Arc<dyn RulePlugin>,
+    serialized_name: &'static str,
+}
+
+lazy_static! {
+    static ref PLUGINS: RwLock<Vec<Registered>> = RwLock::new(vec![]);
+}
+
+/// Register a plugin rule, returning the `Rule` that uses it. This fails if
+/// another rule has the same serialized name. Rules can't be unregistered,
+/// since proofs may still use them.
+pub fn register(plugin: Box<dyn RulePlugin>) -> Result<Rule, String> {
+    let serialized_name = plugin.serialized_name();
+    let mut plugins = PLUGINS.write().unwrap_or_else(PoisonError::into_inner);
+    if RuleM::ALL_SERIALIZED_NAMES.iter().any(|name| *name == serialized_name) || plugins.iter().any(|registered| registered.serialized_name == serialized_name) {
+        return Err(format!("A rule named {serialized_name} already exists"));
+    }
+    let serialized_name = Box::leak(serialized_name.into_boxed_str());
+    plugins.push(Registered { plugin: Arc::from(plugin), serialized_name });
+    Ok(PluginRule(plugins.len() - 1).into())
+}
+
+/// Every registered plugin rule, in the order they were registered
+pub fn rules() -> Vec<Rule> {
+    let plugins = PLUGINS.read().unwrap_or_else(PoisonError::into_inner);
+    (0..plugins.len()).map(|i| PluginRule(i).into()).collect()
+}
+
+/// The plugin of a plugin rule. Plugin rules are only made by `register()`,
+/// so the plugin is always there.
+pub(crate) fn plugin(rule: PluginRule) -> Arc<dyn RulePlugin> {
+    let plugins = PLUGINS.read().unwrap_or_else(PoisonError::into_inner);
+    plugins[rule.0].plugin.clone()
+}
+
+/// Serialized name of a plugin rule
+pub(crate) fn serialized_name(rule: PluginRule) -> &'static str {
+    let plugins = PLUGINS.read().unwrap_or_else(PoisonError::into_inner);
+    plugins[rule.0].serialized_name
+}
+
+/// Plugin rule with a serialized name, if one is registered
+pub(crate) fn from_serialized_name(name: &str) -> Option<Rule> {
+    let plugins = PLUGINS.read().unwrap_or_else(PoisonError::into_inner);
+    plugins.iter().position(|registered| registered.serialized_name == name).map(|i| PluginRule(i).into())
+}
+
+/// Typical use of a rule, with its formulas written out as text
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SchemaDescription {
+    pub deps: Vec<String>,
+    /// The premise of each subproof, if it has one, and the line of it that
+    /// the rule uses
+    pub subdeps: Vec<(Option<String>, String)>,
+    pub conclusion: String,
+}
+
+impl SchemaDescription {
+    /// Parse the schema's formulas
+    pub fn parse(&self) -> Result<RuleSchema, String> {
+        let parse = |text: &str| parser::parse(text).ok_or_else(|| format!("Invalid formula in rule schema: {text}"));
+        let deps = self.deps.iter().map(|dep| parse(dep)).collect::<Result<_, _>>()?;
+        let subdeps = self.subdeps.iter().map(|(premise, line)| Ok((premise.as_deref().map(parse).transpose()?, parse(line)?))).collect::<Result<_, String>>()?;
+        Ok(RuleSchema { deps, subdeps, conclusion: parse(&self.conclusion)? })
+    }
+}
+
+/// Metadata of a plugin rule, as plugins written in other languages describe
+/// their rules:
+///
+/// ```json
+/// {
+///     "name": "Transitivity",
+///     "serialized_name": "MYCOURSE_TRANSITIVITY",
+///     "description": "From P → Q and Q → R, conclude P → R.",
+///     "classifications": ["Conditional Inference"],
+///     "schema": { "deps": ["P -> Q", "Q -> R"], "conclusion": "P -> R" },
+///     "num_deps": 2,
+///     "num_subdeps": 0
+/// }
+/// ```
+///
+/// Classifications are named as the GUI's menus show them. Only `name`,
+/// `serialized_name`, and `classifications` are required.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct RuleDescription {
+    pub name: String,
+    pub serialized_name: String,
+    #[serde(default)]
+    pub description: String,
+    pub classifications: Vec<String>,
+    #[serde(default)]
+    pub schema: Option<SchemaDescription>,
+    #[serde(default)]
+    pub num_deps: Option<usize>,
+    #[serde(default)]
+    pub num_subdeps: Option<usize>,
+}
+
+impl RuleDescription {
+    /// Read a list of rule descriptions from JSON, checking that their
+    /// classifications and schemas are valid
+    pub fn list_from_json(json: &str) -> Result<Vec<RuleDescription>, String> {
+        let descriptions = serde_json::from_str::<Vec<RuleDescription>>(json).map_err(|err| format!("Invalid rule descriptions: {err}"))?;
+        for description in &descriptions {
+            description.parse_classifications()?;
+            description.schema.as_ref().map(SchemaDescription::parse).transpose()?;
+        }
+        Ok(descriptions)
+    }
+
+    /// The classifications, by their names in the GUI's menus
+    pub fn parse_classifications(&self) -> Result<HashSet<RuleClassification>, String> {
+        let parse = |name: &String| RuleClassification::iter().find(|classification| classification.to_string() == *name).ok_or_else(|| format!("Rule {} has unknown classification {:?}", self.name, name));
+        self.classifications.iter().map(parse).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+    use crate::proofs::pooledproof::PooledProof;
+    use crate::proofs::xml_interop;
+    use crate::proofs::Justification;
+    use crate::proofs::Proof;
+    use crate::rules::RuleT;
+
+    use frunk_core::coproduct::Coproduct;
+    use frunk_core::HList;
+
+    /// From any lines, conclude one of them; registered under different names
+    /// by different tests, since the registry is shared
+    struct Pick(&'static str);
+
+    impl RulePlugin for Pick {
+        fn name(&self) -> String {
+            "Pick".into()
+        }
+        fn serialized_name(&self) -> String {
+            self.0.into()
+        }
+        fn classifications(&self) -> HashSet<RuleClassification> {
+            [RuleClassification::Special].into_iter().collect()
+        }
+        fn num_subdeps(&self) -> Option<usize> {
+            Some(0)
+        }
+        fn check(&self, conclusion: &Expr, deps: &[Expr], _: &[PluginSubproof]) -> Result<(), String> {
+            if deps.contains(conclusion) {
+                Ok(())
+            } else {
+                Err(format!("{conclusion} isn't cited"))
+            }
+        }
+    }
+
+    #[test]
+    fn test_register() {
+        let rule = register(Box::new(Pick("TEST_PICK"))).unwrap();
+        assert_eq!(RuleM::from_serialized_name("TEST_PICK"), Some(rule));
+        assert_eq!(RuleM::to_serialized_name(rule), "TEST_PICK");
+        assert!(rules().contains(&rule));
+        assert!(RuleClassification::Special.rules().any(|special| special == rule));
+        assert!(register(Box::new(Pick("TEST_PICK"))).is_err());
+        assert!(register(Box::new(Pick("REITERATION"))).is_err());
+    }
+
+    #[test]
+    fn test_check_and_serialize() {
+        let rule = register(Box::new(Pick("TEST_PICK_CHECK"))).unwrap();
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let a = prf.add_premise(p("A"));
+        let good = prf.add_step(Justification(p("A"), rule, vec![Coproduct::inject(a)], vec![]));
+        let bad = prf.add_step(Justification(p("B"), rule, vec![Coproduct::inject(a)], vec![]));
+        assert_eq!(prf.verify_line(&Coproduct::inject(good)), Ok(()));
+        assert!(prf.verify_line(&Coproduct::inject(bad)).is_err());
+
+        let mut data = vec![];
+        let meta = xml_interop::ProofMetaData::default();
+        xml_interop::xml_from_proof_and_metadata(&prf, &meta, &mut data).unwrap();
+        let (loaded, _) = xml_interop::proof_from_xml::<PooledProof<HList![Expr]>, _>(&data[..]).unwrap();
+        let rules = loaded.direct_lines().into_iter().filter_map(|r| loaded.lookup_step(&r)).map(|Justification(_, rule, _, _)| rule.get_name()).collect::<Vec<_>>();
+        assert_eq!(rules, vec!["Pick", "Pick"]);
+    }
+
+    #[test]
+    fn test_rule_description() {
+        let json = r#"[{ "name": "Transitivity", "serialized_name": "T", "classifications": ["Conditional Inference"], "schema": { "deps": ["P -> Q", "Q -> R"], "conclusion": "P -> R" } }]"#;
+        let descriptions = RuleDescription::list_from_json(json).unwrap();
+        assert_eq!(descriptions[0].parse_classifications(), Ok([RuleClassification::ConditionalInference].into_iter().collect()));
+        assert_eq!(descriptions[0].schema.as_ref().unwrap().parse().unwrap().to_string(), "(P → Q), (Q → R) ⊢ (P → R)");
+        assert!(RuleDescription::list_from_json(r#"[{ "name": "X", "serialized_name": "X", "classifications": ["Nonsense"] }]"#).is_err());
+    }
+}
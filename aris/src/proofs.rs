@@ -128,14 +128,37 @@ fn should_fail_with_lifetime_error() {
 ```
 
 This is a similar trick to the rank-2 type of `runST` in Haskell used to prevent the phantom state from escaping.
+
+# Shared lines and subproofs
+Proofs are DAGs rather than trees: a step can cite a line or subproof that's out of its scope, like a line
+in an earlier sibling subproof, as long as it comes before the step and only rests on assumptions the step
+could cite itself (see `Proof::can_share_dep`). That's as sound as deriving it again, so derivations needed in
+several places, as in machine-generated proofs, only have to be written once.
+
+# Assumptions of subproofs
+Courses differ on how many assumptions a subproof may open with. By default it's exactly one, but with
+`AssumptionPolicy::Multiple` a subproof may have several, which rules like →I and ¬I discharge together as their
+conjunction. The policy is set for the current thread while a closure runs, with `with_assumption_policy`.
+
+# Scoping
+By default a step can cite the lines of every subproof it's in, at any depth. Courses that teach strict scoping
+instead only let a step cite lines of its own subproof, so lines from outside it have to be brought in with
+Reiteration first. That's `ScopingPolicy::Strict`, set for the current thread with `with_scoping_policy`. A step that
+would be correct if the lines it cites from outside its subproof were reiterated fails with
+`ProofCheckError::NeedsReiteration`, naming those lines, so GUIs can offer to insert the Reiteration steps.
 */
 
 use crate::expr::Expr;
 use crate::rules::ProofCheckError;
 use crate::rules::Rule;
+use crate::rules::RuleM;
 
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::hash::Hash;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 
 use frunk_core::coproduct::Coproduct;
 use frunk_core::hlist;
@@ -168,12 +191,55 @@ pub mod pooledproof;
 /// - Doesn't handle binding structure, so can't be used for first order logic, only Propositional logic
 pub mod java_shallow_proof;
 
+/// persistent_proof stores the same pools as pooledproof, but in persistent (structurally shared) collections
+/// # Tradeoffs
+/// ## Pros
+/// - cloning is O(1), so snapshots for undo/redo and history don't deep copy the proof
+/// - no raw pointers, so subproof views can't dangle
+/// ## Cons
+/// - lookups and edits are O(log n) instead of approximately O(1)
+/// - inserting relative to a line is O(n) to find the line
+pub mod persistent_proof;
+
 /// A LinedProof is a wrapper around another proof type that adds lines and strings, for interfacing with the GUI
 pub mod lined_proof;
 
 /// xml_interop contains functions for loading a proof from an xml reader
 pub mod xml_interop;
 
+/// line_info numbers the lines of a proof and finds the ranges of lines its subproofs span, for citing them
+pub mod line_info;
+
+/// diff compares two proofs line by line, for showing them side by side
+pub mod diff;
+
+/// line_checks checks a whole proof a line at a time, reporting each line's result as it's checked
+pub mod line_checks;
+
+/// clipboard copies and pastes lines of proofs in a JSON format that other copies of Aris and the command line tools can read
+pub mod clipboard;
+
+/// lints points out lines that are correct but worth a second look, as warnings that don't make a proof incorrect
+pub mod lints;
+
+/// skeleton blanks parts of a solution's steps, making fill-in-the-blank handouts with answer keys
+pub mod skeleton;
+
+/// timings records when the lines of a proof were written and became correct, and summarizes how long they took
+pub mod timings;
+
+/// similarity scores how alike two proofs are, for spotting submissions copied from each other
+pub mod similarity;
+
+/// report saves the results of checking every line of a proof as JSON, for showing grades without checking again
+pub mod report;
+
+/// hints looks for an equivalence that would make a wrong step right if it were applied first, for adding to its error
+pub mod hints;
+
+/// constants lists the constants of a proof with where each is introduced and the subproof an ∃ Elimination or ∀ Introduction scopes it to
+pub mod constants;
+
 /// DisplayIndented gives a convention for passing around state to pretty printers
 /// it is intended that objects that implement this implement display as:
 /// `fn fmt(&self, fmt: &mut Formatter) -> std::result::Result<(), std::fmt::Error> { self.display_indented(fmt, 1, &mut 1) }`
@@ -196,6 +262,104 @@ pub fn pj_to_pjs<P: Proof>(pj: PjRef<P>) -> PjsRef<P> {
     pj.fold(hlist![|x| Coproduct::inject(x), |x| Coproduct::inject(x)])
 }
 
+/// How many assumptions a subproof may have
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AssumptionPolicy {
+    /// Exactly one, which is discharged as it is
+    #[default]
+    Single,
+    /// One or more, which are discharged as their conjunction, in order
+    Multiple,
+}
+
+thread_local! {
+    static ASSUMPTION_POLICY: Cell<AssumptionPolicy> = const { Cell::new(AssumptionPolicy::Single) };
+}
+
+/// Set how many assumptions subproofs may have on the current thread, for editing and checking proofs
+fn set_assumption_policy(policy: AssumptionPolicy) {
+    ASSUMPTION_POLICY.with(|current| current.set(policy));
+}
+
+/// How many assumptions subproofs may have on the current thread
+pub fn assumption_policy() -> AssumptionPolicy {
+    ASSUMPTION_POLICY.with(Cell::get)
+}
+
+/// Run `f` with subproofs allowed the assumptions `policy` allows on the current thread, and restore the policy set
+/// before afterwards, even if `f` panics
+pub fn with_assumption_policy<T>(policy: AssumptionPolicy, f: impl FnOnce() -> T) -> T {
+    struct Restore(AssumptionPolicy);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_assumption_policy(self.0);
+        }
+    }
+    let _restore = Restore(assumption_policy());
+    set_assumption_policy(policy);
+    f()
+}
+
+/// Which lines a step may cite without reiterating them first
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ScopingPolicy {
+    /// Lines of the step's own subproof and of every subproof it's in
+    #[default]
+    Enclosing,
+    /// Only lines of the step's own subproof, so lines from outside it have to be reiterated into it first
+    Strict,
+}
+
+thread_local! {
+    static SCOPING_POLICY: Cell<ScopingPolicy> = const { Cell::new(ScopingPolicy::Enclosing) };
+}
+
+/// Set which lines steps may cite without reiterating them on the current thread, for checking proofs
+fn set_scoping_policy(policy: ScopingPolicy) {
+    SCOPING_POLICY.with(|current| current.set(policy));
+}
+
+/// Which lines steps may cite without reiterating them on the current thread
+pub fn scoping_policy() -> ScopingPolicy {
+    SCOPING_POLICY.with(Cell::get)
+}
+
+/// Run `f` with steps citing the lines `policy` allows on the current thread, and restore the policy set before
+/// afterwards, even if `f` panics
+pub fn with_scoping_policy<T>(policy: ScopingPolicy, f: impl FnOnce() -> T) -> T {
+    struct Restore(ScopingPolicy);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_scoping_policy(self.0);
+        }
+    }
+    let _restore = Restore(scoping_policy());
+    set_scoping_policy(policy);
+    f()
+}
+
+/// Identifies a state of a proof for `Proof::revision`. Every edit takes a new
+/// one from a global counter, so two proofs only share one if they're copies of
+/// the same state. It's left out of comparisons, since proofs can get to the
+/// same lines by different edits.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Revision(pub(crate) u64);
+
+impl Revision {
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Revision(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl PartialEq for Revision {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Revision {}
+
 /// aris::proofs::Proof is the core trait for working with proofs.
 pub trait Proof: Sized {
     type PremiseReference: Clone + Eq + Ord + Hash;
@@ -285,6 +449,10 @@ pub trait Proof: Sized {
     /// Verifies a specific line in the proof to check its validity.
     fn verify_line(&self, r: &PjRef<Self>) -> Result<(), ProofCheckError<PjRef<Self>, Self::SubproofReference>>;
 
+    /// A number that changes whenever the proof is edited, including through
+    /// its subproofs, so what's computed from the proof can be kept until then
+    fn revision(&self) -> u64;
+
     /// Retrieves the expression for a given proof reference, or `None` if not found.
     fn lookup_expr(&self, r: &PjRef<Self>) -> Option<Expr> {
         r.clone().fold(hlist![|pr| self.lookup_premise(&pr), |jr| self.lookup_step(&jr).map(|x| x.0)])
@@ -367,6 +535,23 @@ pub trait Proof: Sized {
         result
     }
 
+    /// The lines a proof concludes with: its top-level steps proving any of `goals`, or its last top-level step if
+    /// there are no goals
+    fn concluding_lines(&self, goals: &[Expr]) -> Vec<PjRef<Self>> {
+        let steps = self.direct_lines();
+        if goals.is_empty() {
+            return steps.last().cloned().map(Coproduct::inject).into_iter().collect();
+        }
+        steps.into_iter().filter(|jr| self.lookup_step(jr).is_some_and(|just| goals.contains(&just.0))).map(Coproduct::inject).collect()
+    }
+
+    /// The top-level premises that none of `lines` depend on, in order. A line that cites a subproof depends on
+    /// everything the subproof's lines do, since a rule may use any of them.
+    fn unused_premises(&self, lines: &[PjRef<Self>]) -> Vec<Self::PremiseReference> {
+        let used = lines.iter().flat_map(|line| self.transitive_dependencies(line.clone())).collect::<HashSet<_>>();
+        self.premises().into_iter().filter(|pr| !used.contains(&Coproduct::inject(pr.clone()))).collect()
+    }
+
     /// Determines the depth of a specific line in the proof hierarchy.
     /// Returns the number of subproof levels enclosing the line.
     fn depth_of_line(&self, r: &PjsRef<Self>) -> usize {
@@ -379,6 +564,23 @@ pub trait Proof: Sized {
         result
     }
 
+    /// Whether a premise is the assumption of a subproof, which rules like →I discharge, rather than a premise of the
+    /// whole proof, which is never discharged
+    fn is_assumption(&self, r: &Self::PremiseReference) -> bool {
+        self.parent_of_line(&Coproduct::inject(r.clone())).is_some()
+    }
+
+    /// The assumption of a subproof, if it has one, or its first one if it has several
+    fn assumption_of(&self, r: &Self::SubproofReference) -> Option<Self::PremiseReference> {
+        self.lookup_subproof(r)?.premises().into_iter().next()
+    }
+
+    /// Whether another assumption may be added to a subproof: always with `AssumptionPolicy::Multiple`, and only if
+    /// it has none yet with `AssumptionPolicy::Single`
+    fn may_add_assumption(&self, r: &Self::SubproofReference) -> bool {
+        self.lookup_subproof(r).is_some_and(|sub| assumption_policy() == AssumptionPolicy::Multiple || sub.premises().is_empty())
+    }
+
     /// Computes all possible dependencies that a given line can reference.
     /// Updates the provided sets of valid dependencies and subproof references.
     fn possible_deps_for_line(&self, r: &PjRef<Self>, deps: &mut HashSet<PjRef<Self>>, sdeps: &mut HashSet<Self::SubproofReference>) {
@@ -447,6 +649,126 @@ pub trait Proof: Sized {
             Inr(Inr(void)) => match *void {},
         }
     }
+
+    /// The lines among `deps` that the step `r` cites from outside its own subproof, which have to be reiterated
+    /// into it first with `ScopingPolicy::Strict`. There are none with `ScopingPolicy::Enclosing`, and none for
+    /// Reiteration steps themselves, which are how lines are brought in.
+    fn lines_to_reiterate(&self, r: &PjRef<Self>, rule: Rule, deps: &[PjRef<Self>]) -> Vec<PjRef<Self>> {
+        if scoping_policy() == ScopingPolicy::Enclosing || rule == RuleM::Reiteration {
+            return vec![];
+        }
+        let parent = self.parent_of_line(&pj_to_pjs::<Self>(r.clone()));
+        deps.iter().filter(|dep| self.parent_of_line(&pj_to_pjs::<Self>((*dep).clone())) != parent).cloned().collect()
+    }
+
+    /// Assumptions a line or subproof rests on: the premises it cites, directly or through the lines and
+    /// subproofs it cites, other than the ones subproofs discharge
+    fn open_assumptions(&self, r: &PjsRef<Self>) -> HashSet<Self::PremiseReference> {
+        fn aux<P: Proof>(prf: &P, r: PjsRef<P>, memo: &mut HashMap<PjsRef<P>, HashSet<P::PremiseReference>>) -> HashSet<P::PremiseReference> {
+            use frunk_core::coproduct::Coproduct::{Inl, Inr};
+            if let Some(open) = memo.get(&r) {
+                return open.clone();
+            }
+            // Lines that cite themselves through later lines come back here, and add nothing; they fail to
+            // check with ReferencesLaterLine anyway
+            memo.insert(r.clone(), HashSet::new());
+            let open = match r.clone() {
+                Inl(pr) => std::iter::once(pr).collect(),
+                Inr(Inl(jr)) => match prf.lookup_step(&jr) {
+                    Some(Justification(_, _, deps, sdeps)) => deps.into_iter().map(pj_to_pjs::<P>).chain(sdeps.into_iter().map(Coproduct::inject)).flat_map(|dep| aux(prf, dep, memo)).collect(),
+                    None => HashSet::new(),
+                },
+                Inr(Inr(Inl(sr))) => match prf.lookup_subproof(&sr) {
+                    Some(sub) => {
+                        let discharged = sub.premises();
+                        sub.lines().into_iter().map(js_to_pjs::<P>).flat_map(|line| aux(prf, line, memo)).filter(|pr| !discharged.contains(pr)).collect()
+                    }
+                    None => HashSet::new(),
+                },
+                Inr(Inr(Inr(void))) => match void {},
+            };
+            memo.insert(r, open.clone());
+            open
+        }
+        aux(self, r.clone(), &mut HashMap::new())
+    }
+
+    /// Whether r1 can cite r2 as a shared line or subproof: one that's out of r1's scope, but that comes before
+    /// r1 and only rests on assumptions r1 could cite itself
+    fn can_share_dep(&self, r1: &PjRef<Self>, r2: &Coprod!(PjRef<Self>, Self::SubproofReference)) -> bool {
+        use self::Coproduct::{Inl, Inr};
+        let mut order = HashMap::new();
+        lines_in_order(self.top_level_proof(), &mut order);
+        let position = match order.get(r1) {
+            Some(&position) => position,
+            None => return false,
+        };
+        let (lines, shared) = match r2 {
+            Inl(lr) => (vec![lr.clone()], pj_to_pjs::<Self>(lr.clone())),
+            Inr(Inl(sr)) => match self.lookup_subproof(sr) {
+                Some(sub) => (sub.contained_justifications(true).into_iter().collect(), Coproduct::inject(sr.clone())),
+                None => return false,
+            },
+            Inr(Inr(void)) => match *void {},
+        };
+        // Every line of a shared subproof has to come before r1, which also keeps r1 from citing a subproof it's in
+        if lines.is_empty() || !lines.iter().all(|line| order.get(line).map(|&i| i < position).unwrap_or(false)) {
+            return false;
+        }
+        let mut valid_deps = HashSet::new();
+        let mut valid_sdeps = HashSet::new();
+        self.possible_deps_for_line(r1, &mut valid_deps, &mut valid_sdeps);
+        self.open_assumptions(&shared).into_iter().all(|pr| valid_deps.contains(&Coproduct::inject(pr)))
+    }
+
+    /// A formula and its negation, in that order, among `r` and the lines it can cite, which a step right after `r`
+    /// could conclude ⊥ from. The pair whose later line is closest to `r` is preferred.
+    fn contradiction_after_line(&self, r: &PjRef<Self>) -> Option<(PjRef<Self>, PjRef<Self>)> {
+        let mut deps = HashSet::new();
+        self.possible_deps_for_line(r, &mut deps, &mut HashSet::new());
+        deps.insert(r.clone());
+        let mut order = HashMap::new();
+        lines_in_order(self.top_level_proof(), &mut order);
+        let mut lines = deps.into_iter().filter_map(|dep| Some((*order.get(&dep)?, self.lookup_expr(&dep)?, dep))).collect::<Vec<_>>();
+        lines.sort_by_key(|(position, _, _)| *position);
+
+        // Pair each line with an earlier line it negates, or that negates it
+        let mut seen = HashMap::<Expr, PjRef<Self>>::new();
+        let mut found = None;
+        for (_, e, line) in lines {
+            if let Expr::Not { operand } = &e {
+                if let Some(positive) = seen.get(&**operand) {
+                    found = Some((positive.clone(), line.clone()));
+                }
+            }
+            if let Some(negative) = seen.get(&!e.clone()) {
+                found = Some((line.clone(), negative.clone()));
+            }
+            seen.insert(e, line);
+        }
+        found
+    }
+}
+
+/// Number every line of a (sub)proof in order, premises first
+fn lines_in_order<P: Proof>(prf: &P, order: &mut HashMap<PjRef<P>, usize>) {
+    use frunk_core::coproduct::Coproduct::{Inl, Inr};
+    for premise in prf.premises() {
+        order.insert(Coproduct::inject(premise), order.len());
+    }
+    for line in prf.lines() {
+        match line {
+            Inl(jr) => {
+                order.insert(Coproduct::inject(jr), order.len());
+            }
+            Inr(Inl(sr)) => {
+                if let Some(sub) = prf.lookup_subproof(&sr) {
+                    lines_in_order(&sub, order);
+                }
+            }
+            Inr(Inr(void)) => match void {},
+        }
+    }
 }
 
 /// A Justification struct represents a step in the proof.
@@ -0,0 +1,431 @@
+/*!
+# Clipboard format
+
+Lines and subproofs copied out of a proof go on the clipboard as JSON, with
+the MIME type `application/x-aris-proof+json`, so they can be pasted into
+another tab, another copy of Aris, or the command line tools with their rules
+and citations intact. Plain text with a formula per line goes along with it,
+for anything that doesn't know the format.
+
+A fragment looks like
+
+```json
+{
+    "version": 1,
+    "premises": ["(A ∧ B)"],
+    "lines": [
+        {"subproof": {"premises": ["C"], "lines": [{"step": {"formula": "A", "rule": "SIMPLIFICATION", "deps": [1]}}]}},
+        {"step": {"formula": "(C → A)", "rule": "CONDITIONAL_PROOF", "subdeps": [[2, 3]]}}
+    ]
+}
+```
+
+Lines are numbered from 1 within the fragment, premises first, in the order
+they're shown. Steps cite lines by their numbers, and subproofs by the numbers
+of their first and last lines. Citations of lines that weren't copied are left
+out, and rules are named as in `RuleM::to_serialized_name()`. Fields added by
+later versions are ignored.
+*/
+
+use crate::expr::Expr;
+use crate::parser;
+use crate::proofs::js_to_pjs;
+use crate::proofs::pj_to_pjs;
+use crate::proofs::JsRef;
+use crate::proofs::Justification;
+use crate::proofs::PjRef;
+use crate::proofs::PjsRef;
+use crate::proofs::Proof;
+use crate::rules::RuleM;
+
+use std::collections::HashMap;
+
+use frunk_core::coproduct::Coproduct;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// MIME type of copied lines on the clipboard
+pub const MIME_TYPE: &str = "application/x-aris-proof+json";
+
+/// Version of the clipboard format this version of Aris writes
+pub const VERSION: u32 = 1;
+
+/// Lines and subproofs copied from a proof
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipboardProof {
+    pub version: u32,
+    #[serde(default)]
+    pub premises: Vec<String>,
+    #[serde(default)]
+    pub lines: Vec<ClipboardLine>,
+}
+
+/// A step or subproof of copied lines
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardLine {
+    Step(ClipboardStep),
+    Subproof(ClipboardSubproof),
+}
+
+/// A copied step
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipboardStep {
+    /// The formula, as it was written
+    pub formula: String,
+    /// Serialized name of the rule
+    pub rule: String,
+    /// Numbers of the cited lines
+    #[serde(default)]
+    pub deps: Vec<usize>,
+    /// Numbers of the first and last lines of the cited subproofs
+    #[serde(default)]
+    pub subdeps: Vec<(usize, usize)>,
+}
+
+/// A copied subproof
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipboardSubproof {
+    #[serde(default)]
+    pub premises: Vec<String>,
+    #[serde(default)]
+    pub lines: Vec<ClipboardLine>,
+}
+
+/// Numbers lines as they're copied, so later steps can cite them
+struct Copier<'a, P: Proof, F> {
+    prf: &'a P,
+    formula: F,
+    numbers: HashMap<PjRef<P>, usize>,
+    subproofs: HashMap<P::SubproofReference, (usize, usize)>,
+}
+
+impl<'a, P: Proof, F: Fn(&PjRef<P>) -> String> Copier<'a, P, F> {
+    fn copy_formula(&mut self, r: PjRef<P>) -> String {
+        let formula = (self.formula)(&r);
+        self.numbers.insert(r, self.numbers.len() + 1);
+        formula
+    }
+
+    fn copy_line(&mut self, r: JsRef<P>) -> Option<ClipboardLine> {
+        use Coproduct::{Inl, Inr};
+        match r {
+            Inl(jr) => {
+                let Justification(_, rule, deps, sdeps) = self.prf.lookup_step(&jr)?;
+                let deps = deps.iter().filter_map(|dep| self.numbers.get(dep).copied()).collect();
+                let subdeps = sdeps.iter().filter_map(|sdep| self.subproofs.get(sdep).copied()).collect();
+                let formula = self.copy_formula(Coproduct::inject(jr));
+                Some(ClipboardLine::Step(ClipboardStep { formula, rule: RuleM::to_serialized_name(rule).to_string(), deps, subdeps }))
+            }
+            Inr(Inl(sr)) => {
+                let sub = self.prf.lookup_subproof(&sr)?;
+                let first = self.numbers.len() + 1;
+                let premises = sub.premises().into_iter().map(|pr| self.copy_formula(Coproduct::inject(pr))).collect();
+                let lines = sub.lines().into_iter().filter_map(|line| self.copy_line(line)).collect();
+                if self.numbers.len() >= first {
+                    self.subproofs.insert(sr, (first, self.numbers.len()));
+                }
+                Some(ClipboardLine::Subproof(ClipboardSubproof { premises, lines }))
+            }
+            Inr(Inr(void)) => match void {},
+        }
+    }
+}
+
+/// The formula of a pasted line. Formulas that don't parse, like lines that
+/// were still being written, are pasted as blank lines.
+fn parse_formula(text: &str) -> Expr {
+    parser::parse(text).unwrap_or_else(|| Expr::var(""))
+}
+
+/// Adds pasted lines to a proof, remembering where they went so later steps
+/// can cite them
+struct Paster<P: Proof> {
+    lines: Vec<(PjRef<P>, String)>,
+    subproofs: HashMap<(usize, usize), P::SubproofReference>,
+}
+
+impl<P: Proof> Paster<P> {
+    fn justification(&self, step: &ClipboardStep) -> Justification<Expr, PjRef<P>, P::SubproofReference> {
+        let rule = RuleM::from_serialized_name(&step.rule).unwrap_or(RuleM::EmptyRule);
+        let deps = step.deps.iter().filter_map(|&dep| self.lines.get(dep.checked_sub(1)?)).map(|(r, _)| r.clone()).collect();
+        let sdeps = step.subdeps.iter().filter_map(|range| self.subproofs.get(range)).cloned().collect();
+        Justification(parse_formula(&step.formula), rule, deps, sdeps)
+    }
+
+    /// Add a line to `prf` next to `at`, or at the end if `at` is `None`, and
+    /// move `at` to right after the new line
+    fn add_line<Q>(&mut self, prf: &mut Q, line: &ClipboardLine, at: &mut Option<(JsRef<P>, bool)>)
+    where
+        Q: Proof<PremiseReference = P::PremiseReference, JustificationReference = P::JustificationReference, SubproofReference = P::SubproofReference>,
+    {
+        let added = match line {
+            ClipboardLine::Step(step) => {
+                let just = self.justification(step);
+                let jr = match at {
+                    Some((r, after)) => prf.add_step_relative(just, r, *after),
+                    None => prf.add_step(just),
+                };
+                self.lines.push((Coproduct::inject(jr.clone()), step.formula.clone()));
+                Coproduct::inject(jr)
+            }
+            ClipboardLine::Subproof(sub) => {
+                let sr = match at {
+                    Some((r, after)) => prf.add_subproof_relative(r, *after),
+                    None => prf.add_subproof(),
+                };
+                let first = self.lines.len() + 1;
+                prf.with_mut_subproof(&sr, |new| self.fill(new, &sub.premises, &sub.lines));
+                if self.lines.len() >= first {
+                    self.subproofs.insert((first, self.lines.len()), sr.clone());
+                }
+                Coproduct::inject(sr)
+            }
+        };
+        *at = Some((added, true));
+    }
+
+    /// Add premises and lines to the end of `prf`
+    fn fill<Q>(&mut self, prf: &mut Q, premises: &[String], lines: &[ClipboardLine])
+    where
+        Q: Proof<PremiseReference = P::PremiseReference, JustificationReference = P::JustificationReference, SubproofReference = P::SubproofReference>,
+    {
+        for premise in premises {
+            let pr = prf.add_premise(parse_formula(premise));
+            self.lines.push((Coproduct::inject(pr), premise.clone()));
+        }
+        let mut at = None;
+        for line in lines {
+            self.add_line(prf, line, &mut at);
+        }
+    }
+
+    /// Paste a fragment after `after`, a line of `prf`, which is the whole
+    /// proof if `top_level` is set
+    fn paste_after<Q>(&mut self, prf: &mut Q, fragment: &ClipboardProof, after: &PjRef<P>, top_level: bool)
+    where
+        Q: Proof<PremiseReference = P::PremiseReference, JustificationReference = P::JustificationReference, SubproofReference = P::SubproofReference>,
+    {
+        use Coproduct::{Inl, Inr};
+        let mut premises = fragment.premises.iter();
+        let mut at = match after {
+            Inl(pr) => {
+                if top_level {
+                    let mut last = pr.clone();
+                    for premise in premises.by_ref() {
+                        last = prf.add_premise_relative(parse_formula(premise), &last, true);
+                        self.lines.push((Coproduct::inject(last.clone()), premise.clone()));
+                    }
+                }
+                prf.lines().first().map(|first| (first.clone(), false))
+            }
+            Inr(Inl(jr)) => Some((Coproduct::inject(jr.clone()), true)),
+            Inr(Inr(void)) => match *void {},
+        };
+        // Subproofs only have one premise, so other premises become steps
+        for premise in premises {
+            let step = ClipboardStep { formula: premise.clone(), rule: RuleM::to_serialized_name(RuleM::EmptyRule).to_string(), deps: vec![], subdeps: vec![] };
+            self.add_line(prf, &ClipboardLine::Step(step), &mut at);
+        }
+        for line in &fragment.lines {
+            self.add_line(prf, line, &mut at);
+        }
+    }
+}
+
+impl ClipboardProof {
+    /// Copy a whole proof, with its formulas as they're displayed
+    pub fn from_proof<P: Proof>(prf: &P) -> Self {
+        let top = prf.top_level_proof();
+        let items = top.premises().into_iter().map(Coproduct::inject).chain(top.lines().into_iter().map(js_to_pjs::<P>)).collect::<Vec<PjsRef<P>>>();
+        Self::from_items(prf, &items, |r| prf.lookup_expr(r).map(|expr| expr.to_string()).unwrap_or_default())
+    }
+
+    /// Copy premises, steps, and subproofs of a proof, which should be next to
+    /// each other in the same subproof. `formula` gives the text of a line's
+    /// formula, which lets an editor copy lines as they're written, even if
+    /// they don't parse yet.
+    pub fn from_items<P: Proof>(prf: &P, items: &[PjsRef<P>], formula: impl Fn(&PjRef<P>) -> String) -> Self {
+        use Coproduct::{Inl, Inr};
+        let mut copier = Copier { prf, formula, numbers: HashMap::new(), subproofs: HashMap::new() };
+        // Premises are numbered first, wherever they were picked
+        let premises = items
+            .iter()
+            .filter_map(|item| match item {
+                Inl(pr) => Some(copier.copy_formula(Coproduct::inject(pr.clone()))),
+                _ => None,
+            })
+            .collect();
+        let lines = items
+            .iter()
+            .filter_map(|item| match item {
+                Inl(_) => None,
+                Inr(Inl(jr)) => Some(Coproduct::inject(jr.clone())),
+                Inr(Inr(Inl(sr))) => Some(Coproduct::inject(sr.clone())),
+                Inr(Inr(Inr(void))) => match *void {},
+            })
+            .filter_map(|line| copier.copy_line(line))
+            .collect();
+        ClipboardProof { version: VERSION, premises, lines }
+    }
+
+    /// The fragment as JSON, for the clipboard under `MIME_TYPE`
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("clipboard contents are always serializable")
+    }
+
+    /// Read a fragment from the JSON on the clipboard
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let fragment: ClipboardProof = serde_json::from_str(text).map_err(|err| format!("Not copied lines of a proof: {err}"))?;
+        if fragment.version > VERSION {
+            return Err(format!("The lines were copied by a newer version of Aris, with clipboard format version {}, but only versions up to {} can be pasted", fragment.version, VERSION));
+        }
+        Ok(fragment)
+    }
+
+    /// The fragment as plain text, with a formula per line, for pasting into
+    /// anything that doesn't know the clipboard format
+    pub fn to_text(&self) -> String {
+        fn add_lines(premises: &[String], lines: &[ClipboardLine], text: &mut String) {
+            for premise in premises {
+                text.push_str(premise);
+                text.push('\n');
+            }
+            for line in lines {
+                match line {
+                    ClipboardLine::Step(step) => {
+                        text.push_str(&step.formula);
+                        text.push('\n');
+                    }
+                    ClipboardLine::Subproof(sub) => add_lines(&sub.premises, &sub.lines, text),
+                }
+            }
+        }
+        let mut text = String::new();
+        add_lines(&self.premises, &self.lines, &mut text);
+        text
+    }
+
+    /// Read plain text as premises, a formula per non-blank line, for pasting
+    /// text that wasn't copied from a proof
+    pub fn from_text(text: &str) -> Self {
+        let premises = text.lines().map(str::trim).filter(|line| !line.is_empty()).map(String::from).collect();
+        ClipboardProof { version: VERSION, premises, lines: vec![] }
+    }
+
+    /// A new proof made of the fragment
+    pub fn to_proof<P: Proof>(&self) -> P {
+        let mut prf = P::new();
+        let mut paster = Paster::<P> { lines: vec![], subproofs: HashMap::new() };
+        paster.fill(&mut prf, &self.premises, &self.lines);
+        prf
+    }
+
+    /// Paste the fragment into a proof after one of its lines, returning the
+    /// pasted lines in order, with the text of their formulas. After a premise
+    /// of the whole proof, the fragment's premises are pasted as premises, and
+    /// its other lines at the start of the proof's steps. Anywhere else, its
+    /// premises become steps without a rule.
+    pub fn paste<P: Proof>(&self, prf: &mut P, after: &PjRef<P>) -> Vec<(PjRef<P>, String)> {
+        let mut paster = Paster::<P> { lines: vec![], subproofs: HashMap::new() };
+        match prf.parent_of_line(&pj_to_pjs::<P>(after.clone())) {
+            Some(sr) => {
+                prf.with_mut_subproof(&sr, |sub| paster.paste_after(sub, self, after, false));
+            }
+            None => paster.paste_after(prf, self, after, true),
+        }
+        paster.lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+    use crate::proofs::pooledproof::PooledProof;
+
+    use frunk_core::HList;
+
+    type P = PooledProof<HList![Expr]>;
+
+    /// A ∧ B, then a subproof assuming C and concluding A, then C → A
+    fn sample_proof() -> P {
+        let mut prf = P::new();
+        let r1 = prf.add_premise(p("A & B"));
+        let sr = prf.add_subproof();
+        prf.with_mut_subproof(&sr, |sub| {
+            sub.add_premise(p("C"));
+            sub.add_step(Justification(p("A"), RuleM::AndElim, vec![Coproduct::inject(r1)], vec![]));
+        })
+        .unwrap();
+        prf.add_step(Justification(p("C -> A"), RuleM::ImpIntro, vec![], vec![sr]));
+        prf
+    }
+
+    #[test]
+    fn test_clipboard_roundtrip() {
+        let prf = sample_proof();
+        let fragment = ClipboardProof::from_proof(&prf);
+        assert_eq!(fragment.premises, vec!["(A ∧ B)".to_string()]);
+        match &fragment.lines[..] {
+            [ClipboardLine::Subproof(sub), ClipboardLine::Step(step)] => {
+                assert_eq!(sub.premises, vec!["C".to_string()]);
+                assert_eq!(sub.lines, vec![ClipboardLine::Step(ClipboardStep { formula: "A".into(), rule: RuleM::to_serialized_name(RuleM::AndElim).into(), deps: vec![1], subdeps: vec![] })]);
+                assert_eq!(step.subdeps, vec![(2, 3)]);
+            }
+            lines => panic!("unexpected lines {lines:?}"),
+        }
+        assert_eq!(ClipboardProof::from_json(&fragment.to_json()), Ok(fragment.clone()));
+        assert_eq!(fragment.to_text(), "(A ∧ B)\nC\nA\n(C → A)\n");
+
+        let copy = fragment.to_proof::<P>();
+        assert_eq!(ClipboardProof::from_proof(&copy), fragment);
+        for line in copy.exprs() {
+            assert_eq!(copy.verify_line(&line), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_clipboard_paste() {
+        let prf = sample_proof();
+        let top = prf.top_level_proof();
+        // Copy the subproof and the step after it, without the premise it cites
+        let items = top.lines().into_iter().map(js_to_pjs::<P>).collect::<Vec<_>>();
+        let fragment = ClipboardProof::from_items(&prf, &items, |r| prf.lookup_expr(r).unwrap().to_string());
+        assert!(fragment.premises.is_empty());
+
+        let mut target = P::new();
+        let d = target.add_premise(p("D"));
+        let pasted = fragment.paste(&mut target, &Coproduct::inject(d));
+        assert_eq!(pasted.iter().map(|(_, text)| &text[..]).collect::<Vec<_>>(), vec!["C", "A", "(C → A)"]);
+        let lines = ClipboardProof::from_proof(&target);
+        assert_eq!(lines.premises, vec!["D".to_string()]);
+        match &lines.lines[..] {
+            [ClipboardLine::Subproof(sub), ClipboardLine::Step(step)] => {
+                // The citation of the premise that wasn't copied is left out
+                assert_eq!(sub.lines, vec![ClipboardLine::Step(ClipboardStep { formula: "A".into(), rule: RuleM::to_serialized_name(RuleM::AndElim).into(), deps: vec![], subdeps: vec![] })]);
+                assert_eq!(step.subdeps, vec![(2, 3)]);
+            }
+            lines => panic!("unexpected lines {lines:?}"),
+        }
+
+        // Premises pasted after a step become steps
+        let premise = ClipboardProof { version: VERSION, premises: vec!["E".into(), "not a formula (".into()], lines: vec![] };
+        let (last, _) = pasted.last().unwrap().clone();
+        let pasted = premise.paste(&mut target, &last);
+        assert_eq!(pasted.len(), 2);
+        match &pasted[0].0 {
+            Coproduct::Inr(Coproduct::Inl(jr)) => assert_eq!(target.lookup_step(jr).unwrap().1, RuleM::EmptyRule),
+            r => panic!("premise pasted as {r:?}"),
+        }
+        assert_eq!(target.lookup_expr(&pasted[1].0), Some(Expr::var("")));
+    }
+
+    #[test]
+    fn test_clipboard_versions() {
+        assert!(ClipboardProof::from_json(r#"{"version": 1, "premises": ["A"], "extra": true}"#).is_ok());
+        assert!(ClipboardProof::from_json(r#"{"version": 2, "premises": ["A"]}"#).is_err());
+        assert!(ClipboardProof::from_json("A").is_err());
+        assert_eq!(ClipboardProof::from_text("A\n\n  B ∧ C \n").premises, vec!["A".to_string(), "B ∧ C".to_string()]);
+    }
+}
@@ -0,0 +1,246 @@
+//! The constants of a proof, where each one first appears, and the subproof it
+//! belongs to
+//!
+//! A constant that stands for the witness of an existential in an
+//! ∃ Elimination, or for an arbitrary object in a ∀ Introduction, only means
+//! that inside the subproof the rule cites. The same name in another subproof,
+//! or outside of one, is a different constant as far as the rules are
+//! concerned, but nothing about the proof shows it. `constants()` lists every
+//! constant in the terms of a proof, once for each subproof scoping it and
+//! once for its uses outside of any, with the line it first appears on.
+//!
+//! ```
+//! # use aris::expr::Expr;
+//! # use aris::proofs::constants::{constants, Constant, Scope, ScopeKind};
+//! # use aris::proofs::pooledproof::PooledProof;
+//! # use aris::proofs::{Justification, Proof};
+//! # use aris::parser::parse_unwrap as p;
+//! # use aris::rules::RuleM;
+//! # use frunk_core::coproduct::Coproduct;
+//! # use frunk_core::HList;
+//! let mut prf = PooledProof::<HList![Expr]>::new();
+//! let r1 = Coproduct::inject(prf.add_premise(p("exists x P(x)")));
+//! let r2 = Coproduct::inject(prf.add_premise(p("Q(b)")));
+//! let sub = prf.add_subproof();
+//! let r3 = Coproduct::inject(prf.with_mut_subproof(&sub, |sub| {
+//!     let r3 = sub.add_premise(p("P(a)"));
+//!     sub.add_step(Justification(p("exists y P(y)"), RuleM::ExistsIntro, vec![Coproduct::inject(r3)], vec![]));
+//!     r3
+//! }).unwrap());
+//! let r5 = Coproduct::inject(prf.add_step(Justification(p("exists y P(y)"), RuleM::ExistsElim, vec![r1], vec![sub])));
+//! assert_eq!(constants(&prf), vec![
+//!     Constant { name: "b".into(), introduced: r2, scope: None },
+//!     Constant { name: "a".into(), introduced: r3, scope: Some(Scope { kind: ScopeKind::Witness, subproof: sub, step: r5 }) },
+//! ]);
+//! ```
+
+use crate::expr::Expr;
+use crate::expr::QuantKind;
+use crate::proofs::PjRef;
+use crate::proofs::Proof;
+use crate::rules::RuleM;
+
+use frunk_core::coproduct::Coproduct;
+
+/// Why a constant belongs to a subproof
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScopeKind {
+    /// The constant is the witness of the existential an ∃ Elimination
+    /// eliminates, as named by the subproof's assumption
+    Witness,
+    /// The constant is the arbitrary object a ∀ Introduction generalizes over
+    Arbitrary,
+}
+
+impl ScopeKind {
+    /// Describe the kind of scope, like `"∃ witness"`
+    pub fn description(&self) -> &'static str {
+        match self {
+            ScopeKind::Witness => "∃ witness",
+            ScopeKind::Arbitrary => "arbitrary constant",
+        }
+    }
+}
+
+/// A subproof a constant belongs to, and the step citing the subproof that
+/// makes it belong there
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scope<R, S> {
+    pub kind: ScopeKind,
+    pub subproof: S,
+    pub step: R,
+}
+
+/// A constant of a proof, with the first line it appears on within its scope,
+/// or outside of any scope of it, if `scope` is `None`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Constant<R, S> {
+    pub name: String,
+    pub introduced: R,
+    pub scope: Option<Scope<R, S>>,
+}
+
+/// Whether a name has a meaning of its own, like numerals and the empty set,
+/// rather than being a constant
+fn is_interpreted(name: &str) -> bool {
+    name == crate::expr::EMPTY_SET || name.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// The constants of the terms in a formula, in the order they first appear,
+/// leaving out bound variables
+fn term_constants(e: &Expr) -> Vec<String> {
+    fn add_formula(e: &Expr, bound: &mut Vec<String>, out: &mut Vec<String>) {
+        use crate::expr::{ALWAYS, EVENTUALLY, NEXT, UNTIL};
+        match e {
+            // Temporal operators are applied to formulas, rather than terms
+            Expr::Apply { func, args } if matches!(&**func, Expr::Var { name } if [ALWAYS, EVENTUALLY, NEXT, UNTIL].contains(&name.as_str())) => args.iter().for_each(|arg| add_formula(arg, bound, out)),
+            Expr::Apply { args, .. } => args.iter().for_each(|arg| add_term(arg, bound, out)),
+            Expr::Quant { name, body, .. } => {
+                bound.push(name.clone());
+                add_formula(body, bound, out);
+                bound.pop();
+            }
+            _ => e.children().into_iter().for_each(|child| add_formula(child, bound, out)),
+        }
+    }
+    fn add_term(e: &Expr, bound: &mut Vec<String>, out: &mut Vec<String>) {
+        match e {
+            Expr::Var { name } if !bound.contains(name) && !is_interpreted(name) && !out.contains(name) => out.push(name.clone()),
+            Expr::Apply { args, .. } => args.iter().for_each(|arg| add_term(arg, bound, out)),
+            Expr::Assoc { exprs, .. } => exprs.iter().for_each(|e| add_term(e, bound, out)),
+            _ => {}
+        }
+    }
+    let mut out = vec![];
+    add_formula(e, &mut vec![], &mut out);
+    out
+}
+
+/// The constant that `e` puts in place of `name` in `body`, if `e` is an
+/// instance of `body` with a constant for it
+fn instance_constant(body: &Expr, name: &str, e: &Expr) -> Option<String> {
+    if !crate::expr::free_vars(body).contains(name) {
+        return None;
+    }
+    term_constants(e).into_iter().find(|constant| crate::expr::subst(body.clone(), name, Expr::var(constant)) == *e)
+}
+
+/// A constant's name, with the subproof it belongs to
+type NamedScope<P> = (String, Scope<PjRef<P>, <P as Proof>::SubproofReference>);
+
+/// The scope a step using ∃ Elimination or ∀ Introduction gives a constant,
+/// found the way the rules find the constant. The step needn't be correct.
+fn step_scope<P: Proof>(prf: &P, jr: &P::JustificationReference) -> Option<NamedScope<P>> {
+    let just = prf.lookup_step(jr)?;
+    let sr = just.3.first()?;
+    let sub = prf.lookup_subproof(sr)?;
+    let (kind, constant) = if just.1 == RuleM::ExistsElim {
+        match prf.lookup_expr(just.2.first()?)? {
+            Expr::Quant { kind: QuantKind::Exists, name, body } => match sub.premises()[..] {
+                [ref premise] => (ScopeKind::Witness, instance_constant(&body, &name, &prf.lookup_premise(premise)?)?),
+                _ => return None,
+            },
+            _ => return None,
+        }
+    } else if just.1 == RuleM::ForallIntro {
+        match &just.0 {
+            Expr::Quant { kind: QuantKind::Forall, name, body } => (ScopeKind::Arbitrary, sub.exprs().iter().filter_map(|r| prf.lookup_expr(r)).find_map(|e| instance_constant(body, name, &e))?),
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
+    Some((constant, Scope { kind, subproof: sr.clone(), step: Coproduct::inject(jr.clone()) }))
+}
+
+/// Every line of a (sub)proof in order, premises first, with the subproofs
+/// enclosing it, outermost first, and the scopes given by its steps
+fn walk<P: Proof>(prf: &P, sub: &P::Subproof, enclosing: &mut Vec<P::SubproofReference>, lines: &mut Vec<(PjRef<P>, Vec<P::SubproofReference>)>, scopes: &mut Vec<NamedScope<P>>) {
+    use frunk_core::coproduct::Coproduct::{Inl, Inr};
+    for premise in sub.premises() {
+        lines.push((Coproduct::inject(premise), enclosing.clone()));
+    }
+    for line in sub.lines() {
+        match line {
+            Inl(jr) => {
+                scopes.extend(step_scope(prf, &jr));
+                lines.push((Coproduct::inject(jr), enclosing.clone()));
+            }
+            Inr(Inl(sr)) => {
+                if let Some(inner) = sub.lookup_subproof(&sr) {
+                    enclosing.push(sr);
+                    walk(prf, &inner, enclosing, lines, scopes);
+                    enclosing.pop();
+                }
+            }
+            Inr(Inr(void)) => match void {},
+        }
+    }
+}
+
+/// List the constants of a proof, in the order they're introduced. A constant
+/// is listed once for every subproof that ∃ Elimination or ∀ Introduction
+/// scopes it to, and once more if it's used outside of those.
+pub fn constants<P: Proof>(prf: &P) -> Vec<Constant<PjRef<P>, P::SubproofReference>> {
+    let mut lines = vec![];
+    let mut scopes = vec![];
+    walk(prf, prf.top_level_proof(), &mut vec![], &mut lines, &mut scopes);
+
+    let mut ret: Vec<Constant<PjRef<P>, P::SubproofReference>> = vec![];
+    for (r, enclosing) in lines {
+        let Some(e) = prf.lookup_expr(&r) else { continue };
+        for name in term_constants(&e) {
+            // A constant belongs to the innermost subproof scoping it
+            let scope = enclosing.iter().rev().find_map(|sr| scopes.iter().find(|(constant, scope)| *constant == name && scope.subproof == *sr)).map(|(_, scope)| scope.clone());
+            if !ret.iter().any(|constant| constant.name == name && constant.scope == scope) {
+                ret.push(Constant { name, introduced: r.clone(), scope });
+            }
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+    use crate::proofs::pooledproof::PooledProof;
+    use crate::proofs::Justification;
+
+    use frunk_core::HList;
+
+    #[test]
+    fn test_term_constants() {
+        assert_eq!(term_constants(&p("forall x (P(x, a) & Q(f(b, x)))")), vec!["a", "b"]);
+        assert_eq!(term_constants(&p("A & B")), Vec::<String>::new());
+        assert_eq!(term_constants(&p("s(0) + c = c")), vec!["c"]);
+    }
+
+    #[test]
+    fn test_constants() {
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let r1 = Coproduct::inject(prf.add_premise(p("forall x P(x)")));
+        let r2 = Coproduct::inject(prf.add_premise(p("exists x Q(x)")));
+        // ∀ Introduction over a, with a nested ∃ Elimination whose witness is
+        // also named a
+        let outer = prf.add_subproof();
+        let (r3, inner, r5, r7) = prf
+            .with_mut_subproof(&outer, |sub| {
+                let r3 = Coproduct::inject(sub.add_step(Justification(p("P(a)"), RuleM::ForallElim, vec![r1], vec![])));
+                let inner = sub.add_subproof();
+                let r5 = sub
+                    .with_mut_subproof(&inner, |sub| {
+                        let r5 = Coproduct::inject(sub.add_premise(p("Q(a)")));
+                        sub.add_step(Justification(p("exists y Q(y)"), RuleM::ExistsIntro, vec![r5], vec![]));
+                        r5
+                    })
+                    .unwrap();
+                let r7 = Coproduct::inject(sub.add_step(Justification(p("exists y Q(y)"), RuleM::ExistsElim, vec![r2], vec![inner])));
+                (r3, inner, r5, r7)
+            })
+            .unwrap();
+        let r8 = Coproduct::inject(prf.add_step(Justification(p("forall z P(z)"), RuleM::ForallIntro, vec![], vec![outer])));
+        let r9 = Coproduct::inject(prf.add_step(Justification(p("P(a)"), RuleM::ForallElim, vec![r1], vec![])));
+        assert_eq!(constants(&prf), vec![Constant { name: "a".into(), introduced: r3, scope: Some(Scope { kind: ScopeKind::Arbitrary, subproof: outer, step: r8 }) }, Constant { name: "a".into(), introduced: r5, scope: Some(Scope { kind: ScopeKind::Witness, subproof: inner, step: r7 }) }, Constant { name: "a".into(), introduced: r9, scope: None },]);
+    }
+}
@@ -0,0 +1,271 @@
+//! Line-by-line comparison of two proofs, like a submission and a model
+//! solution, or two versions of the same proof.
+//!
+//! Lines are aligned by their depth, kind, and formula, with the longest
+//! common subsequence, so inserting a line doesn't make every later line
+//! differ. Aligned steps whose rules differ, or that cite lines that weren't
+//! aligned with each other, are reported as changed.
+
+use crate::proofs::Justification;
+use crate::proofs::PjRef;
+use crate::proofs::Proof;
+use crate::rules::Rule;
+
+use std::collections::HashMap;
+
+use frunk_core::coproduct::Coproduct;
+
+/// A line cited by a step, as an index into the lines of its proof
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Citation {
+    Line(usize),
+    /// First and last lines of a subproof
+    Subproof(usize, usize),
+}
+
+/// A line of a proof, flattened for comparison
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffLine {
+    /// How many subproofs the line is in
+    pub depth: usize,
+    /// The line's formula, as displayed
+    pub expr: String,
+    /// Rule and citations of a step, or `None` for a premise
+    pub justification: Option<(Rule, Vec<Citation>)>,
+}
+
+/// How a line of one proof corresponds to the other proof. Indices are into
+/// `ProofDiff::left` and `ProofDiff::right`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineDiff {
+    Unchanged {
+        left: usize,
+        right: usize,
+    },
+    /// The line is in both proofs, but its formula, rule, or citations differ
+    Changed {
+        left: usize,
+        right: usize,
+    },
+    /// The line is only in the left proof
+    Removed {
+        left: usize,
+    },
+    /// The line is only in the right proof
+    Added {
+        right: usize,
+    },
+}
+
+/// Lines of two proofs, aligned with each other
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofDiff {
+    pub left: Vec<DiffLine>,
+    pub right: Vec<DiffLine>,
+    /// Every line of both proofs, in order
+    pub lines: Vec<LineDiff>,
+}
+
+impl ProofDiff {
+    /// Whether the proofs have the same lines
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(|line| matches!(line, LineDiff::Unchanged { .. }))
+    }
+}
+
+/// The index of a step's line, and its rule and citations
+type FlatStep<P> = (usize, Justification<(), PjRef<P>, <P as Proof>::SubproofReference>);
+
+/// Flattens a proof into lines, remembering where every line and subproof
+/// ended up so citations can be turned into indices
+struct Flattener<P: Proof> {
+    lines: Vec<DiffLine>,
+    steps: Vec<FlatStep<P>>,
+    line_indices: HashMap<PjRef<P>, usize>,
+    subproof_indices: HashMap<P::SubproofReference, (usize, usize)>,
+}
+
+impl<P: Proof> Flattener<P> {
+    fn add_proof(&mut self, prf: &P::Subproof, depth: usize) {
+        use Coproduct::{Inl, Inr};
+        for premise in prf.premises() {
+            let expr = prf.lookup_premise(&premise).map(|expr| expr.to_string()).unwrap_or_default();
+            self.line_indices.insert(Coproduct::inject(premise), self.lines.len());
+            self.lines.push(DiffLine { depth, expr, justification: None });
+        }
+        for line in prf.lines() {
+            match line {
+                Inl(r) => {
+                    if let Some(Justification(expr, rule, deps, sdeps)) = prf.lookup_step(&r) {
+                        self.line_indices.insert(Coproduct::inject(r), self.lines.len());
+                        self.steps.push((self.lines.len(), Justification((), rule, deps, sdeps)));
+                        self.lines.push(DiffLine { depth, expr: expr.to_string(), justification: Some((rule, vec![])) });
+                    }
+                }
+                Inr(Inl(sr)) => {
+                    let first = self.lines.len();
+                    if let Some(sub) = prf.lookup_subproof(&sr) {
+                        self.add_proof(&sub, depth + 1);
+                    }
+                    if self.lines.len() > first {
+                        self.subproof_indices.insert(sr, (first, self.lines.len() - 1));
+                    }
+                }
+                Inr(Inr(void)) => match void {},
+            }
+        }
+    }
+}
+
+/// Flatten a proof into lines, in order
+pub fn proof_lines<P: Proof>(prf: &P) -> Vec<DiffLine> {
+    let mut flattener = Flattener::<P> { lines: vec![], steps: vec![], line_indices: HashMap::new(), subproof_indices: HashMap::new() };
+    flattener.add_proof(prf.top_level_proof(), 0);
+    let Flattener { mut lines, steps, line_indices, subproof_indices } = flattener;
+    for (i, Justification((), _, deps, sdeps)) in steps {
+        let deps = deps.iter().filter_map(|dep| line_indices.get(dep)).map(|&dep| Citation::Line(dep));
+        let sdeps = sdeps.iter().filter_map(|sdep| subproof_indices.get(sdep)).map(|&(first, last)| Citation::Subproof(first, last));
+        let citations = deps.chain(sdeps).collect();
+        if let Some((_, cited)) = &mut lines[i].justification {
+            *cited = citations;
+        }
+    }
+    lines
+}
+
+/// Pairs of indices of equal items in the longest common subsequence of two
+/// sequences
+fn longest_common_subsequence<T, U>(left: &[T], right: &[U], eq: impl Fn(&T, &U) -> bool) -> Vec<(usize, usize)> {
+    // lengths[i][j] is the length of the LCS of left[i..] and right[j..]
+    let mut lengths = vec![vec![0; right.len() + 1]; left.len() + 1];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            lengths[i][j] = if eq(&left[i], &right[j]) { lengths[i + 1][j + 1] + 1 } else { lengths[i + 1][j].max(lengths[i][j + 1]) };
+        }
+    }
+    let (mut i, mut j, mut pairs) = (0, 0, vec![]);
+    while i < left.len() && j < right.len() {
+        if eq(&left[i], &right[j]) {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// Compare two proofs line by line
+pub fn diff<P: Proof>(left: &P, right: &P) -> ProofDiff {
+    let (left, right) = (proof_lines(left), proof_lines(right));
+    let same_place = |l: &DiffLine, r: &DiffLine| l.depth == r.depth && l.justification.is_none() == r.justification.is_none();
+    let matched = longest_common_subsequence(&left, &right, |l, r| same_place(l, r) && l.expr == r.expr);
+
+    // Between matched lines, lines removed from the left in the same place as
+    // lines added to the right are changes of their formulas
+    let mut pairs = vec![];
+    let (mut i, mut j) = (0, 0);
+    for (next_i, next_j) in matched.into_iter().chain(std::iter::once((left.len(), right.len()))) {
+        while i < next_i && j < next_j && same_place(&left[i], &right[j]) {
+            pairs.push((Some(i), Some(j)));
+            i += 1;
+            j += 1;
+        }
+        pairs.extend((i..next_i).map(|i| (Some(i), None)));
+        pairs.extend((j..next_j).map(|j| (None, Some(j))));
+        if next_i < left.len() {
+            pairs.push((Some(next_i), Some(next_j)));
+        }
+        i = next_i + 1;
+        j = next_j + 1;
+    }
+
+    let aligned = pairs.iter().filter_map(|pair| match pair {
+        (Some(i), Some(j)) => Some((*i, *j)),
+        _ => None,
+    });
+    let right_of = aligned.collect::<HashMap<usize, usize>>();
+    let map_citation = |citation: &Citation| match *citation {
+        Citation::Line(i) => right_of.get(&i).map(|&j| Citation::Line(j)),
+        Citation::Subproof(first, last) => Some(Citation::Subproof(*right_of.get(&first)?, *right_of.get(&last)?)),
+    };
+    let unchanged = |l: &DiffLine, r: &DiffLine| {
+        l.expr == r.expr
+            && match (&l.justification, &r.justification) {
+                (Some((l_rule, l_cited)), Some((r_rule, r_cited))) => l_rule == r_rule && l_cited.iter().map(map_citation).eq(r_cited.iter().cloned().map(Some)),
+                (l, r) => l.is_none() && r.is_none(),
+            }
+    };
+    let lines = pairs
+        .into_iter()
+        .map(|pair| match pair {
+            (Some(left_i), Some(right_i)) if unchanged(&left[left_i], &right[right_i]) => LineDiff::Unchanged { left: left_i, right: right_i },
+            (Some(left_i), Some(right_i)) => LineDiff::Changed { left: left_i, right: right_i },
+            (Some(left_i), None) => LineDiff::Removed { left: left_i },
+            (None, Some(right_i)) => LineDiff::Added { right: right_i },
+            (None, None) => unreachable!("every pair has a line"),
+        })
+        .collect();
+    ProofDiff { left, right, lines }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::Expr;
+    use crate::parser::parse_unwrap as p;
+    use crate::proofs::pooledproof::PooledProof;
+    use crate::rules::RuleM;
+
+    use frunk_core::HList;
+
+    type P = PooledProof<HList![Expr]>;
+
+    /// A ⊢ A ∧ A, with `extra` as another premise after A
+    fn proof(extra: Option<&str>, conclusion: &str) -> P {
+        let mut prf = P::new();
+        let a = prf.add_premise(p("A"));
+        if let Some(extra) = extra {
+            prf.add_premise(p(extra));
+        }
+        prf.add_step(Justification(p(conclusion), RuleM::AndIntro, vec![Coproduct::inject(a)], vec![]));
+        prf
+    }
+
+    #[test]
+    fn test_diff_same_proof() {
+        let diff = diff(&proof(None, "A & A"), &proof(None, "A & A"));
+        assert!(diff.is_empty());
+        assert_eq!(diff.lines, vec![LineDiff::Unchanged { left: 0, right: 0 }, LineDiff::Unchanged { left: 1, right: 1 }]);
+    }
+
+    #[test]
+    fn test_diff_added_line() {
+        // The step cites the same premise, at a different line number
+        let diff = diff(&proof(None, "A & A"), &proof(Some("B"), "A & A"));
+        assert_eq!(diff.lines, vec![LineDiff::Unchanged { left: 0, right: 0 }, LineDiff::Added { right: 1 }, LineDiff::Unchanged { left: 1, right: 2 }]);
+    }
+
+    #[test]
+    fn test_diff_changed_line() {
+        let diff = diff(&proof(Some("B"), "A & A"), &proof(Some("C"), "A & A & A"));
+        assert_eq!(diff.lines, vec![LineDiff::Unchanged { left: 0, right: 0 }, LineDiff::Changed { left: 1, right: 1 }, LineDiff::Changed { left: 2, right: 2 }]);
+    }
+
+    #[test]
+    fn test_diff_subproof_citations() {
+        let mut prf = P::new();
+        let sub = prf.add_subproof();
+        prf.with_mut_subproof(&sub, |sub| {
+            sub.add_premise(p("A"));
+        })
+        .unwrap();
+        prf.add_step(Justification(p("A -> A"), RuleM::ImpIntro, vec![], vec![sub]));
+        let lines = proof_lines(&prf);
+        assert_eq!(lines[0].depth, 1);
+        assert_eq!(lines[1].justification, Some((RuleM::ImpIntro, vec![Citation::Subproof(0, 0)])));
+    }
+}
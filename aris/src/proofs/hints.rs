@@ -0,0 +1,168 @@
+//! Hints for steps that are one equivalence away from being right
+//!
+//! A common dead end is a step that would follow by its rule, except that a
+//! cited line or the step itself needs an equivalence applied first, like
+//! eliminating `¬¬A ∧ B` to `A` without removing the double negation. When a
+//! step is wrong, `equivalence_hint()` tries rewriting its cited lines, then
+//! its own formula, once with each equivalence rule, at every place it
+//! applies, and checks the step again. The first rewrite that makes the step
+//! correct is given as a hint, which GUIs can add to the step's error.
+//!
+//! ```
+//! # use aris::expr::Expr;
+//! # use aris::proofs::hints::{equivalence_hint, Hint};
+//! # use aris::proofs::pooledproof::PooledProof;
+//! # use aris::proofs::{Justification, Proof};
+//! # use aris::parser::parse_unwrap as p;
+//! # use aris::rules::RuleM;
+//! # use frunk_core::coproduct::Coproduct;
+//! # use frunk_core::HList;
+//! let mut prf = PooledProof::<HList![Expr]>::new();
+//! let a = Coproduct::inject(prf.add_premise(p("~~A & B")));
+//! let b = Coproduct::inject(prf.add_step(Justification(p("A"), RuleM::AndElim, vec![a], vec![])));
+//! let hint = equivalence_hint(&prf, &b);
+//! assert_eq!(hint, Some(Hint::RewriteDep { rule: RuleM::DoubleNegation, dep: a }));
+//! assert_eq!(hint.unwrap().message(|_| Some(1)), "Did you mean to apply Double Negation to line 1 first?");
+//! ```
+
+use crate::expr::Expr;
+use crate::proofs::PjRef;
+use crate::proofs::Proof;
+use crate::rule_names;
+use crate::rules::ProofCheckError;
+use crate::rules::Rule;
+use crate::rules::RuleM;
+use crate::rules::RuleT;
+
+use frunk_core::coproduct::Coproduct;
+
+/// Most rewrites tried for a step, so looking for a hint stays quick even when
+/// an equivalence applies all over a long formula. The rewrites also share a
+/// single check's budget from `limits`.
+pub const MAX_REWRITES: usize = 200;
+
+/// An equivalence that would make a wrong step right, if it were applied in a
+/// step of its own
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Hint<R> {
+    /// The step follows from its cited lines once `rule` is applied to `dep`
+    RewriteDep { rule: Rule, dep: R },
+    /// The step's rule concludes something that `rule` turns into the step's
+    /// formula, so `rule` belongs in a step after it
+    RewriteConclusion { rule: Rule },
+}
+
+impl<R> Hint<R> {
+    /// Describe the hint as a question, writing the line it refers to with
+    /// `line_number`
+    pub fn message(&self, line_number: impl Fn(&R) -> Option<usize>) -> String {
+        match self {
+            Hint::RewriteDep { rule, dep } => match line_number(dep) {
+                Some(n) => format!("Did you mean to apply {} to line {n} first?", rule_names::display_name(*rule)),
+                None => format!("Did you mean to apply {} to a cited line first?", rule_names::display_name(*rule)),
+            },
+            Hint::RewriteConclusion { rule } => format!("Did you mean to apply {} in a step of its own, after this one?", rule_names::display_name(*rule)),
+        }
+    }
+}
+
+/// Whether an error is about the formulas of a step, rather than which lines
+/// it cites, so that rewriting them could fix it
+fn is_about_formulas<R, S>(err: &ProofCheckError<R, S>) -> bool {
+    use ProofCheckError::*;
+    match err {
+        DepOfWrongForm(_, _) | ConclusionOfWrongForm(_) | DoesNotOccur(_, _) | DepDoesNotExist(_, _) | Other(_) => true,
+        OneOf(errs) => errs.iter().all(is_about_formulas),
+        LineDoesNotExist(_) | SubproofDoesNotExist(_) | ReferencesLaterLine(_, _) | IncorrectDepCount(_, _) | IncorrectSubDepCount(_, _) | PremiseCitedAsAssumption(_) | IncorrectAssumptionCount(_, _) | NeedsReiteration(_) | TimedOut | Gap => false,
+    }
+}
+
+/// Replace the formula of a line
+fn set_expr<P: Proof>(prf: &mut P, r: &PjRef<P>, e: Expr) {
+    use Coproduct::{Inl, Inr};
+    match r {
+        Inl(pr) => prf.with_mut_premise(pr, |premise| *premise = e),
+        Inr(Inl(jr)) => prf.with_mut_step(jr, |just| just.0 = e),
+        Inr(Inr(void)) => match *void {},
+    };
+}
+
+/// Look for an equivalence that would make the step `r` correct, if it's
+/// wrong because of its formulas. Cited lines are tried before the step
+/// itself, and rules in the order of `RuleM::ALL_RULES`.
+pub fn equivalence_hint<P: Proof + Clone>(prf: &P, r: &PjRef<P>) -> Option<Hint<PjRef<P>>> {
+    let jr = Coproduct::uninject::<P::JustificationReference, _>(r.clone()).ok()?;
+    let just = prf.lookup_step(&jr)?;
+    match prf.verify_line(r) {
+        Err(err) if is_about_formulas(&err) => {}
+        _ => return None,
+    }
+    let equivalences = RuleM::ALL_RULES.iter().filter(|rule| **rule != just.1).filter_map(|rule| Some((*rule, rule.rewrite_rule()?))).collect::<Vec<_>>();
+    let candidates = just.2.iter().map(Some).chain(std::iter::once(None));
+
+    // Lines are rewritten in a copy of the proof, and put back before moving on
+    let mut scratch = prf.clone();
+    let mut tries = 0;
+    crate::limits::with_budget(|| {
+        for dep in candidates {
+            let line = dep.unwrap_or(r);
+            let e = prf.lookup_expr(line)?;
+            for (rule, rewrite) in &equivalences {
+                for (path, _) in rewrite.find_matches(&e) {
+                    if tries == MAX_REWRITES {
+                        return None;
+                    }
+                    tries += 1;
+                    if let Some(rewritten) = rewrite.rewrite_at(&e, &path) {
+                        set_expr(&mut scratch, line, rewritten);
+                        match scratch.verify_line(r) {
+                            Ok(()) => return Some(dep.map_or(Hint::RewriteConclusion { rule: *rule }, |dep| Hint::RewriteDep { rule: *rule, dep: dep.clone() })),
+                            // Later checks would run out of the budget too
+                            Err(ProofCheckError::TimedOut) => return None,
+                            Err(_) => {}
+                        }
+                    }
+                }
+            }
+            set_expr(&mut scratch, line, e);
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+    use crate::proofs::pooledproof::PooledProof;
+    use crate::proofs::Justification;
+
+    use frunk_core::HList;
+
+    #[test]
+    fn test_equivalence_hint() {
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let r1 = Coproduct::inject(prf.add_premise(p("A -> B")));
+        let r2 = Coproduct::inject(prf.add_premise(p("~~A")));
+        let r3 = Coproduct::inject(prf.add_premise(p("C")));
+        // Modus ponens with a double negation left on the antecedent
+        let r4 = Coproduct::inject(prf.add_step(Justification(p("B"), RuleM::ImpElim, vec![r1, r2], vec![])));
+        assert_eq!(equivalence_hint(&prf, &r4), Some(Hint::RewriteDep { rule: RuleM::DoubleNegation, dep: r2 }));
+        // Addition concluding a double negation it doesn't introduce
+        let r5 = Coproduct::inject(prf.add_step(Justification(p("~~(C | D)"), RuleM::OrIntro, vec![r3], vec![])));
+        assert_eq!(equivalence_hint(&prf, &r5), Some(Hint::RewriteConclusion { rule: RuleM::DoubleNegation }));
+        assert_eq!(Hint::<()>::RewriteConclusion { rule: RuleM::DoubleNegation }.message(|_| None), "Did you mean to apply Double Negation in a step of its own, after this one?");
+
+        // Correct steps, steps citing the wrong number of lines, and steps no
+        // single rewrite fixes get no hint
+        let r6 = Coproduct::inject(prf.add_step(Justification(p("C | D"), RuleM::OrIntro, vec![r3], vec![])));
+        assert_eq!(equivalence_hint(&prf, &r6), None);
+        let r7 = Coproduct::inject(prf.add_step(Justification(p("B"), RuleM::ImpElim, vec![r1], vec![])));
+        assert_eq!(equivalence_hint(&prf, &r7), None);
+        let r8 = Coproduct::inject(prf.add_step(Justification(p("D"), RuleM::ImpElim, vec![r1, r2], vec![])));
+        assert_eq!(equivalence_hint(&prf, &r8), None);
+        // The proof isn't changed by looking
+        assert_eq!(prf.lookup_expr(&r2), Some(p("~~A")));
+        assert_eq!(prf.lookup_expr(&r5), Some(p("~~(C | D)")));
+    }
+}
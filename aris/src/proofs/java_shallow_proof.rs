@@ -8,6 +8,10 @@ use crate::rules::ProofCheckError;
 use crate::rules::RuleM;
 use crate::rules::RuleT;
 
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+
 use frunk_core::coproduct::Coproduct;
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -75,6 +79,12 @@ impl Proof for JavaShallowProof {
     fn parent_of_line(&self, _: &PjsRef<Self>) -> Option<Self::SubproofReference> {
         unimplemented!()
     }
+    fn revision(&self) -> u64 {
+        // Editing doesn't change it, so it only differs from another one in its lines
+        let mut hasher = DefaultHasher::new();
+        self.0.hash(&mut hasher);
+        hasher.finish()
+    }
     fn verify_line(&self, r: &PjRef<Self>) -> Result<(), ProofCheckError<PjRef<Self>, Self::SubproofReference>> {
         use frunk_core::coproduct::Coproduct::{Inl, Inr};
         match self.lookup_pj(r) {
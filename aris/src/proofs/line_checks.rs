@@ -0,0 +1,181 @@
+//! Checking a whole proof a line at a time, yielding each line's result as
+//! soon as it's checked, so big proofs can show progress instead of going
+//! quiet until every line is done.
+//!
+//! `LineChecks` holds the lines left to check, without borrowing the proof,
+//! so it can be kept between events and advanced a few lines at a time, like
+//! the web app does between frames. For checking in one go, `LineChecks::iter()`
+//! is an `Iterator` of the results, and on native builds a `futures` `Stream`.
+//!
+//! ```
+//! # use aris::expr::Expr;
+//! # use aris::proofs::line_checks::LineChecks;
+//! # use aris::proofs::pooledproof::PooledProof;
+//! # use aris::proofs::Proof;
+//! # use aris::parser::parse_unwrap as p;
+//! # use frunk_core::HList;
+//! let mut prf = PooledProof::<HList![Expr]>::new();
+//! prf.add_premise(p("A"));
+//! let mut checks = LineChecks::new(&prf);
+//! assert_eq!((checks.checked(), checks.total()), (0, 1));
+//! let result = checks.check_next(&prf).unwrap();
+//! assert_eq!((result.number, result.result), (1, Ok(())));
+//! assert!(checks.check_next(&prf).is_none());
+//! ```
+
+use crate::proofs::PjRef;
+use crate::proofs::Proof;
+use crate::rules::ProofCheckError;
+
+use std::collections::VecDeque;
+
+use frunk_core::coproduct::Coproduct;
+
+/// Result of checking a line
+pub struct LineResult<P: Proof> {
+    pub line: PjRef<P>,
+    /// Line number, counting from 1
+    pub number: usize,
+    pub result: Result<(), ProofCheckError<PjRef<P>, P::SubproofReference>>,
+}
+
+/// Lines of a proof left to check, in order
+pub struct LineChecks<P: Proof> {
+    pending: VecDeque<(usize, PjRef<P>)>,
+    total: usize,
+}
+
+// Nothing is pinned through `LineChecks`, so streams of its results can be
+// polled without pinning, whatever the proof's references are
+impl<P: Proof> Unpin for LineChecks<P> {}
+
+/// Lines of a (sub)proof in order, with their line numbers
+fn add_lines<P: Proof>(prf: &P::Subproof, lines: &mut VecDeque<(usize, PjRef<P>)>) {
+    use Coproduct::{Inl, Inr};
+    for premise in prf.premises() {
+        lines.push_back((lines.len() + 1, Coproduct::inject(premise)));
+    }
+    for line in prf.lines() {
+        match line {
+            Inl(r) => lines.push_back((lines.len() + 1, Coproduct::inject(r))),
+            Inr(Inl(sr)) => {
+                if let Some(sub) = prf.lookup_subproof(&sr) {
+                    add_lines::<P>(&sub, lines);
+                }
+            }
+            Inr(Inr(void)) => match void {},
+        }
+    }
+}
+
+impl<P: Proof> LineChecks<P> {
+    /// Start checking every line of a proof
+    pub fn new(prf: &P) -> Self {
+        let mut pending = VecDeque::new();
+        add_lines::<P>(prf.top_level_proof(), &mut pending);
+        let total = pending.len();
+        LineChecks { pending, total }
+    }
+
+    /// How many lines have been checked
+    pub fn checked(&self) -> usize {
+        self.total - self.pending.len()
+    }
+
+    /// How many lines there are to check in all
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Whether every line has been checked
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Check the next line, or return `None` if every line has been checked.
+    /// `prf` should be the proof the checks were started for.
+    pub fn check_next(&mut self, prf: &P) -> Option<LineResult<P>> {
+        let (number, line) = self.pending.pop_front()?;
+        let result = prf.verify_line(&line);
+        Some(LineResult { line, number, result })
+    }
+
+    /// Check the remaining lines of `prf` as they're iterated over
+    pub fn iter(self, prf: &P) -> LineCheckIter<'_, P> {
+        LineCheckIter { checks: self, prf }
+    }
+}
+
+/// Results of checking the remaining lines of a proof, as they're checked
+pub struct LineCheckIter<'a, P: Proof> {
+    checks: LineChecks<P>,
+    prf: &'a P,
+}
+
+impl<'a, P: Proof> LineCheckIter<'a, P> {
+    /// How many lines have been checked, and how many there are in all
+    pub fn progress(&self) -> (usize, usize) {
+        (self.checks.checked(), self.checks.total())
+    }
+}
+
+impl<'a, P: Proof> Iterator for LineCheckIter<'a, P> {
+    type Item = LineResult<P>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.checks.check_next(self.prf)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.checks.pending.len();
+        (left, Some(left))
+    }
+}
+
+/// Every poll checks one line, so tasks interleave with checking a line at a
+/// time rather than waiting for the whole proof
+#[cfg(not(target_arch = "wasm32"))]
+impl<'a, P: Proof> futures_core::Stream for LineCheckIter<'a, P> {
+    type Item = LineResult<P>;
+
+    fn poll_next(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<Option<Self::Item>> {
+        std::task::Poll::Ready(self.get_mut().next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        Iterator::size_hint(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::Expr;
+    use crate::parser::parse_unwrap as p;
+    use crate::proofs::pooledproof::PooledProof;
+    use crate::proofs::Justification;
+    use crate::rules::RuleM;
+
+    use frunk_core::HList;
+
+    #[test]
+    fn test_line_checks_in_order() {
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let a = prf.add_premise(p("A"));
+        let sub = prf.add_subproof();
+        prf.with_mut_subproof(&sub, |sub| {
+            sub.add_premise(p("B"));
+        })
+        .unwrap();
+        prf.add_step(Justification(p("A & A"), RuleM::AndIntro, vec![Coproduct::inject(a)], vec![]));
+        prf.add_step(Justification(p("C"), RuleM::AndIntro, vec![Coproduct::inject(a)], vec![]));
+
+        let mut results = LineChecks::new(&prf).iter(&prf);
+        assert_eq!(results.progress(), (0, 4));
+        let first = results.next().unwrap();
+        assert_eq!((first.number, first.result), (1, Ok(())));
+        assert_eq!(results.progress(), (1, 4));
+        let rest = results.map(|result| (result.number, result.result.is_ok())).collect::<Vec<_>>();
+        assert_eq!(rest, vec![(2, true), (3, true), (4, false)]);
+    }
+}
@@ -0,0 +1,156 @@
+//! Line numbers of a proof's lines and the ranges of lines its subproofs span,
+//! the way they're shown and cited.
+//!
+//! Lines are numbered from 1, premises before the other lines of their
+//! (sub)proof. A subproof spans from its first premise to its last line,
+//! counting the lines of the subproofs nested in it, so a subproof ending in
+//! another one ends where the nested one does.
+//!
+//! A `LineInfo` remembers the `Proof::revision` it was computed at, so a copy
+//! kept around for a proof can tell when editing the proof has outdated it.
+//!
+//! ```
+//! use aris::expr::Expr;
+//! use aris::parser::parse_unwrap as p;
+//! use aris::proofs::line_info::LineInfo;
+//! use aris::proofs::pooledproof::PooledProof;
+//! use aris::proofs::{Justification, Proof};
+//! use aris::rules::RuleM;
+//!
+//! use frunk_core::HList;
+//!
+//! let mut prf = PooledProof::<HList![Expr]>::new();
+//! prf.add_premise(p("A"));
+//! let outer = prf.add_subproof();
+//! let inner = prf.with_mut_subproof(&outer, |sub| {
+//!     sub.add_premise(p("B"));
+//!     let inner = sub.add_subproof();
+//!     sub.with_mut_subproof(&inner, |sub| {
+//!         sub.add_premise(p("C"));
+//!         sub.add_step(Justification(p("C"), RuleM::Reiteration, vec![], vec![]));
+//!     });
+//!     inner
+//! }).unwrap();
+//!
+//! let info = LineInfo::of(&prf);
+//! assert_eq!(info.line_range_of_subproof(&outer), Some((2, 4)));
+//! assert_eq!(info.line_range_of_subproof(&inner), Some((3, 4)));
+//! ```
+
+use crate::proofs::PjRef;
+use crate::proofs::Proof;
+
+use std::collections::HashMap;
+
+use frunk_core::coproduct::Coproduct;
+
+/// Where the lines and subproofs of a proof are
+pub struct LineInfo<P: Proof> {
+    /// Line number and depth of every line, by its reference
+    pub lines: HashMap<PjRef<P>, (usize, usize)>,
+    /// First and last line numbers of every subproof with any lines
+    pub subproofs: HashMap<P::SubproofReference, (usize, usize)>,
+    /// Revision of the proof the lines were numbered in
+    pub revision: u64,
+}
+
+impl<P: Proof> LineInfo<P> {
+    pub fn of(prf: &P) -> Self {
+        let mut info = LineInfo { lines: HashMap::new(), subproofs: HashMap::new(), revision: prf.revision() };
+        info.add_proof(prf.top_level_proof(), 0);
+        info
+    }
+
+    fn add_proof(&mut self, prf: &P::Subproof, depth: usize) {
+        use Coproduct::{Inl, Inr};
+        for premise in prf.premises() {
+            self.lines.insert(Coproduct::inject(premise), (self.lines.len() + 1, depth));
+        }
+        for line in prf.lines() {
+            match line {
+                Inl(r) => {
+                    self.lines.insert(Coproduct::inject(r), (self.lines.len() + 1, depth));
+                }
+                Inr(Inl(sr)) => {
+                    let first = self.lines.len() + 1;
+                    if let Some(sub) = prf.lookup_subproof(&sr) {
+                        self.add_proof(&sub, depth + 1);
+                    }
+                    if self.lines.len() >= first {
+                        self.subproofs.insert(sr, (first, self.lines.len()));
+                    }
+                }
+                Inr(Inr(void)) => match void {},
+            }
+        }
+    }
+
+    /// Whether the lines were numbered in `prf` as it is now, rather than
+    /// before it was last edited
+    pub fn is_current(&self, prf: &P) -> bool {
+        self.revision == prf.revision()
+    }
+
+    /// Line number of a line
+    pub fn line_of(&self, r: &PjRef<P>) -> Option<usize> {
+        self.lines.get(r).map(|&(line, _)| line)
+    }
+
+    /// First and last line numbers of a subproof, including the lines of
+    /// subproofs nested in it, or `None` if it has no lines
+    pub fn line_range_of_subproof(&self, sr: &P::SubproofReference) -> Option<(usize, usize)> {
+        self.subproofs.get(sr).copied()
+    }
+}
+
+/// Write a range of lines the way subproofs are cited, like `3-5`
+pub fn format_range((first, last): (usize, usize)) -> String {
+    format!("{first}-{last}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::Expr;
+    use crate::parser::parse_unwrap as p;
+    use crate::proofs::pooledproof::PooledProof;
+    use crate::proofs::Justification;
+    use crate::rules::RuleM;
+
+    use frunk_core::HList;
+
+    #[test]
+    fn test_line_info() {
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let r1 = prf.add_premise(p("A"));
+        let empty = prf.add_subproof();
+        let sub = prf.add_subproof();
+        let (r2, nested) = prf
+            .with_mut_subproof(&sub, |sub| {
+                let r2 = sub.add_premise(p("B"));
+                let nested = sub.add_subproof();
+                sub.with_mut_subproof(&nested, |sub| {
+                    sub.add_premise(p("C"));
+                    sub.add_step(Justification(p("C"), RuleM::Reiteration, vec![], vec![]));
+                });
+                (r2, nested)
+            })
+            .unwrap();
+        let r5 = prf.add_step(Justification(p("A"), RuleM::Reiteration, vec![Coproduct::inject(r1)], vec![]));
+
+        let info = LineInfo::of(&prf);
+        assert_eq!(info.line_of(&Coproduct::inject(r1)), Some(1));
+        assert_eq!(info.lines.get(&Coproduct::inject(r2)), Some(&(2, 1)));
+        assert_eq!(info.line_of(&Coproduct::inject(r5)), Some(5));
+        assert_eq!(info.line_range_of_subproof(&empty), None);
+        assert_eq!(info.line_range_of_subproof(&sub), Some((2, 4)));
+        assert_eq!(info.line_range_of_subproof(&nested), Some((3, 4)));
+        assert_eq!(format_range((2, 4)), "2-4");
+
+        // Edits to subproofs outdate it too
+        assert!(info.is_current(&prf));
+        prf.with_mut_subproof(&nested, |sub| sub.add_premise(p("D")));
+        assert!(!info.is_current(&prf));
+        assert_eq!(LineInfo::of(&prf).line_of(&Coproduct::inject(r5)), Some(6));
+    }
+}
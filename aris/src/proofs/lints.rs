@@ -0,0 +1,224 @@
+//! Warnings about lines that are correct, but written in a way worth pointing
+//! out, like restating a line that could have been cited instead, or one
+//! equivalent to it.
+//!
+//! Checking a line gives one of three levels of feedback: the line is
+//! incorrect, it's correct with warnings, or it's correct. Warnings don't make
+//! a proof incorrect, so a proof with warnings still counts as solved.
+//!
+//! ```
+//! # use aris::expr::Expr;
+//! # use aris::proofs::lints::{check_line, LineFeedback, Lint};
+//! # use aris::proofs::pooledproof::PooledProof;
+//! # use aris::proofs::{Justification, Proof};
+//! # use aris::parser::parse_unwrap as p;
+//! # use aris::rules::RuleM;
+//! # use frunk_core::coproduct::Coproduct;
+//! # use frunk_core::HList;
+//! let mut prf = PooledProof::<HList![Expr]>::new();
+//! let a = Coproduct::inject(prf.add_premise(p("A & A")));
+//! let b = Coproduct::inject(prf.add_step(Justification(p("A"), RuleM::AndElim, vec![a], vec![])));
+//! let c = Coproduct::inject(prf.add_step(Justification(p("A & A"), RuleM::AndIntro, vec![b], vec![])));
+//! assert_eq!(check_line(&prf, &b, &[]), LineFeedback::Correct);
+//! assert_eq!(check_line(&prf, &c, &[]), LineFeedback::Warnings(vec![Lint::Restates(a)]));
+//! ```
+
+use crate::equivalence;
+use crate::expr::Expr;
+use crate::parser;
+use crate::proofs::lines_in_order;
+use crate::proofs::PjRef;
+use crate::proofs::Proof;
+use crate::rules::ProofCheckError;
+use crate::rules::RuleM;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use frunk_core::coproduct::Coproduct;
+
+/// Something worth pointing out about a correct line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Lint<R> {
+    /// The step proves the same formula as an earlier line it could cite, up
+    /// to the order of operands and the names of bound variables
+    Restates(R),
+    /// The step proves a formula equivalent to an earlier line it could cite,
+    /// but doesn't
+    Equivalent(R),
+    /// None of the lines the proof concludes with depend on the step
+    UnusedStep,
+    /// The proof requires every formula to be fully parenthesized, as in
+    /// `parser::is_fully_parenthesized()`, and the line wasn't typed that way
+    NotFullyParenthesized,
+}
+
+impl<R> Lint<R> {
+    /// Short, stable name of the kind of warning, like `"restates"`, in the
+    /// style of `ProofCheckError::code()`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lint::Restates(_) => "restates",
+            Lint::Equivalent(_) => "equivalent",
+            Lint::UnusedStep => "unused-step",
+            Lint::NotFullyParenthesized => "not-fully-parenthesized",
+        }
+    }
+
+    /// Describe the warning, writing the lines it refers to with `line_number`
+    pub fn message(&self, line_number: impl Fn(&R) -> Option<usize>) -> String {
+        match self {
+            Lint::Restates(r) => match line_number(r) {
+                Some(n) => format!("This restates line {n}, which could be cited instead."),
+                None => "This restates an earlier line, which could be cited instead.".into(),
+            },
+            Lint::Equivalent(r) => match line_number(r) {
+                Some(n) => format!("This is equivalent to line {n}, so it may not be needed."),
+                None => "This is equivalent to an earlier line, so it may not be needed.".into(),
+            },
+            Lint::UnusedStep => "The conclusion doesn't depend on this step.".into(),
+            Lint::NotFullyParenthesized => "Put every connective, comparison, and quantifier in parentheses of its own, as this proof requires.".into(),
+        }
+    }
+}
+
+/// Feedback on a line: whether it's correct, and if it is, any warnings
+#[derive(Debug, PartialEq, Eq)]
+pub enum LineFeedback<R, S> {
+    Correct,
+    Warnings(Vec<Lint<R>>),
+    Incorrect(ProofCheckError<R, S>),
+    /// The line is a gap, admitted without proof, which is neither correct nor a mistake
+    Gap,
+}
+
+impl<R, S> LineFeedback<R, S> {
+    /// Whether the line is correct, with or without warnings
+    pub fn is_correct(&self) -> bool {
+        matches!(self, LineFeedback::Correct | LineFeedback::Warnings(_))
+    }
+}
+
+/// Warnings about a line, whether or not it's correct. `goals` are the proof's
+/// goals, which the lines it concludes with prove, as in
+/// `Proof::concluding_lines`.
+pub fn lints<P: Proof>(prf: &P, r: &PjRef<P>, goals: &[Expr]) -> Vec<Lint<PjRef<P>>> {
+    let just = match Coproduct::uninject::<P::JustificationReference, _>(r.clone()).ok().and_then(|jr| prf.lookup_step(&jr)) {
+        Some(just) => just,
+        None => return vec![],
+    };
+    let mut lints = vec![];
+
+    // Reiterating a line is what Reiteration is for
+    if just.1 != RuleM::Reiteration {
+        let mut deps = HashSet::new();
+        prf.possible_deps_for_line(r, &mut deps, &mut HashSet::new());
+        let mut order = HashMap::new();
+        lines_in_order(prf.top_level_proof(), &mut order);
+        let earliest = |lines: Vec<&PjRef<P>>| lines.into_iter().min_by_key(|dep| order.get(*dep).copied()).cloned();
+        let formulas = deps.iter().filter_map(|dep| Some((dep, prf.lookup_expr(dep)?))).collect::<Vec<_>>();
+        // Regrouping or reordering a line, or renaming its bound variables, still restates it
+        let canonical = just.0.canonicalize();
+        if let Some(restated) = earliest(formulas.iter().filter(|(_, e)| e.canonicalize() == canonical).map(|(dep, _)| *dep).collect()) {
+            lints.push(Lint::Restates(restated));
+        } else {
+            // Only propositional formulas are compared, since comparing
+            // first-order ones takes a search of models for every line
+            let equivalent = formulas.iter().filter(|(dep, e)| !just.2.contains(dep) && equivalence::are_propositionally_equivalent(e, &just.0) == Some(true)).map(|(dep, _)| *dep).collect();
+            lints.extend(earliest(equivalent).map(Lint::Equivalent));
+        }
+    }
+
+    let concluding_lines = prf.concluding_lines(goals);
+    if !concluding_lines.is_empty() && !concluding_lines.iter().any(|line| prf.transitive_dependencies(line.clone()).contains(r)) {
+        lints.push(Lint::UnusedStep);
+    }
+    lints
+}
+
+/// Warnings about how a line was typed, rather than what it says, given its
+/// text. With `require_full_parens`, as set by a proof's metadata, a line that
+/// isn't fully parenthesized is pointed out. Unlike `lints()`, this applies to
+/// premises too.
+pub fn input_lints<R>(input: &str, require_full_parens: bool) -> Vec<Lint<R>> {
+    if require_full_parens && !parser::is_fully_parenthesized(input) {
+        vec![Lint::NotFullyParenthesized]
+    } else {
+        vec![]
+    }
+}
+
+/// Verify a line, and look for warnings about it if it's correct
+pub fn check_line<P: Proof>(prf: &P, r: &PjRef<P>, goals: &[Expr]) -> LineFeedback<PjRef<P>, P::SubproofReference> {
+    match prf.verify_line(r) {
+        Ok(()) => {}
+        Err(ProofCheckError::Gap) => return LineFeedback::Gap,
+        Err(err) => return LineFeedback::Incorrect(err),
+    }
+    let lints = lints(prf, r, goals);
+    if lints.is_empty() {
+        LineFeedback::Correct
+    } else {
+        LineFeedback::Warnings(lints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+    use crate::proofs::pooledproof::PooledProof;
+    use crate::proofs::Justification;
+
+    use frunk_core::HList;
+
+    #[test]
+    fn test_lints() {
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let r1 = Coproduct::inject(prf.add_premise(p("A")));
+        let r2 = Coproduct::inject(prf.add_premise(p("B")));
+        let sub = prf.add_subproof();
+        let (r3, r4) = prf
+            .with_mut_subproof(&sub, |sub| {
+                let r3 = sub.add_premise(p("C"));
+                let r4 = sub.add_step(Justification(p("A"), RuleM::Reiteration, vec![r1], vec![]));
+                (Coproduct::inject(r3), Coproduct::inject(r4))
+            })
+            .unwrap();
+        let r5 = Coproduct::inject(prf.add_step(Justification(p("C -> A"), RuleM::ImpIntro, vec![], vec![sub])));
+        let r6 = Coproduct::inject(prf.add_step(Justification(p("B & B"), RuleM::AndIntro, vec![r2], vec![])));
+        let r7 = Coproduct::inject(prf.add_step(Justification(p("B"), RuleM::AndElim, vec![r6], vec![])));
+
+        // Reiteration doesn't count as restating, and premises aren't linted
+        assert_eq!(lints(&prf, &r1, &[]), vec![]);
+        assert_eq!(lints(&prf, &r3, &[]), vec![]);
+        assert_eq!(lints(&prf, &r4, &[p("C -> A")]), vec![]);
+        assert_eq!(lints(&prf, &r7, &[]), vec![Lint::Restates(r2)]);
+        // Without goals, the proof concludes with its last step
+        assert_eq!(lints(&prf, &r5, &[]), vec![Lint::UnusedStep]);
+        assert_eq!(lints(&prf, &r6, &[]), vec![]);
+        assert_eq!(lints(&prf, &r6, &[p("C -> A")]), vec![Lint::UnusedStep]);
+        // Until a goal is proved, nothing counts as unused
+        assert_eq!(lints(&prf, &r5, &[p("D")]), vec![]);
+
+        assert_eq!(check_line(&prf, &r6, &[]), LineFeedback::Correct);
+        assert_eq!(check_line(&prf, &r7, &[p("B")]), LineFeedback::Warnings(vec![Lint::Restates(r2)]));
+        let r8 = Coproduct::inject(prf.add_step(Justification(p("A & C"), RuleM::AndIntro, vec![r1, r2], vec![])));
+        assert!(!check_line(&prf, &r8, &[]).is_correct());
+        assert_eq!(Lint::Restates(r3).message(|_| Some(3)), "This restates line 3, which could be cited instead.");
+
+        // Equivalent lines only count if the step doesn't cite them
+        let r9 = Coproduct::inject(prf.add_step(Justification(p("~~B"), RuleM::DoubleNegation, vec![r7], vec![])));
+        assert_eq!(lints(&prf, &r9, &[]), vec![Lint::Equivalent(r2)]);
+        assert_eq!(lints(&prf, &r6, &[p("~~B")]), vec![]);
+
+        // Reordering a line restates it, rather than just being equivalent
+        let r10 = Coproduct::inject(prf.add_premise(p("forall x (P(x) & Q(x))")));
+        let r11 = Coproduct::inject(prf.add_step(Justification(p("forall y (Q(y) & P(y))"), RuleM::AssociationCommutation, vec![r10], vec![])));
+        assert_eq!(lints(&prf, &r11, &[]), vec![Lint::Restates(r10)]);
+
+        assert_eq!(input_lints::<usize>("(A & B) -> C", false), vec![]);
+        assert_eq!(input_lints::<usize>("(A & B) -> C", true), vec![Lint::NotFullyParenthesized]);
+        assert_eq!(input_lints::<usize>("((A & B) -> C)", true), vec![]);
+    }
+}
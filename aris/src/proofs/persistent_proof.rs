@@ -0,0 +1,491 @@
+/*!
+# Structure
+`PersistentProof` stores the same three pools as `PooledProof` (premises, justifications, and subproofs, plus the
+`containing_subproof` back-references), but every pool and every line list is an `im` persistent collection instead of a
+`BTreeMap`/`ZipperVec`. Cloning a `PersistentProof` is therefore O(1), and a clone shares all of its unmodified structure with
+the original; editing either copy only duplicates the O(log n) path from the root of the touched collection to the edited entry.
+
+There are no raw pointers into the pools, so a subproof is just a `PersistentProof` whose `current` field names the subproof it
+views. `lookup_subproof` hands out such a view (sharing the pools), and `with_mut_subproof` temporarily moves the pools into one.
+
+# Snapshots
+Since clones are cheap, history is just a stack of old values, which is what `History` provides:
+
+```
+#[macro_use] extern crate frunk_core;
+use aris::expr::Expr;
+use aris::parser::parse_unwrap as p;
+use aris::proofs::Proof;
+use aris::proofs::persistent_proof::{History, PersistentProof};
+
+let mut history = History::new(PersistentProof::<HList![Expr]>::new());
+history.apply(|prf| prf.add_premise(p("A")));
+history.apply(|prf| prf.add_premise(p("B")));
+assert_eq!(history.current().premises().len(), 2);
+assert!(history.undo());
+assert_eq!(history.current().premises().len(), 1);
+assert!(history.redo());
+assert_eq!(history.current().premises().len(), 2);
+```
+*/
+
+use crate::expr::Expr;
+use crate::proofs::pooledproof::JustKey;
+use crate::proofs::pooledproof::PremKey;
+use crate::proofs::pooledproof::SubKey;
+use crate::proofs::DisplayIndented;
+use crate::proofs::JsRef;
+use crate::proofs::Justification;
+use crate::proofs::PjRef;
+use crate::proofs::PjsRef;
+use crate::proofs::Proof;
+use crate::proofs::Revision;
+use crate::rules::ProofCheckError;
+use crate::rules::RuleT;
+
+use frunk_core::coproduct::Coproduct;
+use frunk_core::hlist::HCons;
+use frunk_core::Coprod;
+use im::OrdMap;
+use im::Vector;
+
+type PersistentRef = Coprod!(PremKey, JustKey);
+type JsKey = Coprod!(JustKey, SubKey);
+type PjsKey = Coprod!(PremKey, JustKey, SubKey);
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct LineLists {
+    premise_list: Vector<PremKey>,
+    line_list: Vector<JsKey>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Pools<T: Clone> {
+    prem_map: OrdMap<PremKey, T>,
+    just_map: OrdMap<JustKey, Justification<T, PersistentRef, SubKey>>,
+    sub_map: OrdMap<SubKey, LineLists>,
+    containing_subproof: OrdMap<PjsKey, SubKey>,
+    root: LineLists,
+    revision: Revision,
+}
+
+/// Insert `val` before or after `rel` in `list`, or at the end if `rel` isn't present
+fn insert_relative<A: Clone + PartialEq>(list: &mut Vector<A>, val: A, rel: &A, after: bool) {
+    match list.index_of(rel) {
+        Some(i) => list.insert(if after { i + 1 } else { i }, val),
+        None => list.push_back(val),
+    }
+}
+
+impl<T: Clone> Pools<T> {
+    fn new() -> Self {
+        Pools { prem_map: OrdMap::new(), just_map: OrdMap::new(), sub_map: OrdMap::new(), containing_subproof: OrdMap::new(), root: LineLists::default(), revision: Revision::next() }
+    }
+    fn next_premkey(&self) -> PremKey {
+        PremKey(self.prem_map.get_max().map(|(key, _)| key.0 + 1).unwrap_or(0))
+    }
+    fn next_justkey(&self) -> JustKey {
+        JustKey(self.just_map.get_max().map(|(key, _)| key.0 + 1).unwrap_or(0))
+    }
+    fn next_subkey(&self) -> SubKey {
+        SubKey(self.sub_map.get_max().map(|(key, _)| key.0 + 1).unwrap_or(0))
+    }
+    /// Line lists of a subproof, where `None` is the top level proof
+    fn lists(&self, sub: Option<SubKey>) -> Option<&LineLists> {
+        match sub {
+            Some(sk) => self.sub_map.get(&sk),
+            None => Some(&self.root),
+        }
+    }
+    fn lists_mut(&mut self, sub: Option<SubKey>) -> Option<&mut LineLists> {
+        match sub {
+            Some(sk) => self.sub_map.get_mut(&sk),
+            None => Some(&mut self.root),
+        }
+    }
+    fn set_parent(&mut self, idx: PjsKey, parent: Option<SubKey>) {
+        if let Some(sk) = parent {
+            self.containing_subproof.insert(idx, sk);
+        }
+    }
+    fn parent_of(&self, idx: &PjsKey) -> Option<SubKey> {
+        self.containing_subproof.get(idx).copied()
+    }
+    fn transitive_parents(&self, mut idx: PjsKey) -> Vec<SubKey> {
+        let mut result = vec![];
+        while let Some(s) = self.parent_of(&idx) {
+            result.push(s);
+            idx = Coproduct::inject(s);
+        }
+        result
+    }
+    /// Remove every reference to a removed line from the justifications, only touching the justifications that cite it
+    fn remove_line_helper(&mut self, idx: &PjsKey) {
+        use frunk_core::coproduct::Coproduct::{Inl, Inr};
+        let (dep, sdep): (Option<PersistentRef>, Option<SubKey>) = match idx {
+            Inl(pr) => (Some(Coproduct::inject(*pr)), None),
+            Inr(Inl(jr)) => (Some(Coproduct::inject(*jr)), None),
+            Inr(Inr(Inl(sr))) => (None, Some(*sr)),
+            Inr(Inr(Inr(void))) => match *void {},
+        };
+        let citing = self.just_map.iter().filter(|(_, j)| dep.is_some_and(|d| j.2.contains(&d)) || sdep.is_some_and(|s| j.3.contains(&s))).map(|(k, _)| *k).collect::<Vec<_>>();
+        for k in citing {
+            if let Some(Justification(_, _, deps, sdeps)) = self.just_map.get_mut(&k) {
+                deps.retain(|d| Some(*d) != dep);
+                sdeps.retain(|s| Some(*s) != sdep);
+            }
+        }
+        self.containing_subproof.remove(idx);
+    }
+    fn remove_premise(&mut self, idx: &PremKey) {
+        let parent = self.parent_of(&Coproduct::inject(*idx));
+        self.prem_map.remove(idx);
+        if let Some(lists) = self.lists_mut(parent) {
+            lists.premise_list.retain(|x| x != idx);
+        }
+        self.remove_line_helper(&Coproduct::inject(*idx));
+    }
+    fn remove_step(&mut self, idx: &JustKey) {
+        let parent = self.parent_of(&Coproduct::inject(*idx));
+        self.just_map.remove(idx);
+        if let Some(lists) = self.lists_mut(parent) {
+            lists.line_list.retain(|x| x.get() != Some(idx));
+        }
+        self.remove_line_helper(&Coproduct::inject(*idx));
+    }
+    fn remove_subproof(&mut self, idx: &SubKey) {
+        use frunk_core::coproduct::Coproduct::{Inl, Inr};
+        let parent = self.parent_of(&Coproduct::inject(*idx));
+        if let Some(sub) = self.sub_map.remove(idx) {
+            for prem in sub.premise_list.iter() {
+                self.remove_premise(prem);
+            }
+            for line in sub.line_list.iter() {
+                match line {
+                    Inl(jr) => self.remove_step(jr),
+                    Inr(Inl(sr)) => self.remove_subproof(sr),
+                    Inr(Inr(void)) => match *void {},
+                }
+            }
+        }
+        if let Some(lists) = self.lists_mut(parent) {
+            lists.line_list.retain(|x| x.get() != Some(idx));
+        }
+        self.remove_line_helper(&Coproduct::inject(*idx));
+    }
+}
+
+/// An immutable-by-default proof whose clones share structure; see the module documentation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PersistentProof<T: Clone> {
+    pools: Pools<T>,
+    current: Option<SubKey>,
+}
+
+impl<Tail: Default + Clone> Proof for PersistentProof<HCons<Expr, Tail>> {
+    type PremiseReference = PremKey;
+    type JustificationReference = JustKey;
+    type SubproofReference = SubKey;
+    type Subproof = Self;
+    fn new() -> Self {
+        PersistentProof { pools: Pools::new(), current: None }
+    }
+    fn top_level_proof(&self) -> &Self {
+        self
+    }
+    fn lookup_premise(&self, r: &Self::PremiseReference) -> Option<Expr> {
+        self.pools.prem_map.get(r).map(|x| x.head.clone())
+    }
+    fn lookup_step(&self, r: &Self::JustificationReference) -> Option<Justification<Expr, PjRef<Self>, Self::SubproofReference>> {
+        self.pools.just_map.get(r).map(|x| x.clone().map0(|y| y.head))
+    }
+    fn lookup_subproof(&self, r: &Self::SubproofReference) -> Option<Self::Subproof> {
+        if self.pools.sub_map.contains_key(r) {
+            Some(PersistentProof { pools: self.pools.clone(), current: Some(*r) })
+        } else {
+            None
+        }
+    }
+    fn with_mut_premise<A, F: FnOnce(&mut Expr) -> A>(&mut self, r: &Self::PremiseReference, f: F) -> Option<A> {
+        self.pools.revision = Revision::next();
+        self.pools.prem_map.get_mut(r).map(|p| f(&mut p.head))
+    }
+    fn with_mut_step<A, F: FnOnce(&mut Justification<Expr, PjRef<Self>, Self::SubproofReference>) -> A>(&mut self, r: &Self::JustificationReference, f: F) -> Option<A> {
+        self.pools.revision = Revision::next();
+        self.pools.just_map.get_mut(r).map(|j_hcons: &mut Justification<HCons<Expr, Tail>, _, _>| {
+            let mut j_expr: Justification<Expr, _, _> = Justification(j_hcons.0.head.clone(), j_hcons.1, j_hcons.2.clone(), j_hcons.3.clone());
+            let ret = f(&mut j_expr);
+            j_hcons.0.head = j_expr.0;
+            j_hcons.1 = j_expr.1;
+            j_hcons.2 = j_expr.2;
+            j_hcons.3 = j_expr.3;
+            ret
+        })
+    }
+    fn with_mut_subproof<A, F: FnOnce(&mut Self::Subproof) -> A>(&mut self, r: &Self::SubproofReference, f: F) -> Option<A> {
+        self.pools.revision = Revision::next();
+        if !self.pools.sub_map.contains_key(r) {
+            return None;
+        }
+        // Move the pools into the view rather than cloning them, so that edits through it don't have to copy shared nodes
+        let mut sub = PersistentProof { pools: std::mem::replace(&mut self.pools, Pools::new()), current: Some(*r) };
+        let ret = f(&mut sub);
+        self.pools = sub.pools;
+        Some(ret)
+    }
+    fn add_premise(&mut self, e: Expr) -> Self::PremiseReference {
+        self.pools.revision = Revision::next();
+        let idx = self.pools.next_premkey();
+        self.pools.prem_map.insert(idx, HCons { head: e, tail: Tail::default() });
+        self.pools.set_parent(Coproduct::inject(idx), self.current);
+        if let Some(lists) = self.pools.lists_mut(self.current) {
+            lists.premise_list.push_back(idx);
+        }
+        idx
+    }
+    fn add_subproof(&mut self) -> Self::SubproofReference {
+        self.pools.revision = Revision::next();
+        let idx = self.pools.next_subkey();
+        self.pools.set_parent(Coproduct::inject(idx), self.current);
+        self.pools.sub_map.insert(idx, LineLists::default());
+        if let Some(lists) = self.pools.lists_mut(self.current) {
+            lists.line_list.push_back(Coproduct::inject(idx));
+        }
+        idx
+    }
+    fn add_step(&mut self, just: Justification<Expr, PjRef<Self>, Self::SubproofReference>) -> Self::JustificationReference {
+        self.pools.revision = Revision::next();
+        let idx = self.pools.next_justkey();
+        self.pools.set_parent(Coproduct::inject(idx), self.current);
+
+        // As in PooledProof, silently drop subproof dependencies on a subproof containing this step
+        let tps = self.pools.transitive_parents(Coproduct::inject(idx));
+        let Justification(e, r, deps, mut sdeps) = just;
+        sdeps.retain(|x| !tps.contains(x));
+
+        if let Some(lists) = self.pools.lists_mut(self.current) {
+            lists.line_list.push_back(Coproduct::inject(idx));
+        }
+        self.pools.just_map.insert(idx, Justification(HCons { head: e, tail: Tail::default() }, r, deps, sdeps));
+        idx
+    }
+    fn add_premise_relative(&mut self, e: Expr, r: &Self::PremiseReference, after: bool) -> Self::PremiseReference {
+        self.pools.revision = Revision::next();
+        let idx = self.pools.next_premkey();
+        self.pools.prem_map.insert(idx, HCons { head: e, tail: Tail::default() });
+        let parent = self.pools.parent_of(&Coproduct::inject(*r));
+        self.pools.set_parent(Coproduct::inject(idx), parent);
+        if let Some(lists) = self.pools.lists_mut(parent) {
+            insert_relative(&mut lists.premise_list, idx, r, after);
+        }
+        idx
+    }
+    fn add_subproof_relative(&mut self, r: &JsRef<Self>, after: bool) -> Self::SubproofReference {
+        self.pools.revision = Revision::next();
+        let idx = self.pools.next_subkey();
+        self.pools.sub_map.insert(idx, LineLists::default());
+        let parent = self.pools.parent_of(&crate::proofs::js_to_pjs::<Self>(*r));
+        self.pools.set_parent(Coproduct::inject(idx), parent);
+        if let Some(lists) = self.pools.lists_mut(parent) {
+            insert_relative(&mut lists.line_list, Coproduct::inject(idx), r, after);
+        }
+        idx
+    }
+    fn add_step_relative(&mut self, just: Justification<Expr, PjRef<Self>, Self::SubproofReference>, r: &JsRef<Self>, after: bool) -> Self::JustificationReference {
+        self.pools.revision = Revision::next();
+        let idx = self.pools.next_justkey();
+        self.pools.just_map.insert(idx, Justification(HCons { head: just.0, tail: Tail::default() }, just.1, just.2, just.3));
+        let parent = self.pools.parent_of(&crate::proofs::js_to_pjs::<Self>(*r));
+        self.pools.set_parent(Coproduct::inject(idx), parent);
+        if let Some(lists) = self.pools.lists_mut(parent) {
+            insert_relative(&mut lists.line_list, Coproduct::inject(idx), r, after);
+        }
+        idx
+    }
+    fn remove_line(&mut self, r: &PjRef<Self>) {
+        self.pools.revision = Revision::next();
+        use frunk_core::coproduct::Coproduct::{Inl, Inr};
+        match r {
+            Inl(pr) => self.pools.remove_premise(pr),
+            Inr(Inl(jr)) => self.pools.remove_step(jr),
+            Inr(Inr(void)) => match *void {},
+        }
+    }
+    fn remove_subproof(&mut self, r: &Self::SubproofReference) {
+        self.pools.revision = Revision::next();
+        self.pools.remove_subproof(r);
+    }
+    fn premises(&self) -> Vec<Self::PremiseReference> {
+        self.pools.lists(self.current).map(|lists| lists.premise_list.iter().cloned().collect()).unwrap_or_default()
+    }
+    fn lines(&self) -> Vec<JsRef<Self>> {
+        self.pools.lists(self.current).map(|lists| lists.line_list.iter().cloned().collect()).unwrap_or_default()
+    }
+    fn parent_of_line(&self, r: &PjsRef<Self>) -> Option<Self::SubproofReference> {
+        self.pools.parent_of(r)
+    }
+    fn revision(&self) -> u64 {
+        self.pools.revision.0
+    }
+    fn verify_line(&self, r: &PjRef<Self>) -> Result<(), ProofCheckError<PjRef<Self>, Self::SubproofReference>> {
+        use self::Coproduct::{Inl, Inr};
+        match self.lookup_pj(r) {
+            None => Err(ProofCheckError::LineDoesNotExist(*r)),
+            Some(Inl(_)) => Ok(()), // premises are always valid
+            Some(Inr(Inl(Justification(conclusion, rule, deps, sdeps)))) => {
+                for dep in deps.iter() {
+                    let dep_co = Coproduct::inject(*dep);
+                    if !self.can_reference_dep(r, &dep_co) && !self.can_share_dep(r, &dep_co) {
+                        return Err(ProofCheckError::ReferencesLaterLine(*r, dep_co));
+                    }
+                }
+                for sdep in sdeps.iter() {
+                    let sdep_co = Coproduct::inject(*sdep);
+                    if !self.can_reference_dep(r, &sdep_co) && !self.can_share_dep(r, &sdep_co) {
+                        return Err(ProofCheckError::ReferencesLaterLine(*r, sdep_co));
+                    }
+                }
+                let outside = self.lines_to_reiterate(r, rule, &deps);
+                rule.check(self, conclusion, deps, sdeps)?;
+                if !outside.is_empty() {
+                    return Err(ProofCheckError::NeedsReiteration(outside));
+                }
+                Ok(())
+            }
+            Some(Inr(Inr(void))) => match void {},
+        }
+    }
+}
+
+impl<Tail: Clone> DisplayIndented for PersistentProof<HCons<Expr, Tail>> {
+    fn display_indented(&self, fmt: &mut std::fmt::Formatter, indent: usize, linecount: &mut usize) -> std::result::Result<(), std::fmt::Error> {
+        fn aux<Tail: Clone>(pools: &Pools<HCons<Expr, Tail>>, fmt: &mut std::fmt::Formatter, indent: usize, linecount: &mut usize, lists: &LineLists) -> std::result::Result<(), std::fmt::Error> {
+            for idx in lists.premise_list.iter() {
+                write!(fmt, "{linecount}:\t")?;
+                for _ in 0..indent {
+                    write!(fmt, "| ")?;
+                }
+                writeln!(fmt, "{}", pools.prem_map[idx].head)?;
+                *linecount += 1;
+            }
+            write!(fmt, "\t")?;
+            for _ in 0..indent {
+                write!(fmt, "| ")?;
+            }
+            for _ in 0..10 {
+                write!(fmt, "-")?;
+            }
+            writeln!(fmt)?;
+            for line in lists.line_list.iter() {
+                match line.uninject::<JustKey, _>() {
+                    Ok(justkey) => pools.just_map[&justkey].display_indented(fmt, indent, linecount)?,
+                    Err(line) => aux(pools, fmt, indent + 1, linecount, &pools.sub_map[&line.uninject::<SubKey, _>().unwrap()])?,
+                }
+            }
+            Ok(())
+        }
+        match self.pools.lists(self.current) {
+            Some(lists) => aux(&self.pools, fmt, indent, linecount, lists),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<Tail: Clone> std::fmt::Display for PersistentProof<HCons<Expr, Tail>> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.display_indented(f, 1, &mut 1)
+    }
+}
+
+/// Linear undo/redo history over cheaply clonable values (e.g. `PersistentProof`)
+#[derive(Clone, Debug)]
+pub struct History<P> {
+    past: Vec<P>,
+    present: P,
+    future: Vec<P>,
+}
+
+impl<P: Clone> History<P> {
+    /// Start a history whose only state is `initial`
+    pub fn new(initial: P) -> Self {
+        History { past: vec![], present: initial, future: vec![] }
+    }
+    /// The current state
+    pub fn current(&self) -> &P {
+        &self.present
+    }
+    /// Edit the current state, recording the previous one so it can be undone. This discards any redoable states.
+    pub fn apply<A, F: FnOnce(&mut P) -> A>(&mut self, f: F) -> A {
+        self.past.push(self.present.clone());
+        self.future.clear();
+        f(&mut self.present)
+    }
+    /// Whether there is a state to go back to
+    pub fn can_undo(&self) -> bool {
+        !self.past.is_empty()
+    }
+    /// Whether there is an undone state to go forward to
+    pub fn can_redo(&self) -> bool {
+        !self.future.is_empty()
+    }
+    /// Go back one state, returning whether anything happened
+    pub fn undo(&mut self) -> bool {
+        match self.past.pop() {
+            Some(prev) => {
+                self.future.push(std::mem::replace(&mut self.present, prev));
+                true
+            }
+            None => false,
+        }
+    }
+    /// Go forward one undone state, returning whether anything happened
+    pub fn redo(&mut self) -> bool {
+        match self.future.pop() {
+            Some(next) => {
+                self.past.push(std::mem::replace(&mut self.present, next));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rules::RuleM;
+
+    use frunk_core::HList;
+
+    #[test]
+    fn test_snapshots_are_independent() {
+        use crate::parser::parse_unwrap as p;
+        let mut prf = PersistentProof::<HList![Expr]>::new();
+        let r1 = prf.add_premise(p("A"));
+        let sub = prf.add_subproof();
+        prf.with_mut_subproof(&sub, |sub| {
+            sub.add_premise(p("B"));
+        })
+        .unwrap();
+        let snapshot = prf.clone();
+
+        let r2 = prf.add_step(Justification(p("A & A"), RuleM::AndIntro, vec![Coproduct::inject(r1)], vec![]));
+        prf.with_mut_premise(&r1, |e| *e = p("C")).unwrap();
+        prf.remove_subproof(&sub);
+
+        assert_eq!(prf.lookup_premise(&r1), Some(p("C")));
+        assert_eq!(prf.lines(), vec![Coproduct::inject(r2)]);
+        assert_eq!(snapshot.lookup_premise(&r1), Some(p("A")));
+        assert_eq!(snapshot.lookup_step(&r2), None);
+        assert_eq!(snapshot.lines(), vec![Coproduct::inject(sub)]);
+        assert_eq!(snapshot.lookup_subproof(&sub).unwrap().premises().len(), 1);
+    }
+
+    #[test]
+    fn prettyprint_persistent() {
+        let prf: PersistentProof<HList![Expr]> = crate::proofs::proof_tests::demo_proof_1();
+        println!("{prf:?}\n{prf}\n");
+        println!("{:?}\n{:?}\n", prf.premises(), prf.lines());
+    }
+}
@@ -114,6 +114,7 @@ use crate::proofs::Justification;
 use crate::proofs::PjRef;
 use crate::proofs::PjsRef;
 use crate::proofs::Proof;
+use crate::proofs::Revision;
 use crate::rules::ProofCheckError;
 use crate::rules::RuleT;
 use crate::zipper_vec::ZipperVec;
@@ -126,13 +127,13 @@ use frunk_core::hlist::HCons;
 use frunk_core::Coprod;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct PremKey(usize);
+pub struct PremKey(pub(super) usize);
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct JustKey(usize);
+pub struct JustKey(pub(super) usize);
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct SubKey(usize);
+pub struct SubKey(pub(super) usize);
 
 type PooledRef = Coprod!(PremKey, JustKey);
 type PjsKey = Coprod!(PremKey, JustKey, SubKey);
@@ -143,11 +144,12 @@ pub struct Pools<T> {
     just_map: BTreeMap<JustKey, Justification<T, PooledRef, SubKey>>,
     sub_map: BTreeMap<SubKey, PooledSubproof<T>>,
     containing_subproof: BTreeMap<PjsKey, SubKey>,
+    revision: Revision,
 }
 
 impl<T> Pools<T> {
     fn new() -> Self {
-        Pools { prem_map: BTreeMap::new(), just_map: BTreeMap::new(), sub_map: BTreeMap::new(), containing_subproof: BTreeMap::new() }
+        Pools { prem_map: BTreeMap::new(), just_map: BTreeMap::new(), sub_map: BTreeMap::new(), containing_subproof: BTreeMap::new(), revision: Revision::next() }
     }
     fn subproof_to_subkey(&self, sub: &PooledSubproof<T>) -> Option<SubKey> {
         for (k, v) in self.sub_map.iter() {
@@ -201,7 +203,7 @@ impl<T> Pools<T> {
     fn remove_premise(&mut self, idx: &PremKey) {
         self.prem_map.remove(idx);
         for (_, v) in self.sub_map.iter_mut() {
-            let premise_list = std::mem::replace(&mut v.premise_list, ZipperVec::new());
+            let premise_list = std::mem::take(&mut v.premise_list);
             v.premise_list = ZipperVec::from_vec(premise_list.iter().filter(|x| x != &idx).cloned().collect());
         }
         self.remove_line_helper(&Coproduct::inject(*idx));
@@ -209,7 +211,7 @@ impl<T> Pools<T> {
     fn remove_step(&mut self, idx: &JustKey) {
         self.just_map.remove(idx);
         for (_, v) in self.sub_map.iter_mut() {
-            let line_list = std::mem::replace(&mut v.line_list, ZipperVec::new());
+            let line_list = std::mem::take(&mut v.line_list);
             v.line_list = ZipperVec::from_vec(line_list.iter().filter(|x| x.get() != Some(idx)).cloned().collect());
         }
         self.remove_line_helper(&Coproduct::inject(*idx));
@@ -233,26 +235,49 @@ impl<T> Pools<T> {
             }
         }
         for (_, v) in self.sub_map.iter_mut() {
-            let line_list = std::mem::replace(&mut v.line_list, ZipperVec::new());
+            let line_list = std::mem::take(&mut v.line_list);
             v.line_list = ZipperVec::from_vec(line_list.iter().filter(|x| x.get() != Some(idx)).cloned().collect());
         }
         self.remove_line_helper(&Coproduct::inject(*idx));
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct PooledProof<T> {
     pools: Box<Pools<T>>,
     proof: PooledSubproof<T>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+impl<T: Clone> Clone for PooledProof<T> {
+    /// Copy the proof, pointing the copy's subproofs at its own pools, so
+    /// editing either proof leaves the other alone
+    fn clone(&self) -> Self {
+        let mut pools = self.pools.clone();
+        let p: *mut Pools<T> = &mut *pools;
+        for sub in pools.sub_map.values_mut() {
+            sub.pools = p;
+        }
+        PooledProof { pools, proof: PooledSubproof { pools: p, ..self.proof.clone() } }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct PooledSubproof<T> {
     pools: *mut Pools<T>,
     premise_list: ZipperVec<PremKey>,
     line_list: ZipperVec<Coproduct<JustKey, Coproduct<SubKey, frunk_core::coproduct::CNil>>>,
 }
 
+// Subproofs are compared by their lines, not by which pools they point to, so
+// a copy of a proof equals the original
+impl<T> PartialEq for PooledSubproof<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.premise_list == other.premise_list && self.line_list == other.line_list
+    }
+}
+
+impl<T> Eq for PooledSubproof<T> {}
+
 impl<T> PooledSubproof<T> {
     /// PooledSubproof::new requires for safety that p points to something that won't move (e.g. a heap-allocated Box)
     fn new(p: &mut Pools<T>) -> Self {
@@ -300,10 +325,12 @@ impl<Tail: Default + Clone> Proof for PooledSubproof<HCons<Expr, Tail>> {
     }
     fn with_mut_premise<A, F: FnOnce(&mut Expr) -> A>(&mut self, r: &Self::PremiseReference, f: F) -> Option<A> {
         let pools = unsafe { &mut *self.pools };
+        pools.revision = Revision::next();
         pools.prem_map.get_mut(r).map(|p: &mut HCons<Expr, Tail>| f(p.get_mut()))
     }
     fn with_mut_step<A, F: FnOnce(&mut Justification<Expr, PjRef<Self>, Self::SubproofReference>) -> A>(&mut self, r: &Self::JustificationReference, f: F) -> Option<A> {
         let pools = unsafe { &mut *self.pools };
+        pools.revision = Revision::next();
         pools.just_map.get_mut(r).map(|j_hcons: &mut Justification<HCons<Expr, Tail>, _, _>| {
             let mut j_expr: Justification<Expr, _, _> = Justification(j_hcons.0.get().clone(), j_hcons.1, j_hcons.2.clone(), j_hcons.3.clone());
             let ret = f(&mut j_expr);
@@ -315,10 +342,13 @@ impl<Tail: Default + Clone> Proof for PooledSubproof<HCons<Expr, Tail>> {
         })
     }
     fn with_mut_subproof<A, F: FnOnce(&mut Self::Subproof) -> A>(&mut self, r: &Self::SubproofReference, f: F) -> Option<A> {
-        unsafe { &mut *self.pools }.sub_map.get_mut(r).map(f)
+        let pools = unsafe { &mut *self.pools };
+        pools.revision = Revision::next();
+        pools.sub_map.get_mut(r).map(f)
     }
     fn add_premise(&mut self, e: Expr) -> Self::PremiseReference {
         let pools = unsafe { &mut *self.pools };
+        pools.revision = Revision::next();
         let idx = pools.next_premkey();
         pools.prem_map.insert(idx, HCons { head: e, tail: Tail::default() });
         pools.set_parent(Coproduct::inject(idx), self);
@@ -327,6 +357,7 @@ impl<Tail: Default + Clone> Proof for PooledSubproof<HCons<Expr, Tail>> {
     }
     fn add_subproof(&mut self) -> Self::SubproofReference {
         let pools = unsafe { &mut *self.pools };
+        pools.revision = Revision::next();
         let idx = pools.next_subkey();
         pools.set_parent(Coproduct::inject(idx), self);
         let sub = PooledSubproof::new(pools);
@@ -336,6 +367,7 @@ impl<Tail: Default + Clone> Proof for PooledSubproof<HCons<Expr, Tail>> {
     }
     fn add_step(&mut self, just: Justification<Expr, PjRef<Self>, Self::SubproofReference>) -> Self::JustificationReference {
         let pools = unsafe { &mut *self.pools };
+        pools.revision = Revision::next();
         let idx = pools.next_justkey();
         pools.set_parent(Coproduct::inject(idx), self);
 
@@ -357,6 +389,7 @@ impl<Tail: Default + Clone> Proof for PooledSubproof<HCons<Expr, Tail>> {
     }
     fn add_premise_relative(&mut self, e: Expr, r: &Self::PremiseReference, after: bool) -> Self::PremiseReference {
         let pools = unsafe { &mut *self.pools };
+        pools.revision = Revision::next();
         let idx = pools.next_premkey();
         pools.prem_map.insert(idx, HCons { head: e, tail: Tail::default() });
         if let Some(s) = pools.parent_of(&Coproduct::inject(*r)) {
@@ -371,6 +404,7 @@ impl<Tail: Default + Clone> Proof for PooledSubproof<HCons<Expr, Tail>> {
     }
     fn add_subproof_relative(&mut self, r: &JsRef<Self>, after: bool) -> Self::SubproofReference {
         let pools = unsafe { &mut *self.pools };
+        pools.revision = Revision::next();
         let idx = pools.next_subkey();
         let sub = PooledSubproof::new(pools);
         pools.sub_map.insert(idx, sub);
@@ -386,6 +420,7 @@ impl<Tail: Default + Clone> Proof for PooledSubproof<HCons<Expr, Tail>> {
     }
     fn add_step_relative(&mut self, just: Justification<Expr, PjRef<Self>, Self::SubproofReference>, r: &JsRef<Self>, after: bool) -> Self::JustificationReference {
         let pools = unsafe { &mut *self.pools };
+        pools.revision = Revision::next();
         let idx = pools.next_justkey();
         // TODO: occurs-before check
         pools.just_map.insert(idx, Justification(HCons { head: just.0, tail: Tail::default() }, just.1, just.2, just.3));
@@ -401,10 +436,12 @@ impl<Tail: Default + Clone> Proof for PooledSubproof<HCons<Expr, Tail>> {
     }
     fn remove_line(&mut self, r: &PjRef<Self>) {
         let pools = unsafe { &mut *self.pools };
+        pools.revision = Revision::next();
         pools.remove_line(r);
     }
     fn remove_subproof(&mut self, r: &Self::SubproofReference) {
         let pools = unsafe { &mut *self.pools };
+        pools.revision = Revision::next();
         pools.remove_subproof(r);
     }
     fn premises(&self) -> Vec<Self::PremiseReference> {
@@ -417,6 +454,9 @@ impl<Tail: Default + Clone> Proof for PooledSubproof<HCons<Expr, Tail>> {
         let pools = unsafe { &mut *self.pools };
         pools.parent_of(r)
     }
+    fn revision(&self) -> u64 {
+        unsafe { &*self.pools }.revision.0
+    }
     fn verify_line(&self, r: &PjRef<Self>) -> Result<(), ProofCheckError<PjRef<Self>, Self::SubproofReference>> {
         use self::Coproduct::{Inl, Inr};
         match self.lookup_pj(r) {
@@ -431,17 +471,22 @@ impl<Tail: Default + Clone> Proof for PooledSubproof<HCons<Expr, Tail>> {
 
                 for dep in deps.iter() {
                     let dep_co = Coproduct::inject(*dep);
-                    if !self.can_reference_dep(r, &dep_co) {
+                    if !self.can_reference_dep(r, &dep_co) && !self.can_share_dep(r, &dep_co) {
                         return Err(ProofCheckError::ReferencesLaterLine(*r, dep_co));
                     }
                 }
                 for sdep in sdeps.iter() {
                     let sdep_co = Coproduct::inject(*sdep);
-                    if !self.can_reference_dep(r, &sdep_co) {
+                    if !self.can_reference_dep(r, &sdep_co) && !self.can_share_dep(r, &sdep_co) {
                         return Err(ProofCheckError::ReferencesLaterLine(*r, sdep_co));
                     }
                 }
-                rule.check(self, conclusion, deps, sdeps)
+                let outside = self.lines_to_reiterate(r, rule, &deps);
+                rule.check(self, conclusion, deps, sdeps)?;
+                if !outside.is_empty() {
+                    return Err(ProofCheckError::NeedsReiteration(outside));
+                }
+                Ok(())
             }
             Some(Inr(Inr(void))) => match void {},
         }
@@ -498,14 +543,14 @@ impl<Tail: Default + Clone> Proof for PooledProof<HCons<Expr, Tail>> {
         self.proof.add_step(just)
     }
     fn remove_line(&mut self, r: &PjRef<Self>) {
-        let premise_list = std::mem::replace(&mut self.proof.premise_list, ZipperVec::new());
+        let premise_list = std::mem::take(&mut self.proof.premise_list);
         self.proof.premise_list = ZipperVec::from_vec(premise_list.iter().filter(|x| Some(x) != r.get().as_ref()).cloned().collect());
-        let line_list = std::mem::replace(&mut self.proof.line_list, ZipperVec::new());
+        let line_list = std::mem::take(&mut self.proof.line_list);
         self.proof.line_list = ZipperVec::from_vec(line_list.iter().filter(|x| (x.get::<JustKey, _>() != r.get()) || x.get::<SubKey, _>().is_some()).cloned().collect());
         self.proof.remove_line(r);
     }
     fn remove_subproof(&mut self, r: &Self::SubproofReference) {
-        let line_list = std::mem::replace(&mut self.proof.line_list, ZipperVec::new());
+        let line_list = std::mem::take(&mut self.proof.line_list);
         self.proof.line_list = ZipperVec::from_vec(line_list.iter().filter(|x| x.get() != Some(r)).cloned().collect());
         self.proof.remove_subproof(r);
     }
@@ -521,6 +566,9 @@ impl<Tail: Default + Clone> Proof for PooledProof<HCons<Expr, Tail>> {
     fn verify_line(&self, r: &PjRef<Self>) -> Result<(), ProofCheckError<PjRef<Self>, Self::SubproofReference>> {
         self.proof.verify_line(r)
     }
+    fn revision(&self) -> u64 {
+        self.proof.revision()
+    }
 }
 
 impl<Tail> DisplayIndented for PooledProof<HCons<Expr, Tail>> {
@@ -588,6 +636,71 @@ mod tests {
         println!("{prf}");
     }
 
+    #[test]
+    fn test_pooledproof_clone() {
+        use crate::parser::parse_unwrap as p;
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let r1 = prf.add_premise(p("A"));
+        let r2 = prf.add_subproof();
+        let r3 = prf.with_mut_subproof(&r2, |sub| sub.add_premise(p("B"))).unwrap();
+        let copy = prf.clone();
+        assert_eq!(copy, prf);
+        prf.with_mut_premise(&r1, |e| *e = p("C")).unwrap();
+        prf.with_mut_subproof(&r2, |sub| sub.add_premise(p("D")));
+        assert_ne!(copy, prf);
+        assert_eq!(copy.lookup_premise(&r1), Some(p("A")));
+        assert_eq!(copy.lookup_subproof(&r2).unwrap().premises(), vec![r3]);
+        drop(prf);
+        assert_eq!(copy.lookup_subproof(&r2).unwrap().lookup_premise(&r3), Some(p("B")));
+    }
+
+    #[test]
+    fn test_unused_premises() {
+        use crate::parser::parse_unwrap as p;
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let r1 = prf.add_premise(p("A"));
+        let r2 = prf.add_premise(p("B"));
+        let r3 = prf.add_premise(p("C"));
+        let r4 = prf.add_subproof();
+        prf.with_mut_subproof(&r4, |sub| {
+            sub.add_premise(p("D"));
+            sub.add_step(Justification(p("A"), RuleM::Reiteration, vec![Coproduct::inject(r1)], vec![]));
+        });
+        let r5 = prf.add_step(Justification(p("D -> A"), RuleM::ImpIntro, vec![], vec![r4]));
+        let r6 = prf.add_step(Justification(p("B & B"), RuleM::AndIntro, vec![Coproduct::inject(r2), Coproduct::inject(r2)], vec![]));
+        assert_eq!(prf.unused_premises(&[Coproduct::inject(r5)]), vec![r2, r3]);
+        assert_eq!(prf.unused_premises(&[Coproduct::inject(r5), Coproduct::inject(r6)]), vec![r3]);
+        assert_eq!(prf.unused_premises(&[Coproduct::inject(r3)]), vec![r1, r2]);
+        assert_eq!(prf.unused_premises(&[]), vec![r1, r2, r3]);
+        assert_eq!(prf.concluding_lines(&[]), vec![Coproduct::inject(r6)]);
+        assert_eq!(prf.concluding_lines(&[p("D -> A"), p("C")]), vec![Coproduct::inject(r5)]);
+    }
+
+    #[test]
+    fn test_contradiction_after_line() {
+        use crate::parser::parse_unwrap as p;
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let r1 = prf.add_premise(p("A -> B"));
+        let r2 = prf.add_premise(p("~B"));
+        let r3 = prf.add_subproof();
+        let (r4, r5, r6) = prf
+            .with_mut_subproof(&r3, |sub| {
+                let r4 = sub.add_premise(p("A"));
+                let r5 = sub.add_step(Justification(p("B"), RuleM::ImpElim, vec![Coproduct::inject(r1), Coproduct::inject(r4)], vec![]));
+                let r6 = sub.add_step(Justification(p("~A"), RuleM::Reiteration, vec![], vec![]));
+                (r4, r5, r6)
+            })
+            .unwrap();
+        let r7 = prf.add_step(Justification(p("~A"), RuleM::NotIntro, vec![], vec![r3]));
+        assert_eq!(prf.contradiction_after_line(&Coproduct::inject(r2)), None);
+        assert_eq!(prf.contradiction_after_line(&Coproduct::inject(r4)), None);
+        assert_eq!(prf.contradiction_after_line(&Coproduct::inject(r5)), Some((Coproduct::inject(r5), Coproduct::inject(r2))));
+        // The later of the two pairs wins
+        assert_eq!(prf.contradiction_after_line(&Coproduct::inject(r6)), Some((Coproduct::inject(r4), Coproduct::inject(r6))));
+        // The subproof's lines are out of reach after it
+        assert_eq!(prf.contradiction_after_line(&Coproduct::inject(r7)), None);
+    }
+
     #[test]
     fn prettyprint_pool() {
         let prf: PooledProof<HList![Expr]> = crate::proofs::proof_tests::demo_proof_1();
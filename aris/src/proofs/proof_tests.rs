@@ -1,6 +1,7 @@
 #![deny(unused_variables, dead_code)]
 
 use crate::expr::Expr;
+use crate::proofs::persistent_proof::PersistentProof;
 use crate::proofs::pooledproof::PooledProof;
 use crate::proofs::Justification;
 use crate::proofs::PjRef;
@@ -46,17 +47,22 @@ where
 
 macro_rules! generate_tests {
     ($proofrepr:ty, $modprefix:ident; $( $generic_test:ident ),+,) => {
+        generate_tests! { $proofrepr, $modprefix, crate::parser::LogicMode::FirstOrder; $( $generic_test ),+, }
+    };
+    ($proofrepr:ty, $modprefix:ident, $logic:expr; $( $generic_test:ident ),+,) => {
         #[cfg(test)]
         mod $modprefix {
             use super::*;
             $(
                 #[test]
                 fn $generic_test() {
-                    super::run_test::<$proofrepr, _>(super::$generic_test);
+                    // The proof is built and checked in the logic, which is only set while the test runs
+                    let syntax = crate::parser::Syntax { logic_mode: $logic, operators: vec![] };
+                    crate::parser::with_syntax(&syntax, || super::run_test::<$proofrepr, _>(super::$generic_test));
                 }
             )+
         }
-    }
+    };
 }
 
 macro_rules! enumerate_subproofless_tests {
@@ -65,16 +71,37 @@ macro_rules! enumerate_subproofless_tests {
             test_andelim, test_contelim, test_orintro, test_reiteration, test_andintro,
             test_contradictionintro, test_notelim, test_impelim, test_commutation_bool,
             test_commutation_bicon, test_association_bool, test_association_bicon,
+            test_association_commutation_bool, test_association_commutation_bicon,
             test_demorgan, test_idempotence, test_doublenegation, test_distribution,
             test_complement, test_identity, test_annihilation, test_inverse, test_absorption,
-            test_reduction, test_adjacency, test_resolution, test_truth_func_conseq, test_empty_rule,
+            test_reduction, test_adjacency, test_resolution, test_truth_func_conseq, test_first_order_conseq, test_empty_rule,
             test_modus_tollens, test_hypothetical_syllogism, test_disjunctive_syllogism,
             test_constructive_dilemma, test_destructive_dilemma, test_halfdemorgan,
             test_strengthen_antecedent, test_weaken_consequent, test_con_intro_negation,
             test_con_elim_negation, test_bicon_intro, test_bicon_intro_negation,
             test_bicon_elim, test_bicon_elim_negation, test_exclusion,
             test_excluded_middle, test_weak_induction, test_strong_induction,
-            test_bicon_contraposition,
+            test_bicon_contraposition, test_null_quantification, test_swapping_quantifiers,
+            test_quantifier_distribution, test_element_intro, test_element_elim, test_subset_elim,
+            test_extensionality, test_temporal_outside_temporal_logic, test_hoare_outside_programs,
+        }
+    };
+}
+
+macro_rules! enumerate_temporal_tests {
+    ($x:ty, $y:ident) => {
+        generate_tests! { $x, $y, crate::parser::LogicMode::Temporal;
+            test_always_elim, test_eventually_intro, test_until_intro, test_until_elim,
+            test_temporal_negation,
+        }
+    };
+}
+
+macro_rules! enumerate_hoare_tests {
+    ($x:ty, $y:ident) => {
+        generate_tests! { $x, $y, crate::parser::LogicMode::Program;
+            test_hoare_skip, test_hoare_assignment, test_hoare_sequence, test_hoare_conditional,
+            test_hoare_while, test_hoare_consequence,
         }
     };
 }
@@ -93,16 +120,24 @@ macro_rules! enumerate_subproofful_tests {
             test_equivintro,
             test_existsintro,
             test_existselim,
+            test_subset_intro,
+            test_shared_lines,
         }
     };
 }
 
 enumerate_subproofless_tests! { PooledProof<HList![Expr]>, test_subproofless_rules_on_pooledproof }
 enumerate_subproofful_tests! { PooledProof<HList![Expr]>, test_subproofful_rules_on_pooledproof }
-
-pub fn demo_proof_1<P: Proof>() -> P
+enumerate_subproofless_tests! { PersistentProof<HList![Expr]>, test_subproofless_rules_on_persistentproof }
+enumerate_subproofful_tests! { PersistentProof<HList![Expr]>, test_subproofful_rules_on_persistentproof }
+enumerate_temporal_tests! { PooledProof<HList![Expr]>, test_temporal_rules_on_pooledproof }
+enumerate_temporal_tests! { PersistentProof<HList![Expr]>, test_temporal_rules_on_persistentproof }
+enumerate_hoare_tests! { PooledProof<HList![Expr]>, test_hoare_rules_on_pooledproof }
+enumerate_hoare_tests! { PersistentProof<HList![Expr]>, test_hoare_rules_on_persistentproof }
+
+pub fn demo_proof_1<P>() -> P
 where
-    P: PartialEq + std::fmt::Debug,
+    P: Proof + PartialEq + std::fmt::Debug,
     PjRef<P>: PartialEq + std::fmt::Debug,
     P::SubproofReference: PartialEq + std::fmt::Debug,
 {
@@ -559,6 +594,42 @@ where
 
     (prf, vec![i(r3), i(r4), i(r6), i(r7), i(r8), i(r9)], vec![i(r5), i(r10), i(r11), i(r12)])
 }
+pub fn test_subset_intro<P: Proof + Debug>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>)
+where
+    P::Subproof: Debug,
+    PjRef<P>: Debug,
+    P::SubproofReference: Debug,
+{
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("c ∈ B"));
+    let r2 = prf.add_subproof();
+    let r4 = prf
+        .with_mut_subproof(&r2, |sub| {
+            let r3 = sub.add_premise(p("a ∈ A ∩ B"));
+            sub.add_step(Justification(p("a ∈ B"), RuleM::ElementElim, vec![i(r3)], vec![]))
+        })
+        .unwrap();
+    let r5 = prf.add_step(Justification(p("A ∩ B ⊆ B"), RuleM::SubsetIntro, vec![], vec![r2.clone()]));
+    let r6 = prf.add_step(Justification(p("A ∩ B ⊆ A"), RuleM::SubsetIntro, vec![], vec![r2.clone()]));
+    let r7 = prf.add_step(Justification(p("A ⊆ B"), RuleM::SubsetIntro, vec![], vec![r2.clone()]));
+    // The element has to be arbitrary, rather than one that's known to be in the second set
+    let r8 = prf.add_subproof();
+    prf.with_mut_subproof(&r8, |sub| {
+        sub.add_premise(p("c ∈ A"));
+        sub.add_step(Justification(p("c ∈ B"), RuleM::Reiteration, vec![i(r1.clone())], vec![]));
+    });
+    let r9 = prf.add_step(Justification(p("A ⊆ B"), RuleM::SubsetIntro, vec![], vec![r8.clone()]));
+    let r10 = prf.add_subproof();
+    prf.with_mut_subproof(&r10, |sub| {
+        let r11 = sub.add_premise(p("a ∈ {x : x ∈ A}"));
+        sub.add_step(Justification(p("a ∈ A"), RuleM::ElementElim, vec![i(r11)], vec![]));
+    });
+    let r12 = prf.add_step(Justification(p("{x : x ∈ A} ⊆ A"), RuleM::SubsetIntro, vec![], vec![r10.clone()]));
+    (prf, vec![i(r4), i(r5), i(r12)], vec![i(r6), i(r7), i(r9)])
+}
+
 pub fn test_existselim<P: Proof + Debug>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>)
 where
     P::Subproof: Debug,
@@ -681,6 +752,29 @@ pub fn test_association_bicon<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
     (prf, vec![i(r2)], vec![i(r3)])
 }
 
+pub fn test_association_commutation_bool<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("(A & B & C) | (P & Q & R & S) | (U <-> V <-> W)"));
+    let r2 = prf.add_step(Justification(p("(U <-> V <-> W) | (((S & (Q & P)) & R) | (C & (B & A)))"), RuleM::AssociationCommutation, vec![i(r1.clone())], vec![]));
+    let r3 = prf.add_step(Justification(p("(A & B & C) | (P & Q & R & S) | (U <-> V <-> W)"), RuleM::AssociationCommutation, vec![i(r1.clone())], vec![]));
+    let r4 = prf.add_step(Justification(p("(A & B & C) | (P & Q & R & S) | (W <-> V <-> U)"), RuleM::AssociationCommutation, vec![i(r1.clone())], vec![]));
+    let r5 = prf.add_step(Justification(p("(A & B) | C | (P & Q & R & S) | (U <-> V <-> W)"), RuleM::AssociationCommutation, vec![i(r1)], vec![]));
+    (prf, vec![i(r2), i(r3)], vec![i(r4), i(r5)])
+}
+
+pub fn test_association_commutation_bicon<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("(a <-> b <-> c <-> d) & (A | B)"));
+    let r2 = prf.add_step(Justification(p("((d <-> b) <-> (c <-> a)) & (A | B)"), RuleM::BiconditionalAssociationCommutation, vec![i(r1.clone())], vec![]));
+    let r3 = prf.add_step(Justification(p("(a <-> b <-> c <-> d) & (B | A)"), RuleM::BiconditionalAssociationCommutation, vec![i(r1.clone())], vec![]));
+    let r4 = prf.add_step(Justification(p("(a <-> b <-> c) & (A | B)"), RuleM::BiconditionalAssociationCommutation, vec![i(r1)], vec![]));
+    (prf, vec![i(r2)], vec![i(r3), i(r4)])
+}
+
 pub fn test_demorgan<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
     use self::coproduct_inject as i;
     use crate::parser::parse_unwrap as p;
@@ -797,7 +891,14 @@ pub fn test_distribution<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
 
     let r4 = prf.add_step(Justification(p("A | (B & C)"), RuleM::Distribution, vec![i(p2)], vec![]));
 
-    (prf, vec![i(r1), i(r2), i(r3)], vec![i(r4)])
+    let p3 = prf.add_premise(p("(A & B) | (A & C) | (A & D)"));
+    let p4 = prf.add_premise(p("(A & B) | (A & C) | (A & D) | (A & E) | (A & F) | (A & G) | (A & H) | (A & I)"));
+    let r5 = prf.add_step(Justification(p("A & (B | C | D)"), RuleM::Distribution, vec![i(p3.clone())], vec![]));
+    let r6 = prf.add_step(Justification(p("A & (D | (B | C))"), RuleM::Distribution, vec![i(p3.clone())], vec![]));
+    let r7 = prf.add_step(Justification(p("A & (B | C | D | E | F | G | H | I)"), RuleM::Distribution, vec![i(p4)], vec![]));
+    let r8 = prf.add_step(Justification(p("A & (B | C)"), RuleM::Distribution, vec![i(p3)], vec![]));
+
+    (prf, vec![i(r1), i(r2), i(r3), i(r5), i(r6), i(r7)], vec![i(r4), i(r8)])
 }
 
 pub fn test_complement<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
@@ -1037,8 +1138,11 @@ pub fn test_resolution<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
     let r8 = prf.add_step(Justification(p("a1 | a2"), RuleM::Resolution, vec![i(p3.clone()), i(p5.clone())], vec![]));
     let r9 = prf.add_step(Justification(p("a1 | a2"), RuleM::Resolution, vec![i(p5.clone()), i(p5)], vec![]));
     let r10 = prf.add_step(Justification(p("a1 | a2 | b1 | b2 | z | d | e | f"), RuleM::Resolution, vec![i(p1.clone()), i(p2.clone())], vec![]));
+    let p7 = prf.add_premise(p("~c | c"));
+    // Resolving on `c` leaves the `c` of the second premise
+    let r11 = prf.add_step(Justification(p("a1 | a2"), RuleM::Resolution, vec![i(p1.clone()), i(p7.clone())], vec![]));
 
-    (prf, vec![i(r1), i(r2), i(r3), i(r4)], vec![i(r5), i(r6), i(r7), i(r8), i(r9), i(r10)])
+    (prf, vec![i(r1), i(r2), i(r3), i(r4)], vec![i(r5), i(r6), i(r7), i(r8), i(r9), i(r10), i(r11)])
 }
 
 pub fn test_truth_func_conseq<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
@@ -1071,6 +1175,28 @@ pub fn test_truth_func_conseq<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
     (prf, vec![i(r1), i(r2), i(r3), i(r4), i(r6), i(r9), i(r11), i(r13), i(r15), i(r17)], vec![i(r5), i(r7), i(r8), i(r10), i(r12), i(r14), i(r16)])
 }
 
+pub fn test_first_order_conseq<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let p1 = prf.add_premise(p("forall x (Man(x) -> Mortal(x))"));
+    let p2 = prf.add_premise(p("Man(socrates)"));
+    let p3 = prf.add_premise(p("forall x (P(x) -> P(s(x)))"));
+    let p4 = prf.add_premise(p("P(0)"));
+    let p5 = prf.add_premise(p("exists x Man(x)"));
+
+    let r1 = prf.add_step(Justification(p("Mortal(socrates)"), RuleM::FirstOrderConsequence, vec![i(p1.clone()), i(p2.clone())], vec![]));
+    let r2 = prf.add_step(Justification(p("exists y Mortal(y)"), RuleM::FirstOrderConsequence, vec![i(p1.clone()), i(p5.clone())], vec![]));
+    let r3 = prf.add_step(Justification(p("P(s(s(0)))"), RuleM::FirstOrderConsequence, vec![i(p3.clone()), i(p4.clone())], vec![]));
+    let r4 = prf.add_step(Justification(p("forall x (~Mortal(x) -> ~Man(x))"), RuleM::FirstOrderConsequence, vec![i(p1.clone())], vec![]));
+    let r5 = prf.add_step(Justification(p("Man(socrates)"), RuleM::FirstOrderConsequence, vec![i(p1.clone()), i(p5)], vec![]));
+    let r6 = prf.add_step(Justification(p("forall x Man(x)"), RuleM::FirstOrderConsequence, vec![i(p2)], vec![]));
+    let r7 = prf.add_step(Justification(p("P(s(s(s(s(0)))))"), RuleM::FirstOrderConsequence, vec![i(p3), i(p4)], vec![]));
+    let r8 = prf.add_step(Justification(p("x < x + 1"), RuleM::FirstOrderConsequence, vec![i(p1)], vec![]));
+
+    (prf, vec![i(r1), i(r2), i(r3), i(r4)], vec![i(r5), i(r6), i(r7), i(r8)])
+}
+
 pub fn test_empty_rule<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
     use self::coproduct_inject as i;
     use crate::parser::parse_unwrap as p;
@@ -1430,7 +1556,12 @@ pub fn test_weak_induction<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
     let r7 = prf.add_step(Justification(p("forall n ~LessThan(n,n)"), RuleM::WeakInduction, vec![i(r1.clone()), i(r2.clone())], vec![]));
     let r8 = prf.add_step(Justification(p("forall x Equals(x,x)"), RuleM::WeakInduction, vec![i(r3.clone()), i(r4.clone())], vec![]));
     let r9 = prf.add_step(Justification(p("forall x Equals(x,0)"), RuleM::WeakInduction, vec![i(r3.clone()), i(r4.clone())], vec![]));
-    (prf, vec![i(r1), i(r2), i(r3), i(r4), i(r5), i(r6), i(r7), i(r8)], vec![i(r9)])
+    let r10 = prf.add_premise(p("0 ≤ 0"));
+    let r11 = prf.add_premise(p("forall n (0 ≤ n -> 0 ≤ n + 1)"));
+    let r12 = prf.add_premise(p("forall n (0 ≤ n -> 0 ≤ 1 + n)"));
+    let r13 = prf.add_step(Justification(p("forall x (0 ≤ x)"), RuleM::WeakInduction, vec![i(r10.clone()), i(r11.clone())], vec![]));
+    let r14 = prf.add_step(Justification(p("forall x (0 ≤ x)"), RuleM::WeakInduction, vec![i(r10.clone()), i(r12.clone())], vec![]));
+    (prf, vec![i(r1), i(r2), i(r3), i(r4), i(r5), i(r6), i(r7), i(r8), i(r10), i(r11), i(r12), i(r13)], vec![i(r9), i(r14)])
 }
 
 pub fn test_strong_induction<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
@@ -1445,7 +1576,243 @@ pub fn test_strong_induction<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
     let r6 = prf.add_step(Justification(p("forall x P(x,n)"), RuleM::StrongInduction, vec![i(r1.clone())], vec![]));
     let r7 = prf.add_step(Justification(p("forall x P(x,n)"), RuleM::StrongInduction, vec![i(r2.clone())], vec![]));
     let r8 = prf.add_step(Justification(p("forall a P(a)"), RuleM::StrongInduction, vec![i(r1.clone())], vec![]));
-    (prf, vec![i(r1), i(r2), i(r3), i(r4), i(r8)], vec![i(r5), i(r6), i(r7)])
+    let r9 = prf.add_premise(p("forall n (forall x (x < n -> P(x)) -> P(n))"));
+    let r10 = prf.add_premise(p("forall n (forall x (x ≤ n -> P(x)) -> P(n))"));
+    let r11 = prf.add_step(Justification(p("forall x P(x)"), RuleM::StrongInduction, vec![i(r9.clone())], vec![]));
+    let r12 = prf.add_step(Justification(p("forall x P(x)"), RuleM::StrongInduction, vec![i(r10.clone())], vec![]));
+    (prf, vec![i(r1), i(r2), i(r3), i(r4), i(r8), i(r9), i(r10), i(r11)], vec![i(r5), i(r6), i(r7), i(r12)])
+}
+
+pub fn test_element_intro<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("a ∈ A"));
+    let r2 = prf.add_premise(p("a ∈ A & a ∈ B"));
+    let r3 = prf.add_premise(p("P(a) & Q(b)"));
+    let r4 = prf.add_step(Justification(p("a ∈ A ∪ B"), RuleM::ElementIntro, vec![i(r1.clone())], vec![]));
+    let r5 = prf.add_step(Justification(p("a ∈ B ∪ A"), RuleM::ElementIntro, vec![i(r1.clone())], vec![]));
+    let r6 = prf.add_step(Justification(p("a ∈ A ∩ B"), RuleM::ElementIntro, vec![i(r2.clone())], vec![]));
+    let r7 = prf.add_step(Justification(p("a ∈ {x : P(x) & Q(b)}"), RuleM::ElementIntro, vec![i(r3.clone())], vec![]));
+    let r8 = prf.add_step(Justification(p("b ∈ {y : P(a) & Q(y)}"), RuleM::ElementIntro, vec![i(r3.clone())], vec![]));
+    let r9 = prf.add_step(Justification(p("a ∈ A ∩ B"), RuleM::ElementIntro, vec![i(r1.clone())], vec![]));
+    let r10 = prf.add_step(Justification(p("a ∈ A ∪ B"), RuleM::ElementIntro, vec![i(r2.clone())], vec![]));
+    let r11 = prf.add_step(Justification(p("b ∈ {x : P(x) & Q(b)}"), RuleM::ElementIntro, vec![i(r3.clone())], vec![]));
+    let r12 = prf.add_step(Justification(p("a ∈ A"), RuleM::ElementIntro, vec![i(r1.clone())], vec![]));
+    (prf, vec![i(r4), i(r5), i(r6), i(r7), i(r8)], vec![i(r9), i(r10), i(r11), i(r12)])
+}
+
+pub fn test_element_elim<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("a ∈ A ∪ B"));
+    let r2 = prf.add_premise(p("a ∈ A ∩ (B ∪ C)"));
+    let r3 = prf.add_premise(p("c ∈ {x : forall y (y ∈ x)}"));
+    let r4 = prf.add_premise(p("a ∈ ∅"));
+    let r5 = prf.add_step(Justification(p("a ∈ A | a ∈ B"), RuleM::ElementElim, vec![i(r1.clone())], vec![]));
+    let r6 = prf.add_step(Justification(p("a ∈ A & a ∈ B ∪ C"), RuleM::ElementElim, vec![i(r2.clone())], vec![]));
+    let r7 = prf.add_step(Justification(p("a ∈ B ∪ C"), RuleM::ElementElim, vec![i(r2.clone())], vec![]));
+    let r8 = prf.add_step(Justification(p("forall y (y ∈ c)"), RuleM::ElementElim, vec![i(r3.clone())], vec![]));
+    let r9 = prf.add_step(Justification(p("_|_"), RuleM::ElementElim, vec![i(r4.clone())], vec![]));
+    let r10 = prf.add_step(Justification(p("a ∈ A"), RuleM::ElementElim, vec![i(r1.clone())], vec![]));
+    let r11 = prf.add_step(Justification(p("a ∈ B"), RuleM::ElementElim, vec![i(r2.clone())], vec![]));
+    let r12 = prf.add_step(Justification(p("forall y (y ∈ x)"), RuleM::ElementElim, vec![i(r3.clone())], vec![]));
+    (prf, vec![i(r5), i(r6), i(r7), i(r8), i(r9)], vec![i(r10), i(r11), i(r12)])
+}
+
+pub fn test_subset_elim<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("A ⊆ B ∪ C"));
+    let r2 = prf.add_premise(p("a ∈ A"));
+    let r3 = prf.add_premise(p("a ∈ B"));
+    let r4 = prf.add_step(Justification(p("a ∈ B ∪ C"), RuleM::SubsetElim, vec![i(r1.clone()), i(r2.clone())], vec![]));
+    let r5 = prf.add_step(Justification(p("a ∈ B ∪ C"), RuleM::SubsetElim, vec![i(r2.clone()), i(r1.clone())], vec![]));
+    let r6 = prf.add_step(Justification(p("a ∈ B ∪ C"), RuleM::SubsetElim, vec![i(r1.clone()), i(r3.clone())], vec![]));
+    let r7 = prf.add_step(Justification(p("a ∈ B"), RuleM::SubsetElim, vec![i(r1.clone()), i(r2.clone())], vec![]));
+    (prf, vec![i(r4), i(r5)], vec![i(r6), i(r7)])
+}
+
+pub fn test_extensionality<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("A ∩ B ⊆ B ∩ A"));
+    let r2 = prf.add_premise(p("B ∩ A ⊆ A ∩ B"));
+    let r3 = prf.add_step(Justification(p("A ∩ B = B ∩ A"), RuleM::Extensionality, vec![i(r1.clone()), i(r2.clone())], vec![]));
+    let r4 = prf.add_step(Justification(p("B ∩ A = A ∩ B"), RuleM::Extensionality, vec![i(r1.clone()), i(r2.clone())], vec![]));
+    let r5 = prf.add_step(Justification(p("A ∩ B = B ∩ A"), RuleM::Extensionality, vec![i(r1.clone()), i(r1.clone())], vec![]));
+    let r6 = prf.add_step(Justification(p("A ∩ B ⊆ B ∩ A"), RuleM::Extensionality, vec![i(r1.clone()), i(r2.clone())], vec![]));
+    (prf, vec![i(r3), i(r4)], vec![i(r5), i(r6)])
+}
+
+pub fn test_always_elim<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("G (p -> X q)"));
+    let r2 = prf.add_premise(p("F p"));
+    let r3 = prf.add_step(Justification(p("p -> X q"), RuleM::AlwaysElim, vec![i(r1.clone())], vec![]));
+    let r4 = prf.add_step(Justification(p("p"), RuleM::AlwaysElim, vec![i(r2)], vec![]));
+    let r5 = prf.add_step(Justification(p("p -> q"), RuleM::AlwaysElim, vec![i(r1)], vec![]));
+    (prf, vec![i(r3)], vec![i(r4), i(r5)])
+}
+
+pub fn test_eventually_intro<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("p & q"));
+    let r2 = prf.add_step(Justification(p("F (p & q)"), RuleM::EventuallyIntro, vec![i(r1.clone())], vec![]));
+    let r3 = prf.add_step(Justification(p("F p"), RuleM::EventuallyIntro, vec![i(r1.clone())], vec![]));
+    let r4 = prf.add_step(Justification(p("G (p & q)"), RuleM::EventuallyIntro, vec![i(r1)], vec![]));
+    (prf, vec![i(r2)], vec![i(r3), i(r4)])
+}
+
+pub fn test_until_intro<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("q"));
+    let r2 = prf.add_step(Justification(p("p U q"), RuleM::UntilIntro, vec![i(r1.clone())], vec![]));
+    let r3 = prf.add_step(Justification(p("(G r) U q"), RuleM::UntilIntro, vec![i(r1.clone())], vec![]));
+    let r4 = prf.add_step(Justification(p("q U p"), RuleM::UntilIntro, vec![i(r1.clone())], vec![]));
+    let r5 = prf.add_step(Justification(p("F q"), RuleM::UntilIntro, vec![i(r1)], vec![]));
+    (prf, vec![i(r2), i(r3)], vec![i(r4), i(r5)])
+}
+
+pub fn test_until_elim<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("p U (q | r)"));
+    let r2 = prf.add_premise(p("F q"));
+    let r3 = prf.add_step(Justification(p("F (q | r)"), RuleM::UntilElim, vec![i(r1.clone())], vec![]));
+    let r4 = prf.add_step(Justification(p("F p"), RuleM::UntilElim, vec![i(r1.clone())], vec![]));
+    let r5 = prf.add_step(Justification(p("q | r"), RuleM::UntilElim, vec![i(r1)], vec![]));
+    let r6 = prf.add_step(Justification(p("F q"), RuleM::UntilElim, vec![i(r2)], vec![]));
+    (prf, vec![i(r3)], vec![i(r4), i(r5), i(r6)])
+}
+
+pub fn test_temporal_negation<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("~G p"));
+    let r2 = prf.add_premise(p("G ~(p & q)"));
+    let r3 = prf.add_premise(p("~X p"));
+    let r4 = prf.add_premise(p("~(p U q)"));
+    let r5 = prf.add_step(Justification(p("F ~p"), RuleM::TemporalNegation, vec![i(r1.clone())], vec![]));
+    let r6 = prf.add_step(Justification(p("~F (p & q)"), RuleM::TemporalNegation, vec![i(r2.clone())], vec![]));
+    let r7 = prf.add_step(Justification(p("X ~p"), RuleM::TemporalNegation, vec![i(r3)], vec![]));
+    let r8 = prf.add_step(Justification(p("G ~p"), RuleM::TemporalNegation, vec![i(r1)], vec![]));
+    let r9 = prf.add_step(Justification(p("~G (p & q)"), RuleM::TemporalNegation, vec![i(r2)], vec![]));
+    let r10 = prf.add_step(Justification(p("~p U ~q"), RuleM::TemporalNegation, vec![i(r4)], vec![]));
+    (prf, vec![i(r5), i(r6), i(r7)], vec![i(r8), i(r9), i(r10)])
+}
+
+/// Temporal rules check nothing outside temporal logic, where G, F, and U are
+/// only names
+pub fn test_temporal_outside_temporal_logic<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("A"));
+    let r2 = prf.add_premise(p("G(a)"));
+    let r3 = prf.add_step(Justification(p("F(A)"), RuleM::EventuallyIntro, vec![i(r1)], vec![]));
+    let r4 = prf.add_step(Justification(p("a"), RuleM::AlwaysElim, vec![i(r2)], vec![]));
+    (prf, vec![], vec![i(r3), i(r4)])
+}
+
+/// Hoare logic rules check nothing outside proofs about programs, where hoare
+/// and skip are only names
+pub fn test_hoare_outside_programs<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_step(Justification(p("hoare(P, skip, P)"), RuleM::HoareSkip, vec![], vec![]));
+    (prf, vec![], vec![i(r1)])
+}
+
+pub fn test_hoare_skip<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_step(Justification(p("{x = 1} skip {x = 1}"), RuleM::HoareSkip, vec![], vec![]));
+    let r2 = prf.add_step(Justification(p("{x = 1} skip {x = 2}"), RuleM::HoareSkip, vec![], vec![]));
+    let r3 = prf.add_step(Justification(p("{x = 1} x := x {x = 1}"), RuleM::HoareSkip, vec![], vec![]));
+    (prf, vec![i(r1)], vec![i(r2), i(r3)])
+}
+
+pub fn test_hoare_assignment<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_step(Justification(p("{x + 1 = 2} x := x + 1 {x = 2}"), RuleM::HoareAssignment, vec![], vec![]));
+    let r2 = prf.add_step(Justification(p("{y < 3 & forall x P(x, x)} x := y {x < 3 & forall x P(x, x)}"), RuleM::HoareAssignment, vec![], vec![]));
+    let r3 = prf.add_step(Justification(p("{x = 1} x := x + 1 {x = 2}"), RuleM::HoareAssignment, vec![], vec![]));
+    let r4 = prf.add_step(Justification(p("{x = 2} x := x + 1 {x = 2}"), RuleM::HoareAssignment, vec![], vec![]));
+    let r5 = prf.add_step(Justification(p("{⊤} skip {⊤}"), RuleM::HoareAssignment, vec![], vec![]));
+    (prf, vec![i(r1), i(r2)], vec![i(r3), i(r4), i(r5)])
+}
+
+pub fn test_hoare_sequence<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("{x = 0} x := x + 1 {x = 1}"));
+    let r2 = prf.add_premise(p("{x = 1} y := x; skip {y = 1}"));
+    let r3 = prf.add_step(Justification(p("{x = 0} x := x + 1; y := x; skip {y = 1}"), RuleM::HoareSequence, vec![i(r1.clone()), i(r2.clone())], vec![]));
+    let r4 = prf.add_step(Justification(p("{x = 0} (x := x + 1; y := x); skip {y = 1}"), RuleM::HoareSequence, vec![i(r2.clone()), i(r1.clone())], vec![]));
+    let r5 = prf.add_step(Justification(p("{x = 1} y := x; skip; x := x + 1 {x = 1}"), RuleM::HoareSequence, vec![i(r1.clone()), i(r2.clone())], vec![]));
+    let r6 = prf.add_step(Justification(p("{x = 0} x := x + 1; y := x {y = 1}"), RuleM::HoareSequence, vec![i(r1.clone()), i(r2)], vec![]));
+    let r7 = prf.add_step(Justification(p("{x = 0} x := x + 1; x := x + 1 {x = 1}"), RuleM::HoareSequence, vec![i(r1.clone()), i(r1)], vec![]));
+    (prf, vec![i(r3), i(r4)], vec![i(r5), i(r6), i(r7)])
+}
+
+pub fn test_hoare_conditional<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("{⊤ & x < y} m := y {m = y}"));
+    let r2 = prf.add_premise(p("{~(x < y) & ⊤} m := y {m = y}"));
+    let r3 = prf.add_premise(p("{⊤} m := y {m = y}"));
+    let r4 = prf.add_step(Justification(p("{⊤} if x < y then m := y else m := y end {m = y}"), RuleM::HoareConditional, vec![i(r1.clone()), i(r2.clone())], vec![]));
+    let r5 = prf.add_step(Justification(p("{⊤} if x < y then m := y else m := y end {m = y}"), RuleM::HoareConditional, vec![i(r2.clone()), i(r1.clone())], vec![]));
+    let r6 = prf.add_step(Justification(p("{⊤} if x < y then m := y else m := y end {m = y}"), RuleM::HoareConditional, vec![i(r1.clone()), i(r3)], vec![]));
+    let r7 = prf.add_step(Justification(p("{⊤} if y < x then m := y else m := y end {m = y}"), RuleM::HoareConditional, vec![i(r1.clone()), i(r2.clone())], vec![]));
+    let r8 = prf.add_step(Justification(p("{⊤} m := y {m = y}"), RuleM::HoareConditional, vec![i(r1), i(r2)], vec![]));
+    (prf, vec![i(r4), i(r5)], vec![i(r6), i(r7), i(r8)])
+}
+
+pub fn test_hoare_while<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("{x ≤ 10 & x < 10} x := x + 1 {x ≤ 10}"));
+    let r2 = prf.add_step(Justification(p("{x ≤ 10} while x < 10 do x := x + 1 end {x ≤ 10 & ~(x < 10)}"), RuleM::HoareWhile, vec![i(r1.clone())], vec![]));
+    let r3 = prf.add_step(Justification(p("{x ≤ 10} while x < 10 do x := x + 1 end {~(x < 10) & x ≤ 10}"), RuleM::HoareWhile, vec![i(r1.clone())], vec![]));
+    let r4 = prf.add_step(Justification(p("{x ≤ 10} while x < 10 do x := x + 1 end {x = 10}"), RuleM::HoareWhile, vec![i(r1.clone())], vec![]));
+    let r5 = prf.add_step(Justification(p("{x ≤ 10} while x < 9 do x := x + 1 end {x ≤ 10 & ~(x < 9)}"), RuleM::HoareWhile, vec![i(r1.clone())], vec![]));
+    let r6 = prf.add_step(Justification(p("{x ≤ 10} while x < 10 do x := x + 2 end {x ≤ 10 & ~(x < 10)}"), RuleM::HoareWhile, vec![i(r1)], vec![]));
+    (prf, vec![i(r2), i(r3)], vec![i(r4), i(r5), i(r6)])
+}
+
+pub fn test_hoare_consequence<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("{x + 1 = 2} x := x + 1 {x = 2}"));
+    let r2 = prf.add_premise(p("x = 1 -> x + 1 = 2"));
+    let r3 = prf.add_premise(p("x = 2 -> 0 < x"));
+    let r4 = prf.add_step(Justification(p("{x = 1} x := x + 1 {0 < x}"), RuleM::HoareConsequence, vec![i(r2.clone()), i(r1.clone()), i(r3.clone())], vec![]));
+    let r5 = prf.add_step(Justification(p("{x = 1} x := x + 1 {x = 2}"), RuleM::HoareConsequence, vec![i(r1.clone()), i(r2.clone())], vec![]));
+    let r6 = prf.add_step(Justification(p("{x = 1} x := x + 1 {0 < x}"), RuleM::HoareConsequence, vec![i(r1.clone()), i(r2.clone())], vec![]));
+    let r7 = prf.add_step(Justification(p("{x = 1} x := x + 2 {x = 2}"), RuleM::HoareConsequence, vec![i(r1.clone()), i(r2.clone())], vec![]));
+    let r8 = prf.add_step(Justification(p("{x + 1 = 2} x := x + 1 {x = 2}"), RuleM::HoareConsequence, vec![i(r1), i(r3)], vec![]));
+    (prf, vec![i(r4), i(r5)], vec![i(r6), i(r7), i(r8)])
 }
 
 pub fn test_bicon_contraposition<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
@@ -1459,3 +1826,83 @@ pub fn test_bicon_contraposition<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>
 
     (prf, vec![i(r1), i(r2), i(r3)], vec![i(r4)])
 }
+
+pub fn test_null_quantification<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("forall x (A & P(y))"));
+    let r2 = prf.add_premise(p("exists x P(x)"));
+    let r3 = prf.add_step(Justification(p("A & P(y)"), RuleM::NullQuantification, vec![i(r1.clone())], vec![]));
+    let r4 = prf.add_step(Justification(p("exists x P(x)"), RuleM::NullQuantification, vec![i(r2.clone())], vec![]));
+    let r5 = prf.add_step(Justification(p("P(x)"), RuleM::NullQuantification, vec![i(r2)], vec![]));
+    let r6 = prf.add_step(Justification(p("forall y (A & P(y))"), RuleM::NullQuantification, vec![i(r1)], vec![]));
+
+    (prf, vec![i(r3), i(r4)], vec![i(r5), i(r6)])
+}
+
+pub fn test_swapping_quantifiers<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("forall y forall x R(x, y)"));
+    let r2 = prf.add_premise(p("forall x exists y R(x, y)"));
+    let r3 = prf.add_premise(p("exists c exists b exists a R(a, b)"));
+    let r4 = prf.add_step(Justification(p("forall x forall y R(x, y)"), RuleM::SwappingQuantifiers, vec![i(r1)], vec![]));
+    let r5 = prf.add_step(Justification(p("exists a exists c exists b R(a, b)"), RuleM::SwappingQuantifiers, vec![i(r3)], vec![]));
+    let r6 = prf.add_step(Justification(p("exists y forall x R(x, y)"), RuleM::SwappingQuantifiers, vec![i(r2)], vec![]));
+
+    (prf, vec![i(r4), i(r5)], vec![i(r6)])
+}
+
+pub fn test_quantifier_distribution<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("forall x (P(x) & Q(x))"));
+    let r2 = prf.add_premise(p("exists x (P(x) | Q(x))"));
+    let r3 = prf.add_premise(p("exists x (P(x) & Q(x))"));
+    let r4 = prf.add_step(Justification(p("forall x P(x) & forall x Q(x)"), RuleM::QuantifierDistribution, vec![i(r1)], vec![]));
+    let r5 = prf.add_step(Justification(p("exists x P(x) | exists x Q(x)"), RuleM::QuantifierDistribution, vec![i(r2)], vec![]));
+    let r6 = prf.add_step(Justification(p("exists x P(x) & exists x Q(x)"), RuleM::QuantifierDistribution, vec![i(r3)], vec![]));
+
+    (prf, vec![i(r4), i(r5)], vec![i(r6)])
+}
+
+pub fn test_shared_lines<P: Proof>() -> (P, Vec<PjRef<P>>, Vec<PjRef<P>>) {
+    use self::coproduct_inject as i;
+    use crate::parser::parse_unwrap as p;
+    let mut prf = P::new();
+    let r1 = prf.add_premise(p("A"));
+    let r2 = prf.add_premise(p("B"));
+    let r3 = prf.add_subproof();
+    let (r5, r6, r7) = prf
+        .with_mut_subproof(&r3, |sub| {
+            let r4 = sub.add_premise(p("C"));
+            let r5 = sub.add_step(Justification(p("A & B"), RuleM::AndIntro, vec![i(r1.clone()), i(r2.clone())], vec![]));
+            let r6 = sub.add_step(Justification(p("C & A"), RuleM::AndIntro, vec![i(r4), i(r1.clone())], vec![]));
+            let r7 = sub.add_subproof();
+            sub.with_mut_subproof(&r7, |sub| {
+                sub.add_premise(p("D"));
+                sub.add_step(Justification(p("A"), RuleM::Reiteration, vec![i(r1.clone())], vec![]));
+            });
+            (r5, r6, r7)
+        })
+        .unwrap();
+    let r8 = prf.add_subproof();
+    let (r9, r10, r11) = prf
+        .with_mut_subproof(&r8, |sub| {
+            sub.add_premise(p("E"));
+            // Lines 5 and 7-8 are out of scope, but only rest on premises that are in scope
+            let r9 = sub.add_step(Justification(p("A & B"), RuleM::Reiteration, vec![i(r5.clone())], vec![]));
+            let r10 = sub.add_step(Justification(p("D -> A"), RuleM::ImpIntro, vec![], vec![r7.clone()]));
+            // Line 6 rests on the assumption C
+            let r11 = sub.add_step(Justification(p("C & A"), RuleM::Reiteration, vec![i(r6.clone())], vec![]));
+            (r9, r10, r11)
+        })
+        .unwrap();
+    let r12 = prf.add_step(Justification(p("A & B"), RuleM::Reiteration, vec![i(r9.clone())], vec![]));
+    // Shared lines have to come before the lines citing them
+    let r13 = prf.with_mut_subproof(&r3, |sub| sub.add_step(Justification(p("A & B"), RuleM::Reiteration, vec![i(r9.clone())], vec![]))).unwrap();
+    (prf, vec![i(r9), i(r10), i(r12)], vec![i(r11), i(r13)])
+}
@@ -0,0 +1,229 @@
+/*!
+# Verification reports
+
+The results of checking every line of a proof, as JSON that can be saved
+next to the proof file, so learning management systems and diff tools can
+show how a submission was graded without running the checker again.
+
+A report looks like
+
+```json
+{
+    "format": "aris-report",
+    "version": 1,
+    "lines": [
+        {"line": 1, "depth": 0, "formula": "(A ∧ B)", "verdict": "correct"},
+        {"line": 2, "depth": 0, "formula": "C", "rule": "SIMPLIFICATION", "verdict": "incorrect", "codes": ["does-not-occur"], "messages": ["..."]}
+    ],
+    "unused_premises": []
+}
+```
+
+Lines are numbered from 1, as they're shown, and rules are named as in
+`RuleM::to_serialized_name()`. Errors and warnings are given by their stable
+codes, from `ProofCheckError::code()` and `Lint::code()`, along with the
+messages the checker shows for them. Steps admitted without proof, with the
+`GAP` rule, have the verdict `"gap"`, which is neither correct nor incorrect.
+Each line's formula is included, so a
+viewer can tell whether the proof was edited since it was checked.
+*/
+
+use crate::expr::Expr;
+use crate::proofs::line_info::LineInfo;
+use crate::proofs::lints;
+use crate::proofs::lints::LineFeedback;
+use crate::proofs::PjRef;
+use crate::proofs::Proof;
+use crate::rules::RuleM;
+
+use std::fmt::Debug;
+
+use frunk_core::coproduct::Coproduct;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Value of the `format` field of every report
+pub const FORMAT: &str = "aris-report";
+
+/// Version of the report format written by `Report::to_json()`. Reports of
+/// later versions can't be read.
+pub const VERSION: u32 = 1;
+
+/// How a line did
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Verdict {
+    /// The line is a premise or a correctly applied rule
+    Correct,
+    /// The line is correct, but has warnings
+    Warned,
+    /// The line's rule is applied incorrectly
+    Incorrect,
+    /// The line is a gap, admitted without proof
+    Gap,
+}
+
+/// Result of checking a line
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineReport {
+    /// Line number, counting from 1
+    pub line: usize,
+
+    /// How many subproofs the line is in
+    pub depth: usize,
+
+    /// The line's formula
+    pub formula: String,
+
+    /// The step's rule, or `None` for premises
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+
+    pub verdict: Verdict,
+
+    /// Code of the error of an incorrect line, or of each warning of a
+    /// correct one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub codes: Vec<String>,
+
+    /// Messages of the error or warnings, in the order of `codes`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<String>,
+}
+
+/// Results of checking every line of a proof
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Report {
+    /// Every line of the proof, in order
+    #[serde(default)]
+    pub lines: Vec<LineReport>,
+
+    /// Line numbers of the top-level premises that the conclusion doesn't use
+    #[serde(default)]
+    pub unused_premises: Vec<usize>,
+}
+
+/// What's around the report in its JSON
+#[derive(Serialize, Deserialize)]
+struct Document {
+    format: String,
+    version: u32,
+    #[serde(flatten)]
+    report: Report,
+}
+
+impl Report {
+    /// Check every line of a proof, with `goals` being the formulas it's
+    /// meant to conclude, if any, for finding unused lines
+    pub fn of<P: Proof>(prf: &P, goals: &[Expr]) -> Self
+    where
+        PjRef<P>: Debug,
+        P::SubproofReference: Debug,
+    {
+        use Coproduct::{Inl, Inr};
+        let info = LineInfo::of(prf);
+        let mut lines = info.lines.iter().map(|(r, &(line, depth))| (line, depth, r.clone())).collect::<Vec<_>>();
+        lines.sort_by_key(|(line, _, _)| *line);
+        let lines = lines
+            .into_iter()
+            .map(|(line, depth, r)| {
+                let (formula, rule) = match prf.lookup_pj(&r) {
+                    Some(Inl(e)) => (e.to_string(), None),
+                    Some(Inr(Inl(just))) => (just.0.to_string(), Some(RuleM::to_serialized_name(just.1).to_string())),
+                    Some(Inr(Inr(void))) => match void {},
+                    None => (String::new(), None),
+                };
+                let (verdict, codes, messages) = match lints::check_line(prf, &r, goals) {
+                    LineFeedback::Correct => (Verdict::Correct, vec![], vec![]),
+                    LineFeedback::Warnings(lints) => (Verdict::Warned, lints.iter().map(|lint| lint.code().to_string()).collect(), lints.iter().map(|lint| lint.message(|r| info.line_of(r))).collect()),
+                    LineFeedback::Incorrect(err) => (Verdict::Incorrect, vec![err.code().to_string()], vec![err.message(&|r| info.line_of(r), &|sr| info.line_range_of_subproof(sr))]),
+                    LineFeedback::Gap => (Verdict::Gap, vec![], vec![]),
+                };
+                LineReport { line, depth, formula, rule, verdict, codes, messages }
+            })
+            .collect();
+        let unused_premises = prf.unused_premises(&prf.concluding_lines(goals)).into_iter().filter_map(|pr| info.line_of(&Coproduct::inject(pr))).collect();
+        Report { lines, unused_premises }
+    }
+
+    /// Whether every line is correct, with or without warnings. A proof with
+    /// gaps isn't.
+    pub fn is_correct(&self) -> bool {
+        self.lines.iter().all(|line| matches!(line.verdict, Verdict::Correct | Verdict::Warned))
+    }
+
+    /// Line numbers of the gaps, admitted without proof, that are left
+    pub fn gaps(&self) -> Vec<usize> {
+        self.lines.iter().filter(|line| line.verdict == Verdict::Gap).map(|line| line.line).collect()
+    }
+
+    /// Write the report as JSON
+    pub fn to_json(&self) -> String {
+        let document = Document { format: FORMAT.to_string(), version: VERSION, report: self.clone() };
+        serde_json::to_string_pretty(&document).expect("reports are always serializable")
+    }
+
+    /// Read a report from its JSON
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let document: Document = serde_json::from_str(text).map_err(|err| format!("Not a verification report: {err}"))?;
+        if document.format != FORMAT {
+            return Err(format!("Not a verification report: the format is {:?}, not {:?}", document.format, FORMAT));
+        }
+        if document.version > VERSION {
+            return Err(format!("The verification report is version {}, but only versions up to {} can be read. Try a newer version of Aris.", document.version, VERSION));
+        }
+        Ok(document.report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+    use crate::proofs::pooledproof::PooledProof;
+    use crate::proofs::Justification;
+
+    use frunk_core::HList;
+
+    #[test]
+    fn test_report() {
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let r1 = prf.add_premise(p("A & B"));
+        prf.add_premise(p("C"));
+        let sub = prf.add_subproof();
+        prf.with_mut_subproof(&sub, |sub| {
+            sub.add_premise(p("D"));
+            sub.add_step(Justification(p("A"), RuleM::AndElim, vec![Coproduct::inject(r1)], vec![]));
+        });
+        prf.add_step(Justification(p("D -> A"), RuleM::ImpIntro, vec![], vec![sub]));
+        prf.add_step(Justification(p("C"), RuleM::AndElim, vec![Coproduct::inject(r1)], vec![]));
+
+        let report = Report::of(&prf, &[p("D -> A")]);
+        let verdicts = report.lines.iter().map(|line| (line.line, line.depth, line.verdict)).collect::<Vec<_>>();
+        assert_eq!(verdicts, vec![(1, 0, Verdict::Correct), (2, 0, Verdict::Correct), (3, 1, Verdict::Correct), (4, 1, Verdict::Correct), (5, 0, Verdict::Correct), (6, 0, Verdict::Incorrect)]);
+        assert_eq!(report.lines[4].rule.as_deref(), Some("CONDITIONAL_PROOF"));
+        assert_eq!(report.lines[5].codes, vec!["does-not-occur".to_string()]);
+        assert_eq!(report.lines[5].messages.len(), 1);
+        assert_eq!(report.unused_premises, vec![2]);
+        assert!(!report.is_correct());
+
+        assert_eq!(Report::from_json(&report.to_json()), Ok(report));
+        assert!(Report::from_json(r#"{"format": "aris-report", "version": 2}"#).is_err());
+        assert!(Report::from_json(r#"{"format": "aris-workspace", "version": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_report_gaps() {
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let r1 = prf.add_premise(p("A"));
+        let r2 = prf.add_step(Justification(p("A -> B"), RuleM::Gap, vec![], vec![]));
+        prf.add_step(Justification(p("B"), RuleM::ImpElim, vec![Coproduct::inject(r1), Coproduct::inject(r2)], vec![]));
+
+        let report = Report::of(&prf, &[]);
+        let verdicts = report.lines.iter().map(|line| line.verdict).collect::<Vec<_>>();
+        assert_eq!(verdicts, vec![Verdict::Correct, Verdict::Gap, Verdict::Correct]);
+        assert_eq!(report.gaps(), vec![2]);
+        assert!(!report.is_correct());
+        assert!(report.to_json().contains(r#""verdict": "gap""#));
+    }
+}
@@ -0,0 +1,124 @@
+//! How alike two proofs are, for spotting submissions copied from each other.
+//!
+//! Each proof is reduced to the multiset of its lines, each line being its
+//! depth, its formula in the form `Expr::canonicalize()` gives, and its rule,
+//! and the score is the size of the multisets' intersection over the size of
+//! their union. Reordering lines, reordering the operands of a formula, or
+//! renaming bound variables doesn't change the score. The premises of the
+//! top-level proof are left out, since they come from the assignment rather
+//! than from whoever wrote the proof.
+//!
+//! ```
+//! use aris::expr::Expr;
+//! use aris::parser::parse_unwrap as p;
+//! use aris::proofs::pooledproof::PooledProof;
+//! use aris::proofs::similarity::similarity;
+//! use aris::proofs::{Justification, Proof};
+//! use aris::rules::RuleM;
+//!
+//! use frunk_core::coproduct::Coproduct;
+//! use frunk_core::HList;
+//!
+//! let proof = |conclusion: &str| {
+//!     let mut prf = PooledProof::<HList![Expr]>::new();
+//!     let r1 = prf.add_premise(p("A & B"));
+//!     prf.add_step(Justification(p(conclusion), RuleM::AssociationCommutation, vec![Coproduct::inject(r1)], vec![]));
+//!     prf
+//! };
+//! assert_eq!(similarity(&proof("B & A"), &proof("B & A")), 1.0);
+//! assert_eq!(similarity(&proof("B & A"), &proof("A & B")), 1.0);
+//! assert_eq!(similarity(&proof("B & A"), &proof("A & B & A")), 0.0);
+//! ```
+
+use crate::expr::Expr;
+use crate::proofs::Justification;
+use crate::proofs::Proof;
+use crate::rules::RuleT;
+
+use std::collections::HashMap;
+
+use frunk_core::coproduct::Coproduct;
+
+/// A line of a proof, as far as `similarity` is concerned: its depth, its
+/// canonical formula, and the name of its rule if it's a step
+pub type Fingerprint = (usize, Expr, Option<String>);
+
+fn add_fingerprints<P: Proof>(prf: &P, depth: usize, counts: &mut HashMap<Fingerprint, usize>) {
+    use Coproduct::{Inl, Inr};
+    if depth > 0 {
+        for premise in prf.premises() {
+            if let Some(e) = prf.lookup_premise(&premise) {
+                *counts.entry((depth, e.canonicalize(), None)).or_default() += 1;
+            }
+        }
+    }
+    for line in prf.lines() {
+        match line {
+            Inl(r) => {
+                if let Some(Justification(e, rule, _, _)) = prf.lookup_step(&r) {
+                    *counts.entry((depth, e.canonicalize(), Some(rule.get_name()))).or_default() += 1;
+                }
+            }
+            Inr(Inl(sr)) => {
+                if let Some(sub) = prf.lookup_subproof(&sr) {
+                    add_fingerprints(&sub, depth + 1, counts);
+                }
+            }
+            Inr(Inr(void)) => match void {},
+        }
+    }
+}
+
+/// How many times each line occurs in a proof, leaving out the premises of the
+/// top-level proof
+pub fn fingerprints<P: Proof>(prf: &P) -> HashMap<Fingerprint, usize> {
+    let mut counts = HashMap::new();
+    add_fingerprints(prf.top_level_proof(), 0, &mut counts);
+    counts
+}
+
+/// How alike two proofs are, from 0 for having no lines in common to 1 for
+/// having the same lines. Two proofs without any steps are alike.
+pub fn similarity<P: Proof, Q: Proof>(left: &P, right: &Q) -> f64 {
+    let (left, right) = (fingerprints(left), fingerprints(right));
+    let shared = left.iter().map(|(line, &count)| count.min(right.get(line).copied().unwrap_or(0))).sum::<usize>();
+    let all = left.values().sum::<usize>() + right.values().sum::<usize>() - shared;
+    if all == 0 {
+        1.0
+    } else {
+        shared as f64 / all as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+    use crate::proofs::pooledproof::PooledProof;
+    use crate::rules::Rule;
+    use crate::rules::RuleM;
+
+    use frunk_core::HList;
+
+    #[test]
+    fn test_similarity() {
+        type P = PooledProof<HList![Expr]>;
+        // Reorders a premise, naming its bound variable `name`, then has a subproof
+        let proof = |name: &str, rule: Rule| {
+            let mut prf = P::new();
+            let r1 = prf.add_premise(p(&format!("forall {name} (P({name}) & Q({name}))")));
+            prf.add_step(Justification(p(&format!("forall {name} (Q({name}) & P({name}))")), rule, vec![Coproduct::inject(r1)], vec![]));
+            let sub = prf.add_subproof();
+            prf.with_mut_subproof(&sub, |sub| {
+                sub.add_premise(p("P(a)"));
+                sub.add_step(Justification(p("Q(a)"), RuleM::Reiteration, vec![], vec![]));
+            });
+            prf
+        };
+        assert_eq!(similarity(&proof("x", RuleM::AssociationCommutation), &proof("y", RuleM::AssociationCommutation)), 1.0);
+        // One of the three lines that aren't top-level premises differs
+        assert_eq!(similarity(&proof("x", RuleM::AssociationCommutation), &proof("x", RuleM::Commutation)), 0.5);
+        assert_eq!(similarity(&P::new(), &P::new()), 1.0);
+        assert_eq!(similarity(&proof("x", RuleM::Commutation), &P::new()), 0.0);
+    }
+}
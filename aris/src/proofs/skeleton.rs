@@ -0,0 +1,398 @@
+//! Fill-in-the-blank handouts made from solution proofs.
+//!
+//! `blank_steps()` removes the formulas, rules, or citations of some steps of
+//! a complete proof, as much as a `Difficulty` says, leaving a handout for
+//! students to fill in, and returns an answer key with what was removed.
+//! Premises are never blanked, and neither are the formulas of the lines the
+//! proof concludes with, so the handout still says what it proves.
+//!
+//! A handout saved with its `Blanks` in its metadata is a cloze proof: only
+//! the blanked cells are for students to fill in, and everything else is
+//! locked. `check_blanks()` checks just the filled blanks, against the locked
+//! lines around them, and `changed_locked_cells()` finds the locked cells a
+//! submission changed anyway.
+//!
+//! ```
+//! # use aris::expr::Expr;
+//! # use aris::proofs::pooledproof::PooledProof;
+//! # use aris::proofs::skeleton::{blank_steps, Difficulty};
+//! # use aris::proofs::{Justification, Proof};
+//! # use aris::parser::parse_unwrap as p;
+//! # use aris::rules::RuleM;
+//! # use frunk_core::coproduct::Coproduct;
+//! # use frunk_core::HList;
+//! let mut prf = PooledProof::<HList![Expr]>::new();
+//! let a = Coproduct::inject(prf.add_premise(p("A & B")));
+//! let b = Coproduct::inject(prf.add_step(Justification(p("A"), RuleM::AndElim, vec![a], vec![])));
+//! let c = Coproduct::inject(prf.add_step(Justification(p("B"), RuleM::AndElim, vec![a], vec![])));
+//! prf.add_step(Justification(p("B & A"), RuleM::AndIntro, vec![c, b], vec![]));
+//! let key = blank_steps(&mut prf, &[], Difficulty::Medium, 0);
+//! assert_eq!(key.to_string(), "Line 2: rule ∧ Elimination; citations 1\nLine 4: rule ∧ Introduction; citations 3, 2\n");
+//! ```
+
+use crate::expr::Expr;
+use crate::proofs::line_info;
+use crate::proofs::line_info::LineInfo;
+use crate::proofs::lines_in_order;
+use crate::proofs::pj_to_pjs;
+use crate::proofs::Justification;
+use crate::proofs::PjRef;
+use crate::proofs::Proof;
+use crate::rules::ProofCheckError;
+use crate::rules::Rule;
+use crate::rules::RuleM;
+use crate::rules::RuleT;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use frunk_core::coproduct::Coproduct;
+
+/// A part of a step that can be blanked
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Blank {
+    Formula,
+    Rule,
+    Citations,
+}
+
+impl Blank {
+    /// Name of the part in proof files
+    pub fn name(self) -> &'static str {
+        match self {
+            Blank::Formula => "formula",
+            Blank::Rule => "rule",
+            Blank::Citations => "citations",
+        }
+    }
+}
+
+impl FromStr for Blank {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        [Blank::Formula, Blank::Rule, Blank::Citations].into_iter().find(|blank| blank.name() == s).ok_or_else(|| format!("Unknown blank {s:?}; it can be formula, rule, or citations"))
+    }
+}
+
+/// The blanked parts of a cloze proof's steps, by line number counting from 1
+pub type Blanks = BTreeMap<usize, BTreeSet<Blank>>;
+
+/// How much of a solution a handout leaves for students to fill in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    /// The rules of every third step
+    Easy,
+    /// The rules and citations of every other step
+    Medium,
+    /// The formulas, rules, and citations of every other step
+    Hard,
+}
+
+impl Difficulty {
+    /// The parts blanked in each step that's blanked
+    pub fn blanks(self) -> &'static [Blank] {
+        match self {
+            Difficulty::Easy => &[Blank::Rule],
+            Difficulty::Medium => &[Blank::Rule, Blank::Citations],
+            Difficulty::Hard => &[Blank::Formula, Blank::Rule, Blank::Citations],
+        }
+    }
+
+    /// Steps are blanked once every this many steps
+    pub fn spacing(self) -> usize {
+        match self {
+            Difficulty::Easy => 3,
+            Difficulty::Medium | Difficulty::Hard => 2,
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!("Unknown difficulty {s:?}; it can be easy, medium, or hard")),
+        }
+    }
+}
+
+/// A line cited by a blanked step, by line number
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Citation {
+    Line(usize),
+    /// First and last lines of a subproof
+    Subproof(usize, usize),
+}
+
+impl fmt::Display for Citation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Citation::Line(n) => write!(f, "{n}"),
+            Citation::Subproof(first, last) => write!(f, "{}", line_info::format_range((*first, *last))),
+        }
+    }
+}
+
+/// What was removed from a step, with `None` for the parts left in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Answer {
+    /// Line number of the step, counting from 1
+    pub line: usize,
+    pub formula: Option<Expr>,
+    pub rule: Option<Rule>,
+    pub citations: Option<Vec<Citation>>,
+}
+
+/// Answers to a handout, in order of line number
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnswerKey(pub Vec<Answer>);
+
+impl AnswerKey {
+    /// The parts of each line that were blanked, to save with the handout
+    pub fn blanks(&self) -> Blanks {
+        self.0
+            .iter()
+            .map(|answer| {
+                let parts = [(answer.formula.is_some(), Blank::Formula), (answer.rule.is_some(), Blank::Rule), (answer.citations.is_some(), Blank::Citations)];
+                (answer.line, parts.into_iter().filter_map(|(blanked, blank)| blanked.then_some(blank)).collect())
+            })
+            .collect()
+    }
+}
+
+/// One answer per line, like `Line 3: formula P ∧ Q; rule ∧ Introduction`
+impl fmt::Display for AnswerKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for answer in &self.0 {
+            let mut parts = vec![];
+            if let Some(formula) = &answer.formula {
+                parts.push(format!("formula {formula}"));
+            }
+            if let Some(rule) = answer.rule {
+                parts.push(format!("rule {}", rule.get_name()));
+            }
+            if let Some(citations) = &answer.citations {
+                let citations = citations.iter().map(Citation::to_string).collect::<Vec<_>>();
+                parts.push(if citations.is_empty() { "no citations".into() } else { format!("citations {}", citations.join(", ")) });
+            }
+            writeln!(f, "Line {}: {}", answer.line, parts.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Blank parts of the steps of a proof, as much as `difficulty` says, and
+/// return what was removed. Which steps are blanked is shifted by `offset`, so
+/// different offsets give different handouts of the same proof. `goals` are
+/// the proof's goals, whose lines keep their formulas, as in
+/// `Proof::concluding_lines`.
+pub fn blank_steps<P: Proof>(prf: &mut P, goals: &[Expr], difficulty: Difficulty, offset: usize) -> AnswerKey {
+    use Coproduct::{Inl, Inr};
+    let mut order = HashMap::new();
+    lines_in_order(prf.top_level_proof(), &mut order);
+    let info = LineInfo::of(prf);
+    let mut steps = order.iter().filter_map(|(r, &i)| if let Inr(Inl(jr)) = r { Some((i, jr.clone())) } else { None }).collect::<Vec<_>>();
+    steps.sort();
+    let concluding = prf.concluding_lines(goals);
+
+    let spacing = difficulty.spacing();
+    let mut answers = vec![];
+    for (i, jr) in steps.into_iter().skip((spacing - offset % spacing) % spacing).step_by(spacing) {
+        let just = match prf.lookup_step(&jr) {
+            Some(just) => just,
+            None => continue,
+        };
+        let mut answer = Answer { line: i + 1, formula: None, rule: None, citations: None };
+        for blank in difficulty.blanks() {
+            match blank {
+                Blank::Formula if !concluding.contains(&Coproduct::inject(jr.clone())) => answer.formula = Some(just.0.clone()),
+                Blank::Formula => {}
+                Blank::Rule => answer.rule = Some(just.1),
+                Blank::Citations => answer.citations = Some(citations(&just, &info)),
+            }
+        }
+        prf.with_mut_step(&jr, |step| {
+            if answer.formula.is_some() {
+                step.0 = Expr::var("");
+            }
+            if answer.rule.is_some() {
+                step.1 = RuleM::EmptyRule;
+            }
+            if answer.citations.is_some() {
+                step.2.clear();
+                step.3.clear();
+            }
+        });
+        answers.push(answer);
+    }
+    AnswerKey(answers)
+}
+
+/// Whether a blanked cell of a cloze proof was filled in, and if so, whether
+/// its line checks out
+#[derive(Debug, PartialEq, Eq)]
+pub enum BlankCheck<R, S> {
+    Unfilled,
+    Correct,
+    Incorrect(ProofCheckError<R, S>),
+}
+
+/// Check the lines of a cloze proof with blanks, by line number. Lines with a
+/// blank formula or rule that's still empty are unfilled; the rest are
+/// checked against the lines they cite, which are taken as they are, since
+/// students can only change the blanks.
+pub fn check_blanks<P: Proof>(prf: &P, blanks: &Blanks) -> BTreeMap<usize, BlankCheck<PjRef<P>, P::SubproofReference>> {
+    let mut order = HashMap::new();
+    lines_in_order(prf.top_level_proof(), &mut order);
+    let steps = order.iter().filter_map(|(r, &i)| if let Coproduct::Inr(Coproduct::Inl(jr)) = r { Some((i + 1, jr.clone())) } else { None }).collect::<HashMap<_, _>>();
+    let mut checks = BTreeMap::new();
+    for (&line, parts) in blanks {
+        let jr = match steps.get(&line) {
+            Some(jr) => jr.clone(),
+            None => continue,
+        };
+        let just = match prf.lookup_step(&jr) {
+            Some(just) => just,
+            None => continue,
+        };
+        let unfilled = (parts.contains(&Blank::Formula) && just.0 == Expr::var("")) || (parts.contains(&Blank::Rule) && just.1 == RuleM::EmptyRule);
+        let check = if unfilled {
+            BlankCheck::Unfilled
+        } else {
+            match prf.verify_line(&Coproduct::inject(jr)) {
+                Ok(()) => BlankCheck::Correct,
+                Err(err) => BlankCheck::Incorrect(err),
+            }
+        };
+        checks.insert(line, check);
+    }
+    checks
+}
+
+/// Line numbers of the locked cells of a cloze proof's `handout` that `prf`
+/// changed, counting from 1. Premises and unblanked formulas, rules, and
+/// citations have to stay as they are, and so do the lines themselves; lines
+/// added or removed are reported along with the lines they shift, and so are
+/// lines moved in or out of subproofs.
+pub fn changed_locked_cells<P: Proof>(prf: &P, handout: &P, blanks: &Blanks) -> Vec<usize> {
+    let (mut order, mut handout_order) = (HashMap::new(), HashMap::new());
+    lines_in_order(prf.top_level_proof(), &mut order);
+    lines_in_order(handout.top_level_proof(), &mut handout_order);
+    let (lines, handout_lines) = (lines_by_number(&order), lines_by_number(&handout_order));
+    let (info, handout_info) = (LineInfo::of(prf), LineInfo::of(handout));
+    // Lines are in the same subproof if their subproofs start on the same line
+    fn subproof_start<P: Proof>(prf: &P, r: &PjRef<P>, info: &LineInfo<P>) -> Option<usize> {
+        prf.parent_of_line(&pj_to_pjs::<P>(r.clone())).and_then(|sr| info.line_range_of_subproof(&sr)).map(|(first, _)| first)
+    }
+    let no_blanks = BTreeSet::new();
+    let mut changed = vec![];
+    for i in 0..std::cmp::max(lines.len(), handout_lines.len()) {
+        let unchanged = match (lines.get(i), handout_lines.get(i)) {
+            (Some(r), Some(handout_r)) if subproof_start(prf, r, &info) == subproof_start(handout, handout_r, &handout_info) => {
+                let parts = blanks.get(&(i + 1)).unwrap_or(&no_blanks);
+                match (prf.lookup_pj(r), handout.lookup_pj(handout_r)) {
+                    (Some(Coproduct::Inl(premise)), Some(Coproduct::Inl(handout_premise))) => premise == handout_premise,
+                    (Some(Coproduct::Inr(Coproduct::Inl(just))), Some(Coproduct::Inr(Coproduct::Inl(handout_just)))) => (parts.contains(&Blank::Formula) || just.0 == handout_just.0) && (parts.contains(&Blank::Rule) || just.1 == handout_just.1) && (parts.contains(&Blank::Citations) || citations(&just, &info) == citations(&handout_just, &handout_info)),
+                    _ => false,
+                }
+            }
+            _ => false,
+        };
+        if !unchanged {
+            changed.push(i + 1);
+        }
+    }
+    changed
+}
+
+/// Lines in `order`, sorted by line number
+fn lines_by_number<R: Clone>(order: &HashMap<R, usize>) -> Vec<R> {
+    let mut lines = order.iter().map(|(r, &i)| (i, r.clone())).collect::<Vec<_>>();
+    lines.sort_by_key(|(i, _)| *i);
+    lines.into_iter().map(|(_, r)| r).collect()
+}
+
+/// Lines and subproofs a step cites, by line number
+fn citations<P: Proof>(just: &Justification<Expr, PjRef<P>, P::SubproofReference>, info: &LineInfo<P>) -> Vec<Citation> {
+    let lines = just.2.iter().filter_map(|dep| info.line_of(dep)).map(Citation::Line);
+    let subproofs = just.3.iter().filter_map(|sr| info.line_range_of_subproof(sr)).map(|(first, last)| Citation::Subproof(first, last));
+    lines.chain(subproofs).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+    use crate::proofs::pooledproof::PooledProof;
+
+    use frunk_core::HList;
+
+    #[test]
+    fn test_blank_steps() {
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let r1 = Coproduct::inject(prf.add_premise(p("A")));
+        let sub = prf.add_subproof();
+        prf.with_mut_subproof(&sub, |sub| {
+            let r2 = sub.add_premise(p("B"));
+            sub.add_step(Justification(p("A"), RuleM::Reiteration, vec![r1], vec![]));
+            sub.add_step(Justification(p("B"), RuleM::Reiteration, vec![Coproduct::inject(r2)], vec![]));
+        });
+        prf.add_step(Justification(p("B -> A"), RuleM::ImpIntro, vec![], vec![sub]));
+
+        let mut other = prf.clone();
+        let key = blank_steps(&mut other, &[], Difficulty::Hard, 1);
+        assert_eq!(key, AnswerKey(vec![Answer { line: 4, formula: Some(p("B")), rule: Some(RuleM::Reiteration), citations: Some(vec![Citation::Line(2)]) }]));
+        // The last step keeps its formula, since it's what the proof concludes
+        let key = blank_steps(&mut prf, &[], Difficulty::Hard, 0);
+        assert_eq!(key.0[1], Answer { line: 5, formula: None, rule: Some(RuleM::ImpIntro), citations: Some(vec![Citation::Subproof(2, 4)]) });
+        assert_eq!(key.to_string(), "Line 3: formula A; rule Reiteration; citations 1\nLine 5: rule → Introduction; citations 2-4\n");
+        let last = prf.direct_lines()[0];
+        assert_eq!(prf.lookup_step(&last), Some(Justification(p("B -> A"), RuleM::EmptyRule, vec![], vec![])));
+
+        assert_eq!("medium".parse(), Ok(Difficulty::Medium));
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn test_cloze() {
+        let solution = || {
+            let mut prf = PooledProof::<HList![Expr]>::new();
+            let r1 = Coproduct::inject(prf.add_premise(p("A & B")));
+            let r2 = prf.add_step(Justification(p("A"), RuleM::AndElim, vec![r1], vec![]));
+            let r3 = prf.add_step(Justification(p("B"), RuleM::AndElim, vec![r1], vec![]));
+            prf.add_step(Justification(p("B & A"), RuleM::AndIntro, vec![Coproduct::inject(r3), Coproduct::inject(r2)], vec![]));
+            (prf, r1, r2, r3)
+        };
+        let (mut handout, ..) = solution();
+        let key = blank_steps(&mut handout, &[], Difficulty::Hard, 1);
+        let blanks = key.blanks();
+        assert_eq!(blanks, Blanks::from([(3, [Blank::Formula, Blank::Rule, Blank::Citations].into_iter().collect())]));
+        assert_eq!(check_blanks(&handout, &blanks), BTreeMap::from([(3, BlankCheck::Unfilled)]));
+        assert!(changed_locked_cells(&handout, &handout, &blanks).is_empty());
+
+        // Filling the blanks back in makes the line correct
+        let (filled, ..) = solution();
+        assert_eq!(check_blanks(&filled, &blanks), BTreeMap::from([(3, BlankCheck::Correct)]));
+        assert!(changed_locked_cells(&filled, &handout, &blanks).is_empty());
+        let (mut wrong, r1, r2, r3) = solution();
+        wrong.with_mut_step(&r3, |step| step.0 = p("C"));
+        assert!(matches!(check_blanks(&wrong, &blanks)[&3], BlankCheck::Incorrect(_)));
+
+        // Locked cells can't be changed, and neither can the lines
+        wrong.with_mut_step(&r2, |step| step.1 = RuleM::Reiteration);
+        assert_eq!(changed_locked_cells(&wrong, &handout, &blanks), vec![2]);
+        wrong.add_step(Justification(p("A"), RuleM::AndElim, vec![r1], vec![]));
+        assert_eq!(changed_locked_cells(&wrong, &handout, &blanks), vec![2, 5]);
+
+        assert_eq!("citations".parse(), Ok(Blank::Citations));
+        assert!("premise".parse::<Blank>().is_err());
+    }
+}
@@ -0,0 +1,120 @@
+//! When students wrote each line of a proof, for research on where they get
+//! stuck.
+//!
+//! A proof whose metadata has a `<timings>` element records, for each line,
+//! when it was first created and when it last became correct. The times are
+//! left out of the proof's hash, since they change as the proof is worked on
+//! without changing what it proves. `Timings::summary()` condenses them into
+//! how long the proof took and which line took longest.
+//!
+//! ```
+//! use aris::proofs::timings::{LineTiming, Timings};
+//!
+//! let timings = Timings { lines: [(1, LineTiming { created: 0, correct: Some(1_000) }), (2, LineTiming { created: 5_000, correct: Some(95_000) }), (3, LineTiming { created: 100_000, correct: None })].into_iter().collect() };
+//! assert_eq!(timings.summary().to_string(), "2 of 3 lines correct, 1m 35s in all; median 45s to get a line correct, longest 1m 30s on line 2");
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// When a line was first created and when it last became correct, in
+/// milliseconds since the Unix epoch
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LineTiming {
+    pub created: u64,
+    pub correct: Option<u64>,
+}
+
+impl LineTiming {
+    /// How long the line took to become correct, if it is
+    pub fn time_to_correct(&self) -> Option<u64> {
+        self.correct.map(|correct| correct.saturating_sub(self.created))
+    }
+}
+
+/// Timings of a proof's lines
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// Timings by line number, counting from 1
+    pub lines: BTreeMap<usize, LineTiming>,
+}
+
+/// How long a proof's lines took, from its `Timings`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimingSummary {
+    /// Number of lines with timings
+    pub lines: usize,
+    /// Number of those that became correct
+    pub correct: usize,
+    /// Milliseconds from the first line's creation to the last time a line
+    /// became correct, if any did
+    pub elapsed: Option<u64>,
+    /// Median milliseconds lines took to become correct
+    pub median_to_correct: Option<u64>,
+    /// The line that took longest to become correct, and how long it took
+    pub slowest: Option<(usize, u64)>,
+}
+
+impl Timings {
+    pub fn summary(&self) -> TimingSummary {
+        let mut times = self.lines.iter().filter_map(|(&line, timing)| Some((line, timing.time_to_correct()?))).collect::<Vec<_>>();
+        let first_created = self.lines.values().map(|timing| timing.created).min();
+        let last_correct = self.lines.values().filter_map(|timing| timing.correct).max();
+        let elapsed = first_created.zip(last_correct).map(|(first, last)| last.saturating_sub(first));
+        // The earliest line wins ties for slowest
+        let slowest = times.iter().copied().rev().max_by_key(|&(_, time)| time);
+        times.sort_by_key(|&(_, time)| time);
+        let median_to_correct = match times.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(times[n / 2].1),
+            n => Some((times[n / 2 - 1].1 + times[n / 2].1) / 2),
+        };
+        TimingSummary { lines: self.lines.len(), correct: times.len(), elapsed, median_to_correct, slowest }
+    }
+}
+
+/// Write milliseconds as hours, minutes, and seconds, like `1h 5m` or `2m 30s`
+pub fn format_duration(ms: u64) -> String {
+    let seconds = ms / 1000;
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s}s"),
+        (h, m, _) => format!("{h}h {m}m"),
+    }
+}
+
+impl fmt::Display for TimingSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} of {} lines correct", self.correct, self.lines)?;
+        if let Some(elapsed) = self.elapsed {
+            write!(f, ", {} in all", format_duration(elapsed))?;
+        }
+        if let Some(median) = self.median_to_correct {
+            write!(f, "; median {} to get a line correct", format_duration(median))?;
+        }
+        if let Some((line, time)) = self.slowest {
+            write!(f, ", longest {} on line {}", format_duration(time), line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(Timings::default().summary(), TimingSummary::default());
+        assert_eq!(Timings::default().summary().to_string(), "0 of 0 lines correct");
+
+        let timing = |created, correct| LineTiming { created, correct };
+        let timings = Timings { lines: [(1, timing(1_000, None)), (2, timing(2_000, Some(12_000))), (3, timing(3_000, Some(13_000))), (4, timing(4_000, Some(4_000)))].into_iter().collect() };
+        let summary = timings.summary();
+        assert_eq!(summary, TimingSummary { lines: 4, correct: 3, elapsed: Some(12_000), median_to_correct: Some(10_000), slowest: Some((2, 10_000)) });
+
+        assert_eq!(format_duration(59_999), "59s");
+        assert_eq!(format_duration(61_000), "1m 1s");
+        assert_eq!(format_duration(3_725_000), "1h 2m");
+    }
+}
@@ -1,27 +1,136 @@
 use crate::expr::Expr;
+use crate::parser::InfixOperator;
+use crate::parser::LogicMode;
+use crate::parser::Syntax;
+use crate::proofs::skeleton::Blank;
+use crate::proofs::skeleton::Blanks;
+use crate::proofs::timings::LineTiming;
+use crate::proofs::timings::Timings;
+use crate::proofs::AssumptionPolicy;
 use crate::proofs::Justification;
 use crate::proofs::PjRef;
 use crate::proofs::Proof;
+use crate::proofs::ScopingPolicy;
 use crate::rules::RuleM;
 
 use std::collections::HashMap;
 use std::io::{Read, Write};
 
+use ed25519_dalek::Signature;
+use ed25519_dalek::Signer;
+use ed25519_dalek::Verifier;
 use frunk_core::coproduct::Coproduct;
 use xml::reader::EventReader;
 
-#[derive(Debug, Clone)]
+pub use ed25519_dalek::SigningKey;
+pub use ed25519_dalek::VerifyingKey;
+
+#[derive(Debug, Clone, Default)]
 pub struct ProofMetaData {
     pub author: Option<String>, // TODO: it seems like the java SaveManager might treat this as a Vec<String>
     pub hash: Option<String>,
+    /// Ed25519 signature of the hash, as base64, if the proof was saved with
+    /// `xml_from_proof_and_metadata_signed`, stored in the `<signature>`
+    /// element. Checkers can confirm it with `verify_signature`.
+    pub signature: Option<String>,
     pub goals: Vec<Expr>,
+    /// Lockdown settings, if the proof is an exam assignment
+    pub exam: Option<ExamConfig>,
+    /// Feedback settings, if the proof is a practice assignment that only
+    /// shows whether lines are correct when it's submitted
+    pub delayed_feedback: Option<DelayedFeedback>,
+    /// Infix operators the proof declares, stored in the `<operators>`
+    /// element of its metadata. Editors and checkers should declare them
+    /// while the proof is open, as `ProofMetaData::while_open` does.
+    pub operators: Vec<InfixOperator>,
+    /// Logic the proof is written in, stored in the `<logic>` element of its
+    /// metadata if it isn't first-order. Editors and checkers should set it
+    /// while the proof is open, as `ProofMetaData::while_open` does.
+    pub logic_mode: LogicMode,
+    /// Whether the proof's conclusion has to use every premise, stored as an
+    /// empty `<useallpremises>` element. Editors should point out unused
+    /// premises and not count the proof as solved until there are none.
+    pub require_all_premises: bool,
+    /// Whether every formula has to be written fully parenthesized, stored as
+    /// an empty `<fullparens>` element. Editors should point out lines that
+    /// aren't, as `lints::input_lints()` does.
+    pub require_full_parens: bool,
+    /// How many assumptions the proof's subproofs may have, stored in the
+    /// `<assumptions>` element of its metadata if they may have several.
+    /// Editors and checkers should set it while the proof is open, as
+    /// `ProofMetaData::while_open` does.
+    pub assumption_policy: AssumptionPolicy,
+    /// Which lines steps may cite without reiterating them, stored in the
+    /// `<scoping>` element of its metadata if scoping is strict. Like the
+    /// assumption policy, it's set while the proof is open.
+    pub scoping_policy: ScopingPolicy,
+    /// Cells for students to fill in, if the proof is a cloze handout, stored
+    /// as `<blank>` elements in a `<cloze>` element. Editors should lock every
+    /// other cell, and checkers should only check the lines with blanks.
+    pub blanks: Blanks,
+    /// When each line was written and became correct, if the proof records
+    /// it, stored in the `<timings>` element. Editors should keep them up to
+    /// date while such a proof is open. They're left out of the hash.
+    pub timings: Option<Timings>,
+}
+
+impl ProofMetaData {
+    /// The syntax the proof is written in, with its logic and the operators
+    /// it declares
+    pub fn syntax(&self) -> Syntax {
+        Syntax { logic_mode: self.logic_mode, operators: self.operators.clone() }
+    }
+
+    /// Run `f` with the proof's syntax, assumption policy, and scoping policy
+    /// set on the current thread, as they should be while the proof is
+    /// checked, and restore the ones set before afterwards
+    pub fn while_open<T>(&self, f: impl FnOnce() -> T) -> T {
+        crate::parser::with_syntax(&self.syntax(), || crate::proofs::with_assumption_policy(self.assumption_policy, || crate::proofs::with_scoping_policy(self.scoping_policy, f)))
+    }
+}
+
+/// Settings of a proof handed out as an exam, stored in the `<exam>` element
+/// of its metadata. Editors should lock down features that could give away
+/// answers while such a proof is open.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExamConfig {
+    /// Time allowed for the exam, in minutes, if it's timed
+    pub duration_minutes: Option<u32>,
+    /// Name or ID of the student taking the exam, which is written into the
+    /// saved proof so it's covered by the hash
+    pub student: Option<String>,
+}
+
+/// Settings of a proof handed out with delayed feedback, stored in the
+/// `<delayedfeedback>` element of its metadata. Editors should hide whether
+/// lines are correct until the student submits the proof to be checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DelayedFeedback {
+    /// How many times the proof may be checked, if it's limited
+    pub allowed_checks: Option<u32>,
+    /// How many times the proof has been checked, which is written into the
+    /// saved proof so reopening it doesn't give the checks back
+    pub checks_used: u32,
 }
 
+impl DelayedFeedback {
+    /// How many more times the proof may be checked, or `None` if there's no
+    /// limit
+    pub fn checks_left(&self) -> Option<u32> {
+        self.allowed_checks.map(|allowed| allowed.saturating_sub(self.checks_used))
+    }
+}
+
+/// Read a proof and its metadata. The proof's lines are parsed in the logic
+/// and with the operators it declares, whatever syntax is set on the current
+/// thread, which is left alone.
 pub fn proof_from_xml<P: Proof, R: Read>(r: R) -> Result<(P, ProofMetaData), String> {
     let mut er = EventReader::new(r);
 
-    let mut metadata = ProofMetaData { author: None, hash: None, goals: vec![] };
+    let mut metadata = ProofMetaData::default();
 
+    // The metadata comes before the lines, so the syntax is known by the time they're parsed
+    let mut syntax = Syntax::default();
     let mut element_stack = vec![];
     let mut attribute_stack = vec![];
     let mut contents = String::new();
@@ -29,10 +138,11 @@ pub fn proof_from_xml<P: Proof, R: Read>(r: R) -> Result<(P, ProofMetaData), Str
     macro_rules! parse {
         ($x:expr) => {{
             let s: &str = $x;
-            match crate::parser::parse(&s) {
-                Some(e) => e,
-                None if s == "" => Expr::Var { name: "".to_string() },
-                None => return Err(format!("Failed to parse {:?}, element stack {:?}", s, element_stack)),
+            match crate::parser::try_parse_with(&syntax, &s) {
+                Ok(e) => e,
+                Err(_) if s == "" => Expr::Var { name: "".to_string() },
+                Err(err) if err.is_too_large() => return Err(format!("Failed to parse a line, element stack {:?}: {}", element_stack, err)),
+                Err(_) => return Err(format!("Failed to parse {:?}, element stack {:?}", s, element_stack)),
             }
         }};
     }
@@ -72,6 +182,11 @@ pub fn proof_from_xml<P: Proof, R: Read>(r: R) -> Result<(P, ProofMetaData), Str
                         last_rule = "".into();
                         seen_premises = vec![];
                     }
+                    "exam" => metadata.exam = Some(ExamConfig::default()),
+                    "delayedfeedback" => metadata.delayed_feedback = Some(DelayedFeedback::default()),
+                    "useallpremises" => metadata.require_all_premises = true,
+                    "fullparens" => metadata.require_full_parens = true,
+                    "timings" => metadata.timings = Some(Timings::default()),
                     _ => (),
                 }
             }
@@ -82,7 +197,7 @@ pub fn proof_from_xml<P: Proof, R: Read>(r: R) -> Result<(P, ProofMetaData), Str
                 //println!("end {:?} {:?}", element_stack, contents);
                 let element = element_stack.pop().unwrap();
                 assert_eq!(name.local_name, element);
-                let _attributes = attribute_stack.pop().unwrap();
+                let attributes = attribute_stack.pop().unwrap();
                 //println!("{:?} {:?}", element, attributes);
                 macro_rules! on_current_proof {
                     ($n:ident, $x:expr) => {
@@ -104,6 +219,70 @@ pub fn proof_from_xml<P: Proof, R: Read>(r: R) -> Result<(P, ProofMetaData), Str
                 match &*element {
                     "author" => metadata.author = Some(contents.clone()),
                     "hash" => metadata.hash = Some(contents.clone()),
+                    "signature" => metadata.signature = Some(contents.clone()),
+                    "duration" => {
+                        if let Some(exam) = &mut metadata.exam {
+                            exam.duration_minutes = Some(contents.trim().parse().map_err(|_| format!("Invalid exam duration {contents:?}"))?);
+                        }
+                    }
+                    "student" => {
+                        if let Some(exam) = &mut metadata.exam {
+                            exam.student = Some(contents.clone());
+                        }
+                    }
+                    "allowedchecks" => {
+                        if let Some(feedback) = &mut metadata.delayed_feedback {
+                            feedback.allowed_checks = Some(contents.trim().parse().map_err(|_| format!("Invalid number of allowed checks {contents:?}"))?);
+                        }
+                    }
+                    "checksused" => {
+                        if let Some(feedback) = &mut metadata.delayed_feedback {
+                            feedback.checks_used = contents.trim().parse().map_err(|_| format!("Invalid number of used checks {contents:?}"))?;
+                        }
+                    }
+                    "operator" => {
+                        let precedence = attributes.iter().find(|x| x.name.local_name == "precedence").map(|x| x.value.trim().parse().map_err(|_| format!("Invalid operator precedence {:?}", x.value))).transpose()?.unwrap_or(0);
+                        metadata.operators.push(InfixOperator::new(contents.trim(), precedence)?);
+                    }
+                    "operators" => syntax.operators = metadata.operators.clone(),
+                    "blank" => {
+                        let line = attributes.iter().find(|x| x.name.local_name == "line").ok_or("blank element has no line attribute")?;
+                        let line = line.value.trim().parse().map_err(|_| format!("Invalid blank line number {:?}", line.value))?;
+                        let parts = contents.split_whitespace().map(str::parse::<Blank>).collect::<Result<_, _>>()?;
+                        metadata.blanks.insert(line, parts);
+                    }
+                    "timing" => {
+                        let attribute = |name: &str| attributes.iter().find(|x| x.name.local_name == name).map(|x| x.value.trim().parse::<u64>().map_err(|_| format!("Invalid timing {name} {:?}", x.value))).transpose();
+                        let line = attribute("line")?.ok_or("timing element has no line attribute")?;
+                        let timing = LineTiming { created: attribute("created")?.unwrap_or(0), correct: attribute("correct")? };
+                        if let Some(timings) = &mut metadata.timings {
+                            timings.lines.insert(line as usize, timing);
+                        }
+                    }
+                    "assumptions" => {
+                        metadata.assumption_policy = match contents.trim() {
+                            "single" => AssumptionPolicy::Single,
+                            "multiple" => AssumptionPolicy::Multiple,
+                            policy => return Err(format!("Unknown assumption policy {policy:?}")),
+                        };
+                    }
+                    "scoping" => {
+                        metadata.scoping_policy = match contents.trim() {
+                            "enclosing" => ScopingPolicy::Enclosing,
+                            "strict" => ScopingPolicy::Strict,
+                            policy => return Err(format!("Unknown scoping policy {policy:?}")),
+                        };
+                    }
+                    "logic" => {
+                        metadata.logic_mode = match contents.trim() {
+                            "first-order" => LogicMode::FirstOrder,
+                            "higher-order" => LogicMode::HigherOrder,
+                            "temporal" => LogicMode::Temporal,
+                            "program" => LogicMode::Program,
+                            logic => return Err(format!("Unknown logic {logic:?}")),
+                        };
+                        syntax.logic_mode = metadata.logic_mode;
+                    }
                     "raw" => {
                         last_raw = contents.clone();
                     }
@@ -136,10 +315,8 @@ pub fn proof_from_xml<P: Proof, R: Read>(r: R) -> Result<(P, ProofMetaData), Str
                             }
                         }
                     }
-                    "goal" => {
-                        if !last_raw.is_empty() {
-                            metadata.goals.push(parse!(&last_raw));
-                        }
+                    "goal" if !last_raw.is_empty() => {
+                        metadata.goals.push(parse!(&last_raw));
                     }
                     _ => (),
                 }
@@ -155,7 +332,14 @@ pub fn proof_from_xml<P: Proof, R: Read>(r: R) -> Result<(P, ProofMetaData), Str
     Ok((proof, metadata))
 }
 
+/// Write a proof and its metadata, printing its lines with the operators it
+/// declares
 pub fn xml_from_proof_and_metadata<P: Proof, W: Write>(prf: &P, meta: &ProofMetaData, out: W) -> xml::writer::Result<()> {
+    // Temporal operators print differently depending on the logic
+    crate::parser::with_syntax(&meta.syntax(), || write_xml_from_proof_and_metadata(prf, meta, out))
+}
+
+fn write_xml_from_proof_and_metadata<P: Proof, W: Write>(prf: &P, meta: &ProofMetaData, out: W) -> xml::writer::Result<()> {
     use xml::writer::{
         EmitterConfig, EventWriter,
         XmlEvent::{self, *},
@@ -179,6 +363,78 @@ pub fn xml_from_proof_and_metadata<P: Proof, W: Write>(prf: &P, meta: &ProofMeta
     if let Some(hash) = &meta.hash {
         leaf_tag(&mut ew, "hash", hash)?;
     }
+    if let Some(signature) = &meta.signature {
+        leaf_tag(&mut ew, "signature", signature)?;
+    }
+    if let Some(exam) = &meta.exam {
+        ew.write(XmlEvent::start_element("exam"))?;
+        if let Some(duration) = exam.duration_minutes {
+            leaf_tag(&mut ew, "duration", &duration.to_string())?;
+        }
+        if let Some(student) = &exam.student {
+            leaf_tag(&mut ew, "student", student)?;
+        }
+        ew.write(XmlEvent::end_element().name("exam"))?;
+    }
+    if let Some(feedback) = &meta.delayed_feedback {
+        ew.write(XmlEvent::start_element("delayedfeedback"))?;
+        if let Some(allowed) = feedback.allowed_checks {
+            leaf_tag(&mut ew, "allowedchecks", &allowed.to_string())?;
+        }
+        leaf_tag(&mut ew, "checksused", &feedback.checks_used.to_string())?;
+        ew.write(XmlEvent::end_element().name("delayedfeedback"))?;
+    }
+    if !meta.operators.is_empty() {
+        ew.write(XmlEvent::start_element("operators"))?;
+        for op in &meta.operators {
+            ew.write(XmlEvent::start_element("operator").attr("precedence", &op.precedence.to_string()))?;
+            ew.write(Characters(&op.symbol))?;
+            ew.write(XmlEvent::end_element().name("operator"))?;
+        }
+        ew.write(XmlEvent::end_element().name("operators"))?;
+    }
+    match meta.logic_mode {
+        LogicMode::FirstOrder => {}
+        LogicMode::HigherOrder => leaf_tag(&mut ew, "logic", "higher-order")?,
+        LogicMode::Temporal => leaf_tag(&mut ew, "logic", "temporal")?,
+        LogicMode::Program => leaf_tag(&mut ew, "logic", "program")?,
+    }
+    if meta.assumption_policy == AssumptionPolicy::Multiple {
+        leaf_tag(&mut ew, "assumptions", "multiple")?;
+    }
+    if meta.scoping_policy == ScopingPolicy::Strict {
+        leaf_tag(&mut ew, "scoping", "strict")?;
+    }
+    if meta.require_all_premises {
+        ew.write(XmlEvent::start_element("useallpremises"))?;
+        ew.write(XmlEvent::end_element().name("useallpremises"))?;
+    }
+    if meta.require_full_parens {
+        ew.write(XmlEvent::start_element("fullparens"))?;
+        ew.write(XmlEvent::end_element().name("fullparens"))?;
+    }
+    if !meta.blanks.is_empty() {
+        ew.write(XmlEvent::start_element("cloze"))?;
+        for (line, parts) in &meta.blanks {
+            ew.write(XmlEvent::start_element("blank").attr("line", &line.to_string()))?;
+            ew.write(Characters(&parts.iter().map(|blank| blank.name()).collect::<Vec<_>>().join(" ")))?;
+            ew.write(XmlEvent::end_element().name("blank"))?;
+        }
+        ew.write(XmlEvent::end_element().name("cloze"))?;
+    }
+    if let Some(timings) = &meta.timings {
+        ew.write(XmlEvent::start_element("timings"))?;
+        for (line, timing) in &timings.lines {
+            let (line, created, correct) = (line.to_string(), timing.created.to_string(), timing.correct.map(|correct| correct.to_string()));
+            let element = XmlEvent::start_element("timing").attr("line", &line).attr("created", &created);
+            ew.write(match &correct {
+                Some(correct) => element.attr("correct", correct),
+                None => element,
+            })?;
+            ew.write(XmlEvent::end_element().name("timing"))?;
+        }
+        ew.write(XmlEvent::end_element().name("timings"))?;
+    }
     ew.write(XmlEvent::end_element().name("metadata"))?;
 
     struct SerializationState<P: Proof> {
@@ -263,24 +519,80 @@ pub fn xml_from_proof_and_metadata<P: Proof, W: Write>(prf: &P, meta: &ProofMeta
     Ok(())
 }
 
-pub fn xml_from_proof_and_metadata_with_hash<P: Proof, W: Write>(prf: &P, meta: &ProofMetaData, out: W) -> xml::writer::Result<()> {
-    use base64::Engine;
+/// SHA-256 of a proof saved without its hash, signature, or timings,
+/// followed by its author. Timings change without changing the proof, so
+/// they aren't covered.
+fn canonical_hash<P: Proof>(prf: &P, meta: &ProofMetaData) -> xml::writer::Result<Vec<u8>> {
     use sha2::Digest;
-    let mut meta = meta.clone();
-    meta.hash = None;
+    let hashed = ProofMetaData { hash: None, signature: None, timings: None, ..meta.clone() };
     let mut payload = vec![];
-    xml_from_proof_and_metadata(prf, &meta, &mut payload)?;
+    xml_from_proof_and_metadata(prf, &hashed, &mut payload)?;
     let mut ctx = sha2::Sha256::new();
     ctx.update(&payload[..]);
     ctx.update(b"\n");
     if let Some(author) = &meta.author {
         ctx.update(author);
     }
-    let hash = ctx.finalize();
-    meta.hash = Some(base64::engine::general_purpose::STANDARD.encode(&hash[..]));
+    Ok(ctx.finalize().to_vec())
+}
+
+/// Save a proof with its hash, which covers the proof and its metadata. A
+/// signature it was opened with is dropped, since it wouldn't match anymore.
+pub fn xml_from_proof_and_metadata_with_hash<P: Proof, W: Write>(prf: &P, meta: &ProofMetaData, out: W) -> xml::writer::Result<()> {
+    use base64::Engine;
+    let hash = canonical_hash(prf, meta)?;
+    let meta = ProofMetaData { hash: Some(base64::engine::general_purpose::STANDARD.encode(&hash[..])), signature: None, ..meta.clone() };
+    xml_from_proof_and_metadata(prf, &meta, out)
+}
+
+/// Save a proof with its hash, like `xml_from_proof_and_metadata_with_hash`,
+/// and an Ed25519 signature of the hash made with `key`, so whoever has its
+/// public key can confirm where the proof was saved with `verify_signature`
+pub fn xml_from_proof_and_metadata_signed<P: Proof, W: Write>(prf: &P, meta: &ProofMetaData, key: &SigningKey, out: W) -> xml::writer::Result<()> {
+    use base64::Engine;
+    let hash = canonical_hash(prf, meta)?;
+    let signature = key.sign(&hash);
+    let meta = ProofMetaData { hash: Some(base64::engine::general_purpose::STANDARD.encode(&hash[..])), signature: Some(base64::engine::general_purpose::STANDARD.encode(signature.to_bytes())), ..meta.clone() };
     xml_from_proof_and_metadata(prf, &meta, out)
 }
 
+/// Confirm that a proof was saved with `xml_from_proof_and_metadata_signed`
+/// by the holder of the secret key of `key`, and hasn't changed since: its
+/// hash has to match the proof and its metadata, and its signature the hash
+pub fn verify_signature<P: Proof>(prf: &P, meta: &ProofMetaData, key: &VerifyingKey) -> Result<(), String> {
+    use base64::Engine;
+    let signature = meta.signature.as_ref().ok_or("The proof isn't signed")?;
+    let hash = canonical_hash(prf, meta).map_err(|err| format!("Could not hash the proof: {err}"))?;
+    if meta.hash.as_ref() != Some(&base64::engine::general_purpose::STANDARD.encode(&hash[..])) {
+        return Err("The proof's hash doesn't match its contents".into());
+    }
+    let signature = base64::engine::general_purpose::STANDARD.decode(signature).ok().and_then(|bytes| Signature::from_slice(&bytes).ok()).ok_or("The proof's signature isn't valid base64 of an Ed25519 signature")?;
+    key.verify(&hash, &signature).map_err(|_| "The proof's signature doesn't match its hash".into())
+}
+
+/// A secret key for `xml_from_proof_and_metadata_signed`, from the base64 of
+/// its 32 bytes
+pub fn signing_key_from_base64(key: &str) -> Result<SigningKey, String> {
+    use base64::Engine;
+    let bytes = base64::engine::general_purpose::STANDARD.decode(key.trim()).map_err(|err| format!("The signing key isn't valid base64: {err}"))?;
+    let bytes = <[u8; 32]>::try_from(&bytes[..]).map_err(|_| format!("The signing key is {} bytes long, not 32", bytes.len()))?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+/// A public key for `verify_signature`, from the base64 of its 32 bytes
+pub fn verifying_key_from_base64(key: &str) -> Result<VerifyingKey, String> {
+    use base64::Engine;
+    let bytes = base64::engine::general_purpose::STANDARD.decode(key.trim()).map_err(|err| format!("The public key isn't valid base64: {err}"))?;
+    let bytes = <[u8; 32]>::try_from(&bytes[..]).map_err(|_| format!("The public key is {} bytes long, not 32", bytes.len()))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|err| format!("The public key isn't a valid Ed25519 key: {err}"))
+}
+
+/// The base64 of a public key's 32 bytes, the inverse of `verifying_key_from_base64`
+pub fn verifying_key_to_base64(key: &VerifyingKey) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(key.as_bytes())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -361,4 +673,280 @@ mod tests {
         println!("{prf}");
         println!("{metadata:?}");
     }
+
+    #[test]
+    fn test_xml_exam_metadata() {
+        let xml = b"<bram>\n  <metadata>\n    <author>UNKNOWN</author>\n    <exam>\n      <duration>45</duration>\n    </exam>\n  </metadata>\n  <proof id=\"0\">\n    <assumption linenum=\"0\">\n      <raw>p</raw>\n    </assumption>\n  </proof>\n</bram>\n";
+        type P = PooledProof<HList![Expr]>;
+        let (prf, mut metadata) = proof_from_xml::<P, _>(&xml[..]).unwrap();
+        assert_eq!(metadata.exam, Some(ExamConfig { duration_minutes: Some(45), student: None }));
+
+        metadata.exam.as_mut().unwrap().student = Some("Jane Doe".into());
+        let mut reserialized = vec![];
+        xml_from_proof_and_metadata_with_hash(&prf, &metadata, &mut reserialized).unwrap();
+        let (_, metadata) = proof_from_xml::<P, _>(&reserialized[..]).unwrap();
+        assert_eq!(metadata.exam, Some(ExamConfig { duration_minutes: Some(45), student: Some("Jane Doe".into()) }));
+        assert!(metadata.hash.is_some());
+
+        let no_exam = b"<bram>\n  <metadata>\n    <author>UNKNOWN</author>\n  </metadata>\n  <proof id=\"0\"/>\n</bram>\n";
+        let (_, metadata) = proof_from_xml::<P, _>(&no_exam[..]).unwrap();
+        assert_eq!(metadata.exam, None);
+        assert_eq!(metadata.delayed_feedback, None);
+    }
+
+    #[test]
+    fn test_xml_delayed_feedback_metadata() {
+        let xml = b"<bram>\n  <metadata>\n    <author>UNKNOWN</author>\n    <delayedfeedback>\n      <allowedchecks>3</allowedchecks>\n    </delayedfeedback>\n  </metadata>\n  <proof id=\"0\">\n    <assumption linenum=\"0\">\n      <raw>p</raw>\n    </assumption>\n  </proof>\n</bram>\n";
+        type P = PooledProof<HList![Expr]>;
+        let (prf, mut metadata) = proof_from_xml::<P, _>(&xml[..]).unwrap();
+        assert_eq!(metadata.delayed_feedback, Some(DelayedFeedback { allowed_checks: Some(3), checks_used: 0 }));
+
+        metadata.delayed_feedback.as_mut().unwrap().checks_used = 2;
+        let mut reserialized = vec![];
+        xml_from_proof_and_metadata_with_hash(&prf, &metadata, &mut reserialized).unwrap();
+        let (_, metadata) = proof_from_xml::<P, _>(&reserialized[..]).unwrap();
+        let feedback = metadata.delayed_feedback.unwrap();
+        assert_eq!(feedback, DelayedFeedback { allowed_checks: Some(3), checks_used: 2 });
+        assert_eq!(feedback.checks_left(), Some(1));
+        assert_eq!(DelayedFeedback { allowed_checks: Some(1), checks_used: 2 }.checks_left(), Some(0));
+        assert_eq!(DelayedFeedback::default().checks_left(), None);
+    }
+
+    #[test]
+    fn test_xml_require_all_premises() {
+        let xml = b"<bram>\n  <metadata>\n    <useallpremises/>\n  </metadata>\n  <proof id=\"0\">\n    <assumption linenum=\"0\">\n      <raw>p</raw>\n    </assumption>\n  </proof>\n</bram>\n";
+        type P = PooledProof<HList![Expr]>;
+        let (prf, metadata) = proof_from_xml::<P, _>(&xml[..]).unwrap();
+        assert!(metadata.require_all_premises);
+
+        let mut reserialized = vec![];
+        xml_from_proof_and_metadata(&prf, &metadata, &mut reserialized).unwrap();
+        assert!(proof_from_xml::<P, _>(&reserialized[..]).unwrap().1.require_all_premises);
+        let xml = String::from_utf8(xml.to_vec()).unwrap().replace("<useallpremises/>", "");
+        assert!(!proof_from_xml::<P, _>(xml.as_bytes()).unwrap().1.require_all_premises);
+    }
+
+    #[test]
+    fn test_xml_assumption_policy() {
+        let xml = b"<bram>\n  <metadata>\n    <assumptions>multiple</assumptions>\n  </metadata>\n  <proof id=\"0\">\n    <assumption linenum=\"0\">\n      <raw>p</raw>\n    </assumption>\n  </proof>\n</bram>\n";
+        type P = PooledProof<HList![Expr]>;
+        let (prf, metadata) = proof_from_xml::<P, _>(&xml[..]).unwrap();
+        assert_eq!(metadata.assumption_policy, AssumptionPolicy::Multiple);
+
+        let mut reserialized = vec![];
+        xml_from_proof_and_metadata(&prf, &metadata, &mut reserialized).unwrap();
+        assert_eq!(proof_from_xml::<P, _>(&reserialized[..]).unwrap().1.assumption_policy, AssumptionPolicy::Multiple);
+        let xml = String::from_utf8(xml.to_vec()).unwrap();
+        assert_eq!(proof_from_xml::<P, _>(xml.replace("<assumptions>multiple</assumptions>", "").as_bytes()).unwrap().1.assumption_policy, AssumptionPolicy::Single);
+        assert!(proof_from_xml::<P, _>(xml.replace("multiple", "several").as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_xml_signature() {
+        let data = &include_bytes!("../../../example-proofs/propositional_logic_arguments_for_proofs_ii_problem_10.bram")[..];
+        type P = PooledProof<HList![Expr]>;
+        let (prf, metadata) = proof_from_xml::<P, _>(data).unwrap();
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let public_key = key.verifying_key();
+
+        // Signing keeps the hash of a proof saved without a signature
+        let mut unsigned = vec![];
+        xml_from_proof_and_metadata_with_hash(&prf, &metadata, &mut unsigned).unwrap();
+        let mut signed = vec![];
+        xml_from_proof_and_metadata_signed(&prf, &metadata, &key, &mut signed).unwrap();
+        let (unsigned_prf, unsigned_meta) = proof_from_xml::<P, _>(&unsigned[..]).unwrap();
+        let (signed_prf, signed_meta) = proof_from_xml::<P, _>(&signed[..]).unwrap();
+        assert_eq!(signed_meta.hash, unsigned_meta.hash);
+        assert!(signed_meta.signature.is_some());
+        assert_eq!(verify_signature(&signed_prf, &signed_meta, &public_key), Ok(()));
+        assert_eq!(verify_signature(&unsigned_prf, &unsigned_meta, &public_key), Err("The proof isn't signed".into()));
+        assert_eq!(verify_signature(&signed_prf, &signed_meta, &SigningKey::from_bytes(&[8; 32]).verifying_key()), Err("The proof's signature doesn't match its hash".into()));
+
+        // Saving it again without the key drops the signature, which wouldn't match anymore
+        let mut resaved = vec![];
+        xml_from_proof_and_metadata_with_hash(&signed_prf, &signed_meta, &mut resaved).unwrap();
+        assert_eq!(proof_from_xml::<P, _>(&resaved[..]).unwrap().1.signature, None);
+
+        // Keys are given as base64
+        use base64::Engine;
+        let encoded = verifying_key_to_base64(&public_key);
+        assert_eq!(encoded, base64::engine::general_purpose::STANDARD.encode(public_key.as_bytes()));
+        assert_eq!(verifying_key_from_base64(&encoded), Ok(public_key));
+        assert_eq!(signing_key_from_base64(&base64::engine::general_purpose::STANDARD.encode([7; 32])).unwrap().verifying_key(), public_key);
+        assert!(verifying_key_from_base64("not a key").is_err());
+        assert!(signing_key_from_base64(&encoded[..8]).is_err());
+    }
+
+    #[test]
+    fn test_xml_signature_tampered() {
+        let data = &include_bytes!("../../../example-proofs/propositional_logic_arguments_for_proofs_ii_problem_10.bram")[..];
+        type P = PooledProof<HList![Expr]>;
+        let (prf, metadata) = proof_from_xml::<P, _>(data).unwrap();
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let mut signed = vec![];
+        xml_from_proof_and_metadata_signed(&prf, &metadata, &key, &mut signed).unwrap();
+        let signed = String::from_utf8(signed).unwrap();
+        let verify = |xml: &str| {
+            let (prf, meta) = proof_from_xml::<P, _>(xml.as_bytes()).unwrap();
+            verify_signature(&prf, &meta, &key.verifying_key())
+        };
+        assert_eq!(verify(&signed), Ok(()));
+
+        // Changing a line, the author, or the metadata breaks the hash
+        assert_eq!(verify(&signed.replacen("<raw>(¬A ∨ B)</raw>", "<raw>(A ∨ B)</raw>", 1)), Err("The proof's hash doesn't match its contents".into()));
+        assert_eq!(verify(&signed.replace("<author>UNKNOWN</author>", "<author>someone else</author>")), Err("The proof's hash doesn't match its contents".into()));
+        assert_eq!(verify(&signed.replace("</metadata>", "<useallpremises/></metadata>")), Err("The proof's hash doesn't match its contents".into()));
+
+        // Changing the proof and hashing it again breaks the signature instead
+        let (tampered, meta) = proof_from_xml::<P, _>(signed.replacen("<raw>(¬A ∨ B)</raw>", "<raw>(A ∨ B)</raw>", 1).as_bytes()).unwrap();
+        let mut rehashed = vec![];
+        xml_from_proof_and_metadata_with_hash(&tampered, &meta, &mut rehashed).unwrap();
+        let rehashed = String::from_utf8(rehashed).unwrap();
+        let signature = meta.signature.unwrap();
+        let rehashed = rehashed.replace("</hash>", &format!("</hash>\n    <signature>{signature}</signature>"));
+        assert_eq!(verify(&rehashed), Err("The proof's signature doesn't match its hash".into()));
+        assert!(verify(&signed.replace(&signature, "AAAA")).is_err());
+    }
+
+    #[test]
+    fn test_xml_scoping_policy() {
+        use crate::parser::parse_unwrap as p;
+        use crate::rules::ProofCheckError;
+
+        let xml = "<bram>\n  <metadata>\n    <scoping>strict</scoping>\n  </metadata>\n  <proof id=\"0\">\n  </proof>\n</bram>\n";
+        type P = PooledProof<HList![Expr]>;
+        let metadata = proof_from_xml::<P, _>(xml.as_bytes()).unwrap().1;
+        assert_eq!(metadata.scoping_policy, ScopingPolicy::Strict);
+        assert_eq!(proof_from_xml::<P, _>(xml.replace("<scoping>strict</scoping>", "").as_bytes()).unwrap().1.scoping_policy, ScopingPolicy::Enclosing);
+        assert!(proof_from_xml::<P, _>(xml.replace(">strict<", ">loose<").as_bytes()).is_err());
+
+        // A step citing a line from outside its subproof is only correct in a
+        // proof that doesn't require reiterating it first
+        let mut prf = P::new();
+        let a = Coproduct::inject(prf.add_premise(p("A")));
+        let sub = prf.add_subproof();
+        prf.with_mut_subproof(&sub, |sub| {
+            let b = Coproduct::inject(sub.add_premise(p("B")));
+            sub.add_step(Justification(p("A & B"), RuleM::AndIntro, vec![a, b], vec![]));
+        });
+        let mut serialized = vec![];
+        xml_from_proof_and_metadata(&prf, &metadata, &mut serialized).unwrap();
+        let (prf, metadata) = proof_from_xml::<P, _>(&serialized[..]).unwrap();
+        assert_eq!(metadata.scoping_policy, ScopingPolicy::Strict);
+        let sub = prf.lookup_subproof(&Coproduct::uninject(prf.lines()[0]).unwrap()).unwrap();
+        let step = Coproduct::inject(sub.direct_lines()[0]);
+        assert_eq!(metadata.while_open(|| prf.verify_line(&step)), Err(ProofCheckError::NeedsReiteration(vec![Coproduct::inject(prf.premises()[0])])));
+        assert_eq!(prf.verify_line(&step), Ok(()));
+    }
+
+    #[test]
+    fn test_xml_require_full_parens() {
+        let xml = b"<bram>\n  <metadata>\n    <fullparens/>\n  </metadata>\n  <proof id=\"0\">\n    <assumption linenum=\"0\">\n      <raw>p</raw>\n    </assumption>\n  </proof>\n</bram>\n";
+        type P = PooledProof<HList![Expr]>;
+        let (prf, metadata) = proof_from_xml::<P, _>(&xml[..]).unwrap();
+        assert!(metadata.require_full_parens);
+        assert!(!metadata.require_all_premises);
+
+        let mut reserialized = vec![];
+        xml_from_proof_and_metadata(&prf, &metadata, &mut reserialized).unwrap();
+        assert!(proof_from_xml::<P, _>(&reserialized[..]).unwrap().1.require_full_parens);
+    }
+
+    #[test]
+    fn test_xml_cloze() {
+        let xml = "<bram>\n  <metadata>\n    <cloze>\n      <blank line=\"2\">rule citations</blank>\n    </cloze>\n  </metadata>\n  <proof id=\"0\">\n    <assumption linenum=\"0\">\n      <raw>p</raw>\n    </assumption>\n    <step linenum=\"1\">\n      <raw>p</raw>\n      <rule>EMPTY_RULE</rule>\n    </step>\n  </proof>\n</bram>\n";
+        type P = PooledProof<HList![Expr]>;
+        let (prf, metadata) = proof_from_xml::<P, _>(xml.as_bytes()).unwrap();
+        let blanks = Blanks::from([(2, [Blank::Rule, Blank::Citations].into_iter().collect())]);
+        assert_eq!(metadata.blanks, blanks);
+
+        let mut reserialized = vec![];
+        xml_from_proof_and_metadata(&prf, &metadata, &mut reserialized).unwrap();
+        assert_eq!(proof_from_xml::<P, _>(&reserialized[..]).unwrap().1.blanks, blanks);
+        assert!(proof_from_xml::<P, _>(xml.replace("rule citations", "premise").as_bytes()).is_err());
+        assert!(proof_from_xml::<P, _>(xml.replace("line=\"2\"", "line=\"two\"").as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_xml_timings() {
+        let xml = "<bram>\n  <metadata>\n    <timings>\n      <timing line=\"1\" created=\"1000\" correct=\"1500\"/>\n      <timing line=\"2\" created=\"2000\"/>\n    </timings>\n  </metadata>\n  <proof id=\"0\">\n    <assumption linenum=\"0\">\n      <raw>p</raw>\n    </assumption>\n  </proof>\n</bram>\n";
+        type P = PooledProof<HList![Expr]>;
+        let (prf, mut metadata) = proof_from_xml::<P, _>(xml.as_bytes()).unwrap();
+        let timings = Timings { lines: [(1, LineTiming { created: 1000, correct: Some(1500) }), (2, LineTiming { created: 2000, correct: None })].into_iter().collect() };
+        assert_eq!(metadata.timings, Some(timings.clone()));
+
+        let mut reserialized = vec![];
+        xml_from_proof_and_metadata_with_hash(&prf, &metadata, &mut reserialized).unwrap();
+        let (_, reread) = proof_from_xml::<P, _>(&reserialized[..]).unwrap();
+        assert_eq!(reread.timings, Some(timings));
+        // Timings don't change the hash
+        metadata.timings = None;
+        let mut untimed = vec![];
+        xml_from_proof_and_metadata_with_hash(&prf, &metadata, &mut untimed).unwrap();
+        assert_eq!(proof_from_xml::<P, _>(&untimed[..]).unwrap().1.hash, reread.hash);
+
+        assert_eq!(proof_from_xml::<P, _>(xml.replace("<timings>", "").replace("</timings>", "").as_bytes()).unwrap().1.timings, None);
+        assert!(proof_from_xml::<P, _>(xml.replace("\"1500\"", "\"soon\"").as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_xml_operators() {
+        let xml = "<bram>\n  <metadata>\n    <operators>\n      <operator precedence=\"1\">R</operator>\n      <operator precedence=\"2\">⊕</operator>\n    </operators>\n  </metadata>\n  <proof id=\"0\">\n    <assumption linenum=\"0\">\n      <raw>x R y ⊕ z</raw>\n    </assumption>\n  </proof>\n</bram>\n";
+        type P = PooledProof<HList![Expr]>;
+        // Operators declared on the thread are neither used nor changed while a proof is read
+        let declared = vec![InfixOperator::new("S", 0).unwrap()];
+        crate::parser::with_syntax(&Syntax { operators: declared.clone(), ..Syntax::default() }, || {
+            let (prf, metadata) = proof_from_xml::<P, _>(xml.as_bytes()).unwrap();
+            assert_eq!(metadata.operators, vec![InfixOperator::new("R", 1).unwrap(), InfixOperator::new("⊕", 2).unwrap()]);
+            let (x, y, z) = (Expr::var("x"), Expr::var("y"), Expr::var("z"));
+            let premise = Expr::apply(Expr::var("R"), &[x, Expr::apply(Expr::var("⊕"), &[y, z])]);
+            assert_eq!(prf.lookup_premise(&prf.premises()[0]), Some(premise.clone()));
+            assert_eq!(crate::parser::operators(), declared);
+            let undeclared = "<bram>\n  <proof id=\"0\">\n    <assumption linenum=\"0\">\n      <raw>x S y</raw>\n    </assumption>\n  </proof>\n</bram>\n";
+            assert!(proof_from_xml::<P, _>(undeclared.as_bytes()).is_err());
+
+            let mut reserialized = vec![];
+            xml_from_proof_and_metadata(&prf, &metadata, &mut reserialized).unwrap();
+            assert!(String::from_utf8_lossy(&reserialized).contains("<raw>(x R (y ⊕ z))</raw>"));
+            assert_eq!(crate::parser::operators(), declared);
+            let (prf, metadata) = proof_from_xml::<P, _>(&reserialized[..]).unwrap();
+            assert_eq!(metadata.operators.len(), 2);
+            assert_eq!(prf.lookup_premise(&prf.premises()[0]), Some(premise));
+        });
+
+        let invalid = xml.replace(">R<", ">-><");
+        assert!(proof_from_xml::<P, _>(invalid.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_xml_logic_mode() {
+        let xml = "<bram>\n  <metadata>\n    <logic>higher-order</logic>\n  </metadata>\n  <proof id=\"0\">\n    <assumption linenum=\"0\">\n      <raw>(λx P(x))(a)</raw>\n    </assumption>\n  </proof>\n</bram>\n";
+        type P = PooledProof<HList![Expr]>;
+        let (prf, metadata) = proof_from_xml::<P, _>(xml.as_bytes()).unwrap();
+        assert_eq!(metadata.logic_mode, LogicMode::HigherOrder);
+        assert_eq!(prf.lookup_premise(&prf.premises()[0]), Some(Expr::apply(Expr::lambda("x", Expr::apply(Expr::var("P"), &[Expr::var("x")])), &[Expr::var("a")])));
+        assert_eq!(crate::parser::logic_mode(), LogicMode::FirstOrder);
+
+        let mut reserialized = vec![];
+        xml_from_proof_and_metadata(&prf, &metadata, &mut reserialized).unwrap();
+        let (_, metadata) = proof_from_xml::<P, _>(&reserialized[..]).unwrap();
+        assert_eq!(metadata.logic_mode, LogicMode::HigherOrder);
+
+        // Lambdas are only understood in higher-order logic
+        assert!(proof_from_xml::<P, _>(xml.replace("<logic>higher-order</logic>", "").as_bytes()).is_err());
+        assert!(proof_from_xml::<P, _>(xml.replace("higher-order", "modal").as_bytes()).is_err());
+
+        let xml = xml.replace("higher-order", "temporal").replace("(λx P(x))(a)", "p U X q");
+        let (prf, metadata) = proof_from_xml::<P, _>(xml.as_bytes()).unwrap();
+        assert_eq!(metadata.logic_mode, LogicMode::Temporal);
+        assert_eq!(prf.lookup_premise(&prf.premises()[0]), Some(Expr::until(Expr::var("p"), Expr::next(Expr::var("q")))));
+        let mut reserialized = vec![];
+        xml_from_proof_and_metadata(&prf, &metadata, &mut reserialized).unwrap();
+        assert!(String::from_utf8(reserialized).unwrap().contains("<raw>(p U X q)</raw>"));
+
+        let xml = xml.replace("temporal", "program").replace("p U X q", "{x = 0} x := 1; skip {x = 1}");
+        let (prf, metadata) = proof_from_xml::<P, _>(xml.as_bytes()).unwrap();
+        assert_eq!(metadata.logic_mode, LogicMode::Program);
+        assert!(prf.lookup_premise(&prf.premises()[0]).is_some_and(|premise| premise.as_triple().is_some()));
+    }
 }
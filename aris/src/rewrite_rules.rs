@@ -6,7 +6,11 @@ use crate::expr::gen_var;
 use crate::expr::subst;
 use crate::expr::Constraint;
 use crate::expr::Expr;
+use crate::expr::Op;
+use crate::expr::TraversalOrder;
+use crate::interned::ExprArena;
 
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
@@ -15,6 +19,151 @@ use itertools::Itertools;
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct RewriteRule {
     pub reductions: Vec<(Expr, Expr)>,
+    pub conditional_reductions: Vec<ConditionalReduction>,
+}
+
+/// A requirement on what a `ConditionalReduction`'s metavariables matched, checked before it is applied
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SideCondition {
+    /// The variable matched by the metavariable `var` doesn't occur free in what `expr` matched (`var ∉ FV(expr)`)
+    NotFree { var: String, expr: String },
+    /// The metavariable matched an atomic formula: `⊤`, `⊥`, a variable, or a predicate application
+    Atomic(String),
+    /// The variable matched by `var` comes after the one matched by `other` alphabetically. Reductions that
+    /// swap commuting binders use this to only ever move them into sorted order, so they can't undo themselves.
+    SortsAfter { var: String, other: String },
+}
+
+impl SideCondition {
+    /// Helper for constructing `NotFree` conditions
+    pub fn not_free(var: &str, expr: &str) -> SideCondition {
+        SideCondition::NotFree { var: var.into(), expr: expr.into() }
+    }
+    /// Helper for constructing `Atomic` conditions
+    pub fn atomic(metavar: &str) -> SideCondition {
+        SideCondition::Atomic(metavar.into())
+    }
+    /// Helper for constructing `SortsAfter` conditions
+    pub fn sorts_after(var: &str, other: &str) -> SideCondition {
+        SideCondition::SortsAfter { var: var.into(), other: other.into() }
+    }
+
+    fn holds(&self, bindings: &HashMap<String, Expr>) -> bool {
+        match self {
+            SideCondition::NotFree { var, expr } => match (bindings.get(var), bindings.get(expr)) {
+                (Some(Expr::Var { name }), Some(expr)) => !free_vars(expr).contains(name),
+                _ => false,
+            },
+            SideCondition::Atomic(metavar) => matches!(bindings.get(metavar), Some(Expr::Contra | Expr::Taut | Expr::Var { .. } | Expr::Apply { .. })),
+            SideCondition::SortsAfter { var, other } => match (bindings.get(var), bindings.get(other)) {
+                (Some(Expr::Var { name: var }), Some(Expr::Var { name: other })) => var > other,
+                _ => false,
+            },
+        }
+    }
+}
+
+/// A reduction that is only applied when its side conditions hold.
+///
+/// Unlike the plain reductions of a `RewriteRule`, which are matched by unification modulo renaming of
+/// bound variables, these are matched schematically: every variable in the pattern is a metavariable,
+/// including quantified ones, so `forall x phi` matches `forall y P(y)` with `x` bound to `y` and `phi` to
+/// `P(y)`. The replacement is instantiated by plain replacement (not capture-avoiding substitution), so
+/// it is up to the side conditions to rule out variable capture.
+///
+/// ```
+/// use aris::parser::parse_unwrap as p;
+/// use aris::rewrite_rules::{ConditionalReduction, SideCondition};
+///
+/// let null_quantification = ConditionalReduction::new(p("forall x phi"), p("phi"), vec![SideCondition::not_free("x", "phi")]);
+/// assert_eq!(null_quantification.apply(&p("forall y (A & B)")), Some(p("A & B")));
+/// assert_eq!(null_quantification.apply(&p("forall y (A & P(y))")), None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ConditionalReduction {
+    pub pattern: Expr,
+    pub replacement: Expr,
+    pub side_conditions: Vec<SideCondition>,
+}
+
+impl ConditionalReduction {
+    /// Construct a conditional reduction. Panics if the replacement uses a variable that the pattern doesn't.
+    pub fn new(pattern: Expr, replacement: Expr, side_conditions: Vec<SideCondition>) -> Self {
+        let metavars = schema_vars(&pattern);
+        assert!(schema_vars(&replacement).is_subset(&metavars), "replacement {replacement} has variables not in pattern {pattern}");
+        ConditionalReduction { pattern, replacement, side_conditions }
+    }
+
+    /// Match `e` against the pattern and check the side conditions, returning the metavariable bindings on success
+    pub fn matches(&self, e: &Expr) -> Option<HashMap<String, Expr>> {
+        let mut bindings = HashMap::new();
+        if match_schema(&self.pattern, e, &mut bindings) && self.side_conditions.iter().all(|c| c.holds(&bindings)) {
+            Some(bindings)
+        } else {
+            None
+        }
+    }
+
+    /// Rewrite `e` if the reduction applies to it
+    pub fn apply(&self, e: &Expr) -> Option<Expr> {
+        self.matches(e).map(|bindings| instantiate_schema(&self.replacement, &bindings))
+    }
+}
+
+/// All variable names in a schematic pattern, both free and quantified
+fn schema_vars(pattern: &Expr) -> HashSet<String> {
+    let mut vars = HashSet::new();
+    pattern.visit(TraversalOrder::PreOrder, &mut |e| match e {
+        Expr::Var { name } | Expr::Quant { name, .. } => {
+            vars.insert(name.clone());
+        }
+        _ => {}
+    });
+    vars
+}
+
+/// Bind a metavariable, or check that it's consistent with an existing binding
+fn bind_metavar(name: &str, e: &Expr, bindings: &mut HashMap<String, Expr>) -> bool {
+    match bindings.get(name) {
+        Some(bound) => bound == e,
+        None => {
+            bindings.insert(name.to_owned(), e.clone());
+            true
+        }
+    }
+}
+
+/// Syntactically match `e` against a pattern in which every variable is a metavariable, see `ConditionalReduction`
+pub(crate) fn match_schema(pattern: &Expr, e: &Expr, bindings: &mut HashMap<String, Expr>) -> bool {
+    match (pattern, e) {
+        (Expr::Contra, Expr::Contra) | (Expr::Taut, Expr::Taut) => true,
+        (Expr::Var { name }, e) => bind_metavar(name, e, bindings),
+        (Expr::Apply { func: pf, args: pa }, Expr::Apply { func: ef, args: ea }) => pa.len() == ea.len() && match_schema(pf, ef, bindings) && pa.iter().zip(ea).all(|(p, e)| match_schema(p, e, bindings)),
+        (Expr::Not { operand: p }, Expr::Not { operand: e }) => match_schema(p, e, bindings),
+        (Expr::Impl { left: pl, right: pr }, Expr::Impl { left: el, right: er }) => match_schema(pl, el, bindings) && match_schema(pr, er, bindings),
+        (Expr::Assoc { op: po, exprs: pe }, Expr::Assoc { op: eo, exprs: ee }) => po == eo && pe.len() == ee.len() && pe.iter().zip(ee).all(|(p, e)| match_schema(p, e, bindings)),
+        (Expr::Quant { kind: pk, name: pn, body: pb }, Expr::Quant { kind: ek, name: en, body: eb }) => pk == ek && bind_metavar(pn, &Expr::var(en), bindings) && match_schema(pb, eb, bindings),
+        _ => false,
+    }
+}
+
+/// Replace the metavariables in a schematic replacement with what they're bound to, see `ConditionalReduction`
+pub(crate) fn instantiate_schema(replacement: &Expr, bindings: &HashMap<String, Expr>) -> Expr {
+    let mut result = replacement.clone();
+    result.visit_mut(TraversalOrder::PostOrder, &mut |e| match e {
+        Expr::Var { name } => {
+            if let Some(bound) = bindings.get(name) {
+                *e = bound.clone();
+            }
+        }
+        Expr::Quant { name, .. } => {
+            if let Some(Expr::Var { name: bound }) = bindings.get(name) {
+                *name = bound.clone();
+            }
+        }
+        _ => {}
+    });
+    result
 }
 
 impl RewriteRule {
@@ -25,12 +174,144 @@ impl RewriteRule {
         use crate::parser::parse_unwrap as p;
         let reductions = permute_patterns(patterns.iter().map(|(premise, conclusion)| (p(premise), p(conclusion))).collect::<Vec<_>>());
 
-        RewriteRule { reductions }
+        RewriteRule { reductions, conditional_reductions: vec![] }
+    }
+
+    /// Like `from_patterns()`, but for reductions with side conditions, of the form
+    /// [("pattern", "replacement", &[condition, ...]), ...]. See `ConditionalReduction` for how these are matched.
+    pub fn from_conditional_patterns(patterns: &[(&str, &str, &[SideCondition])]) -> Self {
+        use crate::parser::parse_unwrap as p;
+        let conditional_reductions = patterns.iter().flat_map(|(pattern, replacement, conditions)| permute_ops(p(pattern)).into_iter().map(move |pattern| ConditionalReduction::new(pattern, p(replacement), conditions.to_vec()))).collect();
+
+        RewriteRule { reductions: vec![], conditional_reductions }
     }
 
     /// Reduce an expression with the rewrite rule's reductions
     pub fn reduce(&self, e: Expr) -> Expr {
-        reduce_pattern(e, &self.reductions)
+        reduce_pattern(e, &self.reductions, &self.conditional_reductions)
+    }
+
+    /// Rewrite an expression with the rule's reductions according to a `Strategy`. The returned
+    /// `bool` indicates whether anything was rewritten.
+    ///
+    /// ```
+    /// use aris::parser::parse_unwrap as p;
+    /// use aris::rewrite_rules::{RewriteRule, Strategy};
+    ///
+    /// let double_negation = RewriteRule::from_patterns(&[("~~P", "P")]);
+    /// let e = p("~~~~A & ~~B");
+    /// assert_eq!(double_negation.rewrite(e.clone(), &Strategy::OnceTopDown), (p("~~A & ~~B"), true));
+    /// assert_eq!(double_negation.rewrite(e.clone(), &Strategy::At(vec![1], Box::new(Strategy::Root))), (p("~~~~A & B"), true));
+    /// assert_eq!(double_negation.rewrite(e.clone(), &Strategy::exhaustive()), (p("A & B"), true));
+    /// assert_eq!(double_negation.rewrite(p("A"), &Strategy::exhaustive()), (p("A"), false));
+    /// ```
+    pub fn rewrite(&self, e: Expr, strategy: &Strategy) -> (Expr, bool) {
+        let patterns = freevarsify_pattern(&e, &self.reductions);
+        strategy.run(e, &patterns, &self.conditional_reductions)
+    }
+
+    /// Rewrite the subexpression of `e` at `path` once, returning `None` if none of the rule's
+    /// reductions apply there. This is what a GUI uses to apply an equivalence to a subformula the
+    /// user selected.
+    ///
+    /// ```
+    /// use aris::parser::parse_unwrap as p;
+    /// use aris::rewrite_rules::RewriteRule;
+    ///
+    /// let double_negation = RewriteRule::from_patterns(&[("~~P", "P")]);
+    /// assert_eq!(double_negation.rewrite_at(&p("~~A -> ~~B"), &[1]), Some(p("~~A -> B")));
+    /// assert_eq!(double_negation.rewrite_at(&p("~~A -> ~~B"), &[]), None);
+    /// ```
+    pub fn rewrite_at(&self, e: &Expr, path: &[usize]) -> Option<Expr> {
+        match self.rewrite(e.clone(), &Strategy::At(path.to_vec(), Box::new(Strategy::Root))) {
+            (rewritten, true) => Some(rewritten),
+            (_, false) => None,
+        }
+    }
+
+    /// Find every position in `e` (in pre-order) where one of the rule's reductions applies, along
+    /// with the bindings of the first reduction that matches there. Positions are paths as used by
+    /// `Expr::subexpr_at()` and `Strategy::At`.
+    pub fn find_matches(&self, e: &Expr) -> Vec<(Vec<usize>, HashMap<String, Expr>)> {
+        let mut out = vec![];
+        visit_positions(e, &mut |path, e| {
+            let bindings = self.reductions.iter().find_map(|(pattern, _)| match_pattern_partial(pattern, e, true).map(|(bindings, _)| bindings)).or_else(|| self.conditional_reductions.iter().find_map(|reduction| reduction.matches(e)));
+            if let Some(bindings) = bindings {
+                out.push((path.to_vec(), bindings));
+            }
+        });
+        out
+    }
+
+    /// Look for reductions that make rewriting loop forever, and for pairs of reductions that overlap
+    /// without agreeing on the result (critical pairs), so that a hand-written rule set can be checked
+    /// before it's handed to the checker. Only plain reductions are analyzed, since the side conditions
+    /// of conditional reductions are usually there to rule out exactly these problems.
+    ///
+    /// This only catches the obvious cases: an empty result doesn't prove that the rule terminates.
+    ///
+    /// ```
+    /// use aris::rewrite_rules::{RewriteRule, RewriteWarning};
+    ///
+    /// assert!(RewriteRule::from_patterns(&[("~~P", "P")]).analyze().is_empty());
+    /// let contraposition = RewriteRule::from_patterns(&[("P -> Q", "~Q -> ~P")]);
+    /// assert!(matches!(&contraposition.analyze()[..], [RewriteWarning::SelfEmbedding { .. }]));
+    /// ```
+    pub fn analyze(&self) -> Vec<RewriteWarning> {
+        // Permutations of the same pattern made by `from_patterns()` would all report the same problems
+        let mut seen = HashSet::new();
+        let reductions = self.reductions.iter().filter(|(pattern, replacement)| seen.insert((pattern.canonicalize(), replacement.canonicalize()))).collect::<Vec<_>>();
+
+        let mut warnings = vec![];
+        let mut looping = vec![false; reductions.len()];
+        for (i, (pattern, replacement)) in reductions.iter().enumerate() {
+            let warning = if let Expr::Var { .. } = pattern {
+                RewriteWarning::MatchesEverything { pattern: pattern.clone(), replacement: replacement.clone() }
+            } else if embeds(pattern, replacement) {
+                RewriteWarning::SelfEmbedding { pattern: pattern.clone(), replacement: replacement.clone() }
+            } else {
+                continue;
+            };
+            looping[i] = true;
+            warnings.push(warning);
+        }
+
+        for (i, (p1, r1)) in reductions.iter().enumerate() {
+            for (j, (p2, r2)) in reductions.iter().enumerate() {
+                if looping[i] || looping[j] {
+                    continue;
+                }
+                if i < j && (undoes(p1, r1, p2, r2) || undoes(p2, r2, p1, r1)) {
+                    warnings.push(RewriteWarning::Cycle { first: ((*p1).clone(), (*r1).clone()), second: ((*p2).clone(), (*r2).clone()) });
+                }
+                for (overlap, left, right) in critical_pairs(p1, r1, p2, r2) {
+                    // Overlaps at the root are symmetric, so each unordered pair is enough
+                    if overlap.1.is_empty() && j < i {
+                        continue;
+                    }
+                    let (left, right) = (self.normalize_bounded(left), self.normalize_bounded(right));
+                    let (cl, cr) = (left.canonicalize(), right.canonicalize());
+                    let reported = warnings.iter().any(|w| matches!(w, RewriteWarning::CriticalPair { left, right, .. } if (left.canonicalize(), right.canonicalize()) == (cl.clone(), cr.clone()) || (left.canonicalize(), right.canonicalize()) == (cr.clone(), cl.clone())));
+                    if cl != cr && !reported {
+                        warnings.push(RewriteWarning::CriticalPair { overlap: overlap.0, left, right });
+                    }
+                }
+            }
+        }
+        warnings
+    }
+
+    /// Rewrite `e` top-down until nothing applies or `MAX_JOIN_STEPS` rewrites were done, whichever is first
+    fn normalize_bounded(&self, e: Expr) -> Expr {
+        let mut e = e;
+        for _ in 0..MAX_JOIN_STEPS {
+            let (new, changed) = self.rewrite(e, &Strategy::OnceTopDown);
+            e = new;
+            if !changed {
+                break;
+            }
+        }
+        e
     }
 
     //     /// Reduce an expression with the rewrite rule's reductions, yielding a set
@@ -40,6 +321,185 @@ impl RewriteRule {
     //     }
 }
 
+/// Where and how often a `RewriteRule` is applied by `RewriteRule::rewrite()`.
+///
+/// Positions are paths of indices into `Expr::children()`, as used by `Expr::subexpr_at()`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// Rewrite the whole expression, if one of the reductions matches it
+    Root,
+    /// Rewrite the first matching subexpression in pre-order (outermost first)
+    OnceTopDown,
+    /// Rewrite the first matching subexpression in post-order (innermost first)
+    OnceBottomUp,
+    /// Run a strategy on the subexpression at a position; nothing is rewritten if the position doesn't exist
+    At(Vec<usize>, Box<Strategy>),
+    /// Run a strategy until it stops rewriting. This loops forever if the rule can rewrite its own output indefinitely.
+    Repeat(Box<Strategy>),
+    /// Run each strategy in turn
+    Seq(Vec<Strategy>),
+}
+
+impl Strategy {
+    /// Rewrite top-down until no reduction applies anywhere
+    pub fn exhaustive() -> Strategy {
+        Strategy::Repeat(Box::new(Strategy::OnceTopDown))
+    }
+
+    /// Run the strategy with patterns returned by `freevarsify_pattern()`
+    fn run(&self, e: Expr, patterns: &[(Expr, Expr, HashSet<String>)], conditional: &[ConditionalReduction]) -> (Expr, bool) {
+        match self {
+            Strategy::Root => reduce_transform_func(e, patterns, conditional),
+            Strategy::OnceTopDown => {
+                let (mut e, changed) = reduce_transform_func(e, patterns, conditional);
+                if changed {
+                    return (e, true);
+                }
+                let changed = rewrite_first_child(&mut e, |child| self.run(child, patterns, conditional));
+                (e, changed)
+            }
+            Strategy::OnceBottomUp => {
+                let mut e = e;
+                if rewrite_first_child(&mut e, |child| self.run(child, patterns, conditional)) {
+                    return (e, true);
+                }
+                reduce_transform_func(e, patterns, conditional)
+            }
+            Strategy::At(path, strategy) => {
+                let mut e = e;
+                let changed = match e.subexpr_at_mut(path) {
+                    Some(sub) => {
+                        let (new, changed) = strategy.run(std::mem::replace(sub, Expr::Contra), patterns, conditional);
+                        *sub = new;
+                        changed
+                    }
+                    None => false,
+                };
+                (e, changed)
+            }
+            Strategy::Repeat(strategy) => {
+                let (mut e, mut changed) = strategy.run(e, patterns, conditional);
+                let mut again = changed;
+                while again {
+                    (e, again) = strategy.run(e, patterns, conditional);
+                    changed |= again;
+                }
+                (e, changed)
+            }
+            Strategy::Seq(strategies) => strategies.iter().fold((e, false), |(e, changed), strategy| {
+                let (e, now) = strategy.run(e, patterns, conditional);
+                (e, changed || now)
+            }),
+        }
+    }
+}
+
+/// A potential problem with a `RewriteRule`'s reductions, found by `RewriteRule::analyze()`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RewriteWarning {
+    /// The pattern is a lone metavariable, so the reduction applies to every formula, including its own result
+    MatchesEverything { pattern: Expr, replacement: Expr },
+    /// The replacement contains an instance of the pattern, so the reduction can be applied to its own result forever
+    SelfEmbedding { pattern: Expr, replacement: Expr },
+    /// Each reduction turns the other's replacement back into something the other one matches, so
+    /// applying them in turn never stops
+    Cycle { first: (Expr, Expr), second: (Expr, Expr) },
+    /// The patterns of two reductions overlap on `overlap`, which is rewritten to `left` by one and to
+    /// `right` by the other. Neither can be rewritten any further to make them agree, so the result of
+    /// normalizing with the rule depends on which reduction is tried first.
+    CriticalPair { overlap: Expr, left: Expr, right: Expr },
+}
+
+impl std::fmt::Display for RewriteWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RewriteWarning::MatchesEverything { pattern, replacement } => write!(f, "{pattern} ⇒ {replacement} applies to every formula, so it can be applied forever"),
+            RewriteWarning::SelfEmbedding { pattern, replacement } => write!(f, "{replacement} contains an instance of {pattern}, so {pattern} ⇒ {replacement} can be applied forever"),
+            RewriteWarning::Cycle { first: (p1, r1), second: (p2, r2) } => write!(f, "{p1} ⇒ {r1} and {p2} ⇒ {r2} undo each other, so they can be applied forever"),
+            RewriteWarning::CriticalPair { overlap, left, right } => write!(f, "{overlap} can be rewritten to both {left} and {right}, which can't be rewritten to the same formula"),
+        }
+    }
+}
+
+/// How many rewrites `RewriteRule::analyze()` tries when checking whether the two sides of a critical
+/// pair rewrite to the same formula. Limited because the rule being analyzed might not terminate.
+const MAX_JOIN_STEPS: usize = 64;
+
+/// Whether `pattern` matches some subexpression of `e`, treating the free variables of `e` as constants
+fn embeds(pattern: &Expr, e: &Expr) -> bool {
+    let mut found = false;
+    e.visit(TraversalOrder::PreOrder, &mut |sub| found = found || match_pattern_partial(pattern, sub, true).is_some());
+    found
+}
+
+/// Whether rewriting the replacement `r1` once with `p2 ⇒ r2` gives something that `p1` matches again
+fn undoes(p1: &Expr, r1: &Expr, p2: &Expr, r2: &Expr) -> bool {
+    let second = RewriteRule { reductions: vec![(p2.clone(), r2.clone())], conditional_reductions: vec![] };
+    match second.rewrite(r1.clone(), &Strategy::OnceTopDown) {
+        (rewritten, true) => embeds(p1, &rewritten),
+        (_, false) => false,
+    }
+}
+
+/// The critical pairs of two reductions: for every non-variable subexpression of `p1` (outside of
+/// quantifiers) that unifies with `p2`, the most general formula that both apply to, along with the
+/// path to where `p2` applies, and the two results. Quantified subexpressions are skipped because
+/// unifying under a binder would need to avoid capture.
+fn critical_pairs(p1: &Expr, r1: &Expr, p2: &Expr, r2: &Expr) -> Vec<((Expr, Vec<usize>), Expr, Expr)> {
+    fn positions(e: &Expr, path: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+        if let Expr::Var { .. } = e {
+            return;
+        }
+        out.push(path.clone());
+        if let Expr::Quant { .. } = e {
+            return;
+        }
+        for (i, child) in e.children().into_iter().enumerate() {
+            path.push(i);
+            positions(child, path, out);
+            path.pop();
+        }
+    }
+
+    // Rename the second reduction's metavariables apart from the first's
+    let mut avoid = free_vars(p1);
+    let (mut p2, mut r2) = (p2.clone(), r2.clone());
+    for var in free_vars(&p2) {
+        let fresh = gen_var(&var, &avoid);
+        avoid.insert(fresh.clone());
+        p2 = subst(p2, &var, Expr::var(&fresh));
+        r2 = subst(r2, &var, Expr::var(&fresh));
+    }
+
+    let mut paths = vec![];
+    positions(p1, &mut vec![], &mut paths);
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let unifier = crate::expr::unify(vec![Constraint::Equal(p1.subexpr_at(&path)?.clone(), p2.clone())].into_iter().collect())?;
+            // Later substitutions in the unifier can mention variables that earlier ones replace
+            let apply = |e: &Expr| unifier.0.iter().rev().fold(e.clone(), |e, (var, t)| subst(e, var, t.clone()));
+            let overlap = apply(p1);
+            let mut right = overlap.clone();
+            *right.subexpr_at_mut(&path)? = apply(&r2);
+            Some(((overlap, path), apply(r1), right))
+        })
+        .collect()
+}
+
+/// Run `f` on the children of `e` from left to right, stopping after the first one it rewrites.
+/// Returns whether any child was rewritten.
+fn rewrite_first_child<F: Fn(Expr) -> (Expr, bool)>(e: &mut Expr, f: F) -> bool {
+    for child in e.children_mut() {
+        let (new, changed) = f(std::mem::replace(child, Expr::Contra));
+        *child = new;
+        if changed {
+            return true;
+        }
+    }
+    false
+}
+
 /// Permute all binary and associative operations in an expression, resulting in a list of
 /// expressions of all permutations
 /// E.g. ((A & B) & C) ==> [((A & B) & C), ((B & A) & C), (C & (A & B)), (C & (B & A))]
@@ -107,9 +567,12 @@ fn permute_ops(e: Expr) -> Vec<Expr> {
 
 /// Permute the search expression of every pattern, all mapping to the same replacement
 /// E.g. [(A & B) -> C, (A | B) -> C] ==> [(A & B) -> C, (B & A) -> C, (A | B) -> C, (B | A) -> C]
+/// Patterns with repeated operands (e.g. `P & P`) permute into duplicates, which are dropped.
 fn permute_patterns(patterns: Vec<(Expr, Expr)>) -> Vec<(Expr, Expr)> {
+    let mut arena = ExprArena::new();
+    let mut seen = HashSet::new();
     // Permute_ops of all input patterns
-    patterns.into_iter().flat_map(|(find, replace)| permute_ops(find).into_iter().map(move |find| (find, replace.clone()))).collect::<Vec<_>>()
+    patterns.into_iter().flat_map(|(find, replace)| permute_ops(find).into_iter().map(move |find| (find, replace.clone()))).filter(|(find, replace)| seen.insert((arena.intern(find), arena.intern(replace)))).collect::<Vec<_>>()
 }
 
 /// Reduce an expression by a pattern with a set of variables
@@ -121,10 +584,26 @@ fn permute_patterns(patterns: Vec<(Expr, Expr)>) -> Vec<(Expr, Expr)> {
 /// with `match` on all pattern variables defined in `pattern_vars` will be replaced by `replace` with
 /// the substitutions from the unification.
 ///
-/// Limitations: Cannot do variadic versions of assoc binops, you need a constant number of args
-fn reduce_pattern(e: Expr, patterns: &[(Expr, Expr)]) -> Expr {
+/// Conjunctions and disjunctions are matched modulo associativity and commutativity (see
+/// `ac_match()`), so a pattern like `(P & Q) | (P & R)` also rewrites part of a longer chain.
+/// Other assoc binops need a constant number of args.
+fn reduce_pattern(e: Expr, patterns: &[(Expr, Expr)], conditional: &[ConditionalReduction]) -> Expr {
     let patterns = freevarsify_pattern(&e, patterns);
-    e.transform(&|expr| reduce_transform_func(expr, &patterns))
+    // Whether a subexpression can be reduced only depends on the subexpression, so the ones that couldn't be are
+    // remembered by id, and not matched against every pattern again each pass
+    let arena = RefCell::new(ExprArena::new());
+    let irreducible = RefCell::new(HashSet::new());
+    e.transform(&|expr| {
+        let id = arena.borrow_mut().intern(&expr);
+        if irreducible.borrow().contains(&id) {
+            return (expr, false);
+        }
+        let (result, reduced) = reduce_transform_func(expr, &patterns, conditional);
+        if !reduced {
+            irreducible.borrow_mut().insert(id);
+        }
+        (result, reduced)
+    })
 }
 
 // /// Like `reduce_pattern()`, but creates a set of possible reductions. This set
@@ -142,34 +621,279 @@ fn reduce_pattern(e: Expr, patterns: &[(Expr, Expr)]) -> Expr {
 /// Parameters:
 ///   * `expr` - expression to reduce
 ///   * `patterns` - patterns returned by `freevarsify_pattern()`
-fn reduce_transform_func(expr: Expr, patterns: &[(Expr, Expr, HashSet<String>)]) -> (Expr, bool) {
+///   * `conditional` - reductions with side conditions, tried after `patterns`
+fn reduce_transform_func(expr: Expr, patterns: &[(Expr, Expr, HashSet<String>)], conditional: &[ConditionalReduction]) -> (Expr, bool) {
     // Try all our patterns at every level of the tree
     for (pattern, replace, pattern_vars) in patterns {
-        // Unify3D
-        let ret = crate::expr::unify(vec![Constraint::Equal(pattern.clone(), expr.clone())].into_iter().collect());
-        if let Some(ret) = ret {
-            // Collect all unification results and make sure we actually match exactly
-            let mut subs = HashMap::new();
-            let mut any_bad = false;
-            for subst in ret.0 {
-                // We only want to unify our pattern variables. This prevents us from going backwards
-                // and unifying a pattern variable in expr with some expression of our pattern variable
-                if pattern_vars.contains(&subst.0) {
-                    // Sanity check: Only one unification per variable
-                    assert!(subs.insert(subst.0, subst.1).is_none());
-                } else {
-                    any_bad = true;
+        if let Some((subs, rest)) = ac_match(pattern, pattern_vars, &expr, true) {
+            let subst_replace = subs.into_iter().fold(replace.clone(), |z, (x, y)| crate::expr::subst(z, &x, y));
+            // Operands of a longer chain that the pattern didn't use stay next to the replacement
+            return match &expr {
+                Expr::Assoc { op, .. } if !rest.is_empty() => (Expr::Assoc { op: *op, exprs: std::iter::once(subst_replace).chain(rest).collect() }, true),
+                _ => (subst_replace, true),
+            };
+        }
+    }
+    for reduction in conditional {
+        if let Some(result) = reduction.apply(&expr) {
+            return (result, true);
+        }
+    }
+    (expr, false)
+}
+
+/// Unify `pattern` with `expr`, only allowing the variables in `pattern_vars` to be bound. None of
+/// `pattern_vars` may occur free in `expr`; `freevarsify_pattern()` ensures this.
+fn unify_pattern(pattern: &Expr, pattern_vars: &HashSet<String>, expr: &Expr) -> Option<HashMap<String, Expr>> {
+    // Unify3D
+    let ret = crate::expr::unify(vec![Constraint::Equal(pattern.clone(), expr.clone())].into_iter().collect())?;
+
+    // Collect all unification results and make sure we actually match exactly
+    let mut subs = HashMap::new();
+    for subst in ret.0 {
+        // We only want to unify our pattern variables. This prevents us from going backwards
+        // and unifying a pattern variable in expr with some expression of our pattern variable
+        if !pattern_vars.contains(&subst.0) {
+            return None;
+        }
+        // Sanity check: Only one unification per variable
+        assert!(subs.insert(subst.0, subst.1).is_none());
+    }
+
+    // Make sure we have a substitution for every variable in the pattern set (and only for them)
+    if subs.len() == pattern_vars.len() {
+        Some(subs)
+    } else {
+        None
+    }
+}
+
+/// Chains of ∧ or ∨ with more operands than this aren't regrouped by `ac_match()`, since the number
+/// of regroupings grows exponentially
+const MAX_AC_OPERANDS: usize = 8;
+
+/// Whether an operator is matched modulo associativity and commutativity
+fn is_ac(op: Op) -> bool {
+    matches!(op, Op::And | Op::Or)
+}
+
+/// Like `unify_pattern()`, but ∧ and ∨ are associative and commutative: `P & Q` matches `B & A`, and
+/// also `A & B & C` (with `P` bound to `A & B`). Operands that are grouped together to match one
+/// pattern operand are sorted, so a metavariable used twice matches the same operands in any order.
+///
+/// If `partial` is set and both `pattern` and `expr` are chains of the same AC operator, the pattern
+/// may also match just some of the operands of `expr`, e.g. `P & ~P` matches `A & B & ~A`. The
+/// operands it didn't use are returned next to the bindings; they're empty for a full match.
+fn ac_match(pattern: &Expr, pattern_vars: &HashSet<String>, expr: &Expr, partial: bool) -> Option<(HashMap<String, Expr>, Vec<Expr>)> {
+    if let Some(subs) = unify_pattern(pattern, pattern_vars, expr) {
+        return Some((subs, vec![]));
+    }
+    let find = |e: &Expr| ac_variants(pattern, e).into_iter().find_map(|candidate| unify_pattern(pattern, pattern_vars, &candidate));
+    match (pattern, expr) {
+        (Expr::Assoc { op: pop, exprs: pexprs }, Expr::Assoc { op, exprs }) if pop == op && is_ac(*op) => {
+            let operands = ac_operands(*op, exprs);
+            if operands.len() > MAX_AC_OPERANDS {
+                return None;
+            }
+            // Smaller subsets first, so that as much of the chain as possible is left alone
+            let sizes = if partial { pexprs.len()..=operands.len() } else { operands.len()..=operands.len() };
+            for size in sizes {
+                for chosen in (0..operands.len()).combinations(size) {
+                    let subset = chosen.iter().map(|&i| operands[i].clone()).collect::<Vec<_>>();
+                    if let Some(subs) = find(&Expr::Assoc { op: *op, exprs: subset }) {
+                        let rest = (0..operands.len()).filter(|i| !chosen.contains(i)).map(|i| operands[i].clone()).collect();
+                        return Some((subs, rest));
+                    }
                 }
             }
+            None
+        }
+        _ => find(expr).map(|subs| (subs, vec![])),
+    }
+}
+
+/// The operands of a chain of `op`, with nested chains of the same operator flattened into it
+fn ac_operands(op: Op, exprs: &[Expr]) -> Vec<Expr> {
+    exprs
+        .iter()
+        .flat_map(|e| match e {
+            Expr::Assoc { op: inner, exprs } if *inner == op => ac_operands(op, exprs),
+            e => vec![e.clone()],
+        })
+        .collect()
+}
+
+/// Whether `e` could possibly match `pattern`, judging only by their outermost constructors
+fn same_head(pattern: &Expr, e: &Expr) -> bool {
+    match (pattern, e) {
+        (Expr::Var { .. }, _) => true,
+        (Expr::Assoc { op: po, .. }, Expr::Assoc { op: eo, .. }) => po == eo,
+        (Expr::Quant { kind: pk, .. }, Expr::Quant { kind: ek, .. }) => pk == ek,
+        _ => std::mem::discriminant(pattern) == std::mem::discriminant(e),
+    }
+}
+
+/// Every way of splitting `items` into `k` nonempty groups, where the order of the groups matters but
+/// items keep their relative order within a group
+fn groupings(items: &[Expr], k: usize) -> Vec<Vec<Vec<Expr>>> {
+    (0..items.len())
+        .map(|_| 0..k)
+        .multi_cartesian_product()
+        .filter(|assignment| (0..k).all(|group| assignment.contains(&group)))
+        .map(|assignment| {
+            let mut groups = vec![vec![]; k];
+            for (item, group) in items.iter().zip(assignment) {
+                groups[group].push(item.clone());
+            }
+            groups
+        })
+        .collect()
+}
 
-            // Make sure we have a substitution for every variable in the pattern set (and only for them)
-            if !any_bad && subs.len() == pattern_vars.len() {
-                let subst_replace = subs.into_iter().fold(replace.clone(), |z, (x, y)| crate::expr::subst(z, &x, y));
-                return (subst_replace, true);
+/// All the ways of regrouping and reordering the ∧ and ∨ chains of `e` so that they have the same
+/// number of operands as the corresponding chains in `pattern`, for `ac_match()` to unify against.
+/// Regroupings that obviously can't match are skipped.
+fn ac_variants(pattern: &Expr, e: &Expr) -> Vec<Expr> {
+    match (pattern, e) {
+        (Expr::Assoc { op: pop, exprs: pexprs }, Expr::Assoc { op, exprs }) if pop == op && is_ac(*op) => {
+            let operands = ac_operands(*op, exprs);
+            if operands.len() < pexprs.len() || operands.len() > MAX_AC_OPERANDS {
+                return vec![];
             }
+            groupings(&operands, pexprs.len())
+                .into_iter()
+                .flat_map(|groups| {
+                    groups
+                        .into_iter()
+                        .zip(pexprs)
+                        .map(|(mut group, pexpr)| {
+                            let grouped = if group.len() == 1 {
+                                group.remove(0)
+                            } else {
+                                group.sort();
+                                Expr::Assoc { op: *op, exprs: group }
+                            };
+                            if same_head(pexpr, &grouped) {
+                                ac_variants(pexpr, &grouped)
+                            } else {
+                                vec![]
+                            }
+                        })
+                        .multi_cartesian_product()
+                        .map(|exprs| Expr::Assoc { op: *op, exprs })
+                        .collect::<Vec<_>>()
+                })
+                .collect()
         }
+        (Expr::Assoc { op: pop, exprs: pexprs }, Expr::Assoc { op, exprs }) if pop == op && pexprs.len() == exprs.len() => pexprs.iter().zip(exprs).map(|(p, e)| ac_variants(p, e)).multi_cartesian_product().map(|exprs| Expr::Assoc { op: *op, exprs }).collect(),
+        (Expr::Not { operand: p }, Expr::Not { operand }) => ac_variants(p, operand).into_iter().map(|operand| Expr::Not { operand: Box::new(operand) }).collect(),
+        (Expr::Impl { left: pl, right: pr }, Expr::Impl { left, right }) => ac_variants(pl, left).into_iter().cartesian_product(ac_variants(pr, right)).map(|(left, right)| Expr::implies(left, right)).collect(),
+        (Expr::Apply { func: pf, args: pa }, Expr::Apply { func, args }) if pa.len() == args.len() => std::iter::once(ac_variants(pf, func))
+            .chain(pa.iter().zip(args).map(|(p, e)| ac_variants(p, e)))
+            .multi_cartesian_product()
+            .map(|mut args| {
+                let func = Box::new(args.remove(0));
+                Expr::Apply { func, args }
+            })
+            .collect(),
+        (Expr::Quant { kind: pk, body: pb, .. }, Expr::Quant { kind, name, body }) if pk == kind => ac_variants(pb, body).into_iter().map(|body| Expr::Quant { kind: *kind, name: name.clone(), body: Box::new(body) }).collect(),
+        _ => vec![e.clone()],
+    }
+}
+
+/// Match an expression against a pattern, treating every free variable of the pattern as a
+/// metavariable. On success, returns what each metavariable is bound to. Bound variables are
+/// matched up to renaming, like in `unify()`, and ∧ and ∨ up to associativity and commutativity.
+///
+/// ```
+/// use aris::parser::parse_unwrap as p;
+/// use aris::rewrite_rules::match_pattern;
+///
+/// let bindings = match_pattern(&p("P & (Q | P)"), &p("A & ((B -> C) | A)")).unwrap();
+/// assert_eq!(bindings["P"], p("A"));
+/// assert_eq!(bindings["Q"], p("B -> C"));
+/// assert_eq!(match_pattern(&p("P & (Q | P)"), &p("A & (B | C)")), None);
+/// assert_eq!(match_pattern(&p("P & ~P"), &p("~A & A")).unwrap()["P"], p("A"));
+/// ```
+pub fn match_pattern(pattern: &Expr, expr: &Expr) -> Option<HashMap<String, Expr>> {
+    match_pattern_partial(pattern, expr, false).map(|(bindings, _)| bindings)
+}
+
+/// `match_pattern()`, optionally allowing a partial match of a chain as in `ac_match()`
+fn match_pattern_partial(pattern: &Expr, expr: &Expr, partial: bool) -> Option<(HashMap<String, Expr>, Vec<Expr>)> {
+    match_metavars(pattern, &free_vars(pattern), expr, partial)
+}
+
+/// `match_pattern_partial()`, with only the free variables of the pattern in `metavars` treated as
+/// metavariables. The other variables of the pattern only match themselves.
+fn match_metavars(pattern: &Expr, metavars: &HashSet<String>, expr: &Expr, partial: bool) -> Option<(HashMap<String, Expr>, Vec<Expr>)> {
+    // Rename the metavariables away from the names in expr (and from each other), remembering the original names
+    let mut avoid = &free_vars(expr) | &free_vars(pattern);
+    let mut renamed = pattern.clone();
+    let mut original_names = HashMap::new();
+    for var in metavars {
+        let fresh = gen_var(var, &avoid);
+        avoid.insert(fresh.clone());
+        renamed = subst(renamed, var, Expr::var(&fresh));
+        original_names.insert(fresh, var.clone());
+    }
+    let pattern_vars = original_names.keys().cloned().collect::<HashSet<_>>();
+    let (subs, rest) = ac_match(&renamed, &pattern_vars, expr, partial)?;
+    Some((subs.into_iter().map(|(fresh, e)| (original_names[&fresh].clone(), e)).collect(), rest))
+}
+
+/// Find every position in `expr` (in pre-order) where `pattern` occurs, for searching a proof for
+/// formulas of some shape. Variables of the pattern whose names start with `_` are metavariables:
+/// each bare `_` matches any subexpression on its own, while a named one like `_x` has to match the
+/// same subexpression everywhere it appears. All other variables only match themselves. Positions
+/// are paths as used by `Expr::subexpr_at()`.
+///
+/// ```
+/// use aris::parser::parse_unwrap as p;
+/// use aris::rewrite_rules::find_pattern;
+///
+/// assert_eq!(find_pattern(&p("_ -> B"), &p("(A -> B) & ((C & D) -> B)")), vec![vec![0], vec![1]]);
+/// assert_eq!(find_pattern(&p("_ -> B"), &p("B -> A")), Vec::<Vec<usize>>::new());
+/// assert_eq!(find_pattern(&p("_x | ~_x"), &p("A | ~A")), vec![Vec::<usize>::new()]);
+/// assert_eq!(find_pattern(&p("_x | ~_x"), &p("A | ~B")), Vec::<Vec<usize>>::new());
+/// ```
+pub fn find_pattern(pattern: &Expr, expr: &Expr) -> Vec<Vec<usize>> {
+    // Give every bare `_` its own name, so that they match independently
+    let mut pattern = pattern.clone();
+    let mut avoid = free_vars(&pattern);
+    pattern.visit_mut(TraversalOrder::PreOrder, &mut |e| {
+        if let Expr::Var { name } = e {
+            if name == "_" {
+                *name = gen_var("_", &avoid);
+                avoid.insert(name.clone());
+            }
+        }
+    });
+    let metavars = free_vars(&pattern).into_iter().filter(|var| var.starts_with('_')).collect::<HashSet<_>>();
+
+    let mut out = vec![];
+    visit_positions(expr, &mut |path, e| {
+        if match_metavars(&pattern, &metavars, e, true).is_some() {
+            out.push(path.to_vec());
+        }
+    });
+    out
+}
+
+/// Call `f` on every subexpression of `e` with its path, in pre-order. This uses an explicit stack
+/// instead of recursion, so searching a deeply nested expression can't overflow the call stack.
+fn visit_positions<'a>(e: &'a Expr, f: &mut impl FnMut(&[usize], &'a Expr)) {
+    // Each subexpression is on the stack with its depth and its index in its parent
+    let mut path = vec![];
+    let mut stack = vec![(e, 0, 0)];
+    while let Some((e, depth, i)) = stack.pop() {
+        if depth > 0 {
+            path.truncate(depth - 1);
+            path.push(i);
+        }
+        f(&path, e);
+        stack.extend(e.children().into_iter().enumerate().rev().map(|(i, child)| (child, depth + 1, i)));
     }
-    (expr, false)
 }
 
 /// Helper function for `reduce_pattern()` and `reduce_pattern_set()`; given an
@@ -236,6 +960,126 @@ mod tests {
         println!("{} {} {} {} {} {} {} {}", p2[0], p2[1], p2[2], p2[3], p2[4], p2[5], p2[6], p2[7]);
     }
 
+    #[test]
+    fn test_strategies() {
+        use crate::parser::parse_unwrap as p;
+
+        let demorgan = RewriteRule::from_patterns(&[("~(P & Q)", "~P | ~Q")]);
+        let e = p("~(~(A & B) & C)");
+        assert_eq!(demorgan.rewrite(e.clone(), &Strategy::Root), (p("~~(A & B) | ~C"), true));
+        assert_eq!(demorgan.rewrite(e.clone(), &Strategy::OnceTopDown), (p("~~(A & B) | ~C"), true));
+        assert_eq!(demorgan.rewrite(e.clone(), &Strategy::OnceBottomUp), (p("~((~A | ~B) & C)"), true));
+        assert_eq!(demorgan.rewrite(e.clone(), &Strategy::At(vec![0, 0], Box::new(Strategy::Root))), (p("~((~A | ~B) & C)"), true));
+        assert_eq!(demorgan.rewrite(e.clone(), &Strategy::At(vec![0, 1], Box::new(Strategy::Root))), (e.clone(), false));
+        assert_eq!(demorgan.rewrite(e.clone(), &Strategy::At(vec![5], Box::new(Strategy::OnceTopDown))), (e.clone(), false));
+        assert_eq!(demorgan.rewrite(e.clone(), &Strategy::Seq(vec![Strategy::OnceTopDown, Strategy::OnceTopDown])), (p("~(~A | ~B) | ~C"), true));
+        assert_eq!(demorgan.rewrite(e, &Strategy::exhaustive()), (p("~(~A | ~B) | ~C"), true));
+    }
+
+    #[test]
+    fn test_match_pattern() {
+        use crate::parser::parse_unwrap as p;
+        use maplit::hashmap;
+
+        // Metavariables may share names with variables in the matched expression
+        assert_eq!(match_pattern(&p("P & Q"), &p("Q & P")), Some(hashmap! { "P".to_owned() => p("Q"), "Q".to_owned() => p("P") }));
+        assert_eq!(match_pattern(&p("forall x P(x, y)"), &p("forall z Q(z, A)")), Some(hashmap! { "P".to_owned() => p("Q"), "y".to_owned() => p("A") }));
+        assert_eq!(match_pattern(&p("~P"), &p("A")), None);
+        assert_eq!(match_pattern(&p("^|^"), &p("^|^")), Some(hashmap! {}));
+
+        let distribution = RewriteRule::from_patterns(&[("(P & Q) | (P & R)", "P & (Q | R)")]);
+        let matches = distribution.find_matches(&p("~((A & B) | (A & C)) -> ((D & E) | (E & F))"));
+        assert_eq!(matches.iter().map(|(path, _)| path.clone()).collect::<Vec<_>>(), vec![vec![0, 0], vec![1]]);
+        assert_eq!(matches[1].1["P"], p("E"));
+    }
+
+    #[test]
+    fn test_find_pattern() {
+        use crate::parser::parse_unwrap as p;
+
+        let e = p("(forall x (P(x) -> Q)) & (A -> Q) & B & C");
+        assert_eq!(find_pattern(&p("_ -> Q"), &e), vec![vec![0, 0], vec![1]]);
+        assert_eq!(find_pattern(&p("_(x) -> _"), &e), vec![vec![0, 0]]);
+        // Parts of chains match, and the two `_`s don't have to be the same
+        assert_eq!(find_pattern(&p("_ & _"), &e), vec![Vec::<usize>::new()]);
+        assert_eq!(find_pattern(&p("_x & _x"), &e), Vec::<Vec<usize>>::new());
+        assert_eq!(find_pattern(&p("Q"), &e), vec![vec![0, 0, 1], vec![1, 1]]);
+    }
+
+    #[test]
+    fn test_conditional_reductions() {
+        use crate::parser::parse_unwrap as p;
+
+        let rule = RewriteRule::from_conditional_patterns(&[("forall x (phi & psi)", "phi & forall x psi", &[SideCondition::not_free("x", "phi")]), ("~~phi", "phi", &[SideCondition::atomic("phi")])]);
+        // The pattern is permuted, so the side condition can be satisfied by either conjunct
+        assert_eq!(rule.reduce(p("forall y (P(y) & A)")), p("A & forall y P(y)"));
+        assert_eq!(rule.reduce(p("forall y (P(y) & Q(y))")), p("forall y (P(y) & Q(y))"));
+        assert_eq!(rule.reduce(p("~~P(a) & ~~(A | B)")), p("P(a) & ~~(A | B)"));
+        // Both kinds of reductions can be mixed in one rule, and are found by find_matches
+        let mut rule = rule;
+        rule.reductions.push((p("P | P"), p("P")));
+        let matches = rule.find_matches(&p("(B | B) -> ~~B"));
+        assert_eq!(matches.iter().map(|(path, _)| path.clone()).collect::<Vec<_>>(), vec![vec![0], vec![1]]);
+        assert_eq!(rule.reduce(p("(B | B) -> ~~B")), p("B -> B"));
+    }
+
+    #[test]
+    fn test_ac_matching() {
+        use crate::parser::parse_unwrap as p;
+
+        let distribution = RewriteRule::from_patterns(&[("(P & Q) | (P & R)", "P & (Q | R)")]);
+        // Operand order within the chains doesn't matter
+        assert_eq!(distribution.reduce(p("(B & A) | (C & A)")), p("A & (B | C)"));
+        // Only part of a longer chain is rewritten, the rest is kept
+        assert_eq!(distribution.reduce(p("(A & B) | D | (A & C)")), p("(A & (B | C)) | D"));
+        // A metavariable can stand for several operands of a chain, in any order
+        assert_eq!(distribution.reduce(p("(A & B & C) | (B & A & D)")), p("(A & B) & (C | D)"));
+        // Grouping doesn't matter either
+        assert_eq!(distribution.reduce(p("((A & B) | D) | (A & C)")), p("(A & (B | C)) | D"));
+        assert_eq!(distribution.reduce(p("(A & B) | (C & D)")), p("(A & B) | (C & D)"));
+        // Factoring a longer chain leaves what's factored out nested, so it's equal once flattened
+        let flattened = |e: Expr| e.combine_associative_ops("bool").sort_commutative_ops("bool");
+        assert_eq!(flattened(distribution.reduce(p("(A & B) | (A & C) | (A & D)"))), flattened(p("A & (B | C | D)")));
+        assert_eq!(flattened(distribution.reduce(p("(A & B) | (A & C) | (A & D) | (A & E) | (A & F) | (A & G) | (A & H) | (A & I)"))), flattened(p("A & (B | C | D | E | F | G | H | I)")));
+
+        let bindings = match_pattern(&p("P & Q"), &p("A & B & C")).unwrap();
+        assert_eq!((&bindings["P"], &bindings["Q"]), (&p("A & B"), &p("C")));
+        // match_pattern() only reports matches of the whole chain, but find_matches() also finds partial ones
+        assert_eq!(match_pattern(&p("P & ~P"), &p("A & B & ~A")), None);
+        let complement = RewriteRule::from_patterns(&[("P & ~P", "_|_")]);
+        assert_eq!(complement.find_matches(&p("A & B & ~A")).len(), 1);
+        assert_eq!(complement.reduce(p("A & B & ~A")), p("_|_ & B"));
+    }
+
+    #[test]
+    fn test_analyze() {
+        use crate::parser::parse_unwrap as p;
+
+        let duplication = RewriteRule::from_patterns(&[("P", "P & P")]);
+        assert_eq!(duplication.analyze(), vec![RewriteWarning::MatchesEverything { pattern: p("P"), replacement: p("P & P") }]);
+
+        // Commutation can always be applied again, however the operands are permuted
+        let commutation = RewriteRule::from_patterns(&[("P & Q", "Q & P")]);
+        assert!(matches!(&commutation.analyze()[..], [RewriteWarning::SelfEmbedding { .. }]));
+
+        let impl_or = RewriteRule::from_patterns(&[("P -> Q", "~P | Q"), ("~P | Q", "P -> Q")]);
+        assert!(matches!(&impl_or.analyze()[..], [RewriteWarning::Cycle { .. }]));
+
+        // ~~(A & B) is rewritten to both A & B and ~(~A | ~B), which has nowhere to go without the other DeMorgan's law
+        let mut demorgan = RewriteRule::from_patterns(&[("~~P", "P"), ("~(P & Q)", "~P | ~Q")]);
+        let warnings = demorgan.analyze();
+        assert_eq!(warnings.len(), 1);
+        match &warnings[0] {
+            RewriteWarning::CriticalPair { left, right, .. } => {
+                let (left, right) = (left.canonicalize(), right.canonicalize());
+                assert!((matches!(left, Expr::Assoc { op: Op::And, .. }) && matches!(right, Expr::Not { .. })) || (matches!(left, Expr::Not { .. }) && matches!(right, Expr::Assoc { op: Op::And, .. })));
+            }
+            w => panic!("unexpected warning {w}"),
+        }
+        demorgan.reductions.extend(RewriteRule::from_patterns(&[("~(P | Q)", "~P & ~Q")]).reductions);
+        assert_eq!(demorgan.analyze(), vec![]);
+    }
+
     #[test]
     fn test_reduce_pattern() {
         // DeMorgan's for and/or that have only two parameters
@@ -249,6 +1093,6 @@ mod tests {
         let replace2 = Expr::assoc(Op::And, &[!Expr::var("phi"), !Expr::var("psi")]);
 
         let patterns = vec![(pattern1, replace1), (pattern2, replace2)];
-        reduce_pattern(Expr::var("some_expr"), &patterns);
+        reduce_pattern(Expr::var("some_expr"), &patterns, &[]);
     }
 }
@@ -0,0 +1,116 @@
+/*!
+# Rule examples
+
+Every rule comes with a tiny proof that uses it correctly, as its last step,
+so students can see how the rule is applied before trying it themselves. The
+rule's schema gives its shape; the example gives an instance of it, with
+whole formulas standing in for the schema's letters, and any subproofs the
+rule cites filled in.
+
+The examples are stored in `rule_examples.json`, as fragments in the
+clipboard format of `proofs::clipboard` keyed by the rules' serialized names,
+and every line of every example is checked by the tests.
+
+```
+use aris::expr::Expr;
+use aris::proofs::pooledproof::PooledProof;
+use aris::proofs::Proof;
+use aris::rule_examples;
+use aris::rules::RuleM;
+use frunk_core::HList;
+
+let prf = rule_examples::example::<PooledProof<HList![Expr]>>(RuleM::ImpElim).unwrap();
+assert_eq!(prf.premises().len(), 2);
+assert!(rule_examples::example::<PooledProof<HList![Expr]>>(RuleM::EmptyRule).is_none());
+```
+*/
+
+use crate::parser;
+use crate::proofs::clipboard::ClipboardProof;
+use crate::proofs::Proof;
+use crate::rules::Rule;
+use crate::rules::RuleM;
+
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref EXAMPLES: HashMap<String, ClipboardProof> = serde_json::from_str(include_str!("rule_examples.json")).expect("rule_examples.json should be valid");
+}
+
+/// The example of a rule, as lines in the clipboard format, or `None` for
+/// rules without one, which are those that aren't really rules, like
+/// `RuleM::EmptyRule`
+pub fn fragment(rule: Rule) -> Option<&'static ClipboardProof> {
+    EXAMPLES.get(RuleM::to_serialized_name(rule))
+}
+
+/// The example of a rule, as a new proof whose last step uses the rule. Its
+/// formulas are read with the default syntax, whatever the syntax in use, but
+/// rules used in only one logic, given by `rules::logic_of()`, only check in
+/// that logic.
+pub fn example<P: Proof>(rule: Rule) -> Option<P> {
+    let fragment = fragment(rule)?;
+    Some(parser::with_default_syntax(|| fragment.to_proof()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::Expr;
+    use crate::proofs::clipboard::ClipboardLine;
+    use crate::proofs::pooledproof::PooledProof;
+    use crate::proofs::report::Report;
+    use crate::proofs::report::Verdict;
+    use crate::rules;
+
+    use frunk_core::HList;
+
+    #[test]
+    fn test_every_rule_has_an_example() {
+        for &rule in RuleM::ALL_RULES {
+            let expected = rule != RuleM::EmptyRule && rule != RuleM::Gap;
+            assert_eq!(fragment(rule).is_some(), expected, "{}", RuleM::to_serialized_name(rule));
+        }
+        for name in EXAMPLES.keys() {
+            assert!(RuleM::from_serialized_name(name).is_some(), "{name} isn't a rule");
+        }
+    }
+
+    #[test]
+    fn test_examples_check() {
+        fn formulas<'a>(premises: &'a [String], lines: &'a [ClipboardLine], out: &mut Vec<&'a str>) {
+            out.extend(premises.iter().map(String::as_str));
+            for line in lines {
+                match line {
+                    ClipboardLine::Step(step) => out.push(&step.formula),
+                    ClipboardLine::Subproof(sub) => formulas(&sub.premises, &sub.lines, out),
+                }
+            }
+        }
+
+        for &rule in RuleM::ALL_RULES {
+            let Some(fragment) = fragment(rule) else { continue };
+            let name = RuleM::to_serialized_name(rule);
+            let mut texts = vec![];
+            formulas(&fragment.premises, &fragment.lines, &mut texts);
+            for text in texts {
+                assert!(parser::with_default_syntax(|| parser::parse(text)).is_some(), "example of {name} has a formula that doesn't parse: {text}");
+            }
+            match fragment.lines.last() {
+                Some(ClipboardLine::Step(step)) => assert_eq!(step.rule, name, "example of {name} doesn't end with it"),
+                _ => panic!("example of {name} doesn't end with a step"),
+            }
+
+            let prf = example::<PooledProof<HList![Expr]>>(rule).unwrap();
+            let syntax = parser::Syntax { logic_mode: rules::logic_of(rule).unwrap_or_default(), operators: vec![] };
+            let report = parser::with_syntax(&syntax, || Report::of(&prf, &[]));
+            // Rules that only reorder a formula, like Commutation, restate the
+            // line they cite, which is warned about, but nothing else should be
+            let (last, rest) = report.lines.split_last().unwrap();
+            assert!(matches!(last.verdict, Verdict::Correct | Verdict::Warned), "the example of {name} doesn't check: {:?}", last.messages);
+            for line in rest {
+                assert_eq!(line.verdict, Verdict::Correct, "line {} of the example of {name} isn't correct: {:?}", line.line, line.messages);
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+/*!
+# Course-specific rule names
+
+Textbooks disagree on what to call rules: one course's "→ Elimination" is
+another's "Modus Ponens", and "Disjunctive Syllogism" is often just "DS".
+Courses can set their own names for rules with `set_aliases()`, which GUIs show
+wherever they show a rule's name by calling `display_name()` instead of
+`RuleT::get_name()`. Aliases are keyed by serialized names, which are still
+what proof files store, so proofs move between courses unchanged.
+
+```
+use aris::rule_names;
+use aris::rules::{RuleM, RuleT};
+
+let aliases = rule_names::aliases_from_json(r#"{ "MODUS_PONENS": "Modus Ponens" }"#).unwrap();
+rule_names::set_aliases(aliases).unwrap();
+assert_eq!(rule_names::display_name(RuleM::ImpElim), "Modus Ponens");
+assert_eq!(RuleM::ImpElim.get_name(), "→ Elimination");
+assert_eq!(rule_names::display_name(RuleM::AndElim), RuleM::AndElim.get_name());
+# rule_names::set_aliases(Default::default()).unwrap();
+```
+*/
+
+use crate::rules::Rule;
+use crate::rules::RuleM;
+use crate::rules::RuleT;
+
+use std::collections::HashMap;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+lazy_static! {
+    static ref ALIASES: RwLock<HashMap<String, String>> = RwLock::new(HashMap::new());
+}
+
+/// Replace the course's rule names with `aliases`, which map serialized names
+/// to the names to show. Fails without changing anything if a serialized name
+/// isn't a rule's, or an alias is blank.
+pub fn set_aliases(aliases: HashMap<String, String>) -> Result<(), String> {
+    for (serialized_name, alias) in &aliases {
+        if RuleM::from_serialized_name(serialized_name).is_none() {
+            return Err(format!("There's no rule named {serialized_name} to rename"));
+        }
+        if alias.trim().is_empty() {
+            return Err(format!("The name for {serialized_name} is blank"));
+        }
+    }
+    *ALIASES.write().unwrap_or_else(PoisonError::into_inner) = aliases;
+    Ok(())
+}
+
+/// Read rule names from a JSON object mapping serialized names to aliases,
+/// like `{ "DISJUNCTIVE_SYLLOGISM": "DS" }`
+pub fn aliases_from_json(json: &str) -> Result<HashMap<String, String>, String> {
+    serde_json::from_str(json).map_err(|err| format!("Invalid rule names: {err}"))
+}
+
+/// The course's name for a rule, or its usual name if the course doesn't
+/// rename it
+pub fn display_name(rule: Rule) -> String {
+    let aliases = ALIASES.read().unwrap_or_else(PoisonError::into_inner);
+    aliases.get(RuleM::to_serialized_name(rule)).cloned().unwrap_or_else(|| rule.get_name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_names() {
+        assert!(aliases_from_json("[]").is_err());
+        assert!(set_aliases([("NOT_A_RULE".to_string(), "Nothing".to_string())].into_iter().collect()).is_err());
+        assert!(set_aliases([("DISJUNCTIVE_SYLLOGISM".to_string(), " ".to_string())].into_iter().collect()).is_err());
+        assert_eq!(display_name(RuleM::DisjunctiveSyllogism), RuleM::DisjunctiveSyllogism.get_name());
+    }
+}
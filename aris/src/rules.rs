@@ -13,6 +13,8 @@ The `SharedChecks` wrapper and `frunk_core::coproduct::Coproduct` tie the differ
 
 `SharedChecks`'s `RuleT` instance enforces common requirements based on the inner type's metadata (mostly number of dependencies.
 
+Rules from outside Aris are `PluginRule`s, which look up their metadata and checks in the registry of `crate::plugins`.
+
 ## Name metadata
 
 `RuleT::get_name` is a human-readable name, often with unicode, for displaying in the UIs.
@@ -37,6 +39,7 @@ Each `check` implementation usually starts off with bringing the rules of the re
     - For the string, use same name as in the Java (deserializing the UI's usage of it will fail if the name isn't the same)
 - In the `impl RuleT for WhicheverEnum` block:
     - Add the metadata, if applicable
+    - Add a description and schema for the GUI's rule reference
     - Add the new rule to the `check` method's main match block, with an `unimplemented!()` body
 - Verify that all the structural changes compile, possibly commit the structural changes so far
     - Commit `b86de7fbe6bea3947ef864b8f253be34ec0c1306` is a good example of what the structure should look like at this point
@@ -55,13 +58,22 @@ Adding the tests and implementing the rule can be interleaved; it's convenient t
 */
 
 use crate::equivs;
-use crate::expr::subst;
 use crate::expr::Constraint;
 use crate::expr::Expr;
 use crate::expr::Op;
 use crate::expr::QuantKind;
+use crate::interned::ExprArena;
+use crate::limits;
+use crate::parser::logic_mode;
+use crate::parser::LogicMode;
+use crate::plugins;
+use crate::plugins::PluginSubproof;
+use crate::proofs::line_info;
+use crate::proofs::AssumptionPolicy;
+use crate::proofs::Justification;
 use crate::proofs::PjRef;
 use crate::proofs::Proof;
+use crate::rewrite_rules;
 use crate::rewrite_rules::RewriteRule;
 
 use std::collections::BTreeSet;
@@ -150,6 +162,7 @@ pub enum BooleanEquivalence {
     DeMorgan,
     Association,
     Commutation,
+    AssociationCommutation,
     Idempotence,
     Distribution,
     DoubleNegation,
@@ -184,6 +197,7 @@ pub enum BiconditionalEquivalence {
     BiconditionalContraposition,
     BiconditionalCommutation,
     BiconditionalAssociation,
+    BiconditionalAssociationCommutation,
     BiconditionalReduction,
     BiconditionalComplement,
     BiconditionalIdentity,
@@ -210,6 +224,7 @@ pub enum Special {
     Reiteration,
     Resolution,
     TruthFunctionalConsequence,
+    FirstOrderConsequence,
 }
 
 #[allow(missing_docs)]
@@ -219,6 +234,47 @@ pub enum Induction {
     Strong,
 }
 
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reduction {
+    Conjunction,
+    Disjunction,
+    Negation,
+    BicondReduction,
+    CondReduction,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SetTheory {
+    ElementIntro,
+    ElementElim,
+    SubsetIntro,
+    SubsetElim,
+    Extensionality,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Temporal {
+    AlwaysElim,
+    EventuallyIntro,
+    UntilIntro,
+    UntilElim,
+    TemporalNegation,
+}
+
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hoare {
+    Skip,
+    Assignment,
+    Sequence,
+    Conditional,
+    While,
+    Consequence,
+}
+
 /// This should be the default rule when creating a new step in a UI. It
 /// always fails, and isn't part of any `RuleClassification`s.
 ///
@@ -231,13 +287,30 @@ pub enum Induction {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct EmptyRule;
 
+/// A step admitted without proof, as a placeholder for sketching a proof top
+/// down. It always fails with `ProofCheckError::Gap`, which UIs show apart
+/// from mistakes, and lines may cite it like any other. It isn't part of any
+/// `RuleClassification`s.
+///
+/// ```rust
+/// use aris::rules::{Gap, RuleT};
+///
+/// assert_eq!(Gap.get_classifications().len(), 0);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gap;
+
+/// A rule registered with `crate::plugins::register()`, by its index in the
+/// registry
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PluginRule(pub(crate) usize);
+
 /// The RuleT instance for SharedChecks does checking that is common to all the rules;
 ///  it should always be the outermost constructor of the Rule type alias.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SharedChecks<T>(T);
 
-
-pub type Rule = SharedChecks<Coprod!(PropositionalInference, PredicateInference, BooleanInference, ConditionalInference, BiconditionalInference, QuantifierInference, BooleanEquivalence, ConditionalEquivalence, BiconditionalEquivalence, QuantifierEquivalence, Special, Induction, Reduction, EmptyRule)>;
+pub type Rule = SharedChecks<Coprod!(PropositionalInference, PredicateInference, BooleanInference, ConditionalInference, BiconditionalInference, QuantifierInference, BooleanEquivalence, ConditionalEquivalence, BiconditionalEquivalence, QuantifierEquivalence, Special, Induction, Reduction, SetTheory, Temporal, Hoare, EmptyRule, Gap, PluginRule)>;
 
 /// Conveniences for constructing rules of the appropriate type, primarily for testing.
 /// The non-standard naming conventions here are because a module is being used to pretend to be an enum.
@@ -257,11 +330,11 @@ pub mod RuleM {
             /// Convert a Rule to a string compatible with the Java enum `edu.rpi.aris.rules.RuleList`
             #[allow(unused_parens)]
             pub fn to_serialized_name(rule: Rule) -> &'static str {
-                declare_rules! { DECLARE_MATCH; on: rule; default: unreachable!(); $([$value, $name]),+ }
+                declare_rules! { DECLARE_MATCH; on: rule; default: crate::plugins::serialized_name(*rule.0.get::<PluginRule, _>().expect("every other rule is declared")); $([$value, $name]),+ }
             }
-            /// Convert string from the Java enum `edu.rpi.aris.rules.RuleList` to a Rule
+            /// Convert string from the Java enum `edu.rpi.aris.rules.RuleList`, or the name of a plugin rule, to a Rule
             pub fn from_serialized_name(name: &str) -> Option<Rule> {
-                Some(declare_rules! { DECLARE_MATCH; on: name; default: { return None; }; $([$name, $id]),+ })
+                Some(declare_rules! { DECLARE_MATCH; on: name; default: { return crate::plugins::from_serialized_name(name); }; $([$name, $id]),+ })
             }
         };
         (DECLARE_STATICS; [$id: ident, $value:expr]) => {
@@ -328,6 +401,7 @@ pub mod RuleM {
 
         [Association, "ASSOCIATION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BooleanEquivalence::Association)))))))))],
         [Commutation, "COMMUTATION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BooleanEquivalence::Commutation)))))))))],
+        [AssociationCommutation, "ASSOCIATION_COMMUTATION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BooleanEquivalence::AssociationCommutation)))))))))],
         [Idempotence, "IDEMPOTENCE", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BooleanEquivalence::Idempotence)))))))))],
         [DeMorgan, "DE_MORGAN", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BooleanEquivalence::DeMorgan)))))))))],
         [Distribution, "DISTRIBUTION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BooleanEquivalence::Distribution)))))))))],
@@ -355,6 +429,7 @@ pub mod RuleM {
         [BiconditionalContraposition, "BICONDITIONAL_CONTRAPOSITION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BiconditionalEquivalence::BiconditionalContraposition)))))))))))],
         [BiconditionalCommutation, "BICONDITIONAL_COMMUTATION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BiconditionalEquivalence::BiconditionalCommutation)))))))))))],
         [BiconditionalAssociation, "BICONDITIONAL_ASSOCIATION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BiconditionalEquivalence::BiconditionalAssociation)))))))))))],
+        [BiconditionalAssociationCommutation, "BICONDITIONAL_ASSOCIATION_COMMUTATION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BiconditionalEquivalence::BiconditionalAssociationCommutation)))))))))))],
         [BiconditionalReduction, "BICONDITIONAL_REDUCTION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BiconditionalEquivalence::BiconditionalReduction)))))))))))],
         [BiconditionalComplement, "BICONDITIONAL_COMPLEMENT", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BiconditionalEquivalence::BiconditionalComplement)))))))))))],
         [BiconditionalIdentity, "BICONDITIONAL_IDENTITY", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(BiconditionalEquivalence::BiconditionalIdentity)))))))))))],
@@ -373,11 +448,38 @@ pub mod RuleM {
         [Reiteration, "REITERATION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Special::Reiteration)))))))))))))],
         [Resolution, "RESOLUTION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Special::Resolution)))))))))))))],
         [TruthFunctionalConsequence, "TRUTHFUNCTIONAL_CONSEQUENCE", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Special::TruthFunctionalConsequence)))))))))))))],
+        [FirstOrderConsequence, "FIRST_ORDER_CONSEQUENCE", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Special::FirstOrderConsequence)))))))))))))],
 
         [WeakInduction, "WEAK_INDUCTION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Induction::Weak))))))))))))))],
         [StrongInduction, "STRONG_INDUCTION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Induction::Strong))))))))))))))],
 
-        [EmptyRule, "EMPTY_RULE", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(super::EmptyRule)))))))))))))))]
+        [Conjunction, "REDUCTION_CONJUNCTION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(super::Reduction::Conjunction)))))))))))))))],
+        [Disjunction, "REDUCTION_DISJUNCTION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(super::Reduction::Disjunction)))))))))))))))],
+        [Negation, "REDUCTION_NEGATION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(super::Reduction::Negation)))))))))))))))],
+        [BicondReduction, "BICOND_REDUCTION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(super::Reduction::BicondReduction)))))))))))))))],
+        [CondReduction, "COND_REDUCTION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(super::Reduction::CondReduction)))))))))))))))],
+
+        [ElementIntro, "ELEMENT_INTRO", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(SetTheory::ElementIntro))))))))))))))))],
+        [ElementElim, "ELEMENT_ELIM", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(SetTheory::ElementElim))))))))))))))))],
+        [SubsetIntro, "SUBSET_INTRO", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(SetTheory::SubsetIntro))))))))))))))))],
+        [SubsetElim, "SUBSET_ELIM", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(SetTheory::SubsetElim))))))))))))))))],
+        [Extensionality, "EXTENSIONALITY", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(SetTheory::Extensionality))))))))))))))))],
+
+        [AlwaysElim, "ALWAYS_ELIM", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Temporal::AlwaysElim)))))))))))))))))],
+        [EventuallyIntro, "EVENTUALLY_INTRO", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Temporal::EventuallyIntro)))))))))))))))))],
+        [UntilIntro, "UNTIL_INTRO", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Temporal::UntilIntro)))))))))))))))))],
+        [UntilElim, "UNTIL_ELIM", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Temporal::UntilElim)))))))))))))))))],
+        [TemporalNegation, "TEMPORAL_NEGATION", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Temporal::TemporalNegation)))))))))))))))))],
+
+        [HoareSkip, "HOARE_SKIP", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Hoare::Skip))))))))))))))))))],
+        [HoareAssignment, "HOARE_ASSIGNMENT", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Hoare::Assignment))))))))))))))))))],
+        [HoareSequence, "HOARE_SEQUENCE", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Hoare::Sequence))))))))))))))))))],
+        [HoareConditional, "HOARE_CONDITIONAL", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Hoare::Conditional))))))))))))))))))],
+        [HoareWhile, "HOARE_WHILE", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Hoare::While))))))))))))))))))],
+        [HoareConsequence, "HOARE_CONSEQUENCE", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(Hoare::Consequence))))))))))))))))))],
+
+        [EmptyRule, "EMPTY_RULE", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(super::EmptyRule)))))))))))))))))))],
+        [Gap, "GAP", (SharedChecks(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inr(Inl(super::Gap))))))))))))))))))))]
     }
 }
 
@@ -405,12 +507,112 @@ pub enum RuleClassification {
     QuantifierEquivalence,
     Special,
     Induction,
+    Reduction,
+    #[strum(to_string = "Set Theory")]
+    SetTheory,
+    #[strum(to_string = "Temporal Logic")]
+    Temporal,
+    #[strum(to_string = "Hoare Logic")]
+    Hoare,
 }
 
 impl RuleClassification {
-    /// Get an iterator over the rules in this rule classification
+    /// Get an iterator over the rules in this rule classification, including
+    /// registered plugin rules
     pub fn rules(self) -> impl Iterator<Item = Rule> {
-        RuleM::ALL_RULES.iter().filter(move |rule| rule.get_classifications().contains(&self)).cloned()
+        RuleM::ALL_RULES.iter().cloned().chain(crate::plugins::rules()).filter(move |rule| rule.get_classifications().contains(&self))
+    }
+}
+
+/// The logic a rule can only be used in, like temporal logic for the temporal rules, or `None` for rules used in
+/// any logic
+pub fn logic_of(rule: Rule) -> Option<LogicMode> {
+    rule.get_classifications().into_iter().find_map(|classification| match classification {
+        RuleClassification::Temporal => Some(LogicMode::Temporal),
+        RuleClassification::Hoare => Some(LogicMode::Program),
+        _ => None,
+    })
+}
+
+/// A typical use of a rule: from lines of the forms in `deps` and subproofs of the forms in `subdeps`, the rule concludes `conclusion`.
+/// Rules that accept several forms (e.g. `AndIntro` with any number of conjuncts) are shown with just one.
+///
+/// ```
+/// use aris::rules::{RuleM, RuleT};
+///
+/// assert_eq!(RuleM::ImpElim.get_schema().unwrap().to_string(), "(P → Q), P ⊢ Q");
+/// assert_eq!(RuleM::ImpIntro.get_schema().unwrap().to_string(), "[P ⋯ Q] ⊢ (P → Q)");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleSchema {
+    pub deps: Vec<Expr>,
+    /// The premise of each subproof, if it has one, and the line of it that the rule uses
+    pub subdeps: Vec<(Option<Expr>, Expr)>,
+    pub conclusion: Expr,
+}
+
+impl RuleSchema {
+    /// Parse a schema written in this module, panicking on syntax errors. Schemas are written in first-order logic,
+    /// whatever the logic the current thread parses.
+    fn parse(deps: &[&str], subdeps: &[(Option<&str>, &str)], conclusion: &str) -> Self {
+        use crate::parser::parse_unwrap as p;
+        crate::parser::with_default_syntax(|| RuleSchema { deps: deps.iter().map(|dep| p(dep)).collect(), subdeps: subdeps.iter().map(|(premise, line)| (premise.map(p), p(line))).collect(), conclusion: p(conclusion) })
+    }
+
+    /// Build a proof whose last step uses `rule` on premises and subproofs of the schema's forms. The lines
+    /// inside the subproofs are left without a rule, since only the last step is being demonstrated.
+    pub fn example_proof<P: Proof>(&self, rule: Rule) -> P {
+        let mut prf = P::new();
+        let deps = self.deps.iter().map(|dep| Coproduct::inject(prf.add_premise(dep.clone()))).collect();
+        let sdeps = self
+            .subdeps
+            .iter()
+            .map(|(premise, line)| {
+                let sub = prf.add_subproof();
+                prf.with_mut_subproof(&sub, |subproof| {
+                    if let Some(premise) = premise {
+                        subproof.add_premise(premise.clone());
+                    }
+                    subproof.add_step(Justification(line.clone(), RuleM::EmptyRule, vec![], vec![]));
+                });
+                sub
+            })
+            .collect();
+        prf.add_step(Justification(self.conclusion.clone(), rule, deps, sdeps));
+        prf
+    }
+
+    /// The use of the rule that concludes `goal`, worked out backward by matching the schema's conclusion against
+    /// it, or `None` if it doesn't match. Formulas the goal doesn't determine, like the antecedent that →E needs,
+    /// are left blank, as `Expr::var("")`.
+    pub fn backward(&self, goal: &Expr) -> Option<RuleSchema> {
+        let mut bindings = HashMap::new();
+        if !rewrite_rules::match_schema(&self.conclusion, goal, &mut bindings) {
+            return None;
+        }
+        let instantiate = |e: &Expr| {
+            if crate::expr::free_vars(e).iter().all(|name| bindings.contains_key(name)) {
+                rewrite_rules::instantiate_schema(e, &bindings)
+            } else {
+                Expr::var("")
+            }
+        };
+        Some(RuleSchema { deps: self.deps.iter().map(instantiate).collect(), subdeps: self.subdeps.iter().map(|(premise, line)| (premise.as_ref().map(instantiate), instantiate(line))).collect(), conclusion: goal.clone() })
+    }
+}
+
+impl std::fmt::Display for RuleSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let subproofs = self.subdeps.iter().map(|(premise, line)| match premise {
+            Some(premise) => format!("[{premise} ⋯ {line}]"),
+            None => format!("[⋯ {line}]"),
+        });
+        let antecedents = self.deps.iter().map(ToString::to_string).chain(subproofs).collect::<Vec<_>>();
+        if antecedents.is_empty() {
+            write!(f, "⊢ {}", self.conclusion)
+        } else {
+            write!(f, "{} ⊢ {}", antecedents.join(", "), self.conclusion)
+        }
     }
 }
 
@@ -418,6 +620,10 @@ impl RuleClassification {
 pub trait RuleT {
     /// get_name gets the name of the rule for display in the GUI
     fn get_name(&self) -> String;
+    /// get_description gets a one-sentence explanation of the rule for the GUI's rule reference
+    fn get_description(&self) -> String;
+    /// get_schema gets a typical use of the rule, shown in the GUI's rule reference and used to build an example proof; None for rules with nothing to show
+    fn get_schema(&self) -> Option<RuleSchema>;
     /// get_classifications is used to tell the GUI which panes/right click menus to put the rule under
     fn get_classifications(&self) -> HashSet<RuleClassification>;
     /// num_deps is used by SharedChecks to ensure that the right number of dependencies are provided, None indicates that no checking is done (e.g. for variadic rules)
@@ -426,6 +632,14 @@ pub trait RuleT {
     fn num_subdeps(&self) -> Option<usize>;
     /// check that expr is a valid conclusion of the rule given the corresponding lists of dependencies and subproof dependencies, returning Ok(()) on success, and an error to display in the GUI on failure
     fn check<P: Proof>(self, p: &P, expr: Expr, deps: Vec<PjRef<P>>, sdeps: Vec<P::SubproofReference>) -> Result<(), ProofCheckError<PjRef<P>, P::SubproofReference>>;
+    /// rewrite_rule gets a rewrite rule whose rewrites this rule accepts as steps, so that the GUI can apply it to a selected subformula; None for rules that have no such rewrite rule
+    fn rewrite_rule(&self) -> Option<&'static RewriteRule> {
+        None
+    }
+    /// backward works out what a step using the rule has to cite to conclude `goal`, as in `RuleSchema::backward`, so that the GUI can build a proof from its conclusion up; None for rules that can't conclude it, or that have no schema to work from
+    fn backward(&self, goal: &Expr) -> Option<RuleSchema> {
+        self.get_schema()?.backward(goal)
+    }
 }
 
 impl<A: RuleT, B: RuleT> RuleT for Coproduct<A, B> {
@@ -435,6 +649,18 @@ impl<A: RuleT, B: RuleT> RuleT for Coproduct<A, B> {
             Inr(x) => x.get_name(),
         }
     }
+    fn get_description(&self) -> String {
+        match self {
+            Inl(x) => x.get_description(),
+            Inr(x) => x.get_description(),
+        }
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        match self {
+            Inl(x) => x.get_schema(),
+            Inr(x) => x.get_schema(),
+        }
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         match self {
             Inl(x) => x.get_classifications(),
@@ -459,11 +685,29 @@ impl<A: RuleT, B: RuleT> RuleT for Coproduct<A, B> {
             Inr(x) => x.check(p, expr, deps, sdeps),
         }
     }
+    fn rewrite_rule(&self) -> Option<&'static RewriteRule> {
+        match self {
+            Inl(x) => x.rewrite_rule(),
+            Inr(x) => x.rewrite_rule(),
+        }
+    }
+    fn backward(&self, goal: &Expr) -> Option<RuleSchema> {
+        match self {
+            Inl(x) => x.backward(goal),
+            Inr(x) => x.backward(goal),
+        }
+    }
 }
 impl RuleT for frunk_core::coproduct::CNil {
     fn get_name(&self) -> String {
         match *self {}
     }
+    fn get_description(&self) -> String {
+        match *self {}
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        match *self {}
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         match *self {}
     }
@@ -482,6 +726,12 @@ impl<T: RuleT> RuleT for SharedChecks<T> {
     fn get_name(&self) -> String {
         self.0.get_name()
     }
+    fn get_description(&self) -> String {
+        self.0.get_description()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        self.0.get_schema()
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         self.0.get_classifications()
     }
@@ -493,6 +743,14 @@ impl<T: RuleT> RuleT for SharedChecks<T> {
     }
     fn check<P: Proof>(self, p: &P, expr: Expr, deps: Vec<PjRef<P>>, sdeps: Vec<P::SubproofReference>) -> Result<(), ProofCheckError<PjRef<P>, P::SubproofReference>> {
         use ProofCheckError::*;
+        // Citing a premise of the proof in place of a subproof deserves a better message than the counts being off
+        if let (Some(directs), Some(subs)) = (self.num_deps(), self.num_subdeps()) {
+            if deps.len() > directs && sdeps.len() < subs {
+                if let Some(premise) = deps.iter().rev().find(|dep| matches!(dep, Coproduct::Inl(pr) if !p.is_assumption(pr))) {
+                    return Err(PremiseCitedAsAssumption(premise.clone()));
+                }
+            }
+        }
         if let Some(directs) = self.num_deps() {
             if deps.len() != directs {
                 return Err(IncorrectDepCount(deps, directs));
@@ -503,8 +761,33 @@ impl<T: RuleT> RuleT for SharedChecks<T> {
                 return Err(IncorrectSubDepCount(sdeps, subs));
             }
         }
-        // TODO: enforce that each subproof has exactly 1 premise
-        self.0.check(p, expr, deps, sdeps)
+        limits::with_budget(|| self.0.check(p, expr, deps, sdeps))
+    }
+    fn rewrite_rule(&self) -> Option<&'static RewriteRule> {
+        self.0.rewrite_rule()
+    }
+    fn backward(&self, goal: &Expr) -> Option<RuleSchema> {
+        self.0.backward(goal)
+    }
+}
+
+/// The assumptions a rule like →I discharges from a cited subproof, as one formula: the subproof's assumption, or
+/// the conjunction of its assumptions, in order, if `proofs::assumption_policy()` allows several
+fn discharged_assumption<P: Proof>(p: &P, sr: &P::SubproofReference) -> Result<Expr, ProofCheckError<PjRef<P>, P::SubproofReference>> {
+    let mut assumptions = p.lookup_subproof_or_die(sr)?.premises().into_iter().map(|r| p.lookup_premise_or_die(&r)).collect::<Result<Vec<_>, _>>()?;
+    match (assumptions.len(), crate::proofs::assumption_policy()) {
+        (1, _) => Ok(assumptions.remove(0)),
+        (n, AssumptionPolicy::Multiple) if n > 1 => Ok(Expr::Assoc { op: Op::And, exprs: assumptions }),
+        (n, _) => Err(ProofCheckError::IncorrectAssumptionCount(sr.clone(), n)),
+    }
+}
+
+/// The assumption of a cited subproof that has to have exactly one, whatever `proofs::assumption_policy()` allows,
+/// like the one naming the witness for ∃E
+fn single_assumption<P: Proof>(p: &P, sr: &P::SubproofReference) -> Result<Expr, ProofCheckError<PjRef<P>, P::SubproofReference>> {
+    match p.lookup_subproof_or_die(sr)?.premises()[..] {
+        [ref assumption] => p.lookup_premise_or_die(assumption),
+        ref assumptions => Err(ProofCheckError::IncorrectAssumptionCount(sr.clone(), assumptions.len())),
     }
 }
 
@@ -545,6 +828,56 @@ impl RuleT for PropositionalInference {
         }
         .into()
     }
+    fn get_description(&self) -> String {
+        use PropositionalInference::*;
+        match self {
+            AndIntro => "Conclude the conjunction of any number of lines.",
+            AndElim => "Conclude one or more of the conjuncts of a conjunction.",
+            OrIntro => "Conclude a disjunction that has a line as one of its disjuncts.",
+            OrElim => "Conclude a formula that follows from each disjunct of a disjunction, shown by a subproof assuming each disjunct.",
+            ImpIntro => "Conclude a conditional from a subproof that assumes its antecedent and reaches its consequent.",
+            ImpElim => "From a conditional and its antecedent, conclude its consequent (modus ponens).",
+            NotIntro => "Conclude the negation of a formula from a subproof that assumes it and reaches a contradiction.",
+            NotElim => "Remove a double negation.",
+            ContradictionIntro => "Conclude a contradiction from a formula and its negation.",
+            ContradictionElim => "Conclude anything from a contradiction.",
+            BiconditionalIntro => "Conclude a biconditional from subproofs (or conditionals) showing that each side implies the other.",
+            BiconditionalElim => "From a biconditional and one of its sides, conclude the other side.",
+            EquivalenceIntro => "Conclude that formulas are equivalent from conditionals (or subproofs) showing that each implies the others.",
+            EquivalenceElim => "From an equivalence and one of its members, conclude another member.",
+        }
+        .into()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use PropositionalInference::*;
+        Some(match self {
+            AndIntro => RuleSchema::parse(&["P", "Q"], &[], "P & Q"),
+            AndElim => RuleSchema::parse(&["P & Q"], &[], "P"),
+            OrIntro => RuleSchema::parse(&["P"], &[], "P | Q"),
+            OrElim => RuleSchema::parse(&["P | Q"], &[(Some("P"), "R"), (Some("Q"), "R")], "R"),
+            ImpIntro => RuleSchema::parse(&[], &[(Some("P"), "Q")], "P -> Q"),
+            ImpElim => RuleSchema::parse(&["P -> Q", "P"], &[], "Q"),
+            NotIntro => RuleSchema::parse(&[], &[(Some("P"), "_|_")], "~P"),
+            NotElim => RuleSchema::parse(&["~~P"], &[], "P"),
+            ContradictionIntro => RuleSchema::parse(&["P", "~P"], &[], "_|_"),
+            ContradictionElim => RuleSchema::parse(&["_|_"], &[], "P"),
+            BiconditionalIntro => RuleSchema::parse(&[], &[(Some("P"), "Q"), (Some("Q"), "P")], "P <-> Q"),
+            BiconditionalElim => RuleSchema::parse(&["P <-> Q", "P"], &[], "Q"),
+            EquivalenceIntro => RuleSchema::parse(&["P -> Q", "Q -> P"], &[], "P === Q"),
+            EquivalenceElim => RuleSchema::parse(&["P === Q", "P"], &[], "Q"),
+        })
+    }
+    fn backward(&self, goal: &Expr) -> Option<RuleSchema> {
+        use PropositionalInference::*;
+        match (self, goal) {
+            // Conjunctions of any number of conjuncts are introduced from all of them
+            (AndIntro, Expr::Assoc { op: Op::And, exprs }) => Some(RuleSchema { deps: exprs.clone(), subdeps: vec![], conclusion: goal.clone() }),
+            // Which disjunct a disjunction is introduced from is up to the proof
+            (OrIntro, Expr::Assoc { op: Op::Or, .. }) => Some(RuleSchema { deps: vec![Expr::var("")], subdeps: vec![], conclusion: goal.clone() }),
+            (AndIntro | OrIntro, _) => None,
+            _ => self.get_schema()?.backward(goal),
+        }
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         use PropositionalInference::*;
         use RuleClassification::*;
@@ -665,13 +998,14 @@ impl RuleT for PropositionalInference {
                 }
                 let prem = p.lookup_expr_or_die(&deps[0])?;
                 if let Expr::Assoc { op: Op::Or, ref exprs } = prem {
+                    let assumptions = sdeps.iter().map(|sr| discharged_assumption(p, sr)).collect::<Result<Vec<_>, _>>()?;
                     let sproofs = sdeps.into_iter().map(|r| p.lookup_subproof_or_die(&r)).collect::<Result<Vec<_>, _>>()?;
                     // if not all the subproofs have lines whose expressions contain the conclusion, return an error
                     let all_sproofs_have_conclusion = sproofs.iter().all(|sproof| sproof.lines().into_iter().filter_map(|x| x.get::<P::JustificationReference, _>().and_then(|y| p.lookup_step(y)).map(|y| y.0)).any(|c| c == conclusion));
                     if !all_sproofs_have_conclusion {
                         return Err(DepDoesNotExist(conclusion, false));
                     }
-                    if let Some(e) = exprs.iter().find(|&e| !sproofs.iter().any(|sproof| sproof.premises().into_iter().next().and_then(|r| p.lookup_premise(&r)).map(|x| x == *e) == Some(true))) {
+                    if let Some(e) = exprs.iter().find(|&e| !assumptions.contains(e)) {
                         return Err(DepDoesNotExist(e.clone(), false));
                     }
                     Ok(())
@@ -681,12 +1015,10 @@ impl RuleT for PropositionalInference {
             }
             ImpIntro => {
                 let sproof = p.lookup_subproof_or_die(&sdeps[0])?;
-                // TODO: allow generalized premises
-                assert_eq!(sproof.premises().len(), 1);
+                let prem = discharged_assumption(p, &sdeps[0])?;
                 if let Expr::Impl { ref left, ref right } = conclusion {
-                    let prem = sproof.premises().into_iter().map(|r| p.lookup_premise_or_die(&r)).collect::<Result<Vec<Expr>, _>>()?;
-                    if **left != prem[0] {
-                        return Err(DoesNotOccur(*left.clone(), prem[0].clone()));
+                    if **left != prem {
+                        return Err(DoesNotOccur(*left.clone(), prem));
                     }
                     let conc = sproof.lines().into_iter().filter_map(|x| x.get::<P::JustificationReference, _>().cloned()).map(|r| p.lookup_expr_or_die(&Coproduct::inject(r))).collect::<Result<Vec<Expr>, _>>()?;
                     if !conc.iter().any(|c| c == &**right) {
@@ -728,15 +1060,13 @@ impl RuleT for PropositionalInference {
             }
             NotIntro => {
                 let sproof = p.lookup_subproof_or_die(&sdeps[0])?;
-                // TODO: allow generalized premises
-                assert_eq!(sproof.premises().len(), 1);
+                let prem = discharged_assumption(p, &sdeps[0])?;
                 if let Expr::Not { ref operand } = conclusion {
-                    let prem = sproof.premises().into_iter().map(|r| p.lookup_premise_or_die(&r)).collect::<Result<Vec<Expr>, _>>()?;
-                    if **operand != prem[0] {
-                        return Err(DoesNotOccur(*operand.clone(), prem[0].clone()));
+                    if **operand != prem {
+                        return Err(DoesNotOccur(*operand.clone(), prem));
                     }
                     let conc = sproof.lines().into_iter().filter_map(|x| x.get::<P::JustificationReference, _>().cloned()).map(|r| p.lookup_expr_or_die(&Coproduct::inject(r))).collect::<Result<Vec<Expr>, _>>()?;
-                    if !conc.iter().any(|x| *x == Expr::Contra) {
+                    if !conc.contains(&Expr::Contra) {
                         return Err(DepDoesNotExist(Expr::Contra, false));
                     }
                     Ok(())
@@ -819,7 +1149,7 @@ impl RuleT for PropositionalInference {
                             }
                         }
                         let prems = deps.into_iter().map(|r| p.lookup_expr_or_die(&r)).collect::<Result<Vec<Expr>, _>>()?;
-                        let sproofs = sdeps.into_iter().map(|r| p.lookup_subproof_or_die(&r)).collect::<Result<Vec<_>, _>>()?;
+                        let sproofs = sdeps.iter().map(|r| p.lookup_subproof_or_die(r)).collect::<Result<Vec<_>, _>>()?;
                         let mut slab = HashMap::new();
                         let mut counter = 0;
                         let next: &mut dyn FnMut() -> _ = &mut || {
@@ -846,9 +1176,8 @@ impl RuleT for PropositionalInference {
                                 _ => return Err(OneOf(btreeset![DepOfWrongForm(prem.clone(), Expr::assoc_place_holder(oper)), DepOfWrongForm(prem.clone(), Expr::impl_place_holder()),])),
                             }
                         }
-                        for sproof in sproofs.iter() {
-                            assert_eq!(sproof.premises().len(), 1);
-                            let prem = sproof.lookup_premise_or_die(&sproof.premises()[0])?;
+                        for (sr, sproof) in sdeps.iter().zip(sproofs.iter()) {
+                            let prem = discharged_assumption(p, sr)?;
                             slab.entry(prem.clone()).or_insert_with(|| next());
                             for r in sproof.exprs() {
                                 let e = sproof.lookup_expr_or_die(&r)?.clone();
@@ -911,6 +1240,17 @@ impl RuleT for PropositionalInference {
     }
 }
 
+/// A line outside `sproof` that `line` depends on and that mentions `var`, which keeps `var` from being arbitrary
+fn generalizable_variable_counterexample<P: Proof>(sproof: &P, line: PjRef<P>, var: &str) -> Option<Expr> {
+    let contained = sproof.contained_justifications(true);
+    //println!("gvc contained {:?}", contained.iter().map(|x| sproof.lookup_expr(&x)).collect::<Vec<_>>());
+    let reachable = sproof.transitive_dependencies(line);
+    //println!("gvc reachable {:?}", reachable.iter().map(|x| sproof.lookup_expr(&x)).collect::<Vec<_>>());
+    let outside = reachable.difference(&contained);
+    //println!("gvc outside {:?}", outside.clone().map(|x| sproof.lookup_expr(&x)).collect::<Vec<_>>());
+    outside.filter_map(|x| sproof.lookup_expr(x)).find(|e| crate::expr::free_vars(e).contains(var))
+}
+
 impl RuleT for PredicateInference {
     fn get_name(&self) -> String {
         use PredicateInference::*;
@@ -922,6 +1262,41 @@ impl RuleT for PredicateInference {
         }
         .into()
     }
+    fn get_description(&self) -> String {
+        use PredicateInference::*;
+        match self {
+            ForallIntro => "Generalize a line about an arbitrary constant, reached in a subproof without depending on anything outside it that mentions the constant.",
+            ForallElim => "Instantiate a universal statement with any term.",
+            ExistsIntro => "Conclude that something has a property from a particular term that has it.",
+            ExistsElim => "Conclude a formula that follows from a subproof assuming the body of an existential for a new constant, as long as the constant doesn't escape the subproof.",
+        }
+        .into()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use PredicateInference::*;
+        Some(match self {
+            ForallIntro => RuleSchema::parse(&[], &[(None, "P(a)")], "forall x P(x)"),
+            ForallElim => RuleSchema::parse(&["forall x P(x)"], &[], "P(a)"),
+            ExistsIntro => RuleSchema::parse(&["P(a)"], &[], "exists x P(x)"),
+            ExistsElim => RuleSchema::parse(&["exists x P(x)"], &[(Some("P(a)"), "Q")], "Q"),
+        })
+    }
+    fn backward(&self, goal: &Expr) -> Option<RuleSchema> {
+        use PredicateInference::*;
+        let blank = Expr::var("");
+        let (deps, subdeps) = match (self, goal) {
+            // The arbitrary constant is named apart from the goal, though the rest of the proof may need it renamed
+            (ForallIntro, Expr::Quant { kind: QuantKind::Forall, name, body }) => {
+                let constant = crate::expr::gen_var("a", &crate::expr::free_vars(goal));
+                (vec![], vec![(None, crate::expr::subst((**body).clone(), name, Expr::var(&constant)))])
+            }
+            // Which instance, or which quantified formula, a step starts from is up to the proof
+            (ExistsIntro, Expr::Quant { kind: QuantKind::Exists, .. }) | (ForallElim, _) => (vec![blank], vec![]),
+            (ExistsElim, _) => (vec![blank.clone()], vec![(Some(blank), goal.clone())]),
+            _ => return None,
+        };
+        Some(RuleSchema { deps, subdeps, conclusion: goal.clone() })
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         use PredicateInference::*;
         use RuleClassification::*;
@@ -970,15 +1345,6 @@ impl RuleT for PredicateInference {
                 Err(Other(format!("No substitution found between {e1} and {e2}.")))
             }
         }
-        fn generalizable_variable_counterexample<P: Proof>(sproof: &P, line: PjRef<P>, var: &str) -> Option<Expr> {
-            let contained = sproof.contained_justifications(true);
-            //println!("gvc contained {:?}", contained.iter().map(|x| sproof.lookup_expr(&x)).collect::<Vec<_>>());
-            let reachable = sproof.transitive_dependencies(line);
-            //println!("gvc reachable {:?}", reachable.iter().map(|x| sproof.lookup_expr(&x)).collect::<Vec<_>>());
-            let outside = reachable.difference(&contained);
-            //println!("gvc outside {:?}", outside.clone().map(|x| sproof.lookup_expr(&x)).collect::<Vec<_>>());
-            outside.filter_map(|x| sproof.lookup_expr(x)).find(|e| crate::expr::free_vars(e).contains(var))
-        }
         match self {
             ForallIntro => {
                 let sproof = p.lookup_subproof_or_die(&sdeps[0])?;
@@ -1042,12 +1408,7 @@ impl RuleT for PredicateInference {
                 let sproof = p.lookup_subproof_or_die(&sdeps[0])?;
                 let skolemname = {
                     if let Expr::Quant { kind: QuantKind::Exists, ref name, ref body } = prem {
-                        let subprems = sproof.premises();
-                        if subprems.len() != 1 {
-                            // TODO: can/should this be generalized?
-                            return Err(Other(format!("Subproof has {} premises, expected 1.", subprems.len())));
-                        }
-                        let subprem = p.lookup_premise_or_die(&subprems[0])?;
+                        let subprem = single_assumption(p, &sdeps[0])?;
                         if let Ok(Expr::Var { name: skolemname }) = unifies_wrt_var::<P>(body, &subprem, name) {
                             skolemname
                         } else {
@@ -1086,6 +1447,25 @@ impl RuleT for BooleanInference {
         }
         .into()
     }
+    fn get_description(&self) -> String {
+        use BooleanInference::*;
+        match self {
+            DisjunctiveSyllogism => "From a disjunction and the negation of one disjunct, conclude the other disjunct.",
+            Exclusion => "From the negation of a conjunction and one conjunct, conclude the negation of the other conjunct.",
+            ExcludedMiddle => "Conclude that any formula is either true or false, without any dependencies.",
+            HalfDeMorgan => "From the negation of a disjunction, conclude the negation of one of the disjuncts.",
+        }
+        .into()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use BooleanInference::*;
+        Some(match self {
+            DisjunctiveSyllogism => RuleSchema::parse(&["P | Q", "~P"], &[], "Q"),
+            Exclusion => RuleSchema::parse(&["~(P & Q)", "P"], &[], "~Q"),
+            ExcludedMiddle => RuleSchema::parse(&[], &[], "P | ~P"),
+            HalfDeMorgan => RuleSchema::parse(&["~(P | Q)"], &[], "~P"),
+        })
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         [RuleClassification::BooleanInference].iter().cloned().collect()
     }
@@ -1221,6 +1601,33 @@ impl RuleT for ConditionalInference {
         }
         .into()
     }
+    fn get_description(&self) -> String {
+        use ConditionalInference::*;
+        match self {
+            ModusTollens => "From a conditional and the negation of its consequent, conclude the negation of its antecedent.",
+            HypotheticalSyllogism => "Chain two conditionals together.",
+            ConstructiveDilemma => "From two conditionals and the disjunction of their antecedents, conclude the disjunction of their consequents.",
+            DestructiveDilemma => "From two conditionals and the disjunction of their negated consequents, conclude the disjunction of their negated antecedents.",
+            StrengthenAntecedent => "Add a conjunct to the antecedent of a conditional.",
+            WeakenConsequent => "Add a disjunct to the consequent of a conditional.",
+            ConIntroNegation => "Conclude a conditional from the negation of its antecedent, or from its consequent.",
+            ConElimNegation => "From a negated conditional, conclude its antecedent or the negation of its consequent.",
+        }
+        .into()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use ConditionalInference::*;
+        Some(match self {
+            ModusTollens => RuleSchema::parse(&["P -> Q", "~Q"], &[], "~P"),
+            HypotheticalSyllogism => RuleSchema::parse(&["P -> Q", "Q -> R"], &[], "P -> R"),
+            ConstructiveDilemma => RuleSchema::parse(&["P -> Q", "R -> S", "P | R"], &[], "Q | S"),
+            DestructiveDilemma => RuleSchema::parse(&["P -> R", "Q -> S", "~R | ~S"], &[], "~P | ~Q"),
+            StrengthenAntecedent => RuleSchema::parse(&["P -> R"], &[], "(P & Q) -> R"),
+            WeakenConsequent => RuleSchema::parse(&["P -> Q"], &[], "P -> (Q | R)"),
+            ConIntroNegation => RuleSchema::parse(&["~P"], &[], "P -> Q"),
+            ConElimNegation => RuleSchema::parse(&["~(P -> Q)"], &[], "P"),
+        })
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         [RuleClassification::ConditionalInference].iter().cloned().collect()
     }
@@ -1514,6 +1921,25 @@ impl RuleT for BiconditionalInference {
         }
         .into()
     }
+    fn get_description(&self) -> String {
+        use BiconditionalInference::*;
+        match self {
+            BiconIntro => "Conclude a biconditional from two lines that are both true, or both negated.",
+            BiconIntroNegation => "Conclude a negated biconditional from a line and the negation of another.",
+            BiconElim => "From a biconditional and the negation of one side, conclude the negation of the other side.",
+            BiconElimNegation => "From a negated biconditional and one side, conclude the negation of the other side.",
+        }
+        .into()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use BiconditionalInference::*;
+        Some(match self {
+            BiconIntro => RuleSchema::parse(&["P", "Q"], &[], "P <-> Q"),
+            BiconIntroNegation => RuleSchema::parse(&["~P", "Q"], &[], "~(P <-> Q)"),
+            BiconElim => RuleSchema::parse(&["P <-> Q", "~P"], &[], "~Q"),
+            BiconElimNegation => RuleSchema::parse(&["~(P <-> Q)", "P"], &[], "~Q"),
+        })
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         [RuleClassification::BiconditionalInference].iter().cloned().collect()
     }
@@ -1542,16 +1968,12 @@ impl RuleT for BiconditionalInference {
                     |i, j| {
                         match (i, j) {
                             // Case 1: Both premises are variables (P and Q)
-                            (Expr::Var { name: ref left }, Expr::Var { name: ref right }) => {
-                                if conclusion == Expr::assoc(Op::Bicon, &[Expr::var(left), Expr::var(right)]) {
-                                    return AnyOrderResult::Ok;
-                                }
+                            (Expr::Var { name: ref left }, Expr::Var { name: ref right }) if conclusion == Expr::assoc(Op::Bicon, &[Expr::var(left), Expr::var(right)]) => {
+                                return AnyOrderResult::Ok;
                             }
                             // Case 2: Both premises are negations (~P and ~Q)
-                            (Expr::Not { operand: ref left }, Expr::Not { operand: ref right }) => {
-                                if conclusion == Expr::assoc(Op::Bicon, &[*left.clone(), *right.clone()]) || conclusion == Expr::assoc(Op::Bicon, &[Expr::Not { operand: left.clone() }, Expr::Not { operand: right.clone() }]) {
-                                    return AnyOrderResult::Ok;
-                                }
+                            (Expr::Not { operand: ref left }, Expr::Not { operand: ref right }) if conclusion == Expr::assoc(Op::Bicon, &[*left.clone(), *right.clone()]) || conclusion == Expr::assoc(Op::Bicon, &[Expr::Not { operand: left.clone() }, Expr::Not { operand: right.clone() }]) => {
+                                return AnyOrderResult::Ok;
                             }
                             _ => {}
                         }
@@ -1571,16 +1993,12 @@ impl RuleT for BiconditionalInference {
                     |i, j| {
                         match (i, j) {
                             // Case 1: Premises: ~P and Q, Conclusion: ~(P <-> Q)
-                            (Expr::Not { operand: ref left }, Expr::Var { name: ref right }) => {
-                                if conclusion == (Expr::Not { operand: Box::new(Expr::assoc(Op::Bicon, &[*left.clone(), Expr::var(right)])) }) {
-                                    return AnyOrderResult::Ok;
-                                }
+                            (Expr::Not { operand: ref left }, Expr::Var { name: ref right }) if conclusion == (Expr::Not { operand: Box::new(Expr::assoc(Op::Bicon, &[*left.clone(), Expr::var(right)])) }) => {
+                                return AnyOrderResult::Ok;
                             }
                             // Case 2: Premises are P and ~Q, Conclusion: ~(P <-> Q)
-                            (Expr::Var { name: ref left }, Expr::Not { operand: ref right }) => {
-                                if conclusion == (Expr::Not { operand: Box::new(Expr::assoc(Op::Bicon, &[Expr::var(left), *right.clone()])) }) {
-                                    return AnyOrderResult::Ok;
-                                }
+                            (Expr::Var { name: ref left }, Expr::Not { operand: ref right }) if conclusion == (Expr::Not { operand: Box::new(Expr::assoc(Op::Bicon, &[Expr::var(left), *right.clone()])) }) => {
+                                return AnyOrderResult::Ok;
                             }
                             _ => {}
                         }
@@ -1666,6 +2084,19 @@ impl RuleT for QuantifierInference {
         }
         .into()
     }
+    fn get_description(&self) -> String {
+        use QuantifierInference::*;
+        match self {
+            QuantInference => "Split an existential over a conjunction, or merge universals under a disjunction.",
+        }
+        .into()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use QuantifierInference::*;
+        Some(match self {
+            QuantInference => RuleSchema::parse(&["exists x (P(x) & Q(x))"], &[], "(exists x P(x)) & (exists x Q(x))"),
+        })
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         [RuleClassification::QuantifierInference].iter().cloned().collect()
     }
@@ -1730,15 +2161,13 @@ where
 {
     let premise = p.lookup_expr_or_die(&deps[0])?;
 
-    let premise_possibilities = normalize_fn(premise);
+    // Interning both sides turns the pairwise comparison of possibly deep expressions into set membership on ids
+    let mut arena = ExprArena::new();
+    let premise_possibilities = normalize_fn(premise).iter().map(|e| arena.intern(e)).collect::<HashSet<_>>();
     let conclusion_possibilities = normalize_fn(conclusion);
 
-    for premise_expr in premise_possibilities.iter() {
-        for conclusion_expr in conclusion_possibilities.iter() {
-            if premise_expr == conclusion_expr {
-                return Ok(());
-            }
-        }
+    if conclusion_possibilities.iter().any(|e| premise_possibilities.contains(&arena.intern(e))) {
+        return Ok(());
     }
 
     Err(ProofCheckError::Other("None of the possible normalized premises match the conclusion.".to_string()))
@@ -1755,6 +2184,7 @@ impl RuleT for BooleanEquivalence {
             DeMorgan => "DeMorgan",
             Association => "Association",
             Commutation => "Commutation",
+            AssociationCommutation => "Association & Commutation",
             Idempotence => "Idempotence",
             Distribution => "Distribution",
             DoubleNegation => "Double Negation",
@@ -1768,6 +2198,45 @@ impl RuleT for BooleanEquivalence {
         }
         .into()
     }
+    fn get_description(&self) -> String {
+        use BooleanEquivalence::*;
+        match self {
+            DeMorgan => "Push a negation into a conjunction or disjunction, swapping the operator.",
+            Association => "Regroup a chain of conjunctions or disjunctions.",
+            Commutation => "Reorder the operands of a conjunction or disjunction.",
+            AssociationCommutation => "Regroup and reorder a chain of conjunctions or disjunctions in one step.",
+            Idempotence => "Remove repeated operands of a conjunction or disjunction.",
+            Distribution => "Distribute a conjunction over a disjunction, or a disjunction over a conjunction.",
+            DoubleNegation => "Remove or add a double negation.",
+            Complement => "A conjunction containing a formula and its negation is a contradiction, and such a disjunction is a tautology.",
+            Identity => "Drop ⊤ from a conjunction, or ⊥ from a disjunction.",
+            Annihilation => "A conjunction with ⊥ is ⊥, and a disjunction with ⊤ is ⊤.",
+            Inverse => "The negation of ⊤ is ⊥, and the negation of ⊥ is ⊤.",
+            Absorption => "Drop a conjunction or disjunction that repeats an operand of the formula around it.",
+            Reduction => "Drop a negated copy of an operand from a disjunction next to it.",
+            Adjacency => "Combine two conjunctions (or disjunctions) that differ only in the sign of one operand.",
+        }
+        .into()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use BooleanEquivalence::*;
+        Some(match self {
+            DeMorgan => RuleSchema::parse(&["~(P & Q)"], &[], "~P | ~Q"),
+            Association => RuleSchema::parse(&["(P & Q) & R"], &[], "P & (Q & R)"),
+            Commutation => RuleSchema::parse(&["P & Q"], &[], "Q & P"),
+            AssociationCommutation => RuleSchema::parse(&["(P & Q) & R"], &[], "R & (Q & P)"),
+            Idempotence => RuleSchema::parse(&["P & P"], &[], "P"),
+            Distribution => RuleSchema::parse(&["P & (Q | R)"], &[], "(P & Q) | (P & R)"),
+            DoubleNegation => RuleSchema::parse(&["~~P"], &[], "P"),
+            Complement => RuleSchema::parse(&["P & ~P"], &[], "_|_"),
+            Identity => RuleSchema::parse(&["P & ^|^"], &[], "P"),
+            Annihilation => RuleSchema::parse(&["P & _|_"], &[], "_|_"),
+            Inverse => RuleSchema::parse(&["~^|^"], &[], "_|_"),
+            Absorption => RuleSchema::parse(&["P | (P & Q)"], &[], "P"),
+            Reduction => RuleSchema::parse(&["P & (~P | Q)"], &[], "P & Q"),
+            Adjacency => RuleSchema::parse(&["(P & Q) | (P & ~Q)"], &[], "P"),
+        })
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         [RuleClassification::BooleanEquivalence].iter().cloned().collect()
     }
@@ -1783,12 +2252,15 @@ impl RuleT for BooleanEquivalence {
             DeMorgan => check_by_normalize_first_expr(p, deps, conclusion, false, |e| e.normalize_demorgans(), "none"),
             Association => check_by_normalize_first_expr(p, deps, conclusion, false, |e| e.combine_associative_ops("bool"), "bool"),
             Commutation => check_by_normalize_first_expr(p, deps, conclusion, false, |e| e.sort_commutative_ops("bool"), "bool"),
+            // Flattening before sorting puts every reassociation and reordering of a chain into the same form
+            AssociationCommutation => check_by_normalize_first_expr(p, deps, conclusion, false, |e| e.normalize_ac(&|op| op != Op::Bicon), "bool"),
             Idempotence => check_by_normalize_first_expr(p, deps, conclusion, true, |e| e.normalize_idempotence(), "none"),
             DoubleNegation => check_by_rewrite_rule_confl(p, deps, conclusion, false, &equivs::DOUBLE_NEGATION, "none"),
             // Distribution and Reduction have outputs containing binops that need commutative sorting
             // because we can't expect people to know the specific order of outputs that our definition
-            // of the rules uses
-            Distribution => check_by_rewrite_rule_confl(p, deps, conclusion, true, &equivs::DISTRIBUTION, "none"),
+            // of the rules uses. Factoring a longer chain nests the chain it leaves behind, so both sides
+            // are flattened too.
+            Distribution => check_by_normalize_first_expr(p, deps, conclusion, true, |e| equivs::DISTRIBUTION.reduce(e).combine_associative_ops("bool"), "none"),
             Complement => check_by_normalize_first_expr(p, deps, conclusion, true, |e| e.normalize_complement(), "none"),
             Identity => check_by_rewrite_rule_confl(p, deps, conclusion, false, &equivs::IDENTITY, "none"),
             Annihilation => check_by_rewrite_rule_confl(p, deps, conclusion, false, &equivs::ANNIHILATION, "none"),
@@ -1798,6 +2270,17 @@ impl RuleT for BooleanEquivalence {
             Adjacency => check_by_normalize_first_expr(p, deps, conclusion, true, |e| e.normalize_adjacency(), "none"),
         }
     }
+    fn rewrite_rule(&self) -> Option<&'static RewriteRule> {
+        use BooleanEquivalence::*;
+        match self {
+            DoubleNegation => Some(&*equivs::DOUBLE_NEGATION),
+            Distribution => Some(&*equivs::DISTRIBUTION),
+            Identity => Some(&*equivs::IDENTITY),
+            Annihilation => Some(&*equivs::ANNIHILATION),
+            Inverse => Some(&*equivs::INVERSE),
+            DeMorgan | Association | Commutation | AssociationCommutation | Idempotence | Complement | Absorption | Reduction | Adjacency => None,
+        }
+    }
 }
 
 impl RuleT for ConditionalEquivalence {
@@ -1817,6 +2300,37 @@ impl RuleT for ConditionalEquivalence {
         }
         .into()
     }
+    fn get_description(&self) -> String {
+        use ConditionalEquivalence::*;
+        match self {
+            Implication => "Rewrite a conditional as a disjunction.",
+            Contraposition => "Swap and negate the antecedent and consequent of a conditional.",
+            Exportation => "Turn nested conditionals into one with a conjunction as the antecedent.",
+            ConditionalDistribution => "Distribute a conditional over a conjunction or disjunction.",
+            ConditionalAbsorption => "Drop a conditional that is made redundant by a conjunct next to it.",
+            ConditionalReduction => "Replace a conditional by its consequent when its antecedent is a conjunct next to it.",
+            ConditionalIdempotence => "A formula that implies its own negation is false.",
+            ConditionalComplement => "A formula always implies itself.",
+            ConditionalIdentity => "A conditional with ⊤ as its antecedent is just its consequent.",
+            ConditionalAnnihilation => "A conditional with ⊤ as its consequent, or ⊥ as its antecedent, is ⊤.",
+        }
+        .into()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use ConditionalEquivalence::*;
+        Some(match self {
+            Implication => RuleSchema::parse(&["P -> Q"], &[], "~P | Q"),
+            Contraposition => RuleSchema::parse(&["P -> Q"], &[], "~Q -> ~P"),
+            Exportation => RuleSchema::parse(&["P -> (Q -> R)"], &[], "(P & Q) -> R"),
+            ConditionalDistribution => RuleSchema::parse(&["P -> (Q & R)"], &[], "(P -> Q) & (P -> R)"),
+            ConditionalAbsorption => RuleSchema::parse(&["P & (~P -> Q)"], &[], "P"),
+            ConditionalReduction => RuleSchema::parse(&["P & (P -> Q)"], &[], "P & Q"),
+            ConditionalIdempotence => RuleSchema::parse(&["P -> ~P"], &[], "~P"),
+            ConditionalComplement => RuleSchema::parse(&["P -> P"], &[], "^|^"),
+            ConditionalIdentity => RuleSchema::parse(&["^|^ -> P"], &[], "P"),
+            ConditionalAnnihilation => RuleSchema::parse(&["P -> ^|^"], &[], "^|^"),
+        })
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         [RuleClassification::ConditionalEquivalence].iter().cloned().collect()
     }
@@ -1841,6 +2355,21 @@ impl RuleT for ConditionalEquivalence {
             ConditionalAnnihilation => check_by_rewrite_rule_confl(p, deps, conclusion, false, &equivs::CONDITIONAL_ANNIHILATION, "none"),
         }
     }
+    fn rewrite_rule(&self) -> Option<&'static RewriteRule> {
+        use ConditionalEquivalence::*;
+        Some(match self {
+            Implication => &*equivs::CONDITIONAL_IMPLICATION,
+            Contraposition => &*equivs::CONDITIONAL_CONTRAPOSITION,
+            Exportation => &*equivs::CONDITIONAL_EXPORTATION,
+            ConditionalDistribution => &*equivs::CONDITIONAL_DISTRIBUTION,
+            ConditionalAbsorption => &*equivs::CONDITIONAL_ABSORPTION,
+            ConditionalReduction => &*equivs::CONDITIONAL_REDUCTION,
+            ConditionalIdempotence => &*equivs::CONDITIONAL_IDEMPOTENCE,
+            ConditionalComplement => &*equivs::CONDITIONAL_COMPLEMENT,
+            ConditionalIdentity => &*equivs::CONDITIONAL_IDENTITY,
+            ConditionalAnnihilation => &*equivs::CONDITIONAL_ANNIHILATION,
+        })
+    }
 }
 
 impl RuleT for BiconditionalEquivalence {
@@ -1851,6 +2380,7 @@ impl RuleT for BiconditionalEquivalence {
             BiconditionalContraposition => "Biconditional Contraposition",
             BiconditionalCommutation => "Biconditional Commutation",
             BiconditionalAssociation => "Biconditional Association",
+            BiconditionalAssociationCommutation => "Biconditional Association & Commutation",
             BiconditionalReduction => "Biconditional Reduction",
             BiconditionalComplement => "Biconditional Complement",
             BiconditionalIdentity => "Biconditional Identity",
@@ -1860,6 +2390,39 @@ impl RuleT for BiconditionalEquivalence {
         }
         .into()
     }
+    fn get_description(&self) -> String {
+        use BiconditionalEquivalence::*;
+        match self {
+            BiEquivalence => "Rewrite a pair of conditionals going both ways as a biconditional.",
+            BiconditionalContraposition => "Negate both sides of a biconditional.",
+            BiconditionalCommutation => "Reorder the sides of a biconditional.",
+            BiconditionalAssociation => "Regroup a chain of biconditionals.",
+            BiconditionalAssociationCommutation => "Regroup and reorder a chain of biconditionals in one step.",
+            BiconditionalReduction => "Replace a biconditional by its other side when one side is a conjunct next to it.",
+            BiconditionalComplement => "A formula is equivalent to itself, and never to its negation.",
+            BiconditionalIdentity => "A biconditional with ⊤ is its other side, and one with ⊥ is the negation of its other side.",
+            BiconditionalNegation => "Move a negation from one side of a biconditional to the whole biconditional.",
+            BiconditionalSubstitution => "Replace one side of a biconditional with the other in a conjunct next to it.",
+            KnightsAndKnaves => "A formula is equivalent to its conjunction with another exactly when it implies the other.",
+        }
+        .into()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use BiconditionalEquivalence::*;
+        Some(match self {
+            BiEquivalence => RuleSchema::parse(&["(P -> Q) & (Q -> P)"], &[], "P <-> Q"),
+            BiconditionalContraposition => RuleSchema::parse(&["P <-> Q"], &[], "~P <-> ~Q"),
+            BiconditionalCommutation => RuleSchema::parse(&["P <-> Q"], &[], "Q <-> P"),
+            BiconditionalAssociation => RuleSchema::parse(&["P <-> (Q <-> R)"], &[], "(P <-> Q) <-> R"),
+            BiconditionalAssociationCommutation => RuleSchema::parse(&["(P <-> Q) <-> R"], &[], "R <-> (Q <-> P)"),
+            BiconditionalReduction => RuleSchema::parse(&["P & (P <-> Q)"], &[], "P & Q"),
+            BiconditionalComplement => RuleSchema::parse(&["P <-> ~P"], &[], "_|_"),
+            BiconditionalIdentity => RuleSchema::parse(&["P <-> ^|^"], &[], "P"),
+            BiconditionalNegation => RuleSchema::parse(&["~P <-> Q"], &[], "~(P <-> Q)"),
+            BiconditionalSubstitution => RuleSchema::parse(&["(P <-> Q) & S(P)"], &[], "(P <-> Q) & S(Q)"),
+            KnightsAndKnaves => RuleSchema::parse(&["P <-> (P & Q)"], &[], "P -> Q"),
+        })
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         [RuleClassification::BiconditionalEquivalence].iter().cloned().collect()
     }
@@ -1876,6 +2439,7 @@ impl RuleT for BiconditionalEquivalence {
             BiconditionalContraposition => check_by_normalize_first_expr(p, deps, conclusion, false, |e| e.normalize_biconditional_contraposition(), "none"),
             BiconditionalCommutation => check_by_normalize_first_expr(p, deps, conclusion, false, |e| e.sort_commutative_ops("bicon"), "bicon"),
             BiconditionalAssociation => check_by_normalize_first_expr(p, deps, conclusion, false, |e| e.combine_associative_ops("bicon"), "bicon"),
+            BiconditionalAssociationCommutation => check_by_normalize_first_expr(p, deps, conclusion, false, |e| e.normalize_ac(&|op| op == Op::Bicon), "bicon"),
             BiconditionalReduction => check_by_rewrite_rule_confl(p, deps, conclusion, true, &equivs::BICONDITIONAL_REDUCTION, "none"),
             BiconditionalComplement => check_by_rewrite_rule_confl(p, deps, conclusion, false, &equivs::BICONDITIONAL_COMPLEMENT, "none"),
             BiconditionalIdentity => check_by_rewrite_rule_confl(p, deps, conclusion, false, &equivs::BICONDITIONAL_IDENTITY, "none"),
@@ -1893,6 +2457,18 @@ impl RuleT for BiconditionalEquivalence {
             KnightsAndKnaves => check_by_rewrite_rule_confl(p, deps, conclusion, true, &equivs::KNIGHTS_AND_KNAVES, "none"),
         }
     }
+    fn rewrite_rule(&self) -> Option<&'static RewriteRule> {
+        use BiconditionalEquivalence::*;
+        match self {
+            BiEquivalence => Some(&*equivs::BICONDITIONAL_EQUIVALENCE),
+            BiconditionalReduction => Some(&*equivs::BICONDITIONAL_REDUCTION),
+            BiconditionalComplement => Some(&*equivs::BICONDITIONAL_COMPLEMENT),
+            BiconditionalIdentity => Some(&*equivs::BICONDITIONAL_IDENTITY),
+            BiconditionalNegation => Some(&*equivs::BICONDITIONAL_NEGATION),
+            KnightsAndKnaves => Some(&*equivs::KNIGHTS_AND_KNAVES),
+            BiconditionalContraposition | BiconditionalCommutation | BiconditionalAssociation | BiconditionalAssociationCommutation | BiconditionalSubstitution => None,
+        }
+    }
 }
 
 /// Perform biconditional substitution in an expression.
@@ -1982,6 +2558,31 @@ impl RuleT for QuantifierEquivalence {
         }
         .into()
     }
+    fn get_description(&self) -> String {
+        use QuantifierEquivalence::*;
+        match self {
+            QuantifierNegation => "Push a negation through a quantifier, switching between ∀ and ∃.",
+            NullQuantification => "Drop a quantifier whose variable doesn't occur in its body.",
+            ReplacingBoundVars => "Rename a bound variable.",
+            SwappingQuantifiers => "Reorder adjacent quantifiers of the same kind.",
+            AristoteleanSquare => "Relate negated universal and existential statements about a conditional or conjunction.",
+            QuantifierDistribution => "Distribute ∀ over a conjunction, or ∃ over a disjunction.",
+            PrenexLaws => "Move a quantifier past a conjunct, disjunct, or side of a conditional that doesn't mention its variable.",
+        }
+        .into()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use QuantifierEquivalence::*;
+        Some(match self {
+            QuantifierNegation => RuleSchema::parse(&["~forall x P(x)"], &[], "exists x ~P(x)"),
+            NullQuantification => RuleSchema::parse(&["forall x P"], &[], "P"),
+            ReplacingBoundVars => RuleSchema::parse(&["forall x P(x)"], &[], "forall y P(y)"),
+            SwappingQuantifiers => RuleSchema::parse(&["forall x forall y P(x, y)"], &[], "forall y forall x P(x, y)"),
+            AristoteleanSquare => RuleSchema::parse(&["~forall x (P(x) -> Q(x))"], &[], "exists x (P(x) & ~Q(x))"),
+            QuantifierDistribution => RuleSchema::parse(&["forall x (P(x) & Q(x))"], &[], "(forall x P(x)) & (forall x Q(x))"),
+            PrenexLaws => RuleSchema::parse(&["forall x (P(x) & Q)"], &[], "(forall x P(x)) & Q"),
+        })
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         [RuleClassification::QuantifierEquivalence].iter().cloned().collect()
     }
@@ -1995,14 +2596,24 @@ impl RuleT for QuantifierEquivalence {
         use QuantifierEquivalence::*;
         match self {
             QuantifierNegation => check_by_normalize_first_expr(p, deps, conclusion, false, Expr::negate_quantifiers, "none"),
-            NullQuantification => check_by_normalize_first_expr(p, deps, conclusion, false, Expr::normalize_null_quantifiers, "none"),
+            NullQuantification => check_by_rewrite_rule_confl(p, deps, conclusion, false, &equivs::NULL_QUANTIFICATION, "none"),
             ReplacingBoundVars => check_by_normalize_first_expr(p, deps, conclusion, false, Expr::replacing_bound_vars, "none"),
-            SwappingQuantifiers => check_by_normalize_first_expr(p, deps, conclusion, false, Expr::swap_quantifiers, "none"),
+            SwappingQuantifiers => check_by_rewrite_rule_confl(p, deps, conclusion, false, &equivs::SWAPPING_QUANTIFIERS, "none"),
             AristoteleanSquare => check_by_normalize_first_expr(p, deps, conclusion, false, Expr::aristotelean_square, "none"),
+            // The normalizer distributes over a chain of any length at once, which the binary rewrite rule can't
             QuantifierDistribution => check_by_normalize_first_expr(p, deps, conclusion, false, Expr::quantifier_distribution, "none"),
             PrenexLaws => check_by_normalize_first_expr(p, deps, conclusion, false, Expr::normalize_prenex_laws, "none"),
         }
     }
+    fn rewrite_rule(&self) -> Option<&'static RewriteRule> {
+        use QuantifierEquivalence::*;
+        match self {
+            NullQuantification => Some(&*equivs::NULL_QUANTIFICATION),
+            SwappingQuantifiers => Some(&*equivs::SWAPPING_QUANTIFIERS),
+            QuantifierDistribution => Some(&*equivs::QUANTIFIER_DISTRIBUTION),
+            QuantifierNegation | ReplacingBoundVars | AristoteleanSquare | PrenexLaws => None,
+        }
+    }
 }
 
 impl RuleT for Special {
@@ -2012,9 +2623,29 @@ impl RuleT for Special {
             Reiteration => "Reiteration",
             Resolution => "Resolution",
             TruthFunctionalConsequence => "Truth-Functional Consequence",
+            FirstOrderConsequence => "First-Order Consequence",
         }
         .into()
     }
+    fn get_description(&self) -> String {
+        use Special::*;
+        match self {
+            Reiteration => "Repeat an earlier line.",
+            Resolution => "From two disjunctions containing a formula and its negation, conclude the disjunction of everything else.",
+            TruthFunctionalConsequence => "Conclude any formula that is true whenever all the dependencies are, judging only by the truth tables of the connectives.",
+            FirstOrderConsequence => "Conclude any formula that is true in every model where all the dependencies are, if instances of them with terms up to 2 functions deep show it.",
+        }
+        .into()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use Special::*;
+        Some(match self {
+            Reiteration => RuleSchema::parse(&["P"], &[], "P"),
+            Resolution => RuleSchema::parse(&["P | Q", "~P | R"], &[], "Q | R"),
+            TruthFunctionalConsequence => RuleSchema::parse(&["P", "P -> Q"], &[], "Q"),
+            FirstOrderConsequence => RuleSchema::parse(&["forall x (P(x) -> Q(x))", "P(a)"], &[], "Q(a)"),
+        })
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         [RuleClassification::Special].iter().cloned().collect()
     }
@@ -2023,13 +2654,13 @@ impl RuleT for Special {
         match self {
             Reiteration => Some(1),
             Resolution => Some(2),
-            TruthFunctionalConsequence => None,
+            TruthFunctionalConsequence | FirstOrderConsequence => None,
         }
     }
     fn num_subdeps(&self) -> Option<usize> {
         use Special::*;
         match self {
-            Reiteration | Resolution | TruthFunctionalConsequence => Some(0),
+            Reiteration | Resolution | TruthFunctionalConsequence | FirstOrderConsequence => Some(0),
         }
     }
     fn check<P: Proof>(self, p: &P, conclusion: Expr, deps: Vec<PjRef<P>>, _sdeps: Vec<P::SubproofReference>) -> Result<(), ProofCheckError<PjRef<P>, P::SubproofReference>> {
@@ -2047,9 +2678,9 @@ impl RuleT for Special {
             Resolution => {
                 let prem0 = p.lookup_expr_or_die(&deps[0])?;
                 let prem1 = p.lookup_expr_or_die(&deps[1])?;
-                let mut premise_disjuncts = HashSet::new();
-                premise_disjuncts.extend(prem0.disjuncts());
-                premise_disjuncts.extend(prem1.disjuncts());
+                let prem0_disjuncts = prem0.disjuncts().into_iter().collect::<HashSet<_>>();
+                let prem1_disjuncts = prem1.disjuncts().into_iter().collect::<HashSet<_>>();
+                let premise_disjuncts = &prem0_disjuncts | &prem1_disjuncts;
                 let conclusion_disjuncts = conclusion.disjuncts().into_iter().collect::<HashSet<_>>();
                 let mut remainder = premise_disjuncts.difference(&conclusion_disjuncts).cloned().collect::<Vec<Expr>>();
                 remainder.sort();
@@ -2061,7 +2692,18 @@ impl RuleT for Special {
 
                 // Ensure remainder forms a contradiction
                 match &remainder[..] {
-                    [e1, e2] => do_expressions_contradict::<P>(e1, e2),
+                    [e1, e2] => {
+                        do_expressions_contradict::<P>(e1, e2)?;
+                        // The contradicting terms are one from each premise, and only they are left out of the
+                        // conclusion: a premise that also has the other one still contributes it
+                        let resolvent = |from0: &Expr, from1: &Expr| prem0_disjuncts.iter().filter(|e| *e != from0).chain(prem1_disjuncts.iter().filter(|e| *e != from1)).cloned().collect::<HashSet<_>>();
+                        let resolves = |from0: &Expr, from1: &Expr| prem0_disjuncts.contains(from0) && prem1_disjuncts.contains(from1) && resolvent(from0, from1) == conclusion_disjuncts;
+                        if resolves(e1, e2) || resolves(e2, e1) {
+                            Ok(())
+                        } else {
+                            Err(ProofCheckError::Other(format!("{e1} and {e2} have to be resolved away, one from each premise, leaving everything else in the conclusion.")))
+                        }
+                    }
                     _ => {
                         let mut pretty_remainder: String = "{".into();
                         for (i, expr) in remainder.iter().enumerate() {
@@ -2076,17 +2718,17 @@ impl RuleT for Special {
                 // Closure for making CNF conversion errors
                 let cnf_error = || ProofCheckError::Other("Failed converting to CNF; the propositions for this rule should not use quantifiers, arithmetic, or application.".to_string());
 
-                // Closure to convert expression into CNF and change to result type
-                let into_cnf = |expr: Expr| expr.into_cnf().ok_or_else(cnf_error);
-
                 // Convert the premises to a single expression by AND-ing them together
                 let premises = deps.into_iter().map(|dep| p.lookup_expr_or_die(&dep)).collect::<Result<Vec<Expr>, _>>()?;
                 let premise = Expr::Assoc { op: Op::And, exprs: premises };
 
                 // Create `varisat` formula of `~(P -> Q)`. If this is
                 // unsatisfiable, then we've proven `P -> Q`.
-                let sat = !(Expr::implies(premise, conclusion));
-                let (sat, vars) = into_cnf(sat)?.to_varisat();
+                let sat = (!(Expr::implies(premise, conclusion))).into_nnf().ok_or_else(cnf_error)?;
+                // Distributing ORs while converting to CNF can take
+                // exponentially many clauses
+                limits::spend(sat.cnf_clause_count())?;
+                let (sat, vars) = sat.into_cnf().to_varisat();
                 let mut solver = varisat::Solver::new();
                 solver.add_formula(&sat);
 
@@ -2115,6 +2757,18 @@ impl RuleT for Special {
                     None => Ok(()),
                 }
             }
+            FirstOrderConsequence => {
+                use crate::herbrand::Consequence;
+                let premises = deps.iter().map(|dep| p.lookup_expr_or_die(dep)).collect::<Result<Vec<Expr>, _>>()?;
+                let config = crate::herbrand::Config::default();
+                match crate::herbrand::consequence(&premises, &conclusion, &config).map_err(ProofCheckError::Other)? {
+                    Consequence::Follows { .. } => Ok(()),
+                    Consequence::DoesNotFollow => Err(ProofCheckError::Other("The conclusion doesn't follow from the dependencies; there's a model where they're true and it's false.".into())),
+                    Consequence::Undecided { depth: Some(depth) } => Err(ProofCheckError::Other(format!("The conclusion couldn't be shown to follow from instances of the dependencies with terms up to {depth} functions deep. It may not follow, or it may need deeper terms; try breaking it into smaller steps."))),
+                    Consequence::Undecided { depth: None } => Err(ProofCheckError::Other("The dependencies have too many instances to check whether the conclusion follows; try breaking it into smaller steps.".into())),
+                    Consequence::TimedOut { .. } => Err(ProofCheckError::TimedOut),
+                }
+            }
         }
     }
 }
@@ -2128,6 +2782,21 @@ impl RuleT for Induction {
         .into()
     }
 
+    fn get_description(&self) -> String {
+        match self {
+            Induction::Weak => "Conclude that a property holds for every natural number from a base case for 0 and a step from each number to its successor.",
+            Induction::Strong => "Conclude that a property holds for every natural number by showing it holds for each number whenever it holds for all smaller ones.",
+        }
+        .into()
+    }
+
+    fn get_schema(&self) -> Option<RuleSchema> {
+        Some(match self {
+            Induction::Weak => RuleSchema::parse(&["P(0)", "forall n (P(n) -> P(s(n)))"], &[], "forall x P(x)"),
+            Induction::Strong => RuleSchema::parse(&["forall n ((forall x (LessThan(x, n) -> P(x))) -> P(n))"], &[], "forall x P(x)"),
+        })
+    }
+
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         [RuleClassification::Induction].iter().cloned().collect()
     }
@@ -2158,8 +2827,7 @@ impl RuleT for Induction {
                     &prem1,
                     &prem2,
                     |base_case, inductive_case| {
-                        let zero = Expr::var("0");
-                        let succ = Expr::var("s");
+                        let zero = Expr::num(0);
 
                         let expected_base_case = crate::expr::subst(property.clone(), quantified_var, zero);
                         if base_case != &expected_base_case {
@@ -2182,8 +2850,11 @@ impl RuleT for Induction {
                         if inductive_premise != &expected_inductive_premise {
                             return AnyOrderResult::Err(ProofCheckError::DepOfWrongForm(inductive_premise.clone(), expected_inductive_premise));
                         }
-                        let expected_inductive_conclusion = crate::expr::subst(property.clone(), quantified_var, Expr::apply(succ, &[Expr::var(induction_var)]));
-                        if inductive_conclusion != &expected_inductive_conclusion {
+                        // The successor of `n` can be written `s(n)` or `n + 1`
+                        let successors = [Expr::apply(Expr::var("s"), &[Expr::var(induction_var)]), Expr::assoc(Op::Add, &[Expr::var(induction_var), Expr::num(1)])];
+                        let expected_inductive_conclusions = successors.map(|succ| crate::expr::subst(property.clone(), quantified_var, succ));
+                        if !expected_inductive_conclusions.contains(inductive_conclusion) {
+                            let [expected_inductive_conclusion, _] = expected_inductive_conclusions;
                             return AnyOrderResult::Err(ProofCheckError::DepOfWrongForm(inductive_conclusion.clone(), expected_inductive_conclusion));
                         }
                         AnyOrderResult::Ok
@@ -2214,8 +2885,9 @@ impl RuleT for Induction {
                 if crate::expr::free_vars(&conclusion).contains(&x) {
                     return Err(ProofCheckError::Other(format!("Variable '{x}' is free in '{conclusion}'")));
                 }
-                let expected_x_lt_n = Expr::apply(Expr::var("LessThan"), &[Expr::var(&x), Expr::var(&n)]);
-                if x_lt_n != expected_x_lt_n {
+                // `x < n` can also be written `LessThan(x, n)`
+                let expected_x_lt_n = Expr::less(Expr::var(&x), Expr::var(&n));
+                if x_lt_n != expected_x_lt_n && x_lt_n != Expr::apply(Expr::var("LessThan"), &[Expr::var(&x), Expr::var(&n)]) {
                     return Err(ProofCheckError::DepOfWrongForm(x_lt_n, expected_x_lt_n));
                 }
                 let expected_property_x = crate::expr::subst(property.clone(), quantified_var, Expr::var(&x));
@@ -2228,7 +2900,6 @@ impl RuleT for Induction {
     }
 }
 
-
 impl RuleT for Reduction {
     fn get_name(&self) -> String {
         use Reduction::*;
@@ -2241,14 +2912,32 @@ impl RuleT for Reduction {
         }
         .into()
     }
+    fn get_description(&self) -> String {
+        use Reduction::*;
+        match self {
+            Conjunction => "Simplify a conjunction with ⊤ or ⊥.",
+            Disjunction => "Simplify a disjunction with ⊤ or ⊥.",
+            Negation => "Simplify the negation of ⊤ or ⊥.",
+            BicondReduction => "Simplify a biconditional with ⊤ or ⊥.",
+            CondReduction => "Simplify a conditional with ⊤ or ⊥.",
+        }
+        .into()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use Reduction::*;
+        Some(match self {
+            Conjunction => RuleSchema::parse(&["P & ^|^"], &[], "P"),
+            Disjunction => RuleSchema::parse(&["P | _|_"], &[], "P"),
+            Negation => RuleSchema::parse(&["~^|^"], &[], "_|_"),
+            BicondReduction => RuleSchema::parse(&["P <-> ^|^"], &[], "P"),
+            CondReduction => RuleSchema::parse(&["^|^ -> P"], &[], "P"),
+        })
+    }
     fn get_classifications(&self) -> HashSet<RuleClassification> {
         [RuleClassification::Reduction].iter().cloned().collect()
     }
     fn num_deps(&self) -> Option<usize> {
-        use Reduction::*;
-        match self {
-            _ => Some(1),
-        }
+        Some(1)
     }
     fn num_subdeps(&self) -> Option<usize> {
         Some(0)
@@ -2264,35 +2953,572 @@ impl RuleT for Reduction {
             CondReduction => check_by_rewrite_rule_confl(p, deps, conclusion, false, &equivs::COND_REDUCTION, "none"),
         }
     }
+    fn rewrite_rule(&self) -> Option<&'static RewriteRule> {
+        use Reduction::*;
+        Some(match self {
+            Conjunction => &*equivs::CONJUNCTION,
+            Disjunction => &*equivs::DISJUNCTION,
+            Negation => &*equivs::INVERSE,
+            BicondReduction => &*equivs::BICOND_REDUCTION,
+            CondReduction => &*equivs::COND_REDUCTION,
+        })
+    }
 }
 
+/// What it means for `element` to be in `set`, if `set` is the empty set, a union, an intersection, or a set builder
+fn membership_definition(element: &Expr, set: &Expr) -> Option<Expr> {
+    if *set == Expr::var(crate::expr::EMPTY_SET) {
+        return Some(Expr::Contra);
+    }
+    match set {
+        Expr::Quant { kind: QuantKind::SetBuilder, name, body } => Some(crate::expr::subst(*body.clone(), name, element.clone())),
+        _ => match set.as_infix() {
+            Some((crate::expr::UNION, left, right)) => Some(Expr::assoc(Op::Or, &[Expr::element(element.clone(), left.clone()), Expr::element(element.clone(), right.clone())])),
+            Some((crate::expr::INTERSECTION, left, right)) => Some(Expr::assoc(Op::And, &[Expr::element(element.clone(), left.clone()), Expr::element(element.clone(), right.clone())])),
+            _ => None,
+        },
+    }
+}
 
-impl RuleT for EmptyRule {
+impl RuleT for SetTheory {
     fn get_name(&self) -> String {
-        "Rule".to_string()
+        use SetTheory::*;
+        match self {
+            ElementIntro => "∈ Introduction",
+            ElementElim => "∈ Elimination",
+            SubsetIntro => "⊆ Introduction",
+            SubsetElim => "⊆ Elimination",
+            Extensionality => "Extensionality",
+        }
+        .into()
+    }
+
+    fn get_description(&self) -> String {
+        use SetTheory::*;
+        match self {
+            ElementIntro => "Conclude that something is in a union, intersection, or set builder from what being in it means, or that it's in a union from being in either set.",
+            ElementElim => "Conclude what being in the empty set, a union, an intersection, or a set builder means from something being in it, or that it's in either set of an intersection.",
+            SubsetIntro => "Conclude that one set is a subset of another from a subproof showing that an arbitrary element of the first is in the second.",
+            SubsetElim => "Conclude that an element of a set is in each set it's a subset of.",
+            Extensionality => "Conclude that two sets are equal from each being a subset of the other.",
+        }
+        .into()
     }
+
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use SetTheory::*;
+        Some(match self {
+            ElementIntro => RuleSchema::parse(&["P(a)"], &[], "a ∈ {x : P(x)}"),
+            ElementElim => RuleSchema::parse(&["a ∈ A ∩ B"], &[], "a ∈ A & a ∈ B"),
+            SubsetIntro => RuleSchema::parse(&[], &[(Some("a ∈ A"), "a ∈ B")], "A ⊆ B"),
+            SubsetElim => RuleSchema::parse(&["A ⊆ B", "a ∈ A"], &[], "a ∈ B"),
+            Extensionality => RuleSchema::parse(&["A ⊆ B", "B ⊆ A"], &[], "A = B"),
+        })
+    }
+
     fn get_classifications(&self) -> HashSet<RuleClassification> {
-        HashSet::new()
+        [RuleClassification::SetTheory].iter().cloned().collect()
     }
+
     fn num_deps(&self) -> Option<usize> {
-        None
+        use SetTheory::*;
+        match self {
+            ElementIntro | ElementElim => Some(1),
+            SubsetIntro => Some(0),
+            SubsetElim | Extensionality => Some(2),
+        }
     }
+
     fn num_subdeps(&self) -> Option<usize> {
-        None
-    }
-    fn check<P: Proof>(self, _: &P, _: Expr, _: Vec<PjRef<P>>, _: Vec<P::SubproofReference>) -> Result<(), ProofCheckError<PjRef<P>, P::SubproofReference>> {
-        Err(ProofCheckError::Other("No rule selected".to_string()))
+        use SetTheory::*;
+        match self {
+            SubsetIntro => Some(1),
+            ElementIntro | ElementElim | SubsetElim | Extensionality => Some(0),
+        }
     }
-}
 
-/// Helper type for `any_order()`. The `check_func` parameter of `any_order()`
-/// returns this.
-enum AnyOrderResult<R, S> {
-    /// The rule checked successfully
-    Ok,
-
-    /// The rule check failed with an error
-    Err(ProofCheckError<R, S>),
+    fn check<P: Proof>(self, p: &P, conclusion: Expr, deps: Vec<PjRef<P>>, sdeps: Vec<P::SubproofReference>) -> Result<(), ProofCheckError<PjRef<P>, P::SubproofReference>> {
+        use ProofCheckError::*;
+        use SetTheory::*;
+        let element_place_holder = || Expr::element(Expr::var("_"), Expr::var("_"));
+        let subset_place_holder = || Expr::subset(Expr::var("_"), Expr::var("_"));
+        match self {
+            ElementIntro => {
+                let prem = p.lookup_expr_or_die(&deps[0])?;
+                let (element, set) = conclusion.as_element().ok_or_else(|| ConclusionOfWrongForm(element_place_holder()))?;
+                let definition = membership_definition(element, set).ok_or_else(|| Other(format!("{set} isn't a union, intersection, or set builder.")))?;
+                // Being in either set of a union is enough
+                let in_either = set.as_infix().filter(|(symbol, _, _)| *symbol == crate::expr::UNION).is_some_and(|(_, left, right)| [left, right].iter().any(|side| prem == Expr::element(element.clone(), (*side).clone())));
+                if prem == definition || in_either {
+                    Ok(())
+                } else {
+                    Err(DepOfWrongForm(prem, definition))
+                }
+            }
+            ElementElim => {
+                let prem = p.lookup_expr_or_die(&deps[0])?;
+                let (element, set) = prem.as_element().ok_or_else(|| DepOfWrongForm(prem.clone(), element_place_holder()))?;
+                let definition = membership_definition(element, set).ok_or_else(|| Other(format!("{set} isn't the empty set, a union, an intersection, or a set builder.")))?;
+                // Being in an intersection is enough to be in either set
+                let in_either = set.as_infix().filter(|(symbol, _, _)| *symbol == crate::expr::INTERSECTION).is_some_and(|(_, left, right)| [left, right].iter().any(|side| conclusion == Expr::element(element.clone(), (*side).clone())));
+                if conclusion == definition || in_either {
+                    Ok(())
+                } else {
+                    Err(ConclusionOfWrongForm(definition))
+                }
+            }
+            SubsetIntro => {
+                let (left, right) = conclusion.as_subset().ok_or_else(|| ConclusionOfWrongForm(subset_place_holder()))?;
+                let sproof = p.lookup_subproof_or_die(&sdeps[0])?;
+                let premise = single_assumption(p, &sdeps[0])?;
+                let element = match premise.as_element() {
+                    Some((element @ Expr::Var { .. }, set)) if set == left => element.clone(),
+                    _ => return Err(DepOfWrongForm(premise, Expr::element(Expr::var("_"), left.clone()))),
+                };
+                let Expr::Var { name } = &element else { unreachable!() };
+                if crate::expr::free_vars(&conclusion).contains(name) {
+                    return Err(Other(format!("The element {name} isn't arbitrary, since it occurs in the conclusion {conclusion}.")));
+                }
+                let expected = Expr::element(element.clone(), right.clone());
+                for r in sproof.exprs() {
+                    if sproof.lookup_expr_or_die(&r)? == expected {
+                        if let Some(dangling) = generalizable_variable_counterexample(&sproof, r, name) {
+                            return Err(Other(format!("The element {name} occurs in dependency {dangling} that's outside the subproof.")));
+                        }
+                        return Ok(());
+                    }
+                }
+                Err(Other(format!("Couldn't find a subproof line equal to {expected}.")))
+            }
+            SubsetElim => {
+                let prem1 = p.lookup_expr_or_die(&deps[0])?;
+                let prem2 = p.lookup_expr_or_die(&deps[1])?;
+                either_order(
+                    &prem1,
+                    &prem2,
+                    |subset, membership| {
+                        let (Some((left, right)), Some((element, set))) = (subset.as_subset(), membership.as_element()) else {
+                            return AnyOrderResult::WrongOrder;
+                        };
+                        if set != left {
+                            return AnyOrderResult::Err(DepOfWrongForm(membership.clone(), Expr::element(Expr::var("_"), left.clone())));
+                        }
+                        let expected = Expr::element(element.clone(), right.clone());
+                        if conclusion != expected {
+                            return AnyOrderResult::Err(ConclusionOfWrongForm(expected));
+                        }
+                        AnyOrderResult::Ok
+                    },
+                    || DepDoesNotExist(subset_place_holder(), true),
+                )
+            }
+            Extensionality => {
+                let (left, right) = match conclusion.as_infix() {
+                    Some((crate::expr::EQUALS, left, right)) => (left, right),
+                    _ => return Err(ConclusionOfWrongForm(Expr::equals(Expr::var("_"), Expr::var("_")))),
+                };
+                let prem1 = p.lookup_expr_or_die(&deps[0])?;
+                let prem2 = p.lookup_expr_or_die(&deps[1])?;
+                let expected = (Expr::subset(left.clone(), right.clone()), Expr::subset(right.clone(), left.clone()));
+                if (prem1 == expected.0 && prem2 == expected.1) || (prem1 == expected.1 && prem2 == expected.0) {
+                    Ok(())
+                } else if [&prem1, &prem2].contains(&&expected.0) {
+                    Err(DepDoesNotExist(expected.1, false))
+                } else {
+                    Err(DepDoesNotExist(expected.0, false))
+                }
+            }
+        }
+    }
+}
+
+/// The formula with the negation moved across its outermost temporal operator, by `¬G φ ≡ F ¬φ`, `¬F φ ≡ G ¬φ`, and
+/// `¬X φ ≡ X ¬φ`, if it's a negated temporal operator or a temporal operator applied to a negation
+fn temporal_dual(e: &Expr) -> Option<Expr> {
+    let dual = |name: &str| match name {
+        crate::expr::ALWAYS => Some(crate::expr::EVENTUALLY),
+        crate::expr::EVENTUALLY => Some(crate::expr::ALWAYS),
+        crate::expr::NEXT => Some(crate::expr::NEXT),
+        _ => None,
+    };
+    let as_prefix = |e: &Expr| match e {
+        Expr::Apply { func, args } => match (&**func, args.as_slice()) {
+            (Expr::Var { name }, [operand]) => Some((dual(name)?, operand.clone())),
+            _ => None,
+        },
+        _ => None,
+    };
+    match e {
+        Expr::Not { operand } => as_prefix(operand).map(|(dual, operand)| Expr::apply(Expr::var(dual), &[!operand])),
+        _ => match as_prefix(e)? {
+            (dual, Expr::Not { operand }) => Some(!Expr::apply(Expr::var(dual), &[*operand])),
+            _ => None,
+        },
+    }
+}
+
+impl RuleT for Temporal {
+    fn get_name(&self) -> String {
+        use Temporal::*;
+        match self {
+            AlwaysElim => "G Elimination",
+            EventuallyIntro => "F Introduction",
+            UntilIntro => "U Introduction",
+            UntilElim => "U Elimination",
+            TemporalNegation => "Temporal Negation",
+        }
+        .into()
+    }
+
+    fn get_description(&self) -> String {
+        use Temporal::*;
+        match self {
+            AlwaysElim => "Conclude that something is true now from it always being true.",
+            EventuallyIntro => "Conclude that something is eventually true from it being true now.",
+            UntilIntro => "Conclude that anything holds until something that's true now.",
+            UntilElim => "Conclude that what something holds until is eventually true.",
+            TemporalNegation => "Move a negation across G, F, or X, which swaps G and F.",
+        }
+        .into()
+    }
+
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use Temporal::*;
+        Some(match self {
+            AlwaysElim => RuleSchema::parse(&["G(P)"], &[], "P"),
+            EventuallyIntro => RuleSchema::parse(&["P"], &[], "F(P)"),
+            UntilIntro => RuleSchema::parse(&["Q"], &[], "U(P, Q)"),
+            UntilElim => RuleSchema::parse(&["U(P, Q)"], &[], "F(Q)"),
+            TemporalNegation => RuleSchema::parse(&["~G(P)"], &[], "F(~P)"),
+        })
+    }
+
+    fn get_classifications(&self) -> HashSet<RuleClassification> {
+        [RuleClassification::Temporal].iter().cloned().collect()
+    }
+
+    fn num_deps(&self) -> Option<usize> {
+        Some(1)
+    }
+
+    fn num_subdeps(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn check<P: Proof>(self, p: &P, conclusion: Expr, deps: Vec<PjRef<P>>, _sdeps: Vec<P::SubproofReference>) -> Result<(), ProofCheckError<PjRef<P>, P::SubproofReference>> {
+        use ProofCheckError::*;
+        use Temporal::*;
+        // Outside temporal logic, G, F, and U are just names
+        if logic_mode() != LogicMode::Temporal {
+            return Err(Other(format!("{} can only be used in temporal logic.", self.get_name())));
+        }
+        match self {
+            AlwaysElim => {
+                let prem = p.lookup_expr_or_die(&deps[0])?;
+                let expected = Expr::always(conclusion);
+                if prem == expected {
+                    Ok(())
+                } else {
+                    Err(DepOfWrongForm(prem, expected))
+                }
+            }
+            EventuallyIntro => {
+                let expected = Expr::eventually(p.lookup_expr_or_die(&deps[0])?);
+                if conclusion == expected {
+                    Ok(())
+                } else {
+                    Err(ConclusionOfWrongForm(expected))
+                }
+            }
+            UntilIntro => {
+                let prem = p.lookup_expr_or_die(&deps[0])?;
+                match conclusion.as_until() {
+                    Some((_, right)) if *right == prem => Ok(()),
+                    _ => Err(ConclusionOfWrongForm(Expr::until(Expr::var("_"), prem))),
+                }
+            }
+            UntilElim => {
+                let prem = p.lookup_expr_or_die(&deps[0])?;
+                let (_, right) = prem.as_until().ok_or_else(|| DepOfWrongForm(prem.clone(), Expr::until(Expr::var("_"), Expr::var("_"))))?;
+                let expected = Expr::eventually(right.clone());
+                if conclusion == expected {
+                    Ok(())
+                } else {
+                    Err(ConclusionOfWrongForm(expected))
+                }
+            }
+            TemporalNegation => {
+                let prem = p.lookup_expr_or_die(&deps[0])?;
+                if temporal_dual(&prem).as_ref() == Some(&conclusion) || temporal_dual(&conclusion).as_ref() == Some(&prem) {
+                    return Ok(());
+                }
+                match temporal_dual(&prem) {
+                    Some(expected) => Err(ConclusionOfWrongForm(expected)),
+                    None => Err(DepOfWrongForm(prem, !Expr::always(Expr::var("_")))),
+                }
+            }
+        }
+    }
+}
+
+/// Whether `e` is the conjunction of `conjunct` with `base`, in any order, and with or without `⊤`
+fn conjoins(e: &Expr, base: &Expr, conjunct: &Expr) -> bool {
+    let mut expected = base.conjuncts();
+    expected.push(conjunct.clone());
+    let set = |conjuncts: Vec<Expr>| conjuncts.into_iter().filter(|e| *e != Expr::Taut).collect::<HashSet<_>>();
+    set(e.conjuncts()) == set(expected)
+}
+
+impl RuleT for Hoare {
+    fn get_name(&self) -> String {
+        use Hoare::*;
+        match self {
+            Skip => "Skip",
+            Assignment => "Assignment",
+            Sequence => "Composition",
+            Conditional => "Conditional",
+            While => "While",
+            Consequence => "Consequence",
+        }
+        .into()
+    }
+
+    fn get_description(&self) -> String {
+        use Hoare::*;
+        match self {
+            Skip => "Conclude that skip keeps any condition true.",
+            Assignment => "Conclude that assigning a term to a variable makes a condition true, if it was true of the term before.",
+            Sequence => "Conclude a triple about two programs run one after the other, from triples about each where the first ends how the second starts.",
+            Conditional => "Conclude a triple about a conditional from triples about each branch, starting from the precondition and whether the condition is true.",
+            While => "Conclude that a loop keeps an invariant true, and ends with its condition false, from its body keeping the invariant true while the condition is.",
+            Consequence => "Strengthen the precondition or weaken the postcondition of a triple, given implications between the old and new conditions.",
+        }
+        .into()
+    }
+
+    fn get_schema(&self) -> Option<RuleSchema> {
+        use Hoare::*;
+        Some(match self {
+            Skip => RuleSchema::parse(&[], &[], "hoare(P, skip, P)"),
+            Assignment => RuleSchema::parse(&[], &[], "hoare(P(e), assign(x, e), P(x))"),
+            Sequence => RuleSchema::parse(&["hoare(P, S, R)", "hoare(R, T, Q)"], &[], "hoare(P, seq(S, T), Q)"),
+            Conditional => RuleSchema::parse(&["hoare(P & B, S, Q)", "hoare(P & ~B, T, Q)"], &[], "hoare(P, if(B, S, T), Q)"),
+            While => RuleSchema::parse(&["hoare(I & B, S, I)"], &[], "hoare(I, while(B, S), I & ~B)"),
+            Consequence => RuleSchema::parse(&["P -> R", "hoare(R, S, T)", "T -> Q"], &[], "hoare(P, S, Q)"),
+        })
+    }
+
+    fn get_classifications(&self) -> HashSet<RuleClassification> {
+        [RuleClassification::Hoare].iter().cloned().collect()
+    }
+
+    fn num_deps(&self) -> Option<usize> {
+        use Hoare::*;
+        match self {
+            Skip | Assignment => Some(0),
+            While => Some(1),
+            Sequence | Conditional => Some(2),
+            // The implications aren't needed for conditions that stay the same
+            Consequence => None,
+        }
+    }
+
+    fn num_subdeps(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn check<P: Proof>(self, p: &P, conclusion: Expr, deps: Vec<PjRef<P>>, _sdeps: Vec<P::SubproofReference>) -> Result<(), ProofCheckError<PjRef<P>, P::SubproofReference>> {
+        use crate::expr::Statement;
+        use Hoare::*;
+        use ProofCheckError::*;
+        // Outside proofs about programs, hoare is just a name
+        if logic_mode() != LogicMode::Program {
+            return Err(Other(format!("{} can only be used in proofs about programs.", self.get_name())));
+        }
+        let triple_place_holder = || Expr::triple(Expr::var("_"), Expr::var("_"), Expr::var("_"));
+        let (pre, program, post) = conclusion.as_triple().ok_or_else(|| ConclusionOfWrongForm(triple_place_holder()))?;
+        let dep_triple = |dep: &PjRef<P>| {
+            let dep = p.lookup_expr_or_die(dep)?;
+            match dep.as_triple() {
+                Some((pre, program, post)) => Ok((pre.clone(), program.clone(), post.clone())),
+                None => Err(DepOfWrongForm(dep, triple_place_holder())),
+            }
+        };
+        match self {
+            Skip => {
+                let expected = Expr::triple(pre.clone(), Expr::skip(), pre.clone());
+                if conclusion == expected {
+                    Ok(())
+                } else {
+                    Err(ConclusionOfWrongForm(expected))
+                }
+            }
+            Assignment => {
+                let Some(Statement::Assign(name, value)) = program.as_statement() else {
+                    return Err(ConclusionOfWrongForm(Expr::triple(Expr::var("_"), Expr::assign("_", Expr::var("_")), post.clone())));
+                };
+                let expected = crate::expr::subst(post.clone(), name, value.clone());
+                if *pre == expected {
+                    Ok(())
+                } else {
+                    Err(ConclusionOfWrongForm(Expr::triple(expected, program.clone(), post.clone())))
+                }
+            }
+            Sequence => {
+                let (first, second) = (dep_triple(&deps[0])?, dep_triple(&deps[1])?);
+                let composes = |(pre1, program1, post1): &(Expr, Expr, Expr), (pre2, program2, post2): &(Expr, Expr, Expr)| post1 == pre2 && pre1 == pre && post2 == post && program1.statements().into_iter().chain(program2.statements()).eq(program.statements());
+                if composes(&first, &second) || composes(&second, &first) {
+                    return Ok(());
+                }
+                // Suggest the order in which the triples meet in the middle
+                let ((pre1, program1, _), (_, program2, post2)) = if second.2 == first.0 { (second, first) } else { (first, second) };
+                Err(ConclusionOfWrongForm(Expr::triple(pre1, Expr::sequence(program1, program2), post2)))
+            }
+            Conditional => {
+                let Some(Statement::If(cond, then, otherwise)) = program.as_statement() else {
+                    return Err(ConclusionOfWrongForm(Expr::triple(pre.clone(), Expr::if_then_else(Expr::var("_"), Expr::var("_"), Expr::var("_")), post.clone())));
+                };
+                let branches = [dep_triple(&deps[0])?, dep_triple(&deps[1])?];
+                for (branch, condition) in [(then, cond.clone()), (otherwise, !cond.clone())] {
+                    if !branches.iter().any(|(dep_pre, dep_program, dep_post)| dep_program == branch && dep_post == post && conjoins(dep_pre, pre, &condition)) {
+                        return Err(DepDoesNotExist(Expr::triple(Expr::assoc(Op::And, &[pre.clone(), condition]), branch.clone(), post.clone()), false));
+                    }
+                }
+                Ok(())
+            }
+            While => {
+                let Some(Statement::While(cond, body)) = program.as_statement() else {
+                    return Err(ConclusionOfWrongForm(Expr::triple(pre.clone(), Expr::while_loop(Expr::var("_"), Expr::var("_")), post.clone())));
+                };
+                if !conjoins(post, pre, &!cond.clone()) {
+                    return Err(ConclusionOfWrongForm(Expr::triple(pre.clone(), program.clone(), Expr::assoc(Op::And, &[pre.clone(), !cond.clone()]))));
+                }
+                let (dep_pre, dep_program, dep_post) = dep_triple(&deps[0])?;
+                if dep_program == *body && dep_post == *pre && conjoins(&dep_pre, pre, cond) {
+                    Ok(())
+                } else {
+                    Err(DepOfWrongForm(Expr::triple(dep_pre, dep_program, dep_post), Expr::triple(Expr::assoc(Op::And, &[pre.clone(), cond.clone()]), body.clone(), pre.clone())))
+                }
+            }
+            Consequence => {
+                let deps = deps.iter().map(|dep| p.lookup_expr_or_die(dep)).collect::<Result<Vec<Expr>, _>>()?;
+                let triples = deps.iter().filter_map(Expr::as_triple).collect::<Vec<_>>();
+                let (dep_pre, dep_program, dep_post) = match triples.as_slice() {
+                    [triple] => *triple,
+                    [] => return Err(DepDoesNotExist(Expr::triple(Expr::var("_"), program.clone(), Expr::var("_")), true)),
+                    _ => return Err(Other("Only one of the dependencies can be a triple.".into())),
+                };
+                if dep_program != program {
+                    return Err(ConclusionOfWrongForm(Expr::triple(pre.clone(), dep_program.clone(), post.clone())));
+                }
+                // The implications for conditions that stay the same can be left out
+                let needed = [(pre, dep_pre), (dep_post, post)].into_iter().filter(|(from, to)| from != to).map(|(from, to)| Expr::implies(from.clone(), to.clone())).collect::<Vec<_>>();
+                if let Some(missing) = needed.iter().find(|implication| !deps.contains(implication)) {
+                    return Err(DepDoesNotExist(missing.clone(), false));
+                }
+                match deps.iter().find(|dep| dep.as_triple().is_none() && !needed.contains(dep)) {
+                    Some(unused) => Err(Other(format!("{unused} isn't one of the implications between the conditions of the triples."))),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl RuleT for EmptyRule {
+    fn get_name(&self) -> String {
+        "Rule".to_string()
+    }
+    fn get_description(&self) -> String {
+        "No rule has been chosen for this step yet.".to_string()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        None
+    }
+    fn get_classifications(&self) -> HashSet<RuleClassification> {
+        HashSet::new()
+    }
+    fn num_deps(&self) -> Option<usize> {
+        None
+    }
+    fn num_subdeps(&self) -> Option<usize> {
+        None
+    }
+    fn check<P: Proof>(self, _: &P, _: Expr, _: Vec<PjRef<P>>, _: Vec<P::SubproofReference>) -> Result<(), ProofCheckError<PjRef<P>, P::SubproofReference>> {
+        Err(ProofCheckError::Other("No rule selected".to_string()))
+    }
+}
+
+impl RuleT for Gap {
+    fn get_name(&self) -> String {
+        "Gap".to_string()
+    }
+    fn get_description(&self) -> String {
+        "Admit this step without proof for now, to come back to it later. A proof with gaps isn't finished.".to_string()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        None
+    }
+    fn get_classifications(&self) -> HashSet<RuleClassification> {
+        HashSet::new()
+    }
+    fn num_deps(&self) -> Option<usize> {
+        None
+    }
+    fn num_subdeps(&self) -> Option<usize> {
+        None
+    }
+    fn check<P: Proof>(self, _: &P, _: Expr, _: Vec<PjRef<P>>, _: Vec<P::SubproofReference>) -> Result<(), ProofCheckError<PjRef<P>, P::SubproofReference>> {
+        Err(ProofCheckError::Gap)
+    }
+}
+
+impl From<PluginRule> for Rule {
+    fn from(rule: PluginRule) -> Rule {
+        SharedChecks(Coproduct::inject(rule))
+    }
+}
+
+impl RuleT for PluginRule {
+    fn get_name(&self) -> String {
+        plugins::plugin(*self).name()
+    }
+    fn get_description(&self) -> String {
+        plugins::plugin(*self).description()
+    }
+    fn get_schema(&self) -> Option<RuleSchema> {
+        plugins::plugin(*self).schema()
+    }
+    fn get_classifications(&self) -> HashSet<RuleClassification> {
+        plugins::plugin(*self).classifications()
+    }
+    fn num_deps(&self) -> Option<usize> {
+        plugins::plugin(*self).num_deps()
+    }
+    fn num_subdeps(&self) -> Option<usize> {
+        plugins::plugin(*self).num_subdeps()
+    }
+    fn check<P: Proof>(self, p: &P, conclusion: Expr, deps: Vec<PjRef<P>>, sdeps: Vec<P::SubproofReference>) -> Result<(), ProofCheckError<PjRef<P>, P::SubproofReference>> {
+        let deps = deps.iter().map(|dep| p.lookup_expr_or_die(dep)).collect::<Result<Vec<_>, _>>()?;
+        let mut subproofs = vec![];
+        for sdep in sdeps.iter() {
+            let sproof = p.lookup_subproof_or_die(sdep)?;
+            let premises = sproof.premises().into_iter().map(|r| p.lookup_premise_or_die(&r)).collect::<Result<Vec<_>, _>>()?;
+            let lines = sproof.direct_lines().into_iter().map(|r| p.lookup_expr_or_die(&Coproduct::inject(r))).collect::<Result<Vec<_>, _>>()?;
+            subproofs.push(PluginSubproof { premises, lines });
+        }
+        plugins::plugin(self).check(&conclusion, &deps, &subproofs).map_err(ProofCheckError::Other)
+    }
+}
+
+/// Helper type for `any_order()`. The `check_func` parameter of `any_order()`
+/// returns this.
+enum AnyOrderResult<R, S> {
+    /// The rule checked successfully
+    Ok,
+
+    /// The rule check failed with an error
+    Err(ProofCheckError<R, S>),
 
     /// The rule check reports that the dependencies are probably in the wrong
     /// order
@@ -2305,10 +3531,9 @@ enum AnyOrderResult<R, S> {
 /// ## Parameters
 ///   * `deps` - the dependencies to check
 ///   * `check_func` - function checking a rule and assuming a given ordering of
-///                    the dependencies
+///     the dependencies
 ///   * `fallthrough_handler` - function to obtain an error that occurs when all
-///                             orderings have dependencies that are in the
-///                             wrong form.
+///     orderings have dependencies that are in the wrong form.
 ///
 /// ## `check_func`
 ///
@@ -2332,6 +3557,9 @@ where
     F: Fn(&[&Expr]) -> AnyOrderResult<R, S>,
     E: FnOnce() -> ProofCheckError<R, S>,
 {
+    // There are n! orders to try
+    limits::spend((1..=deps.len() as u64).fold(1, u64::saturating_mul))?;
+
     // Iterator over the check results of all the permutations that weren't
     // `AnyOrderResult::WrongOrder`
     let mut results = deps.iter().permutations(deps.len()).map(|deps| check_func(&deps)).filter_map(|result: AnyOrderResult<R, S>| match result {
@@ -2388,6 +3616,14 @@ pub enum ProofCheckError<R, S> {
     IncorrectDepCount(Vec<R>, usize),
     /// The wrong number of subproof dependencies were provided for a rule
     IncorrectSubDepCount(Vec<S>, usize),
+    /// A premise of the whole proof was cited where the rule needs a subproof, whose assumption it discharges
+    PremiseCitedAsAssumption(R),
+    /// A cited subproof has `.1` assumptions, where the rule needs one, or one or more if
+    /// `proofs::assumption_policy()` allows several
+    IncorrectAssumptionCount(S, usize),
+    /// The step would be correct, but cites these lines from outside its subproof, which
+    /// `proofs::scoping_policy()` requires to be reiterated into it first
+    NeedsReiteration(Vec<R>),
     /// A dependency `.0` was of the wrong form, and a placeholder `.1` was expected
     DepOfWrongForm(Expr, Expr),
     /// The conclusion of a rule was different from what was expected
@@ -2398,19 +3634,107 @@ pub enum ProofCheckError<R, S> {
     DepDoesNotExist(Expr, bool),
     /// Multiple errors apply
     OneOf(BTreeSet<ProofCheckError<R, S>>),
+    /// Checking took longer than `limits::limits()` allows
+    TimedOut,
+    /// The step is a `Gap`, admitted without proof
+    Gap,
     /// Escape hatch for custom errors
     Other(String),
 }
 
-impl<R: std::fmt::Debug, S: std::fmt::Debug> std::fmt::Display for ProofCheckError<R, S> {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl<R, S> From<limits::TimedOut> for ProofCheckError<R, S> {
+    fn from(_: limits::TimedOut) -> Self {
+        ProofCheckError::TimedOut
+    }
+}
+
+impl<R, S> ProofCheckError<R, S> {
+    /// Short, stable name of the kind of error, like `"dep-of-wrong-form"`,
+    /// for reporting errors without the proof's formulas or line references
+    pub fn code(&self) -> &'static str {
+        use ProofCheckError::*;
+        match self {
+            LineDoesNotExist(_) => "line-does-not-exist",
+            SubproofDoesNotExist(_) => "subproof-does-not-exist",
+            ReferencesLaterLine(_, _) => "references-later-line",
+            IncorrectDepCount(_, _) => "incorrect-dep-count",
+            IncorrectSubDepCount(_, _) => "incorrect-sub-dep-count",
+            PremiseCitedAsAssumption(_) => "premise-cited-as-assumption",
+            IncorrectAssumptionCount(_, _) => "incorrect-assumption-count",
+            NeedsReiteration(_) => "needs-reiteration",
+            DepOfWrongForm(_, _) => "dep-of-wrong-form",
+            ConclusionOfWrongForm(_) => "conclusion-of-wrong-form",
+            DoesNotOccur(_, _) => "does-not-occur",
+            DepDoesNotExist(_, _) => "dep-does-not-exist",
+            OneOf(_) => "one-of",
+            TimedOut => "timed-out",
+            Gap => "gap",
+            Other(_) => "other",
+        }
+    }
+}
+
+impl<R: std::fmt::Debug, S: std::fmt::Debug> ProofCheckError<R, S> {
+    /// Describe the error, writing the lines and subproofs it refers to with `line_number` and `subproof_lines`, like
+    /// `line 3` and `lines 3-5`. The ones they don't number are written the way `Display` writes them.
+    pub fn message(&self, line_number: &dyn Fn(&R) -> Option<usize>, subproof_lines: &dyn Fn(&S) -> Option<(usize, usize)>) -> String {
+        struct Message<'a, R, S>(&'a ProofCheckError<R, S>, &'a dyn Fn(&R) -> Option<usize>, &'a dyn Fn(&S) -> Option<(usize, usize)>);
+        impl<R: std::fmt::Debug, S: std::fmt::Debug> std::fmt::Display for Message<'_, R, S> {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                self.0.write_message(f, self.1, self.2)
+            }
+        }
+        Message(self, line_number, subproof_lines).to_string()
+    }
+
+    fn write_message(&self, f: &mut std::fmt::Formatter, line_number: &dyn Fn(&R) -> Option<usize>, subproof_lines: &dyn Fn(&S) -> Option<(usize, usize)>) -> std::fmt::Result {
         use ProofCheckError::*;
         match self {
-            LineDoesNotExist(r) => write!(f, "The referenced line {r:?} does not exist."),
-            SubproofDoesNotExist(s) => write!(f, "The referenced subproof {s:?} does not exist."),
-            ReferencesLaterLine(line, dep) => write!(f, "The dependency {dep:?} is after the step that uses it ({line:?})."),
+            LineDoesNotExist(r) => match line_number(r) {
+                Some(line) => write!(f, "The referenced line {line} does not exist."),
+                None => write!(f, "The referenced line {r:?} does not exist."),
+            },
+            SubproofDoesNotExist(s) => match subproof_lines(s) {
+                Some(range) => write!(f, "The referenced subproof at lines {} does not exist.", line_info::format_range(range)),
+                None => write!(f, "The referenced subproof {s:?} does not exist."),
+            },
+            ReferencesLaterLine(line, dep) => {
+                let dep_lines = match dep {
+                    Coproduct::Inl(r) => line_number(r).map(|n| format!("line {n}")),
+                    Coproduct::Inr(Coproduct::Inl(s)) => subproof_lines(s).map(|range| format!("lines {}", line_info::format_range(range))),
+                    Coproduct::Inr(Coproduct::Inr(void)) => match *void {},
+                };
+                match (line_number(line), dep_lines) {
+                    (Some(line), Some(dep_lines)) => write!(f, "The dependency on {dep_lines} is after the step that uses it, on line {line}."),
+                    _ => write!(f, "The dependency {dep:?} is after the step that uses it ({line:?})."),
+                }
+            }
             IncorrectDepCount(deps, n) => write!(f, "Too {} dependencies (expected: {}, provided: {}).", if deps.len() > *n { "many" } else { "few" }, n, deps.len()),
             IncorrectSubDepCount(sdeps, n) => write!(f, "Too {} subproof dependencies (expected: {}, provided: {}).", if sdeps.len() > *n { "many" } else { "few" }, n, sdeps.len()),
+            PremiseCitedAsAssumption(r) => {
+                let premise = match line_number(r) {
+                    Some(line) => format!("line {line}"),
+                    None => format!("{r:?}"),
+                };
+                write!(f, "The premise on {premise} can't be discharged, since it's a premise of the whole proof. Cite a subproof that assumes it instead.")
+            }
+            IncorrectAssumptionCount(s, n) => {
+                let subproof = match subproof_lines(s) {
+                    Some(range) => format!("at lines {}", line_info::format_range(range)),
+                    None => format!("{s:?}"),
+                };
+                match n {
+                    0 => write!(f, "The subproof {subproof} has no assumption to discharge."),
+                    n => write!(f, "The subproof {subproof} has {n} assumptions, but only one can be discharged here."),
+                }
+            }
+            NeedsReiteration(lines) => {
+                let lines = lines.iter().map(|r| line_number(r).map(|n| n.to_string()).unwrap_or_else(|| format!("{r:?}"))).collect::<Vec<_>>();
+                match &lines[..] {
+                    [line] => write!(f, "Line {line} is outside this subproof, so it has to be reiterated into it before it's cited."),
+                    lines => write!(f, "Lines {} are outside this subproof, so they have to be reiterated into it before they're cited.", lines.join(", ")),
+                }
+            }
             DepOfWrongForm(x, y) => write!(f, "A dependency ({x}) is of the wrong form, expected {y}."),
             ConclusionOfWrongForm(kind) => write!(f, "The conclusion is of the wrong form, expected {kind}."),
             DoesNotOccur(x, y) => write!(f, "{x} does not occur in {y}."),
@@ -2419,15 +3743,24 @@ impl<R: std::fmt::Debug, S: std::fmt::Debug> std::fmt::Display for ProofCheckErr
                 assert!(errs.len() > 1);
                 writeln!(f, "One of the following requirements was not met:")?;
                 for err in errs {
-                    writeln!(f, "{err}")?;
+                    err.write_message(f, line_number, subproof_lines)?;
+                    writeln!(f)?;
                 }
                 Ok(())
             }
+            TimedOut => write!(f, "Checking this line took too long, so it was stopped. Try breaking it into smaller steps."),
+            Gap => write!(f, "This step is a gap, admitted without proof for now."),
             Other(msg) => write!(f, "{msg}"),
         }
     }
 }
 
+impl<R: std::fmt::Debug, S: std::fmt::Debug> std::fmt::Display for ProofCheckError<R, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.write_message(f, &|_| None, &|_| None)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2474,4 +3807,230 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_error_message() {
+        use ProofCheckError::*;
+
+        // Lines are letters numbered from `a`, and `z` isn't in the proof
+        let line_number = |r: &char| (*r != 'z').then(|| *r as usize - 'a' as usize + 1);
+        let subproof_lines = |s: &u8| Some((*s as usize, *s as usize + 2));
+        let later = ReferencesLaterLine::<char, u8>('a', Coproduct::Inr(Coproduct::Inl(4)));
+        assert_eq!(later.message(&line_number, &subproof_lines), "The dependency on lines 4-6 is after the step that uses it, on line 1.");
+        assert_eq!(later.message(&|_| None, &subproof_lines), later.to_string());
+        assert_eq!(LineDoesNotExist::<char, u8>('c').message(&line_number, &subproof_lines), "The referenced line 3 does not exist.");
+        assert_eq!(LineDoesNotExist::<char, u8>('z').message(&line_number, &subproof_lines), "The referenced line 'z' does not exist.");
+        let one_of = OneOf([SubproofDoesNotExist::<char, u8>(2), TimedOut].into_iter().collect());
+        assert!(one_of.message(&line_number, &subproof_lines).contains("The referenced subproof at lines 2-4 does not exist.\n"));
+    }
+
+    #[test]
+    fn test_premise_cited_as_assumption() {
+        use crate::parser::parse_unwrap as p;
+        use crate::proofs::Justification;
+
+        type P = crate::proofs::pooledproof::PooledProof<HList![Expr]>;
+
+        let mut prf = P::new();
+        let premise = prf.add_premise(p("A"));
+        let sub = prf.add_subproof();
+        let assumption = prf
+            .with_mut_subproof(&sub, |sub| {
+                let assumption = sub.add_premise(p("A"));
+                sub.add_step(Justification(p("A"), RuleM::Reiteration, vec![Coproduct::inject(assumption)], vec![]));
+                assumption
+            })
+            .unwrap();
+        assert!(!prf.is_assumption(&premise));
+        assert!(prf.is_assumption(&assumption));
+        assert_eq!(prf.assumption_of(&sub), Some(assumption));
+
+        let premise = Coproduct::inject(premise);
+        let wrong = prf.add_step(Justification(p("A -> A"), RuleM::ImpIntro, vec![premise], vec![]));
+        let right = prf.add_step(Justification(p("A -> A"), RuleM::ImpIntro, vec![], vec![sub]));
+        assert_eq!(prf.verify_line(&Coproduct::inject(wrong)), Err(ProofCheckError::PremiseCitedAsAssumption(premise)));
+        assert_eq!(prf.verify_line(&Coproduct::inject(right)), Ok(()));
+        // Too many lines are cited, but none of them could be a subproof's assumption
+        let step = prf.add_step(Justification(p("A"), RuleM::Reiteration, vec![premise], vec![]));
+        let cites_step = prf.add_step(Justification(p("A -> A"), RuleM::ImpIntro, vec![Coproduct::inject(step)], vec![]));
+        assert!(matches!(prf.verify_line(&Coproduct::inject(cites_step)), Err(ProofCheckError::IncorrectDepCount(_, 0))));
+    }
+
+    #[test]
+    fn test_assumption_policy() {
+        use crate::parser::parse_unwrap as p;
+        use crate::proofs::with_assumption_policy;
+
+        type P = crate::proofs::pooledproof::PooledProof<HList![Expr]>;
+
+        let mut prf = P::new();
+        prf.add_premise(p("C"));
+        let sub = prf.add_subproof();
+        prf.with_mut_subproof(&sub, |sub| {
+            let a = sub.add_premise(p("A"));
+            let b = sub.add_premise(p("B"));
+            sub.add_step(Justification(p("A & B"), RuleM::AndIntro, vec![Coproduct::inject(a), Coproduct::inject(b)], vec![]));
+        });
+        let imp = Coproduct::inject(prf.add_step(Justification(p("(A & B) -> (A & B)"), RuleM::ImpIntro, vec![], vec![sub])));
+        let exists = prf.add_premise(p("exists x P(x)"));
+        let elim = Coproduct::inject(prf.add_step(Justification(p("A & B"), RuleM::ExistsElim, vec![Coproduct::inject(exists)], vec![sub])));
+
+        // One assumption, unless the policy allows several, which are discharged together
+        assert_eq!(prf.verify_line(&imp), Err(ProofCheckError::IncorrectAssumptionCount(sub, 2)));
+        assert!(!prf.may_add_assumption(&sub));
+        with_assumption_policy(AssumptionPolicy::Multiple, || {
+            assert_eq!(prf.verify_line(&imp), Ok(()));
+            assert!(prf.may_add_assumption(&sub));
+            // ∃E needs a single assumption naming the witness either way
+            assert_eq!(prf.verify_line(&elim), Err(ProofCheckError::IncorrectAssumptionCount(sub, 2)));
+        });
+        assert_eq!(prf.verify_line(&imp), Err(ProofCheckError::IncorrectAssumptionCount(sub, 2)));
+        assert_eq!(ProofCheckError::<(), usize>::IncorrectAssumptionCount(0, 2).message(&|_| None, &|_| Some((2, 4))), "The subproof at lines 2-4 has 2 assumptions, but only one can be discharged here.");
+    }
+
+    #[test]
+    fn test_scoping_policy() {
+        use crate::parser::parse_unwrap as p;
+        use crate::proofs::with_scoping_policy;
+        use crate::proofs::ScopingPolicy;
+
+        type P = crate::proofs::pooledproof::PooledProof<HList![Expr]>;
+
+        let mut prf = P::new();
+        let a = Coproduct::inject(prf.add_premise(p("A")));
+        let b = Coproduct::inject(prf.add_premise(p("B")));
+        let sub = prf.add_subproof();
+        let (conj, reit, wrong, inner) = prf
+            .with_mut_subproof(&sub, |sub| {
+                let c = Coproduct::inject(sub.add_premise(p("C")));
+                let conj = sub.add_step(Justification(p("A & B & C"), RuleM::AndIntro, vec![a, b, c], vec![]));
+                let reit = sub.add_step(Justification(p("A"), RuleM::Reiteration, vec![a], vec![]));
+                let wrong = sub.add_step(Justification(p("B & A"), RuleM::AndIntro, vec![a], vec![]));
+                let inner = sub.add_step(Justification(p("A & C"), RuleM::AndIntro, vec![Coproduct::inject(reit), c], vec![]));
+                (Coproduct::inject(conj), Coproduct::inject(reit), Coproduct::inject(wrong), Coproduct::inject(inner))
+            })
+            .unwrap();
+
+        // Lines of enclosing subproofs can be cited directly, unless scoping is strict, where a step that would be
+        // correct otherwise names the lines to reiterate, and one that wouldn't is wrong as it is
+        assert_eq!(prf.verify_line(&conj), Ok(()));
+        with_scoping_policy(ScopingPolicy::Strict, || {
+            assert_eq!(prf.verify_line(&conj), Err(ProofCheckError::NeedsReiteration(vec![a, b])));
+            assert_eq!(prf.verify_line(&reit), Ok(()));
+            assert!(matches!(prf.verify_line(&wrong), Err(ProofCheckError::DepDoesNotExist(_, _))));
+            assert_eq!(prf.verify_line(&inner), Ok(()));
+        });
+        assert_eq!(prf.verify_line(&wrong).map_err(|err| err.code()), Err("dep-does-not-exist"));
+        assert_eq!(ProofCheckError::<char, u8>::NeedsReiteration(vec!['a', 'b']).message(&|r| Some(*r as usize - 'a' as usize + 1), &|_| None), "Lines 1, 2 are outside this subproof, so they have to be reiterated into it before they're cited.");
+    }
+
+    #[test]
+    fn test_rewrite_rule_at_subformula() {
+        use crate::parser::parse_unwrap as p;
+
+        type P = crate::proofs::pooledproof::PooledProof<HList![Expr]>;
+
+        let mut prf = P::new();
+        let premise = prf.add_premise(p("~~A & (B -> C)"));
+        let premise = Coproduct::inject(premise);
+
+        // Rewriting the selected subformula with a rule's rewrite rule produces a step that the rule accepts
+        for (rule, path, expected) in [(RuleM::DoubleNegation, vec![0], "A & (B -> C)"), (RuleM::Implication, vec![1], "~~A & (~B | C)")] {
+            let premise_expr = prf.lookup_expr(&premise).unwrap();
+            let conclusion = rule.rewrite_rule().unwrap().rewrite_at(&premise_expr, &path).unwrap();
+            assert_eq!(conclusion, p(expected));
+            let step = prf.add_step(crate::proofs::Justification(conclusion, rule, vec![premise], vec![]));
+            assert!(prf.verify_line(&Coproduct::inject(step)).is_ok());
+        }
+        assert_eq!(RuleM::DoubleNegation.rewrite_rule().unwrap().rewrite_at(&p("~~A & (B -> C)"), &[1]), None);
+        assert!(RuleM::DeMorgan.rewrite_rule().is_none());
+    }
+
+    #[test]
+    fn test_backward() {
+        use crate::parser::parse_unwrap as p;
+
+        let blank = Expr::var("");
+        let backward = |rule: Rule, goal: &str| rule.backward(&p(goal)).map(|schema| (schema.deps, schema.subdeps));
+        assert_eq!(backward(RuleM::ImpIntro, "A -> (B | C)"), Some((vec![], vec![(Some(p("A")), p("B | C"))])));
+        assert_eq!(backward(RuleM::ImpIntro, "A & B"), None);
+        // →E's antecedent can be anything
+        assert_eq!(backward(RuleM::ImpElim, "B"), Some((vec![blank.clone(), blank.clone()], vec![])));
+        assert_eq!(backward(RuleM::AndIntro, "A & B & C"), Some((vec![p("A"), p("B"), p("C")], vec![])));
+        assert_eq!(backward(RuleM::OrIntro, "A | B"), Some((vec![blank.clone()], vec![])));
+        assert_eq!(backward(RuleM::DeMorgan, "~A | ~(B -> C)"), Some((vec![p("~(A & (B -> C))")], vec![])));
+        assert_eq!(backward(RuleM::ForallIntro, "forall x (P(x) -> Q(a))"), Some((vec![], vec![(None, p("P(a0) -> Q(a)"))])));
+        assert_eq!(backward(RuleM::ExistsElim, "Q(b)"), Some((vec![blank.clone()], vec![(Some(blank.clone()), p("Q(b)"))])));
+        assert_eq!(backward(RuleM::EmptyRule, "A"), None);
+
+        // Working backward from a schema's own conclusion gets back as much of the schema as it determines
+        for rule in RuleM::ALL_RULES {
+            let Some(schema) = rule.get_schema() else { continue };
+            let found = rule.backward(&schema.conclusion).unwrap_or_else(|| panic!("{} doesn't work backward from {}", rule.get_name(), schema.conclusion));
+            let agrees = |found: &Expr, expected: &Expr| *found == blank || found == expected;
+            assert_eq!(found.deps.len(), schema.deps.len(), "{}", rule.get_name());
+            assert!(found.deps.iter().zip(&schema.deps).all(|(found, expected)| agrees(found, expected)), "{}: {:?}", rule.get_name(), found.deps);
+            assert_eq!(found.subdeps.len(), schema.subdeps.len(), "{}", rule.get_name());
+            for ((premise, line), (expected_premise, expected_line)) in found.subdeps.iter().zip(&schema.subdeps) {
+                assert_eq!(premise.is_some(), expected_premise.is_some(), "{}", rule.get_name());
+                assert!(premise.iter().zip(expected_premise).all(|(found, expected)| agrees(found, expected)) && agrees(line, expected_line), "{}: {:?}", rule.get_name(), found.subdeps);
+            }
+        }
+    }
+
+    #[test]
+    fn test_example_proofs_check() {
+        type P = crate::proofs::pooledproof::PooledProof<HList![Expr]>;
+
+        for rule in RuleM::ALL_RULES {
+            let schema = match rule.get_schema() {
+                Some(schema) => schema,
+                None => continue,
+            };
+            let prf = schema.example_proof::<P>(*rule);
+            let last = prf.lines().into_iter().filter_map(|line| line.get::<<P as Proof>::JustificationReference, _>().cloned()).next_back().unwrap();
+            let syntax = crate::parser::Syntax { logic_mode: logic_of(*rule).unwrap_or_default(), operators: vec![] };
+            let result = crate::parser::with_syntax(&syntax, || prf.verify_line(&Coproduct::inject(last)));
+            assert!(result.is_ok(), "example of {} ({schema}) doesn't check: {result:?}", rule.get_name());
+        }
+    }
+
+    #[test]
+    fn test_proof_check_error_code() {
+        use crate::parser::parse_unwrap as p;
+
+        type P = crate::proofs::pooledproof::PooledProof<HList![Expr]>;
+
+        let mut prf = P::new();
+        let premise = prf.add_premise(p("A & B"));
+        let step = prf.add_step(crate::proofs::Justification(p("C"), RuleM::AndElim, vec![Coproduct::inject(premise)], vec![]));
+        let err = prf.verify_line(&Coproduct::inject(step)).unwrap_err();
+        assert_eq!(err.code(), "does-not-occur", "{err}");
+        let step = prf.add_step(crate::proofs::Justification(p("A"), RuleM::AndElim, vec![], vec![]));
+        assert_eq!(prf.verify_line(&Coproduct::inject(step)).unwrap_err().code(), "incorrect-dep-count");
+    }
+
+    #[test]
+    fn test_check_times_out() {
+        use crate::limits::CheckLimits;
+        use crate::parser::parse_unwrap as p;
+
+        type P = crate::proofs::pooledproof::PooledProof<HList![Expr]>;
+
+        crate::limits::set_limits(CheckLimits { max_steps: 1000, timeout: None });
+        let mut prf = P::new();
+        // A disjunction of 20 conjunctions takes 2^20 clauses in CNF
+        let disjuncts = (0..20).map(|i| format!("(A{i} & B{i})")).collect::<Vec<_>>();
+        let premise = prf.add_premise(p(&disjuncts.join(" | ")));
+        let step = prf.add_step(crate::proofs::Justification(p("A0 | B0 | A1"), RuleM::TruthFunctionalConsequence, vec![Coproduct::inject(premise)], vec![]));
+        let err = prf.verify_line(&Coproduct::inject(step)).unwrap_err();
+        assert_eq!(err, ProofCheckError::TimedOut);
+        assert_eq!(err.code(), "timed-out");
+
+        // Smaller lines still check within the limits
+        let premise = prf.add_premise(p("(A & B) | (C & D)"));
+        let step = prf.add_step(crate::proofs::Justification(p("A | C"), RuleM::TruthFunctionalConsequence, vec![Coproduct::inject(premise)], vec![]));
+        assert_eq!(prf.verify_line(&Coproduct::inject(step)), Ok(()));
+        crate::limits::set_limits(CheckLimits::default());
+    }
 }
@@ -0,0 +1,978 @@
+/*!
+# Checking rules for soundness by brute force
+
+A rule is sound when every step it accepts has a conclusion that's true
+whenever the lines and subproofs the step cites are. This module checks that
+for particular steps by trying every small model: every valuation of the
+variables for quantifier-free formulas, and every interpretation of the
+predicates over domains of a few elements otherwise. That can't prove a rule
+sound, but it catches the usual mistakes, like a missing side condition on a
+quantifier rule.
+
+It only needs a `Rule`, so the same checks work on rules from rule packs (see
+`crate::plugins`), and their authors can test them the way the built-in rules
+are tested:
+
+```
+use aris::parser::parse_unwrap as p;
+use aris::rules::RuleM;
+use aris::soundness::{self, Config};
+
+let formulas = ["A", "~A", "A & B", "A -> B"].map(p);
+assert!(soundness::check_rule(RuleM::ModusTollens, &formulas, &Config::default()).unwrap() > 0);
+
+// Affirming the consequent isn't sound
+let countermodel = soundness::countermodel(&[p("A -> B"), p("B")], &p("A"), &Config::default()).unwrap();
+assert!(countermodel.is_some());
+```
+
+A subproof counts as the conditionals from its premises to each of its lines.
+Constants that only occur in the subproof are arbitrary, so those conditionals
+are generalized over them, which is what makes `∀ Introduction` and
+`∃ Elimination` sound. In first-order models, terms can only be variables and
+constants; formulas applying functions to terms can't be checked.
+
+The same search explains wrong steps of a proof: `step_countermodel` looks for
+a model where what a first-order step cites is true and its conclusion false,
+which editors can show alongside the rule's own error.
+
+Formulas with sums, products, or comparisons are about the natural numbers,
+rather than arbitrary models, so they're checked by trying every assignment of
+small numbers to their variables instead. They can't have quantifiers or
+predicates other than `<` and `≤`, since a countermodel found with only some of
+the numbers wouldn't be one in the natural numbers.
+
+```
+use aris::parser::parse_unwrap as p;
+use aris::soundness::{self, Config};
+
+assert_eq!(soundness::countermodel(&[p("x < y")], &p("x + 1 ≤ y"), &Config::default()), Ok(None));
+assert!(soundness::countermodel(&[p("x ≤ y")], &p("x × y ≤ y"), &Config::default()).unwrap().is_some());
+```
+*/
+
+use crate::expr::free_vars;
+use crate::expr::Expr;
+use crate::expr::Op;
+use crate::expr::QuantKind;
+use crate::expr::TraversalOrder;
+use crate::expr::ELEMENT;
+use crate::expr::EQUALS;
+use crate::expr::LESS;
+use crate::expr::SUBSET;
+use crate::plugins::PluginSubproof;
+use crate::proofs::pooledproof::PooledProof;
+use crate::proofs::Justification;
+use crate::proofs::PjRef;
+use crate::proofs::Proof;
+use crate::rewrite_rules::instantiate_schema;
+use crate::rewrite_rules::ConditionalReduction;
+use crate::rewrite_rules::RewriteRule;
+use crate::rules::Rule;
+use crate::rules::RuleM;
+use crate::rules::RuleSchema;
+use crate::rules::RuleT;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::iter;
+
+use frunk_core::coproduct::Coproduct;
+use frunk_core::HList;
+
+/// How many models to try
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// Largest domain to try for first-order formulas; every size from 1 up to it is tried
+    pub max_domain: usize,
+    /// Most models to try for one claim. Larger domains are skipped once they'd need more, and claims that
+    /// need more even with one element can't be checked.
+    pub max_models: u64,
+    /// Largest number to try for the variables of arithmetic formulas; every number from 0 up to it is tried
+    pub max_number: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { max_domain: 3, max_models: 1 << 16, max_number: 4 }
+    }
+}
+
+/// The symbols of some formulas, which a first-order model has to interpret
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Signature {
+    /// Predicates with their arities; propositional variables have arity 0
+    pub arities: BTreeMap<String, usize>,
+    /// Free variables used as terms
+    pub constants: BTreeSet<String>,
+}
+
+impl Signature {
+    /// The signature of some formulas, or what they use that a model can't interpret
+    pub fn of<'a>(exprs: impl IntoIterator<Item = &'a Expr>) -> Result<Signature, String> {
+        let mut signature = Signature::default();
+        for e in exprs {
+            signature.add(e, &mut vec![])?;
+        }
+        if let Some(name) = signature.constants.iter().find(|name| signature.arities.contains_key(*name)) {
+            return Err(format!("{name} is used both as a term and as a formula"));
+        }
+        Ok(signature)
+    }
+
+    fn add(&mut self, e: &Expr, bound: &mut Vec<String>) -> Result<(), String> {
+        match e {
+            Expr::Contra | Expr::Taut => Ok(()),
+            Expr::Var { name } => self.add_predicate(name, 0),
+            Expr::Apply { func, args } => {
+                match &**func {
+                    Expr::Var { name } => self.add_predicate(name, args.len())?,
+                    _ => return Err(format!("{func} isn't the name of a predicate")),
+                }
+                for arg in args {
+                    match arg {
+                        Expr::Var { name } => {
+                            if !bound.contains(name) {
+                                self.constants.insert(name.clone());
+                            }
+                        }
+                        _ => return Err(format!("{arg} is a compound term, and only variables and constants are supported")),
+                    }
+                }
+                Ok(())
+            }
+            Expr::Not { operand } => self.add(operand, bound),
+            Expr::Impl { left, right } => {
+                self.add(left, bound)?;
+                self.add(right, bound)
+            }
+            Expr::Assoc { op: Op::Add | Op::Mult, .. } => Err(format!("{e} uses arithmetic")),
+            Expr::Assoc { exprs, .. } => exprs.iter().try_for_each(|e| self.add(e, bound)),
+            Expr::Quant { kind: QuantKind::SetBuilder, .. } => Err(format!("{e} is a set")),
+            Expr::Quant { kind: QuantKind::Lambda, .. } => Err(format!("{e} is a function")),
+            Expr::Quant { name, body, .. } => {
+                bound.push(name.clone());
+                let result = self.add(body, bound);
+                bound.pop();
+                result
+            }
+        }
+    }
+
+    fn add_predicate(&mut self, name: &str, arity: usize) -> Result<(), String> {
+        match self.arities.get(name) {
+            Some(&other) if other != arity => Err(format!("{name} is used with both {other} and {arity} arguments")),
+            _ => {
+                self.arities.insert(name.to_owned(), arity);
+                Ok(())
+            }
+        }
+    }
+
+    /// Number of models of the signature with `domain` elements, if it fits in a `u64`
+    pub fn num_models(&self, domain: usize) -> Option<u64> {
+        let domain = domain as u64;
+        let bits = self.arities.values().try_fold(0u32, |bits, &arity| bits.checked_add(u32::try_from(domain.checked_pow(arity as u32)?).ok()?))?;
+        let assignments = domain.checked_pow(self.constants.len() as u32)?;
+        1u64.checked_shl(bits)?.checked_mul(assignments)
+    }
+
+    /// Every model of the signature with `domain` elements. Check `num_models()` first, since there can be a lot.
+    pub fn models(&self, domain: usize) -> impl Iterator<Item = Model> + '_ {
+        let count = self.num_models(domain).expect("too many models to enumerate");
+        (0..count).map(move |mut index| {
+            let constants = self
+                .constants
+                .iter()
+                .map(|name| {
+                    let element = (index % domain as u64) as usize;
+                    index /= domain as u64;
+                    (name.clone(), element)
+                })
+                .collect();
+            let tables = self
+                .arities
+                .iter()
+                .map(|(name, &arity)| {
+                    let table = (0..domain.pow(arity as u32))
+                        .map(|_| {
+                            let value = (index & 1) == 1;
+                            index >>= 1;
+                            value
+                        })
+                        .collect();
+                    (name.clone(), table)
+                })
+                .collect();
+            Model { domain, arities: self.arities.clone(), tables, constants }
+        })
+    }
+}
+
+/// A finite first-order model, whose domain is `0..domain`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Model {
+    pub domain: usize,
+    /// Arity of each predicate; propositional variables have arity 0
+    pub arities: BTreeMap<String, usize>,
+    /// Truth table of each predicate, indexed by its arguments read as a base-`domain` number with the first
+    /// argument as the least significant digit, like `Expr::eval` does with two elements
+    pub tables: BTreeMap<String, Vec<bool>>,
+    /// The element each constant names
+    pub constants: BTreeMap<String, usize>,
+}
+
+impl Model {
+    /// Whether `e` is true in the model. This panics on symbols the model doesn't interpret, see `Signature::of()`.
+    pub fn eval(&self, e: &Expr) -> bool {
+        self.eval_in(e, &mut HashMap::new())
+    }
+
+    fn element(&self, term: &Expr, env: &HashMap<String, usize>) -> usize {
+        match term {
+            Expr::Var { name } => *env.get(name).or_else(|| self.constants.get(name)).unwrap_or_else(|| panic!("{name} isn't interpreted by the model")),
+            _ => panic!("unsupported term {term}"),
+        }
+    }
+
+    fn eval_in(&self, e: &Expr, env: &mut HashMap<String, usize>) -> bool {
+        match e {
+            Expr::Contra => false,
+            Expr::Taut => true,
+            Expr::Var { name } => self.tables[name][0],
+            Expr::Apply { func, args } => {
+                let index = args.iter().rev().fold(0, |index, arg| index * self.domain + self.element(arg, env));
+                match &**func {
+                    Expr::Var { name } => self.tables[name][index],
+                    _ => panic!("unsupported predicate {func}"),
+                }
+            }
+            Expr::Not { operand } => !self.eval_in(operand, env),
+            Expr::Impl { left, right } => !self.eval_in(left, env) || self.eval_in(right, env),
+            Expr::Assoc { op, exprs } => {
+                let mut values = exprs.iter().map(|e| self.eval_in(e, env));
+                match op {
+                    Op::And => values.all(|v| v),
+                    Op::Or => values.any(|v| v),
+                    // Chains fold the same way as in `Expr::eval`
+                    Op::Bicon | Op::Equiv => values.fold(true, |acc, v| acc == v),
+                    Op::Add | Op::Mult => panic!("unsupported operator {op}"),
+                }
+            }
+            Expr::Quant { kind, name, body } => {
+                let shadowed = env.get(name).copied();
+                let mut values = (0..self.domain).map(|d| {
+                    env.insert(name.clone(), d);
+                    self.eval_in(body, env)
+                });
+                let value = match kind {
+                    QuantKind::Forall => values.all(|v| v),
+                    QuantKind::Exists => values.any(|v| v),
+                    QuantKind::SetBuilder | QuantKind::Lambda => panic!("unsupported formula {e}"),
+                };
+                match shadowed {
+                    Some(d) => env.insert(name.clone(), d),
+                    None => env.remove(name),
+                };
+                value
+            }
+        }
+    }
+
+    /// A tuple of elements, as it's indexed in a truth table
+    fn tuple(&self, mut index: usize, arity: usize) -> String {
+        let elements = (0..arity)
+            .map(|_| {
+                let element = index % self.domain;
+                index /= self.domain;
+                element.to_string()
+            })
+            .collect::<Vec<_>>();
+        match elements.as_slice() {
+            [element] => element.clone(),
+            _ => format!("({})", elements.join(", ")),
+        }
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let elements = (0..self.domain).map(|d| d.to_string()).collect::<Vec<_>>();
+        write!(f, "domain {{{}}}", elements.join(", "))?;
+        for (name, element) in &self.constants {
+            write!(f, ", {name} = {element}")?;
+        }
+        for (name, table) in &self.tables {
+            match self.arities[name] {
+                0 => write!(f, ", {name} = {}", table[0])?,
+                arity => {
+                    let tuples = table.iter().enumerate().filter(|(_, &value)| value).map(|(index, _)| self.tuple(index, arity)).collect::<Vec<_>>();
+                    write!(f, ", {name} = {{{}}}", tuples.join(", "))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where premises are true and a conclusion is false
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Countermodel {
+    /// Truth tables of the variables and predicates of quantifier-free formulas, in the form `Expr::eval` takes
+    Valuation(BTreeMap<String, Vec<bool>>),
+    /// A first-order model
+    Model(Model),
+    /// Numbers for the variables used as terms of arithmetic formulas, and truth values for the others
+    Arithmetic { numbers: BTreeMap<String, u64>, propositions: BTreeMap<String, bool> },
+}
+
+impl fmt::Display for Countermodel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Countermodel::Valuation(tables) => {
+                let values = tables
+                    .iter()
+                    .map(|(name, table)| match table.as_slice() {
+                        [value] => format!("{name} = {value}"),
+                        _ => format!("{name} = {table:?}"),
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "{}", values.join(", "))
+            }
+            Countermodel::Model(model) => write!(f, "{model}"),
+            Countermodel::Arithmetic { numbers, propositions } => {
+                let values = numbers.iter().map(|(name, n)| format!("{name} = {n}")).chain(propositions.iter().map(|(name, value)| format!("{name} = {value}"))).collect::<Vec<_>>();
+                write!(f, "{}", values.join(", "))
+            }
+        }
+    }
+}
+
+/// Whether `Expr::eval` can evaluate an expression: it has no quantifiers or arithmetic, and only applies named predicates
+fn is_propositional(e: &Expr) -> bool {
+    match e {
+        Expr::Contra | Expr::Taut | Expr::Var { .. } => true,
+        Expr::Apply { func, args } => matches!(**func, Expr::Var { .. }) && args.iter().all(is_propositional),
+        Expr::Not { operand } => is_propositional(operand),
+        Expr::Impl { left, right } => is_propositional(left) && is_propositional(right),
+        Expr::Assoc { op, exprs } => matches!(op, Op::And | Op::Or | Op::Bicon) && exprs.iter().all(is_propositional),
+        Expr::Quant { .. } => false,
+    }
+}
+
+/// Whether an expression uses arithmetic: sums, products, or comparisons
+fn uses_arithmetic(e: &Expr) -> bool {
+    let mut found = false;
+    e.visit(TraversalOrder::PreOrder, &mut |e| found |= matches!(e, Expr::Assoc { op: Op::Add | Op::Mult, .. }) || e.as_comparison().is_some());
+    found
+}
+
+/// The variables of quantifier-free arithmetic formulas
+#[derive(Default)]
+struct ArithmeticVars {
+    /// Variables used as terms, which stand for natural numbers
+    numbers: BTreeSet<String>,
+    /// Variables used as formulas
+    propositions: BTreeSet<String>,
+}
+
+impl ArithmeticVars {
+    fn add_formula(&mut self, e: &Expr) -> Result<(), String> {
+        if let Some((_, left, right)) = e.as_comparison() {
+            self.add_term(left)?;
+            return self.add_term(right);
+        }
+        match e {
+            Expr::Contra | Expr::Taut => Ok(()),
+            Expr::Var { name } => {
+                self.propositions.insert(name.clone());
+                Ok(())
+            }
+            Expr::Apply { func, .. } => Err(format!("{func} isn't interpreted in the natural numbers")),
+            Expr::Not { operand } => self.add_formula(operand),
+            Expr::Impl { left, right } => {
+                self.add_formula(left)?;
+                self.add_formula(right)
+            }
+            Expr::Assoc { op: Op::Add | Op::Mult, .. } => Err(format!("{e} is used as a formula, but it's a number")),
+            Expr::Assoc { exprs, .. } => exprs.iter().try_for_each(|e| self.add_formula(e)),
+            Expr::Quant { .. } => Err(format!("{e} quantifies over the natural numbers")),
+        }
+    }
+
+    fn add_term(&mut self, e: &Expr) -> Result<(), String> {
+        match e {
+            Expr::Var { name } => {
+                if e.as_num().is_none() {
+                    self.numbers.insert(name.clone());
+                }
+                Ok(())
+            }
+            Expr::Assoc { op: Op::Add | Op::Mult, exprs } => exprs.iter().try_for_each(|e| self.add_term(e)),
+            _ => Err(format!("{e} is a term, but only numbers, variables, sums, and products are supported")),
+        }
+    }
+}
+
+/// The value of an arithmetic term, or `None` if it doesn't fit in a `u64`. This panics on variables that aren't
+/// assigned a number.
+fn eval_term(e: &Expr, numbers: &BTreeMap<String, u64>) -> Option<u64> {
+    match e {
+        Expr::Var { name } => e.as_num().or_else(|| Some(numbers[name])),
+        Expr::Assoc { op: Op::Add, exprs } => exprs.iter().try_fold(0u64, |sum, e| sum.checked_add(eval_term(e, numbers)?)),
+        Expr::Assoc { op: Op::Mult, exprs } => exprs.iter().try_fold(1u64, |product, e| product.checked_mul(eval_term(e, numbers)?)),
+        _ => panic!("unsupported term {e}"),
+    }
+}
+
+/// Whether an arithmetic formula is true, or `None` if one of its terms doesn't fit in a `u64`. This panics on
+/// what `ArithmeticVars` rejects.
+fn eval_arithmetic(e: &Expr, numbers: &BTreeMap<String, u64>, propositions: &BTreeMap<String, bool>) -> Option<bool> {
+    if let Some((relation, left, right)) = e.as_comparison() {
+        let (left, right) = (eval_term(left, numbers)?, eval_term(right, numbers)?);
+        return Some(if relation == LESS { left < right } else { left <= right });
+    }
+    let eval = |e: &Expr| eval_arithmetic(e, numbers, propositions);
+    Some(match e {
+        Expr::Contra => false,
+        Expr::Taut => true,
+        Expr::Var { name } => propositions[name],
+        Expr::Not { operand } => !eval(operand)?,
+        Expr::Impl { left, right } => !eval(left)? || eval(right)?,
+        Expr::Assoc { op, exprs } => {
+            let values = exprs.iter().map(eval).collect::<Option<Vec<_>>>()?;
+            match op {
+                Op::And => values.iter().all(|&v| v),
+                Op::Or => values.iter().any(|&v| v),
+                Op::Bicon | Op::Equiv => values.iter().fold(true, |acc, &v| acc == v),
+                Op::Add | Op::Mult => panic!("unsupported operator {op}"),
+            }
+        }
+        Expr::Apply { .. } | Expr::Quant { .. } => panic!("unsupported formula {e}"),
+    })
+}
+
+/// Search for an assignment of numbers up to `config.max_number` where quantifier-free arithmetic premises are
+/// true and the conclusion is false
+fn arithmetic_countermodel(premises: &[Expr], conclusion: &Expr, config: &Config) -> Result<Option<Countermodel>, String> {
+    let mut vars = ArithmeticVars::default();
+    for e in premises.iter().chain(iter::once(conclusion)) {
+        vars.add_formula(e)?;
+    }
+    if let Some(name) = vars.numbers.intersection(&vars.propositions).next() {
+        return Err(format!("{name} is used both as a number and as a formula"));
+    }
+    let base = config.max_number.checked_add(1).ok_or("there are too many numbers to check")?;
+    let count = base.checked_pow(vars.numbers.len() as u32).and_then(|count| count.checked_mul(1u64.checked_shl(vars.propositions.len() as u32)?)).filter(|&count| count <= config.max_models).ok_or("there are too many assignments of numbers to check")?;
+    for mut index in 0..count {
+        let numbers = vars
+            .numbers
+            .iter()
+            .map(|name| {
+                let n = index % base;
+                index /= base;
+                (name.clone(), n)
+            })
+            .collect::<BTreeMap<_, _>>();
+        let propositions = vars
+            .propositions
+            .iter()
+            .map(|name| {
+                let value = (index & 1) == 1;
+                index >>= 1;
+                (name.clone(), value)
+            })
+            .collect::<BTreeMap<_, _>>();
+        let eval = |e: &Expr| eval_arithmetic(e, &numbers, &propositions).ok_or_else(|| format!("{e} has numbers too large to evaluate"));
+        if premises.iter().map(eval).collect::<Result<Vec<_>, _>>()?.into_iter().all(|v| v) && !eval(conclusion)? {
+            return Ok(Some(Countermodel::Arithmetic { numbers, propositions }));
+        }
+    }
+    Ok(None)
+}
+
+/// Search for a model where the premises are true and the conclusion is false. Formulas using arithmetic are
+/// tried with every assignment of numbers up to `config.max_number`, other quantifier-free formulas in every
+/// valuation, and others in every model with up to `config.max_domain` elements. The error says why the formulas
+/// couldn't be checked.
+pub fn countermodel(premises: &[Expr], conclusion: &Expr, config: &Config) -> Result<Option<Countermodel>, String> {
+    let exprs = premises.iter().chain(iter::once(conclusion));
+    if exprs.clone().any(uses_arithmetic) {
+        return arithmetic_countermodel(premises, conclusion, config);
+    }
+    if exprs.clone().all(is_propositional) {
+        let mut arities = HashMap::new();
+        for e in exprs {
+            e.infer_arities(&mut arities);
+        }
+        let mut names = arities.into_iter().collect::<Vec<_>>();
+        names.sort();
+        let bits = names.iter().try_fold(0u32, |bits, (_, arity)| bits.checked_add(1u32.checked_shl(*arity as u32)?));
+        let count = bits.and_then(|bits| 1u64.checked_shl(bits)).filter(|&count| count <= config.max_models).ok_or("there are too many valuations to check")?;
+        for row in 0..count {
+            let mut bit = 0;
+            let env = names
+                .iter()
+                .map(|(name, arity)| {
+                    let table = (0..1usize << arity)
+                        .map(|_| {
+                            let value = ((row >> bit) & 1) == 1;
+                            bit += 1;
+                            value
+                        })
+                        .collect();
+                    (name.clone(), table)
+                })
+                .collect::<HashMap<String, Vec<bool>>>();
+            if premises.iter().all(|e| e.eval(&env)) && !conclusion.eval(&env) {
+                return Ok(Some(Countermodel::Valuation(env.into_iter().collect())));
+            }
+        }
+        return Ok(None);
+    }
+
+    let signature = Signature::of(exprs)?;
+    let mut checked = false;
+    for domain in 1..=config.max_domain {
+        if signature.num_models(domain).is_none_or(|count| count > config.max_models) {
+            break;
+        }
+        checked = true;
+        if let Some(model) = signature.models(domain).find(|model| premises.iter().all(|e| model.eval(e)) && !model.eval(conclusion)) {
+            return Ok(Some(Countermodel::Model(model)));
+        }
+    }
+    if checked {
+        Ok(None)
+    } else {
+        Err("there are too many models to check".into())
+    }
+}
+
+/// Predicates whose meaning is fixed, which first-order models would interpret as arbitrary relations
+const INTERPRETED: [&str; 3] = [EQUALS, ELEMENT, SUBSET];
+
+/// Search for a first-order model where the lines and subproofs a step cites are true and its conclusion is
+/// false, which shows the step doesn't follow from what it cites, whichever rule it uses. Steps that aren't
+/// first-order, or that use predicates like `=` whose meaning is fixed, get `None`, as do premises.
+pub fn step_countermodel<P: Proof>(prf: &P, r: &PjRef<P>, config: &Config) -> Option<Model> {
+    let instance = Instance::of_step(prf, r)?;
+    let signature = Signature::of(instance.exprs()).ok()?;
+    if signature.arities.keys().any(|name| INTERPRETED.contains(&name.as_str())) {
+        return None;
+    }
+    match countermodel(&instance.antecedents(), &instance.conclusion, config) {
+        Ok(Some(Countermodel::Model(model))) => Some(model),
+        _ => None,
+    }
+}
+
+/// A step to check: the formulas of the lines and subproofs it cites, and its conclusion
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instance {
+    pub deps: Vec<Expr>,
+    pub subproofs: Vec<PluginSubproof>,
+    pub conclusion: Expr,
+}
+
+impl Instance {
+    /// The use of a rule that a schema shows
+    pub fn from_schema(schema: &RuleSchema) -> Instance {
+        let subproofs = schema.subdeps.iter().map(|(premise, line)| PluginSubproof { premises: premise.iter().cloned().collect(), lines: vec![line.clone()] }).collect();
+        Instance { deps: schema.deps.clone(), subproofs, conclusion: schema.conclusion.clone() }
+    }
+
+    /// The step a line of a proof takes, or `None` if the line is a premise or cites something that isn't there
+    pub fn of_step<P: Proof>(prf: &P, r: &PjRef<P>) -> Option<Instance> {
+        let Justification(conclusion, _, deps, sdeps) = prf.lookup_step(&Coproduct::uninject::<P::JustificationReference, _>(r.clone()).ok()?)?;
+        let deps = deps.iter().map(|dep| prf.lookup_expr(dep)).collect::<Option<Vec<_>>>()?;
+        let mut subproofs = vec![];
+        for sdep in &sdeps {
+            let sproof = prf.lookup_subproof(sdep)?;
+            let premises = sproof.premises().into_iter().map(|r| prf.lookup_premise(&r)).collect::<Option<Vec<_>>>()?;
+            let lines = sproof.direct_lines().into_iter().map(|r| prf.lookup_expr(&Coproduct::inject(r))).collect::<Option<Vec<_>>>()?;
+            subproofs.push(PluginSubproof { premises, lines });
+        }
+        Some(Instance { deps, subproofs, conclusion })
+    }
+
+    /// Every formula of the step
+    fn exprs(&self) -> impl Iterator<Item = &Expr> {
+        self.deps.iter().chain(self.subproofs.iter().flat_map(|subproof| subproof.premises.iter().chain(&subproof.lines))).chain(iter::once(&self.conclusion))
+    }
+
+    /// The step with metavariables replaced by what they're bound to
+    fn instantiate(&self, bindings: &HashMap<String, Expr>) -> Instance {
+        let instantiate = |exprs: &[Expr]| -> Vec<Expr> { exprs.iter().map(|e| instantiate_schema(e, bindings)).collect() };
+        let subproofs = self.subproofs.iter().map(|subproof| PluginSubproof { premises: instantiate(&subproof.premises), lines: instantiate(&subproof.lines) }).collect();
+        Instance { deps: instantiate(&self.deps), subproofs, conclusion: instantiate_schema(&self.conclusion, bindings) }
+    }
+
+    /// Check the step with `rule`, in a proof of just what it cites
+    pub fn check(&self, rule: Rule) -> Result<(), String> {
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let deps = self.deps.iter().map(|dep| Coproduct::inject(prf.add_premise(dep.clone()))).collect();
+        let sdeps = self
+            .subproofs
+            .iter()
+            .map(|subproof| {
+                let sub = prf.add_subproof();
+                prf.with_mut_subproof(&sub, |inner| {
+                    for premise in &subproof.premises {
+                        inner.add_premise(premise.clone());
+                    }
+                    for line in &subproof.lines {
+                        inner.add_step(Justification(line.clone(), RuleM::EmptyRule, vec![], vec![]));
+                    }
+                });
+                sub
+            })
+            .collect();
+        let step = prf.add_step(Justification(self.conclusion.clone(), rule, deps, sdeps));
+        prf.verify_line(&Coproduct::inject(step)).map_err(|err| err.to_string())
+    }
+
+    /// What the conclusion has to follow from: the cited lines, and the conditionals from each subproof's
+    /// premises to its lines, generalized over the constants that only occur in the subproof
+    pub fn antecedents(&self) -> Vec<Expr> {
+        let outside = self.deps.iter().chain(iter::once(&self.conclusion)).flat_map(free_vars).collect::<HashSet<_>>();
+        let mut antecedents = self.deps.clone();
+        for subproof in &self.subproofs {
+            let arbitrary = Signature::of(subproof.premises.iter().chain(&subproof.lines)).map(|signature| signature.constants.into_iter().filter(|name| !outside.contains(name)).collect::<Vec<_>>()).unwrap_or_default();
+            for line in &subproof.lines {
+                let conditional = match subproof.premises.as_slice() {
+                    [] => line.clone(),
+                    [premise] => Expr::implies(premise.clone(), line.clone()),
+                    premises => Expr::implies(Expr::assoc(Op::And, premises), line.clone()),
+                };
+                antecedents.push(arbitrary.iter().rev().fold(conditional, |body, name| Expr::Quant { kind: QuantKind::Forall, name: name.clone(), body: Box::new(body) }));
+            }
+        }
+        antecedents
+    }
+}
+
+impl fmt::Display for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let subproofs = self.subproofs.iter().map(|subproof| {
+            let lines = subproof.lines.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+            match subproof.premises.as_slice() {
+                [] => format!("[⋯ {lines}]"),
+                premises => format!("[{} ⋯ {lines}]", premises.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")),
+            }
+        });
+        let antecedents = self.deps.iter().map(ToString::to_string).chain(subproofs).collect::<Vec<_>>();
+        if antecedents.is_empty() {
+            write!(f, "⊢ {}", self.conclusion)
+        } else {
+            write!(f, "{} ⊢ {}", antecedents.join(", "), self.conclusion)
+        }
+    }
+}
+
+/// Why a rule isn't sound, or couldn't be checked
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SoundnessError {
+    /// The rule accepted a step whose conclusion is false in a model where everything it cites is true
+    UnsoundStep { instance: Instance, countermodel: Box<Countermodel> },
+    /// The rule rewrites a formula into one that differs from it in a model
+    UnsoundRewrite { before: Expr, after: Expr, countermodel: Box<Countermodel> },
+    /// The formulas involved use something the models can't interpret
+    Unsupported(String),
+}
+
+impl fmt::Display for SoundnessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SoundnessError::UnsoundStep { instance, countermodel } => write!(f, "the rule accepts {instance}, which fails where {countermodel}"),
+            SoundnessError::UnsoundRewrite { before, after, countermodel } => write!(f, "the rule rewrites {before} to {after}, which differ where {countermodel}"),
+            SoundnessError::Unsupported(reason) => write!(f, "the rule couldn't be checked, since {reason}"),
+        }
+    }
+}
+
+/// Every way of binding each name to one of its values
+fn bindings(choices: &[(String, Vec<Expr>)]) -> Vec<HashMap<String, Expr>> {
+    choices.iter().fold(vec![HashMap::new()], |bindings, (name, values)| {
+        bindings
+            .iter()
+            .flat_map(|binding| {
+                values.iter().map(move |value| {
+                    let mut binding = binding.clone();
+                    binding.insert(name.clone(), value.clone());
+                    binding
+                })
+            })
+            .collect()
+    })
+}
+
+/// Variables used as formulas, rather than as terms or predicates, outside the scope of any quantifier binding them
+fn formula_vars(e: &Expr, bound: &mut Vec<String>, vars: &mut BTreeSet<String>) {
+    match e {
+        Expr::Contra | Expr::Taut | Expr::Apply { .. } => {}
+        Expr::Var { name } => {
+            if !bound.contains(name) {
+                vars.insert(name.clone());
+            }
+        }
+        Expr::Not { operand } => formula_vars(operand, bound, vars),
+        Expr::Impl { left, right } => {
+            formula_vars(left, bound, vars);
+            formula_vars(right, bound, vars);
+        }
+        Expr::Assoc { exprs, .. } => {
+            for e in exprs {
+                formula_vars(e, bound, vars);
+            }
+        }
+        Expr::Quant { name, body, .. } => {
+            bound.push(name.clone());
+            formula_vars(body, bound, vars);
+            bound.pop();
+        }
+    }
+}
+
+/// Steps like the one a schema shows, with its propositional variables replaced by each combination of
+/// `formulas`, concluding either what the schema does or one of `formulas`. A sound rule accepts the first kind,
+/// and only accepts the second kind when the conclusion happens to follow anyway.
+pub fn schema_instances(schema: &RuleSchema, formulas: &[Expr]) -> Vec<Instance> {
+    let schema = Instance::from_schema(schema);
+    let mut vars = BTreeSet::new();
+    for e in schema.exprs() {
+        formula_vars(e, &mut vec![], &mut vars);
+    }
+    let choices = vars.into_iter().map(|var| (var, formulas.to_vec())).collect::<Vec<_>>();
+    let mut instances = vec![];
+    for binding in bindings(&choices) {
+        let instance = schema.instantiate(&binding);
+        instances.extend(formulas.iter().filter(|&conclusion| *conclusion != instance.conclusion).map(|conclusion| Instance { conclusion: conclusion.clone(), ..instance.clone() }));
+        instances.push(instance);
+    }
+    instances
+}
+
+/// Check the steps among `instances` that `rule` accepts, returning how many it accepted
+pub fn check_instances(rule: Rule, instances: impl IntoIterator<Item = Instance>, config: &Config) -> Result<usize, SoundnessError> {
+    let mut accepted = 0;
+    for instance in instances {
+        if instance.check(rule).is_err() {
+            continue;
+        }
+        accepted += 1;
+        match countermodel(&instance.antecedents(), &instance.conclusion, config) {
+            Ok(None) => {}
+            Ok(Some(countermodel)) => return Err(SoundnessError::UnsoundStep { instance, countermodel: Box::new(countermodel) }),
+            Err(reason) => return Err(SoundnessError::Unsupported(reason)),
+        }
+    }
+    Ok(accepted)
+}
+
+/// Check that two formulas are true in the same models, as they have to be for a rule to rewrite one into the other
+pub fn check_equivalence(before: &Expr, after: &Expr, config: &Config) -> Result<(), SoundnessError> {
+    for (premise, conclusion) in [(before, after), (after, before)] {
+        match countermodel(std::slice::from_ref(premise), conclusion, config) {
+            Ok(None) => {}
+            Ok(Some(countermodel)) => return Err(SoundnessError::UnsoundRewrite { before: before.clone(), after: after.clone(), countermodel: Box::new(countermodel) }),
+            Err(reason) => return Err(SoundnessError::Unsupported(reason)),
+        }
+    }
+    Ok(())
+}
+
+/// Check a reduction with side conditions on the formulas it applies to when its formula metavariables are bound
+/// to each of `formulas`, and the variables its quantifiers bind are each variable of the pattern or constant of
+/// `formulas`, so that they can clash. Returns how many of those the side conditions let it rewrite.
+pub fn check_conditional_reduction(reduction: &ConditionalReduction, formulas: &[Expr], config: &Config) -> Result<usize, SoundnessError> {
+    let mut variables = BTreeSet::new();
+    let mut metavars = BTreeSet::new();
+    reduction.pattern.visit(TraversalOrder::PreOrder, &mut |e| match e {
+        Expr::Quant { name, .. } => {
+            variables.insert(name.clone());
+        }
+        Expr::Var { name } => {
+            metavars.insert(name.clone());
+        }
+        _ => {}
+    });
+    let metavars = metavars.difference(&variables).cloned().collect::<Vec<_>>();
+    let mut candidates = variables.clone();
+    for formula in formulas {
+        if let Ok(signature) = Signature::of(iter::once(formula)) {
+            candidates.extend(signature.constants);
+        }
+    }
+    let candidates = candidates.iter().map(|name| Expr::var(name)).collect::<Vec<_>>();
+    let choices = variables.into_iter().map(|var| (var, candidates.clone())).chain(metavars.into_iter().map(|var| (var, formulas.to_vec()))).collect::<Vec<_>>();
+
+    let mut applied = 0;
+    for binding in bindings(&choices) {
+        let before = instantiate_schema(&reduction.pattern, &binding);
+        if let Some(after) = reduction.apply(&before) {
+            applied += 1;
+            check_equivalence(&before, &after, config)?;
+        }
+    }
+    Ok(applied)
+}
+
+/// Check every reduction of a rewrite rule, returning how many rewrites were checked. Reductions without side
+/// conditions are checked as they are, with their metavariables as propositional variables and predicates; see
+/// `check_conditional_reduction()` for the others.
+pub fn check_rewrite_rule(rule: &RewriteRule, formulas: &[Expr], config: &Config) -> Result<usize, SoundnessError> {
+    for (lhs, rhs) in &rule.reductions {
+        check_equivalence(lhs, rhs, config)?;
+    }
+    let mut checked = rule.reductions.len();
+    for reduction in &rule.conditional_reductions {
+        checked += check_conditional_reduction(reduction, formulas, config)?;
+    }
+    Ok(checked)
+}
+
+/// Check a rule on the `schema_instances()` of its schema and, if it rewrites with a `RewriteRule`, every
+/// reduction of that, returning how many steps and rewrites were checked. `formulas` should be small, since
+/// steps are made from every combination of them.
+pub fn check_rule(rule: Rule, formulas: &[Expr], config: &Config) -> Result<usize, SoundnessError> {
+    let mut checked = 0;
+    if let Some(rewrite_rule) = rule.rewrite_rule() {
+        checked += check_rewrite_rule(rewrite_rule, formulas, config)?;
+    }
+    if let Some(schema) = rule.get_schema() {
+        checked += check_instances(rule, schema_instances(&schema, formulas), config)?;
+    }
+    Ok(checked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+    use crate::plugins::RulePlugin;
+    use crate::rules::RuleClassification;
+
+    #[test]
+    fn test_countermodel() {
+        let config = Config::default();
+        assert_eq!(countermodel(&[p("A -> B"), p("A")], &p("B"), &config), Ok(None));
+        let valuation = [("A", vec![false]), ("B", vec![true])].into_iter().map(|(name, table)| (name.to_owned(), table)).collect();
+        assert_eq!(countermodel(&[p("A -> B"), p("B")], &p("A"), &config), Ok(Some(Countermodel::Valuation(valuation))));
+
+        assert_eq!(countermodel(&[p("exists x forall y R(x, y)")], &p("forall y exists x R(x, y)"), &config), Ok(None));
+        match countermodel(&[p("forall y exists x R(x, y)")], &p("exists x forall y R(x, y)"), &config) {
+            Ok(Some(Countermodel::Model(model))) => assert_eq!(model.domain, 2, "{model}"),
+            other => panic!("expected a two-element countermodel, got {other:?}"),
+        }
+        assert!(countermodel(&[p("forall x P(f(x))")], &p("P(f(a))"), &config).is_err());
+        assert!(countermodel(&[p("forall x P(x)")], &p("P"), &config).is_err());
+    }
+
+    #[test]
+    fn test_arithmetic_countermodel() {
+        let config = Config::default();
+        assert_eq!(countermodel(&[p("x < y"), p("y < z")], &p("x + 1 < z"), &config), Ok(None));
+        assert_eq!(countermodel(&[], &p("((x + y) × 2 ≤ (x × 2) + (y × 2)) & (x ≤ x × x | x < 1)"), &config), Ok(None));
+        assert_eq!(countermodel(&[p("A -> x < 3")], &p("~A | x ≤ 2"), &config), Ok(None));
+
+        let numbers = [("x", 0), ("y", 0)].into_iter().map(|(name, n)| (name.to_owned(), n)).collect();
+        assert_eq!(countermodel(&[p("x ≤ y")], &p("x < y"), &config), Ok(Some(Countermodel::Arithmetic { numbers, propositions: BTreeMap::new() })));
+        match countermodel(&[p("x + x ≤ 6")], &p("x < 3"), &config) {
+            Ok(Some(Countermodel::Arithmetic { numbers, .. })) => assert_eq!(numbers["x"], 3),
+            other => panic!("expected x = 3, got {other:?}"),
+        }
+
+        assert!(countermodel(&[p("forall n (0 ≤ n)")], &p("0 ≤ m"), &config).is_err());
+        assert!(countermodel(&[p("P(x)")], &p("x < x + 1"), &config).is_err());
+        assert!(countermodel(&[p("x")], &p("x < x + 1"), &config).is_err());
+        assert!(countermodel(&[], &p("18446744073709551615 + 1 ≤ 0"), &config).is_err());
+    }
+
+    #[test]
+    fn test_subproofs_generalize_arbitrary_constants() {
+        let forall_intro = Instance { deps: vec![], subproofs: vec![PluginSubproof { premises: vec![], lines: vec![p("P(a)")] }], conclusion: p("forall x P(x)") };
+        assert_eq!(forall_intro.antecedents(), vec![p("forall a P(a)")]);
+        assert_eq!(countermodel(&forall_intro.antecedents(), &forall_intro.conclusion, &Config::default()), Ok(None));
+
+        let escaped = Instance { deps: vec![p("exists x P(x)")], subproofs: vec![PluginSubproof { premises: vec![p("P(a)")], lines: vec![p("P(a)")] }], conclusion: p("P(a)") };
+        assert_eq!(escaped.antecedents(), vec![p("exists x P(x)"), p("P(a) -> P(a)")]);
+        assert!(matches!(countermodel(&escaped.antecedents(), &escaped.conclusion, &Config::default()), Ok(Some(_))));
+    }
+
+    #[test]
+    fn test_step_countermodel() {
+        let config = Config::default();
+        let mut prf = PooledProof::<HList![Expr]>::new();
+        let premise = Coproduct::inject(prf.add_premise(p("forall x exists y R(x, y)")));
+        let swapped = Coproduct::inject(prf.add_step(Justification(p("exists y forall x R(x, y)"), RuleM::ForallElim, vec![premise], vec![])));
+        let instance = prf.add_step(Justification(p("exists y R(a, y)"), RuleM::ExistsIntro, vec![premise], vec![]));
+        let equality = prf.add_premise(p("a = b"));
+        let symmetric = prf.add_step(Justification(p("b = a"), RuleM::ForallElim, vec![Coproduct::inject(equality)], vec![]));
+
+        let model = step_countermodel(&prf, &swapped, &config).expect("swapping quantifiers has a countermodel");
+        assert!(model.eval(&p("forall x exists y R(x, y)")) && !model.eval(&p("exists y forall x R(x, y)")), "{model}");
+        // Steps that follow from what they cite have none, even with the wrong rule
+        assert_eq!(step_countermodel(&prf, &Coproduct::inject(instance), &config), None);
+        assert_eq!(step_countermodel(&prf, &premise, &config), None);
+        // `=` isn't an arbitrary relation, so a model where `b = a` is false is no countermodel
+        assert_eq!(step_countermodel(&prf, &Coproduct::inject(symmetric), &config), None);
+    }
+
+    #[test]
+    fn test_builtin_rules_are_sound() {
+        let formulas = ["A", "B", "~A", "A & B", "A -> B"].map(p);
+        // Induction is only sound in the natural numbers, not in every model, set theory rules are only sound
+        // for what ∈ and ⊆ mean, rather than any relations, temporal rules only for what G, F, and U mean on traces,
+        // and Hoare logic rules only for what programs do
+        let interpreted = [RuleClassification::Induction, RuleClassification::SetTheory, RuleClassification::Temporal, RuleClassification::Hoare];
+        for &rule in RuleM::ALL_RULES.iter().filter(|rule| !interpreted.iter().any(|c| rule.get_classifications().contains(c))) {
+            match check_rule(rule, &formulas, &Config::default()) {
+                Ok(_) | Err(SoundnessError::Unsupported(_)) => {}
+                Err(err) => panic!("{}: {}", rule.get_name(), err),
+            }
+        }
+    }
+
+    /// Affirming the consequent, registered as a rule pack would
+    struct AffirmingTheConsequent;
+
+    impl RulePlugin for AffirmingTheConsequent {
+        fn name(&self) -> String {
+            "Affirming the Consequent".into()
+        }
+        fn serialized_name(&self) -> String {
+            "TEST_AFFIRMING_THE_CONSEQUENT".into()
+        }
+        fn schema(&self) -> Option<RuleSchema> {
+            Some(RuleSchema { deps: vec![p("P -> Q"), p("Q")], subdeps: vec![], conclusion: p("P") })
+        }
+        fn classifications(&self) -> HashSet<RuleClassification> {
+            [RuleClassification::Special].into_iter().collect()
+        }
+        fn num_deps(&self) -> Option<usize> {
+            Some(2)
+        }
+        fn num_subdeps(&self) -> Option<usize> {
+            Some(0)
+        }
+        fn check(&self, conclusion: &Expr, deps: &[Expr], _: &[PluginSubproof]) -> Result<(), String> {
+            match &deps[0] {
+                Expr::Impl { left, right } if **left == *conclusion && **right == deps[1] => Ok(()),
+                _ => Err("not of the form P → Q, Q ⊢ P".into()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsound_plugin_rule() {
+        let rule = crate::plugins::register(Box::new(AffirmingTheConsequent)).unwrap();
+        match check_rule(rule, &[p("A"), p("B")], &Config::default()) {
+            Err(SoundnessError::UnsoundStep { instance, .. }) => assert_eq!(instance.deps.len(), 2),
+            other => panic!("expected an unsound step, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,263 @@
+/*!
+# Generators for property-based testing
+
+[`proptest`] strategies for well-formed expressions and for proofs whose every
+line checks, so tests can state properties of all formulas and proofs rather
+than a few hand-written ones. Downstream crates get them with the `proptest`
+feature:
+
+```toml
+[dev-dependencies]
+aris = { path = "../aris", features = ["proptest"] }
+```
+
+`Expr` implements proptest's `Arbitrary`, so `any::<Expr>()` works too.
+
+```
+use aris::expr::Expr;
+use aris::parser::parse;
+use proptest::prelude::*;
+
+proptest!(|(expr in any::<Expr>())| {
+    prop_assert_eq!(parse(&expr.to_string()), Some(expr));
+});
+```
+
+[`proptest`]: https://docs.rs/proptest
+*/
+
+use crate::expr::Expr;
+use crate::expr::Op;
+use crate::expr::QuantKind;
+use crate::proofs::pooledproof::PooledProof;
+use crate::proofs::Justification;
+use crate::proofs::PjRef;
+use crate::proofs::Proof;
+use crate::rules::RuleM;
+
+use frunk_core::coproduct::Coproduct;
+use frunk_core::HList;
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+/// Variables of the formulas of `propositional_expr()`
+pub const PROPOSITIONAL_VARS: &[&str] = &["A", "B", "C"];
+
+/// Names of variables, functions, and bound variables. They're kept short, so
+/// none of them are keywords like `forall`.
+pub fn name() -> impl Strategy<Value = String> {
+    "[A-Za-z][A-Za-z0-9_]{0,3}"
+}
+
+/// Associative operators
+pub fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![Just(Op::And), Just(Op::Or), Just(Op::Bicon), Just(Op::Equiv), Just(Op::Add), Just(Op::Mult)]
+}
+
+/// Kinds of quantifiers
+pub fn quant_kind() -> impl Strategy<Value = QuantKind> {
+    prop_oneof![Just(QuantKind::Forall), Just(QuantKind::Exists)]
+}
+
+/// Expressions of every kind, shaped like the parser's results, so printing
+/// one and parsing it back gives the same expression: functions are
+/// variables, and associative operators have at least two operands.
+pub fn expr() -> impl Strategy<Value = Expr> {
+    let leaf = prop_oneof![Just(Expr::Contra), Just(Expr::Taut), name().prop_map(|name| Expr::Var { name })];
+    leaf.prop_recursive(4, 32, 3, |inner| prop_oneof![(name(), prop::collection::vec(inner.clone(), 0..3)).prop_map(|(func, args)| Expr::apply(Expr::var(&func), &args)), inner.clone().prop_map(|operand| Expr::Not { operand: Box::new(operand) }), (inner.clone(), inner.clone()).prop_map(|(left, right)| Expr::implies(left, right)), (op(), prop::collection::vec(inner.clone(), 2..4)).prop_map(|(op, exprs)| Expr::Assoc { op, exprs }), (quant_kind(), name(), inner).prop_map(|(kind, name, body)| Expr::Quant { kind, name, body: Box::new(body) })])
+}
+
+/// Quantifier-free formulas of `PROPOSITIONAL_VARS` with the connectives
+/// `Expr::eval()` knows, for comparing what rules accept with truth tables.
+/// Biconditionals have two operands, since longer ones are read differently
+/// by different rules.
+pub fn propositional_expr() -> impl Strategy<Value = Expr> {
+    let leaf = prop_oneof![Just(Expr::Contra), Just(Expr::Taut), prop::sample::select(PROPOSITIONAL_VARS).prop_map(Expr::var)];
+    leaf.prop_recursive(3, 16, 3, |inner| prop_oneof![inner.clone().prop_map(|operand| Expr::Not { operand: Box::new(operand) }), (inner.clone(), inner.clone()).prop_map(|(left, right)| Expr::implies(left, right)), (prop_oneof![Just(Op::And), Just(Op::Or)], prop::collection::vec(inner.clone(), 2..4)).prop_map(|(op, exprs)| Expr::Assoc { op, exprs }), (inner.clone(), inner).prop_map(|(left, right)| Expr::assoc(Op::Bicon, &[left, right]))])
+}
+
+impl Arbitrary for Expr {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Expr>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        expr().boxed()
+    }
+}
+
+/// A step to add to a proof built by `valid_proof()`. Steps that don't apply
+/// to the line they're given, like eliminating a conjunction from a line
+/// that isn't one, reiterate the line instead.
+#[derive(Clone, Debug)]
+enum Move {
+    Reiterate,
+    AndIntro(Index),
+    AndElim(Index),
+    OrIntro(Expr),
+    ImpElim,
+    /// Assume a formula in a subproof, reiterate the line in it, and conclude
+    /// that the formula implies the line
+    ImpIntro(Expr),
+}
+
+fn proof_move() -> impl Strategy<Value = (Index, Move)> {
+    let kind = prop_oneof![Just(Move::Reiterate), any::<Index>().prop_map(Move::AndIntro), any::<Index>().prop_map(Move::AndElim), propositional_expr().prop_map(Move::OrIntro), Just(Move::ImpElim), propositional_expr().prop_map(Move::ImpIntro)];
+    (any::<Index>(), kind)
+}
+
+type P = PooledProof<HList![Expr]>;
+
+/// Build a proof from premises by making moves on its lines
+fn build_proof(premises: Vec<Expr>, moves: Vec<(Index, Move)>) -> P {
+    let mut prf = P::new();
+    let mut lines = premises.into_iter().map(|premise| (Coproduct::inject(prf.add_premise(premise.clone())), premise)).collect::<Vec<(PjRef<P>, Expr)>>();
+    for (line, kind) in moves {
+        let (r, expr) = line.get(&lines).clone();
+        let reiterate = Justification(expr.clone(), RuleM::Reiteration, vec![r], vec![]);
+        let just = match kind {
+            Move::Reiterate => reiterate,
+            Move::AndIntro(other) => {
+                let (other_r, other_expr) = other.get(&lines).clone();
+                if other_r == r {
+                    reiterate
+                } else {
+                    Justification(Expr::assoc(Op::And, &[expr, other_expr]), RuleM::AndIntro, vec![r, other_r], vec![])
+                }
+            }
+            Move::AndElim(conjunct) => match &expr {
+                // ∧E reads a conjunction it concludes as several conjuncts of
+                // the line it cites, so a conjunct that's one itself is only
+                // concluded by reiterating
+                Expr::Assoc { op: Op::And, exprs } => match conjunct.get(exprs) {
+                    Expr::Assoc { op: Op::And, .. } => reiterate,
+                    conjunct => Justification(conjunct.clone(), RuleM::AndElim, vec![r], vec![]),
+                },
+                _ => reiterate,
+            },
+            Move::OrIntro(other) => Justification(Expr::assoc(Op::Or, &[expr, other]), RuleM::OrIntro, vec![r], vec![]),
+            Move::ImpElim => {
+                let antecedent = match &expr {
+                    Expr::Impl { left, .. } => lines.iter().find(|(_, line)| line == &**left),
+                    _ => None,
+                };
+                match (&expr, antecedent) {
+                    (Expr::Impl { right, .. }, Some((antecedent, _))) => Justification((**right).clone(), RuleM::ImpElim, vec![r, *antecedent], vec![]),
+                    _ => reiterate,
+                }
+            }
+            Move::ImpIntro(assumption) => {
+                let sr = prf.add_subproof();
+                prf.with_mut_subproof(&sr, |sub| {
+                    sub.add_premise(assumption.clone());
+                    sub.add_step(reiterate);
+                });
+                Justification(Expr::implies(assumption, expr), RuleM::ImpIntro, vec![], vec![sr])
+            }
+        };
+        let conclusion = just.0.clone();
+        lines.push((Coproduct::inject(prf.add_step(just)), conclusion));
+    }
+    prf
+}
+
+/// Proofs of `propositional_expr()` formulas whose every line is correct,
+/// built from one to three premises with introduction and elimination rules,
+/// and subproofs for conditional introduction
+pub fn valid_proof() -> impl Strategy<Value = P> {
+    (prop::collection::vec(propositional_expr(), 1..4), prop::collection::vec(proof_move(), 0..8)).prop_map(|(premises, moves)| build_proof(premises, moves))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use crate::proofs::clipboard::ClipboardProof;
+    use crate::proofs::diff::proof_lines;
+    use crate::proofs::xml_interop;
+    use crate::rules::RuleClassification;
+    use crate::rules::RuleT;
+
+    use std::collections::HashMap;
+
+    /// Whether `conclusion` is true in every row of the truth table where all
+    /// of `deps` are
+    fn entails(deps: &[Expr], conclusion: &Expr) -> bool {
+        (0..1 << PROPOSITIONAL_VARS.len()).all(|row: usize| {
+            let env = PROPOSITIONAL_VARS.iter().enumerate().map(|(i, var)| (var.to_string(), vec![row & (1 << i) != 0])).collect::<HashMap<String, Vec<bool>>>();
+            !deps.iter().all(|dep| dep.eval(&env)) || conclusion.eval(&env)
+        })
+    }
+
+    /// Lines to cite and a conclusion to draw from them, which is often
+    /// related to them, so some rules accept it
+    fn deps_and_conclusion() -> impl Strategy<Value = (Vec<Expr>, Expr)> {
+        prop::collection::vec(propositional_expr(), 1..4).prop_flat_map(|deps| {
+            let dep = prop::sample::select(deps.clone());
+            let conclusion = prop_oneof![propositional_expr(), dep.clone(), dep.clone().prop_map(|dep| Expr::Not { operand: Box::new(dep) }), (dep.clone(), dep.clone()).prop_map(|(left, right)| Expr::assoc(Op::And, &[left, right])), (dep, propositional_expr()).prop_map(|(left, right)| Expr::assoc(Op::Or, &[left, right]))];
+            (Just(deps), conclusion)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_display_roundtrip(expr in any::<Expr>()) {
+            prop_assert_eq!(parser::parse(&expr.to_string()), Some(expr));
+        }
+
+        #[test]
+        fn test_serde_roundtrip(expr in any::<Expr>()) {
+            let json = serde_json::to_string(&expr).unwrap();
+            prop_assert_eq!(serde_json::from_str::<Expr>(&json).unwrap(), expr);
+        }
+
+        #[test]
+        fn test_valid_proofs_check(prf in valid_proof()) {
+            for line in prf.exprs() {
+                prop_assert_eq!(prf.verify_line(&line), Ok(()), "{}", prf);
+            }
+        }
+
+        #[test]
+        fn test_xml_roundtrip(prf in valid_proof()) {
+            let meta = xml_interop::ProofMetaData::default();
+            let mut data = vec![];
+            xml_interop::xml_from_proof_and_metadata(&prf, &meta, &mut data).unwrap();
+            let (reloaded, _) = xml_interop::proof_from_xml::<P, _>(&data[..]).unwrap();
+            prop_assert_eq!(proof_lines(&reloaded), proof_lines(&prf));
+        }
+
+        #[test]
+        fn test_clipboard_roundtrip(prf in valid_proof()) {
+            let fragment = ClipboardProof::from_proof(&prf);
+            prop_assert_eq!(ClipboardProof::from_json(&fragment.to_json()), Ok(fragment.clone()));
+            prop_assert_eq!(proof_lines(&fragment.to_proof::<P>()), proof_lines(&prf));
+        }
+
+        #[test]
+        fn test_valid_proof_steps_follow(prf in valid_proof()) {
+            for jr in prf.direct_lines() {
+                let Justification(conclusion, rule, deps, sdeps) = prf.lookup_step(&jr).unwrap();
+                if sdeps.is_empty() {
+                    let deps = deps.iter().map(|dep| prf.lookup_expr(dep).unwrap()).collect::<Vec<Expr>>();
+                    prop_assert!(entails(&deps, &conclusion), "{} concluded {} from {:?}", rule.get_name(), conclusion, deps);
+                }
+            }
+        }
+
+        /// Every rule that doesn't cite subproofs or deal with quantifiers
+        /// only accepts conclusions that follow from the lines cited
+        #[test]
+        fn test_rules_are_sound((deps, conclusion) in deps_and_conclusion()) {
+            let mut prf = P::new();
+            let refs = deps.iter().map(|dep| Coproduct::inject(prf.add_premise(dep.clone()))).collect::<Vec<PjRef<P>>>();
+            let step = prf.add_step(Justification(conclusion.clone(), RuleM::EmptyRule, refs, vec![]));
+            let quantified = [RuleClassification::QuantifierInference, RuleClassification::QuantifierEquivalence, RuleClassification::Induction];
+            for &rule in RuleM::ALL_RULES.iter().filter(|rule| rule.num_subdeps() == Some(0) && !quantified.iter().any(|c| rule.get_classifications().contains(c))) {
+                prf.with_mut_step(&step, |just| just.1 = rule);
+                if prf.verify_line(&Coproduct::inject(step)).is_ok() {
+                    prop_assert!(entails(&deps, &conclusion), "{} concluded {} from {:?}", rule.get_name(), conclusion, deps);
+                }
+            }
+        }
+    }
+}
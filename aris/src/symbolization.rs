@@ -0,0 +1,136 @@
+/*!
+# Symbolization exercises
+
+A symbolization exercise gives an English sentence and a key saying what each
+symbol means, and asks for a formula saying the same thing. Answers are graded
+up to logical equivalence with the expected formula, as `crate::equivalence`
+decides it, so `¬(P ∧ Q)` is as good an answer as
+`¬P ∨ ¬Q`. Wrong answers are graded with a case telling them apart from the
+sentence, which is usually the most useful thing to show a student.
+
+```
+use aris::parser::parse_unwrap as p;
+use aris::soundness::Config;
+use aris::symbolization::{Grade, Symbolization};
+
+let exercise = Symbolization {
+    sentence: "It rains only if the ground is wet.".into(),
+    key: [("R".into(), "it rains".into()), ("W".into(), "the ground is wet".into())].into_iter().collect(),
+    expected: p("R -> W"),
+};
+assert_eq!(exercise.grade(&p("~W -> ~R"), &Config::default()), Grade::Correct);
+// The converse is true when the ground is wet but it doesn't rain
+assert!(matches!(exercise.grade(&p("W -> R"), &Config::default()), Grade::Different(_)));
+assert!(matches!(exercise.grade(&p("R -> S"), &Config::default()), Grade::UnknownSymbols(_)));
+```
+*/
+
+use crate::equivalence;
+use crate::equivalence::Equivalence;
+use crate::expr::free_vars;
+use crate::expr::Expr;
+use crate::soundness::Config;
+use crate::soundness::Countermodel;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A sentence to symbolize, with what its symbols mean and a formula for it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbolization {
+    pub sentence: String,
+    /// Meanings of the symbols answers may use, like `"R"` for `"it rains"`.
+    /// Answers may use any symbols if this is empty.
+    pub key: BTreeMap<String, String>,
+    /// A correct answer; any formula equivalent to it is correct too
+    pub expected: Expr,
+}
+
+/// How an answer to a symbolization exercise compares to the expected formula
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Grade {
+    /// The answer is equivalent to the expected formula
+    Correct,
+    /// The answer implies the expected formula, but is false in this case
+    /// where the expected formula is true
+    TooStrong(Countermodel),
+    /// The expected formula implies the answer, but the answer is true in this
+    /// case where the expected formula is false
+    TooWeak(Countermodel),
+    /// Neither implies the other; the answer is true in this case where the
+    /// expected formula is false
+    Different(Countermodel),
+    /// The answer uses symbols the key doesn't have, in order
+    UnknownSymbols(Vec<String>),
+    /// The formulas couldn't be compared, for this reason
+    Unchecked(String),
+}
+
+impl Grade {
+    pub fn is_correct(&self) -> bool {
+        matches!(self, Grade::Correct)
+    }
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Grade::Correct => write!(f, "This says the same thing as the sentence."),
+            Grade::TooStrong(countermodel) => write!(f, "This says too much: the sentence is true where {countermodel}, but this isn't."),
+            Grade::TooWeak(countermodel) => write!(f, "This doesn't say enough: it's true where {countermodel}, but the sentence isn't."),
+            Grade::Different(countermodel) => write!(f, "This is true where {countermodel}, but the sentence isn't."),
+            Grade::UnknownSymbols(names) => write!(f, "The key doesn't say what {} means.", names.join(", ")),
+            Grade::Unchecked(reason) => write!(f, "This couldn't be compared to the sentence, since {reason}."),
+        }
+    }
+}
+
+impl Symbolization {
+    /// Grade an answer, trying the models `config` allows for a case where it
+    /// and the expected formula differ
+    pub fn grade(&self, answer: &Expr, config: &Config) -> Grade {
+        if !self.key.is_empty() {
+            let mut unknown = free_vars(answer).into_iter().filter(|name| !self.key.contains_key(name)).collect::<Vec<_>>();
+            if !unknown.is_empty() {
+                unknown.sort();
+                return Grade::UnknownSymbols(unknown);
+            }
+        }
+        let (countermodel, answer_holds) = match equivalence::are_equivalent_within(answer, &self.expected, config) {
+            Equivalence::Equivalent | Equivalence::NoCounterexample => return Grade::Correct,
+            Equivalence::Inequivalent { countermodel, first_holds } => (countermodel, first_holds),
+            Equivalence::Unknown(reason) => return Grade::Unchecked(reason),
+        };
+        // Whether the answer is too strong or too weak depends on whether it
+        // differs from the sentence the other way too
+        let (premise, conclusion) = if answer_holds { (&self.expected, answer) } else { (answer, &self.expected) };
+        match equivalence::counterexample(premise, conclusion, config) {
+            Ok(None) if answer_holds => Grade::TooWeak(countermodel),
+            Ok(None) => Grade::TooStrong(countermodel),
+            Ok(Some(_)) if answer_holds => Grade::Different(countermodel),
+            Ok(Some(other)) => Grade::Different(other),
+            Err(reason) => Grade::Unchecked(reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+
+    #[test]
+    fn test_grade() {
+        let config = Config::default();
+        let exercise = Symbolization { sentence: "Every dog barks.".into(), key: BTreeMap::new(), expected: p("forall x (D(x) -> B(x))") };
+        assert_eq!(exercise.grade(&p("~exists x (D(x) & ~B(x))"), &config), Grade::Correct);
+        assert!(matches!(exercise.grade(&p("forall x (D(x) & B(x))"), &config), Grade::TooStrong(_)));
+        assert!(matches!(exercise.grade(&p("exists x (D(x) -> B(x))"), &config), Grade::TooWeak(_)));
+        assert!(matches!(exercise.grade(&p("forall x (B(x) -> D(x))"), &config), Grade::Different(_)));
+        assert!(matches!(exercise.grade(&p("P + Q"), &config), Grade::Unchecked(_)));
+
+        let exercise = Symbolization { key: [("P".to_string(), "it's sunny".to_string())].into_iter().collect(), ..exercise };
+        assert_eq!(exercise.grade(&p("Q | (R & P)"), &config), Grade::UnknownSymbols(vec!["Q".into(), "R".into()]));
+        assert_eq!(Grade::UnknownSymbols(vec!["Q".into()]).to_string(), "The key doesn't say what Q means.");
+    }
+}
@@ -0,0 +1,288 @@
+/*!
+# Linear temporal logic over bounded traces
+
+Formulas of linear temporal logic are about traces, infinite sequences of
+states in which some propositions are true. With the parser's logic mode set to
+`LogicMode::Temporal`, they're written with `G φ` (`φ` is true from now on),
+`F φ` (`φ` is true now or later), `X φ` (`φ` is true in the next state), and
+`φ U ψ` (`ψ` is true at some point, and `φ` is until then). A formula is true
+of a trace if it's true in its first state.
+
+This module checks formulas on traces that are lassos: a few states, after
+which the trace loops back to one of them forever. Every satisfiable formula is
+true of some lasso, but not necessarily a short one, so searching the lassos up
+to a length can find counterexamples to a consequence without proving that
+there are none.
+
+```
+use aris::parser::{parse_unwrap as p, with_syntax, LogicMode, Syntax};
+use aris::temporal;
+
+with_syntax(&Syntax { logic_mode: LogicMode::Temporal, ..Syntax::default() }, || {
+    assert_eq!(temporal::countertrace(&[p("G (p -> X p)"), p("p")], &p("G p"), 3), Ok(None));
+
+    // Something happening eventually doesn't mean it keeps happening
+    let trace = temporal::countertrace(&[p("F p")], &p("F G p"), 3).unwrap().unwrap();
+    assert_eq!(temporal::eval(&p("F G p"), &trace), Ok(false));
+});
+```
+*/
+
+use crate::expr::Expr;
+use crate::expr::Op;
+use crate::expr::ALWAYS;
+use crate::expr::EVENTUALLY;
+use crate::expr::NEXT;
+use crate::expr::UNTIL;
+use crate::proofs::PjRef;
+use crate::proofs::Proof;
+use crate::soundness::Instance;
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Longest traces searched for counterexamples by default
+pub const DEFAULT_MAX_TRACE_LEN: usize = 3;
+
+/// A trace that's a lasso: its states, after which it loops back to the state
+/// at `loop_start` forever
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace {
+    /// The propositions true in each state
+    pub states: Vec<BTreeSet<String>>,
+    /// The index of the state that follows the last one
+    pub loop_start: usize,
+}
+
+impl Trace {
+    /// The index of the state after the one at `i`
+    fn successor(&self, i: usize) -> usize {
+        if i + 1 < self.states.len() {
+            i + 1
+        } else {
+            self.loop_start
+        }
+    }
+
+    /// The indices of the states from the one at `i` on, as far as the first
+    /// repeated one, which covers every state the trace reaches from there
+    fn from(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(Some(i), move |&j| Some(self.successor(j))).take(self.states.len())
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, state) in self.states.iter().enumerate() {
+            let propositions = state.iter().map(String::as_str).collect::<Vec<_>>().join(", ");
+            write!(f, "{i}: {{{propositions}}} → ")?;
+        }
+        write!(f, "back to {}", self.loop_start)
+    }
+}
+
+/// The propositions of a formula of linear temporal logic, or why it isn't one
+fn propositions(e: &Expr, found: &mut BTreeSet<String>) -> Result<(), String> {
+    match e {
+        Expr::Contra | Expr::Taut => Ok(()),
+        Expr::Var { name } => {
+            found.insert(name.clone());
+            Ok(())
+        }
+        Expr::Apply { func, args } => match (&**func, args.as_slice()) {
+            (Expr::Var { name }, [operand]) if [ALWAYS, EVENTUALLY, NEXT].contains(&name.as_str()) => propositions(operand, found),
+            (Expr::Var { name }, [left, right]) if name == UNTIL => {
+                propositions(left, found)?;
+                propositions(right, found)
+            }
+            _ => Err(format!("{e} isn't a temporal operator applied to formulas")),
+        },
+        Expr::Not { operand } => propositions(operand, found),
+        Expr::Impl { left, right } => {
+            propositions(left, found)?;
+            propositions(right, found)
+        }
+        Expr::Assoc { op: Op::Add | Op::Mult, .. } => Err(format!("{e} uses arithmetic")),
+        Expr::Assoc { exprs, .. } => exprs.iter().try_for_each(|e| propositions(e, found)),
+        Expr::Quant { .. } => Err(format!("{e} binds a variable, and only propositional temporal logic is supported")),
+    }
+}
+
+/// Whether a formula is true at the state at `i` of a trace, once it's known to
+/// be a formula of linear temporal logic
+fn eval_at(e: &Expr, trace: &Trace, i: usize) -> bool {
+    match e {
+        Expr::Contra => false,
+        Expr::Taut => true,
+        Expr::Var { name } => trace.states[i].contains(name),
+        Expr::Apply { func, args } => match (&**func, args.as_slice()) {
+            (Expr::Var { name }, [operand]) if name == ALWAYS => trace.from(i).all(|j| eval_at(operand, trace, j)),
+            (Expr::Var { name }, [operand]) if name == EVENTUALLY => trace.from(i).any(|j| eval_at(operand, trace, j)),
+            (Expr::Var { name }, [operand]) if name == NEXT => eval_at(operand, trace, trace.successor(i)),
+            (Expr::Var { name }, [left, right]) if name == UNTIL => {
+                for j in trace.from(i) {
+                    if eval_at(right, trace, j) {
+                        return true;
+                    }
+                    if !eval_at(left, trace, j) {
+                        return false;
+                    }
+                }
+                // The trace came back around without `right` ever being true
+                false
+            }
+            _ => unreachable!("checked by `propositions`"),
+        },
+        Expr::Not { operand } => !eval_at(operand, trace, i),
+        Expr::Impl { left, right } => !eval_at(left, trace, i) || eval_at(right, trace, i),
+        Expr::Assoc { op, exprs } => {
+            let mut values = exprs.iter().map(|e| eval_at(e, trace, i));
+            match op {
+                Op::And => values.all(|v| v),
+                Op::Or => values.any(|v| v),
+                // Chains fold the same way as in `Expr::eval`
+                Op::Bicon | Op::Equiv => values.fold(true, |acc, v| acc == v),
+                Op::Add | Op::Mult => unreachable!("checked by `propositions`"),
+            }
+        }
+        Expr::Quant { .. } => unreachable!("checked by `propositions`"),
+    }
+}
+
+/// Whether a formula of linear temporal logic is true of a trace, or why it
+/// can't be evaluated
+pub fn eval(e: &Expr, trace: &Trace) -> Result<bool, String> {
+    propositions(e, &mut BTreeSet::new())?;
+    if trace.states.is_empty() || trace.loop_start >= trace.states.len() {
+        return Err("A trace needs a state to loop back to".into());
+    }
+    Ok(eval_at(e, trace, 0))
+}
+
+/// The propositions of some formulas of linear temporal logic, in order
+fn all_propositions<'a>(formulas: impl IntoIterator<Item = &'a Expr>) -> Result<Vec<String>, String> {
+    let mut names = BTreeSet::new();
+    for e in formulas {
+        propositions(e, &mut names)?;
+    }
+    Ok(names.into_iter().collect())
+}
+
+/// How many traces `countertrace` may try for the formulas, which grows
+/// exponentially with the number of propositions and the length of the traces
+pub fn search_size(formulas: &[Expr], max_len: usize) -> Result<u64, String> {
+    let names = all_propositions(formulas)?.len();
+    Ok((1..=max_len).map(|len| if names * len >= 64 { u64::MAX } else { (len as u64).saturating_mul(1 << (names * len)) }).fold(0, u64::saturating_add))
+}
+
+/// Find a trace of at most `max_len` states where all the premises are true
+/// and the conclusion is false, trying shorter traces first, or say why the
+/// formulas can't be checked
+pub fn countertrace(premises: &[Expr], conclusion: &Expr, max_len: usize) -> Result<Option<Trace>, String> {
+    let names = all_propositions(premises.iter().chain(std::iter::once(conclusion)))?;
+    for len in 1..=max_len {
+        let bits = names.len() * len;
+        if bits >= 64 {
+            return Err(format!("There are too many propositions to try every trace of {len} states"));
+        }
+        for loop_start in 0..len {
+            for valuation in 0..1u64 << bits {
+                let states = (0..len).map(|i| names.iter().enumerate().filter(|(k, _)| valuation & (1 << (i * names.len() + k)) != 0).map(|(_, name)| name.clone()).collect()).collect();
+                let trace = Trace { states, loop_start };
+                if premises.iter().all(|e| eval_at(e, &trace, 0)) && !eval_at(conclusion, &trace, 0) {
+                    return Ok(Some(trace));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Search the traces of at most `max_len` states for one where the lines a step
+/// cites are true and its conclusion is false, which shows the step doesn't
+/// follow from what it cites, whichever rule it uses. Premises, steps citing
+/// subproofs, steps that aren't in linear temporal logic, and steps with more
+/// than `max_traces` traces to try get `None`.
+pub fn step_countertrace<P: Proof>(prf: &P, r: &PjRef<P>, max_len: usize, max_traces: u64) -> Option<Trace> {
+    let instance = Instance::of_step(prf, r)?;
+    if !instance.subproofs.is_empty() {
+        return None;
+    }
+    let formulas = instance.deps.iter().chain(std::iter::once(&instance.conclusion)).cloned().collect::<Vec<_>>();
+    if search_size(&formulas, max_len).ok()? > max_traces {
+        return None;
+    }
+    countertrace(&instance.deps, &instance.conclusion, max_len).ok()?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::parser::{parse_unwrap as p, with_syntax, LogicMode, Syntax};
+    use crate::proofs::pooledproof::PooledProof;
+    use crate::proofs::Justification;
+    use crate::rules::RuleM;
+
+    use frunk_core::coproduct::Coproduct;
+    use frunk_core::HList;
+
+    #[test]
+    fn test_eval() {
+        with_syntax(&Syntax { logic_mode: LogicMode::Temporal, ..Syntax::default() }, || {
+            let state = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<BTreeSet<_>>();
+            // p, then q, then r forever
+            let trace = Trace { states: vec![state(&["p"]), state(&["q"]), state(&["r"])], loop_start: 2 };
+            for (formula, expected) in [("p", true), ("X q", true), ("X X X r", true), ("p U q", true), ("p U r", false), ("F G r", true), ("G F p", false), ("~q U (q & X r)", true), ("G (q -> X G ~q)", true)] {
+                assert_eq!(eval(&p(formula), &trace), Ok(expected), "{formula}");
+            }
+            // Looping back to the start makes p happen infinitely often
+            let trace = Trace { loop_start: 0, ..trace };
+            assert_eq!(eval(&p("G F p & ~F G r"), &trace), Ok(true));
+            assert!(eval(&p("forall x P(x)"), &trace).is_err());
+            assert!(eval(&p("p"), &Trace { states: vec![], loop_start: 0 }).is_err());
+            assert_eq!(trace.to_string(), "0: {p} → 1: {q} → 2: {r} → back to 0");
+            assert_eq!(search_size(&[p("G (p -> q)"), p("G p")], 2), Ok(4 + 2 * 16));
+            assert_eq!(search_size(&[p("p")], 100), Ok(u64::MAX));
+        });
+    }
+
+    #[test]
+    fn test_countertrace() {
+        with_syntax(&Syntax { logic_mode: LogicMode::Temporal, ..Syntax::default() }, || {
+            for (premises, conclusion) in [(&["G p"][..], "p & X G p"), (&["p U q"], "F q"), (&["~F p"], "G ~p"), (&["G (p -> q)", "G p"], "G q")] {
+                let premises = premises.iter().map(|e| p(e)).collect::<Vec<_>>();
+                assert_eq!(countertrace(&premises, &p(conclusion), DEFAULT_MAX_TRACE_LEN), Ok(None), "{conclusion}");
+            }
+            for (premises, conclusion) in [(&["F p"][..], "G p"), (&["G F p"], "F G p"), (&["p"], "X p"), (&["F p", "F q"], "F (p & q)")] {
+                let premises = premises.iter().map(|e| p(e)).collect::<Vec<_>>();
+                let trace = countertrace(&premises, &p(conclusion), DEFAULT_MAX_TRACE_LEN).unwrap().unwrap_or_else(|| panic!("{conclusion}"));
+                assert!(premises.iter().all(|e| eval(e, &trace) == Ok(true)));
+                assert_eq!(eval(&p(conclusion), &trace), Ok(false));
+            }
+            // Every combination of p and q happening eventually needs 4 states
+            let conclusion = p("~(F (p & ~q) & F (~p & q) & F (p & q) & F (~p & ~q))");
+            assert_eq!(countertrace(&[], &conclusion, 3), Ok(None));
+            let trace = countertrace(&[], &conclusion, 4).unwrap().unwrap();
+            assert_eq!(eval(&conclusion, &trace), Ok(false));
+        });
+    }
+
+    #[test]
+    fn test_step_countertrace() {
+        with_syntax(&Syntax { logic_mode: LogicMode::Temporal, ..Syntax::default() }, || {
+            let mut prf = PooledProof::<HList![Expr]>::new();
+            let premise = Coproduct::inject(prf.add_premise(p("F p")));
+            let always = Coproduct::inject(prf.add_step(Justification(p("G p"), RuleM::AlwaysElim, vec![premise], vec![])));
+            let eventually = prf.add_step(Justification(p("F (p | q)"), RuleM::EventuallyIntro, vec![premise], vec![]));
+
+            let trace = step_countertrace(&prf, &always, DEFAULT_MAX_TRACE_LEN, u64::MAX).expect("F p doesn't mean G p");
+            assert_eq!((eval(&p("F p"), &trace), eval(&p("G p"), &trace)), (Ok(true), Ok(false)), "{trace}");
+            // Steps that follow from what they cite have none, even with the wrong rule
+            assert_eq!(step_countertrace(&prf, &Coproduct::inject(eventually), DEFAULT_MAX_TRACE_LEN, u64::MAX), None);
+            assert_eq!(step_countertrace(&prf, &premise, DEFAULT_MAX_TRACE_LEN, u64::MAX), None);
+            // Nor do steps with too many traces to try
+            assert_eq!(step_countertrace(&prf, &always, DEFAULT_MAX_TRACE_LEN, 10), None);
+        });
+    }
+}
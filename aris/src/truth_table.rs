@@ -0,0 +1,274 @@
+/*!
+# Truth tables of propositional formulas
+
+A `TruthTable` is a column of truth values, one for each assignment to some
+variables. Rows are in the usual textbook order: the first row makes every
+variable true, and the first variable changes slowest.
+
+Besides checking formulas against tables, this is what truth-functional
+completeness exercises are checked with: students are given a table, and maybe
+a set of connectives they may use, and have to write a formula with that table.
+
+```
+use aris::parser::parse_unwrap as p;
+use aris::truth_table::{Connective, TruthTable};
+
+let xor = TruthTable::parse(vec!["P".into(), "Q".into()], "FTTF").unwrap();
+assert_eq!(xor.mismatched_rows(&p("~(P <-> Q)")), Ok(vec![]));
+// P ∨ Q is wrong when both are true, in the first row
+assert_eq!(xor.mismatched_rows(&p("P | Q")), Ok(vec![0]));
+assert_eq!(Connective::used_in(&p("~(P <-> Q)")), Ok([Connective::Not, Connective::Bicon].into_iter().collect()));
+```
+*/
+
+use crate::expr::Expr;
+use crate::expr::Op;
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Most variables a table can have, which keeps tables small enough to show
+pub const MAX_VARIABLES: usize = 8;
+
+/// Truth values of a formula for every assignment to some variables
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TruthTable {
+    variables: Vec<String>,
+    values: Vec<bool>,
+}
+
+impl TruthTable {
+    /// A table with a value for each of the `2^n` rows of `n` variables
+    pub fn new(variables: Vec<String>, values: Vec<bool>) -> Result<Self, String> {
+        if variables.len() > MAX_VARIABLES {
+            return Err(format!("A truth table can have at most {MAX_VARIABLES} variables"));
+        }
+        if let Some(name) = variables.iter().enumerate().find(|(i, name)| variables[..*i].contains(name)).map(|(_, name)| name) {
+            return Err(format!("{name} is listed twice"));
+        }
+        if values.len() != 1 << variables.len() {
+            return Err(format!("A truth table of {} variables has {} rows, not {}", variables.len(), 1 << variables.len(), values.len()));
+        }
+        Ok(TruthTable { variables, values })
+    }
+
+    /// A table with its column written as `T`s and `F`s, or `1`s and `0`s,
+    /// from the first row to the last
+    pub fn parse(variables: Vec<String>, column: &str) -> Result<Self, String> {
+        let values = column
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| match c {
+                'T' | 't' | '1' => Ok(true),
+                'F' | 'f' | '0' => Ok(false),
+                _ => Err(format!("{c:?} isn't a truth value")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(variables, values)
+    }
+
+    /// The table of a formula over `variables`, which have to include every
+    /// variable of the formula
+    pub fn of(formula: &Expr, variables: Vec<String>) -> Result<Self, String> {
+        let values = (0..1 << variables.len()).map(|row| eval(formula, &assignment(&variables, row))).collect::<Result<Vec<_>, _>>()?;
+        Self::new(variables, values)
+    }
+
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    pub fn values(&self) -> &[bool] {
+        &self.values
+    }
+
+    /// The value of each variable in a row
+    pub fn row(&self, row: usize) -> Vec<bool> {
+        (0..self.variables.len()).map(|k| row_value(self.variables.len(), row, k)).collect()
+    }
+
+    /// The rows where a formula's value differs from the table's, in order,
+    /// or why the formula can't be tabulated over the table's variables
+    pub fn mismatched_rows(&self, formula: &Expr) -> Result<Vec<usize>, String> {
+        let table = Self::of(formula, self.variables.clone())?;
+        Ok((0..self.values.len()).filter(|&row| table.values[row] != self.values[row]).collect())
+    }
+
+    /// The column written as `T`s and `F`s, as `parse` reads it
+    pub fn column(&self) -> String {
+        self.values.iter().map(|&value| if value { 'T' } else { 'F' }).collect()
+    }
+}
+
+/// The value of the `k`th of `n` variables in a row
+fn row_value(n: usize, row: usize, k: usize) -> bool {
+    (row >> (n - 1 - k)) & 1 == 0
+}
+
+fn assignment(variables: &[String], row: usize) -> HashMap<&str, bool> {
+    variables.iter().enumerate().map(|(k, name)| (name.as_str(), row_value(variables.len(), row, k))).collect()
+}
+
+/// Value of a propositional formula under an assignment, or why it doesn't
+/// have one
+fn eval(e: &Expr, assignment: &HashMap<&str, bool>) -> Result<bool, String> {
+    match e {
+        Expr::Contra => Ok(false),
+        Expr::Taut => Ok(true),
+        Expr::Var { name } => assignment.get(name.as_str()).copied().ok_or_else(|| format!("{name} isn't one of the table's variables")),
+        Expr::Apply { .. } => Err(format!("{e} isn't propositional")),
+        Expr::Not { operand } => Ok(!eval(operand, assignment)?),
+        Expr::Impl { left, right } => Ok(!eval(left, assignment)? || eval(right, assignment)?),
+        Expr::Assoc { op: Op::Add | Op::Mult, .. } => Err(format!("{e} uses arithmetic")),
+        Expr::Assoc { op, exprs } => {
+            let values = exprs.iter().map(|e| eval(e, assignment)).collect::<Result<Vec<_>, _>>()?;
+            Ok(match op {
+                Op::And => values.into_iter().all(|v| v),
+                Op::Or => values.into_iter().any(|v| v),
+                // Chains fold the same way as in `Expr::eval`
+                Op::Bicon | Op::Equiv => values.into_iter().fold(true, |acc, v| acc == v),
+                Op::Add | Op::Mult => unreachable!("arithmetic was ruled out above"),
+            })
+        }
+        Expr::Quant { .. } => Err(format!("{e} has a quantifier")),
+    }
+}
+
+/// A connective of propositional logic, for restricting which ones an answer
+/// may use
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Connective {
+    Not,
+    And,
+    Or,
+    Implies,
+    Bicon,
+    Equiv,
+    Taut,
+    Contra,
+}
+
+impl Connective {
+    /// The connectives a formula uses, or why it isn't propositional
+    pub fn used_in(e: &Expr) -> Result<BTreeSet<Connective>, String> {
+        fn aux(e: &Expr, found: &mut BTreeSet<Connective>) -> Result<(), String> {
+            match e {
+                Expr::Contra => {
+                    found.insert(Connective::Contra);
+                }
+                Expr::Taut => {
+                    found.insert(Connective::Taut);
+                }
+                Expr::Var { .. } => {}
+                Expr::Not { operand } => {
+                    found.insert(Connective::Not);
+                    aux(operand, found)?;
+                }
+                Expr::Impl { left, right } => {
+                    found.insert(Connective::Implies);
+                    aux(left, found)?;
+                    aux(right, found)?;
+                }
+                Expr::Assoc { op, exprs } => {
+                    found.insert(match op {
+                        Op::And => Connective::And,
+                        Op::Or => Connective::Or,
+                        Op::Bicon => Connective::Bicon,
+                        Op::Equiv => Connective::Equiv,
+                        Op::Add | Op::Mult => return Err(format!("{e} uses arithmetic")),
+                    });
+                    exprs.iter().try_for_each(|e| aux(e, found))?;
+                }
+                Expr::Apply { .. } | Expr::Quant { .. } => return Err(format!("{e} isn't propositional")),
+            }
+            Ok(())
+        }
+        let mut found = BTreeSet::new();
+        aux(e, &mut found)?;
+        Ok(found)
+    }
+
+    /// The symbol of the connective, as formulas are displayed
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Connective::Not => "¬",
+            Connective::And => "∧",
+            Connective::Or => "∨",
+            Connective::Implies => "→",
+            Connective::Bicon => "↔",
+            Connective::Equiv => "≡",
+            Connective::Taut => "⊤",
+            Connective::Contra => "⊥",
+        }
+    }
+}
+
+impl fmt::Display for Connective {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// Connectives are read from their symbols, or from the ASCII the parser
+/// accepts for them
+impl FromStr for Connective {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        Ok(match s.trim() {
+            "¬" | "~" => Connective::Not,
+            "∧" | "&" => Connective::And,
+            "∨" | "|" => Connective::Or,
+            "→" | "->" => Connective::Implies,
+            "↔" | "<->" => Connective::Bicon,
+            "≡" | "===" => Connective::Equiv,
+            "⊤" | "^|^" => Connective::Taut,
+            "⊥" | "_|_" => Connective::Contra,
+            other => return Err(format!("{other:?} isn't a connective")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_unwrap as p;
+
+    fn vars(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_truth_table() {
+        let table = TruthTable::of(&p("P -> Q"), vars(&["P", "Q"])).unwrap();
+        assert_eq!(table.column(), "TFTT");
+        assert_eq!(table.row(1), vec![true, false]);
+        assert_eq!(TruthTable::parse(vars(&["P", "Q"]), "1011"), Ok(table.clone()));
+        // Variables the formula doesn't use just repeat its values
+        assert_eq!(TruthTable::of(&p("~R"), vars(&["P", "R"])).unwrap().column(), "FTFT");
+        assert_eq!(TruthTable::of(&p("^|^"), vec![]).unwrap().column(), "T");
+        assert_eq!(table.mismatched_rows(&p("~P | Q")), Ok(vec![]));
+        assert_eq!(table.mismatched_rows(&p("P <-> Q")), Ok(vec![2]));
+
+        assert!(table.mismatched_rows(&p("P -> R")).is_err());
+        assert!(table.mismatched_rows(&p("forall x P")).is_err());
+        assert!(table.mismatched_rows(&p("F(P)")).is_err());
+        assert!(TruthTable::parse(vars(&["P", "Q"]), "TFT").is_err());
+        assert!(TruthTable::parse(vars(&["P", "P"]), "TFTT").is_err());
+        assert!(TruthTable::parse(vars(&["P"]), "TX").is_err());
+    }
+
+    #[test]
+    fn test_connectives() {
+        assert_eq!(Connective::used_in(&p("P -> (Q & _|_)")), Ok([Connective::Implies, Connective::And, Connective::Contra].into_iter().collect()));
+        assert_eq!(Connective::used_in(&p("P")), Ok(BTreeSet::new()));
+        assert!(Connective::used_in(&p("exists x P(x)")).is_err());
+        for connective in [Connective::Not, Connective::And, Connective::Or, Connective::Implies, Connective::Bicon, Connective::Equiv, Connective::Taut, Connective::Contra] {
+            assert_eq!(connective.symbol().parse(), Ok(connective));
+        }
+        assert_eq!("->".parse(), Ok(Connective::Implies));
+        assert!("+".parse::<Connective>().is_err());
+    }
+}
@@ -0,0 +1,176 @@
+/*!
+# Workspace archives
+
+A workspace is everything a user has going in Aris: their open proofs, their
+practice progress, and their settings. Exporting it to a single archive lets
+students move between machines without losing their state.
+
+Archives are JSON objects, like
+
+```json
+{
+    "format": "aris-workspace",
+    "version": 1,
+    "proofs": [{"name": "hw1.bram", "data": "<?xml ...>"}],
+    "progress": "solved\tP ⊢ P\n",
+    "settings": {"font-size": "16", "keymap": "vim"}
+}
+```
+
+where `data` is a proof in the XML format of `proofs::xml_interop`. Progress
+and settings are kept as the text they're saved as, since only the app that
+wrote them knows their meaning. Fields added by later versions are ignored.
+
+Proofs that record when their lines were written, as `proofs::timings`
+describes, keep their timings in their data, and `Workspace::timings_csv()`
+collects them into one table for research.
+*/
+
+use crate::expr::Expr;
+use crate::proofs::pooledproof::PooledProof;
+use crate::proofs::timings::Timings;
+use crate::proofs::xml_interop;
+
+use std::collections::BTreeMap;
+
+use frunk_core::HList;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Value of the `format` field of every archive
+pub const FORMAT: &str = "aris-workspace";
+
+/// Version of the archive format written by `Workspace::to_json()`. Archives
+/// of later versions can't be read.
+pub const VERSION: u32 = 1;
+
+/// Everything a user has going in Aris
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workspace {
+    /// Open proofs, in the order they're opened in on import
+    #[serde(default)]
+    pub proofs: Vec<WorkspaceProof>,
+
+    /// Practice progress, as the text it's saved as
+    #[serde(default)]
+    pub progress: Option<String>,
+
+    /// Settings, as the keys and values they're saved with
+    #[serde(default)]
+    pub settings: BTreeMap<String, String>,
+}
+
+/// A proof in a workspace
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceProof {
+    /// Name of the proof, like its file name
+    pub name: String,
+
+    /// The proof, in the XML format of `proofs::xml_interop`
+    pub data: String,
+}
+
+/// What's around the workspace in an archive
+#[derive(Serialize, Deserialize)]
+struct Archive {
+    format: String,
+    version: u32,
+    #[serde(flatten)]
+    workspace: Workspace,
+}
+
+impl Workspace {
+    /// Write the workspace as an archive
+    pub fn to_json(&self) -> String {
+        let archive = Archive { format: FORMAT.to_string(), version: VERSION, workspace: self.clone() };
+        serde_json::to_string_pretty(&archive).expect("workspaces are always serializable")
+    }
+
+    /// Read a workspace from an archive
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let archive: Archive = serde_json::from_str(text).map_err(|err| format!("Not a workspace archive: {err}"))?;
+        if archive.format != FORMAT {
+            return Err(format!("Not a workspace archive: the format is {:?}, not {:?}", archive.format, FORMAT));
+        }
+        if archive.version > VERSION {
+            return Err(format!("The workspace archive is version {}, but only versions up to {} can be read. Try a newer version of Aris.", archive.version, VERSION));
+        }
+        Ok(archive.workspace)
+    }
+
+    /// Names of the proofs that aren't valid proofs, with why, so they can be
+    /// reported instead of failing to open
+    pub fn invalid_proofs(&self) -> Vec<(String, String)> {
+        type P = PooledProof<HList![Expr]>;
+        self.proofs.iter().filter_map(|proof| xml_interop::proof_from_xml::<P, _>(proof.data.as_bytes()).err().map(|err| (proof.name.clone(), err))).collect()
+    }
+
+    /// Names and timings of the valid proofs that record timings
+    pub fn timings(&self) -> Vec<(String, Timings)> {
+        type P = PooledProof<HList![Expr]>;
+        self.proofs.iter().filter_map(|proof| Some((proof.name.clone(), xml_interop::proof_from_xml::<P, _>(proof.data.as_bytes()).ok()?.1.timings?))).collect()
+    }
+
+    /// The timings of every line of the proofs that record them, as CSV with
+    /// a header row. Times are in milliseconds since the Unix epoch, and
+    /// lines that never became correct have blank `correct` and
+    /// `ms_to_correct` columns.
+    pub fn timings_csv(&self) -> String {
+        let mut csv = "proof,line,created,correct,ms_to_correct\n".to_string();
+        for (name, timings) in self.timings() {
+            // Quote names, doubling any quotes in them
+            let name = format!("\"{}\"", name.replace('"', "\"\""));
+            for (line, timing) in &timings.lines {
+                let optional = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+                csv += &format!("{},{},{},{},{}\n", name, line, timing.created, optional(timing.correct), optional(timing.time_to_correct()));
+            }
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Workspace {
+        let data = include_str!("../../example-proofs/propositional_logic_arguments_for_proofs_ii_problem_10.bram");
+        let proofs = vec![WorkspaceProof { name: "problem_10.bram".into(), data: data.into() }];
+        let settings = [("font-size", "20"), ("keymap", "vim")].iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+        Workspace { proofs, progress: Some("solved\tP \u{22a2} P\n".into()), settings }
+    }
+
+    #[test]
+    fn test_workspace_roundtrip() {
+        let workspace = example();
+        assert_eq!(Workspace::from_json(&workspace.to_json()), Ok(workspace));
+        assert_eq!(Workspace::from_json(r#"{"format": "aris-workspace", "version": 1}"#), Ok(Workspace::default()));
+    }
+
+    #[test]
+    fn test_workspace_rejects_other_archives() {
+        assert!(Workspace::from_json("[]").is_err());
+        assert!(Workspace::from_json(r#"{"format": "something-else", "version": 1}"#).is_err());
+        assert!(Workspace::from_json(r#"{"format": "aris-workspace", "version": 2}"#).is_err());
+    }
+
+    #[test]
+    fn test_workspace_timings() {
+        let mut workspace = example();
+        assert_eq!(workspace.timings_csv(), "proof,line,created,correct,ms_to_correct\n");
+        let timed = workspace.proofs[0].data.replace("</metadata>", "<timings><timing line=\"1\" created=\"100\" correct=\"350\"/><timing line=\"2\" created=\"200\"/></timings></metadata>");
+        workspace.proofs.push(WorkspaceProof { name: "say \"hi\".bram".into(), data: timed });
+        assert_eq!(workspace.timings().len(), 1);
+        assert_eq!(workspace.timings_csv(), "proof,line,created,correct,ms_to_correct\n\"say \"\"hi\"\".bram\",1,100,350,250\n\"say \"\"hi\"\".bram\",2,200,,\n");
+    }
+
+    #[test]
+    fn test_workspace_invalid_proofs() {
+        let mut workspace = example();
+        assert_eq!(workspace.invalid_proofs(), vec![]);
+        workspace.proofs.push(WorkspaceProof { name: "broken.bram".into(), data: "not a proof".into() });
+        let invalid = workspace.invalid_proofs();
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].0, "broken.bram");
+    }
+}
@@ -0,0 +1,163 @@
+//! Command line tool for checking every line of a proof file, printing each
+//! line's result as soon as it's checked, so big proofs show their progress.
+//! Cloze handouts only have their blanks checked, since the rest is locked.
+//! Wrong first-order steps are shown with a small model they fail in, and
+//! wrong temporal logic steps with a short trace they fail on, when one is
+//! found. Gaps, steps admitted without proof, are listed apart from
+//! mistakes, and a proof with any left isn't finished. With `--report <file>`,
+//! the results are also saved as a JSON verification report, in the format of
+//! `aris::proofs::report`. With `--public-key <key>`, the base64 of an Ed25519
+//! public key, the proof is only checked if it's signed with that key, as
+//! exams signed with `aris-sign` by the server they were submitted to are.
+
+use aris::expr::Expr;
+use aris::parser::LogicMode;
+use aris::proofs::line_checks::LineChecks;
+use aris::proofs::line_info::LineInfo;
+use aris::proofs::lints;
+use aris::proofs::report::Report;
+use aris::proofs::skeleton;
+use aris::proofs::skeleton::BlankCheck;
+use aris::proofs::skeleton::Blanks;
+use aris::proofs::xml_interop;
+use aris::proofs::xml_interop::proof_from_xml;
+use aris::proofs::Proof;
+use aris::rules::ProofCheckError;
+use aris::soundness;
+use aris::soundness::Config;
+use aris::temporal;
+
+use std::env;
+use std::fs::File;
+use std::io::Write;
+
+use frunk_core::HList;
+
+type P = aris::proofs::pooledproof::PooledProof<HList![Expr]>;
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+
+    let usage = || format!("Usage: {} [--report <report.json>] [--public-key <key>] <proof>", args[0]);
+    let (mut report_path, mut public_key) = (None, None);
+    let mut rest = &args[1..];
+    let path = loop {
+        match rest {
+            [path] => break path,
+            [flag, report, tail @ ..] if flag == "--report" => (report_path, rest) = (Some(report), tail),
+            [flag, key, tail @ ..] if flag == "--public-key" => (public_key, rest) = (Some(xml_interop::verifying_key_from_base64(key)?), tail),
+            _ => return Err(usage()),
+        }
+    };
+
+    let file = File::open(path).map_err(|err| format!("Could not open {path}: {err}"))?;
+
+    let (prf, meta) = proof_from_xml::<P, _>(&file)?;
+    if let Some(key) = &public_key {
+        xml_interop::verify_signature(&prf, &meta, key).map_err(|err| format!("{path} isn't signed with the given key: {err}"))?;
+        println!("Signature: valid");
+    }
+    // Lines are checked in the proof's own syntax, and with as many
+    // assumptions per subproof as it allows
+    meta.while_open(|| {
+        if let Some(report_path) = report_path {
+            let report = Report::of(&prf, &meta.goals);
+            std::fs::write(report_path, report.to_json()).map_err(|err| format!("Could not write {report_path}: {err}"))?;
+        }
+        if !meta.blanks.is_empty() {
+            return check_cloze(&prf, &meta.blanks);
+        }
+
+        let checks = LineChecks::new(&prf);
+        let total = checks.total();
+        let mut incorrect = 0;
+        let mut warned = 0;
+        let mut gaps = vec![];
+        let info = LineInfo::of(&prf);
+        let mut stdout = std::io::stdout();
+        for line in checks.iter(&prf) {
+            match line.result {
+                Ok(()) => {
+                    let lints = lints::lints(&prf, &line.line, &meta.goals);
+                    if lints.is_empty() {
+                        println!("[{}/{}] Line {}: correct", line.number, total, line.number);
+                    } else {
+                        // Warnings don't make the line incorrect
+                        warned += 1;
+                        let messages = lints.iter().map(|lint| lint.message(|r| info.line_of(r))).collect::<Vec<_>>();
+                        println!("[{}/{}] Line {}: correct, with warnings: {}", line.number, total, line.number, messages.join(" "));
+                    }
+                }
+                Err(ProofCheckError::Gap) => {
+                    gaps.push(line.number);
+                    println!("[{}/{}] Line {}: gap, admitted without proof", line.number, total, line.number);
+                }
+                Err(err) => {
+                    incorrect += 1;
+                    let err = err.message(&|r| info.line_of(r), &|sr| info.line_range_of_subproof(sr));
+                    let counterexample = match meta.logic_mode {
+                        LogicMode::Temporal => temporal::step_countertrace(&prf, &line.line, temporal::DEFAULT_MAX_TRACE_LEN, Config::default().max_models).map(|trace| format!("of the trace {trace}")),
+                        _ => soundness::step_countermodel(&prf, &line.line, &Config::default()).map(|model| format!("in the model with {model}")),
+                    };
+                    match counterexample {
+                        Some(counterexample) => println!("[{}/{}] Line {}: {} It doesn't follow from what it cites, which is all true {}, where it's false.", line.number, total, line.number, err, counterexample),
+                        None => println!("[{}/{}] Line {}: {}", line.number, total, line.number, err),
+                    }
+                }
+            }
+            // Show each line as it's checked, even when the output is piped
+            let _ = stdout.flush();
+        }
+
+        let unused = prf.unused_premises(&prf.concluding_lines(&meta.goals));
+        for premise in &unused {
+            println!("Line {}: premise isn't used by the conclusion", info.line_of(&frunk_core::coproduct::Coproduct::inject(*premise)).unwrap_or_default());
+        }
+
+        if incorrect > 0 {
+            return Err(format!("{incorrect} of {total} lines are incorrect"));
+        }
+        if !gaps.is_empty() {
+            let lines = gaps.iter().map(|line| line.to_string()).collect::<Vec<_>>();
+            return Err(format!("The proof isn't finished, with {} gaps left, at lines {}", gaps.len(), lines.join(", ")));
+        }
+        if meta.require_all_premises && !unused.is_empty() {
+            return Err(format!("{} premises aren't used, and the proof has to use all of them", unused.len()));
+        }
+        match warned {
+            0 => println!("All {total} lines are correct"),
+            _ => println!("All {total} lines are correct, {warned} with warnings"),
+        }
+        Ok(())
+    })
+}
+
+/// Check the lines of a cloze handout with blanks, which are the only ones
+/// students can change
+fn check_cloze(prf: &P, blanks: &Blanks) -> Result<(), String> {
+    let checks = skeleton::check_blanks(prf, blanks);
+    let mut unfilled = 0;
+    let mut incorrect = 0;
+    let info = LineInfo::of(prf);
+    for (line, check) in &checks {
+        match check {
+            BlankCheck::Unfilled => {
+                unfilled += 1;
+                println!("Line {line}: not filled in");
+            }
+            BlankCheck::Correct => println!("Line {line}: correct"),
+            BlankCheck::Incorrect(err) => {
+                incorrect += 1;
+                println!("Line {}: {}", line, err.message(&|r| info.line_of(r), &|sr| info.line_range_of_subproof(sr)));
+            }
+        }
+    }
+    if incorrect > 0 {
+        return Err(format!("{incorrect} of {} filled-in lines are incorrect", checks.len()));
+    }
+    if unfilled > 0 {
+        return Err(format!("{unfilled} of {} lines aren't filled in", checks.len()));
+    }
+    println!("All {} filled-in lines are correct", checks.len());
+    Ok(())
+}
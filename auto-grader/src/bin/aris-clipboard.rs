@@ -0,0 +1,53 @@
+//! Command line tool for moving lines between proof files and the Aris web
+//! app through the clipboard, in the format of `aris::proofs::clipboard`.
+//!
+//! `copy` prints a proof as clipboard contents, for a clipboard tool to put on
+//! the clipboard under the MIME type `application/x-aris-proof+json`, like
+//! `aris-clipboard copy hw1.bram | xclip -selection clipboard -t application/x-aris-proof+json`.
+//! `paste` reads clipboard contents from standard input into a new proof file.
+//! Plain text is read as premises, a formula per line.
+
+use aris::expr::Expr;
+use aris::proofs::clipboard::ClipboardProof;
+use aris::proofs::xml_interop::proof_from_xml;
+use aris::proofs::xml_interop::xml_from_proof_and_metadata;
+use aris::proofs::xml_interop::ProofMetaData;
+
+use std::env;
+use std::fs::File;
+use std::io::Read;
+
+use frunk_core::HList;
+
+type P = aris::proofs::pooledproof::PooledProof<HList![Expr]>;
+
+fn usage(program: &str) -> String {
+    format!("Usage:\n  {program} copy <proof>\n  {program} paste <proof> < contents")
+}
+
+fn copy(path: &str) -> Result<(), String> {
+    let file = File::open(path).map_err(|err| format!("Could not open {path}: {err}"))?;
+    let (prf, _) = proof_from_xml::<P, _>(&file)?;
+    println!("{}", ClipboardProof::from_proof(&prf).to_json());
+    Ok(())
+}
+
+fn paste(path: &str) -> Result<(), String> {
+    let mut contents = String::new();
+    std::io::stdin().read_to_string(&mut contents).map_err(|err| format!("Could not read the clipboard contents: {err}"))?;
+    // Anything that doesn't look like JSON is plain text
+    let fragment = if contents.trim_start().starts_with('{') { ClipboardProof::from_json(&contents)? } else { ClipboardProof::from_text(&contents) };
+    let prf = fragment.to_proof::<P>();
+    let file = File::create(path).map_err(|err| format!("Could not create {path}: {err}"))?;
+    let meta = ProofMetaData::default();
+    xml_from_proof_and_metadata(&prf, &meta, file).map_err(|err| format!("Could not write {path}: {err}"))
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    match &args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [_, "copy", proof] => copy(proof),
+        [_, "paste", proof] => paste(proof),
+        _ => Err(usage(args.first().map(String::as_str).unwrap_or("aris-clipboard"))),
+    }
+}
@@ -0,0 +1,53 @@
+//! Command line tool for signing proof files on a course's server, in the way
+//! of `aris::proofs::xml_interop::xml_from_proof_and_metadata_signed`.
+//!
+//! Official builds of the web app sign the exams they save, but anyone can
+//! read their key out of them, so a course's server can instead sign exams as
+//! they arrive with a key that never leaves it, and graders check the
+//! signatures with the matching public key. The key file
+//! holds the base64 of a 32 byte Ed25519 secret key, like the output of
+//! `head -c 32 /dev/urandom | base64`. `public-key` prints the base64 of its
+//! public key, for `aris-check --public-key` and `aris-auto-grader --public-key`.
+
+use aris::expr::Expr;
+use aris::proofs::xml_interop;
+use aris::proofs::xml_interop::proof_from_xml;
+use aris::proofs::xml_interop::SigningKey;
+
+use std::env;
+use std::fs::File;
+
+use frunk_core::HList;
+
+type P = aris::proofs::pooledproof::PooledProof<HList![Expr]>;
+
+fn usage(program: &str) -> String {
+    format!("Usage:\n  {program} sign <key file> <proof> <signed proof>\n  {program} public-key <key file>")
+}
+
+fn read_key(path: &str) -> Result<SigningKey, String> {
+    let key = std::fs::read_to_string(path).map_err(|err| format!("Could not read {path}: {err}"))?;
+    xml_interop::signing_key_from_base64(&key)
+}
+
+fn sign(key: &str, path: &str, signed: &str) -> Result<(), String> {
+    let key = read_key(key)?;
+    let file = File::open(path).map_err(|err| format!("Could not open {path}: {err}"))?;
+    let (prf, meta) = proof_from_xml::<P, _>(&file)?;
+    let out = File::create(signed).map_err(|err| format!("Could not create {signed}: {err}"))?;
+    xml_interop::xml_from_proof_and_metadata_signed(&prf, &meta, &key, out).map_err(|err| format!("Could not write {signed}: {err}"))
+}
+
+fn public_key(key: &str) -> Result<(), String> {
+    println!("{}", xml_interop::verifying_key_to_base64(&read_key(key)?.verifying_key()));
+    Ok(())
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    match &args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [_, "sign", key, proof, signed] => sign(key, proof, signed),
+        [_, "public-key", key] => public_key(key),
+        _ => Err(usage(args.first().map(String::as_str).unwrap_or("aris-sign"))),
+    }
+}
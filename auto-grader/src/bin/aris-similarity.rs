@@ -0,0 +1,42 @@
+//! Command line tool for comparing proofs, like the submissions for an
+//! assignment, in the way of `aris::proofs::similarity`.
+//!
+//! Every pair of proofs is scored, and the pairs are printed from most to
+//! least alike, so submissions copied from each other end up at the top.
+
+use aris::expr::Expr;
+use aris::proofs::similarity::similarity;
+use aris::proofs::xml_interop::proof_from_xml;
+
+use std::env;
+use std::fs::File;
+
+use frunk_core::HList;
+
+type P = aris::proofs::pooledproof::PooledProof<HList![Expr]>;
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        return Err(format!("Usage: {} <proof> <proof> [proof...]", args[0]));
+    }
+
+    let mut proofs = vec![];
+    for path in &args[1..] {
+        let file = File::open(path).map_err(|err| format!("Could not open {path}: {err}"))?;
+        let (prf, _) = proof_from_xml::<P, _>(&file).map_err(|err| format!("{path} isn't a valid proof: {err}"))?;
+        proofs.push((path, prf));
+    }
+
+    let mut pairs = vec![];
+    for (i, (left_path, left)) in proofs.iter().enumerate() {
+        for (right_path, right) in &proofs[i + 1..] {
+            pairs.push((similarity(left, right), left_path, right_path));
+        }
+    }
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+    for (score, left, right) in pairs {
+        println!("{:5.1}%  {}  {}", score * 100.0, left, right);
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+//! Command line tool for making a fill-in-the-blank handout from a solution
+//! proof, in the way of `aris::proofs::skeleton`.
+//!
+//! The handout is written as a new proof file, with the solution's conclusion
+//! as its goal if the solution doesn't have goals, and the answer key as a
+//! text file listing what was removed from each line. The handout marks its
+//! blanks, so editors lock the rest of it and checkers only check the blanks.
+//! Giving a different offset blanks different steps, for making several
+//! versions of a handout.
+
+use aris::expr::Expr;
+use aris::proofs::skeleton;
+use aris::proofs::skeleton::Difficulty;
+use aris::proofs::timings::Timings;
+use aris::proofs::xml_interop::proof_from_xml;
+use aris::proofs::xml_interop::xml_from_proof_and_metadata;
+use aris::proofs::xml_interop::ProofMetaData;
+use aris::proofs::Proof;
+
+use std::env;
+use std::fs::File;
+
+use frunk_core::HList;
+
+type P = aris::proofs::pooledproof::PooledProof<HList![Expr]>;
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+
+    let (solution, difficulty, handout, answer_key, offset) = match &args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [_, solution, difficulty, handout, answer_key] => (*solution, *difficulty, *handout, *answer_key, "0"),
+        [_, solution, difficulty, handout, answer_key, offset] => (*solution, *difficulty, *handout, *answer_key, *offset),
+        _ => return Err(format!("Usage: {} <solution> <easy|medium|hard> <handout> <answer key> [offset]", args[0])),
+    };
+    let difficulty = difficulty.parse::<Difficulty>()?;
+    let offset = offset.parse::<usize>().map_err(|_| format!("The offset {offset:?} isn't a number"))?;
+
+    let file = File::open(solution).map_err(|err| format!("Could not open {solution}: {err}"))?;
+    let (mut prf, meta) = proof_from_xml::<P, _>(&file)?;
+
+    // The handout is solved once it proves what the solution concludes
+    let goals = match &meta.goals[..] {
+        [] => prf.concluding_lines(&[]).iter().filter_map(|line| prf.lookup_expr(line)).collect(),
+        goals => goals.to_vec(),
+    };
+    let key = skeleton::blank_steps(&mut prf, &goals, difficulty, offset);
+
+    // The handout records timings if the solution did, but starts without any
+    let timings = meta.timings.as_ref().map(|_| Timings::default());
+    let handout_meta = ProofMetaData { goals, operators: meta.operators, logic_mode: meta.logic_mode, require_all_premises: meta.require_all_premises, require_full_parens: meta.require_full_parens, assumption_policy: meta.assumption_policy, scoping_policy: meta.scoping_policy, blanks: key.blanks(), timings, ..Default::default() };
+    let file = File::create(handout).map_err(|err| format!("Could not create {handout}: {err}"))?;
+    xml_from_proof_and_metadata(&prf, &handout_meta, file).map_err(|err| format!("Could not write {handout}: {err}"))?;
+    std::fs::write(answer_key, key.to_string()).map_err(|err| format!("Could not write {answer_key}: {err}"))?;
+
+    println!("Blanked {} lines", key.0.len());
+    Ok(())
+}
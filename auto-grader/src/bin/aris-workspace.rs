@@ -0,0 +1,100 @@
+//! Command line tool for workspace archives exported from the Aris web app,
+//! for unpacking them into proof files and packing proof files into them.
+//!
+//! A workspace's directory has its proofs as files, with its practice progress
+//! in `progress.txt` and its settings in `settings.txt`, as `key=value` lines.
+//! Unpacking also writes the line timings of the proofs that record them to
+//! `timings.csv`, which is only for reading and isn't packed back up.
+
+use aris::workspace::Workspace;
+use aris::workspace::WorkspaceProof;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const PROGRESS_FILE: &str = "progress.txt";
+const SETTINGS_FILE: &str = "settings.txt";
+const TIMINGS_FILE: &str = "timings.csv";
+
+fn usage(program: &str) -> String {
+    format!("Usage:\n  {program} list <archive>\n  {program} unpack <archive> <directory>\n  {program} pack <directory> <archive>")
+}
+
+fn read_archive(path: &Path) -> Result<Workspace, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+    Workspace::from_json(&text)
+}
+
+fn list(archive: &Path) -> Result<(), String> {
+    let workspace = read_archive(archive)?;
+    println!("Proofs:");
+    for proof in &workspace.proofs {
+        println!("  {} ({} bytes)", proof.name, proof.data.len());
+    }
+    for (name, err) in workspace.invalid_proofs() {
+        println!("Invalid proof {name}: {err}");
+    }
+    for (name, timings) in workspace.timings() {
+        println!("Timings of {}: {}", name, timings.summary());
+    }
+    println!("Progress: {} records", workspace.progress.as_deref().map(|progress| progress.lines().count()).unwrap_or(0));
+    println!("Settings:");
+    for (key, value) in &workspace.settings {
+        println!("  {key}={value}");
+    }
+    Ok(())
+}
+
+fn unpack(archive: &Path, directory: &Path) -> Result<(), String> {
+    let workspace = read_archive(archive)?;
+    fs::create_dir_all(directory).map_err(|err| format!("Could not create {}: {}", directory.display(), err))?;
+    let write = |name: &str, contents: &str| {
+        let path = directory.join(name);
+        fs::write(&path, contents).map_err(|err| format!("Could not write {}: {}", path.display(), err))
+    };
+    for proof in &workspace.proofs {
+        // Only keep the file name, so archives can't write outside the directory
+        let name = Path::new(&proof.name).file_name().and_then(|name| name.to_str()).filter(|name| ![PROGRESS_FILE, SETTINGS_FILE, TIMINGS_FILE].contains(name)).ok_or_else(|| format!("Invalid proof name {:?}", proof.name))?;
+        write(name, &proof.data)?;
+    }
+    if let Some(progress) = &workspace.progress {
+        write(PROGRESS_FILE, progress)?;
+    }
+    if !workspace.timings().is_empty() {
+        write(TIMINGS_FILE, &workspace.timings_csv())?;
+    }
+    write(SETTINGS_FILE, &workspace.settings.iter().map(|(key, value)| format!("{key}={value}\n")).collect::<String>())
+}
+
+fn pack(directory: &Path, archive: &Path) -> Result<(), String> {
+    let mut workspace = Workspace::default();
+    let entries = fs::read_dir(directory).map_err(|err| format!("Could not read {}: {}", directory.display(), err))?;
+    let mut paths = entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).filter(|path| path.is_file()).collect::<Vec<_>>();
+    paths.sort();
+    for path in paths {
+        let name = path.file_name().and_then(|name| name.to_str()).ok_or_else(|| format!("Invalid file name {}", path.display()))?.to_string();
+        let contents = fs::read_to_string(&path).map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+        match &*name {
+            PROGRESS_FILE => workspace.progress = Some(contents),
+            SETTINGS_FILE => workspace.settings = contents.lines().filter_map(|line| line.split_once('=')).map(|(key, value)| (key.to_string(), value.to_string())).collect(),
+            // Timings are in the proofs already
+            TIMINGS_FILE => {}
+            _ => workspace.proofs.push(WorkspaceProof { name, data: contents }),
+        }
+    }
+    if let Some((name, err)) = workspace.invalid_proofs().into_iter().next() {
+        return Err(format!("{name} is not a valid proof: {err}"));
+    }
+    fs::write(archive, workspace.to_json()).map_err(|err| format!("Could not write {}: {}", archive.display(), err))
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    match &args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [_, "list", archive] => list(Path::new(archive)),
+        [_, "unpack", archive, directory] => unpack(Path::new(archive), Path::new(directory)),
+        [_, "pack", directory, archive] => pack(Path::new(directory), Path::new(archive)),
+        _ => Err(usage(args.first().map(String::as_str).unwrap_or("aris-workspace"))),
+    }
+}
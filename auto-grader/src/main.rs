@@ -3,7 +3,11 @@
 
 use aris::expr::Expr;
 use aris::proofs::lined_proof::LinedProof;
+use aris::proofs::skeleton;
+use aris::proofs::xml_interop;
 use aris::proofs::xml_interop::proof_from_xml;
+use aris::proofs::xml_interop::ProofMetaData;
+use aris::proofs::xml_interop::VerifyingKey;
 use aris::proofs::{Justification, PjRef, Proof};
 use aris::rules::ProofCheckError;
 
@@ -11,11 +15,14 @@ use std::collections::HashSet;
 use std::env;
 use std::fmt::Debug;
 use std::fs::File;
+use std::io::Read;
 use std::path::Path;
 
 use frunk_core::coproduct::Coproduct;
 use frunk_core::HList;
 
+type P = aris::proofs::pooledproof::PooledProof<HList![Expr]>;
+
 type ValidateError<P> = (PjRef<P>, ProofCheckError<PjRef<P>, <P as Proof>::SubproofReference>);
 
 fn validate_recursive<P: Proof>(proof: &P, line: PjRef<P>) -> Result<(), ValidateError<P>>
@@ -63,25 +70,42 @@ where
 //
 // Assert that the assumptions are the same, that the step(goal) appears at the top level of the
 // student assignment and that the goal is valid in the student proof all the way to the premises.
+//
+// With `--public-key <key>`, the base64 of an Ed25519 public key, the student assignment also has
+// to be signed with that key, as exams signed with `aris-sign` by the server they were submitted to are.
 
 fn main() -> Result<(), String> {
     let args: Vec<_> = env::args().collect();
 
-    if args.len() != 3 {
-        return Err(format!("Usage: {} <instructor assignment> <student assignment>", args[0]));
-    }
+    let (public_key, instructor_path, student_path) = match &args[1..] {
+        [instructor, student] => (None, instructor, student),
+        [flag, key, instructor, student] if flag == "--public-key" => (Some(xml_interop::verifying_key_from_base64(key)?), instructor, student),
+        _ => return Err(format!("Usage: {} [--public-key <key>] <instructor assignment> <student assignment>", args[0])),
+    };
 
-    let instructor_path = Path::new(&args[1]);
-    let student_path = Path::new(&args[2]);
+    let instructor_file = File::open(Path::new(instructor_path)).expect("Could not open instructor file");
+    let student_file = File::open(Path::new(student_path)).expect("Could not open student file");
 
-    let instructor_file = File::open(instructor_path).expect("Could not open instructor file");
-    let student_file = File::open(student_path).expect("Could not open student file");
+    grade_xml(&instructor_file, &student_file, public_key.as_ref())
+}
 
-    type P = aris::proofs::pooledproof::PooledProof<HList![Expr]>;
+/// Grade a student's proof of an instructor's assignment, both saved as XML.
+/// With a public key, the student's proof has to be signed with it.
+fn grade_xml(instructor: impl Read, student: impl Read, public_key: Option<&VerifyingKey>) -> Result<(), String> {
+    let (i_prf, i_meta) = proof_from_xml::<P, _>(instructor)?;
+    let (s_prf, s_meta) = proof_from_xml::<P, _>(student)?;
+    if let Some(key) = public_key {
+        xml_interop::verify_signature(&s_prf, &s_meta, key).map_err(|err| format!("The student assignment isn't signed with the given key: {err}"))?;
+    }
 
-    let (i_prf, i_meta) = proof_from_xml::<P, _>(&instructor_file).unwrap();
-    let (s_prf, _) = proof_from_xml::<P, _>(&student_file).unwrap();
+    // The student's proof is checked in the assignment's syntax, and with as
+    // many assumptions per subproof as the assignment allows, whatever the
+    // student's file says
+    i_meta.while_open(|| grade(&i_prf, &i_meta, &s_prf))
+}
 
+/// Grade a student's proof of an instructor's assignment
+fn grade(i_prf: &P, i_meta: &ProofMetaData, s_prf: &P) -> Result<(), String> {
     let instructor_premises = i_prf.premises();
     let student_premises = s_prf.premises();
 
@@ -93,14 +117,22 @@ fn main() -> Result<(), String> {
         return Err("Premises do not match!".into());
     }
 
+    // An instructor assignment with blanks is a cloze handout, where only the
+    // blanks may be filled in
+    if !i_meta.blanks.is_empty() {
+        if let Some(line) = skeleton::changed_locked_cells(s_prf, i_prf, &i_meta.blanks).first() {
+            return Err(format!("Line {line} was changed, but only the blanks may be filled in."));
+        }
+    }
+
     // Gets the top level lines
     let _ = i_prf.direct_lines();
     let student_lines = s_prf.direct_lines();
 
     // Verify that the goals are in the student lines and that the instructor's conclusion line matches some student's conclusion, and that the student's conclusion checks out using DFS.
-    for i_goal in i_meta.goals {
-        if let Some(i) = student_lines.iter().find(|i| s_prf.lookup_expr(&Coproduct::inject(**i)).as_ref() == Some(&i_goal)) {
-            match validate_recursive(&s_prf, Coproduct::inject(*i)) {
+    for i_goal in &i_meta.goals {
+        if let Some(i) = student_lines.iter().find(|i| s_prf.lookup_expr(&Coproduct::inject(**i)).as_ref() == Some(i_goal)) {
+            match validate_recursive(s_prf, Coproduct::inject(*i)) {
                 Ok(()) => {}
                 Err((r, e)) => {
                     return {
@@ -113,9 +145,70 @@ fn main() -> Result<(), String> {
                 }
             }
         } else {
-            return Err(format!("Goal {} is not in student proof.", i_goal.clone()));
+            return Err(format!("Goal {} is not in student proof.", i_goal));
+        }
+    }
+
+    if i_meta.require_all_premises {
+        let goal_lines = s_prf.concluding_lines(&i_meta.goals);
+        if let Some(unused) = s_prf.unused_premises(&goal_lines).first() {
+            return Err(format!("Premise {} is not used to prove the goals.", s_prf.lookup_premise(unused).expect("premise exists")));
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use aris::parser::parse_unwrap as p;
+    use aris::proofs::xml_interop::xml_from_proof_and_metadata;
+    use aris::proofs::AssumptionPolicy;
+    use aris::rules::RuleM;
+
+    #[test]
+    fn test_grade_with_several_assumptions() {
+        let goal = p("(A & B) -> (A & B)");
+        let instructor = "<bram>\n  <metadata>\n    <assumptions>multiple</assumptions>\n  </metadata>\n  <proof id=\"0\">\n    <goal>\n      <raw>(A ∧ B) → (A ∧ B)</raw>\n    </goal>\n  </proof>\n</bram>\n";
+
+        // →I discharging both assumptions of a subproof at once
+        let mut student = P::new();
+        let sub = student.add_subproof();
+        student.with_mut_subproof(&sub, |sub| {
+            let a = sub.add_premise(p("A"));
+            let b = sub.add_premise(p("B"));
+            sub.add_step(Justification(p("A & B"), RuleM::AndIntro, vec![Coproduct::inject(a), Coproduct::inject(b)], vec![]));
+        });
+        student.add_step(Justification(goal, RuleM::ImpIntro, vec![], vec![sub]));
+        let metadata = ProofMetaData { assumption_policy: AssumptionPolicy::Multiple, ..Default::default() };
+        let mut saved = vec![];
+        xml_from_proof_and_metadata(&student, &metadata, &mut saved).unwrap();
+
+        assert_eq!(grade_xml(instructor.as_bytes(), &saved[..], None), Ok(()));
+        // The assignment's policy is what counts, not the one the student's proof was saved with
+        let single = instructor.replace("<assumptions>multiple</assumptions>", "");
+        assert!(grade_xml(single.as_bytes(), &saved[..], None).unwrap_err().contains("validate_recursive failed for line 4"));
+    }
+
+    #[test]
+    fn test_grade_signed() {
+        let instructor = "<bram>\n  <proof id=\"0\">\n    <assumption linenum=\"0\">\n      <raw>A</raw>\n    </assumption>\n    <goal>\n      <raw>A</raw>\n    </goal>\n  </proof>\n</bram>\n";
+        let mut student = P::new();
+        let a = student.add_premise(p("A"));
+        student.add_step(Justification(p("A"), RuleM::Reiteration, vec![Coproduct::inject(a)], vec![]));
+        let metadata = ProofMetaData { author: Some("student".into()), ..Default::default() };
+        let key = xml_interop::SigningKey::from_bytes(&[7; 32]);
+        let mut signed = vec![];
+        xml_interop::xml_from_proof_and_metadata_signed(&student, &metadata, &key, &mut signed).unwrap();
+        let mut unsigned = vec![];
+        xml_interop::xml_from_proof_and_metadata_with_hash(&student, &metadata, &mut unsigned).unwrap();
+
+        assert_eq!(grade_xml(instructor.as_bytes(), &signed[..], Some(&key.verifying_key())), Ok(()));
+        assert_eq!(grade_xml(instructor.as_bytes(), &unsigned[..], None), Ok(()));
+        assert!(grade_xml(instructor.as_bytes(), &unsigned[..], Some(&key.verifying_key())).unwrap_err().contains("isn't signed"));
+        let other = xml_interop::SigningKey::from_bytes(&[8; 32]).verifying_key();
+        assert!(grade_xml(instructor.as_bytes(), &signed[..], Some(&other)).unwrap_err().contains("doesn't match its hash"));
+    }
+}
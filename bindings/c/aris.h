@@ -28,7 +28,7 @@ typedef enum Op {
    */
   Add,
   /**
-   * Arithmetic multiplication `*`
+   * Arithmetic multiplication `×`
    */
   Mult,
 } Op;
@@ -45,6 +45,16 @@ typedef enum QuantKind {
    * Existential quantifier `∃`
    */
   Exists,
+  /**
+   * Set builder `{x : φ(x)}`, the set of everything that satisfies `φ`. It binds its variable like a quantifier,
+   * but it makes a term rather than a formula, so quantifier equivalences don't apply to it.
+   */
+  SetBuilder,
+  /**
+   * Lambda abstraction `λx φ(x)`, the function taking `x` to `φ(x)`, in higher-order logic. Like a set builder, it
+   * binds its variable without quantifying over it.
+   */
+  Lambda,
 } QuantKind;
 
 typedef struct String String;
@@ -53,6 +63,10 @@ typedef struct Vec_Expr Vec_Expr;
 
 /**
  * A logical expression
+ *
+ * Cloning, comparing, hashing, and showing an expression, like the traversals below, use explicit stacks instead
+ * of recursion, so they work however deeply it's nested. Dropping and ordering one still recurse, which is why
+ * the parser doesn't build expressions nested more than `parser::ParseLimits::max_depth` levels deep.
  */
 typedef enum Expr_Tag {
   /**
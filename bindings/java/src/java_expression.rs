@@ -72,6 +72,8 @@ pub fn jobject_to_expr(env: &JNIEnv, obj: JObject) -> jni::errors::Result<Expr>
         "edu.rpi.aris.ast.Expression$EquivExpression" => handle_assoc(Op::Equiv),
         "edu.rpi.aris.ast.Expression$ForallExpression" => handle_quantifier(QuantKind::Forall),
         "edu.rpi.aris.ast.Expression$ExistsExpression" => handle_quantifier(QuantKind::Exists),
+        "edu.rpi.aris.ast.Expression$SetBuilderExpression" => handle_quantifier(QuantKind::SetBuilder),
+        "edu.rpi.aris.ast.Expression$LambdaExpression" => handle_quantifier(QuantKind::Lambda),
         "edu.rpi.aris.ast.Expression$ContradictionExpression" => Ok(Expr::Contra),
         "edu.rpi.aris.ast.Expression$TautologyExpression" => Ok(Expr::Taut),
         "edu.rpi.aris.ast.Expression$ApplyExpression" => {
@@ -163,6 +165,8 @@ impl HasClass for QuantKind {
         match self {
             QuantKind::Forall => "Ledu/rpi/aris/ast/Expression$ForallExpression;",
             QuantKind::Exists => "Ledu/rpi/aris/ast/Expression$ExistsExpression;",
+            QuantKind::SetBuilder => "Ledu/rpi/aris/ast/Expression$SetBuilderExpression;",
+            QuantKind::Lambda => "Ledu/rpi/aris/ast/Expression$LambdaExpression;",
         }
     }
 }
@@ -6,7 +6,10 @@ use serde_wasm_bindgen::to_value;
 use wasm_bindgen::prelude::*;
 
 pub fn parse_helper(input: &str) -> JsResult<Expr> {
-    let ret = aris::parser::parse(input).ok_or("aris: parse error")?;
+    let ret = aris::parser::try_parse(input).map_err(|err| match err {
+        aris::parser::ParseError::Syntax => "aris: parse error".to_string(),
+        err => format!("aris: {err}"),
+    })?;
     Ok(ret)
 }
 
@@ -0,0 +1,22 @@
+//! Decodes the `ARIS_SIGNING_KEY` exams are signed with, if there is one, so a
+//! build with a key that isn't valid fails instead of the app failing to save
+
+use base64::Engine;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=ARIS_SIGNING_KEY");
+    let key = match env::var("ARIS_SIGNING_KEY") {
+        Ok(key) => {
+            let bytes = base64::engine::general_purpose::STANDARD.decode(key.trim()).unwrap_or_else(|err| panic!("ARIS_SIGNING_KEY isn't valid base64: {err}"));
+            let bytes = <[u8; 32]>::try_from(&bytes[..]).unwrap_or_else(|_| panic!("ARIS_SIGNING_KEY is {} bytes long, not 32", bytes.len()));
+            format!("Some({bytes:?})")
+        }
+        Err(_) => "None".into(),
+    };
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR isn't set");
+    fs::write(Path::new(&out_dir).join("signing_key.rs"), key).expect("couldn't write the signing key");
+}
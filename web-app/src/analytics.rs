@@ -0,0 +1,50 @@
+//! Usage analytics for research on how students learn
+//!
+//! The app records nothing unless the page opts in by calling
+//! `setAnalyticsSink` with a function, which is then called with each event of
+//! `aris::analytics` as an object, like:
+//!
+//! ```json
+//! { "event": "rule-selected", "rule": "MODUS_PONENS" }
+//! ```
+//!
+//! The app never sends the events anywhere itself. Whether they're kept, and
+//! where, is up to the page, which should ask for consent first. Calling
+//! `setAnalyticsSink(null)` stops recording.
+
+use aris::analytics::Event;
+use aris::analytics::Sink;
+
+use std::cell::RefCell;
+
+use wasm_bindgen::prelude::*;
+
+thread_local! {
+    static FUNCTION: RefCell<Option<js_sys::Function>> = const { RefCell::new(None) };
+}
+
+/// Sink passing events to the page's function
+struct PageSink;
+
+impl Sink for PageSink {
+    fn record(&self, event: &Event) {
+        let function = FUNCTION.with(|function| function.borrow().clone());
+        if let (Some(function), Ok(event)) = (function, js_sys::JSON::parse(&event.to_json())) {
+            // A failing sink shouldn't get in the way of the student
+            let _ = function.call1(&JsValue::NULL, &event);
+        }
+    }
+}
+
+/// Call `sink` with every event from now on, or stop recording events if it's
+/// `null`
+#[wasm_bindgen(js_name = setAnalyticsSink)]
+pub fn set_analytics_sink(sink: Option<js_sys::Function>) {
+    let enabled = sink.is_some();
+    FUNCTION.with(|function| *function.borrow_mut() = sink);
+    if enabled {
+        aris::analytics::set_sink(Box::new(PageSink));
+    } else {
+        aris::analytics::clear_sink();
+    }
+}
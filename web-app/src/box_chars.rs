@@ -1,4 +1,5 @@
 pub const VERT: char = '│';
 pub const VERT_RIGHT: char = '├';
+pub const DOWN_RIGHT: char = '╭';
 pub const UP_RIGHT: char = '╰';
 pub const HORIZ: char = '─';
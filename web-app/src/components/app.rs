@@ -1,19 +1,46 @@
+use crate::components::command_palette::Command;
+use crate::components::file_drop::FileDrop;
 use crate::components::nav_bar::NavBarMsg;
 use crate::components::nav_bar::NavBarWidget;
 use crate::components::proof_widget::ProofWidget;
 use crate::components::proof_widget::ProofWidgetMsg;
 use crate::components::tabbed_container::TabbedContainer;
 use crate::components::tabbed_container::TabbedContainerMsg;
+use crate::crash;
+use crate::preferences::Preferences;
+use crate::signing;
 use crate::util::P;
 
+use aris::proofs::xml_interop;
+use aris::proofs::xml_interop::ProofMetaData;
+use aris::workspace::Workspace;
+
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
+use gloo::file::callbacks::FileReader;
+use gloo::timers::callback::Timeout;
 use yew::html::Scope;
 use yew::prelude::*;
 
+/// How long, in milliseconds, toasts are shown
+const TOAST_DURATION_MS: u32 = 8000;
+
 pub struct App {
     tabcontainer_link: Option<Scope<TabbedContainer>>,
+    navbar_link: Option<Scope<NavBarWidget>>,
     proofs: HashMap<String, Scope<ProofWidget>>,
+    preferences: Preferences,
+    /// Handles for reading files dropped on the app, by their IDs
+    file_readers: HashMap<usize, FileReader>,
+    /// Error messages about opening files, by their IDs
+    toasts: Vec<(usize, String)>,
+    /// Handles for hiding the toasts
+    #[allow(dead_code)]
+    toast_timeouts: HashMap<usize, Timeout>,
+    /// ID of the next file read or toast
+    next_id: usize,
 }
 
 pub enum AppMsg {
@@ -28,7 +55,66 @@ pub enum AppMsg {
         link: Scope<ProofWidget>,
     },
     #[allow(clippy::type_complexity)]
-    GetProofFromCurrentTab(Box<dyn FnOnce(String, &P)>),
+    GetProofFromCurrentTab(Box<dyn FnOnce(String, &P, &ProofMetaData)>),
+    /// Get the names and contents of every open proof, sorted by name
+    GetAllProofs(Callback<Vec<(String, Vec<u8>)>>),
+    /// Get the command palette commands of the proof in the current tab, if
+    /// the current tab is a proof
+    GetCommandsFromCurrentTab(Callback<Vec<Command>>),
+    /// Change and save the user's preferences
+    SetPreferences(Preferences),
+    /// Open proofs and workspace archives, such as files dropped on the app
+    OpenFiles(web_sys::FileList),
+    /// The file read with this ID was read
+    FileRead {
+        id: usize,
+        name: String,
+        result: Result<String, String>,
+    },
+    /// Show an error message for a moment
+    ShowToast(String),
+    DismissToast(usize),
+}
+
+impl App {
+    fn render_toasts(&self, ctx: &Context<Self>) -> Html {
+        let toasts = self.toasts.iter().map(|(id, message)| {
+            let id = *id;
+            html! {
+                <div class="toast show" role="alert" aria-live="assertive" aria-atomic="true">
+                    <div class="toast-header">
+                        <strong class="mr-auto text-danger"> { "Error" } </strong>
+                        <button type="button" class="ml-2 mb-1 close" aria-label="Close" onclick={ ctx.link().callback(move |_| AppMsg::DismissToast(id)) }>
+                            <span aria-hidden="true"> { "\u{d7}" } </span>
+                        </button>
+                    </div>
+                    <div class="toast-body"> { message } </div>
+                </div>
+            }
+        });
+        html! { <div class="toast-stack"> { for toasts } </div> }
+    }
+
+    /// Open a proof or workspace archive read from a file
+    fn open_file(&mut self, ctx: &Context<Self>, name: String, contents: String) {
+        // Workspace archives are JSON, and proofs are XML
+        if contents.trim_start().starts_with('{') {
+            match (Workspace::from_json(&contents), &self.navbar_link) {
+                (Ok(_), Some(navbar_link)) => navbar_link.send_message(NavBarMsg::WorkspaceRead(Ok(contents))),
+                (Ok(_), None) => {}
+                (Err(err), _) => ctx.link().send_message(AppMsg::ShowToast(format!("Couldn't open {name}: {err}"))),
+            }
+            return;
+        }
+        match xml_interop::proof_from_xml::<P, _>(contents.as_bytes()) {
+            Ok(_) => {
+                let name_ = name.clone();
+                let oncreate = ctx.link().callback(move |link| AppMsg::RegisterProofName { name: name_.clone(), link });
+                ctx.link().send_message(AppMsg::CreateTab { name, content: html! { <ProofWidget verbose=true data={ Some(contents.into_bytes()) } oncreate={ oncreate } /> } });
+            }
+            Err(err) => ctx.link().send_message(AppMsg::ShowToast(format!("Couldn't open {name}, since it isn't an Aris proof: {err}"))),
+        }
+    }
 }
 
 impl Component for App {
@@ -36,10 +122,10 @@ impl Component for App {
     type Properties = ();
 
     fn create(_: &Context<Self>) -> Self {
-        Self { tabcontainer_link: None, proofs: HashMap::new() }
+        Self { tabcontainer_link: None, navbar_link: None, proofs: HashMap::new(), preferences: Preferences::load(), file_readers: HashMap::new(), toasts: vec![], toast_timeouts: HashMap::new(), next_id: 0 }
     }
 
-    fn update(&mut self, _: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             AppMsg::TabbedContainerInit(tabcontainer_link) => {
                 self.tabcontainer_link = Some(tabcontainer_link);
@@ -48,6 +134,7 @@ impl Component for App {
             AppMsg::NavBarInit(menuwidget_link) => {
                 // create the first blank proof tab
                 menuwidget_link.send_message(NavBarMsg::FileNew);
+                self.navbar_link = Some(menuwidget_link);
                 false
             }
             AppMsg::CreateTab { name, content } => {
@@ -65,12 +152,82 @@ impl Component for App {
                     let proofs = self.proofs.clone();
                     tabcontainer_link.send_message(TabbedContainerMsg::GetCurrent(Box::new(move |_, name| {
                         if let Some(link) = proofs.get(&*name) {
-                            link.send_message(ProofWidgetMsg::CallOnProof(Box::new(move |prf| f(name, prf))));
+                            link.send_message(ProofWidgetMsg::CallOnProof(Box::new(move |prf, metadata| f(name, prf, metadata))));
+                        }
+                    })));
+                }
+                false
+            }
+            AppMsg::GetAllProofs(done) => {
+                if self.proofs.is_empty() {
+                    done.emit(vec![]);
+                }
+                let count = self.proofs.len();
+                let collected = Rc::new(RefCell::new(vec![]));
+                for (name, link) in &self.proofs {
+                    let (name, collected, done) = (name.clone(), collected.clone(), done.clone());
+                    link.send_message(ProofWidgetMsg::CallOnProof(Box::new(move |prf, metadata| {
+                        let data = signing::xml_from_proof_and_metadata(prf, metadata);
+                        let mut collected = collected.borrow_mut();
+                        collected.push((name, data));
+                        // Every proof answers, since tabs are never closed
+                        if collected.len() == count {
+                            collected.sort();
+                            done.emit(std::mem::take(&mut *collected));
                         }
                     })));
                 }
                 false
             }
+            AppMsg::GetCommandsFromCurrentTab(callback) => {
+                if let Some(tabcontainer_link) = &self.tabcontainer_link {
+                    let proofs = self.proofs.clone();
+                    tabcontainer_link.send_message(TabbedContainerMsg::GetCurrent(Box::new(move |_, name| {
+                        if let Some(link) = proofs.get(&*name) {
+                            link.send_message(ProofWidgetMsg::GetCommands(callback));
+                        }
+                    })));
+                }
+                false
+            }
+            AppMsg::SetPreferences(preferences) => {
+                preferences.save();
+                self.preferences = preferences;
+                true
+            }
+            AppMsg::OpenFiles(files) => {
+                for file in (0..files.length()).filter_map(|i| files.get(i)) {
+                    let id = self.next_id;
+                    self.next_id += 1;
+                    let name = file.name();
+                    let read = ctx.link().callback(move |result: Result<String, String>| AppMsg::FileRead { id, name: name.clone(), result });
+                    let reader = gloo::file::callbacks::read_as_text(&gloo::file::Blob::from(file), move |result| read.emit(result.map_err(|err| err.to_string())));
+                    self.file_readers.insert(id, reader);
+                }
+                false
+            }
+            AppMsg::FileRead { id, name, result } => {
+                self.file_readers.remove(&id);
+                match result {
+                    Ok(contents) => self.open_file(ctx, name, contents),
+                    Err(err) => ctx.link().send_message(AppMsg::ShowToast(format!("Couldn't read {name}: {err}"))),
+                }
+                false
+            }
+            AppMsg::ShowToast(message) => {
+                crash::log("toast", message.clone());
+                let id = self.next_id;
+                self.next_id += 1;
+                self.toasts.push((id, message));
+                let link = ctx.link().clone();
+                self.toast_timeouts.insert(id, Timeout::new(TOAST_DURATION_MS, move || link.send_message(AppMsg::DismissToast(id))));
+                true
+            }
+            AppMsg::DismissToast(id) => {
+                self.toasts.retain(|(toast, _)| *toast != id);
+                self.toast_timeouts.remove(&id);
+                true
+            }
         }
     }
 
@@ -87,10 +244,14 @@ impl Component for App {
             </TabbedContainer>
         };
         html! {
-            <div>
-                <NavBarWidget parent={ ctx.link().clone() } oncreate={ ctx.link().callback(AppMsg::NavBarInit) } />
-                { tabview }
-            </div>
+            <ContextProvider<Preferences> context={ self.preferences.clone() }>
+                <div>
+                    <NavBarWidget parent={ ctx.link().clone() } oncreate={ ctx.link().callback(AppMsg::NavBarInit) } />
+                    { tabview }
+                    <FileDrop ondrop={ ctx.link().callback(AppMsg::OpenFiles) } />
+                    { self.render_toasts(ctx) }
+                </div>
+            </ContextProvider<Preferences>>
         }
     }
 }
@@ -0,0 +1,332 @@
+//! Command palette, opened with <kbd>Ctrl-Shift-P</kbd>, for finding and
+//! running any action by name
+//!
+//! The palette lists the commands handed to it by the navigation bar, like
+//! file operations and view toggles, along with the commands of the proof in
+//! the current tab, like the line actions of its selected line. Typing filters
+//! the commands with a fuzzy match, the arrow keys pick one, and
+//! <kbd>Enter</kbd> runs it.
+
+use crate::components::app::App;
+use crate::components::app::AppMsg;
+
+use derivative::Derivative;
+use gloo::events::EventListener;
+use gloo::events::EventListenerOptions;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::UnwrapThrowExt;
+use web_sys::HtmlElement;
+use web_sys::HtmlInputElement;
+use yew::html::Scope;
+use yew::prelude::*;
+
+/// An action that can be run from the command palette
+#[derive(Clone, PartialEq)]
+pub struct Command {
+    /// Name of the command, which the search matches against
+    pub label: String,
+
+    /// Kind of command, such as "File" or "Line", shown next to the label
+    pub group: &'static str,
+
+    /// Keyboard shortcut that also runs the command, if any, like `"Ctrl-A"`
+    pub shortcut: Option<String>,
+
+    /// Run the command
+    pub run: Callback<()>,
+}
+
+impl Command {
+    pub fn new(group: &'static str, label: impl Into<String>, run: Callback<()>) -> Self {
+        Self { label: label.into(), group, shortcut: None, run }
+    }
+
+    pub fn with_shortcut(self, shortcut: impl Into<String>) -> Self {
+        Self { shortcut: Some(shortcut.into()), ..self }
+    }
+}
+
+/// Fuzzy match `query` against `text`, ignoring case and whitespace in the
+/// query. Every character of the query must appear in `text` in order, but not
+/// necessarily next to each other. Returns a score, higher for better matches,
+/// and the positions of the matched characters in `text`, or `None` if it
+/// doesn't match.
+///
+/// Matches at the start of a word, or right after the previous matched
+/// character, score higher, so "ins" ranks "Insert step" above "Delete
+/// premise".
+fn fuzzy_match(query: &str, text: &str) -> Option<(i32, Vec<usize>)> {
+    let mut query = query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut positions: Vec<usize> = vec![];
+    let mut prev: Option<char> = None;
+    for (i, c) in text.chars().enumerate() {
+        let wanted = match query.peek() {
+            Some(wanted) => *wanted,
+            None => break,
+        };
+        if c.to_lowercase().eq(std::iter::once(wanted)) {
+            score += 1;
+            if positions.last().map(|last| last + 1 == i).unwrap_or(false) {
+                score += 2;
+            }
+            if prev.map(|prev| !prev.is_alphanumeric()).unwrap_or(true) {
+                score += 3;
+            }
+            positions.push(i);
+            query.next();
+        }
+        prev = Some(c);
+    }
+    query.peek().is_none().then_some((score, positions))
+}
+
+/// Render `label` with the characters at `positions` in bold
+fn render_highlighted(label: &str, positions: &[usize]) -> Html {
+    label
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if positions.contains(&i) {
+                html! { <b>{ c }</b> }
+            } else {
+                html! { <>{ c }</> }
+            }
+        })
+        .collect()
+}
+
+pub struct CommandPalette {
+    /// Whether the palette is shown
+    open: bool,
+
+    /// Text typed in the search field
+    query: String,
+
+    /// Index of the highlighted command among the matching ones
+    selected: usize,
+
+    /// Commands of the proof in the current tab, gathered when opening
+    proof_commands: Vec<Command>,
+
+    /// Whether to scroll the highlighted command into view after the next
+    /// render, after moving the highlight with the keyboard
+    scroll_to_selected: bool,
+
+    input_ref: NodeRef,
+
+    list_ref: NodeRef,
+
+    /// Handle for listening for <kbd>Ctrl-Shift-P</kbd>
+    #[allow(dead_code)]
+    key_listener: EventListener,
+}
+
+pub enum CommandPaletteMsg {
+    /// Open the palette, or close it if it's open
+    Toggle,
+    Close,
+    /// The proof in the current tab sent its commands
+    ProofCommands(Vec<Command>),
+    /// Search field text was edited
+    QueryChanged(String),
+    /// Move the highlight down by `offset` commands, wrapping around at the ends
+    MoveSelection(isize),
+    /// Highlight the command at this index, such as the one under the mouse
+    Select(usize),
+    /// Run the highlighted command and close the palette
+    Run,
+}
+
+#[derive(Properties, Clone, Derivative)]
+#[derivative(PartialEq)]
+pub struct CommandPaletteProps {
+    #[derivative(PartialEq = "ignore")]
+    pub parent: Scope<App>,
+
+    /// Commands that don't depend on the current tab
+    pub commands: Vec<Command>,
+}
+
+impl CommandPalette {
+    /// The commands matching the search, best match first, along with the
+    /// positions of the matched characters in their labels
+    fn matches<'a>(&'a self, ctx: &'a Context<Self>) -> Vec<(&'a Command, Vec<usize>)> {
+        let mut matches = self
+            .proof_commands
+            .iter()
+            .chain(ctx.props().commands.iter())
+            .filter_map(|command| {
+                let (score, positions) = fuzzy_match(&self.query, &command.label)?;
+                Some((score, command, positions))
+            })
+            .collect::<Vec<_>>();
+        // Sorting is stable, so equally good matches keep their order
+        matches.sort_by_key(|(score, _, _)| -score);
+        matches.into_iter().map(|(_, command, positions)| (command, positions)).collect()
+    }
+}
+
+impl Component for CommandPalette {
+    type Message = CommandPaletteMsg;
+    type Properties = CommandPaletteProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let toggle = ctx.link().callback(|()| CommandPaletteMsg::Toggle);
+        let document = web_sys::window().expect_throw("window is undefined").document().expect_throw("document is undefined");
+        let key_listener = EventListener::new_with_options(&document, "keydown", EventListenerOptions::enable_prevent_default(), move |event: &Event| {
+            let event = event.dyn_ref::<web_sys::KeyboardEvent>().unwrap_throw();
+            if event.ctrl_key() && event.shift_key() && event.key().eq_ignore_ascii_case("p") {
+                event.prevent_default();
+                toggle.emit(());
+            }
+        });
+        Self { open: false, query: String::new(), selected: 0, proof_commands: vec![], scroll_to_selected: false, input_ref: NodeRef::default(), list_ref: NodeRef::default(), key_listener }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            CommandPaletteMsg::Toggle if self.open => Component::update(self, ctx, CommandPaletteMsg::Close),
+            CommandPaletteMsg::Toggle => {
+                self.open = true;
+                self.query.clear();
+                self.selected = 0;
+                self.proof_commands.clear();
+                ctx.props().parent.send_message(AppMsg::GetCommandsFromCurrentTab(ctx.link().callback(CommandPaletteMsg::ProofCommands)));
+                true
+            }
+            CommandPaletteMsg::Close => {
+                self.open = false;
+                true
+            }
+            CommandPaletteMsg::ProofCommands(commands) => {
+                self.proof_commands = commands;
+                true
+            }
+            CommandPaletteMsg::QueryChanged(query) => {
+                self.query = query;
+                self.selected = 0;
+                true
+            }
+            CommandPaletteMsg::MoveSelection(offset) => {
+                let count = self.matches(ctx).len() as isize;
+                if count > 0 {
+                    self.selected = (self.selected as isize + offset).rem_euclid(count) as usize;
+                    self.scroll_to_selected = true;
+                }
+                true
+            }
+            CommandPaletteMsg::Select(index) => {
+                let changed = self.selected != index;
+                self.selected = index;
+                changed
+            }
+            CommandPaletteMsg::Run => {
+                let run = self.matches(ctx).get(self.selected).map(|(command, _)| command.run.clone());
+                if let Some(run) = run {
+                    // Close first, so the command can move the focus elsewhere
+                    self.open = false;
+                    run.emit(());
+                }
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        if !self.open {
+            return html! {};
+        }
+
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            CommandPaletteMsg::QueryChanged(input.value())
+        });
+        let onkeydown = ctx.link().batch_callback(|e: KeyboardEvent| {
+            // Keep the keys from reaching the line action shortcuts of the
+            // selected proof line
+            e.stop_propagation();
+            let msg = match e.key().as_str() {
+                "ArrowDown" => CommandPaletteMsg::MoveSelection(1),
+                "ArrowUp" => CommandPaletteMsg::MoveSelection(-1),
+                "PageDown" => CommandPaletteMsg::MoveSelection(10),
+                "PageUp" => CommandPaletteMsg::MoveSelection(-10),
+                "Enter" => CommandPaletteMsg::Run,
+                "Escape" => CommandPaletteMsg::Close,
+                key if e.ctrl_key() && e.shift_key() && key.eq_ignore_ascii_case("p") => CommandPaletteMsg::Close,
+                _ => return None,
+            };
+            e.prevent_default();
+            Some(msg)
+        });
+
+        let matches = self.matches(ctx);
+        let items = if matches.is_empty() {
+            html! { <div class="list-group-item text-muted"> { "No matching commands" } </div> }
+        } else {
+            matches
+                .iter()
+                .enumerate()
+                .map(|(i, (command, positions))| {
+                    let class = if i == self.selected { "list-group-item list-group-item-action active" } else { "list-group-item list-group-item-action" };
+                    // Keep the focus in the search field when clicking
+                    let onmousedown = Callback::from(|e: MouseEvent| e.prevent_default());
+                    let shortcut = command.shortcut.as_ref().map(|shortcut| html! { <kbd class="ml-2"> { shortcut } </kbd> });
+                    html! {
+                        <div class={ class } role="option" aria-selected={ (i == self.selected).to_string() } { onmousedown }
+                            onmousemove={ ctx.link().callback(move |_| CommandPaletteMsg::Select(i)) }
+                            onclick={ ctx.link().callback(|_| CommandPaletteMsg::Run) }>
+                            <span class="command-palette-group"> { command.group } </span>
+                            { render_highlighted(&command.label, positions) }
+                            { for shortcut }
+                        </div>
+                    }
+                })
+                .collect::<Html>()
+        };
+
+        html! {
+            <>
+                <div class="command-palette-backdrop" onclick={ ctx.link().callback(|_| CommandPaletteMsg::Close) }></div>
+                <div class="command-palette card shadow" role="dialog" aria-label="Command palette">
+                    <input
+                        ref={ self.input_ref.clone() }
+                        type="text"
+                        class="form-control"
+                        placeholder="Type a command"
+                        role="combobox"
+                        aria-expanded="true"
+                        value={ self.query.clone() }
+                        onblur={ ctx.link().callback(|_| CommandPaletteMsg::Close) }
+                        { oninput }
+                        { onkeydown } />
+                    <div ref={ self.list_ref.clone() } class="list-group list-group-flush command-palette-list" role="listbox">
+                        { items }
+                    </div>
+                </div>
+            </>
+        }
+    }
+
+    /// Focuses the search field while the palette is open, and keeps the
+    /// highlighted command scrolled into view
+    fn rendered(&mut self, _: &Context<Self>, _: bool) {
+        if !self.open {
+            return;
+        }
+        if let Some(input) = self.input_ref.cast::<HtmlElement>() {
+            input.focus().unwrap_throw();
+        }
+        if std::mem::take(&mut self.scroll_to_selected) {
+            let list = self.list_ref.cast::<HtmlElement>();
+            let item = list.as_ref().and_then(|list| list.children().item(self.selected as u32)).and_then(|item| item.dyn_into::<HtmlElement>().ok());
+            if let (Some(list), Some(item)) = (list, item) {
+                if item.offset_top() < list.scroll_top() {
+                    list.set_scroll_top(item.offset_top());
+                } else if item.offset_top() + item.offset_height() > list.scroll_top() + list.client_height() {
+                    list.set_scroll_top(item.offset_top() + item.offset_height() - list.client_height());
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,32 @@
+use aris::proofs::clipboard;
+use aris::proofs::clipboard::ClipboardProof;
+use wasm_bindgen::JsCast;
 use yew::prelude::*;
 
+/// Text with several lines pasted into an `ExprEntry`, which would otherwise
+/// be crammed into the one text field
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PastedLines {
+    /// The non-blank lines of the pasted text, trimmed
+    pub lines: Vec<String>,
+
+    /// What the text field would contain after pasting the lines into it,
+    /// joined by spaces, at the cursor
+    pub inline: String,
+
+    /// The lines with their rules and citations, if they were copied from a
+    /// proof
+    pub fragment: Option<ClipboardProof>,
+}
+
 /// A text field for entering expressions
 pub struct ExprEntry {
     /// Reference to `<input>` node
     node_ref: NodeRef,
+
+    /// Whether to restore the cursor position from the `caret` property after
+    /// the next render, because the text field now shows a different line
+    restore_caret: bool,
 }
 
 /// Message sent to `ExprEntry`
@@ -13,6 +36,15 @@ pub enum ExprEntryMsg {
 
     /// Text field was focused
     OnFocus,
+
+    /// Text was pasted into the text field
+    OnPaste(Event),
+
+    /// The cursor may have moved, by typing, clicking, or leaving the field
+    CaretMoved,
+
+    /// A key was pressed in the text field
+    OnKeydown(KeyboardEvent),
 }
 
 /// Properties for `ExprEntry`
@@ -26,6 +58,27 @@ pub struct ExprEntryProps {
     #[prop_or_default]
     pub onfocus: Option<Callback<()>>,
 
+    /// Callback to call when text with several lines is pasted. If this is
+    /// set, such text isn't pasted into the text field, and the callback
+    /// decides what to do with it instead.
+    #[prop_or_default]
+    pub onpastelines: Option<Callback<PastedLines>>,
+
+    /// Callback to call when the cursor moves, with the start and end of the
+    /// selection in characters
+    #[prop_or_default]
+    pub oncaret: Option<Callback<(u32, u32)>>,
+
+    /// Callback to call when Enter is pressed without any modifier keys, such
+    /// as to finish the line and move on to the next one
+    #[prop_or_default]
+    pub onenter: Option<Callback<()>>,
+
+    /// Cursor position to restore when the text field is focused, as from
+    /// `oncaret`. Without one, the cursor is put at the end of the text.
+    #[prop_or_default]
+    pub caret: Option<(u32, u32)>,
+
     /// Whether the text field should be focused
     ///
     /// ## Values:
@@ -38,6 +91,11 @@ pub struct ExprEntryProps {
     /// Initial text in text field when it is loaded
     pub init_value: String,
 
+    /// Whether the text can't be edited, though it can still be selected and
+    /// focused
+    #[prop_or_default]
+    pub readonly: bool,
+
     /// An ID to use for our strings
     pub id: String,
 }
@@ -46,7 +104,7 @@ impl Component for ExprEntry {
     type Message = ExprEntryMsg;
     type Properties = ExprEntryProps;
     fn create(_: &Context<Self>) -> Self {
-        Self { node_ref: NodeRef::default() }
+        Self { node_ref: NodeRef::default(), restore_caret: false }
     }
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
@@ -60,9 +118,32 @@ impl Component for ExprEntry {
                 }
                 false
             }
+            ExprEntryMsg::OnPaste(event) => {
+                self.handle_paste(ctx, event);
+                false
+            }
+            ExprEntryMsg::CaretMoved => {
+                self.report_caret(ctx);
+                false
+            }
+            ExprEntryMsg::OnKeydown(event) => {
+                let modified = event.shift_key() || event.ctrl_key() || event.alt_key() || event.meta_key();
+                if let Some(onenter) = &ctx.props().onenter {
+                    // Leave Enter alone while an input method is composing text
+                    if event.key() == "Enter" && !modified && !event.is_composing() {
+                        onenter.emit(());
+                    }
+                }
+                false
+            }
         }
     }
-    fn changed(&mut self, _: &Context<Self>, _: &Self::Properties) -> bool {
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        // Yew reuses text fields by position, so after lines are inserted or
+        // removed above, this field may be showing another line
+        if ctx.props().id != old_props.id {
+            self.restore_caret = true;
+        }
         true
     }
     fn view(&self, ctx: &Context<Self>) -> Html {
@@ -74,6 +155,12 @@ impl Component for ExprEntry {
                 class="form-control text-input-custom"
                 oninput={ ctx.link().callback(|_| ExprEntryMsg::OnEdit) }
                 onfocus={ ctx.link().callback(|_| ExprEntryMsg::OnFocus) }
+                onpaste={ ctx.link().callback(ExprEntryMsg::OnPaste) }
+                onkeydown={ ctx.link().callback(ExprEntryMsg::OnKeydown) }
+                onkeyup={ ctx.link().callback(|_| ExprEntryMsg::CaretMoved) }
+                onmouseup={ ctx.link().callback(|_| ExprEntryMsg::CaretMoved) }
+                onblur={ ctx.link().callback(|_| ExprEntryMsg::CaretMoved) }
+                readonly={ ctx.props().readonly }
                 value={ ctx.props().init_value.clone() } />
         }
     }
@@ -88,17 +175,79 @@ impl ExprEntry {
         self.node_ref.cast::<web_sys::HtmlInputElement>().expect("failed casting node ref to input element")
     }
 
-    /// Sync the focus of the text field with the `focus` property
-    fn update_focus(&self, ctx: &Context<Self>) {
+    /// Sync the focus of the text field with the `focus` property. When
+    /// focusing the field from elsewhere, or when it's now showing another
+    /// line, the cursor is put back where it was in that line.
+    fn update_focus(&mut self, ctx: &Context<Self>) {
         let input = self.input_element();
+        let restore_caret = std::mem::take(&mut self.restore_caret);
 
         match ctx.props().focus {
-            Some(true) => input.focus().expect("failed focusing expr entry"),
+            Some(true) => {
+                let focused = web_sys::window().and_then(|window| window.document()).and_then(|document| document.active_element()).map(|elem| elem == **input).unwrap_or(false);
+                if focused && !restore_caret {
+                    return;
+                }
+                input.focus().expect("failed focusing expr entry");
+                let len = input.value().chars().count() as u32;
+                let (start, end) = ctx.props().caret.unwrap_or((len, len));
+                input.set_selection_range(start.min(len), end.min(len)).expect("failed setting selection range");
+            }
             Some(false) => input.blur().expect("failed unfocusing expr entry"),
             None => {}
         }
     }
 
+    /// Report the cursor position to the `oncaret` callback
+    fn report_caret(&self, ctx: &Context<Self>) {
+        if let Some(oncaret) = &ctx.props().oncaret {
+            let input_elem = self.input_element();
+            let start = input_elem.selection_start().expect("failed getting selection start").unwrap_or_default();
+            let end = input_elem.selection_end().expect("failed getting selection end").unwrap_or_default();
+            oncaret.emit((start, end));
+        }
+    }
+
+    /// Handle pasting into the text field. Text with several non-blank lines
+    /// is handed to the `onpastelines` callback rather than pasted, if there
+    /// is one, along with the lines' rules and citations if they were copied
+    /// from a proof. Anything else is pasted as usual.
+    fn handle_paste(&self, ctx: &Context<Self>, event: Event) {
+        let onpastelines = match &ctx.props().onpastelines {
+            Some(onpastelines) => onpastelines,
+            None => return,
+        };
+        let data = match event.dyn_ref::<web_sys::ClipboardEvent>().and_then(|event| event.clipboard_data()) {
+            Some(data) => data,
+            None => return,
+        };
+        let fragment = data.get_data(clipboard::MIME_TYPE).ok().and_then(|json| ClipboardProof::from_json(&json).ok());
+        let text = match (&fragment, data.get_data("text/plain").ok()) {
+            (Some(fragment), _) => fragment.to_text(),
+            (None, Some(text)) => text,
+            (None, None) => return,
+        };
+        let lines = text.lines().map(str::trim).filter(|line| !line.is_empty()).map(String::from).collect::<Vec<String>>();
+        if lines.len() < 2 {
+            return;
+        }
+        event.prevent_default();
+
+        // Replace the selected text with the joined lines, like pasting would
+        //
+        // NOTE: The selection is measured in characters, not bytes, so the
+        // `String` must be converted to `Vec<char>`.
+        let input_elem = self.input_element();
+        let value = input_elem.value().chars().collect::<Vec<char>>();
+        let start = (input_elem.selection_start().expect("failed getting selection start").unwrap_or_default() as usize).min(value.len());
+        let end = (input_elem.selection_end().expect("failed getting selection end").unwrap_or_default() as usize).clamp(start, value.len());
+        let left = value[..start].iter().collect::<String>();
+        let right = value[end..].iter().collect::<String>();
+        let inline = aris::macros::expand(&[left, lines.join(" "), right].concat());
+
+        onpastelines.emit(PastedLines { lines, inline, fragment });
+    }
+
     /// Handle an edit of the expression text field by expanding macros with
     /// `aris::macros::expand()`. To preserve the cursor position, the strings
     /// to the left and right of the cursor are expanded separately.
@@ -133,6 +282,9 @@ impl ExprEntry {
         input_elem.set_selection_start(Some(cursor_pos)).expect("failed setting selection start");
         input_elem.set_selection_end(Some(cursor_pos)).expect("failed setting selection end");
 
+        // Report the cursor before the new text, so it's up to date when the
+        // new text is rendered
+        self.report_caret(ctx);
         ctx.props().oninput.emit(value);
     }
 }
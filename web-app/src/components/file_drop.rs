@@ -0,0 +1,99 @@
+use gloo::events::EventListener;
+use gloo::events::EventListenerOptions;
+use wasm_bindgen::JsCast;
+use web_sys::DragEvent;
+use yew::prelude::*;
+
+/// Lets files be dropped anywhere on the app's window, showing an overlay
+/// while files are dragged over it
+pub struct FileDrop {
+    /// How many elements files are being dragged over. Every element the drag
+    /// enters fires `dragenter` before the element it left fires `dragleave`,
+    /// so the drag has left the window once this gets back to zero.
+    drag_depth: u32,
+
+    /// Handles for listening for drags on the window
+    #[allow(dead_code)]
+    listeners: Vec<EventListener>,
+}
+
+/// Handler for a drag event, giving the message to send for it, if any
+type DragHandler = Box<dyn Fn(&DragEvent) -> Option<FileDropMsg>>;
+
+pub enum FileDropMsg {
+    DragEnter,
+    DragLeave,
+    Drop(web_sys::FileList),
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct FileDropProps {
+    /// Called with the files dropped on the window
+    pub ondrop: Callback<web_sys::FileList>,
+}
+
+/// Whether a drag is of files, rather than of text or elements of the page
+fn drags_files(e: &DragEvent) -> bool {
+    e.data_transfer().map(|data| data.types().includes(&"Files".into(), 0)).unwrap_or(false)
+}
+
+impl Component for FileDrop {
+    type Message = FileDropMsg;
+    type Properties = FileDropProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let window = match web_sys::window() {
+            Some(window) => window,
+            None => return Self { drag_depth: 0, listeners: vec![] },
+        };
+        // Drops are only allowed where `dragover` is cancelled, so listeners
+        // have to be able to prevent the default
+        let options = EventListenerOptions::enable_prevent_default();
+        let listen = |event: &'static str, handle: DragHandler| {
+            let link = ctx.link().clone();
+            EventListener::new_with_options(&window, event, options, move |e| {
+                let e: &DragEvent = e.unchecked_ref();
+                if !drags_files(e) {
+                    return;
+                }
+                e.prevent_default();
+                if let Some(msg) = handle(e) {
+                    link.send_message(msg);
+                }
+            })
+        };
+        let listeners = vec![
+            listen("dragenter", Box::new(|_| Some(FileDropMsg::DragEnter))),
+            listen("dragleave", Box::new(|_| Some(FileDropMsg::DragLeave))),
+            listen("dragover", Box::new(|_| None)),
+            // The drop has to be handled here, so the browser doesn't navigate
+            // away to the dropped file
+            listen("drop", Box::new(|e| e.data_transfer().and_then(|data| data.files()).map(FileDropMsg::Drop))),
+        ];
+        Self { drag_depth: 0, listeners }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let was_dragging = self.drag_depth > 0;
+        match msg {
+            FileDropMsg::DragEnter => self.drag_depth += 1,
+            FileDropMsg::DragLeave => self.drag_depth = self.drag_depth.saturating_sub(1),
+            FileDropMsg::Drop(files) => {
+                self.drag_depth = 0;
+                ctx.props().ondrop.emit(files);
+            }
+        }
+        was_dragging != (self.drag_depth > 0)
+    }
+
+    fn view(&self, _: &Context<Self>) -> Html {
+        if self.drag_depth == 0 {
+            return html! {};
+        }
+        html! {
+            <div class="file-drop-overlay">
+                <div class="file-drop-message"> { "Drop proofs or workspace archives to open them" } </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use crate::telemetry;
+use crate::telemetry::Report;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use gloo::events::EventListener;
+use gloo::timers::callback::Interval;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlInputElement;
+use web_sys::MessageEvent;
+use web_sys::WebSocket;
+use yew::prelude::*;
+
+/// How long, in milliseconds, a student can go without fixing an error or
+/// finishing a line before they're shown as stuck
+const STUCK_AFTER_MS: f64 = 5.0 * 60_000.0;
+
+/// How long, in milliseconds, a proof can go without a report before it's
+/// shown as idle
+const IDLE_AFTER_MS: f64 = 10.0 * 60_000.0;
+
+/// Live view of the progress students report during a lab session, for
+/// instructors and TAs. Students are shown as stuck when their proof has had
+/// errors for a while without getting any further.
+pub struct InstructorDashboard {
+    /// URL of the relay to connect to, as typed
+    url: String,
+
+    connection: ConnectionState,
+    socket: Option<WebSocket>,
+
+    /// Handles for the events of `socket`
+    #[allow(dead_code)]
+    socket_listeners: Vec<EventListener>,
+
+    /// Latest progress of every proof, by client and proof
+    proofs: BTreeMap<(String, String), ProofProgress>,
+
+    /// Handle for re-rendering the times since proofs' last activity
+    #[allow(dead_code)]
+    ticker: Interval,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Failed(String),
+}
+
+/// Progress of a student's proof, as last reported
+struct ProofProgress {
+    report: Report,
+
+    /// When the last report arrived, in milliseconds since the Unix epoch
+    last_report: f64,
+
+    /// When the proof last got further, by finishing a line, fixing an error,
+    /// or being solved
+    last_advance: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ProofState {
+    Stuck,
+    Working,
+    Idle,
+    Solved,
+}
+
+impl ProofProgress {
+    fn state(&self, now: f64) -> ProofState {
+        if self.report.solved {
+            ProofState::Solved
+        } else if now - self.last_report > IDLE_AFTER_MS {
+            ProofState::Idle
+        } else if self.report.incorrect + self.report.unparsed > 0 && now - self.last_advance > STUCK_AFTER_MS {
+            ProofState::Stuck
+        } else {
+            ProofState::Working
+        }
+    }
+}
+
+pub enum InstructorDashboardMsg {
+    SetUrl(String),
+    Connect,
+    Disconnect,
+    Opened,
+    Closed,
+    /// A message arrived from the relay
+    Received(String),
+    /// Forget every proof shown
+    Clear,
+    /// Update the times since proofs' last activity
+    Tick,
+}
+
+impl InstructorDashboard {
+    fn disconnect(&mut self) {
+        self.socket_listeners.clear();
+        if let Some(socket) = self.socket.take() {
+            let _ = socket.close();
+        }
+    }
+
+    fn receive(&mut self, text: &str) {
+        let (client, report) = match Report::from_json(text) {
+            Some(message) => message,
+            None => return,
+        };
+        let now = js_sys::Date::now();
+        let key = (client, report.proof.clone());
+        let advanced = match self.proofs.get(&key) {
+            Some(previous) => {
+                let old = &previous.report;
+                report.correct > old.correct || report.incorrect + report.unparsed < old.incorrect + old.unparsed || (report.solved && !old.solved)
+            }
+            None => true,
+        };
+        let last_advance = match self.proofs.get(&key) {
+            Some(previous) if !advanced => previous.last_advance,
+            _ => now,
+        };
+        self.proofs.insert(key, ProofProgress { report, last_report: now, last_advance });
+    }
+
+    fn render_connection(&self, ctx: &Context<Self>) -> Html {
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            InstructorDashboardMsg::SetUrl(input.value())
+        });
+        let onsubmit = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            InstructorDashboardMsg::Connect
+        });
+        let (status, button) = match &self.connection {
+            ConnectionState::Disconnected => (html! { <span class="badge badge-secondary"> { "Disconnected" } </span> }, html! { <button type="submit" class="btn btn-primary"> { "Connect" } </button> }),
+            ConnectionState::Failed(err) => (html! { <span class="badge badge-danger"> { err } </span> }, html! { <button type="submit" class="btn btn-primary"> { "Connect" } </button> }),
+            ConnectionState::Connecting => (html! { <span class="badge badge-info"> { "Connecting\u{2026}" } </span> }, html! { <button type="button" class="btn btn-outline-secondary" onclick={ ctx.link().callback(|_| InstructorDashboardMsg::Disconnect) }> { "Cancel" } </button> }),
+            ConnectionState::Connected => (html! { <span class="badge badge-success"> { "Connected" } </span> }, html! { <button type="button" class="btn btn-outline-secondary" onclick={ ctx.link().callback(|_| InstructorDashboardMsg::Disconnect) }> { "Disconnect" } </button> }),
+        };
+        html! {
+            <form class="form-inline mb-3" { onsubmit }>
+                <label class="mr-2" for="instructor-dashboard-url"> { "Relay" } </label>
+                <input id="instructor-dashboard-url" class="form-control mr-2 w-50" type="text" placeholder="wss://lab.example.edu/aris" value={ self.url.clone() } { oninput } />
+                { button }
+                <span class="ml-3"> { status } </span>
+            </form>
+        }
+    }
+
+    fn render_proofs(&self) -> Html {
+        if self.proofs.is_empty() {
+            return html! { <p class="text-muted"> { "Students show up here once they open the app with ?telemetry= followed by the relay's URL." } </p> };
+        }
+        let now = js_sys::Date::now();
+        let mut proofs = self.proofs.iter().collect::<Vec<_>>();
+        // Stuck students first, longest stuck first
+        proofs.sort_by(|(_, a), (_, b)| a.state(now).cmp(&b.state(now)).then(a.last_advance.total_cmp(&b.last_advance)));
+        let rows = proofs.into_iter().map(|((client, _), progress)| {
+            let report = &progress.report;
+            let (state, row_class) = match progress.state(now) {
+                ProofState::Stuck => (html! { <span class="badge badge-danger"> { "Stuck" } </span> }, "table-danger"),
+                ProofState::Working => (html! { <span class="badge badge-primary"> { "Working" } </span> }, ""),
+                ProofState::Idle => (html! { <span class="badge badge-secondary"> { "Idle" } </span> }, "text-muted"),
+                ProofState::Solved => (html! { <span class="badge badge-success"> { "Solved" } </span> }, ""),
+            };
+            let errors = report.errors.iter().collect::<BTreeSet<_>>();
+            let warnings = report.warnings.iter().collect::<BTreeSet<_>>();
+            html! {
+                <tr class={ row_class }>
+                    <td><code> { client } </code></td>
+                    <td> { &report.problem } </td>
+                    <td> { report.lines } </td>
+                    <td>
+                        { report.correct }
+                        { for warnings.into_iter().map(|code| html! { <span class="badge badge-warning ml-1"> { code } </span> }) }
+                    </td>
+                    <td>
+                        { report.incorrect + report.unparsed }
+                        { for errors.into_iter().map(|code| html! { <span class="badge badge-light ml-1"> { code } </span> }) }
+                    </td>
+                    <td> { minutes_ago(now - progress.last_advance) } </td>
+                    <td> { state } </td>
+                </tr>
+            }
+        });
+        html! {
+            <table class="table table-sm">
+                <thead>
+                    <tr>
+                        <th> { "Student" } </th>
+                        <th> { "Problem" } </th>
+                        <th> { "Lines" } </th>
+                        <th> { "Correct" } </th>
+                        <th> { "Errors" } </th>
+                        <th> { "Last progress" } </th>
+                        <th> { "Status" } </th>
+                    </tr>
+                </thead>
+                <tbody> { for rows } </tbody>
+            </table>
+        }
+    }
+}
+
+/// A duration in milliseconds, written like "3 min ago"
+fn minutes_ago(ms: f64) -> String {
+    match (ms / 60_000.0).floor() as u64 {
+        0 => "just now".to_string(),
+        minutes => format!("{minutes} min ago"),
+    }
+}
+
+impl Component for InstructorDashboard {
+    type Message = InstructorDashboardMsg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let link = ctx.link().clone();
+        let ticker = Interval::new(30_000, move || link.send_message(InstructorDashboardMsg::Tick));
+        // Instructors trying out reporting have the relay in the URL already
+        let url = telemetry::relay_url().unwrap_or_default();
+        Self { url, connection: ConnectionState::Disconnected, socket: None, socket_listeners: vec![], proofs: BTreeMap::new(), ticker }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            InstructorDashboardMsg::SetUrl(url) => {
+                self.url = url;
+                false
+            }
+            InstructorDashboardMsg::Connect => {
+                self.disconnect();
+                let socket = match WebSocket::new(self.url.trim()) {
+                    Ok(socket) => socket,
+                    Err(_) => {
+                        self.connection = ConnectionState::Failed("Invalid relay URL".into());
+                        return true;
+                    }
+                };
+                let link = ctx.link();
+                let onopen = link.callback(|()| InstructorDashboardMsg::Opened);
+                let onclose = link.callback(|()| InstructorDashboardMsg::Closed);
+                let onerror = onclose.clone();
+                let onmessage = link.callback(InstructorDashboardMsg::Received);
+                self.socket_listeners = vec![
+                    EventListener::new(&socket, "open", move |_| onopen.emit(())),
+                    EventListener::new(&socket, "close", move |_| onclose.emit(())),
+                    EventListener::new(&socket, "error", move |_| onerror.emit(())),
+                    EventListener::new(&socket, "message", move |e| {
+                        if let Some(text) = e.dyn_ref::<MessageEvent>().and_then(|e| e.data().as_string()) {
+                            onmessage.emit(text);
+                        }
+                    }),
+                ];
+                self.socket = Some(socket);
+                self.connection = ConnectionState::Connecting;
+                true
+            }
+            InstructorDashboardMsg::Disconnect => {
+                self.disconnect();
+                self.connection = ConnectionState::Disconnected;
+                true
+            }
+            InstructorDashboardMsg::Opened => {
+                if let Some(socket) = &self.socket {
+                    let _ = socket.send_with_str(telemetry::DASHBOARD_HELLO);
+                }
+                self.connection = ConnectionState::Connected;
+                true
+            }
+            InstructorDashboardMsg::Closed => {
+                let was_connected = self.connection == ConnectionState::Connected;
+                self.disconnect();
+                self.connection = ConnectionState::Failed(if was_connected { "Connection lost".into() } else { "Couldn't connect".into() });
+                true
+            }
+            InstructorDashboardMsg::Received(text) => {
+                self.receive(&text);
+                true
+            }
+            InstructorDashboardMsg::Clear => {
+                self.proofs.clear();
+                true
+            }
+            InstructorDashboardMsg::Tick => !self.proofs.is_empty(),
+        }
+    }
+
+    fn destroy(&mut self, _: &Context<Self>) {
+        self.disconnect();
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="m-4">
+                <div class="d-flex justify-content-between align-items-center">
+                    <h2> { "Instructor dashboard" } </h2>
+                    <button type="button" class="btn btn-outline-secondary btn-sm" disabled={ self.proofs.is_empty() } onclick={ ctx.link().callback(|_| InstructorDashboardMsg::Clear) }> { "Clear" } </button>
+                </div>
+                { self.render_connection(ctx) }
+                { self.render_proofs() }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,470 @@
+//! Keyboard-operable drop-down and context menus
+//!
+//! `Menu` renders a Bootstrap-styled drop-down with nested submenus, keeping
+//! track of which item is highlighted itself instead of relying on
+//! Bootstrap's mouse-centric dropdown plugin. Given a position with `at`, it
+//! instead opens right away at that spot without a button, as a context menu.
+//! While the menu is open it owns the keyboard:
+//!
+//!   * <kbd>↓</kbd>/<kbd>↑</kbd> (and <kbd>Home</kbd>/<kbd>End</kbd>) move
+//!     between the items of the innermost open menu
+//!   * <kbd>→</kbd> opens the highlighted submenu and <kbd>←</kbd> closes it
+//!   * <kbd>Enter</kbd> or <kbd>Space</kbd> picks the highlighted item
+//!   * <kbd>Esc</kbd> closes the innermost menu, returning focus to the menu
+//!     button once the whole menu is closed
+//!   * Typing letters jumps to the next item whose label starts with them
+//!
+//! Menus too large to build on every render, like the rules menu of a step,
+//! can be given `build_items` instead of `items`, which builds them each time
+//! the menu opens.
+
+use crate::util::unique_id;
+
+use gloo::events::EventListener;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::UnwrapThrowExt;
+use web_sys::HtmlElement;
+use yew::prelude::*;
+
+/// How long, in milliseconds, a pause in typing has to be before type-ahead
+/// starts matching from scratch
+const TYPEAHEAD_TIMEOUT_MS: f64 = 500.0;
+
+/// An entry in a `Menu`
+#[derive(Clone, PartialEq)]
+pub enum MenuItem {
+    /// An item that does something when picked
+    Action {
+        label: String,
+        /// Tooltip shown when hovering over the item, as Bootstrap tooltip HTML
+        title: Option<String>,
+        /// <kbd>Ctrl</kbd> keyboard shortcut for the same action, shown next to the label
+        shortcut: Option<char>,
+        onselect: Callback<()>,
+    },
+    /// A nested menu
+    Submenu { label: String, items: Vec<MenuItem> },
+}
+
+impl MenuItem {
+    fn label(&self) -> &str {
+        match self {
+            MenuItem::Action { label, .. } | MenuItem::Submenu { label, .. } => label,
+        }
+    }
+}
+
+/// Properties for `Menu`
+#[derive(Clone, Properties, PartialEq)]
+pub struct MenuProps {
+    /// Text of the button that opens the menu
+    #[prop_or_default]
+    pub label: String,
+
+    /// Classes of the button that opens the menu
+    #[prop_or_else(|| "btn btn-secondary dropdown-toggle".into())]
+    pub button_class: String,
+
+    /// Bootstrap direction class of the menu, such as `dropdown` or `dropright`
+    #[prop_or_else(|| "dropdown".into())]
+    pub direction: String,
+
+    #[prop_or_default]
+    pub items: Vec<MenuItem>,
+
+    /// Builds the items when the menu opens, in place of `items`
+    #[prop_or_default]
+    pub build_items: Option<Callback<(), Vec<MenuItem>>>,
+
+    /// Position in the window, in pixels, to open the menu at without a
+    /// button, as a context menu. The menu opens when created, and the parent
+    /// should stop rendering it once `onclose` is called.
+    #[prop_or_default]
+    pub at: Option<(i32, i32)>,
+
+    /// Called when the menu closes
+    #[prop_or_default]
+    pub onclose: Callback<()>,
+}
+
+/// Message sent to `Menu`
+pub enum MenuMsg {
+    /// The menu button was clicked
+    Toggle,
+
+    /// Close the whole menu, moving focus back to the menu button if `refocus`
+    Close {
+        refocus: bool,
+    },
+
+    /// The mouse moved over the item at a path
+    Highlight(Vec<usize>),
+
+    /// The item at a path was clicked
+    Activate(Vec<usize>),
+
+    Keydown(KeyboardEvent),
+}
+
+/// A drop-down menu operable with both mouse and keyboard. See the module
+/// documentation for the keys it handles.
+pub struct Menu {
+    /// Whether the menu is shown
+    open: bool,
+
+    /// Index of the highlighted item in each open menu level, outermost first.
+    /// Submenus along this path are open, and the last entry is the item that
+    /// keys act on.
+    path: Vec<usize>,
+
+    /// Items built by `build_items` when the menu opened, kept while it's open
+    built_items: Option<Vec<MenuItem>>,
+
+    /// Characters typed so far for type-ahead
+    typeahead: String,
+
+    /// Time of the last type-ahead keypress, from `Date.now()`
+    typeahead_time: f64,
+
+    /// Whether to focus the menu button after the next render
+    refocus_button: bool,
+
+    /// Prefix of the element IDs of menu items
+    id: String,
+
+    node_ref: NodeRef,
+    button_ref: NodeRef,
+    list_ref: NodeRef,
+
+    /// Listener closing the menu when clicking outside of it, present while
+    /// the menu is open
+    #[allow(dead_code)]
+    outside_click_listener: Option<EventListener>,
+}
+
+/// Get the list of menu items that contains the item at `path`
+fn siblings_at<'a>(items: &'a [MenuItem], path: &[usize]) -> &'a [MenuItem] {
+    match path.split_first() {
+        Some((&i, rest)) if !rest.is_empty() => match items.get(i) {
+            Some(MenuItem::Submenu { items, .. }) => siblings_at(items, rest),
+            _ => &[],
+        },
+        _ => items,
+    }
+}
+
+/// Get the menu item at `path`
+fn item_at<'a>(items: &'a [MenuItem], path: &[usize]) -> Option<&'a MenuItem> {
+    siblings_at(items, path).get(*path.last()?)
+}
+
+impl Menu {
+    /// The items, as built when the menu opened if they're built then
+    fn items<'a>(&'a self, ctx: &'a Context<Self>) -> &'a [MenuItem] {
+        self.built_items.as_deref().unwrap_or(&ctx.props().items)
+    }
+
+    /// Build the items, if they're built when the menu opens, returning how
+    /// many there are
+    fn build(&mut self, ctx: &Context<Self>) -> usize {
+        if let Some(build_items) = &ctx.props().build_items {
+            self.built_items = Some(build_items.emit(()));
+        }
+        self.items(ctx).len()
+    }
+
+    fn open(&mut self, ctx: &Context<Self>, path: Vec<usize>) {
+        let link = ctx.link().clone();
+        let node_ref = self.node_ref.clone();
+        let document = web_sys::window().expect_throw("window is undefined").document().expect_throw("document is undefined");
+        self.outside_click_listener = Some(EventListener::new(&document, "mousedown", move |event| {
+            let target = event.target().and_then(|target| target.dyn_into::<web_sys::Node>().ok());
+            let inside = node_ref.get().map(|node| node.contains(target.as_ref())).unwrap_or(false);
+            if !inside {
+                link.send_message(MenuMsg::Close { refocus: false });
+            }
+        }));
+        self.open = true;
+        self.path = path;
+        self.typeahead.clear();
+    }
+
+    fn close(&mut self, ctx: &Context<Self>, refocus: bool) {
+        self.open = false;
+        self.path.clear();
+        self.built_items = None;
+        self.outside_click_listener = None;
+        self.refocus_button = refocus;
+        ctx.props().onclose.emit(());
+    }
+
+    /// Pick the item at `path`, opening it if it's a submenu
+    fn activate(&mut self, ctx: &Context<Self>, path: Vec<usize>) {
+        let onselect = match item_at(self.items(ctx), &path) {
+            Some(MenuItem::Submenu { items, .. }) if !items.is_empty() => None,
+            Some(MenuItem::Action { onselect, .. }) => Some(onselect.clone()),
+            _ => return,
+        };
+        match onselect {
+            Some(onselect) => {
+                onselect.emit(());
+                self.close(ctx, true);
+            }
+            None => {
+                self.path = path;
+                self.path.push(0);
+            }
+        }
+    }
+
+    /// Highlight the next item in the innermost open menu whose label starts
+    /// with what was typed. Typing the same letter repeatedly cycles through
+    /// the items starting with it.
+    fn typeahead(&mut self, ctx: &Context<Self>, key: &str) {
+        let now = js_sys::Date::now();
+        if now - self.typeahead_time > TYPEAHEAD_TIMEOUT_MS {
+            self.typeahead.clear();
+        }
+        self.typeahead_time = now;
+        self.typeahead += &key.to_lowercase();
+
+        let labels = siblings_at(self.items(ctx), &self.path).iter().map(|item| item.label().to_lowercase()).collect::<Vec<_>>();
+        let current = match self.path.last_mut() {
+            Some(current) => current,
+            None => return,
+        };
+        let first = self.typeahead.chars().next();
+        let (prefix, start) = if self.typeahead.chars().all(|c| Some(c) == first) { (first.map(String::from).unwrap_or_default(), *current + 1) } else { (self.typeahead.clone(), *current) };
+        let found = (0..labels.len()).map(|i| (start + i) % labels.len()).find(|&i| labels[i].starts_with(&prefix));
+        if let Some(found) = found {
+            *current = found;
+        }
+    }
+
+    /// Handle a keypress in the menu, returning whether to re-render
+    fn keydown(&mut self, ctx: &Context<Self>, event: KeyboardEvent) -> bool {
+        let key = event.key();
+        if !self.open {
+            if !matches!(key.as_str(), "ArrowDown" | "ArrowUp" | "Enter" | " ") {
+                return false;
+            }
+            let count = self.build(ctx);
+            if count == 0 {
+                return false;
+            }
+            self.open(ctx, vec![if key == "ArrowUp" { count - 1 } else { 0 }]);
+            event.prevent_default();
+            event.stop_propagation();
+            return true;
+        }
+
+        // The menu has the keyboard while it's open, so keep the proof's own
+        // shortcuts (like moving between lines with the arrow keys) from
+        // seeing anything
+        event.stop_propagation();
+        if event.ctrl_key() || event.alt_key() || event.meta_key() {
+            return false;
+        }
+
+        let siblings = siblings_at(self.items(ctx), &self.path).len();
+        let current = self.path.last().copied().unwrap_or_default();
+        let typing = !self.typeahead.is_empty() && js_sys::Date::now() - self.typeahead_time <= TYPEAHEAD_TIMEOUT_MS;
+        match key.as_str() {
+            "ArrowDown" => *self.path.last_mut().unwrap_throw() = (current + 1) % siblings,
+            "ArrowUp" => *self.path.last_mut().unwrap_throw() = (current + siblings - 1) % siblings,
+            "Home" => *self.path.last_mut().unwrap_throw() = 0,
+            "End" => *self.path.last_mut().unwrap_throw() = siblings - 1,
+            "ArrowRight" => {
+                if matches!(item_at(self.items(ctx), &self.path), Some(MenuItem::Submenu { .. })) {
+                    self.activate(ctx, self.path.clone());
+                }
+            }
+            "ArrowLeft" if self.path.len() > 1 => {
+                self.path.pop();
+            }
+            " " if typing => self.typeahead(ctx, " "),
+            "Enter" | " " => self.activate(ctx, self.path.clone()),
+            "Escape" if self.path.len() > 1 => {
+                self.path.pop();
+            }
+            "Escape" => self.close(ctx, true),
+            "Tab" => {
+                self.close(ctx, false);
+                return true;
+            }
+            _ if key.chars().count() == 1 => self.typeahead(ctx, &key),
+            _ => return false,
+        }
+        event.prevent_default();
+        true
+    }
+
+    fn render_items(&self, ctx: &Context<Self>, items: &[MenuItem], prefix: &mut Vec<usize>) -> Html {
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                prefix.push(i);
+                let path = prefix.clone();
+                let highlighted = self.path.starts_with(&path);
+                let class = classes!("dropdown-item", highlighted.then_some("active"));
+                let id = self.item_id(&path);
+                let onmouseenter = {
+                    let path = path.clone();
+                    ctx.link().callback(move |_| MenuMsg::Highlight(path.clone()))
+                };
+                let onclick = {
+                    let path = path.clone();
+                    ctx.link().callback(move |_| MenuMsg::Activate(path.clone()))
+                };
+                let html = match item {
+                    MenuItem::Action { label, title, shortcut, .. } => {
+                        let shortcut = match shortcut {
+                            Some(key) => html! {
+                                <span>
+                                    { ' ' }
+                                    <kbd>
+                                        <kbd> { "Ctrl" } </kbd>
+                                        { '-' }
+                                        <kbd> { key.to_uppercase() } </kbd>
+                                    </kbd>
+                                </span>
+                            },
+                            None => html! {},
+                        };
+                        html! {
+                            <button type="button" tabindex="-1" role="menuitem" id={ id } class={ class }
+                                data-toggle={ title.as_ref().map(|_| "tooltip") } data-placement="left" title={ title.clone() }
+                                { onmouseenter } { onclick }>
+                                { label }
+                                { shortcut }
+                            </button>
+                        }
+                    }
+                    MenuItem::Submenu { label, items } => {
+                        let open = highlighted && self.path.len() > path.len();
+                        let submenu = if open {
+                            html! {
+                                <div class="dropdown-menu show" role="menu">
+                                    { self.render_items(ctx, items, prefix) }
+                                </div>
+                            }
+                        } else {
+                            html! {}
+                        };
+                        html! {
+                            <div class="dropright">
+                                <button type="button" tabindex="-1" role="menuitem" aria-haspopup="true" aria-expanded={ open.to_string() } id={ id } class={ classes!(class, "dropdown-toggle") } { onmouseenter } { onclick }>
+                                    { label }
+                                </button>
+                                { submenu }
+                            </div>
+                        }
+                    }
+                };
+                prefix.pop();
+                html
+            })
+            .collect()
+    }
+
+    fn item_id(&self, path: &[usize]) -> String {
+        path.iter().fold(self.id.clone(), |id, i| format!("{id}-{i}"))
+    }
+}
+
+impl Component for Menu {
+    type Message = MenuMsg;
+    type Properties = MenuProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut menu = Self { open: false, path: vec![], built_items: None, typeahead: String::new(), typeahead_time: 0.0, refocus_button: false, id: unique_id("menu"), node_ref: NodeRef::default(), button_ref: NodeRef::default(), list_ref: NodeRef::default(), outside_click_listener: None };
+        if ctx.props().at.is_some() && menu.build(ctx) > 0 {
+            menu.open(ctx, vec![0]);
+        }
+        menu
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            MenuMsg::Toggle => {
+                if self.open {
+                    self.close(ctx, false);
+                } else if self.build(ctx) > 0 {
+                    self.open(ctx, vec![0]);
+                }
+                true
+            }
+            MenuMsg::Close { refocus } => {
+                self.close(ctx, refocus);
+                true
+            }
+            MenuMsg::Highlight(path) => {
+                self.path = path;
+                true
+            }
+            MenuMsg::Activate(path) => {
+                self.activate(ctx, path);
+                true
+            }
+            MenuMsg::Keydown(event) => self.keydown(ctx, event),
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, _: &Self::Properties) -> bool {
+        // The items may have changed under the highlighted path
+        if self.open && item_at(self.items(ctx), &self.path).is_none() {
+            self.close(ctx, false);
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        // Context menus are placed where they were opened, rather than below
+        // their button
+        let style = props.at.map(|(x, y)| format!("position: fixed; left: {x}px; top: {y}px"));
+        let list = if self.open {
+            html! {
+                <div ref={ self.list_ref.clone() } class="dropdown-menu show" role="menu" tabindex="-1" aria-activedescendant={ self.item_id(&self.path) } { style }>
+                    { self.render_items(ctx, self.items(ctx), &mut vec![]) }
+                </div>
+            }
+        } else {
+            html! {}
+        };
+        if props.at.is_some() {
+            return html! {
+                <div ref={ self.node_ref.clone() } onkeydown={ ctx.link().callback(MenuMsg::Keydown) }>
+                    { list }
+                </div>
+            };
+        }
+        html! {
+            <div ref={ self.node_ref.clone() } class={ props.direction.clone() } onkeydown={ ctx.link().callback(MenuMsg::Keydown) }>
+                <button ref={ self.button_ref.clone() } type="button" class={ props.button_class.clone() } aria-haspopup="true" aria-expanded={ self.open.to_string() } onclick={ ctx.link().callback(|_| MenuMsg::Toggle) }>
+                    { &props.label }
+                </button>
+                { list }
+            </div>
+        }
+    }
+
+    /// Keep keyboard focus on the open menu, or move it back to the menu
+    /// button after closing the menu from the keyboard
+    fn rendered(&mut self, _: &Context<Self>, _: bool) {
+        if self.open {
+            if let Some(list) = self.list_ref.cast::<HtmlElement>() {
+                let focused_inside = web_sys::window().and_then(|window| window.document()).and_then(|document| document.active_element()).map(|elem| list.contains(Some(&*elem))).unwrap_or(false);
+                if !focused_inside {
+                    list.focus().unwrap_throw();
+                }
+            }
+        } else if self.refocus_button {
+            self.refocus_button = false;
+            if let Some(button) = self.button_ref.cast::<HtmlElement>() {
+                button.focus().unwrap_throw();
+            }
+        }
+    }
+}
@@ -1,6 +1,20 @@
 pub mod app;
+mod command_palette;
 mod expr_ast_widget;
 mod expr_entry;
+mod file_drop;
+mod instructor_dashboard;
+mod menu;
 mod nav_bar;
-mod proof_widget;
+mod popover;
+mod problem_bank;
+mod progress_page;
+mod proof_diff;
+mod proof_loader;
+pub mod proof_widget;
+mod rule_example;
+mod rule_reference;
+mod saved_proofs;
+mod symbolization_exercise;
 mod tabbed_container;
+mod truth_table_exercise;
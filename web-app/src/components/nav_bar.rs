@@ -1,19 +1,57 @@
 use crate::components::app::App;
 use crate::components::app::AppMsg;
+use crate::components::command_palette::Command;
+use crate::components::command_palette::CommandPalette;
 use crate::components::expr_ast_widget::ExprAstWidget;
+use crate::components::instructor_dashboard::InstructorDashboard;
+use crate::components::problem_bank::ProblemBank;
+use crate::components::progress_page::ProgressPage;
+use crate::components::proof_widget::tutorial;
+use crate::components::proof_widget::tutorial::Tutorial;
 use crate::components::proof_widget::ProofWidget;
+use crate::components::rule_reference::RuleReference;
+use crate::components::saved_proofs::SavedProofs;
+use crate::offline;
+use crate::preferences::Density;
+use crate::preferences::Keymap;
+use crate::preferences::NewStep;
+use crate::preferences::Preferences;
+use crate::preferences::StorageBackend;
+use crate::preferences::Verification;
+use crate::preferences::FONT_SIZE_RANGE;
+use crate::preferences::INDENT_SIZE_RANGE;
+use crate::progress::Progress;
+use crate::proof_image;
+use crate::signing;
+use crate::storage;
+use crate::storage::StoreError;
+use crate::storage::StoredProof;
+
+use aris::workspace::Workspace;
+use aris::workspace::WorkspaceProof;
 
 use derivative::Derivative;
+use gloo::events::EventListener;
+use gloo::file::callbacks::FileReader;
 use gloo::timers::callback::Timeout;
 use wasm_bindgen::UnwrapThrowExt;
 use wasm_bindgen::{closure::Closure, JsCast, JsValue};
 use web_sys::HtmlInputElement;
+use web_sys::HtmlSelectElement;
+use yew::context::ContextHandle;
 use yew::html::Html;
 use yew::html::Scope;
 use yew::prelude::*;
 use yew_octicons::Icon;
 use yew_octicons::IconKind;
 
+/// How long, in milliseconds, the result of saving a proof or importing a
+/// workspace is shown
+const STATUS_DURATION_MS: u32 = 4000;
+
+/// File name of exported workspaces
+const WORKSPACE_FILE_NAME: &str = "aris-workspace.json";
+
 pub struct FileOpenHelper {
     file_open_closure: Closure<dyn FnMut(JsValue)>,
     filename_tx: std::sync::mpsc::Sender<(String, web_sys::FileReader)>,
@@ -54,14 +92,87 @@ pub struct NavBarWidget {
     node_ref: NodeRef,
     next_tab_idx: usize,
     file_open_helper: FileOpenHelper,
+    preferences: Preferences,
+    _preferences_handle: Option<ContextHandle<Preferences>>,
+    /// The tutorials that come with the app, for the file menu
+    tutorials: Vec<Tutorial>,
+    /// Result of the last save or workspace import, shown for a moment
+    /// afterwards
+    status: Option<Result<String, String>>,
+    /// Timer for hiding `status`
+    #[allow(dead_code)]
+    status_timeout: Option<Timeout>,
+    /// Handle for reading a workspace archive the user picked
+    #[allow(dead_code)]
+    workspace_reader: Option<FileReader>,
+    /// Whether the browser is connected to a network
+    online: bool,
+    /// Handles for listening for the browser going online or offline
+    #[allow(dead_code)]
+    connection_listeners: Vec<EventListener>,
+    /// The dialog that's open, if any
+    dialog: Option<Dialog>,
+    dialog_ref: NodeRef,
+    /// Whether to focus the dialog after the next render, so Escape closes it
+    focus_dialog: bool,
+}
+
+/// A dialog opened from the navigation bar
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Dialog {
+    /// The logic symbol macros
+    Help,
+    /// The display settings
+    DisplaySettings,
 }
 
 pub enum NavBarMsg {
     FileNew,
+    /// Open a proof file from the user's computer
     FileOpen(web_sys::FileList),
+    /// Open the list of proofs saved in the user's store
+    OpenSaved,
+    /// Save the proof in the current tab to the user's store
     FileSave,
+    /// The proof was saved, or saving it failed
+    Saved(Result<StoredProof, StoreError>),
+    HideStatus,
+    /// Download every open proof, the practice progress, and the preferences
+    /// as a workspace archive
+    ExportWorkspace,
+    /// Download the workspace archive of these open proofs
+    DownloadWorkspace(Vec<(String, Vec<u8>)>),
+    /// Open the workspace in an archive the user picked
+    ImportWorkspace(web_sys::FileList),
+    /// A workspace archive was read
+    WorkspaceRead(Result<String, String>),
+    /// The browser went online or offline
+    ConnectionChanged,
+    /// Download the proof in the current tab as a file
+    FileDownload,
+    /// Copy the proof in the current tab to the clipboard as an image
+    CopyAsImage,
+    /// The proof was copied as an image, or copying it failed
+    CopiedAsImage(Result<(), String>),
     NewExprTree,
+    RuleReference,
+    /// Open the library of practice problems
+    ProblemBank,
+    /// Open the page of the user's practice progress
+    ShowProgress,
+    /// Open the live view of students' progress, for instructors
+    InstructorDashboard,
+    /// Open a new proof walking through the built-in tutorial at this index
+    StartTutorial(usize),
     ToggleTheme,
+    /// Change the user's preferences
+    SetPreferences(Preferences),
+    /// The user's preferences were changed
+    PreferencesChanged(Preferences),
+    /// Open one of the navigation bar's dialogs
+    OpenDialog(Dialog),
+    /// Close the open dialog
+    CloseDialog,
     Nop,
 }
 #[derive(Properties, Clone, Derivative)]
@@ -79,7 +190,21 @@ impl Component for NavBarWidget {
     fn create(ctx: &Context<Self>) -> Self {
         ctx.props().oncreate.emit(ctx.link().clone());
         let file_open_helper = FileOpenHelper::new(ctx.props().parent.clone());
-        Self { node_ref: NodeRef::default(), next_tab_idx: 1, file_open_helper }
+        let (preferences, preferences_handle) = match ctx.link().context::<Preferences>(ctx.link().callback(NavBarMsg::PreferencesChanged)) {
+            Some((preferences, handle)) => (preferences, Some(handle)),
+            None => (Preferences::default(), None),
+        };
+        let connection_listeners = match web_sys::window() {
+            Some(window) => ["online", "offline"]
+                .into_iter()
+                .map(|event| {
+                    let link = ctx.link().clone();
+                    EventListener::new(&window, event, move |_| link.send_message(NavBarMsg::ConnectionChanged))
+                })
+                .collect(),
+            None => vec![],
+        };
+        Self { node_ref: NodeRef::default(), next_tab_idx: 1, file_open_helper, preferences, _preferences_handle: preferences_handle, tutorials: tutorial::builtin(), status: None, status_timeout: None, workspace_reader: None, online: offline::is_online(), connection_listeners, dialog: None, dialog_ref: NodeRef::default(), focus_dialog: false }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
@@ -93,33 +218,97 @@ impl Component for NavBarWidget {
                 false
             }
             NavBarMsg::FileOpen(file_list) => self.file_open_helper.fileopen(file_list),
+            NavBarMsg::OpenSaved => {
+                let parent = ctx.props().parent.clone();
+                ctx.props().parent.send_message(AppMsg::CreateTab { name: "Saved proofs".into(), content: html! { <SavedProofs parent={ parent } /> } });
+                false
+            }
             NavBarMsg::FileSave => {
+                let store = storage::current(&self.preferences);
+                let saved = ctx.link().callback(NavBarMsg::Saved);
+                ctx.props().parent.send_message(AppMsg::GetProofFromCurrentTab(Box::new(move |name, prf, metadata| {
+                    let data = signing::xml_from_proof_and_metadata(prf, metadata);
+                    store.save(&name, data, saved);
+                })));
+                false
+            }
+            NavBarMsg::Saved(result) => {
+                let location = storage::current(&self.preferences).location();
+                self.show_status(ctx, result.map(|proof| format!("Saved {} to {}", proof.name, location)).map_err(|err| format!("Couldn't save: {err}")));
+                true
+            }
+            NavBarMsg::HideStatus => {
+                self.status = None;
+                true
+            }
+            NavBarMsg::ExportWorkspace => {
+                ctx.props().parent.send_message(AppMsg::GetAllProofs(ctx.link().callback(NavBarMsg::DownloadWorkspace)));
+                false
+            }
+            NavBarMsg::DownloadWorkspace(proofs) => {
+                let proofs = proofs.into_iter().map(|(name, data)| WorkspaceProof { name, data: String::from_utf8_lossy(&data).into_owned() }).collect();
+                let workspace = Workspace { proofs, progress: Some(Progress::load().to_text()), settings: self.preferences.to_settings() };
                 let node = self.node_ref.get().expect("NavBarWidget::node_ref failed");
-                ctx.props().parent.send_message(AppMsg::GetProofFromCurrentTab(Box::new(move |name, prf| {
-                    use aris::proofs::xml_interop;
-                    let mut data = vec![];
-                    let metadata = xml_interop::ProofMetaData { author: Some("ARIS-YEW-UI".into()), hash: None, goals: vec![] };
-                    xml_interop::xml_from_proof_and_metadata_with_hash(prf, &metadata, &mut data).expect("xml_from_proof_and_metadata failed");
-                    let window = web_sys::window().expect("web_sys::window failed");
-                    let document = window.document().expect("window.document failed");
-                    let anchor = document.create_element("a").expect("document.create_element(\"a\") failed");
-                    let anchor = anchor.dyn_into::<web_sys::HtmlAnchorElement>().expect("dyn_into::HtmlAnchorElement failed");
-                    anchor.set_download(&name);
-                    let js_str = JsValue::from_str(&String::from_utf8_lossy(&data));
-                    let js_array = js_sys::Array::new_with_length(1);
-                    js_array.set(0, js_str);
-                    let blob = web_sys::Blob::new_with_str_sequence(&js_array).expect("Blob::new_with_str_sequence failed");
-                    let url = web_sys::Url::create_object_url_with_blob(&blob).expect("Url::create_object_url_with_blob failed");
-                    anchor.set_href(&url);
-                    node.append_child(&anchor).expect("node.append_child failed");
-                    anchor.click();
-                    Timeout::new(0, move || {
-                        node.remove_child(&anchor).expect("node.remove_child failed");
-                    })
-                    .forget();
+                download(node, WORKSPACE_FILE_NAME, workspace.to_json().as_bytes());
+                false
+            }
+            NavBarMsg::ImportWorkspace(file_list) => {
+                if let Some(file) = file_list.get(0) {
+                    let read = ctx.link().callback(NavBarMsg::WorkspaceRead);
+                    self.workspace_reader = Some(gloo::file::callbacks::read_as_text(&gloo::file::Blob::from(file), move |result| read.emit(result.map_err(|err| err.to_string()))));
+                }
+                false
+            }
+            NavBarMsg::WorkspaceRead(result) => {
+                self.workspace_reader = None;
+                let workspace = match result.and_then(|text| Workspace::from_json(&text)) {
+                    Ok(workspace) => workspace,
+                    Err(err) => {
+                        self.show_status(ctx, Err(format!("Couldn't import the workspace: {err}")));
+                        return true;
+                    }
+                };
+                let invalid = workspace.invalid_proofs();
+                let parent = &ctx.props().parent;
+                for proof in workspace.proofs.into_iter().filter(|proof| !invalid.iter().any(|(name, _)| *name == proof.name)) {
+                    let name = proof.name.clone();
+                    let oncreate = parent.callback(move |link| AppMsg::RegisterProofName { name: name.clone(), link });
+                    parent.send_message(AppMsg::CreateTab { name: proof.name, content: html! { <ProofWidget verbose=true data={ Some(proof.data.into_bytes()) } oncreate={ oncreate } /> } });
+                }
+                if let Some(progress) = workspace.progress {
+                    Progress::from_text(&progress).save();
+                }
+                if !workspace.settings.is_empty() {
+                    parent.send_message(AppMsg::SetPreferences(Preferences::from_settings(&workspace.settings)));
+                }
+                let status = match &invalid[..] {
+                    [] => Ok("Imported the workspace".to_string()),
+                    invalid => Err(format!("Imported the workspace, except for proofs that couldn't be read: {}", invalid.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(", "))),
+                };
+                self.show_status(ctx, status);
+                true
+            }
+            NavBarMsg::ConnectionChanged => {
+                self.online = offline::is_online();
+                true
+            }
+            NavBarMsg::FileDownload => {
+                let node = self.node_ref.get().expect("NavBarWidget::node_ref failed");
+                ctx.props().parent.send_message(AppMsg::GetProofFromCurrentTab(Box::new(move |name, prf, metadata| {
+                    let data = signing::xml_from_proof_and_metadata(prf, metadata);
+                    download(node, &name, &data);
                 })));
                 false
             }
+            NavBarMsg::CopyAsImage => {
+                let copied = ctx.link().callback(NavBarMsg::CopiedAsImage);
+                ctx.props().parent.send_message(AppMsg::GetProofFromCurrentTab(Box::new(move |_, prf, _| proof_image::copy_png_to_clipboard(&proof_image::proof_svg(prf), copied))));
+                false
+            }
+            NavBarMsg::CopiedAsImage(result) => {
+                self.show_status(ctx, result.map(|()| "Copied the proof as an image".to_string()).map_err(|err| format!("Couldn't copy the proof: {err}")));
+                true
+            }
             NavBarMsg::NewExprTree => {
                 ctx.props().parent.send_message(AppMsg::CreateTab {
                     name: format!("Expr Tree {}", self.next_tab_idx),
@@ -130,6 +319,33 @@ impl Component for NavBarWidget {
                 self.next_tab_idx += 1;
                 false
             }
+            NavBarMsg::RuleReference => {
+                let parent = ctx.props().parent.clone();
+                ctx.props().parent.send_message(AppMsg::CreateTab { name: "Rule reference".into(), content: html! { <RuleReference parent={ parent } /> } });
+                false
+            }
+            NavBarMsg::ProblemBank => {
+                let parent = ctx.props().parent.clone();
+                ctx.props().parent.send_message(AppMsg::CreateTab { name: "Practice problems".into(), content: html! { <ProblemBank parent={ parent } /> } });
+                false
+            }
+            NavBarMsg::ShowProgress => {
+                ctx.props().parent.send_message(AppMsg::CreateTab { name: "Progress".into(), content: html! { <ProgressPage /> } });
+                false
+            }
+            NavBarMsg::InstructorDashboard => {
+                ctx.props().parent.send_message(AppMsg::CreateTab { name: "Instructor dashboard".into(), content: html! { <InstructorDashboard /> } });
+                false
+            }
+            NavBarMsg::StartTutorial(index) => {
+                if let Some(tutorial) = self.tutorials.get(index) {
+                    let name = format!("Tutorial: {}", tutorial.title);
+                    let name_ = name.clone();
+                    let oncreate = ctx.props().parent.callback(move |link| AppMsg::RegisterProofName { name: name_.clone(), link });
+                    ctx.props().parent.send_message(AppMsg::CreateTab { name, content: html! { <ProofWidget verbose=true data={ None } oncreate={ oncreate } tutorial={ Some(tutorial.clone()) } /> } });
+                }
+                false
+            }
             NavBarMsg::ToggleTheme => {
                 match theme().as_str() {
                     "light" => {
@@ -142,6 +358,30 @@ impl Component for NavBarWidget {
                 }
                 true
             }
+            NavBarMsg::SetPreferences(preferences) => {
+                ctx.props().parent.send_message(AppMsg::SetPreferences(preferences));
+                false
+            }
+            NavBarMsg::PreferencesChanged(preferences) => {
+                self.preferences = preferences;
+                true
+            }
+            NavBarMsg::OpenDialog(dialog) => {
+                // Keep the page behind the dialog from scrolling
+                if let Some(body) = document().body() {
+                    body.class_list().add_1("modal-open").unwrap_throw();
+                }
+                self.dialog = Some(dialog);
+                self.focus_dialog = true;
+                true
+            }
+            NavBarMsg::CloseDialog => {
+                if let Some(body) = document().body() {
+                    body.class_list().remove_1("modal-open").unwrap_throw();
+                }
+                self.dialog = None;
+                true
+            }
             NavBarMsg::Nop => false,
         }
     }
@@ -150,6 +390,14 @@ impl Component for NavBarWidget {
         true
     }
 
+    fn rendered(&mut self, _: &Context<Self>, _: bool) {
+        if std::mem::take(&mut self.focus_dialog) {
+            if let Some(dialog) = self.dialog_ref.cast::<web_sys::HtmlElement>() {
+                dialog.focus().unwrap_throw();
+            }
+        }
+    }
+
     fn view(&self, ctx: &Context<Self>) -> Html {
         let handle_open_file = ctx.link().callback(move |e: Event| {
             let input: HtmlInputElement = e.target_unchecked_into();
@@ -159,6 +407,24 @@ impl Component for NavBarWidget {
             }
         });
 
+        let handle_import_workspace = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            match input.files() {
+                Some(file_list) => NavBarMsg::ImportWorkspace(file_list),
+                None => NavBarMsg::Nop,
+            }
+        });
+
+        let tutorial_items = self.tutorials.iter().enumerate().map(|(i, tutorial)| {
+            let id = format!("file-menu-tutorial-{i}");
+            html! {
+                <div>
+                    <label for={ id.clone() } class="dropdown-item">{ format!("Tutorial: {}", tutorial.title) }</label>
+                    <input id={ id } style="display:none" type="button" onclick={ ctx.link().callback(move |_| NavBarMsg::StartTutorial(i)) } />
+                </div>
+            }
+        });
+
         let file_menu = html! {
             <ul class="navbar-nav">
                 <li ref={ self.node_ref.clone() } class="nav-item dropdown show">
@@ -169,17 +435,74 @@ impl Component for NavBarWidget {
                             <input id="file-menu-new-proof" style="display:none" type="button" onclick={ ctx.link().callback(|_| NavBarMsg::FileNew) } />
                         </div>
                         <div>
-                            <label for="file-menu-open-proof" class="dropdown-item">{"Open proof"}</label>
+                            <label for="file-menu-open-saved-proof" class="dropdown-item">{"Open proof"}</label>
+                            <input id="file-menu-open-saved-proof" style="display:none" type="button" onclick={ ctx.link().callback(|_| NavBarMsg::OpenSaved) } />
+                        </div>
+                        <div>
+                            <label for="file-menu-open-proof" class="dropdown-item">{"Open proof from file"}</label>
                             <input id="file-menu-open-proof" style="display:none" type="file" onchange={ handle_open_file } />
                         </div>
                         <div>
                             <label for="file-menu-save-proof" class="dropdown-item">{"Save proof"}</label>
                             <input id="file-menu-save-proof" style="display:none" type="button" onclick={ ctx.link().callback(|_| NavBarMsg::FileSave) } />
                         </div>
+                        <div>
+                            <label for="file-menu-download-proof" class="dropdown-item">{"Download proof"}</label>
+                            <input id="file-menu-download-proof" style="display:none" type="button" onclick={ ctx.link().callback(|_| NavBarMsg::FileDownload) } />
+                        </div>
+                        <div>
+                            <label for="file-menu-copy-image" class="dropdown-item">{"Copy proof as image"}</label>
+                            <input id="file-menu-copy-image" style="display:none" type="button" onclick={ ctx.link().callback(|_| NavBarMsg::CopyAsImage) } />
+                        </div>
+                        <div>
+                            <label for="file-menu-export-workspace" class="dropdown-item">{"Export workspace"}</label>
+                            <input id="file-menu-export-workspace" style="display:none" type="button" onclick={ ctx.link().callback(|_| NavBarMsg::ExportWorkspace) } />
+                        </div>
+                        <div>
+                            <label for="file-menu-import-workspace" class="dropdown-item">{"Import workspace"}</label>
+                            <input id="file-menu-import-workspace" style="display:none" type="file" accept=".json,application/json" onchange={ handle_import_workspace } />
+                        </div>
                         <div>
                             <label for="file-menu-new-expr-tree" class="dropdown-item">{"New expression tree"}</label>
                             <input id="file-menu-new-expr-tree" style="display:none" type="button" onclick={ ctx.link().callback(|_| NavBarMsg::NewExprTree) } />
                         </div>
+                        <div>
+                            <label for="file-menu-rule-reference" class="dropdown-item">{"Rule reference"}</label>
+                            <input id="file-menu-rule-reference" style="display:none" type="button" onclick={ ctx.link().callback(|_| NavBarMsg::RuleReference) } />
+                        </div>
+                        <div>
+                            <label for="file-menu-problem-bank" class="dropdown-item">{"Practice problems"}</label>
+                            <input id="file-menu-problem-bank" style="display:none" type="button" onclick={ ctx.link().callback(|_| NavBarMsg::ProblemBank) } />
+                        </div>
+                        <div>
+                            <label for="file-menu-progress" class="dropdown-item">{"Progress"}</label>
+                            <input id="file-menu-progress" style="display:none" type="button" onclick={ ctx.link().callback(|_| NavBarMsg::ShowProgress) } />
+                        </div>
+                        <div>
+                            <label for="file-menu-instructor-dashboard" class="dropdown-item">{"Instructor dashboard"}</label>
+                            <input id="file-menu-instructor-dashboard" style="display:none" type="button" onclick={ ctx.link().callback(|_| NavBarMsg::InstructorDashboard) } />
+                        </div>
+                        { for tutorial_items }
+                    </div>
+                </li>
+            </ul>
+        };
+
+        let check_mark = |checked: bool| if checked { "\u{2713} " } else { "" };
+        let view_menu = html! {
+            <ul class="navbar-nav">
+                <li class="nav-item dropdown">
+                    <a class="nav-link dropdown-toggle" href="#" role="button" id="viewMenuLink" data-toggle="dropdown" aria-haspopup="true" aria-expanded="false">{"View"}</a>
+                    <div class="dropdown-menu" aria-labelledby="viewMenuLink">
+                        <a class="dropdown-item" href="#" onclick={ self.preference_callback(ctx, |preferences, _: MouseEvent| preferences.show_cited_formulas = !preferences.show_cited_formulas) }>
+                            { check_mark(self.preferences.show_cited_formulas) }
+                            { "Show cited formulas" }
+                        </a>
+                        <a class="dropdown-item" href="#" onclick={ self.preference_callback(ctx, |preferences, _: MouseEvent| preferences.show_discharges = !preferences.show_discharges) }>
+                            { check_mark(self.preferences.show_discharges) }
+                            { "Show discharged assumptions" }
+                        </a>
+                        <a class="dropdown-item" href="#" onclick={ ctx.link().callback(|e: MouseEvent| { e.prevent_default(); NavBarMsg::OpenDialog(Dialog::DisplaySettings) }) }>{ "Display settings\u{2026}" }</a>
                     </div>
                 </li>
             </ul>
@@ -236,6 +559,7 @@ impl Component for NavBarWidget {
                 <a class="navbar-brand" href="#"> { "Aris" } </a>
 
                 { file_menu }
+                { view_menu }
 
                 // Palette of logic symbols
                 <div class="container">
@@ -244,6 +568,9 @@ impl Component for NavBarWidget {
                     </div>
                 </div>
 
+                { self.render_status() }
+                { if self.online { html! {} } else { html! { <span class="badge badge-warning mr-3" title="Proofs can still be written, checked, and saved in this browser"> { "Offline" } </span> } } }
+
                 <ul class="navbar-nav ml-auto">
                     // Theme toggle
                     <li class="nav-item">
@@ -253,7 +580,7 @@ impl Component for NavBarWidget {
                     </li>
                     // Help menu
                     <li class="nav-item">
-                        <a class="nav-link" data-toggle="modal" data-target="#help-modal">
+                        <a class="nav-link" onclick={ ctx.link().callback(|_| NavBarMsg::OpenDialog(Dialog::Help)) }>
                             { Icon::new_big(IconKind::Question) }
                         </a>
                     </li>
@@ -264,7 +591,305 @@ impl Component for NavBarWidget {
         html! {
             <>
                 { navbar }
-                { render_help_modal() }
+                { self.render_dialog(ctx) }
+                <CommandPalette parent={ ctx.props().parent.clone() } commands={ self.commands(ctx) } />
+            </>
+        }
+    }
+}
+
+impl NavBarWidget {
+    /// Commands of the navigation bar's menus, for the command palette
+    fn commands(&self, ctx: &Context<Self>) -> Vec<Command> {
+        let open_dialog = |dialog| ctx.link().callback(move |()| NavBarMsg::OpenDialog(dialog));
+        let cited_formulas_label = if self.preferences.show_cited_formulas { "Hide cited formulas" } else { "Show cited formulas" };
+        let mut toggled_preferences = self.preferences.clone();
+        toggled_preferences.show_cited_formulas = !toggled_preferences.show_cited_formulas;
+        let discharges_label = if self.preferences.show_discharges { "Hide discharged assumptions" } else { "Show discharged assumptions" };
+        let mut discharges_preferences = self.preferences.clone();
+        discharges_preferences.show_discharges = !discharges_preferences.show_discharges;
+        let mut vim_preferences = self.preferences.clone();
+        let vim_label = match self.preferences.keymap {
+            Keymap::Default => {
+                vim_preferences.keymap = Keymap::Vim;
+                "Use Vim keybindings"
+            }
+            Keymap::Vim => {
+                vim_preferences.keymap = Keymap::Default;
+                "Use default keybindings"
+            }
+        };
+        let mut verification_preferences = self.preferences.clone();
+        let verification_label = match self.preferences.verification {
+            Verification::Live => {
+                verification_preferences.verification = Verification::OnDemand;
+                "Check proof only on demand"
+            }
+            Verification::OnDemand => {
+                verification_preferences.verification = Verification::Live;
+                "Check proof while editing"
+            }
+        };
+        let mut commands = vec![
+            Command::new("File", "New blank proof", ctx.link().callback(|()| NavBarMsg::FileNew)),
+            Command::new("File", "Open proof", ctx.link().callback(|()| NavBarMsg::OpenSaved)),
+            // Opening a file needs the file picker of the file menu's hidden
+            // input
+            Command::new(
+                "File",
+                "Open proof from file\u{2026}",
+                Callback::from(|()| {
+                    if let Some(input) = document().get_element_by_id("file-menu-open-proof").and_then(|elem| elem.dyn_into::<HtmlInputElement>().ok()) {
+                        input.click();
+                    }
+                }),
+            ),
+            Command::new("File", "Save proof", ctx.link().callback(|()| NavBarMsg::FileSave)),
+            Command::new("File", "Download proof", ctx.link().callback(|()| NavBarMsg::FileDownload)),
+            Command::new("File", "Copy proof as image", ctx.link().callback(|()| NavBarMsg::CopyAsImage)),
+            Command::new("File", "Export workspace", ctx.link().callback(|()| NavBarMsg::ExportWorkspace)),
+            Command::new("File", "New expression tree", ctx.link().callback(|()| NavBarMsg::NewExprTree)),
+            Command::new("Help", "Rule reference", ctx.link().callback(|()| NavBarMsg::RuleReference)),
+            Command::new("File", "Practice problems", ctx.link().callback(|()| NavBarMsg::ProblemBank)),
+            Command::new("View", "Progress and achievements", ctx.link().callback(|()| NavBarMsg::ShowProgress)),
+            Command::new("View", "Instructor dashboard", ctx.link().callback(|()| NavBarMsg::InstructorDashboard)),
+            Command::new("View", cited_formulas_label, ctx.link().callback(move |()| NavBarMsg::SetPreferences(toggled_preferences.clone()))),
+            Command::new("View", discharges_label, ctx.link().callback(move |()| NavBarMsg::SetPreferences(discharges_preferences.clone()))),
+            Command::new("View", "Display settings\u{2026}", open_dialog(Dialog::DisplaySettings)),
+            Command::new("Edit", vim_label, ctx.link().callback(move |()| NavBarMsg::SetPreferences(vim_preferences.clone()))),
+            Command::new("Edit", verification_label, ctx.link().callback(move |()| NavBarMsg::SetPreferences(verification_preferences.clone()))),
+            Command::new("View", "Toggle dark theme", ctx.link().callback(|()| NavBarMsg::ToggleTheme)),
+            Command::new("Help", "Logic symbol macros", open_dialog(Dialog::Help)),
+        ];
+        commands.extend(self.tutorials.iter().enumerate().map(|(i, tutorial)| Command::new("Help", format!("Tutorial: {}", tutorial.title), ctx.link().callback(move |()| NavBarMsg::StartTutorial(i)))));
+        commands
+    }
+
+    /// Show the result of saving a proof or importing a workspace for a moment
+    fn show_status(&mut self, ctx: &Context<Self>, status: Result<String, String>) {
+        self.status = Some(status);
+        let link = ctx.link().clone();
+        self.status_timeout = Some(Timeout::new(STATUS_DURATION_MS, move || link.send_message(NavBarMsg::HideStatus)));
+    }
+
+    /// Render the result of the last save or import, while it's shown
+    fn render_status(&self) -> Html {
+        match &self.status {
+            Some(Ok(message)) => html! { <span class="navbar-text text-nowrap mr-3"> { message } </span> },
+            Some(Err(message)) => html! { <span class="navbar-text text-nowrap mr-3 text-warning"> { message } </span> },
+            None => html! {},
+        }
+    }
+
+    /// Callback changing the user's preferences with `change`, which gets the
+    /// current preferences and the event that triggered the callback
+    fn preference_callback<E: AsRef<Event> + 'static>(&self, ctx: &Context<Self>, change: impl Fn(&mut Preferences, E) + 'static) -> Callback<E> {
+        let preferences = self.preferences.clone();
+        ctx.link().callback(move |e: E| {
+            e.as_ref().prevent_default();
+            let mut preferences = preferences.clone();
+            change(&mut preferences, e);
+            NavBarMsg::SetPreferences(preferences)
+        })
+    }
+
+    /// Render the open dialog, closed with its close button, by clicking
+    /// outside it, or with Escape
+    fn render_dialog(&self, ctx: &Context<Self>) -> Html {
+        let (id, title, body) = match self.dialog {
+            Some(Dialog::Help) => ("help-modal", "Aris Help", render_help_body()),
+            Some(Dialog::DisplaySettings) => ("display-settings-modal", "Display settings", self.render_display_settings_body(ctx)),
+            None => return html! {},
+        };
+        let close = ctx.link().callback(|_| NavBarMsg::CloseDialog);
+        // The dialog fills the page, so clicks on it that aren't on its
+        // content are outside the content
+        let onclick = ctx.link().batch_callback(|e: MouseEvent| (e.target() == e.current_target()).then_some(NavBarMsg::CloseDialog));
+        let onkeydown = ctx.link().batch_callback(|e: KeyboardEvent| (e.key() == "Escape").then_some(NavBarMsg::CloseDialog));
+        let label = format!("{id}-label");
+
+        html! {
+            <>
+                <div class="modal-backdrop show"></div>
+                <div ref={ self.dialog_ref.clone() } class="modal d-block" id={ id } tabindex="-1" role="dialog" aria-modal="true" aria-labelledby={ label.clone() } { onclick } { onkeydown }>
+                    <div class="modal-dialog" role="document">
+                        <div class="modal-content">
+                            <div class="modal-header">
+                                <h5 class="modal-title" id={ label }> { title } </h5>
+                                <button type="button" class="close" aria-label="Close" onclick={ close }>
+                                    <span aria-hidden="true"> { '×' } </span>
+                                </button>
+                            </div>
+                            <div class="modal-body">
+                                { body }
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            </>
+        }
+    }
+
+    /// Render the settings for the row spacing, formula font size, and indent
+    /// guide size of proofs, the keyboard shortcuts for editing them, when
+    /// they're checked, and where they're saved
+    fn render_display_settings_body(&self, ctx: &Context<Self>) -> Html {
+        let input_value = |e: InputEvent| e.target_unchecked_into::<HtmlInputElement>().value();
+        let density_options = [Density::Comfortable, Density::Compact].iter().map(|&density| {
+            let label = match density {
+                Density::Comfortable => "Comfortable",
+                Density::Compact => "Compact",
+            };
+            html! {
+                <option value={ density.as_str() } selected={ self.preferences.density == density }> { label } </option>
+            }
+        });
+        let onchange_density = self.preference_callback(ctx, move |preferences, e: Event| {
+            if let Ok(density) = e.target_unchecked_into::<HtmlSelectElement>().value().parse() {
+                preferences.density = density;
+            }
+        });
+        let keymap_options = [Keymap::Default, Keymap::Vim].iter().map(|&keymap| {
+            let label = match keymap {
+                Keymap::Default => "Default",
+                Keymap::Vim => "Vim (normal and insert modes)",
+            };
+            html! {
+                <option value={ keymap.as_str() } selected={ self.preferences.keymap == keymap }> { label } </option>
+            }
+        });
+        let onchange_keymap = self.preference_callback(ctx, move |preferences, e: Event| {
+            if let Ok(keymap) = e.target_unchecked_into::<HtmlSelectElement>().value().parse() {
+                preferences.keymap = keymap;
+            }
+        });
+        let verification_options = [Verification::Live, Verification::OnDemand].iter().map(|&verification| {
+            let label = match verification {
+                Verification::Live => "While editing",
+                Verification::OnDemand => "Only when clicking \"Check proof\" or saving",
+            };
+            html! {
+                <option value={ verification.as_str() } selected={ self.preferences.verification == verification }> { label } </option>
+            }
+        });
+        let onchange_verification = self.preference_callback(ctx, move |preferences, e: Event| {
+            if let Ok(verification) = e.target_unchecked_into::<HtmlSelectElement>().value().parse() {
+                preferences.verification = verification;
+            }
+        });
+        let new_step_options = [NewStep::Empty, NewStep::Reiteration, NewStep::SameRule].iter().map(|&new_step| {
+            let label = match new_step {
+                NewStep::Empty => "Empty",
+                NewStep::Reiteration => "Reiterating the line above",
+                NewStep::SameRule => "Using the rule of the step above",
+            };
+            html! {
+                <option value={ new_step.as_str() } selected={ self.preferences.new_step == new_step }> { label } </option>
+            }
+        });
+        let onchange_new_step = self.preference_callback(ctx, move |preferences, e: Event| {
+            if let Ok(new_step) = e.target_unchecked_into::<HtmlSelectElement>().value().parse() {
+                preferences.new_step = new_step;
+            }
+        });
+        let onchange_advance_on_enter = self.preference_callback(ctx, move |preferences, e: Event| {
+            preferences.advance_on_enter = e.target_unchecked_into::<HtmlInputElement>().checked();
+        });
+        let storage_options = [StorageBackend::IndexedDb, StorageBackend::LocalStorage, StorageBackend::Remote].iter().map(|&storage| {
+            let label = match storage {
+                StorageBackend::IndexedDb => "This browser",
+                StorageBackend::LocalStorage => "This browser's local storage (small proofs only)",
+                StorageBackend::Remote => "A server",
+            };
+            html! {
+                <option value={ storage.as_str() } selected={ self.preferences.storage == storage }> { label } </option>
+            }
+        });
+        let onchange_storage = self.preference_callback(ctx, move |preferences, e: Event| {
+            if let Ok(storage) = e.target_unchecked_into::<HtmlSelectElement>().value().parse() {
+                preferences.storage = storage;
+            }
+        });
+        let onchange_storage_url = self.preference_callback(ctx, move |preferences, e: Event| {
+            preferences.storage_url = e.target_unchecked_into::<HtmlInputElement>().value();
+        });
+        let storage_url = match self.preferences.storage {
+            StorageBackend::Remote => html! {
+                <input id="display-settings-storage-url" type="url" class="form-control mt-2" placeholder="https://aris.example.edu/api"
+                    value={ self.preferences.storage_url.clone() } onchange={ onchange_storage_url } />
+            },
+            _ => html! {},
+        };
+        let oninput_font_size = self.preference_callback(ctx, move |preferences, e: InputEvent| {
+            if let Ok(size) = input_value(e).parse() {
+                preferences.font_size = size;
+            }
+        });
+        let oninput_indent_size = self.preference_callback(ctx, move |preferences, e: InputEvent| {
+            if let Ok(size) = input_value(e).parse() {
+                preferences.indent_size = size;
+            }
+        });
+        let onclick_reset = self.preference_callback(ctx, |preferences, _: MouseEvent| {
+            *preferences = Preferences { show_cited_formulas: preferences.show_cited_formulas, show_discharges: preferences.show_discharges, storage: preferences.storage, storage_url: preferences.storage_url.clone(), ..Preferences::default() };
+        });
+
+        html! {
+            <>
+                <div class="form-group">
+                    <label for="display-settings-density"> { "Row spacing" } </label>
+                    <select id="display-settings-density" class="form-control" onchange={ onchange_density }>
+                        { for density_options }
+                    </select>
+                </div>
+                <div class="form-group">
+                    <label for="display-settings-font-size"> { format!("Formula font size: {}px", self.preferences.font_size) } </label>
+                    <input id="display-settings-font-size" type="range" class="custom-range"
+                        min={ FONT_SIZE_RANGE.start().to_string() } max={ FONT_SIZE_RANGE.end().to_string() }
+                        value={ self.preferences.font_size.to_string() } oninput={ oninput_font_size } />
+                </div>
+                <div class="form-group">
+                    <label for="display-settings-indent-size"> { format!("Indent guide size: {}px", self.preferences.indent_size) } </label>
+                    <input id="display-settings-indent-size" type="range" class="custom-range"
+                        min={ INDENT_SIZE_RANGE.start().to_string() } max={ INDENT_SIZE_RANGE.end().to_string() }
+                        value={ self.preferences.indent_size.to_string() } oninput={ oninput_indent_size } />
+                </div>
+                <div class="form-group">
+                    <label for="display-settings-keymap"> { "Keyboard shortcuts" } </label>
+                    <select id="display-settings-keymap" class="form-control" aria-describedby="display-settings-keymap-help" onchange={ onchange_keymap }>
+                        { for keymap_options }
+                    </select>
+                    <small id="display-settings-keymap-help" class="form-text text-muted">
+                        { "In Vim's normal mode, " } <kbd> { "j" } </kbd> { " and " } <kbd> { "k" } </kbd> { " move between lines, " }
+                        <kbd> { "o" } </kbd> { " and " } <kbd> { "O" } </kbd> { " insert a step below or above, or several after a count like " } <kbd> { "5o" } </kbd> { ", " }
+                        <kbd> { "dd" } </kbd> { " deletes the line, and " } <kbd> { "i" } </kbd> { " starts typing. " }
+                        <kbd> { "Esc" } </kbd> { " returns to normal mode." }
+                    </small>
+                </div>
+                <div class="form-group">
+                    <label for="display-settings-verification"> { "Check lines" } </label>
+                    <select id="display-settings-verification" class="form-control" onchange={ onchange_verification }>
+                        { for verification_options }
+                    </select>
+                </div>
+                <div class="form-group">
+                    <label for="display-settings-new-step"> { "Start new steps" } </label>
+                    <select id="display-settings-new-step" class="form-control" onchange={ onchange_new_step }>
+                        { for new_step_options }
+                    </select>
+                </div>
+                <div class="form-group form-check">
+                    <input id="display-settings-advance-on-enter" type="checkbox" class="form-check-input" checked={ self.preferences.advance_on_enter } onchange={ onchange_advance_on_enter } />
+                    <label class="form-check-label" for="display-settings-advance-on-enter"> { "Go to the next line when pressing Enter in a correct line" } </label>
+                </div>
+                <div class="form-group">
+                    <label for="display-settings-storage"> { "Save proofs to" } </label>
+                    <select id="display-settings-storage" class="form-control" onchange={ onchange_storage }>
+                        { for storage_options }
+                    </select>
+                    { storage_url }
+                </div>
+                <button type="button" class="btn btn-secondary" onclick={ onclick_reset }> { "Reset to defaults" } </button>
             </>
         }
     }
@@ -280,31 +905,31 @@ fn document_element() -> web_sys::Element {
     document().document_element().expect_throw("document.document_element()")
 }
 
+/// Download `data` as a file named `name`, through a temporary link added to
+/// `node`
+fn download(node: web_sys::Node, name: &str, data: &[u8]) {
+    let anchor = document().create_element("a").expect("document.create_element(\"a\") failed");
+    let anchor = anchor.dyn_into::<web_sys::HtmlAnchorElement>().expect("dyn_into::HtmlAnchorElement failed");
+    anchor.set_download(name);
+    let js_str = JsValue::from_str(&String::from_utf8_lossy(data));
+    let js_array = js_sys::Array::new_with_length(1);
+    js_array.set(0, js_str);
+    let blob = web_sys::Blob::new_with_str_sequence(&js_array).expect("Blob::new_with_str_sequence failed");
+    let url = web_sys::Url::create_object_url_with_blob(&blob).expect("Url::create_object_url_with_blob failed");
+    anchor.set_href(&url);
+    node.append_child(&anchor).expect("node.append_child failed");
+    anchor.click();
+    Timeout::new(0, move || {
+        node.remove_child(&anchor).expect("node.remove_child failed");
+    })
+    .forget();
+}
+
 /// Get the name of the current theme, or panic if the theme attribute doesn't exist.
 pub fn theme() -> String {
     document_element().get_attribute("theme").expect("failed querying theme")
 }
 
-fn render_help_modal() -> Html {
-    html! {
-        <div class="modal fade" id="help-modal" tabindex="-1" role="dialog" aria-labelledby="help-modal-label" aria-hidden="true">
-            <div class="modal-dialog" role="document">
-                <div class="modal-content">
-                    <div class="modal-header">
-                        <h5 class="modal-title" id="help-modal-label"> { "Aris Help" } </h5>
-                        <button type="button" class="close" data-dismiss="modal" aria-label="Close">
-                            <span aria-hidden="true"> { '×' } </span>
-                        </button>
-                    </div>
-                    <div class="modal-body">
-                        { render_help_body() }
-                    </div>
-                </div>
-            </div>
-        </div>
-    }
-}
-
 fn render_help_body() -> Html {
     // Maximum amount of macros for any symbol
     let max_col_span = aris::macros::TABLE.iter().map(|(_, macros)| macros.len()).max().unwrap_or_default();
@@ -333,6 +958,7 @@ fn render_help_body() -> Html {
 
     html! {
         <>
+            <p> { "Press " } <kbd> { "Ctrl-Shift-P" } </kbd> { " to search for and run any command." } </p>
             <h5> { "Logic symbol macros" } </h5>
             <table class="table table-bordered">
                 <thead>
@@ -0,0 +1,145 @@
+//! Buttons showing a message in a popover next to them when clicked, like the
+//! error buttons of proof lines
+//!
+//! The popover is styled like Bootstrap's, but shown and placed by the
+//! component itself, so it works without Bootstrap's JavaScript or jQuery on
+//! the page. Clicking the button again, clicking outside of the popover, or
+//! pressing <kbd>Esc</kbd> closes it. Details too long to show up front, like
+//! the rule and citations of a wrong step, are behind a "More details" button
+//! in the popover, and showing them is recorded as asking for a hint.
+
+use aris::analytics;
+use aris::analytics::Event;
+
+use gloo::events::EventListener;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::UnwrapThrowExt;
+use yew::prelude::*;
+
+/// Properties for `Popover`
+#[derive(Clone, Properties, PartialEq)]
+pub struct PopoverProps {
+    /// Text of the button
+    pub label: String,
+
+    /// Classes of the button, such as `btn btn-danger`
+    pub class: Classes,
+
+    /// Text of the popover
+    pub content: String,
+
+    /// More about the content, shown below it on request
+    #[prop_or_default]
+    pub details: Option<Html>,
+}
+
+pub enum PopoverMsg {
+    Toggle,
+    Close,
+    ToggleDetails,
+    Keydown(KeyboardEvent),
+}
+
+/// A button that shows `content` in a popover to its right
+pub struct Popover {
+    /// Whether the popover is shown
+    open: bool,
+
+    /// Whether the details are shown in the popover
+    expanded: bool,
+
+    node_ref: NodeRef,
+
+    /// Listener closing the popover when clicking outside of it, present
+    /// while the popover is shown
+    #[allow(dead_code)]
+    outside_click_listener: Option<EventListener>,
+}
+
+impl Popover {
+    fn close(&mut self) {
+        self.open = false;
+        self.expanded = false;
+        self.outside_click_listener = None;
+    }
+}
+
+impl Component for Popover {
+    type Message = PopoverMsg;
+    type Properties = PopoverProps;
+
+    fn create(_: &Context<Self>) -> Self {
+        Self { open: false, expanded: false, node_ref: NodeRef::default(), outside_click_listener: None }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            PopoverMsg::Toggle if !self.open => {
+                let link = ctx.link().clone();
+                let node_ref = self.node_ref.clone();
+                let document = web_sys::window().expect_throw("window is undefined").document().expect_throw("document is undefined");
+                self.outside_click_listener = Some(EventListener::new(&document, "mousedown", move |event| {
+                    let target = event.target().and_then(|target| target.dyn_into::<web_sys::Node>().ok());
+                    let inside = node_ref.get().map(|node| node.contains(target.as_ref())).unwrap_or(false);
+                    if !inside {
+                        link.send_message(PopoverMsg::Close);
+                    }
+                }));
+                self.open = true;
+            }
+            PopoverMsg::Toggle | PopoverMsg::Close => self.close(),
+            PopoverMsg::ToggleDetails => {
+                self.expanded = !self.expanded;
+                if self.expanded {
+                    analytics::record(&Event::HintRequested);
+                }
+            }
+            PopoverMsg::Keydown(event) => {
+                if !self.open || event.key() != "Escape" {
+                    return false;
+                }
+                // Keep Esc from also switching the Vim keymap's mode
+                event.stop_propagation();
+                self.close();
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let popover = if self.open {
+            let details = props.details.clone().map(|details| {
+                let label = if self.expanded { "Fewer details" } else { "More details" };
+                let shown = self.expanded.then(|| html! { <div class="small"> { details } </div> });
+                html! {
+                    <>
+                        <button type="button" class="btn btn-link btn-sm d-block p-0 mt-1" aria-expanded={ self.expanded.to_string() } onclick={ ctx.link().callback(|_| PopoverMsg::ToggleDetails) }>
+                            { label }
+                        </button>
+                        { for shown }
+                    </>
+                }
+            });
+            html! {
+                <div class="popover bs-popover-right" role="tooltip">
+                    <div class="arrow"></div>
+                    <div class="popover-body">
+                        { &props.content }
+                        { for details }
+                    </div>
+                </div>
+            }
+        } else {
+            html! {}
+        };
+        html! {
+            <span ref={ self.node_ref.clone() } class="popover-anchor" onkeydown={ ctx.link().callback(PopoverMsg::Keydown) }>
+                <button type="button" class={ props.class.clone() } aria-expanded={ self.open.to_string() } onclick={ ctx.link().callback(|_| PopoverMsg::Toggle) }>
+                    { &props.label }
+                </button>
+                { popover }
+            </span>
+        }
+    }
+}
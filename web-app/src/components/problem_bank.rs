@@ -0,0 +1,378 @@
+use crate::components::app::App;
+use crate::components::app::AppMsg;
+use crate::components::proof_widget::ProofWidget;
+use crate::components::symbolization_exercise::SymbolizationExercise;
+use crate::components::truth_table_exercise::TruthTableExercise;
+use crate::problems;
+use crate::problems::Problem;
+use crate::problems::Task;
+use crate::problems::MAX_DIFFICULTY;
+use crate::progress;
+use crate::progress::Progress;
+use crate::storage::IndexedDbStore;
+use crate::storage::StoreError;
+
+use std::collections::BTreeSet;
+
+use derivative::Derivative;
+use gloo::events::EventListener;
+use gloo::file::callbacks::FileReader;
+use gloo::timers::callback::Timeout;
+use web_sys::HtmlInputElement;
+use web_sys::HtmlSelectElement;
+use yew::html::Scope;
+use yew::prelude::*;
+
+/// How long to wait after a problem is solved before opening the next one, so
+/// the user gets to see the solved proof
+const ADVANCE_DELAY_MS: u32 = 1500;
+
+/// Library of practice problems, filterable by topic and difficulty. Solving a
+/// problem opens the next unsolved one, in order of difficulty.
+pub struct ProblemBank {
+    problems: Vec<Problem>,
+
+    /// Name of the file the problems were loaded from, or `None` for the
+    /// problems that come with the app
+    manifest_name: Option<String>,
+
+    /// Error loading the last manifest the user picked
+    manifest_error: Option<String>,
+
+    /// Manifests loaded before, kept in IndexedDB, as their names and text
+    saved_manifests: Vec<(String, String)>,
+
+    /// Only show problems practicing this topic
+    topic: Option<String>,
+
+    /// Only show problems of this difficulty
+    difficulty: Option<u32>,
+
+    /// Whether solving a problem opens the next one
+    auto_advance: bool,
+
+    progress: Progress,
+
+    /// Handle for listening for saved progress
+    #[allow(dead_code)]
+    progress_listener: Option<EventListener>,
+
+    /// Handle for reading a manifest the user picked
+    #[allow(dead_code)]
+    manifest_reader: Option<FileReader>,
+
+    /// Handle for opening the next problem after one is solved
+    #[allow(dead_code)]
+    advance_timeout: Option<Timeout>,
+}
+
+pub enum ProblemBankMsg {
+    SetTopic(Option<String>),
+    SetDifficulty(Option<u32>),
+    ToggleAutoAdvance,
+    /// Open a new tab for solving the problem
+    Open(Problem),
+    /// Open the next unsolved problem after this one, or the first if `None`
+    OpenNext(Option<Problem>),
+    /// The proof or exercise of this problem was solved
+    Solved(Problem),
+    /// Read problems from a manifest file the user picked
+    LoadManifest(web_sys::FileList),
+    /// A manifest file with this name was read
+    ManifestRead(String, Result<String, String>),
+    /// Go back to the problems that come with the app
+    UseBuiltin,
+    /// Switch to the saved manifest with this name
+    UseSaved(String),
+    /// The saved manifests were listed
+    SavedListed(Result<Vec<(String, String)>, StoreError>),
+    /// A manifest was saved, so list the saved manifests again
+    ManifestSaved(Result<(), StoreError>),
+    /// The progress was saved, so reload it
+    ReloadProgress,
+}
+
+#[derive(Properties, Clone, Derivative)]
+#[derivative(PartialEq)]
+pub struct ProblemBankProps {
+    #[derivative(PartialEq = "ignore")]
+    pub parent: Scope<App>,
+}
+
+impl ProblemBank {
+    /// Show these problems, clearing the filters, which may not apply to them
+    fn use_problems(&mut self, problems: Vec<Problem>, manifest_name: Option<String>) {
+        self.problems = problems;
+        self.manifest_name = manifest_name;
+        self.manifest_error = None;
+        self.topic = None;
+        self.difficulty = None;
+    }
+
+    fn render_source(&self, ctx: &Context<Self>) -> Html {
+        if self.saved_manifests.is_empty() {
+            return match &self.manifest_name {
+                Some(name) => html! {
+                    <>
+                        { format!("Problems from {name}. ") }
+                        <a href="#" onclick={ ctx.link().callback(|e: MouseEvent| { e.prevent_default(); ProblemBankMsg::UseBuiltin }) }> { "Use the built-in problems" } </a>
+                    </>
+                },
+                None => html! { { "Built-in problems. " } },
+            };
+        }
+        let on_source = ctx.link().callback(|e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            match select.value() {
+                name if name.is_empty() => ProblemBankMsg::UseBuiltin,
+                name => ProblemBankMsg::UseSaved(name),
+            }
+        });
+        html! {
+            <select class="custom-select custom-select-sm w-auto mr-2" aria-label="Problem set" onchange={ on_source }>
+                <option value="" selected={ self.manifest_name.is_none() }> { "Built-in problems" } </option>
+                { for self.saved_manifests.iter().map(|(name, _)| html! { <option value={ name.clone() } selected={ self.manifest_name.as_ref() == Some(name) }> { name.clone() } </option> }) }
+            </select>
+        }
+    }
+
+    fn is_solved(&self, problem: &Problem) -> bool {
+        self.progress.solved.contains(&problem.key())
+    }
+
+    /// Problems matching the topic and difficulty filters, in manifest order
+    fn filtered(&self) -> Vec<Problem> {
+        self.problems.iter().filter(|problem| self.topic.as_ref().map(|topic| problem.topics.contains(topic)).unwrap_or(true) && self.difficulty.map(|difficulty| problem.difficulty == difficulty).unwrap_or(true)).cloned().collect()
+    }
+
+    fn open(&self, ctx: &Context<Self>, problem: Problem) {
+        let name = problem.title.clone();
+        let name_ = name.clone();
+        let parent = &ctx.props().parent;
+        let data = problem.to_xml();
+        let task = problem.task.clone();
+        let onsolved = ctx.link().callback(move |()| ProblemBankMsg::Solved(problem.clone()));
+        let content = match task {
+            Task::Proof { .. } => {
+                let oncreate = parent.callback(move |link| AppMsg::RegisterProofName { name: name_.clone(), link });
+                html! { <ProofWidget verbose=true data={ data } oncreate={ oncreate } onsolved={ Some(onsolved) } /> }
+            }
+            Task::TruthTable { table, connectives } => html! { <TruthTableExercise table={ table } connectives={ connectives } onsolved={ Some(onsolved) } /> },
+            Task::Symbolization(symbolization) => html! { <SymbolizationExercise symbolization={ symbolization } onsolved={ Some(onsolved) } /> },
+        };
+        parent.send_message(AppMsg::CreateTab { name, content });
+    }
+
+    fn render_filters(&self, ctx: &Context<Self>) -> Html {
+        let topics = self.problems.iter().flat_map(|problem| problem.topics.iter().cloned()).collect::<BTreeSet<_>>();
+        let on_topic = ctx.link().callback(|e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            ProblemBankMsg::SetTopic(Some(select.value()).filter(|topic| !topic.is_empty()))
+        });
+        let on_difficulty = ctx.link().callback(|e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            ProblemBankMsg::SetDifficulty(select.value().parse().ok())
+        });
+        html! {
+            <div class="form-row align-items-end">
+                <div class="form-group col-sm-4">
+                    <label for="problem-bank-topic"> { "Topic" } </label>
+                    <select id="problem-bank-topic" class="form-control" onchange={ on_topic }>
+                        <option value="" selected={ self.topic.is_none() }> { "Any topic" } </option>
+                        { for topics.into_iter().map(|topic| html! { <option value={ topic.clone() } selected={ self.topic.as_ref() == Some(&topic) }> { topic.clone() } </option> }) }
+                    </select>
+                </div>
+                <div class="form-group col-sm-4">
+                    <label for="problem-bank-difficulty"> { "Difficulty" } </label>
+                    <select id="problem-bank-difficulty" class="form-control" onchange={ on_difficulty }>
+                        <option value="" selected={ self.difficulty.is_none() }> { "Any difficulty" } </option>
+                        { for (1..=MAX_DIFFICULTY).map(|difficulty| html! { <option value={ difficulty.to_string() } selected={ self.difficulty == Some(difficulty) }> { difficulty_stars(difficulty) } </option> }) }
+                    </select>
+                </div>
+                <div class="form-group col-sm-4">
+                    <div class="form-check">
+                        <input id="problem-bank-auto-advance" class="form-check-input" type="checkbox" checked={ self.auto_advance } onchange={ ctx.link().callback(|_| ProblemBankMsg::ToggleAutoAdvance) } />
+                        <label class="form-check-label" for="problem-bank-auto-advance"> { "Open the next problem after solving one" } </label>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_problem(&self, ctx: &Context<Self>, problem: Problem) -> Html {
+        let solved = self.is_solved(&problem);
+        let statement = match &problem.task {
+            Task::Proof { premises, goal } => format!("{} \u{22a2} {}", premises.iter().map(|premise| premise.to_string()).collect::<Vec<_>>().join(", "), goal),
+            Task::TruthTable { table, connectives } => progress::table_key(table, connectives),
+            Task::Symbolization(symbolization) => symbolization.sentence.clone(),
+        };
+        let title = problem.title.clone();
+        let topics = problem.topics.clone();
+        let difficulty = problem.difficulty;
+        html! {
+            <button type="button" class="list-group-item list-group-item-action" onclick={ ctx.link().callback(move |_| ProblemBankMsg::Open(problem.clone())) }>
+                <div class="d-flex justify-content-between align-items-center">
+                    <h5 class="mb-1">
+                        { title }
+                        { if solved { html! { <span class="badge badge-success ml-2"> { "Solved" } </span> } } else { html! {} } }
+                    </h5>
+                    <small class="text-muted" title={ format!("Difficulty {difficulty} of {MAX_DIFFICULTY}") }> { difficulty_stars(difficulty) } </small>
+                </div>
+                <p class="mb-1"><code> { statement } </code></p>
+                { for topics.into_iter().map(|topic| html! { <span class="badge badge-secondary mr-1"> { topic } </span> }) }
+            </button>
+        }
+    }
+}
+
+/// Difficulty drawn as filled stars out of `MAX_DIFFICULTY`
+fn difficulty_stars(difficulty: u32) -> String {
+    (1..=MAX_DIFFICULTY).map(|i| if i <= difficulty { '\u{2605}' } else { '\u{2606}' }).collect()
+}
+
+impl Component for ProblemBank {
+    type Message = ProblemBankMsg;
+    type Properties = ProblemBankProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let reload = ctx.link().callback(|()| ProblemBankMsg::ReloadProgress);
+        let progress_listener = web_sys::window().map(|window| EventListener::new(&window, progress::CHANGED_EVENT, move |_| reload.emit(())));
+        IndexedDbStore.problem_sets(ctx.link().callback(ProblemBankMsg::SavedListed));
+        Self { problems: problems::builtin(), manifest_name: None, manifest_error: None, saved_manifests: vec![], topic: None, difficulty: None, auto_advance: true, progress: Progress::load(), progress_listener, manifest_reader: None, advance_timeout: None }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ProblemBankMsg::SetTopic(topic) => {
+                self.topic = topic;
+                true
+            }
+            ProblemBankMsg::SetDifficulty(difficulty) => {
+                self.difficulty = difficulty;
+                true
+            }
+            ProblemBankMsg::ToggleAutoAdvance => {
+                self.auto_advance = !self.auto_advance;
+                true
+            }
+            ProblemBankMsg::Open(problem) => {
+                self.open(ctx, problem);
+                false
+            }
+            ProblemBankMsg::OpenNext(current) => {
+                let filtered = self.filtered();
+                if let Some(next) = problems::next_problem(&filtered, current.as_ref(), |problem| self.is_solved(problem)) {
+                    self.open(ctx, next.clone());
+                }
+                false
+            }
+            ProblemBankMsg::Solved(problem) => {
+                // The proof or exercise saves the progress before telling the
+                // bank it was solved, so it's up to date here
+                self.progress = Progress::load();
+                if self.auto_advance {
+                    let open_next = ctx.link().callback(ProblemBankMsg::OpenNext);
+                    self.advance_timeout = Some(Timeout::new(ADVANCE_DELAY_MS, move || open_next.emit(Some(problem))));
+                }
+                true
+            }
+            ProblemBankMsg::LoadManifest(file_list) => {
+                if let Some(file) = file_list.get(0) {
+                    let name = file.name();
+                    let read = ctx.link().callback(move |result: Result<String, String>| ProblemBankMsg::ManifestRead(name.clone(), result));
+                    self.manifest_reader = Some(gloo::file::callbacks::read_as_text(&gloo::file::Blob::from(file), move |result| read.emit(result.map_err(|err| err.to_string()))));
+                }
+                false
+            }
+            ProblemBankMsg::ManifestRead(name, result) => {
+                self.manifest_reader = None;
+                match result.and_then(|text| Ok((problems::parse_manifest(&text)?, text))) {
+                    Ok((problems, text)) => {
+                        // Keep the manifest, so it can be picked again later
+                        // without finding the file
+                        IndexedDbStore.save_problem_set(&name, &text, ctx.link().callback(ProblemBankMsg::ManifestSaved));
+                        self.use_problems(problems, Some(name));
+                    }
+                    Err(err) => self.manifest_error = Some(format!("Couldn't load {name}: {err}")),
+                }
+                true
+            }
+            ProblemBankMsg::UseBuiltin => {
+                self.use_problems(problems::builtin(), None);
+                true
+            }
+            ProblemBankMsg::UseSaved(name) => {
+                let manifest = self.saved_manifests.iter().find(|(saved, _)| *saved == name).map(|(_, text)| problems::parse_manifest(text));
+                match manifest {
+                    Some(Ok(problems)) => self.use_problems(problems, Some(name)),
+                    Some(Err(err)) => self.manifest_error = Some(format!("Couldn't load {name}: {err}")),
+                    None => return false,
+                }
+                true
+            }
+            ProblemBankMsg::SavedListed(manifests) => {
+                // Without IndexedDB, manifests just aren't kept
+                self.saved_manifests = manifests.unwrap_or_default();
+                true
+            }
+            ProblemBankMsg::ManifestSaved(result) => {
+                if result.is_ok() {
+                    IndexedDbStore.problem_sets(ctx.link().callback(ProblemBankMsg::SavedListed));
+                }
+                false
+            }
+            ProblemBankMsg::ReloadProgress => {
+                self.progress = Progress::load();
+                true
+            }
+        }
+    }
+
+    fn changed(&mut self, _: &Context<Self>, _: &Self::Properties) -> bool {
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let on_manifest = ctx.link().callback(|e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            match input.files() {
+                Some(file_list) => ProblemBankMsg::LoadManifest(file_list),
+                None => ProblemBankMsg::ReloadProgress,
+            }
+        });
+        let error = match &self.manifest_error {
+            Some(error) => html! { <div class="alert alert-danger"> { error } </div> },
+            None => html! {},
+        };
+
+        let mut filtered = self.filtered();
+        // Sorting is stable, so problems of the same difficulty keep the order
+        // of the manifest
+        filtered.sort_by_key(|problem| problem.difficulty);
+        let solved = filtered.iter().filter(|problem| self.is_solved(problem)).count();
+        let list = if filtered.is_empty() {
+            html! { <p class="text-muted"> { "No problems match these filters." } </p> }
+        } else {
+            html! { <div class="list-group"> { for filtered.iter().cloned().map(|problem| self.render_problem(ctx, problem)) } </div> }
+        };
+        let all_solved = solved == filtered.len();
+
+        html! {
+            <div class="m-4">
+                <h2> { "Practice problems" } </h2>
+                <p class="text-muted">
+                    { self.render_source(ctx) }
+                    <label for="problem-bank-manifest" class="btn btn-link btn-sm p-0 align-baseline"> { "Load problems from a file\u{2026}" } </label>
+                    <input id="problem-bank-manifest" style="display:none" type="file" accept=".json,application/json" onchange={ on_manifest } />
+                </p>
+                { error }
+                { self.render_filters(ctx) }
+                <div class="d-flex justify-content-between align-items-center mb-2">
+                    <span> { format!("{} of {} solved", solved, filtered.len()) } </span>
+                    <button type="button" class="btn btn-primary btn-sm" disabled={ all_solved } onclick={ ctx.link().callback(|_| ProblemBankMsg::OpenNext(None)) }> { "Next unsolved problem" } </button>
+                </div>
+                { list }
+            </div>
+        }
+    }
+}
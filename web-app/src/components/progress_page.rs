@@ -0,0 +1,126 @@
+use crate::progress;
+use crate::progress::Progress;
+use crate::progress::ACHIEVEMENTS;
+use crate::progress::MASTERY_USES;
+
+use gloo::events::EventListener;
+use yew::prelude::*;
+
+/// Page showing the user's practice statistics and achievements, kept up to
+/// date as proofs are solved in other tabs
+pub struct ProgressPage {
+    progress: Progress,
+
+    /// Handle for listening for saved progress
+    #[allow(dead_code)]
+    progress_listener: Option<EventListener>,
+}
+
+pub enum ProgressPageMsg {
+    /// The progress was saved, so reload it
+    Reload,
+    /// Forget all progress, after confirming
+    Reset,
+}
+
+impl Component for ProgressPage {
+    type Message = ProgressPageMsg;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let reload = ctx.link().callback(|()| ProgressPageMsg::Reload);
+        let progress_listener = web_sys::window().map(|window| EventListener::new(&window, progress::CHANGED_EVENT, move |_| reload.emit(())));
+        Self { progress: Progress::load(), progress_listener }
+    }
+
+    fn update(&mut self, _: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ProgressPageMsg::Reload => {
+                self.progress = Progress::load();
+                true
+            }
+            ProgressPageMsg::Reset => {
+                let confirmed = web_sys::window().and_then(|window| window.confirm_with_message("Forget all solved problems, rules mastered, and streaks?").ok()).unwrap_or(false);
+                if confirmed {
+                    Progress::default().save();
+                }
+                false
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let progress = &self.progress;
+        let stat = |label: &str, value: String| {
+            html! {
+                <div class="col-sm-3 mb-3">
+                    <div class="card text-center">
+                        <div class="card-body">
+                            <h3 class="card-title mb-0"> { value } </h3>
+                            <small class="text-muted"> { label } </small>
+                        </div>
+                    </div>
+                </div>
+            }
+        };
+        let days = |count: u32| if count == 1 { "1 day".to_string() } else { format!("{count} days") };
+
+        let achievements = ACHIEVEMENTS.iter().map(|achievement| {
+            let earned = (achievement.earned)(progress);
+            let class = if earned { "list-group-item" } else { "list-group-item text-muted" };
+            html! {
+                <div class={ class }>
+                    <span class="progress-achievement-mark"> { if earned { "\u{2605}" } else { "\u{2606}" } } </span>
+                    <strong> { achievement.name } </strong>
+                    { " \u{2014} " }
+                    { achievement.description }
+                </div>
+            }
+        });
+
+        let rules = if progress.rule_uses.is_empty() {
+            html! { <p class="text-muted"> { "Rules you use in solved problems show up here." } </p> }
+        } else {
+            let rows = progress.rule_uses.iter().map(|(rule, &uses)| {
+                let percent = uses.min(MASTERY_USES) * 100 / MASTERY_USES;
+                let bar_class = if uses >= MASTERY_USES { "progress-bar bg-success" } else { "progress-bar" };
+                html! {
+                    <tr>
+                        <td> { rule } </td>
+                        <td> { uses } </td>
+                        <td class="w-50">
+                            <div class="progress" title={ format!("{} of {} problems to master", uses.min(MASTERY_USES), MASTERY_USES) }>
+                                <div class={ bar_class } role="progressbar" style={ format!("width: {percent}%") }></div>
+                            </div>
+                        </td>
+                    </tr>
+                }
+            });
+            html! {
+                <table class="table table-sm">
+                    <thead>
+                        <tr><th> { "Rule" } </th><th> { "Problems solved with it" } </th><th> { "Mastery" } </th></tr>
+                    </thead>
+                    <tbody> { for rows } </tbody>
+                </table>
+            }
+        };
+
+        html! {
+            <div class="m-4">
+                <h2> { "Progress" } </h2>
+                <div class="row mt-3">
+                    { stat("Problems solved", progress.solved.len().to_string()) }
+                    { stat("Rules mastered", progress.rules_mastered().count().to_string()) }
+                    { stat("Current streak", days(progress.current_streak(progress::today()))) }
+                    { stat("Longest streak", days(progress.longest_streak())) }
+                </div>
+                <h4 class="mt-3"> { "Achievements" } </h4>
+                <div class="list-group"> { for achievements } </div>
+                <h4 class="mt-4"> { "Rules" } </h4>
+                { rules }
+                <button type="button" class="btn btn-outline-danger btn-sm mt-2" onclick={ ctx.link().callback(|_| ProgressPageMsg::Reset) }> { "Reset progress" } </button>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::util::P;
+
+use aris::proofs::diff;
+use aris::proofs::diff::Citation;
+use aris::proofs::diff::DiffLine;
+use aris::proofs::diff::LineDiff;
+use aris::proofs::line_info;
+use aris::proofs::xml_interop;
+use aris::rule_names;
+
+use yew::prelude::*;
+
+/// Two proofs side by side, like a submission and a model solution, with
+/// their lines aligned and the added, removed, and changed ones highlighted
+pub struct ProofDiffView;
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ProofDiffViewProps {
+    /// What the left proof is, like "Submission"
+    pub left_name: String,
+    /// The left proof, in the XML format of `xml_interop`
+    pub left: Vec<u8>,
+    pub right_name: String,
+    pub right: Vec<u8>,
+}
+
+/// A step's rule and the line numbers it cites, like "∧ Elimination: 1, 3-5"
+fn justification_text(line: &DiffLine) -> String {
+    let (rule, citations) = match &line.justification {
+        Some(justification) => justification,
+        None => return String::new(),
+    };
+    let cited = citations.iter().map(|citation| match citation {
+        Citation::Line(i) => (i + 1).to_string(),
+        Citation::Subproof(first, last) => line_info::format_range((first + 1, last + 1)),
+    });
+    let cited = cited.collect::<Vec<_>>();
+    if cited.is_empty() {
+        rule_names::display_name(*rule)
+    } else {
+        format!("{}: {}", rule_names::display_name(*rule), cited.join(", "))
+    }
+}
+
+/// Cells of a line of one side, or empty cells if it's not on that side
+fn render_cells(lines: &[DiffLine], i: Option<usize>) -> Html {
+    let (i, line) = match i.and_then(|i| lines.get(i).map(|line| (i, line))) {
+        Some(found) => found,
+        None => return html! { <><td></td><td></td><td></td></> },
+    };
+    let style = format!("padding-left: {}em", 0.5 + 1.5 * line.depth as f64);
+    html! {
+        <>
+            <td class="text-muted text-right"> { (i + 1).to_string() } </td>
+            <td { style }> { &line.expr } </td>
+            <td> <small class="text-muted"> { justification_text(line) } </small> </td>
+        </>
+    }
+}
+
+impl Component for ProofDiffView {
+    type Message = ();
+    type Properties = ProofDiffViewProps;
+
+    fn create(_: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let parse = |name: &str, data: &[u8]| xml_interop::proof_from_xml::<P, _>(data).map(|(prf, _)| prf).map_err(|err| format!("{name} isn't a valid proof: {err}"));
+        let (left, right) = match (parse(&props.left_name, &props.left), parse(&props.right_name, &props.right)) {
+            (Ok(left), Ok(right)) => (left, right),
+            (Err(err), _) | (_, Err(err)) => return html! { <div class="alert alert-danger m-4"> { err } </div> },
+        };
+        let diff = diff::diff(&left, &right);
+        let summary = if diff.is_empty() {
+            "The proofs have the same lines.".to_string()
+        } else {
+            let count = |f: fn(&LineDiff) -> bool| diff.lines.iter().filter(|line| f(line)).count();
+            let added = count(|line| matches!(line, LineDiff::Added { .. }));
+            let removed = count(|line| matches!(line, LineDiff::Removed { .. }));
+            let changed = count(|line| matches!(line, LineDiff::Changed { .. }));
+            format!("{added} added, {removed} removed, and {changed} changed lines.")
+        };
+        let rows = diff.lines.iter().map(|line| {
+            let (class, left_i, right_i) = match *line {
+                LineDiff::Unchanged { left, right } => (None, Some(left), Some(right)),
+                LineDiff::Changed { left, right } => (Some("table-warning"), Some(left), Some(right)),
+                LineDiff::Removed { left } => (Some("table-danger"), Some(left), None),
+                LineDiff::Added { right } => (Some("table-success"), None, Some(right)),
+            };
+            html! {
+                <tr class={ classes!(class) }>
+                    { render_cells(&diff.left, left_i) }
+                    { render_cells(&diff.right, right_i) }
+                </tr>
+            }
+        });
+        html! {
+            <div class="m-4">
+                <p class="text-muted">
+                    { summary } { " " }
+                    <span class="badge badge-success"> { "Added" } </span> { " " }
+                    <span class="badge badge-danger"> { "Removed" } </span> { " " }
+                    <span class="badge badge-warning"> { "Changed" } </span>
+                </p>
+                <table class="table table-sm proof-diff">
+                    <thead>
+                        <tr>
+                            <th colspan="3"> { &props.left_name } </th>
+                            <th colspan="3"> { &props.right_name } </th>
+                        </tr>
+                    </thead>
+                    <tbody> { for rows } </tbody>
+                </table>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use crate::components::proof_widget::ProofWidget;
+use crate::preferences::Preferences;
+use crate::storage;
+use crate::storage::IndexedDbStore;
+use crate::storage::StoreError;
+
+use derivative::Derivative;
+use yew::html::Scope;
+use yew::prelude::*;
+
+/// Proof opened from the store picked in the user's preferences. The proof is
+/// loaded in the background, so its tab opens right away, even for large
+/// proofs.
+pub struct ProofLoader {
+    /// Contents of the proof, or `None` while they're being loaded
+    data: Option<Result<Vec<u8>, StoreError>>,
+}
+
+pub enum ProofLoaderMsg {
+    Loaded(Result<Vec<u8>, StoreError>),
+}
+
+#[derive(Properties, Clone, Derivative)]
+#[derivative(PartialEq)]
+pub struct ProofLoaderProps {
+    /// Name of the saved proof
+    pub name: String,
+    /// When the version of the proof to open was saved, or `None` for the
+    /// latest one. Versions are only kept in IndexedDB.
+    #[prop_or_default]
+    pub version: Option<f64>,
+    /// Passed on to the proof's `ProofWidget`
+    #[derivative(PartialEq = "ignore")]
+    pub oncreate: Callback<Scope<ProofWidget>>,
+}
+
+impl Component for ProofLoader {
+    type Message = ProofLoaderMsg;
+    type Properties = ProofLoaderProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let props = ctx.props();
+        let loaded = ctx.link().callback(ProofLoaderMsg::Loaded);
+        match props.version {
+            Some(version) => IndexedDbStore.load_version(&props.name, version, loaded),
+            None => {
+                let preferences = ctx.link().context::<Preferences>(Callback::noop()).map(|(preferences, _)| preferences).unwrap_or_default();
+                storage::current(&preferences).load(&props.name, loaded);
+            }
+        }
+        Self { data: None }
+    }
+
+    fn update(&mut self, _: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ProofLoaderMsg::Loaded(data) => self.data = Some(data),
+        }
+        true
+    }
+
+    fn changed(&mut self, _: &Context<Self>, _: &Self::Properties) -> bool {
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        match &self.data {
+            None => html! {
+                <div class="m-4 d-flex align-items-center text-muted">
+                    <div class="spinner-border spinner-border-sm mr-2" role="status"></div>
+                    { format!("Opening {}\u{2026}", ctx.props().name) }
+                </div>
+            },
+            Some(Err(err)) => html! { <div class="alert alert-danger m-4"> { err.to_string() } </div> },
+            Some(Ok(data)) => html! { <ProofWidget verbose=true data={ Some(data.clone()) } oncreate={ ctx.props().oncreate.clone() } /> },
+        }
+    }
+}
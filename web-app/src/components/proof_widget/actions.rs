@@ -13,6 +13,7 @@ use aris::proofs::PjRef;
 use aris::proofs::Proof;
 
 /// Information associated with a line action
+#[derive(PartialEq)]
 pub struct ActionInfo {
     /// Short description of action, displayed in action selector menu
     pub description: &'static str,
@@ -34,7 +35,7 @@ pub struct ActionInfo {
 /// ## Parameters:
 ///   * `proof` - the current proof object
 ///   * `line_ref` - reference to the current line
-pub fn valid_actions(proof: &P, line_ref: PjRef<P>) -> impl Iterator<Item = &ActionInfo> {
+pub fn valid_actions(proof: &P, line_ref: PjRef<P>) -> impl Iterator<Item = &'static ActionInfo> {
     use frunk_core::coproduct::Coproduct::{Inl, Inr};
 
     // Can the current line be deleted?
@@ -47,7 +48,11 @@ pub fn valid_actions(proof: &P, line_ref: PjRef<P>) -> impl Iterator<Item = &Act
     let is_just = matches!(line_ref, Inr(Inl(_)));
 
     // Is the current line in a subproof?
-    let in_subproof = proof.parent_of_line(&pj_to_pjs::<P>(line_ref)).is_some();
+    let parent = proof.parent_of_line(&pj_to_pjs::<P>(line_ref));
+    let in_subproof = parent.is_some();
+
+    // Can the current line's subproof take another assumption?
+    let may_add_assumption = parent.is_some_and(|parent| proof.may_add_assumption(&parent));
 
     ACTIONS.iter().filter(move |action_info| match action_info.line_action_kind {
         LineActionKind::Insert { relative_to, what, .. } => {
@@ -57,9 +62,10 @@ pub fn valid_actions(proof: &P, line_ref: PjRef<P>) -> impl Iterator<Item = &Act
                 ProofItemKind::Subproof => in_subproof,
             };
 
-            if what == ProofItemKind::Premise {
-                // Subproofs should only have one assumption
-                valid && !in_subproof
+            if what == ProofItemKind::Premise && in_subproof {
+                // Assumptions go next to the subproof's others, if the
+                // assumption policy allows more than one
+                valid && relative_to == ProofItemKind::Premise && may_add_assumption
             } else {
                 valid
             }
@@ -81,18 +87,18 @@ static ACTIONS: [ActionInfo; 15] = [
     ActionInfo { keyboard_shortcut: None, description: "Delete subproof", line_action_kind: LineActionKind::Delete { what: ProofItemKind::Subproof } },
     // Insert actions
     // Subproof-relative insert actions
-    ActionInfo { keyboard_shortcut: None, description: "Insert step before this subproof", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Just, after: false, relative_to: ProofItemKind::Subproof } },
-    ActionInfo { keyboard_shortcut: Some('e'), description: "Insert step after this subproof", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Just, after: true, relative_to: ProofItemKind::Subproof } },
-    ActionInfo { keyboard_shortcut: None, description: "Insert subproof before this subproof", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Subproof, after: false, relative_to: ProofItemKind::Subproof } },
-    ActionInfo { keyboard_shortcut: None, description: "Insert subproof after this subproof", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Subproof, after: true, relative_to: ProofItemKind::Subproof } },
+    ActionInfo { keyboard_shortcut: None, description: "Insert step before this subproof", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Just, after: false, relative_to: ProofItemKind::Subproof, count: 1 } },
+    ActionInfo { keyboard_shortcut: Some('e'), description: "Insert step after this subproof", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Just, after: true, relative_to: ProofItemKind::Subproof, count: 1 } },
+    ActionInfo { keyboard_shortcut: None, description: "Insert subproof before this subproof", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Subproof, after: false, relative_to: ProofItemKind::Subproof, count: 1 } },
+    ActionInfo { keyboard_shortcut: None, description: "Insert subproof after this subproof", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Subproof, after: true, relative_to: ProofItemKind::Subproof, count: 1 } },
     // Premise-relative insert actions
-    ActionInfo { keyboard_shortcut: None, description: "Insert premise before this premise", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Premise, after: false, relative_to: ProofItemKind::Premise } },
-    ActionInfo { keyboard_shortcut: Some('r'), description: "Insert premise after this premise", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Premise, after: true, relative_to: ProofItemKind::Premise } },
-    ActionInfo { keyboard_shortcut: Some('a'), description: "Insert step after this premise", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Just, after: true, relative_to: ProofItemKind::Premise } },
+    ActionInfo { keyboard_shortcut: None, description: "Insert premise before this premise", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Premise, after: false, relative_to: ProofItemKind::Premise, count: 1 } },
+    ActionInfo { keyboard_shortcut: Some('r'), description: "Insert premise after this premise", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Premise, after: true, relative_to: ProofItemKind::Premise, count: 1 } },
+    ActionInfo { keyboard_shortcut: Some('a'), description: "Insert step after this premise", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Just, after: true, relative_to: ProofItemKind::Premise, count: 1 } },
     // Step-relative insert actions
-    ActionInfo { keyboard_shortcut: Some('b'), description: "Insert step before this step", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Just, after: false, relative_to: ProofItemKind::Just } },
-    ActionInfo { keyboard_shortcut: Some('a'), description: "Insert step after this step", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Just, after: true, relative_to: ProofItemKind::Just } },
-    ActionInfo { keyboard_shortcut: None, description: "Insert subproof before this step", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Subproof, after: false, relative_to: ProofItemKind::Just } },
-    ActionInfo { keyboard_shortcut: Some('p'), description: "Insert subproof after this step", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Subproof, after: true, relative_to: ProofItemKind::Just } },
-    ActionInfo { keyboard_shortcut: Some('r'), description: "Insert premise before this step", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Premise, after: false, relative_to: ProofItemKind::Just } },
+    ActionInfo { keyboard_shortcut: Some('b'), description: "Insert step before this step", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Just, after: false, relative_to: ProofItemKind::Just, count: 1 } },
+    ActionInfo { keyboard_shortcut: Some('a'), description: "Insert step after this step", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Just, after: true, relative_to: ProofItemKind::Just, count: 1 } },
+    ActionInfo { keyboard_shortcut: None, description: "Insert subproof before this step", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Subproof, after: false, relative_to: ProofItemKind::Just, count: 1 } },
+    ActionInfo { keyboard_shortcut: Some('p'), description: "Insert subproof after this step", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Subproof, after: true, relative_to: ProofItemKind::Just, count: 1 } },
+    ActionInfo { keyboard_shortcut: Some('r'), description: "Insert premise before this step", line_action_kind: LineActionKind::Insert { what: ProofItemKind::Premise, after: false, relative_to: ProofItemKind::Just, count: 1 } },
 ];
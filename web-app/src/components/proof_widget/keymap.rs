@@ -0,0 +1,124 @@
+//! Keyboard shortcuts of the proof editor
+//!
+//! Key presses are translated into `KeyCommand`s according to the user's
+//! `Keymap` preference, so `ProofWidget` only needs to know how to perform
+//! each command, not which keys trigger it.
+
+use crate::preferences::Keymap;
+
+/// Editing mode of the Vim keymap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Keys move between lines and edit the proof's structure, rather than
+    /// typing into the selected line
+    Normal,
+    /// Keys type into the selected line
+    Insert,
+}
+
+/// Something a key press does in the proof editor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyCommand {
+    /// Perform the line action with this <kbd>Ctrl</kbd> shortcut, as listed
+    /// in `actions::valid_actions()`
+    Shortcut(char),
+    /// Select the line `offset` lines below the selected line, or above it if
+    /// negative
+    MoveSelection(isize),
+    /// Select the first line of the proof
+    FirstLine,
+    /// Select the last line of the proof
+    LastLine,
+    /// Insert this many steps below the selected line
+    InsertBelow(usize),
+    /// Insert this many lines of the same kind above the selected line
+    InsertAbove(usize),
+    /// Delete the selected line
+    DeleteLine,
+    /// Switch the editing mode
+    SetMode(Mode),
+    /// Swallow the key without doing anything, such as a letter typed in
+    /// normal mode
+    Ignore,
+}
+
+/// State of the keymap between key presses
+#[derive(Debug)]
+pub struct KeyState {
+    /// Editing mode, for the Vim keymap
+    pub mode: Mode,
+
+    /// First key of a two-key command, like the first <kbd>d</kbd> of
+    /// <kbd>dd</kbd>, if one was just pressed
+    pending: Option<char>,
+
+    /// Count typed before a command, like the 5 of <kbd>5o</kbd>, if any
+    count: Option<usize>,
+}
+
+impl Default for KeyState {
+    fn default() -> Self {
+        Self { mode: Mode::Normal, pending: None, count: None }
+    }
+}
+
+impl KeyState {
+    /// Translate a key press into the command it performs with `keymap`, if
+    /// any. `key` is the value of `KeyboardEvent.key`, and `ctrl` is whether
+    /// <kbd>Ctrl</kbd> was held.
+    pub fn translate(&mut self, keymap: Keymap, key: &str, ctrl: bool) -> Option<KeyCommand> {
+        let pending = self.pending.take();
+        let count = self.count.take();
+
+        // All keymaps share the line action shortcuts
+        if ctrl {
+            return key.chars().next().map(KeyCommand::Shortcut);
+        }
+        match key {
+            "ArrowDown" => return Some(KeyCommand::MoveSelection(1)),
+            "ArrowUp" => return Some(KeyCommand::MoveSelection(-1)),
+            _ => {}
+        }
+
+        match (keymap, self.mode) {
+            (Keymap::Default, _) => None,
+            (Keymap::Vim, Mode::Insert) => match key {
+                "Escape" => Some(KeyCommand::SetMode(Mode::Normal)),
+                _ => None,
+            },
+            (Keymap::Vim, Mode::Normal) => {
+                let command = match (pending, key) {
+                    // A count starts with a digit other than 0, and only
+                    // applies to the command right after it
+                    (None, digit) if digit.len() == 1 && digit.chars().all(|c| c.is_ascii_digit()) && (count.is_some() || digit != "0") => {
+                        let digit = digit.parse::<usize>().unwrap_or(0);
+                        self.count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                        KeyCommand::Ignore
+                    }
+                    (Some('d'), "d") => KeyCommand::DeleteLine,
+                    (Some('g'), "g") => KeyCommand::FirstLine,
+                    (_, "d") => {
+                        self.pending = Some('d');
+                        KeyCommand::Ignore
+                    }
+                    (_, "g") => {
+                        self.pending = Some('g');
+                        KeyCommand::Ignore
+                    }
+                    (_, "j") => KeyCommand::MoveSelection(1),
+                    (_, "k") => KeyCommand::MoveSelection(-1),
+                    (_, "G") => KeyCommand::LastLine,
+                    (_, "o") => KeyCommand::InsertBelow(count.unwrap_or(1)),
+                    (_, "O") => KeyCommand::InsertAbove(count.unwrap_or(1)),
+                    (_, "i" | "a" | "I" | "A" | "Enter") => KeyCommand::SetMode(Mode::Insert),
+                    // Swallow keys that would edit the line, but let others,
+                    // like Tab, behave as usual
+                    (_, "Backspace" | "Delete") => KeyCommand::Ignore,
+                    (_, key) if key.chars().count() == 1 => KeyCommand::Ignore,
+                    _ => return None,
+                };
+                Some(command)
+            }
+        }
+    }
+}
@@ -4,32 +4,99 @@
  * utility functions, enums, and a main component (ProofWidget) that manages the state and rendering of the proof editor. */
 
 mod actions;
+mod keymap;
+mod row;
+pub mod tutorial;
 
 use crate::box_chars;
-use crate::components::expr_entry::ExprEntry;
+use crate::components::command_palette::Command;
+use crate::components::expr_entry::PastedLines;
+use crate::components::menu::Menu;
+use crate::components::menu::MenuItem;
 use crate::components::nav_bar::theme;
+use crate::crash;
+use crate::preferences::Keymap;
+use crate::preferences::NewStep;
+use crate::preferences::Preferences;
+use crate::preferences::Verification;
+use crate::progress;
+use crate::progress::Progress;
 use crate::proof_ui_data::ProofUiData;
-use crate::util::calculate_lineinfo;
+use crate::signing;
+use crate::telemetry;
+use crate::util::unique_id;
 use crate::util::P;
+use keymap::KeyCommand;
+use keymap::KeyState;
+use keymap::Mode;
+use row::Citation;
+use row::ErrorDetails;
+use row::Feedback;
+use row::Highlight;
+use row::LineActions;
+use row::ProofRow;
+use row::ProofRowProps;
+use row::StepCells;
+use tutorial::Tutorial;
+
+use aris::analytics;
+use aris::expr::subst_expr;
 use aris::expr::Expr;
+use aris::expr::Op;
+use aris::parser::InfixOperator;
+use aris::parser::LogicMode;
+use aris::parser::ParseError;
+use aris::parser::Syntax;
+use aris::proofs::clipboard;
+use aris::proofs::clipboard::ClipboardProof;
+use aris::proofs::constants;
+use aris::proofs::hints;
+use aris::proofs::js_to_pjs;
+use aris::proofs::line_checks::LineChecks;
+use aris::proofs::line_checks::LineResult;
+use aris::proofs::line_info;
+use aris::proofs::line_info::LineInfo;
+use aris::proofs::lints;
 use aris::proofs::pj_to_pjs;
+use aris::proofs::skeleton::Blank;
+use aris::proofs::skeleton::Blanks;
+use aris::proofs::timings::LineTiming;
+use aris::proofs::timings::Timings;
+use aris::proofs::xml_interop;
+use aris::proofs::xml_interop::DelayedFeedback;
+use aris::proofs::xml_interop::ExamConfig;
+use aris::proofs::xml_interop::ProofMetaData;
+use aris::proofs::AssumptionPolicy;
 use aris::proofs::JsRef;
 use aris::proofs::Justification;
 use aris::proofs::PjRef;
 use aris::proofs::PjsRef;
 use aris::proofs::Proof;
+use aris::proofs::ScopingPolicy;
+use aris::rewrite_rules::find_pattern;
+use aris::rule_names;
+use aris::rules::ProofCheckError;
 use aris::rules::Rule;
 use aris::rules::RuleClassification;
 use aris::rules::RuleM;
 use aris::rules::RuleT;
+use aris::soundness;
+use aris::soundness::Config;
+use aris::temporal;
 use gloo::events::EventListener;
-use gloo::events::EventListenerOptions;
+use gloo::timers::callback::Interval;
+use gloo::timers::callback::Timeout;
 use wasm_bindgen::UnwrapThrowExt;
+use yew::context::ContextHandle;
 use yew::html::Scope;
 
+use std::cell::RefCell;
 use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt;
 use std::mem;
+use std::rc::Rc;
 
 use frunk_core::coproduct::Coproduct;
 use frunk_core::Coprod;
@@ -40,7 +107,57 @@ use web_sys::HtmlElement;
 
 use wasm_bindgen::JsCast;
 
-use js_sys::Math::random;
+/// How long, in milliseconds, typing in a line has to pause before it's parsed
+const PARSE_DELAY_MS: u32 = 300;
+
+/// How long, in milliseconds, to gather changes to the proof before reporting
+/// its progress to the instructor dashboard
+const TELEMETRY_DELAY_MS: u32 = 2000;
+
+/// How long, in milliseconds, lines stay highlighted after jumping to them
+const FLASH_DURATION_MS: u32 = 1500;
+
+/// Most lines one insertion adds, so a mistyped count doesn't bury the proof
+/// in empty lines
+const MAX_INSERT_COUNT: usize = 100;
+
+/// Label of the menu item and command asking for a number of steps to insert
+const INSERT_STEPS_LABEL: &str = "Insert several steps after this line\u{2026}";
+
+/// Number of colors that subproofs and the steps discharging them cycle
+/// through, as the `discharge-N` CSS classes
+const DISCHARGE_COLORS: usize = 6;
+
+/// Label of the menu item and command deriving each conjunct of a line
+const SPLIT_CONJUNCTION_LABEL: &str = "Split conjunction into steps";
+
+/// Label of the menu item and command concluding ⊥ from a formula and its
+/// negation that a line can cite
+const CONCLUDE_CONTRADICTION_LABEL: &str = "Conclude \u{22A5} from a contradiction";
+
+/// Label of the menu item and command reiterating the lines a step cites from
+/// outside its subproof, when scoping is strict
+const REITERATE_CITATIONS_LABEL: &str = "Reiterate cited lines into this subproof";
+
+/// Label of the menu item and command inserting a step admitted without proof,
+/// for sketching a proof before filling in its details
+const INSERT_GAP_LABEL: &str = "Insert a gap after this line";
+
+/// Label of the context menu of a gap listing the rules that could conclude
+/// it, each replacing the gap with what the rule needs as gaps of their own
+const WORK_BACKWARD_LABEL: &str = "Work backward with";
+
+/// How long, in milliseconds, checking the proof runs before letting the page
+/// update, so results of big proofs fill in as they're checked
+const CHECK_SLICE_MS: f64 = 30.0;
+
+/// Models to try for showing why a wrong first-order step doesn't follow,
+/// fewer than the default since it's searched whenever the line is drawn
+const COUNTERMODEL_CONFIG: Config = Config { max_domain: 3, max_models: 1 << 12, max_number: 0 };
+
+/// Most traces to try for showing why a wrong temporal logic step doesn't
+/// follow, for the same reason
+const MAX_COUNTERTRACES: u64 = 1 << 12;
 
 /// Retrieves the document object of the current web page.
 /// This is used for DOM manipulation and event listeners.
@@ -52,10 +169,182 @@ fn document() -> web_sys::Document {
 struct SelectedLine {
     /// Reference to line in proof
     line_ref: PjRef<P>,
+}
+
+/// State of the search bar, present while it's open
+struct SearchState {
+    /// Text typed in the search bar
+    query: String,
+
+    /// The query parsed as an expression pattern, if it parses
+    pattern: Option<Expr>,
+
+    /// Text typed in the replacement field, present while it's shown
+    replacement: Option<String>,
+
+    /// Reference to the search bar's `<input>` node
+    input_ref: NodeRef,
+
+    /// Whether to focus the search bar after the next render
+    focus: bool,
+}
+
+/// State of the dialog for adding premises from text, present while it's open
+struct PremiseImportState {
+    /// Text typed in the dialog, one formula per line
+    text: String,
+
+    /// Lines that failed to parse in the last import, as messages
+    errors: Vec<String>,
+
+    /// Reference to the dialog's `<textarea>` node
+    textarea_ref: NodeRef,
+
+    /// Whether to focus the text area after the next render
+    focus: bool,
+}
+
+/// Text with several lines pasted into a line, waiting for the user to choose
+/// between importing it as separate lines or pasting it into the line
+struct PasteOffer {
+    /// Line the text was pasted into
+    line_ref: PjRef<P>,
+
+    /// The pasted text
+    pasted: PastedLines,
+}
+
+/// The scratchpad beside the proof, present once it was first shown, and
+/// kept while it's hidden so the scratch proof is still there when it's
+/// shown again
+struct Scratchpad {
+    /// The blank proof the scratch proof started as, as XML
+    data: Vec<u8>,
+
+    /// Sends lines from the scratch proof to this one
+    ontransplant: Callback<ClipboardProof>,
+}
 
-    /// Handle for listening for keyboard shortcuts
+/// Result of checking a line, as shown in its feedback cell
+#[derive(Clone, PartialEq)]
+enum LineStatus {
+    /// The line has no text yet
+    Empty,
+    /// The line was edited and is waiting to be parsed
+    Pending,
+    /// The line's text doesn't parse, or is too large to
+    Unparsed(ParseError),
+    /// The line is a premise or a correctly applied rule
+    Correct,
+    /// The line's rule is applied correctly, but it has these warnings, which
+    /// don't stop the proof from being solved
+    Warned(Vec<String>),
+    /// The line's rule is applied incorrectly, with this error message
+    Incorrect(String),
+    /// Checking the line's rule took too long, so it was stopped
+    TimedOut,
+    /// The step is a gap, admitted without proof for now, which is neither
+    /// correct nor a mistake
+    Gap,
+}
+
+impl LineStatus {
+    /// Status of a parsed line, given the result of verifying it, its
+    /// warnings, which are only looked for if it's correct, and a hint, which
+    /// is only looked for if it's not. Errors are described with the line
+    /// numbers in `line_info`.
+    fn from_result(result: Result<(), ProofCheckError<PjRef<P>, <P as Proof>::SubproofReference>>, line_info: &LineInfo<P>, warnings: impl FnOnce() -> Vec<String>, hint: impl FnOnce() -> Option<String>) -> Self {
+        match result {
+            Ok(()) => {
+                let warnings = warnings();
+                if warnings.is_empty() {
+                    LineStatus::Correct
+                } else {
+                    LineStatus::Warned(warnings)
+                }
+            }
+            Err(ProofCheckError::TimedOut) => LineStatus::TimedOut,
+            Err(ProofCheckError::Gap) => LineStatus::Gap,
+            Err(err) => {
+                let message = err.message(&|r| line_info.line_of(r), &|sr| line_info.line_range_of_subproof(sr));
+                LineStatus::Incorrect(match hint() {
+                    Some(hint) => format!("{message} {hint}"),
+                    None => message,
+                })
+            }
+        }
+    }
+
+    /// Whether the line is correct, with or without warnings
+    fn is_correct(&self) -> bool {
+        matches!(self, LineStatus::Correct | LineStatus::Warned(_))
+    }
+}
+
+/// Messages of the warnings about a line of `prf`, typed as `input`,
+/// numbering the lines they refer to as `pud` does
+fn warning_messages(prf: &P, r: PjRef<P>, input: &str, goals: &[Expr], require_full_parens: bool, pud: &ProofUiData<P>) -> Vec<String> {
+    lints::input_lints(input, require_full_parens).into_iter().chain(lints::lints(prf, &r, goals)).map(|lint| lint.message(|r| pud.line_info(prf).line_of(r))).collect()
+}
+
+/// The hint for a wrong line of `prf`, if applying an equivalence first would
+/// make it right, numbering the line it refers to as `pud` does. There are no
+/// hints during exams.
+fn hint_message(prf: &P, r: PjRef<P>, exam: bool, pud: &ProofUiData<P>) -> Option<String> {
+    if exam {
+        return None;
+    }
+    hints::equivalence_hint(prf, &r).map(|hint| hint.message(|r| pud.line_info(prf).line_of(r)))
+}
+
+/// Results of the last check of the proof, shown instead of checking lines as
+/// they're edited with on-demand verification
+struct CheckResults {
+    /// Status of each line when the proof was checked
+    statuses: HashMap<PjRef<P>, LineStatus>,
+
+    /// The proof when it was checked
+    proof: P,
+
+    /// Text of the lines when the proof was checked
+    inputs: HashMap<PjRef<P>, String>,
+
+    /// Whether the proof was edited since it was checked, so the results may
+    /// be out of date
+    stale: bool,
+
+    /// Lines left to check, while the check is running. Their statuses are
+    /// filled in a few at a time, so the page stays responsive.
+    remaining: Option<LineChecks<P>>,
+}
+
+/// Lockdown of a proof opened from an exam assignment. Hints, rule
+/// suggestions, the clipboard, and the debug output are disabled, and saved
+/// proofs are marked with the student taking the exam.
+struct ExamState {
+    /// Settings from the proof's metadata, with the student filled in if they
+    /// entered their name
+    config: ExamConfig,
+
+    /// When time runs out, in milliseconds since the epoch, if the exam is
+    /// timed
+    deadline: Option<f64>,
+
+    /// Timer for updating the countdown every second, until time runs out
     #[allow(dead_code)]
-    key_listener: EventListener,
+    ticker: Option<Interval>,
+}
+
+/// Progress through a guided tutorial
+struct TutorialState {
+    tutorial: Tutorial,
+
+    /// Index of the current step, or the number of steps once the tutorial is
+    /// finished
+    step: usize,
+
+    /// Reference to the callout, for placing it next to its anchor
+    callout_ref: NodeRef,
 }
 
 /// Component for editing proofs
@@ -70,12 +359,137 @@ pub struct ProofWidget {
     /// The currently selected line, highlighted in the UI
     selected_line: Option<SelectedLine>,
 
+    /// Position of the subformula of the selected line picked in the
+    /// equivalence panel, as a path for `Expr::subexpr_at()`
+    selected_subformula: Option<Vec<usize>>,
+
     /// Error message, for if there was an error parsing the proof XML. If this
     /// exists, it is displayed instead of the proof.
     open_error: Option<String>,
 
+    /// The search bar, if it's open
+    search: Option<SearchState>,
+
+    /// The dialog for adding premises from text, if it's open
+    premise_import: Option<PremiseImportState>,
+
+    /// Text with several lines just pasted into a line, if any
+    paste_offer: Option<PasteOffer>,
+
+    /// Line whose context menu is open, and where in the window it was opened
+    context_menu: Option<(PjRef<P>, i32, i32)>,
+
+    /// Line to scroll into view after the next render
+    scroll_to: Option<PjRef<P>>,
+
+    /// Timers for parsing lines that were edited, which are shown as pending
+    /// until they're parsed
+    pending_parses: HashMap<PjRef<P>, Timeout>,
+
+    /// The guided tutorial being followed in this proof, if any
+    tutorial: Option<TutorialState>,
+
+    /// Formulas the proof is supposed to prove, from the metadata of the file
+    /// it was opened from
+    goals: Vec<Expr>,
+
+    /// Whether the proof was solved after the last update, so solving it is
+    /// recorded in the user's progress only once
+    solved: bool,
+
+    /// Exam lockdown, if the proof is an exam assignment
+    exam: Option<ExamState>,
+
+    /// Feedback settings, if the proof is an assignment that only shows line
+    /// feedback when it's checked. The proof is checked on demand then,
+    /// whatever the user's preferences, and results are hidden once it's
+    /// edited again.
+    delayed_feedback: Option<DelayedFeedback>,
+
+    /// Infix operators the proof declares, and the logic it's written in.
+    /// Other proofs may be open in other tabs, so they're set again whenever
+    /// this one is updated or shown.
+    operators: Vec<InfixOperator>,
+    logic_mode: LogicMode,
+
+    /// Whether the assignment requires the conclusion to use every premise,
+    /// so the proof isn't solved while any premise is unused
+    require_all_premises: bool,
+
+    /// Whether the assignment requires every formula to be typed fully
+    /// parenthesized, which lines are warned about otherwise
+    require_full_parens: bool,
+
+    /// How many assumptions the proof's subproofs may have, which is set
+    /// along with the operators and logic while the widget updates and renders
+    assumption_policy: AssumptionPolicy,
+
+    /// Which lines steps may cite without reiterating them, which is set
+    /// along with the assumption policy
+    scoping_policy: ScopingPolicy,
+
+    /// Cells the user can fill in, if the proof is a cloze handout. Every
+    /// other cell is locked, and lines can't be added, removed, or moved.
+    blanks: Blanks,
+
+    /// When each line was created and last became correct, if the proof
+    /// records timings
+    timings: Option<HashMap<PjRef<P>, LineTiming>>,
+
+    /// Lines that were correct as of the last update, to tell when a line
+    /// becomes correct for `timings`
+    correct_lines: BTreeSet<PjRef<P>>,
+
+    /// The proof as of the last call of the `onchange` property and the last
+    /// autosave, to only save it for actual changes rather than every update
+    reported_proof: P,
+
+    /// Results of the last "Check proof", with on-demand verification
+    last_check: Option<CheckResults>,
+
+    /// Timer for checking more lines of the running check
+    #[allow(dead_code)]
+    check_timeout: Option<Timeout>,
+
+    /// Whether the list of lines with errors is shown under the summary bar
+    show_error_list: bool,
+
+    /// Whether the panel listing the proof's constants is shown
+    show_constants: bool,
+
+    /// The scratchpad, once it was first shown, and whether it's shown now
+    scratchpad: Option<Scratchpad>,
+    show_scratchpad: bool,
+
+    /// Timer for reporting progress to the instructor dashboard, if the user
+    /// opted into it
+    telemetry_timeout: Option<Timeout>,
+
+    /// Lines briefly highlighted after clicking a dependency badge citing them
+    flashed_lines: Vec<PjRef<P>>,
+
+    /// Timer for un-highlighting `flashed_lines`
+    #[allow(dead_code)]
+    flash_timeout: Option<Timeout>,
+
+    /// The user's display preferences
+    preferences: Preferences,
+
+    /// Subscription to changes of the preferences
+    #[allow(dead_code)]
+    preferences_handle: Option<ContextHandle<Preferences>>,
+
+    /// State of the keymap, such as the Vim mode
+    key_state: KeyState,
+
     preblob: String,
 
+    /// Sends messages to the widget, for its rows. It's made once, so rows
+    /// can tell they're unchanged by comparing their properties.
+    onmsg: Callback<ProofWidgetMsg>,
+
+    /// ID of the widget's root element, and prefix of the IDs of the elements
+    /// in it, unique on the page
     id: String,
 }
 
@@ -90,24 +504,109 @@ pub enum ProofItemKind {
     Subproof,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum LineActionKind {
-    Insert { what: ProofItemKind, after: bool, relative_to: ProofItemKind },
+    Insert { what: ProofItemKind, after: bool, relative_to: ProofItemKind, count: usize },
     Delete { what: ProofItemKind },
     SetRule { rule: Rule },
     Select,
     ToggleDependency { dep: Coprod![PjRef<P>, <P as Proof>::SubproofReference] },
+    SelectSubformula { path: Vec<usize> },
+    ApplyEquivalence { rule: Rule, path: Vec<usize> },
+    Duplicate,
+    Move { after: bool },
+    SplitConjunction,
+    ConcludeContradiction,
+    ReiterateCitations,
+    InsertGap,
+    WorkBackward { rule: Rule },
 }
 
+/// Function called with the proof and the metadata to save it with
+pub type ProofCallback = Box<dyn FnOnce(&P, &ProofMetaData)>;
+
 /// Message for `ProofWidget`
 pub enum ProofWidgetMsg {
     /// Do nothing
     Nop,
+    /// A line's text was edited, to be parsed once typing pauses
     LineChanged(PjRef<P>, String),
+    /// Parse a line's text, after a pause in editing it
+    ParseLine(PjRef<P>),
+    /// The cursor moved in a line's text field, to this selection
+    CaretMoved(PjRef<P>, (u32, u32)),
+    /// Enter was pressed in a line's text field
+    LineEntered(PjRef<P>),
     LineAction(LineActionKind, PjRef<P>),
-    CallOnProof(Box<dyn FnOnce(&P)>),
+    /// Call a function with the proof and the metadata to save it with
+    CallOnProof(ProofCallback),
+    /// Send the commands for the command palette, like the valid line actions
+    /// of the selected line
+    GetCommands(Callback<Vec<Command>>),
     /// Process keypress, handling any keyboard shortcuts
     Keypress(web_sys::KeyboardEvent),
+    /// Open the search bar, or focus it if it's already open
+    OpenSearch,
+    /// Search bar text was edited
+    SearchChanged(String),
+    /// Select the next match of the search, or the previous one if `forward` is false
+    SearchStep {
+        forward: bool,
+    },
+    CloseSearch,
+    /// Show or hide the replacement field of the search bar
+    ToggleReplace,
+    /// Replacement field text was edited
+    ReplacementChanged(String),
+    /// Replace the searched term or formula with the replacement in every line
+    ReplaceAll,
+    /// Respell every formula the way it's displayed, as in
+    /// `aris::parser::reformat()`
+    ReformatProof,
+    /// Scroll to and briefly highlight lines, such as the ones cited by a step
+    FlashLines(Vec<PjRef<P>>),
+    /// Stop highlighting the lines from `FlashLines`
+    ClearFlash,
+    /// The user changed their display preferences
+    PreferencesChanged(Preferences),
+    /// Open the dialog for adding premises from text
+    OpenPremiseImport,
+    /// Text in the premise import dialog was edited
+    PremiseImportChanged(String),
+    /// Add the formulas in the premise import dialog as premises
+    ImportPremises,
+    ClosePremiseImport,
+    /// Text with several lines was pasted into a line
+    LinesPasted(PjRef<P>, PastedLines),
+    /// Import the pasted lines as separate lines, after the line they were
+    /// pasted into
+    ImportPastedLines,
+    /// Paste the pasted lines into the line they were pasted into, after all
+    PastePastedLinesInline,
+    DismissPastedLines,
+    /// Open the context menu of a line at a position in the window
+    OpenContextMenu(PjRef<P>, i32, i32),
+    CloseContextMenu,
+    /// Show or hide the list of lines with errors
+    ToggleErrorList,
+    /// Show or hide the panel listing the proof's constants
+    ToggleConstants,
+    /// Show or hide the scratchpad beside the proof
+    ToggleScratchpad,
+    /// Add lines sent from the scratchpad after the selected line, or after
+    /// the last line outside of subproofs if no line is selected
+    TransplantLines(ClipboardProof),
+    /// Check every line of the proof, for on-demand verification
+    CheckProof,
+    /// Check more lines of the running check
+    ContinueCheck,
+    /// Update the exam countdown
+    ExamTick,
+    /// Report the proof's progress to the instructor dashboard
+    ReportProgress,
+    /// Move on to the next step of the tutorial, skipping the current one
+    NextTutorialStep,
+    ExitTutorial,
 }
 
 impl fmt::Debug for ProofWidgetMsg {
@@ -116,9 +615,44 @@ impl fmt::Debug for ProofWidgetMsg {
         match self {
             Nop => f.debug_struct("Nop").finish(),
             LineChanged(r, s) => f.debug_tuple("LineChanged").field(&r).field(&s).finish(),
+            ParseLine(r) => f.debug_tuple("ParseLine").field(&r).finish(),
+            CaretMoved(r, caret) => f.debug_tuple("CaretMoved").field(&r).field(&caret).finish(),
+            LineEntered(r) => f.debug_tuple("LineEntered").field(&r).finish(),
             LineAction(lak, r) => f.debug_tuple("LineAction").field(&lak).field(&r).finish(),
             CallOnProof(_) => f.debug_struct("CallOnProof").finish(),
+            GetCommands(_) => f.debug_struct("GetCommands").finish(),
             Keypress(key_event) => f.debug_tuple("Keypress").field(&key_event).finish(),
+            OpenSearch => f.debug_struct("OpenSearch").finish(),
+            SearchChanged(query) => f.debug_tuple("SearchChanged").field(&query).finish(),
+            SearchStep { forward } => f.debug_struct("SearchStep").field("forward", &forward).finish(),
+            CloseSearch => f.debug_struct("CloseSearch").finish(),
+            ToggleReplace => f.debug_struct("ToggleReplace").finish(),
+            ReplacementChanged(replacement) => f.debug_tuple("ReplacementChanged").field(&replacement).finish(),
+            ReplaceAll => f.debug_struct("ReplaceAll").finish(),
+            ReformatProof => f.debug_struct("ReformatProof").finish(),
+            FlashLines(lines) => f.debug_tuple("FlashLines").field(&lines).finish(),
+            ClearFlash => f.debug_struct("ClearFlash").finish(),
+            PreferencesChanged(preferences) => f.debug_tuple("PreferencesChanged").field(&preferences).finish(),
+            OpenPremiseImport => f.debug_struct("OpenPremiseImport").finish(),
+            PremiseImportChanged(text) => f.debug_tuple("PremiseImportChanged").field(&text).finish(),
+            ImportPremises => f.debug_struct("ImportPremises").finish(),
+            ClosePremiseImport => f.debug_struct("ClosePremiseImport").finish(),
+            LinesPasted(r, pasted) => f.debug_tuple("LinesPasted").field(&r).field(&pasted).finish(),
+            ImportPastedLines => f.debug_struct("ImportPastedLines").finish(),
+            PastePastedLinesInline => f.debug_struct("PastePastedLinesInline").finish(),
+            DismissPastedLines => f.debug_struct("DismissPastedLines").finish(),
+            OpenContextMenu(r, x, y) => f.debug_tuple("OpenContextMenu").field(&r).field(&x).field(&y).finish(),
+            CloseContextMenu => f.debug_struct("CloseContextMenu").finish(),
+            ToggleErrorList => f.debug_struct("ToggleErrorList").finish(),
+            ToggleConstants => f.debug_struct("ToggleConstants").finish(),
+            ToggleScratchpad => f.debug_struct("ToggleScratchpad").finish(),
+            TransplantLines(fragment) => f.debug_tuple("TransplantLines").field(&fragment).finish(),
+            CheckProof => f.debug_struct("CheckProof").finish(),
+            ContinueCheck => f.debug_struct("ContinueCheck").finish(),
+            ExamTick => f.debug_struct("ExamTick").finish(),
+            ReportProgress => f.debug_struct("ReportProgress").finish(),
+            NextTutorialStep => f.debug_struct("NextTutorialStep").finish(),
+            ExitTutorial => f.debug_struct("ExitTutorial").finish(),
         }
     }
 }
@@ -128,370 +662,1190 @@ pub struct ProofWidgetProps {
     pub verbose: bool,
     pub data: Option<Vec<u8>>,
     pub oncreate: Callback<Scope<ProofWidget>>,
+    /// Guided tutorial to walk through in the proof
+    #[prop_or_default]
+    pub tutorial: Option<Tutorial>,
+    /// Called when the proof becomes solved
+    #[prop_or_default]
+    pub onsolved: Option<Callback<()>>,
+    /// Called with the proof, as XML, whenever it changes
+    #[prop_or_default]
+    pub onchange: Option<Callback<Vec<u8>>>,
+    /// Show the proof without letting it be edited
+    #[prop_or_default]
+    pub read_only: bool,
+    /// A proof for trying things out, which isn't autosaved, reported to the
+    /// instructor dashboard, or counted in the user's progress
+    #[prop_or_default]
+    pub scratch: bool,
+    /// Called with lines to send to the proof this one is beside, which the
+    /// context menu then offers to send there
+    #[prop_or_default]
+    pub ontransplant: Option<Callback<ClipboardProof>>,
 }
 
 impl ProofWidget {
-    /// Renders a checkbox for managing line dependencies.
-    /// This checkbox is interactive if the dependency is valid, otherwise it is disabled.
-    fn render_line_num_dep_checkbox(&self, ctx: &Context<Self>, line: Option<usize>, proofref: Coprod!(PjRef<P>, <P as Proof>::SubproofReference)) -> Html {
-        let line = match line {
-            Some(line) => line.to_string(),
-            None => "".to_string(),
-        };
-        if let Some(selected_line) = &self.selected_line {
-            use Coproduct::{Inl, Inr};
-            if let Inr(Inl(_)) = selected_line.line_ref {
-                let line_ref = selected_line.line_ref;
-                let toggle_dep = ctx.link().callback(move |_| ProofWidgetMsg::LineAction(LineActionKind::ToggleDependency { dep: proofref }, line_ref));
-                if self.prf.can_reference_dep(&line_ref, &proofref) {
-                    return html! {
-                        <button
-                            type="button"
-                            class="btn btn-secondary"
-                            onclick={ toggle_dep }>
-
-                            { line }
-                        </button>
-                    };
-                }
-            }
-        }
-        html! {
-            <button
-                type="button"
-                class="btn"
-                disabled=true>
-
-                { line }
-            </button>
-        }
+    /// The selected line, if it's a step that can cite `dep` by clicking
+    /// `dep`'s line number. Lines and subproofs out of scope can still be
+    /// cited if they're shared, but not by steps whose citations are locked.
+    fn citing_line(&self, dep: Coprod!(PjRef<P>, <P as Proof>::SubproofReference)) -> Option<PjRef<P>> {
+        use Coproduct::{Inl, Inr};
+        let line_ref = self.selected_line.as_ref()?.line_ref;
+        let citable = matches!(line_ref, Inr(Inl(_))) && (self.prf.can_reference_dep(&line_ref, &dep) || self.prf.can_share_dep(&line_ref, &dep)) && !self.is_locked(line_ref, Blank::Citations);
+        citable.then_some(line_ref)
     }
-    /// Create a drop-down menu allowing the user to select the rule used in a
-    /// justification line. This uses the [Bootstrap-submenu][lib] library.
-    ///
-    /// ## Parameters:
-    ///   + `jref` - reference to the justification line containing this menu
-    ///   + `cur_rule_name` - name of the current selected rule
-    ///
-    /// [lib]: https://github.com/vsn4ik/bootstrap-submenu
-    fn render_rules_menu(&self, ctx: &Context<Self>, jref: <P as Proof>::JustificationReference, cur_rule_name: &str) -> Html {
-        let equivalence_classes = [RuleClassification::BooleanEquivalence, RuleClassification::ConditionalEquivalence, RuleClassification::BiconditionalEquivalence, RuleClassification::QuantifierEquivalence];
 
-        let misc_inference_classes = [RuleClassification::BooleanInference, RuleClassification::ConditionalInference, RuleClassification::BiconditionalInference, RuleClassification::QuantifierInference];
+    /// The rule and citations of a step, as its row shows them
+    fn step_cells(&self, jref: <P as Proof>::JustificationReference) -> StepCells {
+        let just = self.prf.lookup_justification_or_die(&jref).expect("proofref should exist in self.prf");
+        let formula_of = |r: &PjRef<P>| self.pud.ref_to_input.get(r).cloned().unwrap_or_default();
+        let line_info = self.pud.line_info(&self.prf);
+
+        // Whether a citation is of a line or subproof shared from elsewhere in
+        // the proof, rather than one in scope
+        let jr = Coproduct::inject(jref);
+        let is_shared = |dep: Coprod!(PjRef<P>, <P as Proof>::SubproofReference)| !self.prf.can_reference_dep(&jr, &dep) && self.prf.can_share_dep(&jr, &dep);
+
+        // Iterator over line dependencies
+        let deps = just.2.iter().map(|dep| {
+            let (dep_line, _) = line_info.lines[dep];
+            let label = if is_shared(Coproduct::inject(*dep)) { format!("see line {dep_line}") } else { dep_line.to_string() };
+            Citation { class: "badge-dark", label, formulas: formula_of(dep), lines: vec![*dep] }
+        });
 
-        let special_rule_names = ["Reiteration", "Resolution", "Truth-Functional Consequence"];
+        // Iterator over subproof dependencies
+        let sdeps = just.3.iter().filter_map(|sdep| Some((sdep, self.prf.lookup_subproof(sdep)?, line_info.line_range_of_subproof(sdep)?))).map(|(sdep, sub, (lo, hi))| {
+            let sdep_line = if is_shared(Coproduct::inject(*sdep)) { format!("see lines {}", line_info::format_range((lo, hi))) } else { line_info::format_range((lo, hi)) };
+            // A subproof is shown as its assumption and its conclusion
+            let assumption = sub.premises().into_iter().map(|prem| formula_of(&Coproduct::inject(prem))).collect::<Vec<_>>().join(", ");
+            let conclusion = sub.direct_lines().into_iter().last().map(|line| formula_of(&Coproduct::inject(line))).unwrap_or_default();
+            let lines = self.lines_in_order().into_iter().filter(|r| line_info.line_of(r).map(|i| (lo..=hi).contains(&i)).unwrap_or(false)).collect();
+            Citation { class: "badge-secondary", label: sdep_line, formulas: format!("{assumption} ⋯ {conclusion}"), lines }
+        });
 
-        let render_rule_button = |rule: Rule| {
-            let pjref = Coproduct::inject(jref);
-            let image_src = format!("{}/{}.png", if theme() == "dark" { "proofImages_dark" } else { "proofImages_light" }, rule.get_name());
-            html! {
-                <button class="dropdown-item" type="button"
-                    data-toggle="tooltip" data-placement="left"
-                    title={format!("<img id='rule-img' src='{}'/>", image_src)}
-                    onclick={ctx.link().callback(move |_| ProofWidgetMsg::LineAction(LineActionKind::SetRule { rule }, pjref))}
-                >
-                    { rule.get_name() }
-                </button>
-            }
-        };
+        StepCells {
+            jref,
+            rule_name: rule_names::display_name(just.1),
+            rule_locked: self.is_locked(jr, Blank::Rule),
+            // Rule descriptions would be hints during an exam
+            describe_rules: self.exam.is_none(),
+            citations: deps.chain(sdeps).collect(),
+            show_cited_formulas: self.preferences.show_cited_formulas,
+        }
+    }
 
-        let render_rules_from_class = |class: RuleClassification| {
-            html! {
-                <div class="dropdown dropright dropdown-submenu">
-                    <button class="dropdown-item dropdown-toggle" type="button" data-toggle="dropdown">
-                        { format!("{}", class) }
-                    </button>
-                    <div class="dropdown-menu">
-                        { for class.rules().map(render_rule_button) }
-                    </div>
-                </div>
-            }
+    /// Check a line, parsing its text and verifying its rule
+    fn line_status(&self, proofref: PjRef<P>) -> LineStatus {
+        let raw_line = match self.pud.ref_to_input.get(&proofref).filter(|x| !x.is_empty()) {
+            None => return LineStatus::Empty,
+            Some(x) => x,
         };
+        if self.pending_parses.contains_key(&proofref) {
+            return LineStatus::Pending;
+        }
+        match aris::parser::try_parse(raw_line) {
+            Err(err) => LineStatus::Unparsed(err),
+            Ok(_) => LineStatus::from_result(self.prf.verify_line(&proofref), &self.pud.line_info(&self.prf), || warning_messages(&self.prf, proofref, raw_line, &self.goals, self.require_full_parens, &self.pud), || hint_message(&self.prf, proofref, self.exam.is_some(), &self.pud)),
+        }
+    }
 
-        let special_rules = RuleClassification::iter().flat_map(|c| c.rules()).filter(|r| special_rule_names.contains(&r.get_name().as_str())).map(render_rule_button);
-
-        let induction_category = RuleClassification::iter().find(|c| c.to_string() == "Induction").map(render_rules_from_class);
+    /// Feedback for a specific proof line, such as correctness or errors, as
+    /// its row shows it. Premises the conclusion doesn't depend on are pointed
+    /// out, which depends on the lines after them.
+    fn feedback(&self, proofref: PjRef<P>, is_assumption: bool) -> Feedback {
+        let status = self.shown_status(proofref);
+        let unused_premise = match (&status, proofref) {
+            (Some(LineStatus::Correct), Coproduct::Inl(pr)) if !is_assumption && self.unused_premises().contains(&pr) => Some(self.require_all_premises),
+            _ => None,
+        };
+        let details = match status {
+            Some(LineStatus::Incorrect(_)) => self.error_details(proofref),
+            _ => None,
+        };
+        let stale = matches!(&self.last_check, Some(CheckResults { stale: true, .. })) && self.verification() == Verification::OnDemand;
+        Feedback { status, unused_premise, stale, details }
+    }
 
-        let misc_inference_submenu = misc_inference_classes.iter().map(|&c| render_rules_from_class(c));
+    /// Details of a wrong step for its error popover: how its rule is used,
+    /// unless that would be a hint during an exam, the lines it cites, and a
+    /// model showing it doesn't follow from them, if there's a small one
+    fn error_details(&self, proofref: PjRef<P>) -> Option<ErrorDetails> {
+        use Coproduct::{Inl, Inr};
+        let jr = match proofref {
+            Inr(Inl(jr)) => jr,
+            _ => return None,
+        };
+        let Justification(_, rule, deps, sdeps) = self.prf.lookup_step(&jr)?;
+        let line_info = self.pud.line_info(&self.prf);
+        let schema = rule.get_schema().filter(|_| self.exam.is_none()).map(|schema| (rule, schema.to_string()));
+        let mut cited = deps.iter().filter_map(|dep| Some((line_info.line_of(dep)?, self.pud.ref_to_input.get(dep).cloned().unwrap_or_default()))).collect::<Vec<_>>();
+        cited.sort();
+        let cited = cited.into_iter().map(|(line, input)| format!("{line}. {input}"));
+        let cited_subproofs = sdeps.iter().filter_map(|sr| line_info.line_range_of_subproof(sr)).map(|range| format!("Subproof {}", line_info::format_range(range)));
+        // G, F, and U mean something on traces, but a first-order model would take them as arbitrary predicates
+        let (countermodel, countertrace) = match self.logic_mode {
+            LogicMode::Temporal => (None, temporal::step_countertrace(&self.prf, &proofref, temporal::DEFAULT_MAX_TRACE_LEN, MAX_COUNTERTRACES).map(|trace| trace.to_string())),
+            _ => (soundness::step_countermodel(&self.prf, &proofref, &COUNTERMODEL_CONFIG).map(|model| model.to_string()), None),
+        };
+        Some(ErrorDetails { schema, cited: cited.chain(cited_subproofs).collect(), countermodel, countertrace })
+    }
 
-        let equivalence_submenu = equivalence_classes.iter().map(|&c| render_rules_from_class(c));
+    /// How the proof is checked: on demand for assignments with delayed
+    /// feedback, and as picked in the user's preferences otherwise
+    fn verification(&self) -> Verification {
+        match self.delayed_feedback {
+            Some(_) => Verification::OnDemand,
+            None => self.preferences.verification,
+        }
+    }
 
-        let other_menus = RuleClassification::iter().filter(|c| !special_rule_names.contains(&c.to_string().as_str()) && c.to_string() != "Induction" && !equivalence_classes.contains(c) && !misc_inference_classes.contains(c) && c.to_string() != "Special").map(render_rules_from_class);
+    /// Status of a line as shown to the user: checked as it's edited with live
+    /// verification, or as of the last check with on-demand verification.
+    /// Returns `None` if the line hasn't been checked, or if it's been edited
+    /// since with delayed feedback.
+    fn shown_status(&self, proofref: PjRef<P>) -> Option<LineStatus> {
+        match self.verification() {
+            Verification::Live => Some(self.line_status(proofref)),
+            Verification::OnDemand => {
+                let check = self.last_check.as_ref()?;
+                if check.stale && self.delayed_feedback.is_some() {
+                    return None;
+                }
+                check.statuses.get(&proofref).cloned()
+            }
+        }
+    }
 
+    /// Renders the callout of the current tutorial step, which `rendered()`
+    /// places next to the step's anchor
+    fn render_tutorial_callout(&self, ctx: &Context<Self>) -> Html {
+        let tutorial = match &self.tutorial {
+            Some(tutorial) => tutorial,
+            None => return html! {},
+        };
+        let step_count = tutorial.tutorial.steps.len();
+        let exit = ctx.link().callback(|_| ProofWidgetMsg::ExitTutorial);
+        let (heading, text, button) = match tutorial.tutorial.steps.get(tutorial.step) {
+            Some(step) => {
+                let is_last = tutorial.step + 1 == step_count;
+                // Steps with conditions move on by themselves once they're met
+                let label = match (step.expectations.is_empty(), is_last) {
+                    (true, true) => "Finish",
+                    (true, false) => "Next",
+                    (false, _) => "Skip",
+                };
+                let class = if step.expectations.is_empty() { "btn btn-primary btn-sm" } else { "btn btn-link btn-sm" };
+                (format!("Step {} of {}", tutorial.step + 1, step_count), step.text.clone(), html! { <button type="button" class={ class } onclick={ ctx.link().callback(|_| ProofWidgetMsg::NextTutorialStep) }> { label } </button> })
+            }
+            None => ("Done".to_string(), format!("You finished \u{201c}{}\u{201d}.", tutorial.tutorial.title), html! { <button type="button" class="btn btn-primary btn-sm" onclick={ exit.clone() }> { "Close" } </button> }),
+        };
         html! {
-            <div class="dropright">
-                <button class="btn btn-primary dropdown-toggle" type="button" data-toggle="dropdown" data-submenu="">
-                    { cur_rule_name }
-                </button>
-                <div class="dropdown-menu">
-                    { for other_menus }
-                    <div class="dropdown dropright dropdown-submenu">
-                        <button class="dropdown-item dropdown-toggle" type="button" data-toggle="dropdown">{"Misc. Inference"}</button>
-                        <div class="dropdown-menu">{ for misc_inference_submenu }</div>
-                    </div>
-                    <div class="dropdown dropright dropdown-submenu">
-                        <button class="dropdown-item dropdown-toggle" type="button" data-toggle="dropdown">{"Equivalence"}</button>
-                        <div class="dropdown-menu">{ for equivalence_submenu }</div>
-                    </div>
-                    { for special_rules }
-                    { induction_category.unwrap_or_else(|| html! {}) }
+            <div ref={ tutorial.callout_ref.clone() } class="card shadow tutorial-callout" role="dialog" aria-label={ tutorial.tutorial.title.clone() }>
+                <div class="card-header d-flex align-items-center py-1 px-2">
+                    <small class="text-muted"> { format!("{} \u{2014} {}", tutorial.tutorial.title, heading) } </small>
+                    <button type="button" class="close ml-auto" aria-label="Exit tutorial" onclick={ exit }>
+                        <span aria-hidden="true"> { '\u{d7}' } </span>
+                    </button>
+                </div>
+                <div class="card-body p-2">
+                    <p class="card-text mb-2"> { text } </p>
+                    <div class="text-right"> { button } </div>
                 </div>
             </div>
         }
     }
 
-    /// Renders a UI widget for a justification line, including its dependencies and rule selector.
-    fn render_justification_widget(&self, ctx: &Context<Self>, jref: <P as Proof>::JustificationReference) -> Html {
-        let just = self.prf.lookup_justification_or_die(&jref).expect("proofref should exist in self.prf");
-
-        // Iterator over line dependency badges, for rendering list of dependencies
-        let dep_badges = just.2.iter().map(|dep| {
-            let (dep_line, _) = self.pud.ref_to_line_depth[dep];
-            html! {
-                <span class="badge badge-dark m-1"> { dep_line } </span>
+    /// Place the tutorial callout below the current step's anchor, and outline
+    /// the anchor. Without an anchor, the callout stays where it's rendered.
+    fn place_tutorial_callout(&self) {
+        let outlined = document().query_selector_all(&format!("#{} .tutorial-anchor", self.id)).unwrap_throw();
+        for i in 0..outlined.length() {
+            if let Some(elem) = outlined.get(i).and_then(|node| node.dyn_into::<web_sys::Element>().ok()) {
+                elem.class_list().remove_1("tutorial-anchor").unwrap_throw();
             }
-        });
-
-        // Iterator over subproof dependency badges, for rendering list of dependencies
-        let sdep_badges = just.3.iter().filter_map(|sdep| self.prf.lookup_subproof(sdep)).map(|sub| {
-            let (mut lo, mut hi) = (usize::MAX, usize::MIN);
-            for line in sub.premises().into_iter().map(Coproduct::inject).chain(sub.direct_lines().into_iter().map(Coproduct::inject)) {
-                if let Some((i, _)) = self.pud.ref_to_line_depth.get(&line) {
-                    lo = std::cmp::min(lo, *i);
-                    hi = std::cmp::max(hi, *i);
-                }
+        }
+        let tutorial = match &self.tutorial {
+            Some(tutorial) => tutorial,
+            None => return,
+        };
+        let callout = match tutorial.callout_ref.cast::<HtmlElement>() {
+            Some(callout) => callout,
+            None => return,
+        };
+        let anchor = tutorial.tutorial.steps.get(tutorial.step).and_then(|step| step.anchor).and_then(|anchor| document().query_selector(&anchor.selector(&self.id)).ok().flatten());
+        let style = callout.style();
+        match anchor {
+            Some(anchor) => {
+                anchor.class_list().add_1("tutorial-anchor").unwrap_throw();
+                // Positions are relative to the callout's offset parent
+                let rect = anchor.get_bounding_client_rect();
+                let (parent_top, parent_left) = callout.offset_parent().map(|parent| parent.get_bounding_client_rect()).map(|parent| (parent.top(), parent.left())).unwrap_or_default();
+                style.set_property("position", "absolute").unwrap_throw();
+                style.set_property("top", &format!("{}px", rect.bottom() - parent_top + 8.0)).unwrap_throw();
+                style.set_property("left", &format!("{}px", rect.left() - parent_left)).unwrap_throw();
             }
-            let sdep_line = format!("{lo}-{hi}");
-            html! {
-                <span class="badge badge-secondary m-1"> { sdep_line } </span>
+            None => {
+                for property in ["position", "top", "left"] {
+                    style.remove_property(property).unwrap_throw();
+                }
             }
-        });
+        }
+    }
 
-        // Node containing all dependency badges, for rendering list of
-        // dependencies
-        let all_dep_badges = dep_badges.chain(sdep_badges).collect::<Html>();
+    /// Whether the proof is solved: it has a step, every line is correct, and
+    /// its top-level steps include its goals, if it has any
+    fn is_solved(&self) -> bool {
+        use Coproduct::{Inl, Inr};
+        let lines = self.lines_in_order();
+        let has_step = lines.iter().any(|r| matches!(r, Inr(Inl(_))));
+        let all_correct = lines.iter().all(|r| self.line_status(*r).is_correct());
+        let conclusions = self.conclusions();
+        let premises_used = !self.require_all_premises || self.unused_premises().is_empty();
+        has_step && all_correct && self.goals.iter().all(|goal| conclusions.contains(goal)) && premises_used
+    }
 
-        let cur_rule_name = just.1.get_name();
-        let rule_selector = self.render_rules_menu(ctx, jref, &cur_rule_name);
-        html! {
-            <>
-                <td>
-                    // Drop-down menu for selecting rules
-                    { rule_selector }
-                </td>
-                <td>
-                    // Dependency list
-                    <span class="alert alert-secondary small-alert p-1">
-                        { all_dep_badges }
-                    </span>
-                </td>
-            </>
+    /// Top-level premises that the proof's conclusion doesn't use, which is
+    /// none of them until there's a conclusion
+    fn unused_premises(&self) -> Vec<<P as Proof>::PremiseReference> {
+        let concluding_lines = self.prf.concluding_lines(&self.goals);
+        if concluding_lines.is_empty() {
+            return vec![];
         }
+        self.prf.unused_premises(&concluding_lines)
     }
 
-    /// Renders feedback for a specific proof line, such as correctness or errors.
-    /// Feedback includes messages for parse errors, valid premises, and rule violations.
-    fn render_line_feedback(&self, proofref: PjRef<P>, is_subproof: bool) -> Html {
-        use aris::parser::parse;
-        let raw_line = match self.pud.ref_to_input.get(&proofref).and_then(|x| if !x.is_empty() { Some(x) } else { None }) {
-            None => {
-                return html! { <span></span> };
-            }
-            Some(x) => x,
-        };
-        match parse(raw_line).map(|_| self.prf.verify_line(&proofref)) {
-            None => {
-                html! { <span class="alert alert-warning small-alert s1">{ "Parse error" }</span> }
-            }
-            Some(Ok(())) => match proofref {
-                Coproduct::Inl(_) => html! {
-                    <span class="alert alert-success small-alert s2">
-                        { if is_subproof { "Assumption" } else { "Premise" } }
-                    </span>
-                },
-                _ => {
-                    html! { <span class="alert small-alert bg-success text-white s1">{ "Correct" }</span> }
+    /// Formulas of the top-level steps of the proof, which are what it proves
+    fn conclusions(&self) -> Vec<Expr> {
+        self.prf.lines().into_iter().filter_map(|line| line.get::<<P as Proof>::JustificationReference, _>().and_then(|jr| self.prf.lookup_step(jr)).map(|just| just.0)).collect()
+    }
+
+    /// Formulas of the top-level premises of the proof
+    fn premise_formulas(&self) -> Vec<Expr> {
+        self.prf.premises().into_iter().filter_map(|pr| self.prf.lookup_premise(&pr)).collect()
+    }
+
+    /// Progress of the proof, for the instructor dashboard
+    fn telemetry_report(&self) -> telemetry::Report {
+        let problem = progress::problem_key(&self.premise_formulas(), &self.goals);
+        let mut report = telemetry::Report { proof: self.id.clone(), problem, solved: self.is_solved(), ..Default::default() };
+        for r in self.lines_in_order() {
+            match self.line_status(r) {
+                LineStatus::Empty => continue,
+                LineStatus::Pending => {}
+                LineStatus::Unparsed(_) => report.unparsed += 1,
+                LineStatus::Correct => report.correct += 1,
+                LineStatus::Warned(_) => {
+                    report.correct += 1;
+                    let input = self.pud.ref_to_input.get(&r).map_or("", String::as_str);
+                    report.warnings.extend(lints::input_lints(input, self.require_full_parens).into_iter().chain(lints::lints(&self.prf, &r, &self.goals)).map(|lint| lint.code().to_string()));
                 }
-            },
-            Some(Err(err)) => {
-                html! {
-                    <button type="button" class="btn btn-danger s1" data-toggle="popover" data-content={ err.to_string() }>
-                        { "Error" }
-                    </button>
+                LineStatus::Gap => report.gaps += 1,
+                LineStatus::Incorrect(_) | LineStatus::TimedOut => {
+                    report.incorrect += 1;
+                    if let Err(err) = self.prf.verify_line(&r) {
+                        report.errors.push(err.code().to_string());
+                    }
                 }
             }
+            report.lines += 1;
         }
+        report
     }
 
-    /// Renders a single proof line with all associated UI elements, including indentation, feedback, and actions.
-    fn render_proof_line(&self, ctx: &Context<Self>, line: usize, depth: usize, proofref: PjRef<P>, edge_decoration: &str) -> Html {
+    /// Record solving the proof in the user's progress. The problem is written
+    /// as its premises and goals, or its last conclusion if it has no goals.
+    fn record_solved(&self) {
         use Coproduct::{Inl, Inr};
-        let line_num_dep_checkbox = self.render_line_num_dep_checkbox(ctx, Some(line), Coproduct::inject(proofref));
-        let mut indentation = yew::virtual_dom::VList::new();
-        for _ in 0..depth {
-            //indentation.add_child(html! { <span style="background-color:black">{"-"}</span>});
-            //indentation.add_child(html! { <span style="color:white">{"-"}</span>});
-            indentation.add_child(html! { <span class="indent"> { box_chars::VERT } </span>});
-        }
-        indentation.add_child(html! { <span class="indent">{edge_decoration}</span>});
-        let handle_input = ctx.link().callback(move |value: String| ProofWidgetMsg::LineChanged(proofref, value));
-        let select_line = ctx.link().callback(move |()| ProofWidgetMsg::LineAction(LineActionKind::Select, proofref));
-
-        // Menu for selecting a line action
-        let action_selector = {
-            // List of menu items
-            let options = actions::valid_actions(&self.prf, proofref)
-                .map(|action_info| {
-                    let lak = action_info.line_action_kind.clone();
-
-                    // Callback triggering line action
-                    let onclick = ctx.link().callback(move |_| ProofWidgetMsg::LineAction(lak.clone(), proofref));
-
-                    // Badge showing keyboard shortcut of action, if any
-                    let keyboard_shortcut = match action_info.keyboard_shortcut {
-                        Some(key) => {
-                            html! {
-                                <span>
-                                    <kbd>
-                                        <kbd> { "Ctrl" } </kbd>
-                                        { '-' }
-                                        <kbd> { key.to_uppercase() } </kbd>
-                                    </kbd>
-                                </span>
-                            }
-                        }
-                        None => html!(),
-                    };
+        let premises = self.premise_formulas();
+        let conclusions = match &self.goals[..] {
+            [] => self.conclusions().pop().into_iter().collect::<Vec<_>>(),
+            goals => goals.to_vec(),
+        };
+        let problem = progress::problem_key(&premises, &conclusions);
+        let rules = self
+            .lines_in_order()
+            .into_iter()
+            .filter_map(|r| match r {
+                Inr(Inl(jr)) => self.prf.lookup_step(&jr).map(|just| just.1.get_name()),
+                _ => None,
+            })
+            .collect();
+        let mut progress = Progress::load();
+        progress.record_solved(problem, &rules, progress::today());
+        progress.save();
+    }
 
-                    // Item in line actions menu
-                    html! {
-                        <a class="dropdown-item" href="#" onclick={ onclick }>
-                            { action_info.description }
-                            { ' ' }
-                            { keyboard_shortcut }
-                        </a>
-                    }
-                })
-                .collect::<Vec<Html>>();
+    /// The operators and logic of this proof, for parsing and printing its
+    /// lines with `while_open`
+    fn syntax(&self) -> Syntax {
+        Syntax { logic_mode: self.logic_mode, operators: self.operators.clone() }
+    }
 
-            // Menu for selecting a line action
-            html! {
-                <div class="dropdown">
-                    <button
-                        type="button"
-                        class="btn btn-secondary"
-                        id="dropdownMenuButton"
-                        data-toggle="dropdown"
-                        aria-haspopup="true"
-                        aria-expanded="false">
-
-                        { "\u{22EE}" }
-                    </button>
-                    <div class="dropdown-menu" aria-labelledby="dropdownMenuButton">
-                        { options }
-                    </div>
-                </div>
-            }
-        };
-        let init_value = self.pud.ref_to_input.get(&proofref).cloned().unwrap_or_default();
-        let in_subproof = depth > 0;
-        let rule_feedback = self.render_line_feedback(proofref, in_subproof);
-        let is_selected_line = self.selected_line.as_ref().map(|line| line.line_ref == proofref).unwrap_or(false);
-        let is_dep_line = match self.selected_line {
-            Some(SelectedLine { line_ref: Inr(Inl(selected_line)), .. }) => match self.prf.lookup_justification_or_die(&selected_line) {
-                Ok(Justification(_, _, line_deps, _)) => line_deps.contains(&proofref),
-                Err(_) => false,
-            },
-            _ => false,
-        };
-        let class = if is_selected_line {
-            "proof-line table-info"
-        } else if is_dep_line {
-            "proof-line table-secondary"
-        } else {
-            "proof-line"
+    /// Metadata to save the proof with. Exams are saved with the student's
+    /// name as the author and in the exam settings, where the hash covers it.
+    fn save_metadata(&self) -> ProofMetaData {
+        let (author, exam) = match &self.exam {
+            Some(ExamState { config, .. }) => (config.student.clone().unwrap_or_else(|| "UNKNOWN".into()), Some(config.clone())),
+            None => ("ARIS-YEW-UI".into(), None),
         };
-        let feedback_and_just_widgets = match proofref {
-            Inl(_) => {
-                // Premise
-                html! {
-                    <>
-                        <td></td>
-                        <td> { rule_feedback } </td>
-                        <td></td>
-                    </>
-                }
-            }
-            Inr(Inl(jref)) => {
-                // Justification
-                html! {
-                    <>
-                        <td> { rule_feedback } </td>
-                        { self.render_justification_widget(ctx, jref) }
-                    </>
+        ProofMetaData { author: Some(author), goals: self.goals.clone(), exam, delayed_feedback: self.delayed_feedback.clone(), operators: self.operators.clone(), logic_mode: self.logic_mode, require_all_premises: self.require_all_premises, require_full_parens: self.require_full_parens, assumption_policy: self.assumption_policy, scoping_policy: self.scoping_policy, blanks: self.blanks.clone(), timings: self.save_timings(), ..Default::default() }
+    }
+
+    /// Timings to save the proof with, by line number, leaving out lines that
+    /// were deleted
+    fn save_timings(&self) -> Option<Timings> {
+        let timings = self.timings.as_ref()?;
+        let line_info = self.pud.line_info(&self.prf);
+        Some(Timings { lines: self.lines_in_order().into_iter().filter_map(|r| Some((line_info.line_of(&r)?, *timings.get(&r)?))).collect() })
+    }
+
+    /// Record the lines created and the lines that became correct since the
+    /// last update, if the proof records timings
+    fn record_timings(&mut self) {
+        if self.timings.is_none() {
+            return;
+        }
+        let now = js_sys::Date::now() as u64;
+        let lines = self.lines_in_order();
+        let correct = lines.iter().copied().filter(|r| self.line_status(*r).is_correct()).collect::<BTreeSet<_>>();
+        if let Some(timings) = &mut self.timings {
+            for r in lines {
+                let timing = timings.entry(r).or_insert(LineTiming { created: now, correct: None });
+                if correct.contains(&r) && !self.correct_lines.contains(&r) {
+                    timing.correct = Some(now);
                 }
             }
-            Inr(Inr(void)) => match void {},
-        };
-        let id_num = format!("{}{}{}", self.id, &"line-number-", &line.to_string());
-        html! {
-            <tr class={ class }>
-                <td> { line_num_dep_checkbox } </td>
-                <td>
-                    { indentation }
-                    <ExprEntry
-                        oninput={ handle_input }
-                        onfocus={ select_line }
-                        focus={ is_selected_line }
-                        init_value={ init_value }
-                        id={ id_num }/>
-                </td>
-                { feedback_and_just_widgets }
-                <td>{ action_selector }</td>
-            </tr>
         }
+        self.correct_lines = correct;
     }
 
-    /// Renders the entire proof structure as a hierarchical table.
-    /// Subproofs are displayed indented, with dependency management and line actions integrated.
-    fn render_proof(&self, ctx: &Context<Self>, prf: &<P as Proof>::Subproof, sref: Option<<P as Proof>::SubproofReference>, line: &mut usize, depth: &mut usize) -> Html {
-        // output has a bool tag to prune subproof spacers with, because VNode's PartialEq doesn't do the right thing
-        let mut output: Vec<(Html, bool)> = Vec::new();
-        for prem in prf.premises().iter() {
-            let edge_decoration = { box_chars::VERT }.to_string();
-            output.push((self.render_proof_line(ctx, *line, *depth, Coproduct::inject(*prem), &edge_decoration), false));
-            *line += 1;
+    /// Whether a part of a line is locked, because the proof is a cloze
+    /// handout without a blank there
+    fn is_locked(&self, proofref: PjRef<P>, part: Blank) -> bool {
+        !self.blanks.is_empty() && !self.pud.line_info(&self.prf).lines.get(&proofref).and_then(|(line, _)| self.blanks.get(line)).map(|parts| parts.contains(&part)).unwrap_or(false)
+    }
+
+    /// Whether a message would edit a locked part of a cloze proof, which is
+    /// anything but filling in its blanks
+    fn edits_locked_cell(&self, msg: &ProofWidgetMsg) -> bool {
+        if self.blanks.is_empty() {
+            return false;
         }
-        let dep_checkbox = match sref {
-            Some(sr) => self.render_line_num_dep_checkbox(ctx, None, Coproduct::inject(sr)),
-            None => yew::virtual_dom::VNode::from(yew::virtual_dom::VList::new()),
-        };
-        let mut spacer = yew::virtual_dom::VList::new();
-        spacer.add_child(html! { <td>{ dep_checkbox }</td> });
-        //spacer.add_child(html! { <td style="background-color:black"></td> });
-        let mut spacer_lines = String::new();
-        for _ in 0..*depth {
-            spacer_lines.push(box_chars::VERT);
+        match msg {
+            ProofWidgetMsg::LineChanged(r, _) => self.is_locked(*r, Blank::Formula),
+            ProofWidgetMsg::LineAction(LineActionKind::SetRule { .. }, r) => self.is_locked(*r, Blank::Rule),
+            ProofWidgetMsg::LineAction(LineActionKind::ToggleDependency { .. }, r) => self.is_locked(*r, Blank::Citations),
+            ProofWidgetMsg::LineAction(LineActionKind::Insert { .. } | LineActionKind::Delete { .. } | LineActionKind::ApplyEquivalence { .. } | LineActionKind::Duplicate | LineActionKind::Move { .. } | LineActionKind::SplitConjunction | LineActionKind::ConcludeContradiction | LineActionKind::ReiterateCitations | LineActionKind::InsertGap | LineActionKind::WorkBackward { .. }, _) => true,
+            ProofWidgetMsg::ReplaceAll | ProofWidgetMsg::ImportPremises | ProofWidgetMsg::LinesPasted(..) | ProofWidgetMsg::ImportPastedLines | ProofWidgetMsg::PastePastedLinesInline | ProofWidgetMsg::TransplantLines(_) => true,
+            _ => false,
         }
-        spacer_lines += &format!("{}{}", box_chars::VERT_RIGHT, box_chars::HORIZ.to_string().repeat(4));
-        spacer.add_child(html! { <td> <span class="indent"> {spacer_lines} </span> </td> });
+    }
 
-        let spacer = html! { <tr> { spacer } </tr> };
+    /// Start checking every line of the proof, keeping the results for
+    /// on-demand verification
+    fn check_proof(&mut self, ctx: &Context<Self>) {
+        self.flush_pending_parses();
+        let remaining = Some(LineChecks::new(&self.prf));
+        self.last_check = Some(CheckResults { statuses: HashMap::new(), proof: self.prf.clone(), inputs: self.pud.ref_to_input.clone(), stale: false, remaining });
+        self.continue_check(ctx);
+    }
 
-        output.push((spacer, false));
-        let prf_lines = prf.lines();
-        for (i, lineref) in prf_lines.iter().enumerate() {
-            use Coproduct::{Inl, Inr};
-            let edge_decoration = if i == prf_lines.len() - 1 { box_chars::UP_RIGHT } else { box_chars::VERT }.to_string();
+    /// Check lines of the running check for up to `CHECK_SLICE_MS`, then
+    /// schedule checking the rest after the page updates
+    fn continue_check(&mut self, ctx: &Context<Self>) {
+        let (goals, pud, require_full_parens, exam) = (&self.goals, &self.pud, self.require_full_parens, self.exam.is_some());
+        let CheckResults { statuses, proof, inputs, remaining, .. } = match &mut self.last_check {
+            Some(check) => check,
+            None => return,
+        };
+        let checks = match remaining {
+            Some(checks) => checks,
+            None => return,
+        };
+        // Lines are checked against the proof as it was when the check
+        // started, so editing it meanwhile doesn't mix up the results
+        let started = js_sys::Date::now();
+        while js_sys::Date::now() - started < CHECK_SLICE_MS {
+            let LineResult { line, result, .. } = match checks.check_next(proof) {
+                Some(checked) => checked,
+                None => break,
+            };
+            let status = match inputs.get(&line).filter(|input| !input.is_empty()) {
+                None => LineStatus::Empty,
+                Some(input) => match aris::parser::try_parse(input) {
+                    Err(err) => LineStatus::Unparsed(err),
+                    Ok(_) => {
+                        if let Err(err) = &result {
+                            analytics::record(&analytics::Event::ErrorHit { code: err.code().to_string() });
+                        }
+                        LineStatus::from_result(result, &pud.line_info(proof), || warning_messages(proof, line, input, goals, require_full_parens, pud), || hint_message(proof, line, exam, pud))
+                    }
+                },
+            };
+            statuses.insert(line, status);
+        }
+        if checks.is_done() {
+            *remaining = None;
+            self.check_timeout = None;
+        } else {
+            let link = ctx.link().clone();
+            self.check_timeout = Some(Timeout::new(0, move || link.send_message(ProofWidgetMsg::ContinueCheck)));
+        }
+    }
+
+    /// Renders the bar under the proof counting its correct, incorrect, and
+    /// unparsed lines and its gaps, along with the list of lines with errors,
+    /// warnings, or gaps if it's shown. Clicking an entry of the list selects
+    /// and scrolls to its line.
+    fn render_status_bar(&self, ctx: &Context<Self>) -> Html {
+        let statuses = self.lines_in_order().into_iter().filter_map(|r| Some((r, self.shown_status(r)?))).collect::<Vec<_>>();
+        let count = |matches: fn(&LineStatus) -> bool| statuses.iter().filter(|(_, status)| matches(status)).count();
+        let correct = count(LineStatus::is_correct);
+        let warned = count(|status| matches!(status, LineStatus::Warned(_)));
+        let incorrect = count(|status| matches!(status, LineStatus::Incorrect(_) | LineStatus::TimedOut));
+        let unparsed = count(|status| matches!(status, LineStatus::Unparsed(_)));
+        let gaps = count(|status| matches!(status, LineStatus::Gap));
+
+        let errors = statuses
+            .iter()
+            .filter_map(|(r, status)| {
+                let (message, badge) = match status {
+                    LineStatus::Unparsed(err) => (err.to_string(), "badge badge-secondary mr-2"),
+                    LineStatus::Incorrect(err) => (err.clone(), "badge badge-secondary mr-2"),
+                    LineStatus::TimedOut => (ProofCheckError::<(), ()>::TimedOut.to_string(), "badge badge-secondary mr-2"),
+                    LineStatus::Warned(warnings) => (warnings.join(" "), "badge badge-warning mr-2"),
+                    LineStatus::Gap => (ProofCheckError::<(), ()>::Gap.to_string(), "badge badge-info mr-2"),
+                    _ => return None,
+                };
+                let line = self.pud.line_info(&self.prf).line_of(r)?;
+                let r = *r;
+                let onclick = ctx.link().batch_callback(move |_| vec![ProofWidgetMsg::LineAction(LineActionKind::Select, r), ProofWidgetMsg::FlashLines(vec![r])]);
+                Some(html! {
+                    <button type="button" class="list-group-item list-group-item-action py-1" { onclick }>
+                        <span class={ badge }> { line } </span>
+                        { message }
+                    </button>
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Warnings don't stop the proof from being solved, so warned lines count as correct too
+        let warned_badge = if warned > 0 {
+            html! { <span class="badge badge-light mr-2"> { format!("{} with warnings", warned) } </span> }
+        } else {
+            html! {}
+        };
+        // The proof isn't finished until its gaps are filled in
+        let gaps_badge = if gaps > 0 {
+            html! { <span class="badge badge-info mr-2"> { format!("{} {} left", gaps, if gaps == 1 { "gap" } else { "gaps" }) } </span> }
+        } else {
+            html! {}
+        };
+
+        let error_count = errors.len();
+        let toggle = if error_count > 0 {
+            let label = if self.show_error_list { "Hide errors" } else { "Show errors" };
+            html! {
+                <button type="button" class="btn btn-link btn-sm ml-auto" aria-expanded={ self.show_error_list.to_string() } onclick={ ctx.link().callback(|_| ProofWidgetMsg::ToggleErrorList) }>
+                    { label }
+                </button>
+            }
+        } else {
+            html! {}
+        };
+        let error_list = if self.show_error_list && error_count > 0 {
+            html! { <div class="list-group list-group-flush proof-error-list"> { for errors } </div> }
+        } else {
+            html! {}
+        };
+
+        // With on-demand verification, the counts are as of the last check
+        let (check_button, check_state) = match (self.verification(), &self.last_check) {
+            (Verification::Live, _) => (html! {}, html! {}),
+            (Verification::OnDemand, check) if self.delayed_feedback.is_some() => self.render_submit_button(ctx, check.as_ref()),
+            (Verification::OnDemand, check) => {
+                let check_state = match check {
+                    None => html! { <span class="text-muted small mr-2"> { "Not checked yet" } </span> },
+                    Some(CheckResults { stale: true, .. }) => html! { <span class="text-muted small mr-2"> { "Edited since last check" } </span> },
+                    Some(CheckResults { stale: false, .. }) => html! {},
+                };
+                let check_button = html! {
+                    <button type="button" class="btn btn-primary btn-sm mr-2" onclick={ ctx.link().callback(|_| ProofWidgetMsg::CheckProof) }>
+                        { "Check proof" }
+                    </button>
+                };
+                (check_button, check_state)
+            }
+        };
+
+        let check_state = match self.last_check.as_ref().and_then(|check| check.remaining.as_ref()) {
+            Some(checks) => html! {
+                <span class="text-muted small mr-2">
+                    <span class="spinner-border spinner-border-sm mr-1" role="status"></span>
+                    { format!("Checking\u{2026} {} of {} lines", checks.checked(), checks.total()) }
+                </span>
+            },
+            None => check_state,
+        };
+
+        html! {
+            <div class="proof-status-bar card m-2">
+                <div class="card-body d-flex align-items-center py-1 px-2">
+                    { check_button }
+                    <span class="badge badge-success mr-2"> { format!("{} correct", correct) } </span>
+                    { warned_badge }
+                    <span class="badge badge-danger mr-2"> { format!("{} incorrect", incorrect) } </span>
+                    <span class="badge badge-warning mr-2"> { format!("{} unparsed", unparsed) } </span>
+                    { gaps_badge }
+                    { check_state }
+                    { toggle }
+                </div>
+                { error_list }
+            </div>
+        }
+    }
+
+    /// Renders the button for submitting a proof with delayed feedback to be
+    /// checked, and how many checks are left
+    fn render_submit_button(&self, ctx: &Context<Self>, check: Option<&CheckResults>) -> (Html, Html) {
+        let checks_left = self.delayed_feedback.as_ref().and_then(DelayedFeedback::checks_left);
+        let edited = check.map(|check| check.stale).unwrap_or(true);
+        let remaining = match checks_left {
+            Some(1) => "1 check left".to_string(),
+            Some(left) => format!("{left} checks left"),
+            None => "Unlimited checks".to_string(),
+        };
+        let state = match (check, edited) {
+            (_, false) => format!("Feedback shown \u{b7} {remaining}"),
+            (None, true) => format!("Feedback is shown when you submit \u{b7} {remaining}"),
+            (Some(_), true) => format!("Edited since last submission, so feedback is hidden \u{b7} {remaining}"),
+        };
+        let check_button = html! {
+            <button type="button" class="btn btn-primary btn-sm mr-2" disabled={ !edited || checks_left == Some(0) } onclick={ ctx.link().callback(|_| ProofWidgetMsg::CheckProof) }>
+                { "Submit for feedback" }
+            </button>
+        };
+        (check_button, html! { <span class="text-muted small mr-2"> { state } </span> })
+    }
+
+    /// Set a line's text and parse it right away, updating the line's formula
+    /// if it parses. This cancels any pending parse of the line.
+    fn set_line_input(&mut self, r: PjRef<P>, input: String) {
+        self.pending_parses.remove(&r);
+        self.pud.ref_to_input.insert(r, input);
+        self.parse_line(r);
+    }
+
+    /// Record the error of a line that was just edited, if the edit left it
+    /// shown as wrong
+    fn record_error_hit(&self, r: PjRef<P>) {
+        if !analytics::is_enabled() || !matches!(self.verification(), Verification::Live) || !matches!(self.line_status(r), LineStatus::Incorrect(_)) {
+            return;
+        }
+        if let Err(err) = self.prf.verify_line(&r) {
+            analytics::record(&analytics::Event::ErrorHit { code: err.code().to_string() });
+        }
+    }
+
+    /// Parse a line's text, updating its formula if it parses
+    fn parse_line(&mut self, r: PjRef<P>) {
+        use Coproduct::{Inl, Inr};
+        if let Some(e) = self.pud.ref_to_input.get(&r).and_then(|input| aris::parser::parse(input)) {
+            match r {
+                Inl(pr) => {
+                    self.prf.with_mut_premise(&pr, |x| *x = e);
+                }
+                Inr(Inl(jr)) => {
+                    self.prf.with_mut_step(&jr, |x| x.0 = e);
+                }
+                Inr(Inr(void)) => match void {},
+            }
+        }
+    }
+
+    /// Parse every line with a pending parse right away, so the proof is up
+    /// to date with what was typed
+    fn flush_pending_parses(&mut self) {
+        for r in self.pending_parses.drain().map(|(r, _)| r).collect::<Vec<_>>() {
+            self.parse_line(r);
+        }
+    }
+
+    /// Actions of the line actions menu of a line, leaving out those that
+    /// would edit a locked cell
+    fn line_actions(&self, proofref: PjRef<P>) -> LineActions {
+        use Coproduct::Inl;
+        let actions = actions::valid_actions(&self.prf, proofref).filter(|action_info| !self.edits_locked_cell(&ProofWidgetMsg::LineAction(action_info.line_action_kind.clone(), proofref))).collect();
+        let import_premises = matches!(proofref, Inl(_)) && self.prf.parent_of_line(&pj_to_pjs::<P>(proofref)).is_none() && self.blanks.is_empty();
+        LineActions { actions, import_premises }
+    }
+
+    /// Items of the context menu of a line: the line actions menu's items,
+    /// followed by copying, duplicating, and moving the line
+    fn context_menu_items(&self, ctx: &Context<Self>, proofref: PjRef<P>) -> Vec<MenuItem> {
+        let action = |label: &str, lak: LineActionKind| MenuItem::Action { label: label.to_string(), title: None, shortcut: None, onselect: ctx.link().callback(move |()| ProofWidgetMsg::LineAction(lak.clone(), proofref)) };
+        let mut items = self.line_actions(proofref).menu_items(&self.onmsg, proofref);
+        if self.exam.is_none() {
+            let text = self.pud.ref_to_input.get(&proofref).cloned().unwrap_or_default();
+            items.push(MenuItem::Action { label: "Copy formula".to_string(), title: None, shortcut: None, onselect: Callback::from(move |()| copy_to_clipboard(&text)) });
+            if let Some(sr) = self.prf.parent_of_line(&pj_to_pjs::<P>(proofref)) {
+                let fragment = self.copy_items(&[Coproduct::inject(sr)]);
+                items.push(MenuItem::Action { label: "Copy subproof".to_string(), title: None, shortcut: None, onselect: Callback::from(move |()| copy_fragment(&fragment)) });
+            }
+            let top = self.prf.top_level_proof();
+            let all = top.premises().into_iter().map(Coproduct::inject).chain(top.lines().into_iter().map(js_to_pjs::<P>)).collect::<Vec<PjsRef<P>>>();
+            let fragment = self.copy_items(&all);
+            items.push(MenuItem::Action { label: "Copy all lines".to_string(), title: None, shortcut: None, onselect: Callback::from(move |()| copy_fragment(&fragment)) });
+            if let Some(ontransplant) = &ctx.props().ontransplant {
+                let send = |label: &str, items_to_send: &[PjsRef<P>]| {
+                    let (ontransplant, fragment) = (ontransplant.clone(), self.copy_items(items_to_send));
+                    MenuItem::Action { label: label.to_string(), title: None, shortcut: None, onselect: Callback::from(move |()| ontransplant.emit(fragment.clone())) }
+                };
+                items.push(send("Send line to proof", &[pj_to_pjs::<P>(proofref)]));
+                if let Some(sr) = self.prf.parent_of_line(&pj_to_pjs::<P>(proofref)) {
+                    items.push(send("Send subproof to proof", &[Coproduct::inject(sr)]));
+                }
+                items.push(send("Send all lines to proof", &all));
+            }
+        }
+        // Cloze proofs' lines stay where they are
+        if !self.blanks.is_empty() {
+            return items;
+        }
+        items.push(MenuItem::Action { label: INSERT_STEPS_LABEL.to_string(), title: None, shortcut: None, onselect: self.insert_steps_callback(ctx, proofref) });
+        if self.duplicate_kind(proofref).is_some() {
+            items.push(action("Duplicate line", LineActionKind::Duplicate));
+        }
+        if !self.conjuncts_of(proofref).is_empty() {
+            items.push(action(SPLIT_CONJUNCTION_LABEL, LineActionKind::SplitConjunction));
+        }
+        if self.prf.contradiction_after_line(&proofref).is_some() {
+            items.push(action(CONCLUDE_CONTRADICTION_LABEL, LineActionKind::ConcludeContradiction));
+        }
+        if !self.lines_to_reiterate(proofref).is_empty() {
+            items.push(action(REITERATE_CITATIONS_LABEL, LineActionKind::ReiterateCitations));
+        }
+        items.push(action(INSERT_GAP_LABEL, LineActionKind::InsertGap));
+        let backward = self.backward_rules(proofref);
+        if !backward.is_empty() {
+            let submenus = backward.into_iter().map(|(class, rules)| MenuItem::Submenu { label: class.to_string(), items: rules.into_iter().map(|rule| action(&rule_names::display_name(rule), LineActionKind::WorkBackward { rule })).collect() }).collect();
+            items.push(MenuItem::Submenu { label: WORK_BACKWARD_LABEL.to_string(), items: submenus });
+        }
+        if self.adjacent_line(proofref, false).is_some() {
+            items.push(action("Move up", LineActionKind::Move { after: false }));
+        }
+        if self.adjacent_line(proofref, true).is_some() {
+            items.push(action("Move down", LineActionKind::Move { after: true }));
+        }
+        items
+    }
+
+    /// The first step citing each subproof, which discharges its assumption,
+    /// with the color the step shares with the subproofs it's the first to
+    /// cite. Colors are handed out in order of the steps, cycling through
+    /// `DISCHARGE_COLORS` of them.
+    fn discharges(&self) -> HashMap<<P as Proof>::SubproofReference, (<P as Proof>::JustificationReference, usize)> {
+        use Coproduct::{Inl, Inr};
+        let mut discharges = HashMap::new();
+        let mut color = 0;
+        for line in self.lines_in_order() {
+            let (jr, sdeps) = match line {
+                Inr(Inl(jr)) => match self.prf.lookup_step(&jr) {
+                    Some(Justification(_, _, _, sdeps)) => (jr, sdeps),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let discharged = sdeps.into_iter().filter(|sr| !discharges.contains_key(sr)).collect::<Vec<_>>();
+            if !discharged.is_empty() {
+                discharges.extend(discharged.into_iter().map(|sr| (sr, (jr, color % DISCHARGE_COLORS))));
+                color += 1;
+            }
+        }
+        discharges
+    }
+
+    /// The color of the subproofs a step discharges, if it's the first step
+    /// citing any, as given by `discharges()`, along with their line ranges
+    fn discharge_mark(&self, jr: <P as Proof>::JustificationReference) -> Option<(usize, String)> {
+        let line_info = self.pud.line_info(&self.prf);
+        let mut discharged = self.discharges().into_iter().filter(|(_, (by, _))| *by == jr).filter_map(|(sr, (_, color))| Some((line_info.line_range_of_subproof(&sr)?, color))).collect::<Vec<_>>();
+        discharged.sort();
+        let &(_, color) = discharged.first()?;
+        Some((color, discharged.iter().map(|&(range, _)| line_info::format_range(range)).collect::<Vec<_>>().join(", ")))
+    }
+
+    /// The conjuncts of a line's formula, if it's a conjunction, which
+    /// `LineActionKind::SplitConjunction` derives a step for each of
+    fn conjuncts_of(&self, proofref: PjRef<P>) -> Vec<Expr> {
+        match self.prf.lookup_expr(&proofref) {
+            Some(Expr::Assoc { op: Op::And, exprs }) => exprs,
+            _ => vec![],
+        }
+    }
+
+    /// The lines a step cites from outside its subproof, if it would be
+    /// correct once they're reiterated into it, which
+    /// `LineActionKind::ReiterateCitations` does
+    fn lines_to_reiterate(&self, proofref: PjRef<P>) -> Vec<PjRef<P>> {
+        match self.prf.verify_line(&proofref) {
+            Err(ProofCheckError::NeedsReiteration(lines)) => lines,
+            _ => vec![],
+        }
+    }
+
+    /// The rules that could conclude a gap, by classification, for working
+    /// backward from it. Lines that aren't gaps, or that are still blank,
+    /// have none.
+    fn backward_rules(&self, proofref: PjRef<P>) -> Vec<(RuleClassification, Vec<Rule>)> {
+        use Coproduct::{Inl, Inr};
+        let goal = match proofref {
+            Inr(Inl(jr)) => match self.prf.lookup_step(&jr) {
+                Some(Justification(goal, rule, _, _)) if rule == RuleM::Gap && goal != Expr::var("") => goal,
+                _ => return vec![],
+            },
+            _ => return vec![],
+        };
+        RuleClassification::iter().map(|class| (class, class.rules().filter(|rule| rule.backward(&goal).is_some()).collect::<Vec<_>>())).filter(|(_, rules)| !rules.is_empty()).collect()
+    }
+
+    /// Callback asking how many empty steps to insert after a line, and then
+    /// inserting them, for setting up a long derivation at once
+    fn insert_steps_callback(&self, ctx: &Context<Self>, proofref: PjRef<P>) -> Callback<()> {
+        use Coproduct::{Inl, Inr};
+        let relative_to = match proofref {
+            Inl(_) => ProofItemKind::Premise,
+            Inr(Inl(_)) => ProofItemKind::Just,
+            Inr(Inr(void)) => match void {},
+        };
+        ctx.link().batch_callback(move |()| {
+            let count = web_sys::window().and_then(|window| window.prompt_with_message_and_default("How many steps to insert?", "5").ok().flatten());
+            count.and_then(|count| count.trim().parse::<usize>().ok()).filter(|&count| count > 0).map(|count| ProofWidgetMsg::LineAction(LineActionKind::Insert { what: ProofItemKind::Just, after: true, relative_to, count }, proofref))
+        })
+    }
+
+    /// Copy lines and subproofs of the proof for the clipboard, with their
+    /// formulas as they're written
+    fn copy_items(&self, items: &[PjsRef<P>]) -> ClipboardProof {
+        ClipboardProof::from_items(&self.prf, items, |r| self.pud.ref_to_input.get(r).cloned().unwrap_or_default())
+    }
+
+    /// The kind of line action inserting a copy of a line right after it:
+    /// another premise for a premise, and another step for a step.
+    /// Assumptions of subproofs can only be duplicated if subproofs may have
+    /// several.
+    fn duplicate_kind(&self, proofref: PjRef<P>) -> Option<LineActionKind> {
+        use Coproduct::{Inl, Inr};
+        match proofref {
+            Inl(_) if self.prf.parent_of_line(&pj_to_pjs::<P>(proofref)).is_none_or(|parent| self.prf.may_add_assumption(&parent)) => Some(LineActionKind::Insert { what: ProofItemKind::Premise, after: true, relative_to: ProofItemKind::Premise, count: 1 }),
+            Inl(_) => None,
+            Inr(Inl(_)) => Some(LineActionKind::Insert { what: ProofItemKind::Just, after: true, relative_to: ProofItemKind::Just, count: 1 }),
+            Inr(Inr(void)) => match void {},
+        }
+    }
+
+    /// The line right after `line_ref` in its subproof, or right before it if
+    /// `after` is false, if it's the same kind of line. A step next to a
+    /// subproof has no adjacent line on that side.
+    fn adjacent_line(&self, line_ref: PjRef<P>, after: bool) -> Option<PjRef<P>> {
+        match self.prf.parent_of_line(&pj_to_pjs::<P>(line_ref)) {
+            Some(sr) => adjacent_line::<P, _>(&self.prf.lookup_subproof(&sr)?, line_ref, after),
+            None => adjacent_line::<P, _>(self.prf.top_level_proof(), line_ref, after),
+        }
+    }
+
+    /// Swap the contents of two lines of the same kind, and the citations of
+    /// them throughout the proof, so that the lines trade places
+    fn swap_lines(&mut self, a: PjRef<P>, b: PjRef<P>) {
+        use Coproduct::{Inl, Inr};
+        self.flush_pending_parses();
+        match (a, b) {
+            (Inl(pa), Inl(pb)) => {
+                let (ea, eb) = match (self.prf.lookup_premise(&pa), self.prf.lookup_premise(&pb)) {
+                    (Some(ea), Some(eb)) => (ea, eb),
+                    _ => return,
+                };
+                self.prf.with_mut_premise(&pa, |e| *e = eb);
+                self.prf.with_mut_premise(&pb, |e| *e = ea);
+            }
+            (Inr(Inl(ja)), Inr(Inl(jb))) => {
+                let (just_a, just_b) = match (self.prf.lookup_step(&ja), self.prf.lookup_step(&jb)) {
+                    (Some(just_a), Some(just_b)) => (just_a, just_b),
+                    _ => return,
+                };
+                self.prf.with_mut_step(&ja, |j| *j = just_b);
+                self.prf.with_mut_step(&jb, |j| *j = just_a);
+            }
+            _ => return,
+        }
+        let input_a = self.pud.ref_to_input.remove(&a);
+        let input_b = self.pud.ref_to_input.remove(&b);
+        self.pud.ref_to_input.extend(input_b.map(|input| (a, input)).into_iter().chain(input_a.map(|input| (b, input))));
+        let caret_a = self.pud.ref_to_caret.remove(&a);
+        let caret_b = self.pud.ref_to_caret.remove(&b);
+        self.pud.ref_to_caret.extend(caret_b.map(|caret| (a, caret)).into_iter().chain(caret_a.map(|caret| (b, caret))));
+        for line in self.lines_in_order() {
+            if let Inr(Inl(jr)) = line {
+                self.prf.with_mut_step(&jr, |j| {
+                    for dep in j.2.iter_mut() {
+                        if *dep == a {
+                            *dep = b;
+                        } else if *dep == b {
+                            *dep = a;
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    /// Renders a proof line as a `ProofRow`, which yew only renders again
+    /// when what it shows changes
+    fn render_row(&self, line: usize, depth: usize, proofref: PjRef<P>, edge_decoration: char) -> Html {
+        use Coproduct::{Inl, Inr};
+        let is_assumption = matches!(proofref, Inl(pr) if self.prf.is_assumption(&pr));
+        let is_selected_line = self.selected_line.as_ref().map(|line| line.line_ref == proofref).unwrap_or(false);
+        let is_dep_line = match self.selected_line {
+            Some(SelectedLine { line_ref: Inr(Inl(selected_line)), .. }) => match self.prf.lookup_justification_or_die(&selected_line) {
+                Ok(Justification(_, _, line_deps, _)) => line_deps.contains(&proofref),
+                Err(_) => false,
+            },
+            _ => false,
+        };
+        let highlight = if is_selected_line {
+            Highlight::Selected
+        } else if self.matches_search(proofref) {
+            Highlight::SearchMatch
+        } else if is_dep_line {
+            Highlight::Cited
+        } else {
+            Highlight::None
+        };
+        let jref = match proofref {
+            Inl(_) => None,
+            Inr(Inl(jref)) => Some(jref),
+            Inr(Inr(void)) => match void {},
+        };
+        let props = ProofRowProps {
+            onmsg: self.onmsg.clone(),
+            widget_id: self.id.clone(),
+            proofref,
+            line,
+            depth,
+            edge_decoration,
+            input: self.pud.ref_to_input.get(&proofref).cloned().unwrap_or_default(),
+            caret: self.pud.ref_to_caret.get(&proofref).copied(),
+            // Leave the focus in the search bar, premise import dialog, or
+            // context menu while they're open
+            focus: (self.search.is_none() && self.premise_import.is_none() && self.context_menu.is_none()).then_some(is_selected_line),
+            readonly: self.is_locked(proofref, Blank::Formula),
+            is_assumption,
+            highlight,
+            flashed: self.flashed_lines.contains(&proofref),
+            citing: self.citing_line(Coproduct::inject(proofref)),
+            discharge: jref.filter(|_| self.preferences.show_discharges).and_then(|jr| self.discharge_mark(jr)),
+            feedback: self.feedback(proofref, is_assumption),
+            step: jref.map(|jr| self.step_cells(jr)),
+            actions: self.line_actions(proofref),
+            advance_on_enter: self.preferences.advance_on_enter,
+            // Pasting is blocked during an exam
+            paste_lines: self.exam.is_none(),
+            paste_offer: self.paste_offer_of(proofref),
+        };
+        html! { <ProofRow ..props /> }
+    }
+
+    /// Renders the context menu of a line, if it's open
+    fn render_context_menu(&self, ctx: &Context<Self>) -> Html {
+        match self.context_menu {
+            // Keyed by position, so right-clicking elsewhere opens a new menu
+            // rather than updating the open one
+            Some((line_ref, x, y)) => html! {
+                <Menu key={ format!("{x},{y}") } items={ self.context_menu_items(ctx, line_ref) } at={ Some((x, y)) } onclose={ ctx.link().callback(|()| ProofWidgetMsg::CloseContextMenu) } />
+            },
+            None => html! {},
+        }
+    }
+
+    /// The number of lines of text pasted into the line and what they'd be
+    /// added as, if such text was just pasted there, for offering the choice
+    /// of what to do with it
+    fn paste_offer_of(&self, proofref: PjRef<P>) -> Option<(usize, &'static str)> {
+        use Coproduct::Inl;
+        let pasted = match &self.paste_offer {
+            Some(PasteOffer { line_ref, pasted }) if *line_ref == proofref => pasted,
+            _ => return None,
+        };
+        let kind = match proofref {
+            _ if pasted.fragment.is_some() => "lines of a proof",
+            Inl(_) if self.prf.parent_of_line(&pj_to_pjs::<P>(proofref)).is_none() => "premises",
+            _ => "steps",
+        };
+        Some((pasted.lines.len(), kind))
+    }
+
+    /// Import text with several lines pasted into `line_ref` as separate
+    /// lines: premises after a top-level premise, and steps otherwise. If the
+    /// line was blank, the first pasted line goes into it. The lines are added
+    /// through the same line actions as the insert menu, and the last one is
+    /// selected. Lines copied from a proof are pasted after the line instead,
+    /// with their subproofs, rules, and citations.
+    fn import_pasted_lines(&mut self, ctx: &Context<Self>, line_ref: PjRef<P>, pasted: PastedLines) {
+        use Coproduct::Inl;
+        if let Some(fragment) = &pasted.fragment {
+            self.flush_pending_parses();
+            let lines = fragment.paste(&mut self.prf, &line_ref);
+            for (r, text) in &lines {
+                self.pud.ref_to_input.insert(*r, text.clone());
+            }
+            if let Some((last, _)) = lines.last() {
+                self.select_line(*last);
+                self.scroll_to = Some(*last);
+            }
+            return;
+        }
+        let mut lines = pasted.lines.iter().map(|line| aris::macros::expand(line));
+        let mut current = line_ref;
+        if self.pud.ref_to_input.get(&current).map(|input| input.trim().is_empty()).unwrap_or(true) {
+            if let Some(first) = lines.next() {
+                self.set_line_input(current, first);
+            }
+        }
+        for line in lines {
+            let (what, relative_to) = match current {
+                Inl(_) if self.prf.parent_of_line(&pj_to_pjs::<P>(current)).is_none() => (ProofItemKind::Premise, ProofItemKind::Premise),
+                Inl(_) => (ProofItemKind::Just, ProofItemKind::Premise),
+                _ => (ProofItemKind::Just, ProofItemKind::Just),
+            };
+            // Inserting selects the new line
+            Component::update(self, ctx, ProofWidgetMsg::LineAction(LineActionKind::Insert { what, after: true, relative_to, count: 1 }, current));
+            current = match &self.selected_line {
+                Some(selected_line) if selected_line.line_ref != current => selected_line.line_ref,
+                _ => break,
+            };
+            self.set_line_input(current, line);
+        }
+        self.select_line(current);
+        self.scroll_to = Some(current);
+    }
+
+    /// Renders a panel for rewriting the selected line with an equivalence.
+    /// The user clicks a subformula in the structure view, and every
+    /// equivalence that applies there is offered as a button; clicking it adds
+    /// the rewritten formula as a new step citing the selected line.
+    fn render_equivalence_panel(&self, ctx: &Context<Self>) -> Html {
+        // The panel suggests rules, which would give away answers in an exam
+        if self.exam.is_some() {
+            return html! {};
+        }
+        let proofref = match &self.selected_line {
+            Some(selected_line) => selected_line.line_ref,
+            None => return html! {},
+        };
+        let expr = match self.prf.lookup_expr(&proofref) {
+            Some(expr) if self.pud.ref_to_input.get(&proofref).map(|input| aris::parser::parse(input).is_some()).unwrap_or(false) => expr,
+            _ => return html! {},
+        };
+        // The line may have been edited since the subformula was picked
+        let path = self.selected_subformula.clone().filter(|path| expr.subexpr_at(path).is_some());
+
+        let rule_buttons = match &path {
+            Some(path) => {
+                let buttons = RuleClassification::iter()
+                    .flat_map(|c| c.rules())
+                    .filter_map(|rule| rule.rewrite_rule().and_then(|rewrite_rule| rewrite_rule.rewrite_at(&expr, path)).map(|rewritten| (rule, rewritten)))
+                    .map(|(rule, rewritten)| {
+                        let path = path.clone();
+                        let onclick = ctx.link().callback(move |_| ProofWidgetMsg::LineAction(LineActionKind::ApplyEquivalence { rule, path: path.clone() }, proofref));
+                        html! {
+                            <button type="button" class="btn btn-outline-primary btn-sm m-1" title={ rewritten.to_string() } onclick={ onclick }>
+                                { rule_names::display_name(rule) }
+                            </button>
+                        }
+                    })
+                    .collect::<Vec<Html>>();
+                if buttons.is_empty() {
+                    html! { <span class="text-muted"> { "No equivalence applies to this subformula" } </span> }
+                } else {
+                    html! { <>{ buttons }</> }
+                }
+            }
+            None => html! { <span class="text-muted"> { "Click a subformula to rewrite it" } </span> },
+        };
+
+        html! {
+            <div class="card m-2">
+                <div class="card-body">
+                    <h6 class="card-title"> { "Apply equivalence" } </h6>
+                    <p class="subformula-picker"> { render_subformula_picker(ctx.link(), proofref, &expr, &mut vec![], path.as_deref()) } </p>
+                    { rule_buttons }
+                </div>
+            </div>
+        }
+    }
+
+    /// Whether the proof may have a scratchpad beside it. Scratch proofs are
+    /// checked as they're written, so assignments that only give feedback on
+    /// demand don't have one, and neither do exams.
+    fn has_scratchpad(&self, ctx: &Context<Self>) -> bool {
+        !ctx.props().scratch && !ctx.props().read_only && self.exam.is_none() && self.delayed_feedback.is_none()
+    }
+
+    /// A blank proof for the scratchpad, as XML, written in this proof's
+    /// syntax, so lines read the same when they're sent here
+    fn scratchpad_data(&self) -> Vec<u8> {
+        let (prf, _) = new_empty_proof();
+        let metadata = ProofMetaData { author: Some("ARIS-YEW-UI".into()), operators: self.operators.clone(), logic_mode: self.logic_mode, require_full_parens: self.require_full_parens, assumption_policy: self.assumption_policy, scoping_policy: self.scoping_policy, ..Default::default() };
+        let mut data = vec![];
+        xml_interop::xml_from_proof_and_metadata_with_hash(&prf, &metadata, &mut data).expect("xml_from_proof_and_metadata failed");
+        data
+    }
+
+    /// Renders the scratchpad beside the proof: a small proof of its own for
+    /// trying things out, whose lines' context menus send them to this proof
+    fn render_scratchpad(&self, ctx: &Context<Self>) -> Html {
+        let scratchpad = match &self.scratchpad {
+            Some(scratchpad) if self.has_scratchpad(ctx) => scratchpad,
+            _ => return html! {},
+        };
+        // Keys typed in the scratch proof are its own, rather than shortcuts
+        // of the selected line here
+        let onkeydown = Callback::from(|e: KeyboardEvent| e.stop_propagation());
+        html! {
+            <div class="card m-2 flex-shrink-0" style="width: 40%" hidden={ !self.show_scratchpad } { onkeydown }>
+                <div class="card-body">
+                    <div class="d-flex align-items-center">
+                        <h6 class="card-title"> { "Scratchpad" } </h6>
+                        <button type="button" class="close ml-auto" aria-label="Close" onclick={ ctx.link().callback(|_| ProofWidgetMsg::ToggleScratchpad) }>
+                            <span aria-hidden="true"> { '\u{d7}' } </span>
+                        </button>
+                    </div>
+                    <p class="small text-muted"> { "Try things out here without changing the proof. Right-click a line to send it to the proof, where it goes after the selected line." } </p>
+                    <ProofWidget verbose=false data={ Some(scratchpad.data.clone()) } oncreate={ Callback::noop() } scratch=true ontransplant={ Some(scratchpad.ontransplant.clone()) } />
+                </div>
+            </div>
+        }
+    }
+
+    /// Renders a panel listing the constants of the proof, with the line each
+    /// is introduced on and the subproof, if any, that ∃ Elimination or
+    /// ∀ Introduction confines it to. Clicking a line number selects the line.
+    fn render_constants_panel(&self, ctx: &Context<Self>) -> Html {
+        if !self.show_constants {
+            return html! {};
+        }
+        let line_info = self.pud.line_info(&self.prf);
+        let rows = constants::constants(&self.prf)
+            .into_iter()
+            .filter_map(|constant| {
+                let line = line_info.line_of(&constant.introduced)?;
+                let scope = match &constant.scope {
+                    Some(scope) => match (line_info.line_range_of_subproof(&scope.subproof), line_info.line_of(&scope.step)) {
+                        (Some(range), Some(step)) => format!("{} in lines {}, used by line {}", scope.kind.description(), line_info::format_range(range), step),
+                        _ => scope.kind.description().to_string(),
+                    },
+                    None => "Whole proof".to_string(),
+                };
+                let r = constant.introduced;
+                let onclick = ctx.link().batch_callback(move |_| vec![ProofWidgetMsg::LineAction(LineActionKind::Select, r), ProofWidgetMsg::FlashLines(vec![r])]);
+                Some(html! {
+                    <tr>
+                        <td> <code> { constant.name } </code> </td>
+                        <td> <button type="button" class="btn btn-link btn-sm p-0" { onclick }> { line } </button> </td>
+                        <td> { scope } </td>
+                    </tr>
+                })
+            })
+            .collect::<Vec<Html>>();
+        let content = if rows.is_empty() {
+            html! { <span class="text-muted"> { "The proof has no constants" } </span> }
+        } else {
+            html! {
+                <table class="table table-sm mb-0">
+                    <thead>
+                        <tr> <th> { "Constant" } </th> <th> { "Introduced" } </th> <th> { "Scope" } </th> </tr>
+                    </thead>
+                    <tbody> { for rows } </tbody>
+                </table>
+            }
+        };
+        html! {
+            <div class="card m-2">
+                <div class="card-body">
+                    <div class="d-flex align-items-center">
+                        <h6 class="card-title"> { "Constants" } </h6>
+                        <button type="button" class="close ml-auto" aria-label="Close" onclick={ ctx.link().callback(|_| ProofWidgetMsg::ToggleConstants) }>
+                            <span aria-hidden="true"> { '\u{d7}' } </span>
+                        </button>
+                    </div>
+                    { content }
+                </div>
+            </div>
+        }
+    }
+
+    /// Renders the entire proof structure as a hierarchical table.
+    /// Subproofs are displayed indented, with dependency management and line actions integrated.
+    fn render_proof(&self, prf: &<P as Proof>::Subproof, sref: Option<<P as Proof>::SubproofReference>, line: &mut usize, depth: &mut usize) -> Html {
+        // output has a bool tag to prune subproof spacers with, because VNode's PartialEq doesn't do the right thing
+        let mut output: Vec<(Html, bool)> = Vec::new();
+        // A subproof starts with a header marking its first line as an
+        // assumption, rather than a premise of the whole proof
+        if let Some(sr) = sref {
+            let mut header_lines = box_chars::VERT.to_string().repeat(*depth);
+            header_lines.push(box_chars::DOWN_RIGHT);
+            // With discharges shown, the header names the step discharging
+            // the assumption, in the step's color
+            let discharge = self.preferences.show_discharges.then(|| self.discharges().get(&sr).copied()).flatten();
+            let discharge = discharge.and_then(|(jr, color)| Some((self.pud.line_info(&self.prf).line_of(&Coproduct::inject(jr))?, color)));
+            let (class, label) = match discharge {
+                Some((line, color)) => (Some(format!("discharge-{color}")), format!("Assumption, discharged on line {line}")),
+                None => (None, "Assumption".to_string()),
+            };
+            output.push((
+                html! {
+                    <tr class={ classes!("subproof-header", class) }>
+                        <td></td>
+                        <td> <span class="indent"> { header_lines } </span> <span class="subproof-label"> { label } </span> </td>
+                    </tr>
+                },
+                false,
+            ));
+        }
+        for prem in prf.premises().iter() {
+            output.push((self.render_row(*line, *depth, Coproduct::inject(*prem), box_chars::VERT), false));
+            *line += 1;
+        }
+        let dep_checkbox = match sref {
+            Some(sr) => row::dependency_button(String::new(), self.citing_line(Coproduct::inject(sr)), Coproduct::inject(sr), &self.onmsg),
+            None => yew::virtual_dom::VNode::from(yew::virtual_dom::VList::new()),
+        };
+        let mut spacer = yew::virtual_dom::VList::new();
+        spacer.add_child(html! { <td>{ dep_checkbox }</td> });
+        //spacer.add_child(html! { <td style="background-color:black"></td> });
+        let mut spacer_lines = String::new();
+        for _ in 0..*depth {
+            spacer_lines.push(box_chars::VERT);
+        }
+        spacer_lines += &format!("{}{}", box_chars::VERT_RIGHT, box_chars::HORIZ.to_string().repeat(4));
+        spacer.add_child(html! { <td> <span class="indent"> {spacer_lines} </span> </td> });
+
+        let spacer = html! { <tr> { spacer } </tr> };
+
+        output.push((spacer, false));
+        let prf_lines = prf.lines();
+        for (i, lineref) in prf_lines.iter().enumerate() {
+            use Coproduct::{Inl, Inr};
+            let edge_decoration = if i == prf_lines.len() - 1 { box_chars::UP_RIGHT } else { box_chars::VERT };
             match lineref {
                 Inl(r) => {
-                    output.push((self.render_proof_line(ctx, *line, *depth, Coproduct::inject(*r), &edge_decoration), false));
+                    output.push((self.render_row(*line, *depth, Coproduct::inject(*r), edge_decoration), false));
                     *line += 1;
                 }
                 Inr(Inl(sr)) => {
                     *depth += 1;
                     //output.push(row_spacer.clone());
-                    output.push((self.render_proof(ctx, &prf.lookup_subproof(sr).unwrap(), Some(*sr), line, depth), false));
+                    output.push((self.render_proof(&prf.lookup_subproof(sr).unwrap(), Some(*sr), line, depth), false));
                     //output.push(row_spacer.clone());
                     *depth -= 1;
                 }
@@ -513,107 +1867,947 @@ impl ProofWidget {
         let output: Vec<Html> = output.into_iter().map(|(x, _)| x).collect();
         let output = yew::virtual_dom::VList::with_children(output, None);
         if *depth == 0 {
-            html! { <table>{ output }</table> }
+            let density = format!("density-{}", self.preferences.density.as_str());
+            let style = format!("--formula-font-size: {}px; --indent-size: {}px", self.preferences.font_size, self.preferences.indent_size);
+            html! { <table class={ density } { style }>{ output }</table> }
         } else {
             yew::virtual_dom::VNode::from(output)
         }
     }
 
-    /// Select the line referenced in `line_ref`. Also, set up a listener for
-    /// line action keyboard shortcuts
-    fn select_line(&mut self, ctx: &Context<Self>, line_ref: PjRef<P>) {
-        let callback = ctx.link().callback(ProofWidgetMsg::Keypress);
+    /// Renders the search bar, if it's open. Enter and Shift-Enter go to the
+    /// next and previous matches, and Escape closes the bar. Below it, the
+    /// replacement field previews replacing the searched term or formula
+    /// throughout the proof.
+    fn render_search_bar(&self, ctx: &Context<Self>) -> Html {
+        let search = match &self.search {
+            Some(search) => search,
+            None => return html! {},
+        };
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            ProofWidgetMsg::SearchChanged(input.value())
+        });
+        let onkeydown = ctx.link().batch_callback(|e: KeyboardEvent| {
+            // Keep the line action shortcuts from acting on the selected line
+            // while typing a search
+            e.stop_propagation();
+            match e.key().as_str() {
+                "Enter" => Some(ProofWidgetMsg::SearchStep { forward: !e.shift_key() }),
+                "Escape" => Some(ProofWidgetMsg::CloseSearch),
+                "f" if e.ctrl_key() => {
+                    e.prevent_default();
+                    Some(ProofWidgetMsg::OpenSearch)
+                }
+                _ => None,
+            }
+        });
+
+        let matches = self.search_matches();
+        let status = if search.query.trim().is_empty() {
+            String::new()
+        } else if matches.is_empty() {
+            "No matches".to_string()
+        } else {
+            match self.selected_line.as_ref().and_then(|selected_line| matches.iter().position(|r| *r == selected_line.line_ref)) {
+                Some(i) => format!("{} of {}", i + 1, matches.len()),
+                None => format!("{} matches", matches.len()),
+            }
+        };
+
+        let search_bar = html! {
+            <div class="input-group m-2 proof-search">
+                <input
+                    ref={ search.input_ref.clone() }
+                    type="search"
+                    class="form-control"
+                    placeholder="Find text, a rule, or a pattern like _ → B"
+                    value={ search.query.clone() }
+                    { oninput }
+                    { onkeydown } />
+                <div class="input-group-append">
+                    <span class="input-group-text"> { status } </span>
+                    <button type="button" class="btn btn-outline-secondary" title="Previous match (Shift-Enter)" onclick={ ctx.link().callback(|_| ProofWidgetMsg::SearchStep { forward: false }) }> { "\u{2191}" } </button>
+                    <button type="button" class="btn btn-outline-secondary" title="Next match (Enter)" onclick={ ctx.link().callback(|_| ProofWidgetMsg::SearchStep { forward: true }) }> { "\u{2193}" } </button>
+                    <button type="button" class={ if search.replacement.is_some() { "btn btn-secondary" } else { "btn btn-outline-secondary" } } title="Find and replace" onclick={ ctx.link().callback(|_| ProofWidgetMsg::ToggleReplace) }> { "Replace" } </button>
+                    <button type="button" class="btn btn-outline-secondary" title="Close (Escape)" onclick={ ctx.link().callback(|_| ProofWidgetMsg::CloseSearch) }> { '×' } </button>
+                </div>
+            </div>
+        };
+
+        html! {
+            <>
+                { search_bar }
+                { self.render_replace_panel(ctx) }
+            </>
+        }
+    }
+
+    /// Renders the replacement field of the search bar, with a preview of the
+    /// change to every line that replacing would affect
+    fn render_replace_panel(&self, ctx: &Context<Self>) -> Html {
+        let (search, replacement) = match &self.search {
+            Some(search @ SearchState { replacement: Some(replacement), .. }) => (search, replacement),
+            _ => return html! {},
+        };
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            ProofWidgetMsg::ReplacementChanged(input.value())
+        });
+        let onkeydown = ctx.link().batch_callback(|e: KeyboardEvent| {
+            e.stop_propagation();
+            match e.key().as_str() {
+                "Escape" => Some(ProofWidgetMsg::CloseSearch),
+                _ => None,
+            }
+        });
+
+        let preview = self.replacement_preview();
+        let preview_html = if search.pattern.is_none() {
+            html! { <p class="text-muted"> { "Search for a term or formula to replace it" } </p> }
+        } else if aris::parser::parse(replacement).is_none() {
+            html! { <p class="text-muted"> { "Enter a term or formula to replace it with" } </p> }
+        } else if preview.is_empty() {
+            html! { <p class="text-muted"> { "No lines would change" } </p> }
+        } else {
+            let rows = preview
+                .iter()
+                .map(|(r, replaced)| {
+                    let line = self.pud.line_info(&self.prf).line_of(r).unwrap_or_default();
+                    let old = self.pud.ref_to_input.get(r).cloned().unwrap_or_default();
+                    html! {
+                        <tr>
+                            <td> { line } </td>
+                            <td> <del class="text-danger"> { old } </del> </td>
+                            <td> { '→' } </td>
+                            <td> <ins class="text-success"> { replaced.to_string() } </ins> </td>
+                        </tr>
+                    }
+                })
+                .collect::<Html>();
+            html! {
+                <table class="table table-sm">
+                    <tbody> { rows } </tbody>
+                </table>
+            }
+        };
+
+        html! {
+            <div class="m-2 proof-search">
+                <div class="input-group mb-2">
+                    <input type="text" class="form-control" placeholder="Replace with" value={ replacement.clone() } { oninput } { onkeydown } />
+                    <div class="input-group-append">
+                        <button type="button" class="btn btn-outline-primary" disabled={ preview.is_empty() } onclick={ ctx.link().callback(|_| ProofWidgetMsg::ReplaceAll) }> { "Replace all" } </button>
+                    </div>
+                </div>
+                { preview_html }
+            </div>
+        }
+    }
+
+    /// Renders the dialog for adding premises from text, if it's open. Each
+    /// line of the text is parsed as a formula, and Ctrl-Enter adds them.
+    fn render_premise_import_dialog(&self, ctx: &Context<Self>) -> Html {
+        let premise_import = match &self.premise_import {
+            Some(premise_import) => premise_import,
+            None => return html! {},
+        };
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            let textarea: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+            ProofWidgetMsg::PremiseImportChanged(textarea.value())
+        });
+        let onkeydown = ctx.link().batch_callback(|e: KeyboardEvent| {
+            // Keep the line shortcuts from acting on the selected line while
+            // typing premises
+            e.stop_propagation();
+            match e.key().as_str() {
+                "Enter" if e.ctrl_key() => Some(ProofWidgetMsg::ImportPremises),
+                "Escape" => Some(ProofWidgetMsg::ClosePremiseImport),
+                _ => None,
+            }
+        });
+        let errors = if premise_import.errors.is_empty() {
+            html! {}
+        } else {
+            html! {
+                <div class="alert alert-warning mt-2 mb-0" role="alert">
+                    <p class="mb-1"> { "These lines couldn't be parsed, so they were left here to fix:" } </p>
+                    <ul class="mb-0">
+                        { for premise_import.errors.iter().map(|error| html! { <li> { error } </li> }) }
+                    </ul>
+                </div>
+            }
+        };
+        let close = ctx.link().callback(|_| ProofWidgetMsg::ClosePremiseImport);
+
+        html! {
+            <>
+                <div class="modal-backdrop show" onclick={ close.clone() }></div>
+                <div class="modal d-block" tabindex="-1" role="dialog" aria-labelledby={ format!("{}-premise-import-label", self.id) }>
+                    <div class="modal-dialog" role="document">
+                        <div class="modal-content">
+                            <div class="modal-header">
+                                <h5 class="modal-title" id={ format!("{}-premise-import-label", self.id) }> { "Add premises from text" } </h5>
+                                <button type="button" class="close" aria-label="Close" onclick={ close.clone() }>
+                                    <span aria-hidden="true"> { '×' } </span>
+                                </button>
+                            </div>
+                            <div class="modal-body">
+                                <p> { "Enter one formula per line. They're added as premises in order, after the existing ones." } </p>
+                                <textarea
+                                    ref={ premise_import.textarea_ref.clone() }
+                                    class="form-control text-monospace"
+                                    rows="10"
+                                    placeholder={ "P → Q\nP ∨ R\n¬R" }
+                                    value={ premise_import.text.clone() }
+                                    { oninput }
+                                    { onkeydown } />
+                                { errors }
+                            </div>
+                            <div class="modal-footer">
+                                <button type="button" class="btn btn-secondary" onclick={ close }> { "Cancel" } </button>
+                                <button type="button" class="btn btn-primary" title="Add premises (Ctrl-Enter)" onclick={ ctx.link().callback(|_| ProofWidgetMsg::ImportPremises) }> { "Add premises" } </button>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            </>
+        }
+    }
+
+    /// Add each line of the premise import dialog's text as a premise, after
+    /// the existing top-level premises. Blank lines are skipped, and lines
+    /// that don't parse are left in the dialog with an error message each,
+    /// closing it only if every line was added. Once premises are added, the
+    /// blank premise of a new proof is no longer needed, so blank top-level
+    /// premises are removed.
+    fn import_premises(&mut self) {
+        use Coproduct::Inl;
+        let premise_import = match &mut self.premise_import {
+            Some(premise_import) => premise_import,
+            None => return,
+        };
+        let mut added = vec![];
+        let mut failed = vec![];
+        premise_import.errors.clear();
+        for (i, line) in premise_import.text.lines().enumerate() {
+            let line = aris::macros::expand(line.trim());
+            if line.is_empty() {
+                continue;
+            }
+            match aris::parser::parse(&line) {
+                Some(expr) => added.push((line, expr)),
+                None => {
+                    premise_import.errors.push(format!("Line {}: {}", i + 1, line));
+                    failed.push(line);
+                }
+            }
+        }
+        premise_import.text = failed.join("\n");
+        if failed.is_empty() {
+            self.premise_import = None;
+        }
+        if added.is_empty() {
+            return;
+        }
+
+        self.flush_pending_parses();
+        let blank_premises = self.prf.premises().into_iter().filter(|pr| self.pud.ref_to_input.get(&Inl(*pr)).map(|input| input.trim().is_empty()).unwrap_or(false)).collect::<Vec<_>>();
+        for (line, expr) in added {
+            let pr = self.prf.add_premise(expr);
+            self.pud.ref_to_input.insert(Inl(pr), line);
+        }
+        for pr in blank_premises {
+            let line_ref = Inl(pr);
+            if self.selected_line.as_ref().map(|selected_line| selected_line.line_ref == line_ref).unwrap_or(false) {
+                self.selected_line = None;
+            }
+            self.pud.ref_to_input.remove(&line_ref);
+            self.pud.ref_to_caret.remove(&line_ref);
+            self.prf.remove_line(&line_ref);
+        }
+    }
+
+    /// All lines of the proof, in order
+    fn lines_in_order(&self) -> Vec<PjRef<P>> {
+        let mut lines = self.pud.line_info(&self.prf).lines.iter().filter(|(r, _)| self.prf.lookup_pj(r).is_some()).map(|(r, (line, _))| (*line, *r)).collect::<Vec<_>>();
+        lines.sort_by_key(|(line, _)| *line);
+        lines.into_iter().map(|(_, r)| r).collect()
+    }
+
+    /// The lines that replacing the searched term or formula would change, in
+    /// order, along with their new formulas. Lines that don't currently parse
+    /// are left alone, rather than replacing in their last good parse.
+    fn replacement_preview(&self) -> Vec<(PjRef<P>, Expr)> {
+        let (target, replacement) = match &self.search {
+            Some(SearchState { pattern: Some(target), replacement: Some(replacement), .. }) => match aris::parser::parse(replacement) {
+                Some(replacement) => (target, replacement),
+                None => return vec![],
+            },
+            _ => return vec![],
+        };
+        self.lines_in_order()
+            .into_iter()
+            .filter_map(|r| {
+                aris::parser::parse(self.pud.ref_to_input.get(&r)?)?;
+                let expr = self.prf.lookup_expr(&r)?;
+                let replaced = subst_expr(expr.clone(), target, replacement.clone());
+                (replaced != expr).then_some((r, replaced))
+            })
+            .collect()
+    }
+
+    /// Whether a line matches the open search: its text or the name of its
+    /// rule contains the query, or the query is a pattern occurring in the
+    /// line's formula, as in `aris::rewrite_rules::find_pattern()`
+    fn matches_search(&self, proofref: PjRef<P>) -> bool {
+        use Coproduct::{Inl, Inr};
+        let search = match &self.search {
+            Some(search) if !search.query.trim().is_empty() => search,
+            _ => return false,
+        };
+        let query = search.query.trim().to_lowercase();
+        let text_matches = self.pud.ref_to_input.get(&proofref).map(|input| input.to_lowercase().contains(&query)).unwrap_or(false);
+        let rule_matches = match proofref {
+            Inr(Inl(jref)) => self.prf.lookup_justification_or_die(&jref).map(|just| [just.1.get_name(), rule_names::display_name(just.1)].iter().any(|name| name.to_lowercase().contains(&query))).unwrap_or(false),
+            _ => false,
+        };
+        let pattern_matches = || match (&search.pattern, self.prf.lookup_expr(&proofref)) {
+            (Some(pattern), Some(expr)) => !find_pattern(pattern, &expr).is_empty(),
+            _ => false,
+        };
+        text_matches || rule_matches || pattern_matches()
+    }
+
+    /// All lines matching the open search, in order
+    fn search_matches(&self) -> Vec<PjRef<P>> {
+        self.lines_in_order().into_iter().filter(|r| self.matches_search(*r)).collect()
+    }
+
+    /// Select the next search match after the selected line, or the previous
+    /// one before it, wrapping around at the ends of the proof. If
+    /// `include_selected` is set, the selected line itself counts as the next
+    /// match, which keeps the selection in place while refining a query.
+    fn step_search(&mut self, forward: bool, include_selected: bool) {
+        let matches = self.search_matches();
+        let line_of = |r: &PjRef<P>| self.pud.line_info(&self.prf).line_of(r);
+        let selected = self.selected_line.as_ref().and_then(|selected_line| line_of(&selected_line.line_ref));
+        let next = match selected {
+            Some(selected) if forward => matches.iter().find(|r| line_of(r).map(|line| line > selected || (include_selected && line == selected)).unwrap_or(false)).or_else(|| matches.first()),
+            Some(selected) => matches.iter().rev().find(|r| line_of(r).map(|line| line < selected || (include_selected && line == selected)).unwrap_or(false)).or_else(|| matches.last()),
+            None if forward => matches.first(),
+            None => matches.last(),
+        };
+        if let Some(&next) = next {
+            self.select_line(next);
+            self.scroll_to = Some(next);
+        }
+    }
+
+    /// Commands for the command palette: the valid line actions and rules of
+    /// the selected line, then the commands for the whole proof
+    fn commands(&self, ctx: &Context<Self>) -> Vec<Command> {
+        use Coproduct::{Inl, Inr};
+        let mut commands = vec![];
+        if let Some(selected_line) = &self.selected_line {
+            let line_ref = selected_line.line_ref;
+            for action in actions::valid_actions(&self.prf, line_ref) {
+                let lak = action.line_action_kind.clone();
+                let command = Command::new("Line", action.description, ctx.link().callback(move |()| ProofWidgetMsg::LineAction(lak.clone(), line_ref)));
+                commands.push(match action.keyboard_shortcut {
+                    Some(key) => command.with_shortcut(format!("Ctrl-{}", key.to_ascii_uppercase())),
+                    None => command,
+                });
+            }
+            if self.blanks.is_empty() {
+                commands.push(Command::new("Line", INSERT_STEPS_LABEL, self.insert_steps_callback(ctx, line_ref)));
+                if !self.conjuncts_of(line_ref).is_empty() {
+                    commands.push(Command::new("Line", SPLIT_CONJUNCTION_LABEL, ctx.link().callback(move |()| ProofWidgetMsg::LineAction(LineActionKind::SplitConjunction, line_ref))));
+                }
+                if self.prf.contradiction_after_line(&line_ref).is_some() {
+                    commands.push(Command::new("Line", CONCLUDE_CONTRADICTION_LABEL, ctx.link().callback(move |()| ProofWidgetMsg::LineAction(LineActionKind::ConcludeContradiction, line_ref))));
+                }
+                if !self.lines_to_reiterate(line_ref).is_empty() {
+                    commands.push(Command::new("Line", REITERATE_CITATIONS_LABEL, ctx.link().callback(move |()| ProofWidgetMsg::LineAction(LineActionKind::ReiterateCitations, line_ref))));
+                }
+                commands.push(Command::new("Line", INSERT_GAP_LABEL, ctx.link().callback(move |()| ProofWidgetMsg::LineAction(LineActionKind::InsertGap, line_ref))));
+            }
+            if let Inr(Inl(_)) = line_ref {
+                for rule in RuleClassification::iter().flat_map(|class| class.rules()).chain([RuleM::Gap]) {
+                    commands.push(Command::new("Rule", format!("Set rule to {}", rule_names::display_name(rule)), ctx.link().callback(move |()| ProofWidgetMsg::LineAction(LineActionKind::SetRule { rule }, line_ref))));
+                }
+            }
+        }
+        if self.verification() == Verification::OnDemand {
+            commands.push(Command::new("Proof", "Check proof", ctx.link().callback(|()| ProofWidgetMsg::CheckProof)));
+        }
+        commands.push(Command::new("Proof", if self.show_error_list { "Hide error list" } else { "Show error list" }, ctx.link().callback(|()| ProofWidgetMsg::ToggleErrorList)));
+        commands.push(Command::new("Proof", if self.show_constants { "Hide constants" } else { "Show constants" }, ctx.link().callback(|()| ProofWidgetMsg::ToggleConstants)));
+        if self.has_scratchpad(ctx) {
+            commands.push(Command::new("Proof", if self.show_scratchpad { "Hide scratchpad" } else { "Show scratchpad" }, ctx.link().callback(|()| ProofWidgetMsg::ToggleScratchpad)));
+        }
+        commands.push(Command::new("Proof", "Add premises from text\u{2026}", ctx.link().callback(|()| ProofWidgetMsg::OpenPremiseImport)));
+        commands.push(Command::new("Proof", "Find in proof", ctx.link().callback(|()| ProofWidgetMsg::OpenSearch)).with_shortcut("Ctrl-F"));
+        commands.push(Command::new("Proof", "Reformat proof", ctx.link().callback(|()| ProofWidgetMsg::ReformatProof)));
+        let replace_open = matches!(self.search, Some(SearchState { replacement: Some(_), .. }));
+        if !replace_open {
+            commands.push(Command::new("Proof", "Find and replace", ctx.link().batch_callback(|()| vec![ProofWidgetMsg::OpenSearch, ProofWidgetMsg::ToggleReplace])));
+        }
+        commands
+    }
+
+    /// Keep the selected line selected if it's still in the proof after lines
+    /// were removed, and otherwise select the nearest line that's left,
+    /// preferring the one after it, out of `lines_before`, the lines in order
+    /// from before they were removed. The selected line could've been deep
+    /// inside a deleted subproof, so the lines after the subproof come next.
+    fn reselect_after_removal(&mut self, lines_before: &[PjRef<P>]) {
+        let selected = match &self.selected_line {
+            Some(selected_line) => selected_line.line_ref,
+            None => return,
+        };
+        let remaining = self.lines_in_order().into_iter().collect::<HashSet<_>>();
+        if remaining.contains(&selected) {
+            return;
+        }
+        let nearest = lines_before.iter().position(|r| *r == selected).and_then(|position| lines_before[position + 1..].iter().chain(lines_before[..position].iter().rev()).find(|r| remaining.contains(r)).copied());
+        match nearest {
+            Some(line_ref) => {
+                self.select_line(line_ref);
+                self.scroll_to = Some(line_ref);
+            }
+            None => {
+                self.selected_line = None;
+                self.selected_subformula = None;
+            }
+        }
+    }
+
+    /// Insert a new line or subproof relative to the line `orig_ref`,
+    /// returning the inserted line, or the assumption of the inserted subproof
+    fn insert_line(&mut self, what: ProofItemKind, after: bool, relative_to: ProofItemKind, orig_ref: PjRef<P>) -> Option<PjRef<P>> {
+        use Coproduct::{Inl, Inr};
+        let to_select;
+        let orig_ref = pj_to_pjs::<P>(orig_ref);
+        let parent = self.prf.parent_of_line(&orig_ref);
+        let insertion_point: PjsRef<P> = match relative_to {
+            ProofItemKind::Premise | ProofItemKind::Just => orig_ref,
+            ProofItemKind::Subproof => match parent {
+                Some(parent) => Coproduct::inject(parent),
+                None => return None,
+            },
+        };
+        match what {
+            ProofItemKind::Premise => match insertion_point {
+                Inl(pr) => {
+                    // Insert premise relative to premise
+                    to_select = Inl(self.prf.add_premise_relative(new_empty_premise(), &pr, after));
+                }
+                Inr(Inl(_)) | Inr(Inr(Inl(_))) => {
+                    // Insert premise relative to line or subproof
+                    to_select = Inl(self.prf.add_premise(new_empty_premise()));
+                }
+                Inr(Inr(Inr(void))) => match void {},
+            },
+            ProofItemKind::Just => match insertion_point {
+                Inl(_) => {
+                    // Insert justification relative to premise
+
+                    // Add justification to enclosing subproof of premise, if it exists
+                    let just_ref = parent.and_then(|parent| self.prf.with_mut_subproof(&parent, |parent| parent.prepend_step(new_empty_step())));
+
+                    // If the insertion point is not in a subproof, add justification to the top-level proof
+                    match just_ref {
+                        Some(just_ref) => to_select = Coproduct::inject(just_ref),
+                        None => to_select = Coproduct::inject(self.prf.prepend_step(new_empty_step())),
+                    }
+                }
+                Inr(Inl(jr)) => {
+                    // Insert justification relative to justification
+                    let jsr = Coproduct::inject(jr);
+                    to_select = Inr(Inl(self.prf.add_step_relative(new_empty_step(), &jsr, after)));
+                }
+                Inr(Inr(Inl(sr))) => {
+                    // Insert justification relative to subproof
+                    let jsr = Coproduct::inject(sr);
+                    to_select = Inr(Inl(self.prf.add_step_relative(new_empty_step(), &jsr, after)));
+                }
+                Inr(Inr(Inr(void))) => match void {},
+            },
+            ProofItemKind::Subproof => {
+                // Convert insertion point from `PjsRef` to `JsRef`,
+                // returning silently on failure
+                let insertion_point: JsRef<P> = match insertion_point.subset() {
+                    Ok(insertion_point) => insertion_point,
+                    // Insertion point is a premise, return silently
+                    Err(_) => return None,
+                };
+                let sr = self.prf.add_subproof_relative(&insertion_point, after);
+                let (assumption, step) = self
+                    .prf
+                    .with_mut_subproof(&sr, |sub| {
+                        let assumption = Inl(sub.add_premise(new_empty_premise()));
+                        (assumption, sub.prepend_step(new_empty_step()))
+                    })
+                    .expect("Subproof doesn't exist after creating it");
+                self.fill_in_new_step(step);
+                return Some(assumption);
+            }
+        }
+        if let Inr(Inl(jr)) = to_select {
+            self.fill_in_new_step(jr);
+        }
+        Some(to_select)
+    }
+
+    /// Fill in a step just inserted with `new_empty_step()` from the line
+    /// above it, as the user's `NewStep` preference asks for
+    fn fill_in_new_step(&mut self, jr: <P as Proof>::JustificationReference) {
+        use Coproduct::{Inl, Inr};
+        let r = Coproduct::inject(jr);
+        let lines = self.lines_in_order();
+        let above = match lines.iter().position(|line| *line == r) {
+            Some(i) if i > 0 => lines[i - 1],
+            _ => return,
+        };
+        match self.preferences.new_step {
+            NewStep::Empty => {}
+            NewStep::Reiteration => {
+                // The line above may end a subproof the step is outside of
+                let mut deps = HashSet::new();
+                let mut sdeps = HashSet::new();
+                self.prf.possible_deps_for_line(&r, &mut deps, &mut sdeps);
+                if deps.contains(&above) {
+                    self.prf.with_mut_step(&jr, |step| {
+                        step.1 = RuleM::Reiteration;
+                        step.2 = vec![above];
+                    });
+                    let input = self.pud.ref_to_input.get(&above).cloned().unwrap_or_default();
+                    self.set_line_input(r, input);
+                }
+            }
+            NewStep::SameRule => match above {
+                Inr(Inl(above)) => {
+                    if let Some(Justification(_, rule, _, _)) = self.prf.lookup_step(&above) {
+                        self.prf.with_mut_step(&jr, |step| step.1 = rule);
+                    }
+                }
+                Inl(_) | Inr(Inr(_)) => {}
+            },
+        }
+    }
+
+    /// Select the line referenced in `line_ref`, which keyboard shortcuts then
+    /// act on
+    fn select_line(&mut self, line_ref: PjRef<P>) {
+        if self.selected_line.as_ref().map(|line| line.line_ref) != Some(line_ref) {
+            self.selected_subformula = None;
+        }
+        self.selected_line = Some(SelectedLine { line_ref });
+    }
+
+    /// Move on from the line `r` after Enter was pressed in it, once it's
+    /// correct: to the next line, if it's a blank line of the same kind in the
+    /// same subproof, or else to a new line inserted after it
+    fn advance_from(&mut self, r: PjRef<P>) -> Vec<ProofWidgetMsg> {
+        use Coproduct::{Inl, Inr};
+        // In Vim's normal mode, Enter starts typing instead
+        if !self.preferences.advance_on_enter || (self.preferences.keymap == Keymap::Vim && self.key_state.mode == Mode::Normal) {
+            return vec![];
+        }
+        self.flush_pending_parses();
+        let ready = match self.verification() {
+            Verification::Live => self.line_status(r).is_correct(),
+            // Lines aren't checked until asked to, so it only has to parse
+            Verification::OnDemand => self.pud.ref_to_input.get(&r).and_then(|input| aris::parser::parse(input)).is_some(),
+        };
+        if !ready {
+            return vec![];
+        }
+
+        // An assumption is followed by the steps of its subproof
+        let kind_of = |line: PjRef<P>| match line {
+            Inl(pr) if !self.prf.is_assumption(&pr) => ProofItemKind::Premise,
+            Inl(_) | Inr(_) => ProofItemKind::Just,
+        };
+        let what = kind_of(r);
+        let lines = self.lines_in_order();
+        let next = lines.iter().position(|line| *line == r).and_then(|i| lines.get(i + 1)).copied();
+        let is_blank = |line: PjRef<P>| self.pud.ref_to_input.get(&line).map(|input| input.trim().is_empty()).unwrap_or(true);
+        let parent_of = |line: PjRef<P>| self.prf.parent_of_line(&pj_to_pjs::<P>(line));
+        match next {
+            Some(next) if kind_of(next) == what && is_blank(next) && parent_of(next) == parent_of(r) => {
+                self.scroll_to = Some(next);
+                vec![ProofWidgetMsg::LineAction(LineActionKind::Select, next)]
+            }
+            _ => {
+                let lak = actions::valid_actions(&self.prf, r).map(|action_info| action_info.line_action_kind.clone()).find(|lak| matches!(*lak, LineActionKind::Insert { what: w, after: true, relative_to, .. } if w == what && relative_to != ProofItemKind::Subproof));
+                lak.map(|lak| ProofWidgetMsg::LineAction(lak, r)).into_iter().collect()
+            }
+        }
+    }
+
+    /// Convert a key press into the `ProofWidgetMsg`s that perform its
+    /// command in the user's keymap, as translated by `KeyState::translate()`
+    ///
+    /// NOTE: This overrides the behavior of built-in web browser shortcuts,
+    /// such as <kbd>Ctrl-A</kbd> and <kbd>Ctrl-P</kbd>.
+    fn process_key(&mut self, key_event: web_sys::KeyboardEvent) -> Vec<ProofWidgetMsg> {
+        use Coproduct::Inl;
+
+        // Get the selected line, or do nothing if there is none
+        let selected_line = match &self.selected_line {
+            Some(selected_line) => selected_line.line_ref,
+            None => return vec![],
+        };
+
+        // Other than the Ctrl shortcuts, keys only apply while typing in the
+        // selected line, rather than in another field of the proof
+        let line_input_id = self.pud.line_info(&self.prf).line_of(&selected_line).map(|line| format!("{}-line-number-{}", self.id, line));
+        let in_line = key_event.target().and_then(|target| target.dyn_into::<web_sys::Element>().ok()).map(|elem| Some(elem.id()) == line_input_id).unwrap_or(false);
+        if !key_event.ctrl_key() && !in_line {
+            return vec![];
+        }
+
+        let command = match self.key_state.translate(self.preferences.keymap, &key_event.key(), key_event.ctrl_key()) {
+            Some(command) => command,
+            None => return vec![],
+        };
+        // Some keyboard shortcuts (like Ctrl-A, Ctrl-P) conflict with typical
+        // web browser keyboard shortcuts. This overrides their behavior.
+        key_event.prevent_default();
+
+        let lines = self.lines_in_order();
+        let position = lines.iter().position(|r| *r == selected_line);
+        let mut select = |line: Option<&PjRef<P>>| match line {
+            Some(&line) => {
+                self.scroll_to = Some(line);
+                vec![ProofWidgetMsg::LineAction(LineActionKind::Select, line)]
+            }
+            None => vec![],
+        };
+        // Find the valid line action, other than one on the enclosing subproof
+        let line_action = |matches: &dyn Fn(&LineActionKind) -> bool| actions::valid_actions(&self.prf, selected_line).map(|action_info| action_info.line_action_kind.clone()).find(|lak| matches(lak));
+        let is_premise = matches!(selected_line, Inl(_));
+
+        match command {
+            KeyCommand::Shortcut(key) => {
+                // Look up the triggered action
+                let action = actions::valid_actions(&self.prf, selected_line).find(|action_info| action_info.keyboard_shortcut == Some(key));
+                action.map(|action| ProofWidgetMsg::LineAction(action.line_action_kind.clone(), selected_line)).into_iter().collect()
+            }
+            KeyCommand::MoveSelection(offset) => {
+                let target = position.and_then(|position| position.checked_add_signed(offset));
+                select(target.and_then(|target| lines.get(target)))
+            }
+            KeyCommand::FirstLine => select(lines.first()),
+            KeyCommand::LastLine => select(lines.last()),
+            KeyCommand::InsertBelow(count) | KeyCommand::InsertAbove(count) => {
+                let (what, after) = match command {
+                    KeyCommand::InsertAbove(_) if is_premise => (ProofItemKind::Premise, false),
+                    KeyCommand::InsertAbove(_) => (ProofItemKind::Just, false),
+                    _ => (ProofItemKind::Just, true),
+                };
+                let lak = line_action(&|lak| matches!(*lak, LineActionKind::Insert { what: w, after: a, relative_to, .. } if w == what && a == after && relative_to != ProofItemKind::Subproof));
+                match lak {
+                    Some(LineActionKind::Insert { what, after, relative_to, .. }) => {
+                        let lak = LineActionKind::Insert { what, after, relative_to, count };
+                        // Start typing in the new line, like Vim's `o`
+                        self.key_state.mode = Mode::Insert;
+                        vec![ProofWidgetMsg::LineAction(lak, selected_line)]
+                    }
+                    _ => vec![],
+                }
+            }
+            KeyCommand::DeleteLine => {
+                // Deleting moves the selection to the line that takes the
+                // deleted line's place
+                let lak = line_action(&|lak| matches!(*lak, LineActionKind::Delete { what } if what != ProofItemKind::Subproof));
+                lak.map(|lak| ProofWidgetMsg::LineAction(lak, selected_line)).into_iter().collect()
+            }
+            KeyCommand::SetMode(mode) => {
+                self.key_state.mode = mode;
+                vec![]
+            }
+            KeyCommand::Ignore => vec![],
+        }
+    }
+}
+
+/// Determines if the user is allowed to remove a line at `line_ref`.
+/// Premises at the top level can only be removed if there are multiple top-level premises.
+/// Steps can always be removed.
+fn may_remove_line<P: Proof>(prf: &P, line_ref: &PjRef<P>) -> bool {
+    use Coproduct::Inl;
+
+    let is_premise = matches!(prf.lookup_pj(line_ref), Some(Inl(_)));
+
+    let parent = prf.parent_of_line(&pj_to_pjs::<P>(line_ref.clone()));
+
+    if is_premise {
+        match parent {
+            // Can't remove the last assumption of a subproof
+            Some(parent) => prf.lookup_subproof(&parent).is_some_and(|sub| sub.premises().len() > 1),
+            // Can't remove the last top-level premise
+            None => prf.premises().len() > 1,
+        }
+    } else {
+        // Steps can always be removed
+        true
+    }
+}
+
+/// Whether a message edits which lines the proof has, rather than what's in
+/// them, after which `ProofUiData` may hold on to lines that are gone
+fn adds_or_removes_lines(msg: &ProofWidgetMsg) -> bool {
+    matches!(msg, ProofWidgetMsg::LineAction(LineActionKind::Insert { .. } | LineActionKind::Delete { .. } | LineActionKind::ApplyEquivalence { .. } | LineActionKind::Duplicate | LineActionKind::Move { .. } | LineActionKind::SplitConjunction | LineActionKind::ConcludeContradiction | LineActionKind::ReiterateCitations | LineActionKind::InsertGap | LineActionKind::WorkBackward { .. }, _) | ProofWidgetMsg::ImportPremises | ProofWidgetMsg::ImportPastedLines | ProofWidgetMsg::TransplantLines(_))
+}
+
+/// The line right after `line_ref` among the lines directly in `sub`, or right
+/// before it if `after` is false, if it's the same kind of line
+fn adjacent_line<P: Proof, Q: Proof<PremiseReference = <P as Proof>::PremiseReference, JustificationReference = <P as Proof>::JustificationReference>>(sub: &Q, line_ref: PjRef<P>, after: bool) -> Option<PjRef<P>> {
+    use Coproduct::{Inl, Inr};
+    let neighbor = |i: usize| if after { i.checked_add(1) } else { i.checked_sub(1) };
+    match line_ref {
+        Inl(pr) => {
+            let premises = sub.premises();
+            let i = premises.iter().position(|p| *p == pr)?;
+            premises.get(neighbor(i)?).cloned().map(Inl)
+        }
+        Inr(Inl(jr)) => {
+            let lines = sub.lines();
+            let i = lines.iter().position(|l| *l == Inl(jr.clone()))?;
+            match lines.get(neighbor(i)?)? {
+                Inl(neighbor) => Some(Inr(Inl(neighbor.clone()))),
+                Inr(_) => None,
+            }
+        }
+        Inr(Inr(void)) => match void {},
+    }
+}
+
+/// Copy text to the clipboard, silently doing nothing if that isn't allowed
+fn copy_to_clipboard(text: &str) {
+    // `Navigator.clipboard` is one of web-sys's unstable APIs, so look it up
+    // dynamically instead
+    let navigator = web_sys::window().expect_throw("window is undefined").navigator();
+    let clipboard = js_sys::Reflect::get(&navigator, &"clipboard".into()).unwrap_or(wasm_bindgen::JsValue::UNDEFINED);
+    if let Ok(write_text) = js_sys::Reflect::get(&clipboard, &"writeText".into()).and_then(|write_text| write_text.dyn_into::<js_sys::Function>()) {
+        let _ = write_text.call1(&clipboard, &text.into());
+    }
+}
+
+/// Copy lines of a proof to the clipboard, in Aris's clipboard format and as
+/// plain text. Formats other than plain text can only be written while
+/// handling a `copy` event, so this starts one.
+fn copy_fragment(fragment: &ClipboardProof) {
+    let document = web_sys::window().expect_throw("window is undefined").document().expect_throw("document is undefined");
+    let (json, text) = (fragment.to_json(), fragment.to_text());
+    // `copy` is dispatched while `execCommand` runs, so the listener is only
+    // needed until it returns
+    let _listener = EventListener::new(&document, "copy", move |event| {
+        if let Some(data) = event.dyn_ref::<web_sys::ClipboardEvent>().and_then(|event| event.clipboard_data()) {
+            let _ = data.set_data(clipboard::MIME_TYPE, &json);
+            let _ = data.set_data("text/plain", &text);
+            event.prevent_default();
+        }
+    });
+    // `Document.execCommand` is deprecated, and missing from web-sys, but
+    // it's the only way to put a custom format on the clipboard
+    if let Ok(exec_command) = js_sys::Reflect::get(&document, &"execCommand".into()).and_then(|exec_command| exec_command.dyn_into::<js_sys::Function>()) {
+        let _ = exec_command.call1(&document, &"copy".into());
+    }
+}
+
+/// Render an expression with every subformula in its own clickable `<span>`,
+/// for picking where to apply an equivalence. `path` is the position of `expr`
+/// in the line's expression, and `selected` is the picked position, if any.
+fn render_subformula_picker(link: &Scope<ProofWidget>, proofref: PjRef<P>, expr: &Expr, path: &mut Vec<usize>, selected: Option<&[usize]>) -> Html {
+    let mut render_child = |i: usize, child: &Expr| {
+        path.push(i);
+        let html = render_subformula_picker(link, proofref, child, path, selected);
+        path.pop();
+        html
+    };
+    let contents = match expr {
+        Expr::Contra | Expr::Taut | Expr::Var { .. } => html! { <>{ expr.to_string() }</> },
+        Expr::Apply { func, args } => {
+            let func = render_child(0, func);
+            let args = args
+                .iter()
+                .enumerate()
+                .map(|(i, arg)| {
+                    if i == 0 {
+                        render_child(i + 1, arg)
+                    } else {
+                        html! { <>{ ", " }{ render_child(i + 1, arg) }</> }
+                    }
+                })
+                .collect::<Html>();
+            html! { <>{ func }{ "(" }{ args }{ ")" }</> }
+        }
+        Expr::Not { operand } => html! { <>{ "¬" }{ render_child(0, operand) }</> },
+        Expr::Impl { left, right } => html! { <>{ "(" }{ render_child(0, left) }{ " → " }{ render_child(1, right) }{ ")" }</> },
+        Expr::Assoc { op, exprs } => {
+            let exprs = exprs
+                .iter()
+                .enumerate()
+                .map(|(i, e)| {
+                    if i == 0 {
+                        render_child(i, e)
+                    } else {
+                        html! { <>{ format!(" {op} ") }{ render_child(i, e) }</> }
+                    }
+                })
+                .collect::<Html>();
+            html! { <>{ "(" }{ exprs }{ ")" }</> }
+        }
+        Expr::Quant { kind, name, body } => html! { <>{ format!("({kind} {name} ") }{ render_child(0, body) }{ ")" }</> },
+    };
+    let class = if selected == Some(&path[..]) { "subformula bg-info text-white" } else { "subformula" };
+    let this_path = path.clone();
+    // Stop propagation so that only the innermost subformula under the cursor is picked
+    let onclick = link.callback(move |e: MouseEvent| {
+        e.stop_propagation();
+        ProofWidgetMsg::LineAction(LineActionKind::SelectSubformula { path: this_path.clone() }, proofref)
+    });
+    html! { <span class={ class } onclick={ onclick }>{ contents }</span> }
+}
+
+/// Escape text for use in the HTML tooltips of the rules menu, which Bootstrap
+/// renders as markup rather than plain text
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// An entry of the rules menu, without the action picking the rule, which
+/// differs between steps
+enum RuleMenuEntry {
+    Rule { rule: Rule, label: String, title: Option<String> },
+    Submenu { label: String, entries: Vec<RuleMenuEntry> },
+}
+
+/// Entries of the rules menu, with each rule's picture for the page's
+/// `theme`, schema, and description in its tooltip if `describe` is set. The
+/// menu has over a hundred rules, so these are built once and shared by the
+/// menus of every step, rather than built for every step on every render.
+fn rule_menu_entries(describe: bool, theme: &str) -> Vec<RuleMenuEntry> {
+    let equivalence_classes = [RuleClassification::BooleanEquivalence, RuleClassification::ConditionalEquivalence, RuleClassification::BiconditionalEquivalence, RuleClassification::QuantifierEquivalence];
+
+    let misc_inference_classes = [RuleClassification::BooleanInference, RuleClassification::ConditionalInference, RuleClassification::BiconditionalInference, RuleClassification::QuantifierInference];
+
+    let special_rule_names = ["Reiteration", "Resolution", "Truth-Functional Consequence"];
+
+    let images = if theme == "dark" { "proofImages_dark" } else { "proofImages_light" };
+
+    let rule_entry = |rule: Rule| {
+        // Rules added since the pictures were drawn don't have one, which the
+        // empty alt text leaves out
+        let image = format!("<img id='rule-img' src='{}/{}.png' alt=''/><br/>", images, html_escape(&rule.get_name()));
+        let schema = rule.get_schema().map(|schema| format!("<code>{}</code><br/>", html_escape(&schema.to_string()))).unwrap_or_default();
+        let title = describe.then(|| format!("{}{}{}", image, schema, html_escape(&rule.get_description())));
+        RuleMenuEntry::Rule { rule, label: rule_names::display_name(rule), title }
+    };
+
+    let rules_from_class = |class: RuleClassification| RuleMenuEntry::Submenu { label: class.to_string(), entries: class.rules().map(rule_entry).collect() };
 
-        let key_listener = EventListener::new_with_options(&document(), "keydown", EventListenerOptions::enable_prevent_default(), move |event: &Event| {
-            let event = event.dyn_ref::<web_sys::KeyboardEvent>().unwrap_throw().clone();
-            callback.emit(event)
-        });
+    let special_rules = RuleClassification::iter().flat_map(|c| c.rules()).filter(|r| special_rule_names.contains(&r.get_name().as_str())).map(rule_entry);
 
-        self.selected_line = Some(SelectedLine { line_ref, key_listener });
-    }
+    let induction_category = RuleClassification::iter().find(|c| c.to_string() == "Induction").map(rules_from_class);
 
-    /// Convert a keyboard shortcut into a `ProofWidgetMsg` that performs the
-    /// action.
-    ///
-    /// NOTE: This overrides the behavior of built-in web browser shortcuts,
-    /// such as <kbd>Ctrl-A</kbd> and <kbd>Ctrl-P</kbd>.
-    fn process_key_shortcut(&self, key_event: web_sys::KeyboardEvent) -> ProofWidgetMsg {
-        // Get the selected line, or do nothing if there is none
-        let selected_line = match &self.selected_line {
-            Some(selected_line) => selected_line.line_ref,
-            None => return ProofWidgetMsg::Nop,
-        };
-
-        // All keyboard shortcuts have the control key held. Do nothing if the
-        // control key isn't pressed.
-        if !key_event.ctrl_key() {
-            // Change focus on ArrowDown or ArrowUp
-            if key_event.key() == "ArrowDown" || key_event.key() == "ArrowUp" {
-                // Get our current id to find the others.
-                let focused_elem_id = match document().active_element() {
-                    Some(focused_elem_id) => focused_elem_id.id(),
-                    None => return ProofWidgetMsg::Nop,
-                };
-                let up_down = match key_event.key().as_str() {
-                    "ArrowDown" => 1,
-                    "ArrowUp" => -1,
-                    _ => return ProofWidgetMsg::Nop,
-                };
-                let signature = format!("{}{}", self.id, "line-number-");
-                let length = signature.chars().count();
-                // Verify that our selected element is the one we will work with.
-                if focused_elem_id.chars().count() < length {
-                    return ProofWidgetMsg::Nop;
-                }
-                let num = focused_elem_id[length..].parse::<i32>().unwrap() + up_down;
-                //let new_id = "#line-number-".to_owned() + &num.to_string();
-                let _focused_input = match document().get_element_by_id(&format!("{}{}", signature, &num.to_string())) {
-                    Some(_focused_input) => _focused_input.unchecked_into::<HtmlElement>().focus(),
-                    None => return ProofWidgetMsg::Nop,
-                };
-            }
+    let set_theory_category = rules_from_class(RuleClassification::SetTheory);
 
-            return ProofWidgetMsg::Nop;
-        }
+    let temporal_category = rules_from_class(RuleClassification::Temporal);
 
-        // Some keyboard shortcuts (like Ctrl-A, Ctrl-P) conflict with typical
-        // web browser keyboard shortcuts. This overrides their behavior.
-        key_event.prevent_default();
+    let hoare_category = rules_from_class(RuleClassification::Hoare);
 
-        // Look up the triggered action
-        let action = actions::valid_actions(&self.prf, selected_line).find(|action_info| action_info.keyboard_shortcut == key_event.key().chars().next());
+    let misc_inference_submenu = RuleMenuEntry::Submenu { label: "Misc. Inference".into(), entries: misc_inference_classes.iter().map(|&c| rules_from_class(c)).collect() };
 
-        if let Some(action) = action {
-            // Return action message
-            let lak = action.line_action_kind.clone();
-            ProofWidgetMsg::LineAction(lak, selected_line)
-        } else {
-            ProofWidgetMsg::Nop
-        }
-    }
-}
+    let equivalence_submenu = RuleMenuEntry::Submenu { label: "Equivalence".into(), entries: equivalence_classes.iter().map(|&c| rules_from_class(c)).collect() };
 
-/// Determines if the user is allowed to remove a line at `line_ref`.
-/// Premises at the top level can only be removed if there are multiple top-level premises.
-/// Steps can always be removed.
-fn may_remove_line<P: Proof>(prf: &P, line_ref: &PjRef<P>) -> bool {
-    use Coproduct::Inl;
+    let other_menus = RuleClassification::iter().filter(|c| !special_rule_names.contains(&c.to_string().as_str()) && c.to_string() != "Induction" && *c != RuleClassification::SetTheory && *c != RuleClassification::Temporal && *c != RuleClassification::Hoare && !equivalence_classes.contains(c) && !misc_inference_classes.contains(c) && c.to_string() != "Special").map(rules_from_class);
 
-    let is_premise = matches!(prf.lookup_pj(line_ref), Some(Inl(_)));
+    other_menus.chain([misc_inference_submenu, equivalence_submenu]).chain(special_rules).chain(induction_category).chain([set_theory_category, temporal_category, hoare_category, rule_entry(RuleM::Gap)]).collect()
+}
 
-    let in_subproof = prf.parent_of_line(&pj_to_pjs::<P>(line_ref.clone())).is_some();
+/// Entries of the rules menu, along with whether they describe the rules and
+/// the theme of their pictures
+type RuleMenu = (bool, String, Rc<Vec<RuleMenuEntry>>);
 
-    if is_premise {
-        if in_subproof {
-            // Subproof premises can't be removed
-            false
-        } else {
-            // Can't remove the last top-level premise
-            prf.premises().len() > 1
+thread_local! {
+    /// Entries of the rules menu, once one was opened
+    static RULE_MENU: RefCell<Option<RuleMenu>> = const { RefCell::new(None) };
+}
+
+/// Entries of the rules menu, describing the rules if `describe` is set, as
+/// built by `rule_menu_entries()` for the page's current theme. They're built
+/// again only when either changes.
+fn rule_menu(describe: bool) -> Rc<Vec<RuleMenuEntry>> {
+    let theme = theme();
+    RULE_MENU.with(|rule_menu| {
+        let mut rule_menu = rule_menu.borrow_mut();
+        match &*rule_menu {
+            Some((described, shown_theme, entries)) if *described == describe && *shown_theme == theme => entries.clone(),
+            _ => {
+                let entries = Rc::new(rule_menu_entries(describe, &theme));
+                *rule_menu = Some((describe, theme, entries.clone()));
+                entries
+            }
         }
-    } else {
-        // Steps can always be removed
-        true
+    })
+}
+
+/// Items of the rules menu of a step, setting the step's rule when picked
+fn rule_menu_items(entries: &[RuleMenuEntry], onmsg: &Callback<ProofWidgetMsg>, jref: <P as Proof>::JustificationReference) -> Vec<MenuItem> {
+    let pjref = Coproduct::inject(jref);
+    entries
+        .iter()
+        .map(|entry| match entry {
+            RuleMenuEntry::Rule { rule, label, title } => {
+                let rule = *rule;
+                MenuItem::Action { label: label.clone(), title: title.clone(), shortcut: None, onselect: onmsg.reform(move |()| ProofWidgetMsg::LineAction(LineActionKind::SetRule { rule }, pjref)) }
+            }
+            RuleMenuEntry::Submenu { label, entries } => MenuItem::Submenu { label: label.clone(), items: rule_menu_items(entries, onmsg, jref) },
+        })
+        .collect()
+}
+
+/// Start the exam lockdown of a proof opened from an exam assignment, asking
+/// for the student's name if the assignment doesn't have it, and starting the
+/// countdown if the exam is timed
+fn start_exam(ctx: &Context<ProofWidget>, mut config: ExamConfig) -> ExamState {
+    if config.student.is_none() {
+        let message = "This proof is an exam. Enter your name or student ID, which will be included when you save it.";
+        let student = web_sys::window().and_then(|window| window.prompt_with_message(message).ok().flatten());
+        config.student = student.map(|student| student.trim().to_string()).filter(|student| !student.is_empty());
+    }
+    let deadline = config.duration_minutes.map(|minutes| js_sys::Date::now() + f64::from(minutes) * 60_000.0);
+    let link = ctx.link().clone();
+    let ticker = deadline.map(|_| Interval::new(1000, move || link.send_message(ProofWidgetMsg::ExamTick)));
+    ExamState { config, deadline, ticker }
+}
+
+/// Render the banner of an exam, with the student's name and the time left
+fn render_exam_banner(exam: &ExamState) -> Html {
+    let student = match &exam.config.student {
+        Some(student) => format!("Exam \u{2014} {student}"),
+        None => "Exam".to_string(),
+    };
+    let countdown = exam.deadline.map(|deadline| {
+        let seconds_left = ((deadline - js_sys::Date::now()) / 1000.0).ceil().max(0.0) as u64;
+        let (text, class) = match seconds_left {
+            0 => ("Time is up".to_string(), "text-danger font-weight-bold"),
+            // Warn in the last five minutes
+            1..=300 => (format!("Time left: {}:{:02}", seconds_left / 60, seconds_left % 60), "text-danger"),
+            _ => (format!("Time left: {}:{:02}", seconds_left / 60, seconds_left % 60), ""),
+        };
+        html! { <span class={ classes!("ml-auto", "exam-countdown", class) } role="timer"> { text } </span> }
+    });
+    html! {
+        <div class="alert alert-info d-flex align-items-center m-2 proof-exam-banner">
+            <strong> { student } </strong>
+            { for countdown }
+        </div>
     }
 }
 
@@ -633,7 +2827,8 @@ fn new_empty_premise() -> Expr {
     Expr::var("")
 }
 
-/// Create a new empty step, the default step when creating a new one in the UI.
+/// Create a new empty step, which `ProofWidget::fill_in_new_step()` then fills
+/// in as the user prefers.
 fn new_empty_step() -> Justification<Expr, PjRef<P>, <P as Proof>::SubproofReference> {
     Justification(Expr::var(""), RuleM::EmptyRule, vec![], vec![])
 }
@@ -651,6 +2846,14 @@ fn new_empty_proof() -> (P, ProofUiData<P>) {
     (proof, pud)
 }
 
+/// Run `f` with a proof's syntax set for parsing and printing its lines, and
+/// how many assumptions its subproofs may have and which lines its steps may
+/// cite for editing and checking them. They're put back afterwards, so other
+/// proofs and exercises on the page read and check theirs with their own.
+fn while_open<T>(syntax: &Syntax, assumption_policy: AssumptionPolicy, scoping_policy: ScopingPolicy, f: impl FnOnce() -> T) -> T {
+    aris::parser::with_syntax(syntax, || aris::proofs::with_assumption_policy(assumption_policy, || aris::proofs::with_scoping_policy(scoping_policy, f)))
+}
+
 impl Component for ProofWidget {
     type Message = ProofWidgetMsg;
     type Properties = ProofWidgetProps;
@@ -659,29 +2862,51 @@ impl Component for ProofWidget {
     /// Initializes the proof, UI data, and error handling based on the input properties.
     fn create(ctx: &Context<Self>) -> Self {
         ctx.props().oncreate.emit(ctx.link().clone());
-        let (prf, pud, error) = match &ctx.props().data {
+        let (prf, pud, metadata, error) = match &ctx.props().data {
             Some(data) => {
                 let result = aris::proofs::xml_interop::proof_from_xml::<P, _>(&data[..]);
                 match result {
-                    Ok((prf, _)) => {
-                        let pud = ProofUiData::from_proof(&prf);
-                        (prf, pud, None)
+                    Ok((prf, metadata)) => {
+                        let pud = metadata.while_open(|| ProofUiData::from_proof(&prf));
+                        (prf, pud, Some(metadata), None)
                     }
                     Err(err) => {
+                        crash::log("error", format!("Could not open a proof: {err}"));
                         let (prf, pud) = new_empty_proof();
-                        (prf, pud, Some(err))
+                        (prf, pud, None, Some(err))
                     }
                 }
             }
             None => {
                 let (prf, pud) = new_empty_proof();
-                (prf, pud, None)
+                (prf, pud, None, None)
             }
         };
+        let (goals, exam, delayed_feedback, operators, logic_mode, require_all_premises, require_full_parens, assumption_policy, scoping_policy, blanks, timings) = metadata.map(|metadata| (metadata.goals, metadata.exam, metadata.delayed_feedback, metadata.operators, metadata.logic_mode, metadata.require_all_premises, metadata.require_full_parens, metadata.assumption_policy, metadata.scoping_policy, metadata.blanks, metadata.timings)).unwrap_or_default();
+        // Timings are saved by line number, but kept by line, so they follow
+        // lines that move
+        let timings = timings.map(|timings| pud.line_info(&prf).lines.iter().filter_map(|(r, (line, _))| Some((*r, *timings.lines.get(line)?))).collect());
+        let exam = exam.map(|config| start_exam(ctx, config));
+        let tutorial = ctx.props().tutorial.clone().map(|tutorial| TutorialState { tutorial, step: 0, callout_ref: NodeRef::default() });
+
+        let id = unique_id("proof");
+
+        let (preferences, preferences_handle) = match ctx.link().context::<Preferences>(ctx.link().callback(ProofWidgetMsg::PreferencesChanged)) {
+            Some((preferences, handle)) => (preferences, Some(handle)),
+            None => (Preferences::default(), None),
+        };
 
-        let id: String = ((random() * 10000.0) as i32).to_string();
-
-        let mut tmp = Self { prf, pud, selected_line: None, open_error: error, preblob: "".into(), id };
+        if let Some(data) = ctx.props().data.as_ref().filter(|_| !ctx.props().scratch) {
+            crash::autosave(&id, data.clone());
+        }
+        let reported_proof = prf.clone();
+        let mut tmp = Self { prf, pud, selected_line: None, selected_subformula: None, open_error: error, search: None, premise_import: None, paste_offer: None, context_menu: None, scroll_to: None, tutorial, goals, solved: false, exam, delayed_feedback, operators, logic_mode, require_all_premises, require_full_parens, assumption_policy, scoping_policy, blanks, timings, correct_lines: BTreeSet::new(), reported_proof, last_check: None, check_timeout: None, show_error_list: false, show_constants: false, scratchpad: None, show_scratchpad: false, pending_parses: HashMap::new(), telemetry_timeout: None, flashed_lines: vec![], flash_timeout: None, preferences, preferences_handle, key_state: KeyState::default(), preblob: "".into(), onmsg: ctx.link().callback(|msg| msg), id };
+        // Opening a solved proof doesn't count as solving it, and lines that
+        // are correct when it's opened didn't just become correct
+        while_open(&tmp.syntax(), tmp.assumption_policy, tmp.scoping_policy, || {
+            tmp.solved = tmp.is_solved();
+            tmp.correct_lines = tmp.lines_in_order().into_iter().filter(|r| tmp.line_status(*r).is_correct()).collect();
+        });
         Component::update(&mut tmp, ctx, ProofWidgetMsg::Nop);
         tmp
     }
@@ -689,108 +2914,144 @@ impl Component for ProofWidget {
     /// Updates the `ProofWidget` state based on messages, such as line edits or rule changes.
     /// This handles line actions, updates proof data, and re-renders the UI as needed.
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let syntax = self.syntax();
+        while_open(&syntax, self.assumption_policy, self.scoping_policy, || self.update_while_open(ctx, msg))
+    }
+
+    /// Handles property changes for the `ProofWidget` component.
+    /// Always triggers a re-render when properties change.
+    fn changed(&mut self, _: &Context<Self>, _: &Self::Properties) -> bool {
+        true
+    }
+
+    /// Stops keeping the proof for the crash dialog once it's closed
+    fn destroy(&mut self, _: &Context<Self>) {
+        crash::forget(&self.id);
+    }
+
+    /// Renders the `ProofWidget` component.
+    /// Displays either the proof editor or an error message if the proof could not be loaded.
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        while_open(&self.syntax(), self.assumption_policy, self.scoping_policy, || self.view_while_open(ctx))
+    }
+
+    /// Executes post-render logic, such as focusing the search bar or premise import dialog, and scrolling to a
+    /// search match or cited line, and placing the tutorial callout.
+    fn rendered(&mut self, _: &Context<Self>, _: bool) {
+        self.place_tutorial_callout();
+        if let Some(search) = &mut self.search {
+            if mem::take(&mut search.focus) {
+                if let Some(input) = search.input_ref.cast::<web_sys::HtmlInputElement>() {
+                    input.focus().unwrap_throw();
+                    input.select();
+                }
+            }
+        }
+        if let Some(premise_import) = &mut self.premise_import {
+            if mem::take(&mut premise_import.focus) {
+                if let Some(textarea) = premise_import.textarea_ref.cast::<HtmlElement>() {
+                    textarea.focus().unwrap_throw();
+                }
+            }
+        }
+        if let Some(line_ref) = self.scroll_to.take() {
+            let line = self.pud.line_info(&self.prf).line_of(&line_ref);
+            if let Some(row) = line.and_then(|line| document().get_element_by_id(&format!("{}-line-row-{}", self.id, line))) {
+                row.scroll_into_view_with_bool(false);
+            }
+        }
+    }
+}
+
+impl ProofWidget {
+    /// `Component::update`, with the proof's syntax and assumption policy set
+    fn update_while_open(&mut self, ctx: &Context<Self>, msg: ProofWidgetMsg) -> bool {
+        // The cursor moves on nearly every keypress, which is too often to log
+        // or re-render for, and nothing shows it besides the text field itself
+        if let ProofWidgetMsg::CaretMoved(r, caret) = msg {
+            self.pud.ref_to_caret.insert(r, caret);
+            return false;
+        }
+        // Likewise, the countdown ticks every second, and nothing changes
+        // besides it
+        if let ProofWidgetMsg::ExamTick = msg {
+            if let Some(exam) = &mut self.exam {
+                if exam.deadline.map(|deadline| js_sys::Date::now() >= deadline).unwrap_or(true) {
+                    exam.ticker = None;
+                }
+            }
+            return true;
+        }
+        // Reports don't change anything shown
+        if let ProofWidgetMsg::ReportProgress = msg {
+            self.telemetry_timeout = None;
+            telemetry::send(&self.telemetry_report());
+            return false;
+        }
+        crash::log("proof", format!("{}: {msg:?}", self.id));
         let mut ret = false;
         if ctx.props().verbose {
             self.preblob += &format!("{msg:?}\n");
             ret = true;
         }
+        if self.edits_locked_cell(&msg) {
+            return ret;
+        }
+        let restructures = adds_or_removes_lines(&msg);
         use Coproduct::{Inl, Inr};
         match msg {
-            ProofWidgetMsg::Nop => {}
+            ProofWidgetMsg::Nop | ProofWidgetMsg::CaretMoved(..) | ProofWidgetMsg::ExamTick | ProofWidgetMsg::ReportProgress => {}
             ProofWidgetMsg::LineChanged(r, input) => {
-                self.pud.ref_to_input.insert(r, input.clone());
-                if let Some(e) = aris::parser::parse(&input) {
-                    match r {
-                        Inl(pr) => {
-                            self.prf.with_mut_premise(&pr, |x| *x = e);
-                        }
-                        Inr(Inl(jr)) => {
-                            self.prf.with_mut_step(&jr, |x| x.0 = e);
-                        }
-                        Inr(Inr(void)) => match void {},
-                    }
+                self.pud.ref_to_input.insert(r, input);
+                // Parse once typing pauses. Replacing a pending timeout
+                // cancels it, so only the last edit gets parsed.
+                let link = ctx.link().clone();
+                let was_pending = self.pending_parses.insert(r, Timeout::new(PARSE_DELAY_MS, move || link.send_message(ProofWidgetMsg::ParseLine(r)))).is_some();
+                // Show the line as pending, unless it already is
+                ret |= !was_pending;
+            }
+            ProofWidgetMsg::ParseLine(r) => {
+                if self.pending_parses.remove(&r).is_some() {
+                    self.parse_line(r);
+                    self.record_error_hit(r);
+                    ret = true;
                 }
-                ret = true;
             }
-            ProofWidgetMsg::LineAction(LineActionKind::Insert { what, after, relative_to }, orig_ref) => {
-                let to_select;
-                let orig_ref = pj_to_pjs::<P>(orig_ref);
-                let parent = self.prf.parent_of_line(&orig_ref);
-                let insertion_point: PjsRef<P> = match relative_to {
-                    ProofItemKind::Premise | ProofItemKind::Just => orig_ref,
-                    ProofItemKind::Subproof => match parent {
-                        Some(parent) => Coproduct::inject(parent),
-                        None => return ret,
-                    },
-                };
-                match what {
-                    ProofItemKind::Premise => match insertion_point {
-                        Inl(pr) => {
-                            // Insert premise relative to premise
-                            to_select = Inl(self.prf.add_premise_relative(new_empty_premise(), &pr, after));
-                        }
-                        Inr(Inl(_)) | Inr(Inr(Inl(_))) => {
-                            // Insert premise relative to line or subproof
-                            to_select = Inl(self.prf.add_premise(new_empty_premise()));
+            ProofWidgetMsg::LineAction(LineActionKind::Insert { what, after, relative_to, count }, orig_ref) => {
+                // Each line after the first goes right after the one inserted
+                // before it, or right before the original line, so they end
+                // up in order, and the first of them is selected
+                let mut anchor = (orig_ref, relative_to);
+                let mut first = None;
+                for _ in 0..count.clamp(1, MAX_INSERT_COUNT) {
+                    match self.insert_line(what, after, anchor.1, anchor.0) {
+                        Some(inserted) if after => {
+                            first.get_or_insert(inserted);
+                            anchor = (inserted, what);
                         }
-                        Inr(Inr(Inr(void))) => match void {},
-                    },
-                    ProofItemKind::Just => match insertion_point {
-                        Inl(_) => {
-                            // Insert justification relative to premise
-
-                            // Add justification to enclosing subproof of premise, if it exists
-                            let just_ref = parent.and_then(|parent| self.prf.with_mut_subproof(&parent, |parent| parent.prepend_step(new_empty_step())));
-
-                            // If the insertion point is not in a subproof, add justification to the top-level proof
-                            match just_ref {
-                                Some(just_ref) => to_select = Coproduct::inject(just_ref),
-                                None => to_select = Coproduct::inject(self.prf.prepend_step(new_empty_step())),
-                            }
+                        Some(inserted) => {
+                            first.get_or_insert(inserted);
                         }
-                        Inr(Inl(jr)) => {
-                            // Insert justification relative to justification
-                            let jsr = Coproduct::inject(jr);
-                            to_select = Inr(Inl(self.prf.add_step_relative(new_empty_step(), &jsr, after)));
-                        }
-                        Inr(Inr(Inl(sr))) => {
-                            // Insert justification relative to subproof
-                            let jsr = Coproduct::inject(sr);
-                            to_select = Inr(Inl(self.prf.add_step_relative(new_empty_step(), &jsr, after)));
-                        }
-                        Inr(Inr(Inr(void))) => match void {},
-                    },
-                    ProofItemKind::Subproof => {
-                        // Convert insertion point from `PjsRef` to `JsRef`,
-                        // returning silently on failure
-                        let insertion_point: JsRef<P> = match insertion_point.subset() {
-                            Ok(insertion_point) => insertion_point,
-                            // Insertion point is a premise, return silently
-                            Err(_) => return ret,
-                        };
-                        let sr = self.prf.add_subproof_relative(&insertion_point, after);
-                        to_select = self
-                            .prf
-                            .with_mut_subproof(&sr, |sub| {
-                                let to_select = Inl(sub.add_premise(new_empty_premise()));
-                                sub.prepend_step(new_empty_step());
-                                to_select
-                            })
-                            .expect("Subproof doesn't exist after creating it");
+                        None => break,
                     }
                 }
-                self.select_line(ctx, to_select);
+                match first {
+                    Some(first) => self.select_line(first),
+                    None => return ret,
+                }
                 self.preblob += &format!("{:?}\n", self.prf.premises());
                 ret = true;
             }
             ProofWidgetMsg::LineAction(LineActionKind::Delete { what }, proofref) => {
+                self.flush_pending_parses();
+                let lines_before = self.lines_in_order();
                 let parent = self.prf.parent_of_line(&pj_to_pjs::<P>(proofref));
                 match what {
                     ProofItemKind::Premise | ProofItemKind::Just => {
                         fn remove_line_if_allowed<P: Proof, Q: Proof<PremiseReference = <P as Proof>::PremiseReference, JustificationReference = <P as Proof>::JustificationReference>>(prf: &mut Q, pud: &mut ProofUiData<P>, proofref: PjRef<Q>) {
                             if may_remove_line(prf, &proofref) {
-                                pud.ref_to_line_depth.remove(&proofref);
                                 pud.ref_to_input.remove(&proofref);
+                                pud.ref_to_caret.remove(&proofref);
                                 prf.remove_line(&proofref);
                             }
                         }
@@ -807,29 +3068,28 @@ impl Component for ProofWidget {
                         }
                     }
                     ProofItemKind::Subproof => {
-                        // TODO: recursively clean out the ProofUiData entries for lines inside a subproof before deletion
                         // shouldn't delete the root subproof
                         if let Some(sr) = parent {
+                            if let Some(sub) = self.prf.lookup_subproof(&sr) {
+                                self.pud.remove_subproof_lines(&sub);
+                            }
                             self.prf.remove_subproof(&sr);
                         }
                     }
                 }
-                // Deselect current line to prevent it from pointing to a
-                // deleted line. The selected line could be deep inside a
-                // deleted subproof, so it's easier to deselect conservatively
-                // than to figure out if the selected line is deleted.
-                self.selected_line = None;
+                self.reselect_after_removal(&lines_before);
                 ret = true;
             }
             ProofWidgetMsg::LineAction(LineActionKind::SetRule { rule }, proofref) => {
                 if let Inr(Inl(jr)) = &proofref {
                     self.prf.with_mut_step(jr, |j| j.1 = rule);
+                    analytics::record(&analytics::Event::RuleSelected { rule: RuleM::to_serialized_name(rule).to_string() });
                 }
-                self.select_line(ctx, proofref);
+                self.select_line(proofref);
                 ret = true;
             }
             ProofWidgetMsg::LineAction(LineActionKind::Select, proofref) => {
-                self.select_line(ctx, proofref);
+                self.select_line(proofref);
                 ret = true;
             }
             ProofWidgetMsg::LineAction(LineActionKind::ToggleDependency { dep }, proofref) => {
@@ -853,48 +3113,487 @@ impl Component for ProofWidget {
                 }
                 ret = true;
             }
+            ProofWidgetMsg::LineAction(LineActionKind::SelectSubformula { path }, proofref) => {
+                self.select_line(proofref);
+                self.selected_subformula = Some(path);
+                ret = true;
+            }
+            ProofWidgetMsg::LineAction(LineActionKind::ApplyEquivalence { rule, path }, proofref) => {
+                let rewritten = self.prf.lookup_expr(&proofref).and_then(|e| rule.rewrite_rule()?.rewrite_at(&e, &path));
+                if let Some(rewritten) = rewritten {
+                    // Insert an empty step right after the line, which selects it, then fill it in
+                    let relative_to = match proofref {
+                        Inl(_) => ProofItemKind::Premise,
+                        _ => ProofItemKind::Just,
+                    };
+                    Component::update(self, ctx, ProofWidgetMsg::LineAction(LineActionKind::Insert { what: ProofItemKind::Just, after: true, relative_to, count: 1 }, proofref));
+                    if let Some(SelectedLine { line_ref: Inr(Inl(jr)), .. }) = &self.selected_line {
+                        let jr = *jr;
+                        self.pud.ref_to_input.insert(Coproduct::inject(jr), rewritten.to_string());
+                        self.prf.with_mut_step(&jr, |j| *j = Justification(rewritten, rule, vec![proofref], vec![]));
+                    }
+                    ret = true;
+                }
+            }
+            ProofWidgetMsg::LineAction(LineActionKind::Duplicate, proofref) => {
+                if let Some(lak) = self.duplicate_kind(proofref) {
+                    // Inserting selects the new line, which then gets a copy
+                    // of the original's formula, and rule and citations if
+                    // it's a step
+                    Component::update(self, ctx, ProofWidgetMsg::LineAction(lak, proofref));
+                    if let Some(copy) = self.selected_line.as_ref().map(|selected_line| selected_line.line_ref).filter(|copy| *copy != proofref) {
+                        let input = self.pud.ref_to_input.get(&proofref).cloned().unwrap_or_default();
+                        self.set_line_input(copy, input);
+                        if let (Inr(Inl(original)), Inr(Inl(copy))) = (proofref, copy) {
+                            if let Some(just) = self.prf.lookup_step(&original) {
+                                self.prf.with_mut_step(&copy, |j| *j = just);
+                            }
+                        }
+                    }
+                    ret = true;
+                }
+            }
+            ProofWidgetMsg::LineAction(LineActionKind::SplitConjunction, proofref) => {
+                let conjuncts = self.conjuncts_of(proofref);
+                if !conjuncts.is_empty() {
+                    // Insert a step per conjunct right after the line, which
+                    // selects the first of them, then fill them in in order
+                    let relative_to = match proofref {
+                        Inl(_) => ProofItemKind::Premise,
+                        _ => ProofItemKind::Just,
+                    };
+                    Component::update(self, ctx, ProofWidgetMsg::LineAction(LineActionKind::Insert { what: ProofItemKind::Just, after: true, relative_to, count: conjuncts.len() }, proofref));
+                    let lines = self.lines_in_order();
+                    let first = self.selected_line.as_ref().map(|selected_line| selected_line.line_ref).filter(|first| *first != proofref);
+                    let inserted = first.and_then(|first| lines.iter().position(|line| *line == first)).map(|start| &lines[start..]).unwrap_or_default();
+                    for (line, conjunct) in inserted.iter().zip(conjuncts) {
+                        if let Inr(Inl(jr)) = *line {
+                            self.pud.ref_to_input.insert(*line, conjunct.to_string());
+                            self.prf.with_mut_step(&jr, |j| *j = Justification(conjunct, RuleM::AndElim, vec![proofref], vec![]));
+                        }
+                    }
+                    ret = true;
+                }
+            }
+            ProofWidgetMsg::LineAction(LineActionKind::ConcludeContradiction, proofref) => {
+                if let Some((positive, negative)) = self.prf.contradiction_after_line(&proofref) {
+                    // Insert an empty step right after the line, which selects it, then fill it in
+                    let relative_to = match proofref {
+                        Inl(_) => ProofItemKind::Premise,
+                        _ => ProofItemKind::Just,
+                    };
+                    Component::update(self, ctx, ProofWidgetMsg::LineAction(LineActionKind::Insert { what: ProofItemKind::Just, after: true, relative_to, count: 1 }, proofref));
+                    if let Some(SelectedLine { line_ref: Inr(Inl(jr)), .. }) = &self.selected_line {
+                        let jr = *jr;
+                        self.pud.ref_to_input.insert(Coproduct::inject(jr), Expr::Contra.to_string());
+                        self.prf.with_mut_step(&jr, |j| *j = Justification(Expr::Contra, RuleM::ContradictionIntro, vec![positive, negative], vec![]));
+                    }
+                    ret = true;
+                }
+            }
+            ProofWidgetMsg::LineAction(LineActionKind::ReiterateCitations, proofref) => {
+                let lines = self.lines_to_reiterate(proofref);
+                if let (Inr(Inl(step)), false) = (proofref, lines.is_empty()) {
+                    // Insert an empty step right before the step for each line, which selects it, then fill it in
+                    // and cite it instead of the line
+                    for line in lines {
+                        let Some(expr) = self.prf.lookup_expr(&line) else { continue };
+                        Component::update(self, ctx, ProofWidgetMsg::LineAction(LineActionKind::Insert { what: ProofItemKind::Just, after: false, relative_to: ProofItemKind::Just, count: 1 }, proofref));
+                        if let Some(SelectedLine { line_ref: Inr(Inl(jr)), .. }) = &self.selected_line {
+                            let jr = *jr;
+                            self.pud.ref_to_input.insert(Coproduct::inject(jr), expr.to_string());
+                            self.prf.with_mut_step(&jr, |j| *j = Justification(expr, RuleM::Reiteration, vec![line], vec![]));
+                            self.prf.with_mut_step(&step, |j| {
+                                for dep in j.2.iter_mut().filter(|dep| **dep == line) {
+                                    *dep = Coproduct::inject(jr);
+                                }
+                                j.2.sort();
+                            });
+                        }
+                    }
+                    self.select_line(proofref);
+                    ret = true;
+                }
+            }
+            ProofWidgetMsg::LineAction(LineActionKind::InsertGap, proofref) => {
+                // Insert an empty step right after the line, which selects it, then admit it
+                let relative_to = match proofref {
+                    Inl(_) => ProofItemKind::Premise,
+                    _ => ProofItemKind::Just,
+                };
+                Component::update(self, ctx, ProofWidgetMsg::LineAction(LineActionKind::Insert { what: ProofItemKind::Just, after: true, relative_to, count: 1 }, proofref));
+                if let Some(SelectedLine { line_ref: Inr(Inl(jr)), .. }) = &self.selected_line {
+                    let jr = *jr;
+                    self.prf.with_mut_step(&jr, |j| j.1 = RuleM::Gap);
+                }
+                ret = true;
+            }
+            ProofWidgetMsg::LineAction(LineActionKind::WorkBackward { rule }, proofref) => {
+                let backward = match proofref {
+                    Inr(Inl(jr)) => self.prf.lookup_step(&jr).and_then(|just| Some((jr, rule.backward(&just.0)?))),
+                    _ => None,
+                };
+                if let Some((jr, schema)) = backward {
+                    // What the step needs goes right before it, in order, as
+                    // gaps of their own, with blanks for what's left to pick
+                    let before: JsRef<P> = Coproduct::inject(jr);
+                    let mut inserted: Vec<PjRef<P>> = vec![];
+                    let mut deps = vec![];
+                    for dep in schema.deps {
+                        let r = Coproduct::inject(self.prf.add_step_relative(Justification(dep.clone(), RuleM::Gap, vec![], vec![]), &before, false));
+                        self.pud.ref_to_input.insert(r, dep.to_string());
+                        inserted.push(r);
+                        deps.push(r);
+                    }
+                    let mut sdeps = vec![];
+                    for (premise, line) in schema.subdeps {
+                        let sr = self.prf.add_subproof_relative(&before, false);
+                        let (premise_input, line_input) = (premise.as_ref().map(ToString::to_string), line.to_string());
+                        let added = self.prf.with_mut_subproof(&sr, |sub| (premise.map(|premise| sub.add_premise(premise)), sub.add_step(Justification(line, RuleM::Gap, vec![], vec![]))));
+                        if let Some((pr, line_jr)) = added {
+                            if let (Some(pr), Some(input)) = (pr, premise_input) {
+                                let r = Coproduct::inject(pr);
+                                self.pud.ref_to_input.insert(r, input);
+                                inserted.push(r);
+                            }
+                            let r = Coproduct::inject(line_jr);
+                            self.pud.ref_to_input.insert(r, line_input);
+                            inserted.push(r);
+                        }
+                        sdeps.push(sr);
+                    }
+                    self.prf.with_mut_step(&jr, |j| {
+                        j.1 = rule;
+                        j.2 = deps;
+                        j.3 = sdeps;
+                    });
+                    analytics::record(&analytics::Event::RuleSelected { rule: RuleM::to_serialized_name(rule).to_string() });
+                    self.select_line(inserted.first().copied().unwrap_or(proofref));
+                    ret = true;
+                }
+            }
+            ProofWidgetMsg::LineAction(LineActionKind::Move { after }, proofref) => {
+                if let Some(other) = self.adjacent_line(proofref, after) {
+                    self.swap_lines(proofref, other);
+                    // Keep the moved line selected at its new place
+                    self.select_line(other);
+                    self.scroll_to = Some(other);
+                    ret = true;
+                }
+            }
             ProofWidgetMsg::CallOnProof(f) => {
-                f(&self.prf);
+                self.flush_pending_parses();
+                // Saving also checks the proof, with on-demand verification,
+                // but it doesn't give away feedback that's delayed
+                if self.verification() == Verification::OnDemand && self.delayed_feedback.is_none() {
+                    self.check_proof(ctx);
+                    ret = true;
+                }
+                f(&self.prf, &self.save_metadata());
+            }
+            ProofWidgetMsg::GetCommands(callback) => {
+                callback.emit(self.commands(ctx));
+            }
+            ProofWidgetMsg::LineEntered(r) => {
+                for msg in self.advance_from(r) {
+                    ret |= Component::update(self, ctx, msg);
+                }
             }
             ProofWidgetMsg::Keypress(key_event) => {
-                let msg = self.process_key_shortcut(key_event);
-                ret = Component::update(self, ctx, msg);
+                let mode = self.key_state.mode;
+                for msg in self.process_key(key_event) {
+                    ret |= Component::update(self, ctx, msg);
+                }
+                // Show the new mode in the mode indicator
+                ret |= self.key_state.mode != mode;
+            }
+            ProofWidgetMsg::OpenSearch => {
+                let search = self.search.get_or_insert_with(|| SearchState { query: String::new(), pattern: None, replacement: None, input_ref: NodeRef::default(), focus: false });
+                search.focus = true;
+                ret = true;
+            }
+            ProofWidgetMsg::SearchChanged(query) => {
+                if let Some(search) = &mut self.search {
+                    search.pattern = aris::parser::parse(&query);
+                    search.query = query;
+                    self.step_search(true, true);
+                }
+                ret = true;
+            }
+            ProofWidgetMsg::SearchStep { forward } => {
+                self.step_search(forward, false);
+                ret = true;
+            }
+            ProofWidgetMsg::CloseSearch => {
+                self.search = None;
+                ret = true;
+            }
+            ProofWidgetMsg::ToggleReplace => {
+                if let Some(search) = &mut self.search {
+                    search.replacement = match search.replacement {
+                        Some(_) => None,
+                        None => Some(String::new()),
+                    };
+                }
+                ret = true;
+            }
+            ProofWidgetMsg::ReplacementChanged(replacement) => {
+                if let Some(search) = &mut self.search {
+                    search.replacement = Some(replacement);
+                }
+                ret = true;
+            }
+            ProofWidgetMsg::FlashLines(lines) => {
+                self.scroll_to = lines.first().copied();
+                self.flashed_lines = lines;
+                let link = ctx.link().clone();
+                // Replacing a pending timeout cancels it, so clicking again restarts the highlight
+                self.flash_timeout = Some(Timeout::new(FLASH_DURATION_MS, move || link.send_message(ProofWidgetMsg::ClearFlash)));
+                ret = true;
+            }
+            ProofWidgetMsg::PreferencesChanged(preferences) => {
+                self.preferences = preferences;
+                ret = true;
+            }
+            ProofWidgetMsg::NextTutorialStep => {
+                if let Some(tutorial) = &mut self.tutorial {
+                    tutorial.step = (tutorial.step + 1).min(tutorial.tutorial.steps.len());
+                }
+                ret = true;
+            }
+            ProofWidgetMsg::ExitTutorial => {
+                self.tutorial = None;
+                ret = true;
+            }
+            ProofWidgetMsg::CheckProof => {
+                // With delayed feedback, every check of an edited proof uses up
+                // one of the allowed checks
+                let edited = self.last_check.as_ref().map(|check| check.stale).unwrap_or(true);
+                match &mut self.delayed_feedback {
+                    Some(_) if !edited => {}
+                    Some(feedback) if feedback.checks_left() == Some(0) => {}
+                    Some(feedback) => {
+                        feedback.checks_used += 1;
+                        self.check_proof(ctx);
+                    }
+                    None => self.check_proof(ctx),
+                }
+                ret = true;
+            }
+            ProofWidgetMsg::ContinueCheck => {
+                self.continue_check(ctx);
+                ret = true;
+            }
+            ProofWidgetMsg::ToggleErrorList => {
+                self.show_error_list = !self.show_error_list;
+                ret = true;
+            }
+            ProofWidgetMsg::ToggleConstants => {
+                self.show_constants = !self.show_constants;
+                ret = true;
+            }
+            ProofWidgetMsg::ToggleScratchpad => {
+                if self.scratchpad.is_none() {
+                    self.scratchpad = Some(Scratchpad { data: self.scratchpad_data(), ontransplant: ctx.link().callback(ProofWidgetMsg::TransplantLines) });
+                }
+                self.show_scratchpad = !self.show_scratchpad;
+                ret = true;
+            }
+            ProofWidgetMsg::TransplantLines(fragment) => {
+                let last = || self.lines_in_order().into_iter().rev().find(|r| self.prf.parent_of_line(&pj_to_pjs::<P>(*r)).is_none());
+                if let Some(line_ref) = self.selected_line.as_ref().map(|selected_line| selected_line.line_ref).or_else(last) {
+                    let lines = fragment.to_text().lines().map(String::from).collect();
+                    self.import_pasted_lines(ctx, line_ref, PastedLines { lines, inline: String::new(), fragment: Some(fragment) });
+                }
+                ret = true;
+            }
+            ProofWidgetMsg::ClearFlash => {
+                self.flashed_lines.clear();
+                self.flash_timeout = None;
+                ret = true;
+            }
+            ProofWidgetMsg::OpenPremiseImport => {
+                let premise_import = self.premise_import.get_or_insert_with(|| PremiseImportState { text: String::new(), errors: vec![], textarea_ref: NodeRef::default(), focus: false });
+                premise_import.focus = true;
+                ret = true;
+            }
+            ProofWidgetMsg::PremiseImportChanged(text) => {
+                if let Some(premise_import) = &mut self.premise_import {
+                    premise_import.text = text;
+                }
+            }
+            ProofWidgetMsg::ImportPremises => {
+                self.import_premises();
+                ret = true;
+            }
+            ProofWidgetMsg::ClosePremiseImport => {
+                self.premise_import = None;
+                ret = true;
+            }
+            ProofWidgetMsg::LinesPasted(line_ref, pasted) => {
+                self.paste_offer = Some(PasteOffer { line_ref, pasted });
+                ret = true;
+            }
+            ProofWidgetMsg::ImportPastedLines => {
+                if let Some(PasteOffer { line_ref, pasted }) = self.paste_offer.take() {
+                    self.import_pasted_lines(ctx, line_ref, pasted);
+                }
+                ret = true;
+            }
+            ProofWidgetMsg::PastePastedLinesInline => {
+                if let Some(PasteOffer { line_ref, pasted }) = self.paste_offer.take() {
+                    self.set_line_input(line_ref, pasted.inline);
+                }
+                ret = true;
+            }
+            ProofWidgetMsg::DismissPastedLines => {
+                self.paste_offer = None;
+                ret = true;
+            }
+            ProofWidgetMsg::OpenContextMenu(_, _, _) if ctx.props().read_only => {}
+            ProofWidgetMsg::OpenContextMenu(line_ref, x, y) => {
+                self.select_line(line_ref);
+                self.context_menu = Some((line_ref, x, y));
+                ret = true;
+            }
+            ProofWidgetMsg::CloseContextMenu => {
+                self.context_menu = None;
+                ret = true;
+            }
+            ProofWidgetMsg::ReplaceAll => {
+                for (r, replaced) in self.replacement_preview() {
+                    self.set_line_input(r, replaced.to_string());
+                }
+                ret = true;
+            }
+            ProofWidgetMsg::ReformatProof => {
+                self.flush_pending_parses();
+                for r in self.lines_in_order() {
+                    // The given formulas of a cloze proof are left as written
+                    if self.is_locked(r, Blank::Formula) {
+                        continue;
+                    }
+                    let input = self.pud.ref_to_input.get(&r);
+                    if let Some(reformatted) = input.and_then(|input| aris::parser::reformat(input)).filter(|reformatted| Some(reformatted) != input) {
+                        self.set_line_input(r, reformatted);
+                    }
+                }
+                ret = true;
             }
         }
+        if restructures {
+            self.pud.prune_orphans(&self.prf);
+        }
         if ret {
-            calculate_lineinfo::<P>(&mut self.pud.ref_to_line_depth, self.prf.top_level_proof(), &mut 1, &mut 0);
+            self.record_timings();
+            if let Some(check) = &mut self.last_check {
+                check.stale = check.proof != self.prf || check.inputs != self.pud.ref_to_input;
+            }
+            // Exams aren't practice, and their progress would show whether
+            // they're solved. Neither would delayed feedback until it's shown.
+            let feedback_shown = self.delayed_feedback.is_none() || self.last_check.as_ref().map(|check| !check.stale && check.remaining.is_none()).unwrap_or(false);
+            let solved = self.exam.is_none() && feedback_shown && self.is_solved();
+            if solved && !self.solved {
+                if !ctx.props().scratch {
+                    self.record_solved();
+                }
+                if let Some(onsolved) = &ctx.props().onsolved {
+                    onsolved.emit(());
+                }
+            }
+            self.solved = solved;
+            if self.prf != self.reported_proof {
+                let data = signing::xml_from_proof_and_metadata(&self.prf, &self.save_metadata());
+                if !ctx.props().scratch {
+                    crash::autosave(&self.id, data.clone());
+                }
+                if let Some(onchange) = &ctx.props().onchange {
+                    onchange.emit(data);
+                }
+                self.reported_proof = self.prf.clone();
+            }
+            // Gather changes for a moment, so typing doesn't send a report on
+            // every keypress
+            if self.telemetry_timeout.is_none() && !ctx.props().scratch && telemetry::relay_url().is_some() {
+                let link = ctx.link().clone();
+                self.telemetry_timeout = Some(Timeout::new(TELEMETRY_DELAY_MS, move || link.send_message(ProofWidgetMsg::ReportProgress)));
+            }
+            // Move on from tutorial steps once the proof meets their conditions
+            if let Some(tutorial) = &mut self.tutorial {
+                while let Some(step) = tutorial.tutorial.steps.get(tutorial.step) {
+                    if step.expectations.is_empty() || !step.expectations.iter().all(|expectation| expectation.is_met(&self.prf, &self.pud)) {
+                        break;
+                    }
+                    tutorial.step += 1;
+                }
+            }
         }
         ret
     }
 
-    /// Handles property changes for the `ProofWidget` component.
-    /// Always triggers a re-render when properties change.
-    fn changed(&mut self, _: &Context<Self>, _: &Self::Properties) -> bool {
-        true
-    }
-
-    /// Renders the `ProofWidget` component.
-    /// Displays either the proof editor or an error message if the proof could not be loaded.
-    fn view(&self, ctx: &Context<Self>) -> Html {
+    /// `Component::view`, with the proof's syntax and assumption policy set
+    fn view_while_open(&self, ctx: &Context<Self>) -> Html {
         let widget = match &self.open_error {
             Some(err) => render_open_error(err),
-            None => self.render_proof(ctx, self.prf.top_level_proof(), None, &mut 1, &mut 0),
+            None => html! {
+                <>
+                    { self.render_proof(self.prf.top_level_proof(), None, &mut 1, &mut 0) }
+                    { self.render_status_bar(ctx) }
+                </>
+            },
         };
-        html! {
-            <div>
-                { widget }
+        // Ctrl-F anywhere in the proof opens the search bar, and other keys
+        // are the selected line's shortcuts. Fields with keys of their own,
+        // like the search bar, keep them from getting here.
+        let onkeydown = ctx.link().callback(|e: KeyboardEvent| {
+            if e.ctrl_key() && e.key() == "f" {
+                e.prevent_default();
+                ProofWidgetMsg::OpenSearch
+            } else {
+                ProofWidgetMsg::Keypress(e)
+            }
+        });
+        // Mode indicator of the Vim keymap. In normal mode, the caret is hidden,
+        // since keys don't type into the line.
+        let (class, mode_indicator) = match (self.preferences.keymap, self.key_state.mode) {
+            (Keymap::Vim, Mode::Normal) => (Some("vim-normal"), html! { <div class="vim-mode text-muted m-2"> { "-- NORMAL --" } </div> }),
+            (Keymap::Vim, Mode::Insert) => (None, html! { <div class="vim-mode text-muted m-2"> { "-- INSERT --" } </div> }),
+            (Keymap::Default, _) => (None, html! {}),
+        };
+        // The clipboard is off limits during an exam, and so is the debug
+        // output, which would show the proof's internals
+        let block_clipboard = self.exam.is_some().then(|| Callback::from(|e: Event| e.prevent_default()));
+        let exam_banner = self.exam.as_ref().map(render_exam_banner);
+        let debug_output = match self.exam {
+            Some(_) => html! {},
+            None => html! {
                 <div style="display: none">
                     <hr />
                     <pre> { format!("{}\n{:#?}", self.prf, self.prf) } </pre>
                     <hr />
                     <pre> { self.preblob.clone() } </pre>
                 </div>
+            },
+        };
+        html! {
+            <div id={ self.id.clone() } class={ classes!(class) } { onkeydown } oncopy={ block_clipboard.clone() } oncut={ block_clipboard.clone() } onpaste={ block_clipboard }>
+                { for exam_banner }
+                { self.render_search_bar(ctx) }
+                <div class="d-flex align-items-start">
+                    // Disabling the fieldset disables every input and button in it
+                    <fieldset class="flex-grow-1" style="min-width: 0" disabled={ ctx.props().read_only }>
+                        { widget }
+                    </fieldset>
+                    { self.render_scratchpad(ctx) }
+                </div>
+                { mode_indicator }
+                { self.render_premise_import_dialog(ctx) }
+                { self.render_context_menu(ctx) }
+                { self.render_equivalence_panel(ctx) }
+                { self.render_constants_panel(ctx) }
+                { self.render_tutorial_callout(ctx) }
+                { debug_output }
             </div>
         }
     }
-
-    /// Executes post-render logic, such as initializing Bootstrap submenus and popovers.
-    fn rendered(&mut self, _: &Context<Self>, _: bool) {
-        js_sys::eval("$('[data-submenu]').submenupicker(); $('[data-toggle=popover]').popover()").unwrap_throw();
-    }
 }
@@ -0,0 +1,523 @@
+//! Rows of the proof table, as components of their own
+//!
+//! `ProofWidget` works out what each row shows, as the row's
+//! `ProofRowProps`, and the row renders itself from them. yew only renders a
+//! row again when its properties change, so typing in a line only renders
+//! that line and the lines showing it, such as the steps citing it.
+
+use super::actions::ActionInfo;
+use super::html_escape;
+use super::rule_menu;
+use super::rule_menu_items;
+use super::LineActionKind;
+use super::LineStatus;
+use super::ProofWidgetMsg;
+use super::P;
+use crate::box_chars;
+use crate::components::expr_entry::ExprEntry;
+use crate::components::expr_entry::PastedLines;
+use crate::components::menu::Menu;
+use crate::components::menu::MenuItem;
+use crate::components::popover::Popover;
+use crate::components::rule_example::RuleExample;
+
+use aris::proofs::PjRef;
+use aris::proofs::Proof;
+use aris::rules::ProofCheckError;
+use aris::rules::Rule;
+
+use frunk_core::coproduct::Coproduct;
+use frunk_core::Coprod;
+use yew::prelude::*;
+
+/// How a row is highlighted
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+    /// The row isn't highlighted
+    None,
+    /// The row is the selected line
+    Selected,
+    /// The row matches the search
+    SearchMatch,
+    /// The selected line cites the row
+    Cited,
+}
+
+/// A line or subproof cited by a step, shown as a badge
+#[derive(Clone, PartialEq)]
+pub struct Citation {
+    /// CSS class of the badge, telling lines and subproofs apart
+    pub class: &'static str,
+
+    /// The cited line numbers
+    pub label: String,
+
+    /// The cited formulas, shown when the badge is hovered
+    pub formulas: String,
+
+    /// The lines to jump to when the badge is clicked
+    pub lines: Vec<PjRef<P>>,
+}
+
+/// The rule and citations of a step
+#[derive(Clone, PartialEq)]
+pub struct StepCells {
+    pub jref: <P as Proof>::JustificationReference,
+
+    /// Name of the step's rule, as shown in the rules menu's button
+    pub rule_name: String,
+
+    /// Whether the step's rule can't be changed, in a cloze proof
+    pub rule_locked: bool,
+
+    /// Whether the rules menu describes the rules, which it doesn't during an
+    /// exam
+    pub describe_rules: bool,
+
+    pub citations: Vec<Citation>,
+
+    /// Whether the cited formulas are shown below the citations
+    pub show_cited_formulas: bool,
+}
+
+/// Details of a wrong step for its error popover
+#[derive(Clone, PartialEq)]
+pub struct ErrorDetails {
+    /// The step's rule and how it's used, unless that would be a hint during
+    /// an exam
+    pub schema: Option<(Rule, String)>,
+
+    /// The lines and subproofs the step cites
+    pub cited: Vec<String>,
+
+    /// A model showing the step doesn't follow from what it cites, if
+    /// there's a small one
+    pub countermodel: Option<String>,
+
+    /// A trace showing a temporal logic step doesn't follow from what it
+    /// cites, if there's a short one
+    pub countertrace: Option<String>,
+}
+
+/// What a line's feedback cell shows
+#[derive(Clone, PartialEq)]
+pub struct Feedback {
+    /// The line's status, if it's shown
+    pub status: Option<LineStatus>,
+
+    /// Whether the line is a premise the conclusion doesn't depend on, and if
+    /// it is, whether the assignment requires using every premise
+    pub unused_premise: Option<bool>,
+
+    /// Whether the proof was edited since it was checked, with on-demand
+    /// verification
+    pub stale: bool,
+
+    /// Details of the error, if the line is a wrong step
+    pub details: Option<ErrorDetails>,
+}
+
+/// The actions of a line's actions menu
+#[derive(Clone, PartialEq)]
+pub struct LineActions {
+    pub actions: Vec<&'static ActionInfo>,
+
+    /// Whether the menu offers adding premises from text, which it does for
+    /// the premises of the whole proof
+    pub import_premises: bool,
+}
+
+impl LineActions {
+    /// Items of the menu, doing the actions on `proofref`
+    pub fn menu_items(&self, onmsg: &Callback<ProofWidgetMsg>, proofref: PjRef<P>) -> Vec<MenuItem> {
+        let mut items = self
+            .actions
+            .iter()
+            .map(|action_info| {
+                let lak = action_info.line_action_kind.clone();
+                MenuItem::Action {
+                    label: action_info.description.to_string(),
+                    title: None,
+                    shortcut: action_info.keyboard_shortcut,
+                    // Callback triggering line action
+                    onselect: onmsg.reform(move |()| ProofWidgetMsg::LineAction(lak.clone(), proofref)),
+                }
+            })
+            .collect::<Vec<MenuItem>>();
+        if self.import_premises {
+            items.push(MenuItem::Action { label: "Add premises from text\u{2026}".to_string(), title: None, shortcut: None, onselect: onmsg.reform(|()| ProofWidgetMsg::OpenPremiseImport) });
+        }
+        items
+    }
+}
+
+/// Properties for `ProofRow`
+#[derive(Clone, PartialEq, Properties)]
+pub struct ProofRowProps {
+    /// Sends the row's events to the `ProofWidget`. It's made once, so it
+    /// compares equal from render to render.
+    pub onmsg: Callback<ProofWidgetMsg>,
+
+    /// ID of the `ProofWidget`, which the IDs of the row's elements start with
+    pub widget_id: String,
+
+    pub proofref: PjRef<P>,
+
+    /// Number of the line
+    pub line: usize,
+
+    /// How deep in subproofs the line is, and the box drawing character
+    /// ending its indentation
+    pub depth: usize,
+    pub edge_decoration: char,
+
+    /// Text of the line, its caret, and whether it's focused, as given to its
+    /// `ExprEntry`
+    pub input: String,
+    pub caret: Option<(u32, u32)>,
+    pub focus: Option<bool>,
+
+    /// Whether the line's formula can't be edited, in a cloze proof
+    pub readonly: bool,
+
+    /// Whether the line is a subproof's assumption
+    pub is_assumption: bool,
+
+    pub highlight: Highlight,
+
+    /// Whether the line was just jumped to from a citation of it
+    pub flashed: bool,
+
+    /// The selected step, if clicking the line number cites the line from it
+    pub citing: Option<PjRef<P>>,
+
+    /// The color and line ranges of the subproofs the step discharges, if
+    /// discharges are shown and it discharges any
+    pub discharge: Option<(usize, String)>,
+
+    pub feedback: Feedback,
+
+    /// The rule and citations, if the line is a step
+    pub step: Option<StepCells>,
+
+    pub actions: LineActions,
+
+    /// Whether Enter adds a line after this one
+    pub advance_on_enter: bool,
+
+    /// Whether pasting several lines into the line offers to add them as
+    /// lines, which it doesn't during an exam
+    pub paste_lines: bool,
+
+    /// The number of lines pasted into the line and what they'd be added as,
+    /// if the choice of what to do with them is offered
+    pub paste_offer: Option<(usize, &'static str)>,
+}
+
+/// A row of the proof table, rendered again only when its properties change
+pub struct ProofRow;
+
+impl Component for ProofRow {
+    type Message = ();
+    type Properties = ProofRowProps;
+
+    fn create(_: &Context<Self>) -> Self {
+        ProofRow
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        use Coproduct::Inl;
+        let props = ctx.props();
+        let onmsg = &props.onmsg;
+        let proofref = props.proofref;
+        let line_num_dep_checkbox = dependency_button(props.line.to_string(), props.citing, Coproduct::inject(proofref), onmsg);
+        let mut indentation = yew::virtual_dom::VList::new();
+        for _ in 0..props.depth {
+            indentation.add_child(html! { <span class="indent"> { box_chars::VERT } </span>});
+        }
+        indentation.add_child(html! { <span class="indent">{ props.edge_decoration }</span>});
+        let handle_input = onmsg.reform(move |value: String| ProofWidgetMsg::LineChanged(proofref, value));
+        let select_line = onmsg.reform(move |()| ProofWidgetMsg::LineAction(LineActionKind::Select, proofref));
+        let handle_caret = onmsg.reform(move |caret: (u32, u32)| ProofWidgetMsg::CaretMoved(proofref, caret));
+        let handle_enter = props.advance_on_enter.then(|| onmsg.reform(move |()| ProofWidgetMsg::LineEntered(proofref)));
+        let handle_paste_lines = props.paste_lines.then(|| onmsg.reform(move |pasted: PastedLines| ProofWidgetMsg::LinesPasted(proofref, pasted)));
+
+        // Menu for selecting a line action
+        let action_selector = html! {
+            <Menu label={ "\u{22EE}" } button_class="btn btn-secondary" items={ props.actions.menu_items(onmsg, proofref) } />
+        };
+        let rule_feedback = render_feedback(&props.feedback, matches!(proofref, Inl(_)), props.is_assumption);
+        let class = match props.highlight {
+            Highlight::Selected => "proof-line table-info",
+            Highlight::SearchMatch => "proof-line table-warning",
+            Highlight::Cited => "proof-line table-secondary",
+            Highlight::None => "proof-line",
+        };
+        let class = classes!(class, props.is_assumption.then_some("assumption-line"), props.flashed.then_some("flash-line"));
+        let feedback_and_just_widgets = match &props.step {
+            None => {
+                // Premise
+                html! {
+                    <>
+                        <td></td>
+                        <td> { rule_feedback } </td>
+                        <td></td>
+                    </>
+                }
+            }
+            Some(step) => {
+                // Justification
+                html! {
+                    <>
+                        <td> { rule_feedback } </td>
+                        { render_step_cells(step, onmsg) }
+                    </>
+                }
+            }
+        };
+        let discharge_mark = match &props.discharge {
+            Some((color, ranges)) => html! {
+                <span class={ classes!("discharge-mark", format!("discharge-{color}")) } title={ format!("Discharges the assumption of lines {ranges}") }> { "\u{25CF}" } </span>
+            },
+            None => html! {},
+        };
+        let id_num = format!("{}-line-number-{}", props.widget_id, props.line);
+        let oncontextmenu = onmsg.reform(move |e: MouseEvent| {
+            e.prevent_default();
+            ProofWidgetMsg::OpenContextMenu(proofref, e.client_x(), e.client_y())
+        });
+        html! {
+            <>
+                <tr class={ class } id={ format!("{}-line-row-{}", props.widget_id, props.line) } { oncontextmenu }>
+                    <td> { line_num_dep_checkbox } { discharge_mark } </td>
+                    <td>
+                        { indentation }
+                        <ExprEntry
+                            oninput={ handle_input }
+                            onfocus={ select_line }
+                            onpastelines={ handle_paste_lines }
+                            oncaret={ handle_caret }
+                            onenter={ handle_enter }
+                            caret={ props.caret }
+                            focus={ props.focus }
+                            init_value={ props.input.clone() }
+                            readonly={ props.readonly }
+                            id={ id_num }/>
+                    </td>
+                    { feedback_and_just_widgets }
+                    <td>{ action_selector }</td>
+                </tr>
+                { render_paste_offer(props.paste_offer, onmsg) }
+            </>
+        }
+    }
+}
+
+/// Renders a line number, or the blank number of a subproof, as a button
+/// citing the line or subproof `dep` from the step `citing` if there's one,
+/// and as a disabled button otherwise
+pub fn dependency_button(label: String, citing: Option<PjRef<P>>, dep: Coprod!(PjRef<P>, <P as Proof>::SubproofReference), onmsg: &Callback<ProofWidgetMsg>) -> Html {
+    match citing {
+        Some(line_ref) => {
+            let toggle_dep = onmsg.reform(move |_| ProofWidgetMsg::LineAction(LineActionKind::ToggleDependency { dep }, line_ref));
+            html! {
+                <button
+                    type="button"
+                    class="btn btn-secondary"
+                    onclick={ toggle_dep }>
+
+                    { label }
+                </button>
+            }
+        }
+        None => html! {
+            <button
+                type="button"
+                class="btn"
+                disabled=true>
+
+                { label }
+            </button>
+        },
+    }
+}
+
+/// Renders the feedback cell of a line, such as whether it's correct or its
+/// error
+fn render_feedback(feedback: &Feedback, is_premise: bool, is_assumption: bool) -> Html {
+    let rendered = match &feedback.status {
+        None | Some(LineStatus::Empty) => html! { <span></span> },
+        Some(LineStatus::Pending) => html! {
+            <span class="d-inline-block s1">
+                <span class="spinner-border spinner-border-sm text-secondary" role="status">
+                    <span class="sr-only"> { "Checking\u{2026}" } </span>
+                </span>
+            </span>
+        },
+        Some(LineStatus::Unparsed(err)) => {
+            let label = if err.is_too_large() { "Too large" } else { "Parse error" };
+            html! { <span class="alert alert-warning small-alert s1" title={ err.to_string() }>{ label }</span> }
+        }
+        Some(LineStatus::Correct) => match feedback.unused_premise {
+            Some(required) => {
+                // Unused premises are only a problem when the assignment requires using them all
+                let class = if required { "alert alert-warning small-alert s2" } else { "alert alert-secondary small-alert s2" };
+                html! { <span class={ class } title="The conclusion doesn't depend on this premise">{ "Unused premise" }</span> }
+            }
+            None if is_premise => html! {
+                <span class="alert alert-success small-alert s2">
+                    { if is_assumption { "Assumption" } else { "Premise" } }
+                </span>
+            },
+            None => {
+                html! { <span class="alert small-alert bg-success text-white s1">{ "Correct" }</span> }
+            }
+        },
+        Some(LineStatus::Warned(warnings)) => {
+            html! { <Popover label="Warning" class="btn btn-warning s1" content={ warnings.join(" ") } /> }
+        }
+        Some(LineStatus::Incorrect(err)) => {
+            html! { <Popover label="Error" class="btn btn-danger s1" content={ err.clone() } details={ feedback.details.as_ref().map(render_error_details) } /> }
+        }
+        Some(LineStatus::TimedOut) => {
+            let message = ProofCheckError::<(), ()>::TimedOut.to_string();
+            html! { <Popover label="Timed out" class="btn btn-warning s1" content={ message } /> }
+        }
+        Some(LineStatus::Gap) => {
+            let message = ProofCheckError::<(), ()>::Gap.to_string();
+            html! { <Popover label="Gap" class="btn btn-info s1" content={ message } /> }
+        }
+    };
+    if feedback.stale {
+        html! {
+            <span class="stale-result" title="The proof was edited since it was checked"> { rendered } </span>
+        }
+    } else {
+        rendered
+    }
+}
+
+/// Renders the details of a wrong step for its error popover
+fn render_error_details(details: &ErrorDetails) -> Html {
+    let schema = details.schema.as_ref().map(|(rule, schema)| {
+        html! {
+            <div>
+                { format!("{} is used like ", aris::rule_names::display_name(*rule)) } <code> { schema } </code>
+                <RuleExample rule={ *rule } read_only=true />
+            </div>
+        }
+    });
+    let citations = if details.cited.is_empty() {
+        html! { <div> { "It doesn't cite any lines." } </div> }
+    } else {
+        html! {
+            <div>
+                { "It cites:" }
+                <ul class="mb-0 pl-3"> { for details.cited.iter().map(|cited| html! { <li> { cited } </li> }) } </ul>
+            </div>
+        }
+    };
+    let countermodel = details.countermodel.as_ref().map(|model| {
+        html! { <div> { format!("It doesn't follow from what it cites, which is all true in the model with {model}, where it's false.") } </div> }
+    });
+    let countertrace = details.countertrace.as_ref().map(|trace| {
+        html! { <div> { format!("It doesn't follow from what it cites, which is all true of the trace {trace}, where it's false.") } </div> }
+    });
+    html! {
+        <>
+            { for schema }
+            { citations }
+            { for countermodel }
+            { for countertrace }
+        </>
+    }
+}
+
+/// Renders the rules menu and citations of a step
+fn render_step_cells(step: &StepCells, onmsg: &Callback<ProofWidgetMsg>) -> Html {
+    // Badge that shows the cited formulas when hovered, and jumps to the
+    // cited lines when clicked
+    let render_badge = |citation: &Citation| {
+        let lines = citation.lines.clone();
+        let onclick = onmsg.reform(move |e: MouseEvent| {
+            e.prevent_default();
+            ProofWidgetMsg::FlashLines(lines.clone())
+        });
+        html! {
+            <a href="#" class={ classes!("badge", citation.class, "m-1") } data-toggle="tooltip" data-placement="top" title={ html_escape(&citation.formulas) } { onclick }>
+                { &citation.label }
+            </a>
+        }
+    };
+
+    // Node containing all dependency badges, for rendering list of
+    // dependencies
+    let all_dep_badges = step.citations.iter().map(render_badge).collect::<Html>();
+
+    // Formulas of the cited lines, if the user wants them shown
+    let cited_formulas = if step.show_cited_formulas && !step.citations.is_empty() {
+        html! {
+            <div class="cited-formulas small text-muted">
+                { for step.citations.iter().map(|citation| html! { <div> { format!("{}: {}", citation.label, citation.formulas) } </div> }) }
+            </div>
+        }
+    } else {
+        html! {}
+    };
+
+    html! {
+        <>
+            <td>
+                // Drop-down menu for selecting rules
+                { render_rules_menu(step, onmsg) }
+            </td>
+            <td>
+                // Dependency list
+                <span class="alert alert-secondary small-alert p-1">
+                    { all_dep_badges }
+                </span>
+                { cited_formulas }
+            </td>
+        </>
+    }
+}
+
+/// Renders the drop-down menu for picking the rule of a step. The menu's
+/// items are only built when it's opened.
+fn render_rules_menu(step: &StepCells, onmsg: &Callback<ProofWidgetMsg>) -> Html {
+    if step.rule_locked {
+        return html! {
+            <button type="button" class="btn btn-primary" disabled=true>{ step.rule_name.clone() }</button>
+        };
+    }
+    let (describe, jref, onmsg) = (step.describe_rules, step.jref, onmsg.clone());
+    let build_items = Callback::from(move |()| rule_menu_items(&rule_menu(describe), &onmsg, jref));
+    html! {
+        <Menu label={ step.rule_name.clone() } button_class="btn btn-primary dropdown-toggle" direction="dropright" { build_items } />
+    }
+}
+
+/// Renders the choice of what to do with text with several lines pasted into
+/// a line, as a row below it, given the number of lines pasted and what
+/// they'd be added as
+fn render_paste_offer(paste_offer: Option<(usize, &'static str)>, onmsg: &Callback<ProofWidgetMsg>) -> Html {
+    let (count, kind) = match paste_offer {
+        Some(paste_offer) => paste_offer,
+        None => return html! {},
+    };
+    html! {
+        <tr>
+            <td></td>
+            <td colspan="5">
+                <div class="alert alert-info small-alert p-2" role="alert">
+                    { format!("Pasted {count} lines. ") }
+                    <button type="button" class="btn btn-sm btn-primary ml-2" onclick={ onmsg.reform(|_| ProofWidgetMsg::ImportPastedLines) }> { format!("Add as {kind}") } </button>
+                    <button type="button" class="btn btn-sm btn-outline-secondary ml-2" onclick={ onmsg.reform(|_| ProofWidgetMsg::PastePastedLinesInline) }> { "Paste into this line" } </button>
+                    <button type="button" class="close ml-2" aria-label="Dismiss" onclick={ onmsg.reform(|_| ProofWidgetMsg::DismissPastedLines) }>
+                        <span aria-hidden="true"> { '×' } </span>
+                    </button>
+                </div>
+            </td>
+        </tr>
+    }
+}
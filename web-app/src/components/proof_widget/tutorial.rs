@@ -0,0 +1,210 @@
+//! Guided tutorials, which walk through building a proof one step at a time
+//!
+//! A tutorial is written as a script of steps. Each step has some text shown
+//! in a callout next to a part of the proof, and what the proof has to look
+//! like to move on to the next step. For example:
+//!
+//! ```text
+//! # Lines starting with `#` are comments
+//! title: Modus ponens
+//!
+//! step:
+//! anchor: line 1 text
+//! text: Type P -> Q into the first premise.
+//! expect: line 1 is P -> Q
+//!
+//! step:
+//! anchor: line 3 rule
+//! text: Pick the rule "→ Elimination".
+//! expect: line 3 rule → Elimination
+//! ```
+//!
+//! Each step starts with `step:`, and has these keys:
+//!
+//! + `text:` the text of the callout. Repeated `text:` lines are joined.
+//! + `anchor:` which part of the proof the callout points at, as `line N`
+//!   followed by `number`, `text`, `rule`, or `menu` for a part of the line
+//! + `expect:` a condition for moving on, repeated to require several. One
+//!   of `line N is FORMULA`, `line N rule RULE`, `line N cites M`, or
+//!   `line N correct`. Steps without conditions have a button for moving on.
+
+use crate::proof_ui_data::ProofUiData;
+use crate::util::P;
+
+use aris::expr::Expr;
+use aris::proofs::PjRef;
+use aris::proofs::Proof;
+use aris::rules::RuleT;
+
+use frunk_core::coproduct::Coproduct;
+
+/// Scripts of the tutorials that come with the app
+const BUILTIN_SCRIPTS: &[&str] = &[include_str!("tutorials/modus_ponens.tutorial")];
+
+/// A guided tutorial, parsed from a script
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tutorial {
+    pub title: String,
+    pub steps: Vec<TutorialStep>,
+}
+
+/// A step of a tutorial
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TutorialStep {
+    /// Text of the callout
+    pub text: String,
+
+    /// Part of the proof the callout points at, if any
+    pub anchor: Option<Anchor>,
+
+    /// Conditions the proof has to meet to move on to the next step
+    pub expectations: Vec<Expectation>,
+}
+
+/// Part of a proof line that a callout points at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Anchor {
+    pub line: usize,
+    pub part: LinePart,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinePart {
+    /// The whole row of the line
+    Row,
+    /// The line number button, which cites the line when clicked
+    Number,
+    /// The text field of the line's formula
+    Text,
+    /// The rule menu of a step
+    Rule,
+    /// The line actions menu
+    Menu,
+}
+
+/// A condition on the proof for moving on from a tutorial step
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expectation {
+    /// The line's text parses as this formula
+    Formula { line: usize, formula: Expr },
+    /// The line is a step using the rule with this name
+    Rule { line: usize, rule: String },
+    /// The line is a step citing line `cited`
+    Cites { line: usize, cited: usize },
+    /// The line is correct
+    Correct { line: usize },
+}
+
+/// The tutorials that come with the app
+pub fn builtin() -> Vec<Tutorial> {
+    BUILTIN_SCRIPTS.iter().map(|script| Tutorial::parse(script).expect("invalid built-in tutorial script")).collect()
+}
+
+impl Tutorial {
+    /// Parse a tutorial script, in the format described in the module
+    /// documentation
+    pub fn parse(script: &str) -> Result<Tutorial, String> {
+        let mut title = None;
+        let mut steps: Vec<TutorialStep> = vec![];
+        for (i, line) in script.lines().enumerate() {
+            let error = |message: &str| format!("line {}: {}", i + 1, message);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once(':').ok_or_else(|| error("expected `key: value`"))?;
+            let value = value.trim();
+            if key == "title" {
+                title = Some(value.to_string());
+                continue;
+            }
+            if key == "step" {
+                steps.push(TutorialStep::default());
+                continue;
+            }
+            let step = steps.last_mut().ok_or_else(|| error("expected `step:` before the step's keys"))?;
+            match key {
+                "text" if step.text.is_empty() => step.text = value.to_string(),
+                "text" => {
+                    step.text.push(' ');
+                    step.text.push_str(value);
+                }
+                "anchor" => step.anchor = Some(parse_anchor(value).ok_or_else(|| error("invalid anchor"))?),
+                "expect" => step.expectations.push(parse_expectation(value).ok_or_else(|| error("invalid expectation"))?),
+                _ => return Err(error(&format!("unknown key {key:?}"))),
+            }
+        }
+        let title = title.ok_or("missing `title:`")?;
+        if steps.is_empty() {
+            return Err("tutorial has no steps".into());
+        }
+        Ok(Tutorial { title, steps })
+    }
+}
+
+/// Split `line N rest` into `N` and `rest`
+fn split_line_number(value: &str) -> Option<(usize, &str)> {
+    let rest = value.strip_prefix("line")?.trim_start();
+    let (number, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    Some((number.parse().ok()?, rest.trim()))
+}
+
+fn parse_anchor(value: &str) -> Option<Anchor> {
+    let (line, part) = split_line_number(value)?;
+    let part = match part {
+        "" => LinePart::Row,
+        "number" => LinePart::Number,
+        "text" => LinePart::Text,
+        "rule" => LinePart::Rule,
+        "menu" => LinePart::Menu,
+        _ => return None,
+    };
+    Some(Anchor { line, part })
+}
+
+fn parse_expectation(value: &str) -> Option<Expectation> {
+    let (line, rest) = split_line_number(value)?;
+    let (kind, arg) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let arg = arg.trim();
+    match kind {
+        "is" => Some(Expectation::Formula { line, formula: aris::parser::parse(&aris::macros::expand(arg))? }),
+        "rule" if !arg.is_empty() => Some(Expectation::Rule { line, rule: arg.to_string() }),
+        "cites" => Some(Expectation::Cites { line, cited: arg.parse().ok()? }),
+        "correct" if arg.is_empty() => Some(Expectation::Correct { line }),
+        _ => None,
+    }
+}
+
+impl Anchor {
+    /// CSS selector of the anchored element, in the proof widget with this ID
+    pub fn selector(&self, widget_id: &str) -> String {
+        let row = format!("#{}-line-row-{}", widget_id, self.line);
+        match self.part {
+            LinePart::Row => row,
+            LinePart::Number => format!("{row} td:first-child .btn"),
+            LinePart::Text => format!("#{}-line-number-{}", widget_id, self.line),
+            LinePart::Rule => format!("{row} .btn-primary.dropdown-toggle"),
+            LinePart::Menu => format!("{row} td:last-child .btn"),
+        }
+    }
+}
+
+impl Expectation {
+    /// Whether the proof meets this condition
+    pub fn is_met(&self, prf: &P, pud: &ProofUiData<P>) -> bool {
+        use Coproduct::{Inl, Inr};
+        let line_ref = |line: usize| -> Option<PjRef<P>> { pud.line_info(prf).lines.iter().find(|(r, (l, _))| *l == line && prf.lookup_pj(r).is_some()).map(|(r, _)| *r) };
+        match self {
+            Expectation::Formula { line, formula } => line_ref(*line).and_then(|r| pud.ref_to_input.get(&r)).and_then(|input| aris::parser::parse(input)).map(|expr| expr == *formula).unwrap_or(false),
+            Expectation::Rule { line, rule } => match line_ref(*line) {
+                Some(Inr(Inl(jr))) => prf.lookup_justification_or_die(&jr).map(|just| just.1.get_name() == *rule).unwrap_or(false),
+                _ => false,
+            },
+            Expectation::Cites { line, cited } => match (line_ref(*line), line_ref(*cited)) {
+                (Some(Inr(Inl(jr))), Some(cited)) => prf.lookup_justification_or_die(&jr).map(|just| just.2.contains(&cited)).unwrap_or(false),
+                _ => false,
+            },
+            Expectation::Correct { line } => line_ref(*line).map(|r| prf.verify_line(&r).is_ok()).unwrap_or(false),
+        }
+    }
+}
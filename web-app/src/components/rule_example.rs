@@ -0,0 +1,89 @@
+//! A button showing a rule's example from `aris::rule_examples` in a scratch
+//! proof widget below it, which isn't saved anywhere, so the example can be
+//! tried out and thrown away
+
+use crate::components::proof_widget::ProofWidget;
+use crate::util::P;
+
+use aris::proofs::xml_interop;
+use aris::rule_examples;
+use aris::rules;
+use aris::rules::Rule;
+
+use yew::prelude::*;
+
+/// The example of a rule, as the XML of a proof to load into a `ProofWidget`,
+/// or `None` if the rule doesn't have one. It's opened in the logic the rule
+/// is used in.
+pub fn example_data(rule: Rule) -> Option<Vec<u8>> {
+    let prf = rule_examples::example::<P>(rule)?;
+    let mut data = vec![];
+    let metadata = xml_interop::ProofMetaData { author: Some("ARIS-YEW-UI".into()), logic_mode: rules::logic_of(rule).unwrap_or_default(), ..Default::default() };
+    xml_interop::xml_from_proof_and_metadata_with_hash(&prf, &metadata, &mut data).expect("xml_from_proof_and_metadata failed");
+    Some(data)
+}
+
+/// Properties for `RuleExample`
+#[derive(Clone, Properties, PartialEq)]
+pub struct RuleExampleProps {
+    pub rule: Rule,
+
+    /// Show the example without letting it be edited
+    #[prop_or_default]
+    pub read_only: bool,
+}
+
+pub enum RuleExampleMsg {
+    Toggle,
+}
+
+/// A button that shows or hides the example of a rule
+pub struct RuleExample {
+    /// The example, as XML, while it's shown
+    data: Option<Vec<u8>>,
+}
+
+impl Component for RuleExample {
+    type Message = RuleExampleMsg;
+    type Properties = RuleExampleProps;
+
+    fn create(_: &Context<Self>) -> Self {
+        Self { data: None }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            // Loading the example again when it's shown again starts over,
+            // undoing whatever was tried in it
+            RuleExampleMsg::Toggle => self.data = if self.data.is_some() { None } else { example_data(ctx.props().rule) },
+        }
+        true
+    }
+
+    fn changed(&mut self, _: &Context<Self>, _: &Self::Properties) -> bool {
+        self.data = None;
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        if rule_examples::fragment(ctx.props().rule).is_none() {
+            return html! {};
+        }
+        let label = if self.data.is_some() { "Hide example" } else { "Show example" };
+        let widget = self.data.clone().map(|data| {
+            html! {
+                <div class="border rounded mt-1">
+                    <ProofWidget verbose=false data={ Some(data) } oncreate={ Callback::noop() } read_only={ ctx.props().read_only } scratch=true />
+                </div>
+            }
+        });
+        html! {
+            <div>
+                <button type="button" class="btn btn-link btn-sm p-0" aria-expanded={ self.data.is_some().to_string() } onclick={ ctx.link().callback(|_| RuleExampleMsg::Toggle) }>
+                    { label }
+                </button>
+                { for widget }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::components::app::App;
+use crate::components::app::AppMsg;
+use crate::components::proof_widget::ProofWidget;
+use crate::components::rule_example;
+use crate::components::rule_example::RuleExample;
+
+use aris::rule_examples;
+use aris::rule_names;
+use aris::rules::Rule;
+use aris::rules::RuleClassification;
+use aris::rules::RuleT;
+
+use derivative::Derivative;
+use strum::IntoEnumIterator;
+use web_sys::HtmlInputElement;
+use yew::html::Scope;
+use yew::prelude::*;
+
+/// Searchable list of every rule, grouped by `RuleClassification`, showing
+/// each rule's schema and description from the rule metadata, and its example
+/// from `aris::rule_examples`, to try out in place or open in a new tab
+pub struct RuleReference {
+    query: String,
+}
+
+pub enum RuleReferenceMsg {
+    Search(String),
+    InsertExample(Rule),
+}
+
+#[derive(Properties, Clone, Derivative)]
+#[derivative(PartialEq)]
+pub struct RuleReferenceProps {
+    #[derivative(PartialEq = "ignore")]
+    pub parent: Scope<App>,
+}
+
+impl RuleReference {
+    /// Whether a rule should be listed for the current search query. The
+    /// query is matched case-insensitively against the rule's name, under the
+    /// course's name for it too, and its description.
+    fn matches(&self, rule: Rule) -> bool {
+        let query = self.query.trim().to_lowercase();
+        let names = [rule.get_name(), rule_names::display_name(rule), rule.get_description()];
+        query.is_empty() || names.iter().any(|name| name.to_lowercase().contains(&query))
+    }
+
+    fn render_rule(&self, ctx: &Context<Self>, rule: Rule) -> Html {
+        let schema = rule.get_schema();
+        let insert_button = match rule_examples::fragment(rule) {
+            Some(_) => html! {
+                <button type="button" class="btn btn-outline-primary btn-sm" onclick={ ctx.link().callback(move |_| RuleReferenceMsg::InsertExample(rule)) }>
+                    { "Open in a tab" }
+                </button>
+            },
+            None => html! {},
+        };
+        html! {
+            <div class="list-group-item">
+                <div class="d-flex justify-content-between align-items-center">
+                    <h5 class="mb-1"> { rule_names::display_name(rule) } </h5>
+                    { insert_button }
+                </div>
+                { for schema.map(|schema| html! { <p class="mb-1"><code> { schema.to_string() } </code></p> }) }
+                <p class="mb-0"> { rule.get_description() } </p>
+                <RuleExample rule={ rule } />
+            </div>
+        }
+    }
+}
+
+impl Component for RuleReference {
+    type Message = RuleReferenceMsg;
+    type Properties = RuleReferenceProps;
+
+    fn create(_: &Context<Self>) -> Self {
+        Self { query: String::new() }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            RuleReferenceMsg::Search(query) => {
+                self.query = query;
+                true
+            }
+            RuleReferenceMsg::InsertExample(rule) => {
+                if let Some(data) = rule_example::example_data(rule) {
+                    let name = format!("{} example", rule_names::display_name(rule));
+                    let name_ = name.clone();
+                    let parent = &ctx.props().parent;
+                    let oncreate = parent.callback(move |link| AppMsg::RegisterProofName { name: name_.clone(), link });
+                    parent.send_message(AppMsg::CreateTab { name, content: html! { <ProofWidget verbose=true data={ Some(data) } oncreate={ oncreate } /> } });
+                }
+                false
+            }
+        }
+    }
+
+    fn changed(&mut self, _: &Context<Self>, _: &Self::Properties) -> bool {
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            RuleReferenceMsg::Search(input.value())
+        });
+
+        let sections = RuleClassification::iter()
+            .filter_map(|class| {
+                let rules = class.rules().filter(|rule| self.matches(*rule)).collect::<Vec<_>>();
+                if rules.is_empty() {
+                    return None;
+                }
+                Some(html! {
+                    <>
+                        <h4 class="mt-3"> { class.to_string() } </h4>
+                        <div class="list-group">
+                            { for rules.into_iter().map(|rule| self.render_rule(ctx, rule)) }
+                        </div>
+                    </>
+                })
+            })
+            .collect::<Html>();
+
+        html! {
+            <div class="m-4">
+                <h2> { "Rule reference" } </h2>
+                <input type="search" class="form-control" placeholder="Search rules by name or description" value={ self.query.clone() } { oninput } />
+                { sections }
+            </div>
+        }
+    }
+}
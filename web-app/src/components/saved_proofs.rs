@@ -0,0 +1,273 @@
+use crate::components::app::App;
+use crate::components::app::AppMsg;
+use crate::components::proof_diff::ProofDiffView;
+use crate::components::proof_loader::ProofLoader;
+use crate::preferences::Preferences;
+use crate::preferences::StorageBackend;
+use crate::storage;
+use crate::storage::IndexedDbStore;
+use crate::storage::StoreError;
+use crate::storage::StoredProof;
+
+use derivative::Derivative;
+use wasm_bindgen::JsValue;
+use yew::context::ContextHandle;
+use yew::html::Scope;
+use yew::prelude::*;
+
+/// Proofs listed from the store, or why listing them failed
+type Listing = Result<Vec<StoredProof>, StoreError>;
+
+/// List of the proofs saved in the store picked in the user's preferences,
+/// for opening them
+pub struct SavedProofs {
+    /// The saved proofs, or `None` while they're being listed
+    proofs: Option<Listing>,
+
+    /// Name of the proof whose kept versions are shown, if any, with the
+    /// versions, or `None` while they're being listed
+    history: Option<(String, Option<Listing>)>,
+
+    /// Why comparing versions of the proof in the history failed, if it did
+    compare_error: Option<StoreError>,
+
+    preferences: Preferences,
+
+    /// Subscription to changes of the preferences
+    #[allow(dead_code)]
+    preferences_handle: Option<ContextHandle<Preferences>>,
+}
+
+pub enum SavedProofsMsg {
+    /// List the saved proofs again
+    Refresh,
+    Listed(Listing),
+    Open(String),
+    /// Show the kept versions of the proof with this name
+    ShowHistory(String),
+    HistoryListed(String, Listing),
+    HideHistory,
+    /// Open a kept version of a proof
+    OpenVersion(StoredProof),
+    /// Compare a kept version of a proof, the first one, with the latest one
+    CompareVersion(StoredProof, StoredProof),
+    VersionsLoaded(StoredProof, Result<(Vec<u8>, Vec<u8>), StoreError>),
+    /// The user's preferences were changed, possibly to another store
+    PreferencesChanged(Preferences),
+}
+
+#[derive(Properties, Clone, Derivative)]
+#[derivative(PartialEq)]
+pub struct SavedProofsProps {
+    #[derivative(PartialEq = "ignore")]
+    pub parent: Scope<App>,
+}
+
+impl SavedProofs {
+    fn refresh(&mut self, ctx: &Context<Self>) {
+        self.proofs = None;
+        storage::current(&self.preferences).list(ctx.link().callback(SavedProofsMsg::Listed));
+    }
+
+    /// Open a proof in a new tab, loading it there
+    fn open_tab(&self, ctx: &Context<Self>, tab_name: String, name: String, version: Option<f64>) {
+        let parent = &ctx.props().parent;
+        let tab_name_ = tab_name.clone();
+        let oncreate = parent.callback(move |link| AppMsg::RegisterProofName { name: tab_name_.clone(), link });
+        parent.send_message(AppMsg::CreateTab { name: tab_name, content: html! { <ProofLoader name={ name } version={ version } oncreate={ oncreate } /> } });
+    }
+
+    fn render_proof(&self, ctx: &Context<Self>, proof: &StoredProof) -> Html {
+        let name = proof.name.clone();
+        let history_button = if self.preferences.storage == StorageBackend::IndexedDb {
+            let name = proof.name.clone();
+            let onclick = ctx.link().callback(move |e: MouseEvent| {
+                // Don't also open the proof
+                e.stop_propagation();
+                SavedProofsMsg::ShowHistory(name.clone())
+            });
+            html! { <button type="button" class="btn btn-link btn-sm p-0" onclick={ onclick }> { "History" } </button> }
+        } else {
+            html! {}
+        };
+        html! {
+            <div class="list-group-item list-group-item-action" role="button" onclick={ ctx.link().callback(move |_| SavedProofsMsg::Open(name.clone())) }>
+                <div class="d-flex justify-content-between align-items-center">
+                    <h5 class="mb-1"> { &proof.name } </h5>
+                    { history_button }
+                </div>
+                <small class="text-muted"> { details(proof) } </small>
+            </div>
+        }
+    }
+
+    fn render_history(&self, ctx: &Context<Self>, name: &str, versions: &Option<Listing>) -> Html {
+        let versions = match versions {
+            None => html! { <div class="spinner-border text-secondary" role="status"><span class="sr-only"> { "Loading\u{2026}" } </span></div> },
+            Some(Err(err)) => html! { <div class="alert alert-danger"> { err.to_string() } </div> },
+            Some(Ok(versions)) if versions.is_empty() => html! { <p class="text-muted"> { "No versions of this proof are kept." } </p> },
+            Some(Ok(versions)) => html! {
+                <div class="list-group">
+                    { for versions.iter().enumerate().map(|(i, version)| {
+                        let version_ = version.clone();
+                        // The first version is the latest one
+                        let compare_button = match (i, versions.first()) {
+                            (1.., Some(latest)) => {
+                                let (version, latest) = (version.clone(), latest.clone());
+                                let onclick = ctx.link().callback(move |e: MouseEvent| {
+                                    // Don't also open the version
+                                    e.stop_propagation();
+                                    SavedProofsMsg::CompareVersion(version.clone(), latest.clone())
+                                });
+                                html! { <button type="button" class="btn btn-link btn-sm p-0" onclick={ onclick }> { "Compare with latest" } </button> }
+                            }
+                            _ => html! {},
+                        };
+                        html! {
+                            <div class="list-group-item list-group-item-action d-flex justify-content-between align-items-center" role="button" onclick={ ctx.link().callback(move |_| SavedProofsMsg::OpenVersion(version_.clone())) }>
+                                <small> { details(version) } </small>
+                                { compare_button }
+                            </div>
+                        }
+                    }) }
+                </div>
+            },
+        };
+        let compare_error = self.compare_error.as_ref().map(|err| html! { <div class="alert alert-danger"> { format!("The versions couldn't be compared: {err}") } </div> });
+        html! {
+            <div class="m-4">
+                <div class="d-flex justify-content-between align-items-center">
+                    <h2> { format!("History of {name}") } </h2>
+                    <button type="button" class="btn btn-outline-secondary btn-sm" onclick={ ctx.link().callback(|_| SavedProofsMsg::HideHistory) }> { "Back to saved proofs" } </button>
+                </div>
+                <p class="text-muted"> { "The versions of the proof kept each time it was saved, most recent first." } </p>
+                { for compare_error }
+                { versions }
+            </div>
+        }
+    }
+}
+
+/// When a proof was saved, as the user's locale writes dates
+fn format_date(modified: f64) -> String {
+    js_sys::Date::new(&JsValue::from_f64(modified)).to_locale_string("default", &JsValue::UNDEFINED).into()
+}
+
+/// Author, date, and size of a saved proof
+fn details(proof: &StoredProof) -> String {
+    match &proof.author {
+        Some(author) => format!("{} \u{b7} {} \u{b7} {}", author, format_date(proof.modified), format_size(proof.size)),
+        None => format!("{} \u{b7} {}", format_date(proof.modified), format_size(proof.size)),
+    }
+}
+
+/// A size in bytes, written like "2.5 KB"
+fn format_size(size: usize) -> String {
+    match size {
+        0..=1023 => format!("{size} B"),
+        1024..=1_048_575 => format!("{:.1} KB", size as f64 / 1024.0),
+        _ => format!("{:.1} MB", size as f64 / 1_048_576.0),
+    }
+}
+
+impl Component for SavedProofs {
+    type Message = SavedProofsMsg;
+    type Properties = SavedProofsProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let (preferences, preferences_handle) = match ctx.link().context::<Preferences>(ctx.link().callback(SavedProofsMsg::PreferencesChanged)) {
+            Some((preferences, handle)) => (preferences, Some(handle)),
+            None => (Preferences::default(), None),
+        };
+        let mut saved_proofs = Self { proofs: None, history: None, compare_error: None, preferences, preferences_handle };
+        saved_proofs.refresh(ctx);
+        saved_proofs
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            SavedProofsMsg::Refresh => self.refresh(ctx),
+            SavedProofsMsg::Listed(proofs) => self.proofs = Some(proofs),
+            SavedProofsMsg::Open(name) => {
+                self.open_tab(ctx, name.clone(), name, None);
+                return false;
+            }
+            SavedProofsMsg::ShowHistory(name) => {
+                self.history = Some((name.clone(), None));
+                self.compare_error = None;
+                let name_ = name.clone();
+                IndexedDbStore.versions(&name, ctx.link().callback(move |versions| SavedProofsMsg::HistoryListed(name_.clone(), versions)));
+            }
+            SavedProofsMsg::HistoryListed(name, versions) => match &mut self.history {
+                // Ignore versions of a proof that's no longer shown
+                Some((shown, listed)) if *shown == name => *listed = Some(versions),
+                _ => return false,
+            },
+            SavedProofsMsg::HideHistory => self.history = None,
+            SavedProofsMsg::OpenVersion(version) => {
+                let tab_name = format!("{} ({})", version.name, format_date(version.modified));
+                self.open_tab(ctx, tab_name, version.name, Some(version.modified));
+                return false;
+            }
+            SavedProofsMsg::CompareVersion(version, latest) => {
+                // Load the versions one after the other, to have both at once
+                let link = ctx.link().clone();
+                let version_ = version.clone();
+                IndexedDbStore.load_version(
+                    &version.name,
+                    version.modified,
+                    Callback::from(move |old: Result<Vec<u8>, StoreError>| {
+                        let version = version_.clone();
+                        let loaded = link.callback(move |new: Result<Vec<u8>, StoreError>| SavedProofsMsg::VersionsLoaded(version.clone(), old.clone().and_then(|old| new.map(|new| (old, new)))));
+                        IndexedDbStore.load_version(&latest.name, latest.modified, loaded);
+                    }),
+                );
+                return false;
+            }
+            SavedProofsMsg::VersionsLoaded(version, loaded) => match loaded {
+                Ok((old, new)) => {
+                    self.compare_error = None;
+                    let name = format!("{} ({} vs. latest)", version.name, format_date(version.modified));
+                    let content = html! { <ProofDiffView left_name={ format_date(version.modified) } left={ old } right_name="Latest" right={ new } /> };
+                    ctx.props().parent.send_message(AppMsg::CreateTab { name, content });
+                }
+                Err(err) => self.compare_error = Some(err),
+            },
+            SavedProofsMsg::PreferencesChanged(preferences) => {
+                let store_changed = (&preferences.storage, &preferences.storage_url) != (&self.preferences.storage, &self.preferences.storage_url);
+                self.preferences = preferences;
+                if store_changed {
+                    self.history = None;
+                    self.refresh(ctx);
+                }
+            }
+        }
+        true
+    }
+
+    fn changed(&mut self, _: &Context<Self>, _: &Self::Properties) -> bool {
+        false
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        if let Some((name, versions)) = &self.history {
+            return self.render_history(ctx, name, versions);
+        }
+        let list = match &self.proofs {
+            None => html! { <div class="spinner-border text-secondary" role="status"><span class="sr-only"> { "Loading\u{2026}" } </span></div> },
+            Some(Err(err)) => html! { <div class="alert alert-danger"> { err.to_string() } </div> },
+            Some(Ok(proofs)) if proofs.is_empty() => html! { <p class="text-muted"> { "No proofs are saved here yet. Proofs are saved here with \"Save proof\" in the File menu." } </p> },
+            Some(Ok(proofs)) => html! { <div class="list-group"> { for proofs.iter().map(|proof| self.render_proof(ctx, proof)) } </div> },
+        };
+        html! {
+            <div class="m-4">
+                <div class="d-flex justify-content-between align-items-center">
+                    <h2> { "Saved proofs" } </h2>
+                    <button type="button" class="btn btn-outline-secondary btn-sm" onclick={ ctx.link().callback(|_| SavedProofsMsg::Refresh) }> { "Refresh" } </button>
+                </div>
+                <p class="text-muted"> { format!("Proofs saved in {}.", storage::current(&self.preferences).location()) } </p>
+                { list }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::components::expr_entry::ExprEntry;
+use crate::components::popover::Popover;
+use crate::progress;
+use crate::progress::Progress;
+
+use aris::parser::ParseError;
+use aris::parser::Syntax;
+use aris::soundness::Config;
+use aris::symbolization::Grade;
+use aris::symbolization::Symbolization;
+
+use std::collections::BTreeSet;
+
+use yew::prelude::*;
+
+/// Exercise where the user symbolizes an English sentence, using the symbols
+/// of its key. The answer is graded as it's typed, and a wrong answer comes
+/// with a case where it and the sentence differ.
+pub struct SymbolizationExercise {
+    /// Text of the formula entered so far
+    input: String,
+
+    /// Whether the formula entered is correct, so solving it again isn't
+    /// recorded twice
+    solved: bool,
+}
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct SymbolizationExerciseProps {
+    pub symbolization: Symbolization,
+
+    /// Callback to call when the exercise is solved
+    #[prop_or_default]
+    pub onsolved: Option<Callback<()>>,
+}
+
+/// How the formula entered compares to the sentence
+enum Feedback {
+    Empty,
+    Unparsed(ParseError),
+    Graded(Grade),
+}
+
+impl SymbolizationExercise {
+    fn feedback(&self, ctx: &Context<Self>) -> Feedback {
+        if self.input.trim().is_empty() {
+            return Feedback::Empty;
+        }
+        // The answers are written in the default syntax, whatever proof is
+        // open
+        match aris::parser::try_parse_with(&Syntax::default(), &self.input) {
+            Err(err) => Feedback::Unparsed(err),
+            Ok(answer) => Feedback::Graded(ctx.props().symbolization.grade(&answer, &Config::default())),
+        }
+    }
+
+    fn render_feedback(feedback: &Feedback) -> Html {
+        match feedback {
+            Feedback::Empty => html! {},
+            Feedback::Unparsed(err) => {
+                let label = if err.is_too_large() { "Too large" } else { "Parse error" };
+                html! { <span class="alert alert-warning small-alert" title={ err.to_string() }>{ label }</span> }
+            }
+            Feedback::Graded(Grade::Correct) => html! { <span class="alert small-alert bg-success text-white">{ "Correct" }</span> },
+            Feedback::Graded(grade) => {
+                // An answer that couldn't be compared may still be right
+                let (class, label) = if let Grade::Unchecked(_) = grade { ("btn btn-warning", "Unchecked") } else { ("btn btn-danger", "Error") };
+                html! { <Popover { label } { class } content={ grade.to_string() } /> }
+            }
+        }
+    }
+}
+
+impl Component for SymbolizationExercise {
+    type Message = String;
+    type Properties = SymbolizationExerciseProps;
+
+    fn create(_: &Context<Self>) -> Self {
+        Self { input: String::new(), solved: false }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, input: Self::Message) -> bool {
+        self.input = input;
+        let solved = matches!(self.feedback(ctx), Feedback::Graded(grade) if grade.is_correct());
+        if solved && !self.solved {
+            let props = ctx.props();
+            let mut progress = Progress::load();
+            progress.record_solved(progress::symbolization_key(&props.symbolization), &BTreeSet::new(), progress::today());
+            progress.save();
+            if let Some(onsolved) = &props.onsolved {
+                onsolved.emit(());
+            }
+        }
+        self.solved = solved;
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let symbolization = &ctx.props().symbolization;
+        let key = if symbolization.key.is_empty() {
+            html! {}
+        } else {
+            html! {
+                <dl class="row">
+                    { for symbolization.key.iter().map(|(symbol, meaning)| html! {
+                        <>
+                            <dt class="col-sm-1"> { symbol.clone() } </dt>
+                            <dd class="col-sm-11"> { meaning.clone() } </dd>
+                        </>
+                    }) }
+                </dl>
+            }
+        };
+        let feedback = self.feedback(ctx);
+        html! {
+            <div class="m-4">
+                <p> { "Symbolize this sentence:" } </p>
+                <blockquote class="blockquote"> { symbolization.sentence.clone() } </blockquote>
+                { key }
+                <div class="d-flex align-items-center">
+                    <div class="flex-grow-1 mr-2">
+                        <ExprEntry oninput={ ctx.link().callback(|value| value) } init_value={ self.input.clone() } id="symbolization-formula" />
+                    </div>
+                    { Self::render_feedback(&feedback) }
+                </div>
+            </div>
+        }
+    }
+}
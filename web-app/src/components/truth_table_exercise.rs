@@ -0,0 +1,177 @@
+use crate::components::expr_entry::ExprEntry;
+use crate::components::popover::Popover;
+use crate::progress;
+use crate::progress::Progress;
+
+use aris::expr::Expr;
+use aris::parser::ParseError;
+use aris::parser::Syntax;
+use aris::truth_table::Connective;
+use aris::truth_table::TruthTable;
+
+use std::collections::BTreeSet;
+
+use yew::prelude::*;
+
+/// Exercise where the user writes a formula with a given truth table, maybe
+/// using only some connectives. The formula's column is shown next to the
+/// target's as it's typed, with the rows where they differ highlighted.
+pub struct TruthTableExercise {
+    /// Text of the formula entered so far
+    input: String,
+
+    /// Whether the formula entered has the table, so solving it again isn't
+    /// recorded twice
+    solved: bool,
+}
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct TruthTableExerciseProps {
+    /// Table the formula has to have
+    pub table: TruthTable,
+
+    /// Connectives the formula may use, or any if this is empty
+    #[prop_or_default]
+    pub connectives: BTreeSet<Connective>,
+
+    /// Callback to call when the exercise is solved
+    #[prop_or_default]
+    pub onsolved: Option<Callback<()>>,
+}
+
+/// How the formula entered compares to the table
+enum Feedback {
+    Empty,
+    Unparsed(ParseError),
+    /// The formula can't be checked against the table, like when it uses a
+    /// connective it may not use
+    Invalid(String),
+    /// The formula's column, and the rows where it differs from the table
+    Checked {
+        table: TruthTable,
+        mismatched: Vec<usize>,
+    },
+}
+
+impl TruthTableExercise {
+    fn feedback(&self, ctx: &Context<Self>) -> Feedback {
+        if self.input.trim().is_empty() {
+            return Feedback::Empty;
+        }
+        // The problem's formulas are written in the default syntax, whatever
+        // proof is open
+        match aris::parser::try_parse_with(&Syntax::default(), &self.input) {
+            Err(err) => Feedback::Unparsed(err),
+            Ok(formula) => check(&formula, &ctx.props().table, &ctx.props().connectives).unwrap_or_else(Feedback::Invalid),
+        }
+    }
+
+    fn render_feedback(feedback: &Feedback) -> Html {
+        match feedback {
+            Feedback::Empty => html! {},
+            Feedback::Unparsed(err) => {
+                let label = if err.is_too_large() { "Too large" } else { "Parse error" };
+                html! { <span class="alert alert-warning small-alert" title={ err.to_string() }>{ label }</span> }
+            }
+            Feedback::Invalid(err) => html! { <Popover label="Error" class="btn btn-danger" content={ err.clone() } /> },
+            Feedback::Checked { mismatched, .. } if mismatched.is_empty() => html! { <span class="alert small-alert bg-success text-white">{ "Correct" }</span> },
+            Feedback::Checked { mismatched, .. } => {
+                let message = match mismatched.len() {
+                    1 => "The formula's value differs from the table's in 1 row.".to_string(),
+                    n => format!("The formula's value differs from the table's in {n} rows."),
+                };
+                html! { <Popover label="Error" class="btn btn-danger" content={ message } /> }
+            }
+        }
+    }
+
+    fn render_table(ctx: &Context<Self>, feedback: &Feedback) -> Html {
+        let target = &ctx.props().table;
+        let (formula, mismatched) = match feedback {
+            Feedback::Checked { table, mismatched } => (Some(table), mismatched.clone()),
+            _ => (None, vec![]),
+        };
+        let value = |value: bool| if value { "T" } else { "F" };
+        let rows = (0..target.values().len()).map(|row| {
+            let class = if mismatched.contains(&row) { "table-danger" } else { "" };
+            let formula_value = formula.map(|formula| value(formula.values()[row])).unwrap_or("");
+            html! {
+                <tr class={ class }>
+                    { for target.row(row).into_iter().map(|variable| html! { <td> { value(variable) } </td> }) }
+                    <td class="font-weight-bold"> { value(target.values()[row]) } </td>
+                    <td> { formula_value } </td>
+                </tr>
+            }
+        });
+        html! {
+            <table class="table table-sm table-bordered text-center w-auto">
+                <thead>
+                    <tr>
+                        { for target.variables().iter().map(|variable| html! { <th> { variable.clone() } </th> }) }
+                        <th> { "Target" } </th>
+                        <th> { "Your formula" } </th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { for rows }
+                </tbody>
+            </table>
+        }
+    }
+}
+
+/// Check a formula against the table, or say why it can't be
+fn check(formula: &Expr, table: &TruthTable, connectives: &BTreeSet<Connective>) -> Result<Feedback, String> {
+    let used = Connective::used_in(formula)?;
+    if !connectives.is_empty() {
+        let disallowed = used.difference(connectives).map(|connective| connective.symbol()).collect::<Vec<_>>();
+        if !disallowed.is_empty() {
+            return Err(format!("The formula may only use {}, not {}.", connectives.iter().map(|connective| connective.symbol()).collect::<Vec<_>>().join(", "), disallowed.join(", ")));
+        }
+    }
+    let mismatched = table.mismatched_rows(formula)?;
+    Ok(Feedback::Checked { table: TruthTable::of(formula, table.variables().to_vec())?, mismatched })
+}
+
+impl Component for TruthTableExercise {
+    type Message = String;
+    type Properties = TruthTableExerciseProps;
+
+    fn create(_: &Context<Self>) -> Self {
+        Self { input: String::new(), solved: false }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, input: Self::Message) -> bool {
+        self.input = input;
+        let solved = matches!(self.feedback(ctx), Feedback::Checked { mismatched, .. } if mismatched.is_empty());
+        if solved && !self.solved {
+            let props = ctx.props();
+            let mut progress = Progress::load();
+            progress.record_solved(progress::table_key(&props.table, &props.connectives), &BTreeSet::new(), progress::today());
+            progress.save();
+            if let Some(onsolved) = &props.onsolved {
+                onsolved.emit(());
+            }
+        }
+        self.solved = solved;
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let instructions = if props.connectives.is_empty() { "Write a formula with this truth table.".to_string() } else { format!("Write a formula with this truth table, using only {}.", props.connectives.iter().map(|connective| connective.symbol()).collect::<Vec<_>>().join(", ")) };
+        let feedback = self.feedback(ctx);
+        html! {
+            <div class="m-4">
+                <p> { instructions } </p>
+                { Self::render_table(ctx, &feedback) }
+                <div class="d-flex align-items-center">
+                    <div class="flex-grow-1 mr-2">
+                        <ExprEntry oninput={ ctx.link().callback(|value| value) } init_value={ self.input.clone() } id="truth-table-formula" />
+                    </div>
+                    { Self::render_feedback(&feedback) }
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Recovering from crashes
+//!
+//! A bug that panics leaves the app's WebAssembly unable to run, so the page
+//! would freeze without a word. `install()` sets a panic hook showing a dialog
+//! instead, which offers every open proof, as of its last edit, for download,
+//! along with a diagnostic report, and a button reloading the page. The dialog
+//! is built directly in the page, since the app's components no longer run.
+//!
+//! The report is built from the event log, which keeps the last `LOG_SIZE`
+//! things that happened, like the messages proof widgets handled and errors
+//! thrown by the page's scripts, along with the panic's message and where it
+//! happened. A report looks like:
+//!
+//! ```json
+//! {
+//!     "format": "aris-diagnostics",
+//!     "version": 1,
+//!     "time": 1700000000000,
+//!     "url": "https://example.edu/aris/",
+//!     "userAgent": "...",
+//!     "panic": "panicked at ...",
+//!     "events": [{ "time": 1699999999000, "kind": "proof", "message": "proof-0: ParseLine(...)" }]
+//! }
+//! ```
+//!
+//! The messages include what was typed into lines, but the proofs themselves
+//! are downloaded separately, so users can choose what they share.
+
+use crate::util::json_field;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+
+use gloo::events::EventListener;
+use wasm_bindgen::JsValue;
+
+/// Value of the `format` field of diagnostic reports
+const FORMAT: &str = "aris-diagnostics";
+
+/// Version of the diagnostic report format
+const VERSION: u32 = 1;
+
+/// Number of events the log keeps
+const LOG_SIZE: usize = 200;
+
+/// Longest message the log keeps, in bytes, so logging a pasted proof
+/// doesn't crowd out everything else
+const MAX_MESSAGE_LEN: usize = 500;
+
+/// Something that happened, in the event log
+struct LogEntry {
+    /// When it happened, in milliseconds since the Unix epoch
+    time: f64,
+
+    /// What sort of thing it was, like `"proof"` or `"error"`
+    kind: &'static str,
+
+    message: String,
+}
+
+thread_local! {
+    static LOG: RefCell<VecDeque<LogEntry>> = const { RefCell::new(VecDeque::new()) };
+
+    /// Every open proof as of its last edit, in the `.bram` format, by the ID
+    /// of its widget
+    static SNAPSHOTS: RefCell<BTreeMap<String, Vec<u8>>> = const { RefCell::new(BTreeMap::new()) };
+
+    static INSTALLED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Add an event to the log
+pub fn log(kind: &'static str, mut message: String) {
+    if message.len() > MAX_MESSAGE_LEN {
+        let mut end = MAX_MESSAGE_LEN;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        message.truncate(end);
+        message.push('\u{2026}');
+    }
+    // The log may be in use if logging is what panicked
+    LOG.with(|log| {
+        if let Ok(mut log) = log.try_borrow_mut() {
+            if log.len() == LOG_SIZE {
+                log.pop_front();
+            }
+            log.push_back(LogEntry { time: js_sys::Date::now(), kind, message });
+        }
+    });
+}
+
+/// Keep a proof, in the `.bram` format, to offer if the app crashes
+pub fn autosave(id: &str, data: Vec<u8>) {
+    SNAPSHOTS.with(|snapshots| snapshots.borrow_mut().insert(id.to_string(), data));
+}
+
+/// Stop keeping a proof that was closed
+pub fn forget(id: &str) {
+    SNAPSHOTS.with(|snapshots| snapshots.borrow_mut().remove(id));
+}
+
+/// Show the recovery dialog on panics, and log errors thrown by the page's
+/// scripts. Installing it more than once does nothing.
+pub fn install() {
+    if INSTALLED.with(|installed| installed.replace(true)) {
+        return;
+    }
+    std::panic::set_hook(Box::new(|info| {
+        let message = info.to_string();
+        gloo::console::error!(message.clone());
+        log("panic", message.clone());
+        if let Err(err) = show_recovery_dialog(&message) {
+            gloo::console::error!("Could not show the recovery dialog:", err);
+        }
+    }));
+    if let Some(window) = web_sys::window() {
+        EventListener::new(&window, "error", |event| log("error", describe(json_field(event, "error").or_else(|| json_field(event, "message"))))).forget();
+        EventListener::new(&window, "unhandledrejection", |event| log("error", describe(json_field(event, "reason")))).forget();
+    }
+    log("start", format!("Aris {}", env!("CARGO_PKG_VERSION")));
+}
+
+/// A thrown value, as text
+fn describe(value: Option<JsValue>) -> String {
+    match value {
+        Some(value) => value.as_string().unwrap_or_else(|| String::from(js_sys::Object::from(value).to_string())),
+        None => "Unknown error".into(),
+    }
+}
+
+/// The diagnostic report, as JSON
+fn diagnostic_report(panic: &str) -> Result<String, JsValue> {
+    let set = |object: &js_sys::Object, field: &str, value: JsValue| js_sys::Reflect::set(object, &JsValue::from_str(field), &value).map(|_| ());
+    let report = js_sys::Object::new();
+    set(&report, "format", FORMAT.into())?;
+    set(&report, "version", VERSION.into())?;
+    set(&report, "time", js_sys::Date::now().into())?;
+    if let Some(window) = web_sys::window() {
+        set(&report, "url", window.location().href()?.into())?;
+        set(&report, "userAgent", window.navigator().user_agent()?.into())?;
+    }
+    set(&report, "panic", panic.into())?;
+    let events = js_sys::Array::new();
+    LOG.with(|log| -> Result<(), JsValue> {
+        if let Ok(log) = log.try_borrow() {
+            for entry in log.iter() {
+                let event = js_sys::Object::new();
+                set(&event, "time", entry.time.into())?;
+                set(&event, "kind", entry.kind.into())?;
+                set(&event, "message", entry.message.as_str().into())?;
+                events.push(&event);
+            }
+        }
+        Ok(())
+    })?;
+    set(&report, "events", events.into())?;
+    js_sys::JSON::stringify_with_replacer_and_space(&report, &JsValue::NULL, &JsValue::from(4)).map(String::from)
+}
+
+/// Show a dialog for saving what can be saved after a panic. It only uses
+/// the page's own scripts, like links to files made up front, since the
+/// app's code can't be run anymore.
+fn show_recovery_dialog(panic: &str) -> Result<(), JsValue> {
+    let document = web_sys::window().and_then(|window| window.document()).ok_or("no document")?;
+    let body = document.body().ok_or("no body")?;
+    let element = |tag: &str, class: &str, text: &str| -> Result<web_sys::Element, JsValue> {
+        let element = document.create_element(tag)?;
+        element.set_class_name(class);
+        if !text.is_empty() {
+            element.set_text_content(Some(text));
+        }
+        Ok(element)
+    };
+    let download_link = |name: &str, text: &str, data: &str| -> Result<web_sys::Element, JsValue> {
+        let blob = web_sys::Blob::new_with_str_sequence(&js_sys::Array::of1(&JsValue::from_str(data)))?;
+        let link = element("a", "", text)?;
+        link.set_attribute("href", &web_sys::Url::create_object_url_with_blob(&blob)?)?;
+        link.set_attribute("download", name)?;
+        Ok(link)
+    };
+
+    let modal = element("div", "modal d-block", "")?;
+    modal.set_attribute("role", "alertdialog")?;
+    modal.set_attribute("aria-modal", "true")?;
+    modal.set_attribute("aria-labelledby", "crash-dialog-title")?;
+    let dialog = element("div", "modal-dialog", "")?;
+    let content = element("div", "modal-content", "")?;
+    let header = element("div", "modal-header", "")?;
+    let title = element("h5", "modal-title", "Aris stopped working")?;
+    title.set_id("crash-dialog-title");
+    header.append_child(&title)?;
+    content.append_child(&header)?;
+
+    let modal_body = element("div", "modal-body", "")?;
+    let snapshots = SNAPSHOTS.with(|snapshots| snapshots.try_borrow().map(|snapshots| snapshots.values().cloned().collect::<Vec<_>>()).unwrap_or_default());
+    if snapshots.is_empty() {
+        modal_body.append_child(element("p", "", "Aris ran into a bug and can't go on. Reload the page to start over.")?.as_ref())?;
+    } else {
+        modal_body.append_child(element("p", "", "Aris ran into a bug and can't go on. Download your proofs, as they were after your last edit, before reloading the page.")?.as_ref())?;
+        let list = element("ul", "", "")?;
+        for (i, data) in snapshots.iter().enumerate() {
+            let item = element("li", "", "")?;
+            let name = format!("recovered-proof-{}.bram", i + 1);
+            item.append_child(download_link(&name, &name, &String::from_utf8_lossy(data))?.as_ref())?;
+            list.append_child(&item)?;
+        }
+        modal_body.append_child(&list)?;
+    }
+    let report = element("p", "small text-muted mb-0", "To help fix the bug, send the ")?;
+    report.append_child(download_link("aris-diagnostics.json", "diagnostic report", &diagnostic_report(panic)?)?.as_ref())?;
+    report.append_child(element("span", "", " to whoever runs this site. It lists what you did before the crash, including what you typed.")?.as_ref())?;
+    modal_body.append_child(&report)?;
+    content.append_child(&modal_body)?;
+
+    let footer = element("div", "modal-footer", "")?;
+    let reload = element("button", "btn btn-primary", "Reload")?;
+    reload.set_attribute("type", "button")?;
+    reload.set_attribute("onclick", "location.reload()")?;
+    footer.append_child(&reload)?;
+    content.append_child(&footer)?;
+    dialog.append_child(&content)?;
+    modal.append_child(&dialog)?;
+
+    body.append_child(element("div", "modal-backdrop show", "")?.as_ref())?;
+    body.append_child(&modal)?;
+    Ok(())
+}
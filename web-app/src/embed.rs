@@ -0,0 +1,87 @@
+//! Embedding proofs in other sites, like course pages and online textbooks
+//!
+//! Host pages load the web app's module, without its `index.html`, and mount
+//! proofs into their own elements:
+//!
+//! ```js
+//! import init, { ArisEmbed } from "./pkg/aris_web_app.js";
+//!
+//! await init();
+//! const proof = ArisEmbed.mount(document.getElementById("exercise"), {
+//!     data: xml,           // proof to show, in the `.bram` format; empty if left out
+//!     readOnly: false,     // show the proof without letting it be edited
+//!     onChange: (xml) => console.log("changed", xml),
+//!     onComplete: () => console.log("solved"),
+//! });
+//! // Later, to remove it from the page
+//! proof.unmount();
+//! ```
+//!
+//! `onChange` is called with the proof, in the `.bram` format, whenever it
+//! changes, and `onComplete` whenever it becomes solved. The page has to load
+//! Bootstrap's CSS and `styles.css` like `index.html` does, since the proof
+//! widget is styled with them. Rule packs are
+//! registered with `registerRulePlugin()` before mounting proofs that use
+//! them, as described in the `plugins` module, and rules are renamed with
+//! `setRuleNames()`, as described in the `rule_names` module. Usage analytics
+//! are only recorded if the page calls `setAnalyticsSink()`, as described in
+//! the `analytics` module. If a bug crashes the app, a dialog offers the
+//! proofs for download, as described in the `crash` module.
+
+use crate::components::proof_widget::ProofWidget;
+use crate::components::proof_widget::ProofWidgetProps;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use yew::AppHandle;
+use yew::Callback;
+
+/// Entry point for embedding proofs, as `ArisEmbed` in JavaScript
+#[wasm_bindgen]
+pub struct ArisEmbed;
+
+/// A proof mounted with `ArisEmbed.mount()`
+#[wasm_bindgen]
+pub struct EmbeddedProof {
+    app: AppHandle<ProofWidget>,
+}
+
+/// Function in the options, if it's there
+fn option_function(options: &JsValue, name: &str) -> Result<Option<js_sys::Function>, JsValue> {
+    match crate::util::json_field(options, name) {
+        Some(value) => value.dyn_into::<js_sys::Function>().map(Some).map_err(|_| JsValue::from_str(&format!("ArisEmbed.mount: {name} must be a function"))),
+        None => Ok(None),
+    }
+}
+
+#[wasm_bindgen]
+impl ArisEmbed {
+    /// Mount a proof into `element`, replacing nothing else on the page. See
+    /// the module documentation for the options.
+    pub fn mount(element: web_sys::Element, options: JsValue) -> Result<EmbeddedProof, JsValue> {
+        crate::crash::install();
+        let data = crate::util::json_field(&options, "data").map(|data| data.as_string().ok_or("ArisEmbed.mount: data must be a string")).transpose()?;
+        let read_only = crate::util::json_field(&options, "readOnly").map(|read_only| read_only.is_truthy()).unwrap_or(false);
+        let onchange = option_function(&options, "onChange")?.map(|f| {
+            Callback::from(move |data: Vec<u8>| {
+                let _ = f.call1(&JsValue::NULL, &JsValue::from_str(&String::from_utf8_lossy(&data)));
+            })
+        });
+        let onsolved = option_function(&options, "onComplete")?.map(|f| {
+            Callback::from(move |()| {
+                let _ = f.call0(&JsValue::NULL);
+            })
+        });
+        let props = ProofWidgetProps { verbose: true, data: data.map(String::into_bytes), oncreate: Callback::noop(), tutorial: None, onsolved, onchange, read_only, scratch: false, ontransplant: None };
+        let app = yew::Renderer::<ProofWidget>::with_root_and_props(element, props).render();
+        Ok(EmbeddedProof { app })
+    }
+}
+
+#[wasm_bindgen]
+impl EmbeddedProof {
+    /// Remove the proof from the page
+    pub fn unmount(self) {
+        self.app.destroy();
+    }
+}
@@ -1,14 +1,31 @@
 #![recursion_limit = "1024"]
 
+mod analytics;
 mod box_chars;
 mod components;
+mod crash;
+mod embed;
+mod offline;
+mod plugins;
+mod preferences;
+mod problems;
+mod progress;
+mod proof_image;
 mod proof_ui_data;
+mod rule_names;
+mod signing;
+mod storage;
+mod telemetry;
 mod util;
 
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen(start)]
+/// Run the whole app in the page's body. Pages embedding proofs use
+/// `ArisEmbed` from the `embed` module instead.
+#[wasm_bindgen]
 pub fn run_app() -> Result<(), JsValue> {
+    crash::install();
+    offline::register_service_worker();
     yew::Renderer::<components::app::App>::new().render();
     Ok(())
 }
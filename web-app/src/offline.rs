@@ -0,0 +1,35 @@
+//! Running the app without a network connection
+//!
+//! `static/service-worker.js` caches every file the app needs, so once the app
+//! has been opened, it opens again offline. Everything else the app uses is
+//! built into it, like the rules, their schemata in the rule reference, the
+//! built-in problems, and the tutorials, so proofs can be written and checked
+//! offline. Only saving to and opening from a server needs the network.
+
+use wasm_bindgen::JsValue;
+
+/// URL of the service worker, relative to `index.html`
+const SERVICE_WORKER_URL: &str = "service-worker.js";
+
+/// Register the service worker caching the app for offline use, in browsers
+/// that support it
+pub fn register_service_worker() {
+    let navigator = match web_sys::window() {
+        Some(window) => window.navigator(),
+        None => return,
+    };
+    // Service workers are missing in old browsers, and in pages not served
+    // over HTTPS or from localhost
+    if !js_sys::Reflect::has(&navigator, &JsValue::from_str("serviceWorker")).unwrap_or(false) {
+        return;
+    }
+    // If registering fails, the app still works, just not offline
+    let _ = navigator.service_worker().register(SERVICE_WORKER_URL);
+}
+
+/// Whether the browser thinks it's connected to a network. Browsers may say
+/// they're online when they can't actually reach anything, but never the
+/// other way around.
+pub fn is_online() -> bool {
+    web_sys::window().map(|window| window.navigator().on_line()).unwrap_or(true)
+}
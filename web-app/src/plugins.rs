@@ -0,0 +1,169 @@
+//! Rule packs compiled to WebAssembly
+//!
+//! Sites serving the app can add rules by listing rule pack modules in a
+//! `plugins.json` next to `index.html`, like `["rules/mycourse.wasm"]`.
+//! `index.html` instantiates each module, without any imports, and registers
+//! it with `registerRulePlugin(instance.exports)` before starting the app, so
+//! the rules are in the menus from the start. Pages embedding proofs can do
+//! the same before mounting them.
+//!
+//! Modules talk to the app with JSON, in UTF-8 strings in their memory. A
+//! string the module returns is a pointer to its length, as a little-endian
+//! `u32`, followed by its bytes. Modules export:
+//!
+//! - `memory`
+//! - `aris_alloc(len: i32) -> i32`, for the app to write `len` bytes to
+//! - `aris_rules() -> i32`, returning a list of rules, each described as in
+//!   `aris::plugins::RuleDescription`
+//! - `aris_check(ptr: i32, len: i32) -> i32`, checking a step described by the
+//!   JSON at `ptr`, and returning an empty string if it's correct, or an error
+//!   message otherwise. Steps are described like:
+//!
+//! ```json
+//! {
+//!     "rule": "MYCOURSE_TRANSITIVITY",
+//!     "conclusion": "(A → C)",
+//!     "deps": ["(A → B)", "(B → C)"],
+//!     "subproofs": [{ "premises": ["A"], "lines": ["B", "C"] }]
+//! }
+//! ```
+//!
+//! Formulas are written as they're displayed in proofs.
+
+use aris::expr::Expr;
+use aris::plugins::PluginSubproof;
+use aris::plugins::RuleDescription;
+use aris::plugins::RulePlugin;
+use aris::rules::RuleClassification;
+use aris::rules::RuleSchema;
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+/// Exports of a rule pack module
+struct Module {
+    memory: js_sys::WebAssembly::Memory,
+    alloc: js_sys::Function,
+    check: js_sys::Function,
+}
+
+thread_local! {
+    static MODULES: RefCell<Vec<Module>> = const { RefCell::new(vec![]) };
+}
+
+/// An exported function of a module
+fn export<T: JsCast>(exports: &JsValue, name: &str) -> Result<T, JsValue> {
+    crate::util::json_field(exports, name).and_then(|export| export.dyn_into::<T>().ok()).ok_or_else(|| JsValue::from_str(&format!("Rule plugin doesn't export {name}")))
+}
+
+impl Module {
+    /// Read a length-prefixed string the module returned
+    fn read_string(&self, ptr: JsValue) -> Result<String, String> {
+        let ptr = ptr.as_f64().ok_or("Rule plugin returned a non-number")? as u32;
+        let bytes = js_sys::Uint8Array::new(&self.memory.buffer());
+        let end = |len: u32| ptr.checked_add(4)?.checked_add(len).filter(|&end| end <= bytes.length());
+        let len_bytes = bytes.subarray(ptr, end(0).ok_or("Rule plugin returned a string out of bounds")?).to_vec();
+        let len = u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]);
+        let text = bytes.subarray(ptr + 4, end(len).ok_or("Rule plugin returned a string out of bounds")?).to_vec();
+        String::from_utf8(text).map_err(|_| "Rule plugin returned a string that isn't UTF-8".to_string())
+    }
+
+    /// Copy a string into the module's memory, returning where it is
+    fn write_string(&self, text: &str) -> Result<u32, String> {
+        let len = JsValue::from(text.len() as u32);
+        let ptr = self.alloc.call1(&JsValue::NULL, &len).map_err(|_| "Rule plugin failed to allocate")?.as_f64().ok_or("Rule plugin allocated a non-number")? as u32;
+        let bytes = js_sys::Uint8Array::new(&self.memory.buffer());
+        if ptr.checked_add(text.len() as u32).map(|end| end > bytes.length()).unwrap_or(true) {
+            return Err("Rule plugin allocated out of bounds".into());
+        }
+        bytes.set(&js_sys::Uint8Array::from(text.as_bytes()), ptr);
+        Ok(ptr)
+    }
+}
+
+/// A rule of a rule pack module
+struct WasmRule {
+    /// Index of the module in `MODULES`
+    module: usize,
+    description: RuleDescription,
+    classifications: HashSet<RuleClassification>,
+    schema: Option<RuleSchema>,
+}
+
+impl RulePlugin for WasmRule {
+    fn name(&self) -> String {
+        self.description.name.clone()
+    }
+    fn serialized_name(&self) -> String {
+        self.description.serialized_name.clone()
+    }
+    fn description(&self) -> String {
+        self.description.description.clone()
+    }
+    fn schema(&self) -> Option<RuleSchema> {
+        self.schema.clone()
+    }
+    fn classifications(&self) -> HashSet<RuleClassification> {
+        self.classifications.clone()
+    }
+    fn num_deps(&self) -> Option<usize> {
+        self.description.num_deps
+    }
+    fn num_subdeps(&self) -> Option<usize> {
+        self.description.num_subdeps
+    }
+    fn check(&self, conclusion: &Expr, deps: &[Expr], subproofs: &[PluginSubproof]) -> Result<(), String> {
+        let exprs = |exprs: &[Expr]| exprs.iter().map(|expr| JsValue::from_str(&expr.to_string())).collect::<js_sys::Array>();
+        let object = js_sys::Object::new();
+        let set = |object: &js_sys::Object, field: &str, value: JsValue| {
+            let _ = js_sys::Reflect::set(object, &JsValue::from_str(field), &value);
+        };
+        set(&object, "rule", JsValue::from_str(&self.description.serialized_name));
+        set(&object, "conclusion", JsValue::from_str(&conclusion.to_string()));
+        set(&object, "deps", exprs(deps).into());
+        let subproofs = subproofs.iter().map(|subproof| {
+            let object = js_sys::Object::new();
+            set(&object, "premises", exprs(&subproof.premises).into());
+            set(&object, "lines", exprs(&subproof.lines).into());
+            JsValue::from(object)
+        });
+        set(&object, "subproofs", subproofs.collect::<js_sys::Array>().into());
+        let request = js_sys::JSON::stringify(&object).map(String::from).map_err(|_| "Could not describe the step to the rule plugin")?;
+
+        let error = MODULES.with(|modules| {
+            let modules = modules.borrow();
+            let module = &modules[self.module];
+            let ptr = module.write_string(&request)?;
+            let result = module.check.call2(&JsValue::NULL, &JsValue::from(ptr), &JsValue::from(request.len() as u32)).map_err(|_| "Rule plugin failed to check the step")?;
+            module.read_string(result)
+        })?;
+        if error.is_empty() {
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+}
+
+/// Register the rules of a rule pack module, given its exports
+#[wasm_bindgen(js_name = registerRulePlugin)]
+pub fn register_rule_plugin(exports: JsValue) -> Result<(), JsValue> {
+    let module = Module { memory: export(&exports, "memory")?, alloc: export(&exports, "aris_alloc")?, check: export(&exports, "aris_check")? };
+    let rules = export::<js_sys::Function>(&exports, "aris_rules")?.call0(&JsValue::NULL)?;
+    let descriptions = RuleDescription::list_from_json(&module.read_string(rules)?)?;
+    let index = MODULES.with(|modules| {
+        let mut modules = modules.borrow_mut();
+        modules.push(module);
+        modules.len() - 1
+    });
+    for description in descriptions {
+        // The descriptions were checked when they were read
+        let classifications = description.parse_classifications()?;
+        let schema = description.schema.as_ref().map(|schema| schema.parse()).transpose()?;
+        aris::plugins::register(Box::new(WasmRule { module: index, description, classifications, schema }))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,281 @@
+//! User preferences for how proofs are displayed, edited, and saved
+//!
+//! Preferences are saved in the browser's `localStorage`, so they carry over
+//! between visits, and are handed down to every component through a Yew
+//! context provided by `App`.
+
+use crate::util::local_storage;
+
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Prefix of the `localStorage` keys of preferences
+const STORAGE_PREFIX: &str = "aris-";
+
+/// Allowed formula font sizes, in pixels
+pub const FONT_SIZE_RANGE: RangeInclusive<u32> = 10..=40;
+
+/// Allowed sizes of the subproof indent guides, in pixels
+pub const INDENT_SIZE_RANGE: RangeInclusive<u32> = 15..=60;
+
+/// Spacing between the rows of a proof
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Density {
+    /// Rows as close together as their contents allow, for long proofs
+    Compact,
+    /// Some room between rows
+    Comfortable,
+}
+
+impl Density {
+    /// Name of the density, used for saving it and in CSS class names
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Density::Compact => "compact",
+            Density::Comfortable => "comfortable",
+        }
+    }
+}
+
+impl FromStr for Density {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "compact" => Ok(Density::Compact),
+            "comfortable" => Ok(Density::Comfortable),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Set of keyboard shortcuts used in the proof editor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Keymap {
+    /// <kbd>Ctrl</kbd> shortcuts for line actions, with the arrow keys moving
+    /// between lines
+    Default,
+    /// Vim-style normal and insert modes, on top of the default shortcuts
+    Vim,
+}
+
+impl Keymap {
+    /// Name of the keymap, used for saving it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Keymap::Default => "default",
+            Keymap::Vim => "vim",
+        }
+    }
+}
+
+impl FromStr for Keymap {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Keymap::Default),
+            "vim" => Ok(Keymap::Vim),
+            _ => Err(()),
+        }
+    }
+}
+
+/// When the rules of a proof's lines are checked
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verification {
+    /// Check every line as it's edited
+    Live,
+    /// Check the proof only when asked to, or when saving it, so feedback can
+    /// be held back until the proof is done
+    OnDemand,
+}
+
+impl Verification {
+    /// Name of the verification mode, used for saving it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verification::Live => "live",
+            Verification::OnDemand => "on-demand",
+        }
+    }
+}
+
+impl FromStr for Verification {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "live" => Ok(Verification::Live),
+            "on-demand" => Ok(Verification::OnDemand),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What a newly inserted step starts out as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NewStep {
+    /// A blank step without a rule
+    Empty,
+    /// A reiteration of the line above, when it can be cited
+    Reiteration,
+    /// A blank step with the rule of the step above, for runs of steps using
+    /// the same rule
+    SameRule,
+}
+
+impl NewStep {
+    /// Name of the kind of new step, used for saving it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NewStep::Empty => "empty",
+            NewStep::Reiteration => "reiteration",
+            NewStep::SameRule => "same-rule",
+        }
+    }
+}
+
+impl FromStr for NewStep {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "empty" => Ok(NewStep::Empty),
+            "reiteration" => Ok(NewStep::Reiteration),
+            "same-rule" => Ok(NewStep::SameRule),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Where proofs are saved to and opened from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageBackend {
+    /// The browser's `localStorage`, which is small but always available
+    LocalStorage,
+    /// The browser's IndexedDB, which holds much more
+    IndexedDb,
+    /// A server, at `Preferences::storage_url`
+    Remote,
+}
+
+impl StorageBackend {
+    /// Name of the backend, used for saving it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StorageBackend::LocalStorage => "local-storage",
+            StorageBackend::IndexedDb => "indexed-db",
+            StorageBackend::Remote => "remote",
+        }
+    }
+}
+
+impl FromStr for StorageBackend {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "local-storage" => Ok(StorageBackend::LocalStorage),
+            "indexed-db" => Ok(StorageBackend::IndexedDb),
+            "remote" => Ok(StorageBackend::Remote),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Display and editing preferences of the user
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Preferences {
+    /// Show the formulas of the lines that each step cites next to its
+    /// dependency badges
+    pub show_cited_formulas: bool,
+
+    /// Color each subproof cited by a step, like the subproof of an →I line,
+    /// along with the step discharging it
+    pub show_discharges: bool,
+
+    /// Spacing between the rows of a proof
+    pub density: Density,
+
+    /// Font size of formulas, in pixels
+    pub font_size: u32,
+
+    /// Size of the box-drawing characters that show how subproofs are
+    /// nested, in pixels
+    pub indent_size: u32,
+
+    /// Keyboard shortcuts of the proof editor
+    pub keymap: Keymap,
+
+    /// When lines are checked
+    pub verification: Verification,
+
+    /// What inserted steps start out as
+    pub new_step: NewStep,
+
+    /// Move on to the next line when pressing Enter in a correct line,
+    /// inserting one if there's no blank line to move to
+    pub advance_on_enter: bool,
+
+    /// Where proofs are saved to and opened from
+    pub storage: StorageBackend,
+
+    /// Base URL of the server proofs are saved to, with `StorageBackend::Remote`
+    pub storage_url: String,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self { show_cited_formulas: false, show_discharges: false, density: Density::Comfortable, font_size: 16, indent_size: 35, keymap: Keymap::Default, verification: Verification::Live, new_step: NewStep::Empty, advance_on_enter: false, storage: StorageBackend::IndexedDb, storage_url: String::new() }
+    }
+}
+
+fn load_value(key: &str) -> Option<String> {
+    local_storage()?.get_item(&format!("{STORAGE_PREFIX}{key}")).ok()?
+}
+
+fn save_value(key: &str, value: &str) {
+    if let Some(storage) = local_storage() {
+        // Not being able to save (e.g. in private browsing) only means the
+        // preference is forgotten on the next visit
+        let _ = storage.set_item(&format!("{STORAGE_PREFIX}{key}"), value);
+    }
+}
+
+impl Preferences {
+    /// Load the saved preferences, using the defaults for any that weren't
+    /// saved or can't be read
+    pub fn load() -> Self {
+        Self::from_values(load_value)
+    }
+
+    /// Read preferences from the keys and values they're saved with, like
+    /// those of `Preferences::to_settings()`, using the defaults for any that
+    /// are missing or can't be read
+    pub fn from_settings(settings: &BTreeMap<String, String>) -> Self {
+        Self::from_values(|key| settings.get(key).cloned())
+    }
+
+    fn from_values(value: impl Fn(&str) -> Option<String>) -> Self {
+        fn parse<T: FromStr>(value: Option<String>) -> Option<T> {
+            value?.parse().ok()
+        }
+        let default = Self::default();
+        Self { show_cited_formulas: parse(value("show-cited-formulas")).unwrap_or(default.show_cited_formulas), show_discharges: parse(value("show-discharges")).unwrap_or(default.show_discharges), density: parse(value("density")).unwrap_or(default.density), font_size: parse(value("font-size")).filter(|size| FONT_SIZE_RANGE.contains(size)).unwrap_or(default.font_size), indent_size: parse(value("indent-size")).filter(|size| INDENT_SIZE_RANGE.contains(size)).unwrap_or(default.indent_size), keymap: parse(value("keymap")).unwrap_or(default.keymap), verification: parse(value("verification")).unwrap_or(default.verification), new_step: parse(value("new-step")).unwrap_or(default.new_step), advance_on_enter: parse(value("advance-on-enter")).unwrap_or(default.advance_on_enter), storage: parse(value("storage")).unwrap_or(default.storage), storage_url: parse(value("storage-url")).unwrap_or(default.storage_url) }
+    }
+
+    /// The preferences as the keys and values they're saved with
+    pub fn to_settings(&self) -> BTreeMap<String, String> {
+        let settings = [("show-cited-formulas", self.show_cited_formulas.to_string()), ("show-discharges", self.show_discharges.to_string()), ("density", self.density.as_str().into()), ("font-size", self.font_size.to_string()), ("indent-size", self.indent_size.to_string()), ("keymap", self.keymap.as_str().into()), ("verification", self.verification.as_str().into()), ("new-step", self.new_step.as_str().into()), ("advance-on-enter", self.advance_on_enter.to_string()), ("storage", self.storage.as_str().into()), ("storage-url", self.storage_url.clone())];
+        settings.into_iter().map(|(key, value)| (key.to_string(), value)).collect()
+    }
+
+    /// Save the preferences, for `Preferences::load()` to find on the next visit
+    pub fn save(&self) {
+        for (key, value) in self.to_settings() {
+            save_value(&key, &value);
+        }
+    }
+}
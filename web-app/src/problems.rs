@@ -0,0 +1,224 @@
+//! Practice problems, loaded from a JSON manifest
+//!
+//! A manifest lists problems with their premises, goal, topics, and
+//! difficulty from 1 (easiest) to 5:
+//!
+//! ```json
+//! {
+//!     "problems": [
+//!         {
+//!             "id": "modus-ponens",
+//!             "title": "Modus ponens",
+//!             "premises": ["P -> Q", "P"],
+//!             "goal": "Q",
+//!             "topics": ["conditionals"],
+//!             "difficulty": 1
+//!         }
+//!     ]
+//! }
+//! ```
+//!
+//! Instead of a proof, a problem can ask for a formula with a truth table,
+//! given by its variables and its column of values from the first row to the
+//! last, as `aris::truth_table::TruthTable::parse` reads them. The formula can
+//! be limited to some connectives, to practice truth-functional completeness:
+//!
+//! ```json
+//! {
+//!     "id": "xor-from-and-not",
+//!     "title": "Exclusive or from ∧ and ¬",
+//!     "variables": ["P", "Q"],
+//!     "table": "FTTF",
+//!     "connectives": ["¬", "∧"],
+//!     "topics": ["truth tables"],
+//!     "difficulty": 3
+//! }
+//! ```
+//!
+//! A problem can also ask to symbolize an English sentence, with a key saying
+//! what the symbols mean. Any answer equivalent to the given formula is
+//! correct, as `aris::symbolization` grades it:
+//!
+//! ```json
+//! {
+//!     "id": "only-if",
+//!     "title": "Only if",
+//!     "sentence": "It rains only if the ground is wet.",
+//!     "key": { "R": "it rains", "W": "the ground is wet" },
+//!     "answer": "R -> W",
+//!     "topics": ["symbolization"],
+//!     "difficulty": 2
+//! }
+//! ```
+//!
+//! The app comes with a manifest of its own, and users can load others.
+
+use crate::progress;
+use crate::util::json_field;
+use crate::util::P;
+
+use aris::expr::Expr;
+use aris::parser::Syntax;
+use aris::proofs::xml_interop;
+use aris::proofs::Justification;
+use aris::proofs::Proof;
+use aris::rules::RuleM;
+use aris::symbolization::Symbolization;
+use aris::truth_table::Connective;
+use aris::truth_table::TruthTable;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use wasm_bindgen::JsValue;
+
+/// Manifest of the problems that come with the app
+const BUILTIN_MANIFEST: &str = include_str!("../static/problems.json");
+
+/// Highest difficulty of a problem
+pub const MAX_DIFFICULTY: u32 = 5;
+
+/// What a problem asks for
+#[derive(Clone, Debug, PartialEq)]
+pub enum Task {
+    /// A proof of `goal` from `premises`
+    Proof { premises: Vec<Expr>, goal: Expr },
+    /// A formula with this truth table, using only `connectives` if there are
+    /// any
+    TruthTable { table: TruthTable, connectives: BTreeSet<Connective> },
+    /// A formula saying what an English sentence does
+    Symbolization(Symbolization),
+}
+
+/// A practice problem
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    /// Identifier of the problem, unique within its manifest
+    pub id: String,
+    pub title: String,
+    pub task: Task,
+    /// Topics the problem practices, like `"conditionals"`
+    pub topics: Vec<String>,
+    /// Difficulty from 1 (easiest) to `MAX_DIFFICULTY`
+    pub difficulty: u32,
+}
+
+/// The problems that come with the app
+pub fn builtin() -> Vec<Problem> {
+    parse_manifest(BUILTIN_MANIFEST).expect("invalid built-in problem manifest")
+}
+
+/// Parse a problem manifest, in the format described in the module
+/// documentation
+pub fn parse_manifest(text: &str) -> Result<Vec<Problem>, String> {
+    let manifest = js_sys::JSON::parse(text).map_err(|_| "The manifest isn't valid JSON".to_string())?;
+    let problems = json_field(&manifest, "problems").ok_or("The manifest has no \"problems\" list")?;
+    if !js_sys::Array::is_array(&problems) {
+        return Err("\"problems\" isn't a list".into());
+    }
+    js_sys::Array::from(&problems).iter().enumerate().map(|(i, problem)| parse_problem(&problem).map_err(|err| format!("Problem {}: {}", i + 1, err))).collect()
+}
+
+/// List of strings in a field of a JSON object, or an empty list if the field
+/// isn't there
+fn get_strings(object: &JsValue, field: &str) -> Result<Vec<String>, String> {
+    match json_field(object, field) {
+        None => Ok(vec![]),
+        Some(list) if js_sys::Array::is_array(&list) => js_sys::Array::from(&list).iter().map(|item| item.as_string().ok_or_else(|| format!("\"{field}\" has an item that isn't a string"))).collect(),
+        Some(_) => Err(format!("\"{field}\" isn't a list")),
+    }
+}
+
+/// Object of strings in a field of a JSON object, or an empty map if the field
+/// isn't there
+fn get_string_map(object: &JsValue, field: &str) -> Result<BTreeMap<String, String>, String> {
+    match json_field(object, field) {
+        None => Ok(BTreeMap::new()),
+        Some(map) if map.is_object() && !js_sys::Array::is_array(&map) => js_sys::Object::keys(&js_sys::Object::from(map.clone()))
+            .iter()
+            .map(|key| {
+                let key = key.as_string().unwrap_or_default();
+                let value = json_field(&map, &key).and_then(|value| value.as_string()).ok_or_else(|| format!("\"{field}\" has a value that isn't a string"))?;
+                Ok((key, value))
+            })
+            .collect(),
+        Some(_) => Err(format!("\"{field}\" isn't an object")),
+    }
+}
+
+/// Problems are written in the default syntax, whatever proof is open
+fn parse_formula(text: &str) -> Result<Expr, String> {
+    aris::parser::parse_with(&Syntax::default(), &aris::macros::expand(text)).ok_or_else(|| format!("{text:?} doesn't parse"))
+}
+
+fn parse_problem(problem: &JsValue) -> Result<Problem, String> {
+    let string = |field: &str| json_field(problem, field).and_then(|value| value.as_string()).ok_or_else(|| format!("missing \"{field}\""));
+    let id = string("id")?;
+    let title = string("title").unwrap_or_else(|_| id.clone());
+    let task = match (json_field(problem, "table"), json_field(problem, "sentence")) {
+        (_, Some(_)) => Task::Symbolization(Symbolization { sentence: string("sentence")?, key: get_string_map(problem, "key")?, expected: parse_formula(&string("answer")?)? }),
+        (Some(_), None) => {
+            let table = TruthTable::parse(get_strings(problem, "variables")?, &string("table")?)?;
+            let connectives = get_strings(problem, "connectives")?.iter().map(|connective| connective.parse()).collect::<Result<_, _>>()?;
+            Task::TruthTable { table, connectives }
+        }
+        (None, None) => {
+            let premises = get_strings(problem, "premises")?.iter().map(|premise| parse_formula(premise)).collect::<Result<Vec<_>, _>>()?;
+            Task::Proof { premises, goal: parse_formula(&string("goal")?)? }
+        }
+    };
+    let topics = get_strings(problem, "topics")?;
+    let difficulty = match json_field(problem, "difficulty").and_then(|value| value.as_f64()) {
+        Some(difficulty) if difficulty.fract() == 0.0 && (1.0..=f64::from(MAX_DIFFICULTY)).contains(&difficulty) => difficulty as u32,
+        Some(_) => return Err(format!("\"difficulty\" isn't a whole number from 1 to {MAX_DIFFICULTY}")),
+        None => 1,
+    };
+    Ok(Problem { id, title, task, topics, difficulty })
+}
+
+impl Problem {
+    /// How the problem is written in the user's progress, so solving its proof
+    /// or exercise counts as solving it
+    pub fn key(&self) -> String {
+        match &self.task {
+            Task::Proof { premises, goal } => progress::problem_key(premises, std::slice::from_ref(goal)),
+            Task::TruthTable { table, connectives } => progress::table_key(table, connectives),
+            Task::Symbolization(symbolization) => progress::symbolization_key(symbolization),
+        }
+    }
+
+    /// A proof to start solving the problem in, as XML for `ProofWidget`. It
+    /// has the problem's premises and an empty step, and the goal is stored
+    /// in its metadata. Returns `None` if the problem isn't a proof.
+    pub fn to_xml(&self) -> Option<Vec<u8>> {
+        let (premises, goal) = match &self.task {
+            Task::Proof { premises, goal } => (premises, goal),
+            Task::TruthTable { .. } | Task::Symbolization(_) => return None,
+        };
+        let mut prf = P::new();
+        for premise in premises {
+            prf.add_premise(premise.clone());
+        }
+        prf.add_step(Justification(Expr::var(""), RuleM::EmptyRule, vec![], vec![]));
+        let metadata = xml_interop::ProofMetaData { author: Some("ARIS-YEW-UI".into()), goals: vec![goal.clone()], ..Default::default() };
+        let mut data = vec![];
+        xml_interop::xml_from_proof_and_metadata_with_hash(&prf, &metadata, &mut data).expect("xml_from_proof_and_metadata failed");
+        Some(data)
+    }
+}
+
+/// The problem to practice after `current`, in order of difficulty: the next
+/// unsolved one among `problems` at the same difficulty, or else the easiest
+/// unsolved one that's harder. `is_solved` tells whether a problem is solved.
+pub fn next_problem<'a>(problems: &'a [Problem], current: Option<&Problem>, is_solved: impl Fn(&Problem) -> bool) -> Option<&'a Problem> {
+    let mut candidates = problems.iter().enumerate().filter(|(_, problem)| Some(*problem) != current && !is_solved(problem)).collect::<Vec<_>>();
+    // Sorting is stable, so problems of the same difficulty keep the order of
+    // the manifest
+    candidates.sort_by_key(|(_, problem)| problem.difficulty);
+    let position = |problem: &Problem| problems.iter().position(|other| other == problem);
+    let after_current = |(i, problem): &(usize, &Problem)| match current {
+        Some(current) => problem.difficulty > current.difficulty || (problem.difficulty == current.difficulty && Some(*i) > position(current)),
+        None => true,
+    };
+    candidates.iter().find(|candidate| after_current(candidate)).or_else(|| candidates.first()).map(|(_, problem)| *problem)
+}
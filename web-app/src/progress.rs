@@ -0,0 +1,209 @@
+//! Practice statistics of the user, like problems solved, rules mastered, and
+//! streaks of days practiced, along with the achievements they earn
+//!
+//! Progress is saved in the browser's `localStorage`. Pages embedding the app
+//! can also keep it elsewhere, such as on a course server, by defining a
+//! global function `arisSyncProgress`, which is called with the saved text
+//! every time progress is saved.
+
+use crate::util::local_storage;
+
+use aris::expr::Expr;
+use aris::symbolization::Symbolization;
+use aris::truth_table::Connective;
+use aris::truth_table::TruthTable;
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+
+/// `localStorage` key of the saved progress
+const STORAGE_KEY: &str = "aris-progress";
+
+/// Name of the global function that's called with the saved progress
+const SYNC_HOOK: &str = "arisSyncProgress";
+
+/// Name of the event fired on `window` when progress is saved
+pub const CHANGED_EVENT: &str = "aris-progress-changed";
+
+/// Number of solved problems a rule has to be used in to count as mastered
+pub const MASTERY_USES: u32 = 3;
+
+/// Practice statistics of the user
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Problems solved, written as their premises and conclusion, like
+    /// `"(P → Q), P ⊢ Q"`, or for other exercises as by `table_key()` and
+    /// `symbolization_key()`
+    pub solved: BTreeSet<String>,
+
+    /// Number of solved problems using each rule, by rule name
+    pub rule_uses: BTreeMap<String, u32>,
+
+    /// Days on which a problem was solved, as days since the Unix epoch in
+    /// local time
+    pub practice_days: BTreeSet<i64>,
+}
+
+/// A milestone of practice, shown on the progress page
+pub struct Achievement {
+    pub name: &'static str,
+    pub description: &'static str,
+
+    /// Whether the progress earns this achievement
+    pub earned: fn(&Progress) -> bool,
+}
+
+/// Every achievement, roughly in order of how long they take to earn
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    // Problems solved
+    Achievement { name: "First proof", description: "Solve a problem", earned: |progress| !progress.solved.is_empty() },
+    Achievement { name: "Getting the hang of it", description: "Solve 10 problems", earned: |progress| progress.solved.len() >= 10 },
+    Achievement { name: "Prover", description: "Solve 50 problems", earned: |progress| progress.solved.len() >= 50 },
+    // Rules mastered
+    Achievement { name: "Apprentice", description: "Master a rule", earned: |progress| progress.rules_mastered().next().is_some() },
+    Achievement { name: "Toolbox", description: "Master 10 rules", earned: |progress| progress.rules_mastered().count() >= 10 },
+    // Streaks
+    Achievement { name: "On a roll", description: "Practice 3 days in a row", earned: |progress| progress.longest_streak() >= 3 },
+    Achievement { name: "Habit", description: "Practice 7 days in a row", earned: |progress| progress.longest_streak() >= 7 },
+];
+
+/// How a problem with these premises and conclusions is written in
+/// `Progress::solved`
+pub fn problem_key(premises: &[Expr], conclusions: &[Expr]) -> String {
+    let join = |exprs: &[Expr]| exprs.iter().map(|expr| expr.to_string()).collect::<Vec<_>>().join(", ");
+    format!("{} \u{22a2} {}", join(premises), join(conclusions))
+}
+
+/// How a truth table exercise is written in `Progress::solved`, like
+/// `"P, Q: FTTF"`, followed by the connectives it may use if they're limited
+pub fn table_key(table: &TruthTable, connectives: &BTreeSet<Connective>) -> String {
+    let key = format!("{}: {}", table.variables().join(", "), table.column());
+    if connectives.is_empty() {
+        return key;
+    }
+    format!("{key} using {}", connectives.iter().map(|connective| connective.symbol()).collect::<Vec<_>>().join(", "))
+}
+
+/// How a symbolization exercise is written in `Progress::solved`, as its
+/// sentence and expected formula, like `"“It rains.” as R"`
+pub fn symbolization_key(symbolization: &Symbolization) -> String {
+    format!("\u{201c}{}\u{201d} as {}", symbolization.sentence, symbolization.expected)
+}
+
+/// Today, as days since the Unix epoch in local time
+pub fn today() -> i64 {
+    let date = js_sys::Date::new_0();
+    let local_ms = date.get_time() - date.get_timezone_offset() * 60_000.0;
+    (local_ms / 86_400_000.0).floor() as i64
+}
+
+impl Progress {
+    /// Record solving a problem today. Rules only count towards mastery the
+    /// first time a problem is solved, so solving the same problem again
+    /// only counts as practice. Returns whether the problem is newly solved.
+    pub fn record_solved(&mut self, problem: String, rules: &BTreeSet<String>, day: i64) -> bool {
+        self.practice_days.insert(day);
+        let new = self.solved.insert(problem);
+        if new {
+            for rule in rules {
+                *self.rule_uses.entry(rule.clone()).or_default() += 1;
+            }
+        }
+        new
+    }
+
+    /// Names of the rules used in at least `MASTERY_USES` solved problems
+    pub fn rules_mastered(&self) -> impl Iterator<Item = &str> {
+        self.rule_uses.iter().filter(|(_, uses)| **uses >= MASTERY_USES).map(|(rule, _)| rule.as_str())
+    }
+
+    /// Number of days in a row, up to today, that the user practiced. A
+    /// streak isn't broken until a whole day passes without practice.
+    pub fn current_streak(&self, today: i64) -> u32 {
+        let mut day = if self.practice_days.contains(&today) { today } else { today - 1 };
+        let mut streak = 0;
+        while self.practice_days.contains(&day) {
+            streak += 1;
+            day -= 1;
+        }
+        streak
+    }
+
+    /// Most days in a row that the user practiced
+    pub fn longest_streak(&self) -> u32 {
+        let mut longest = 0;
+        let mut current = 0;
+        let mut previous = None;
+        for &day in &self.practice_days {
+            current = if previous == Some(day - 1) { current + 1 } else { 1 };
+            longest = longest.max(current);
+            previous = Some(day);
+        }
+        longest
+    }
+
+    /// Write the progress as text, one tab-separated record per line
+    pub fn to_text(&self) -> String {
+        let solved = self.solved.iter().map(|problem| format!("solved\t{problem}\n"));
+        let rules = self.rule_uses.iter().map(|(rule, uses)| format!("rule\t{rule}\t{uses}\n"));
+        let days = self.practice_days.iter().map(|day| format!("day\t{day}\n"));
+        solved.chain(rules).chain(days).collect()
+    }
+
+    /// Read progress written by `Progress::to_text()`, skipping records it
+    /// doesn't understand
+    pub fn from_text(text: &str) -> Self {
+        let mut progress = Self::default();
+        for line in text.lines() {
+            match line.split('\t').collect::<Vec<_>>()[..] {
+                ["solved", problem] => {
+                    progress.solved.insert(problem.to_string());
+                }
+                ["rule", rule, uses] => {
+                    if let Ok(uses) = uses.parse() {
+                        progress.rule_uses.insert(rule.to_string(), uses);
+                    }
+                }
+                ["day", day] => {
+                    if let Ok(day) = day.parse() {
+                        progress.practice_days.insert(day);
+                    }
+                }
+                _ => {}
+            }
+        }
+        progress
+    }
+
+    /// Load the saved progress, or no progress if none was saved
+    pub fn load() -> Self {
+        let text = local_storage().and_then(|storage| storage.get_item(STORAGE_KEY).ok().flatten());
+        text.map(|text| Self::from_text(&text)).unwrap_or_default()
+    }
+
+    /// Save the progress, and hand it to the sync hook if the page has one.
+    /// Fires `CHANGED_EVENT`, so pages showing progress can reload it.
+    pub fn save(&self) {
+        let text = self.to_text();
+        if let Some(storage) = local_storage() {
+            // Like with preferences, not being able to save only means the
+            // progress is forgotten on the next visit
+            let _ = storage.set_item(STORAGE_KEY, &text);
+        }
+        let window = match web_sys::window() {
+            Some(window) => window,
+            None => return,
+        };
+        if let Ok(event) = web_sys::Event::new(CHANGED_EVENT) {
+            let _ = window.dispatch_event(&event);
+        }
+        let hook = js_sys::Reflect::get(&window, &JsValue::from_str(SYNC_HOOK)).ok().and_then(|hook| hook.dyn_into::<js_sys::Function>().ok());
+        if let Some(hook) = hook {
+            // Errors in the hook are the embedding page's to deal with
+            let _ = hook.call1(&JsValue::NULL, &JsValue::from_str(&text));
+        }
+    }
+}
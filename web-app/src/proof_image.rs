@@ -0,0 +1,235 @@
+//! Images of proofs, for pasting into chat and slides
+//!
+//! Proofs are laid out as SVG in the usual Fitch style, with a bar for every
+//! subproof and a line under its assumptions, and can be copied to the
+//! clipboard as PNG by drawing the SVG onto a canvas.
+
+use crate::util::P;
+
+use aris::proofs::line_info;
+use aris::proofs::line_info::LineInfo;
+use aris::proofs::Justification;
+use aris::proofs::PjRef;
+use aris::proofs::Proof;
+use aris::rule_names;
+
+use std::fmt::Write;
+
+use frunk_core::coproduct::Coproduct;
+use gloo::events::EventListener;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use yew::Callback;
+
+const FONT_SIZE: f64 = 16.0;
+
+/// Width of a character of the monospaced font text is set in
+const CHAR_WIDTH: f64 = FONT_SIZE * 0.6;
+
+const ROW_HEIGHT: f64 = 28.0;
+const PADDING: f64 = 16.0;
+
+/// Horizontal space between the bars of nested subproofs
+const BAR_SPACING: f64 = 14.0;
+
+/// Space between the proof's formulas and their justifications
+const JUSTIFICATION_GAP: f64 = 40.0;
+
+/// PNG images are drawn at this many pixels per SVG unit, so they stay sharp
+/// when pasted into slides
+const PNG_SCALE: f64 = 2.0;
+
+/// A proof laid out as an SVG image
+pub struct ProofSvg {
+    pub svg: String,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A line of the proof, as laid out
+struct Row {
+    depth: usize,
+    formula: String,
+    /// Rule and dependencies of steps
+    justification: Option<Justification<(), PjRef<P>, <P as Proof>::SubproofReference>>,
+}
+
+/// A proof laid out in rows, with the bars to draw beside them
+#[derive(Default)]
+struct Layout {
+    rows: Vec<Row>,
+    /// Depth, first row, and last row of every (sub)proof
+    bars: Vec<(usize, usize, usize)>,
+    /// Depth and last row of the assumptions of every (sub)proof that has any
+    assumption_lines: Vec<(usize, usize)>,
+}
+
+impl Layout {
+    fn add_proof(&mut self, prf: &<P as Proof>::Subproof, depth: usize) {
+        use Coproduct::{Inl, Inr};
+        let first = self.rows.len();
+        for premise in prf.premises() {
+            let formula = prf.lookup_premise(&premise).map(|expr| expr.to_string()).unwrap_or_default();
+            self.rows.push(Row { depth, formula, justification: None });
+        }
+        if self.rows.len() > first {
+            self.assumption_lines.push((depth, self.rows.len() - 1));
+        }
+        for line in prf.lines() {
+            match line {
+                Inl(r) => {
+                    let step = prf.lookup_step(&r);
+                    let formula = step.as_ref().map(|step| step.0.to_string()).unwrap_or_default();
+                    let justification = step.map(|Justification(_, rule, deps, sdeps)| Justification((), rule, deps, sdeps));
+                    self.rows.push(Row { depth, formula, justification });
+                }
+                Inr(Inl(sr)) => {
+                    if let Some(sub) = prf.lookup_subproof(&sr) {
+                        self.add_proof(&sub, depth + 1);
+                    }
+                }
+                Inr(Inr(void)) => match void {},
+            }
+        }
+        if self.rows.len() > first {
+            self.bars.push((depth, first, self.rows.len() - 1));
+        }
+    }
+}
+
+/// A step's rule and the line numbers it cites, like "∧ Elimination: 1, 3-5"
+fn justification_text(justification: &Justification<(), PjRef<P>, <P as Proof>::SubproofReference>, info: &LineInfo<P>) -> String {
+    let Justification(_, rule, deps, sdeps) = justification;
+    let deps = deps.iter().filter_map(|dep| info.line_of(dep)).map(|line| line.to_string());
+    let sdeps = sdeps.iter().filter_map(|sdep| info.line_range_of_subproof(sdep)).map(line_info::format_range);
+    let cited = deps.chain(sdeps).collect::<Vec<_>>();
+    if cited.is_empty() {
+        rule_names::display_name(*rule)
+    } else {
+        format!("{}: {}", rule_names::display_name(*rule), cited.join(", "))
+    }
+}
+
+/// Escape text for putting in SVG
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn text_width(text: &str) -> f64 {
+    text.chars().count() as f64 * CHAR_WIDTH
+}
+
+/// Lay out a proof as an SVG image
+pub fn proof_svg(prf: &P) -> ProofSvg {
+    let mut layout = Layout::default();
+    layout.add_proof(prf.top_level_proof(), 0);
+
+    let number_width = text_width(&layout.rows.len().to_string());
+    let bar_x = |depth: usize| PADDING + number_width + BAR_SPACING + depth as f64 * BAR_SPACING;
+    let formula_x = |depth: usize| bar_x(depth) + BAR_SPACING / 2.0;
+    let info = LineInfo::of(prf);
+    let justifications = layout.rows.iter().map(|row| row.justification.as_ref().map(|justification| justification_text(justification, &info)).unwrap_or_default()).collect::<Vec<_>>();
+    let justification_x = layout.rows.iter().map(|row| formula_x(row.depth) + text_width(&row.formula)).fold(0.0, f64::max) + JUSTIFICATION_GAP;
+    let width = justification_x + justifications.iter().map(|justification| text_width(justification)).fold(0.0, f64::max) + PADDING;
+    let height = 2.0 * PADDING + layout.rows.len() as f64 * ROW_HEIGHT;
+    let row_top = |row: usize| PADDING + row as f64 * ROW_HEIGHT;
+    let baseline = |row: usize| row_top(row) + ROW_HEIGHT * 0.7;
+
+    let mut svg = String::new();
+    // Writing to a `String` can't fail
+    let _ = write!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="Consolas, Menlo, monospace" font-size="{FONT_SIZE}">"#);
+    svg.push_str(r#"<rect width="100%" height="100%" fill="white"/><g stroke="black" stroke-width="1.5">"#);
+    for (depth, first, last) in &layout.bars {
+        let x = bar_x(*depth);
+        let _ = write!(svg, r#"<line x1="{x}" y1="{}" x2="{x}" y2="{}"/>"#, row_top(*first) + 4.0, row_top(*last + 1) - 4.0);
+    }
+    for (depth, row) in &layout.assumption_lines {
+        let x = bar_x(*depth);
+        let width = layout.rows.iter().filter(|line| line.depth == *depth).map(|line| text_width(&line.formula)).fold(0.0, f64::max);
+        let _ = write!(svg, r#"<line x1="{x}" y1="{y}" x2="{}" y2="{y}"/>"#, formula_x(*depth) + width, y = row_top(*row + 1) - 2.0);
+    }
+    svg.push_str("</g>");
+    for (i, (row, justification)) in layout.rows.iter().zip(&justifications).enumerate() {
+        let y = baseline(i);
+        let _ = write!(svg, r##"<text x="{}" y="{y}" text-anchor="end" fill="#6c757d">{}</text>"##, PADDING + number_width, i + 1);
+        let _ = write!(svg, r#"<text x="{}" y="{y}">{}</text>"#, formula_x(row.depth), escape(&row.formula));
+        if !justification.is_empty() {
+            let _ = write!(svg, r#"<text x="{justification_x}" y="{y}">{}</text>"#, escape(justification));
+        }
+    }
+    svg.push_str("</svg>");
+    ProofSvg { svg, width, height }
+}
+
+/// Promise of a PNG rendering of an SVG image, as a `Blob`
+fn png_blob(image: &ProofSvg) -> Result<js_sys::Promise, JsValue> {
+    let document = web_sys::window().and_then(|window| window.document()).ok_or("no document")?;
+    let parts = js_sys::Array::of1(&JsValue::from_str(&image.svg));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type("image/svg+xml");
+    let svg_blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = web_sys::Url::create_object_url_with_blob(&svg_blob)?;
+    let img = web_sys::HtmlImageElement::new()?;
+    let canvas = document.create_element("canvas")?.dyn_into::<web_sys::HtmlCanvasElement>()?;
+    canvas.set_width((image.width * PNG_SCALE).ceil() as u32);
+    canvas.set_height((image.height * PNG_SCALE).ceil() as u32);
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let (img_, canvas, url, url_) = (img.clone(), canvas.clone(), url.clone(), url.clone());
+        let reject_ = reject.clone();
+        EventListener::once(&img, "load", move |_| {
+            let draw = || -> Result<(), JsValue> {
+                let context = canvas.get_context("2d")?.ok_or("no 2d context")?.dyn_into::<web_sys::CanvasRenderingContext2d>()?;
+                context.scale(PNG_SCALE, PNG_SCALE)?;
+                context.draw_image_with_html_image_element(&img_, 0.0, 0.0)?;
+                let resolve = resolve.clone();
+                let done = Closure::once_into_js(move |blob: JsValue| {
+                    let _ = resolve.call1(&JsValue::NULL, &blob);
+                });
+                canvas.to_blob(done.unchecked_ref())
+            };
+            let result = draw();
+            let _ = web_sys::Url::revoke_object_url(&url);
+            if let Err(err) = result {
+                let _ = reject_.call1(&JsValue::NULL, &err);
+            }
+        })
+        .forget();
+        EventListener::once(&img, "error", move |_| {
+            let _ = web_sys::Url::revoke_object_url(&url_);
+            let _ = reject.call1(&JsValue::NULL, &JsValue::from_str("the proof couldn't be drawn"));
+        })
+        .forget();
+    });
+    img.set_src(&url);
+    Ok(promise)
+}
+
+/// Copy a proof to the clipboard as a PNG image, calling `done` once it's
+/// copied or copying fails. This has to be called while handling a click or
+/// key press, since browsers only allow writing to the clipboard then.
+pub fn copy_png_to_clipboard(image: &ProofSvg, done: Callback<Result<(), String>>) {
+    let result = (|| -> Result<js_sys::Promise, JsValue> {
+        let window = web_sys::window().ok_or("no window")?;
+        let clipboard = js_sys::Reflect::get(&window.navigator(), &JsValue::from_str("clipboard"))?;
+        let write = js_sys::Reflect::get(&clipboard, &JsValue::from_str("write"))?.dyn_into::<js_sys::Function>().map_err(|_| "this browser can't copy images")?;
+        let clipboard_item = js_sys::Reflect::get(&window, &JsValue::from_str("ClipboardItem"))?.dyn_into::<js_sys::Function>().map_err(|_| "this browser can't copy images")?;
+        // The item is made right away, with a promise of the image, since
+        // Safari only allows writing to the clipboard while handling the event
+        let items = js_sys::Object::new();
+        js_sys::Reflect::set(&items, &JsValue::from_str("image/png"), png_blob(image)?.as_ref())?;
+        let item = js_sys::Reflect::construct(&clipboard_item, &js_sys::Array::of1(&items))?;
+        write.call1(&clipboard, &js_sys::Array::of1(&item))?.dyn_into::<js_sys::Promise>()
+    })();
+    let promise = match result {
+        Ok(promise) => promise,
+        Err(err) => return done.emit(Err(err.as_string().unwrap_or_else(|| "copying failed".into()))),
+    };
+    let done_ = done.clone();
+    let copied = Closure::once(move |_: JsValue| done.emit(Ok(())));
+    let failed = Closure::once(move |err: JsValue| done_.emit(Err(crate::util::json_field(&err, "message").and_then(|message| message.as_string()).unwrap_or_else(|| "copying failed".into()))));
+    let _ = promise.then2(&copied, &failed);
+    // The promise calls one of the closures eventually, and then they're done
+    copied.forget();
+    failed.forget();
+}
@@ -1,23 +1,70 @@
-use crate::util::calculate_lineinfo;
-
 use aris::proofs::js_to_pjs;
+use aris::proofs::line_info::LineInfo;
 use aris::proofs::PjRef;
 use aris::proofs::Proof;
 
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use frunk_core::coproduct::Coproduct;
 
 pub struct ProofUiData<P: Proof> {
-    pub ref_to_line_depth: HashMap<PjRef<P>, (usize, usize)>,
+    /// Line numbers and depths of the lines, and the lines the subproofs
+    /// span, as of when they were last asked for
+    line_info: RefCell<Rc<LineInfo<P>>>,
     pub ref_to_input: HashMap<PjRef<P>, String>,
+    /// Last cursor position in each line's text field, as the start and end
+    /// of the selection in characters, for restoring it when the field is
+    /// focused again or re-rendered for a moved line
+    pub ref_to_caret: HashMap<PjRef<P>, (u32, u32)>,
 }
 
 impl<P: Proof> ProofUiData<P> {
     pub fn from_proof(prf: &P) -> ProofUiData<P> {
-        let mut ref_to_line_depth = HashMap::new();
-        calculate_lineinfo::<P>(&mut ref_to_line_depth, prf.top_level_proof(), &mut 1, &mut 0);
-        ProofUiData { ref_to_line_depth, ref_to_input: initialize_inputs(prf) }
+        ProofUiData { line_info: RefCell::new(Rc::new(LineInfo::of(prf))), ref_to_input: initialize_inputs(prf), ref_to_caret: HashMap::new() }
+    }
+
+    /// Line numbers of `prf`, the proof this is for, numbering its lines
+    /// again if it's been edited since they were last asked for
+    pub fn line_info(&self, prf: &P) -> Rc<LineInfo<P>> {
+        let mut line_info = self.line_info.borrow_mut();
+        if !line_info.is_current(prf) {
+            *line_info = Rc::new(LineInfo::of(prf));
+        }
+        Rc::clone(&line_info)
+    }
+
+    /// Forget the text and cursor positions of the lines of a subproof about
+    /// to be deleted, along with those of the subproofs nested in it
+    pub fn remove_subproof_lines(&mut self, sub: &P::Subproof) {
+        use Coproduct::{Inl, Inr};
+        for line in sub.premises().into_iter().map(Coproduct::inject).chain(sub.lines().into_iter().map(js_to_pjs::<P>)) {
+            match line {
+                Inl(pr) => self.forget(&Coproduct::inject(pr)),
+                Inr(Inl(jr)) => self.forget(&Coproduct::inject(jr)),
+                Inr(Inr(Inl(sr))) => {
+                    if let Some(nested) = sub.lookup_subproof(&sr) {
+                        self.remove_subproof_lines(&nested);
+                    }
+                }
+                Inr(Inr(Inr(void))) => match void {},
+            }
+        }
+    }
+
+    /// Forget everything kept for lines that aren't in `prf` anymore, so a
+    /// line reusing a deleted line's reference doesn't bring back its text.
+    /// Run after edits that may remove lines some other way than deleting them.
+    pub fn prune_orphans(&mut self, prf: &P) {
+        let line_info = self.line_info(prf);
+        self.ref_to_input.retain(|r, _| line_info.lines.contains_key(r));
+        self.ref_to_caret.retain(|r, _| line_info.lines.contains_key(r));
+    }
+
+    fn forget(&mut self, r: &PjRef<P>) {
+        self.ref_to_input.remove(r);
+        self.ref_to_caret.remove(r);
     }
 }
 
@@ -0,0 +1,26 @@
+//! Course-specific names for rules
+//!
+//! Sites serving the app can rename rules to match their course's textbook
+//! with a `rule-names.json` next to `index.html`, mapping rules' serialized
+//! names to the names to show, like:
+//!
+//! ```json
+//! { "MODUS_PONENS": "Modus Ponens", "DISJUNCTIVE_SYLLOGISM": "DS" }
+//! ```
+//!
+//! `index.html` passes the file to `setRuleNames` before starting the app, and
+//! the names are then used in menus, feedback, and exported images. Proof
+//! files still name rules by their serialized names, so they open the same
+//! anywhere. Pages embedding proofs can call `setRuleNames` the same way.
+
+use aris::rule_names;
+
+use wasm_bindgen::prelude::*;
+
+/// Set the course's names for rules from the JSON of a `rule-names.json`
+#[wasm_bindgen(js_name = setRuleNames)]
+pub fn set_rule_names(json: &str) -> Result<(), JsValue> {
+    let aliases = rule_names::aliases_from_json(json)?;
+    rule_names::set_aliases(aliases)?;
+    Ok(())
+}
@@ -0,0 +1,43 @@
+//! Signing exams saved by official builds of the app
+//!
+//! A build made with the `ARIS_SIGNING_KEY` environment variable set to the
+//! base64 of a 32 byte Ed25519 secret key, like the output of
+//! `head -c 32 /dev/urandom | base64`, signs the hash of every exam it saves or
+//! downloads. Graders confirm the signatures with the matching public key,
+//! printed by `aris-sign public-key <key file>`, so an exam edited outside the
+//! app, or saved by a build without the key, is rejected. A key that isn't
+//! valid fails the build.
+//!
+//! The key is built into the app, so someone who digs it out of the
+//! WebAssembly can sign anything with it. A signature shows that an exam was
+//! saved by the official app without being edited afterward, not that nobody
+//! could have forged it. Courses that need more sign exams on their server as
+//! they arrive, with `aris-sign sign`, using a key that never leaves it.
+
+use crate::util::P;
+
+use aris::proofs::xml_interop;
+use aris::proofs::xml_interop::ProofMetaData;
+use aris::proofs::xml_interop::SigningKey;
+
+/// Bytes of the key exams are signed with, if the app was built with one,
+/// decoded by the build script, which rejects keys that aren't valid
+const SIGNING_KEY: Option<[u8; 32]> = include!(concat!(env!("OUT_DIR"), "/signing_key.rs"));
+
+/// The key exams are signed with, if the app was built with one
+fn signing_key() -> Option<SigningKey> {
+    SIGNING_KEY.map(|bytes| SigningKey::from_bytes(&bytes))
+}
+
+/// Save a proof as XML with its hash, like
+/// `xml_interop::xml_from_proof_and_metadata_with_hash`, also signing the hash
+/// if the proof is an exam and the app was built with a key
+pub fn xml_from_proof_and_metadata(prf: &P, metadata: &ProofMetaData) -> Vec<u8> {
+    let mut data = vec![];
+    match signing_key() {
+        Some(key) if metadata.exam.is_some() => xml_interop::xml_from_proof_and_metadata_signed(prf, metadata, &key, &mut data),
+        _ => xml_interop::xml_from_proof_and_metadata_with_hash(prf, metadata, &mut data),
+    }
+    .expect("xml_from_proof_and_metadata failed");
+    data
+}
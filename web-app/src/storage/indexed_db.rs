@@ -0,0 +1,261 @@
+//! Proofs kept in the browser's IndexedDB
+//!
+//! The database has an object store of the proofs' contents and one of their
+//! metadata, both keyed by the proofs' names, so listing the proofs doesn't
+//! read their contents. IndexedDB holds far more than `localStorage`, so the
+//! store also keeps the last versions of every proof, for going back to, and
+//! the problem sets the user loaded into the problem bank.
+
+use super::error_message;
+use super::ProofStore;
+use super::StoreError;
+use super::StoredProof;
+use crate::util::json_field;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gloo::events::EventListener;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use web_sys::EventTarget;
+use web_sys::IdbDatabase;
+use web_sys::IdbKeyRange;
+use web_sys::IdbObjectStore;
+use web_sys::IdbRequest;
+use web_sys::IdbTransactionMode;
+use yew::Callback;
+
+const DATABASE_NAME: &str = "aris";
+
+/// Version of the database's layout, to be increased whenever `upgrade()`
+/// changes it
+const DATABASE_VERSION: u32 = 2;
+
+/// Object store of proofs' contents, as text
+const PROOF_STORE: &str = "proofs";
+
+/// Object store of proofs' metadata, as written by `StoredProof::to_js()`
+const METADATA_STORE: &str = "proof-metadata";
+
+/// Object store of the versions of proofs, keyed by `[name, modified]`. Each
+/// version is its metadata, with its contents in a `data` field.
+const HISTORY_STORE: &str = "proof-history";
+
+/// Object store of problem manifests, as text, keyed by their names
+const PROBLEM_SET_STORE: &str = "problem-sets";
+
+/// Number of versions kept of each proof
+const HISTORY_LIMIT: u32 = 20;
+
+/// Proofs kept in the browser's IndexedDB
+pub struct IndexedDbStore;
+
+/// Call `done` once `target` fires `success_event`, with `true`, or fails, with
+/// `false`
+fn when_done(target: &EventTarget, success_event: &'static str, done: impl FnOnce(bool) + 'static) {
+    let done = Rc::new(RefCell::new(Some(done)));
+    let listeners = Rc::new(RefCell::new(vec![]));
+    for (event, succeeded) in [(success_event, true), ("error", false), ("abort", false)] {
+        let done = done.clone();
+        let listeners_ = listeners.clone();
+        listeners.borrow_mut().push(EventListener::once(target, event, move |_| {
+            // Only the first event counts, so stop listening for the others
+            listeners_.borrow_mut().clear();
+            if let Some(done) = done.borrow_mut().take() {
+                done(succeeded);
+            }
+        }));
+    }
+}
+
+fn request_error(request: &IdbRequest) -> StoreError {
+    match request.error() {
+        Ok(Some(err)) => StoreError::Failed(err.message()),
+        _ => StoreError::Failed("IndexedDB request failed".into()),
+    }
+}
+
+/// Create the object stores that don't exist yet
+fn upgrade(db: &IdbDatabase) {
+    for name in [PROOF_STORE, METADATA_STORE, HISTORY_STORE, PROBLEM_SET_STORE] {
+        if !db.object_store_names().contains(name) {
+            let _ = db.create_object_store(name);
+        }
+    }
+}
+
+/// Open the database, creating or upgrading it if needed
+fn open_database(done: impl FnOnce(Result<IdbDatabase, StoreError>) + 'static) {
+    let factory = match web_sys::window().and_then(|window| window.indexed_db().ok().flatten()) {
+        Some(factory) => factory,
+        None => return done(Err(StoreError::Unavailable("this browser doesn't support IndexedDB".into()))),
+    };
+    let request = match factory.open_with_u32(DATABASE_NAME, DATABASE_VERSION) {
+        Ok(request) => request,
+        Err(err) => return done(Err(StoreError::Unavailable(error_message(&err)))),
+    };
+    let upgrade_request = request.clone();
+    let upgrade_listener = EventListener::new(&request, "upgradeneeded", move |_| {
+        if let Ok(db) = upgrade_request.result().and_then(|db| db.dyn_into::<IdbDatabase>()) {
+            upgrade(&db);
+        }
+    });
+    let request_ = request.clone();
+    when_done(&request, "success", move |succeeded| {
+        drop(upgrade_listener);
+        if !succeeded {
+            return done(Err(request_error(&request_)));
+        }
+        done(request_.result().and_then(|db| db.dyn_into::<IdbDatabase>()).map_err(|err| StoreError::Failed(error_message(&err))));
+    });
+}
+
+/// Run `request` on the opened database, and hand what it results in to
+/// `done`
+fn with_request<T: 'static>(request: impl FnOnce(&IdbDatabase) -> Result<IdbRequest, JsValue> + 'static, result: impl FnOnce(JsValue) -> Result<T, StoreError> + 'static, done: Callback<Result<T, StoreError>>) {
+    open_database(move |db| {
+        let db = match db {
+            Ok(db) => db,
+            Err(err) => return done.emit(Err(err)),
+        };
+        let request = request(&db);
+        // Closing waits for the request's transaction to finish
+        db.close();
+        let request = match request {
+            Ok(request) => request,
+            Err(err) => return done.emit(Err(StoreError::Failed(error_message(&err)))),
+        };
+        let request_ = request.clone();
+        when_done(&request, "success", move |succeeded| {
+            if !succeeded {
+                return done.emit(Err(request_error(&request_)));
+            }
+            done.emit(request_.result().map_err(|err| StoreError::Failed(error_message(&err))).and_then(result));
+        });
+    });
+}
+
+/// Key range of the versions of a proof in `HISTORY_STORE`
+fn versions_range(name: &str) -> Result<IdbKeyRange, JsValue> {
+    let name = JsValue::from_str(name);
+    IdbKeyRange::bound(&js_sys::Array::of2(&name, &JsValue::from_f64(f64::NEG_INFINITY)), &js_sys::Array::of2(&name, &JsValue::from_f64(f64::INFINITY)))
+}
+
+/// Add a version of a proof to `HISTORY_STORE`, forgetting the oldest
+/// versions past `HISTORY_LIMIT`
+fn add_version(history: IdbObjectStore, proof: &StoredProof, data: &JsValue) -> Result<(), JsValue> {
+    let version = proof.to_js();
+    js_sys::Reflect::set(&version, &JsValue::from_str("data"), data)?;
+    history.put_with_key(&version, &js_sys::Array::of2(&JsValue::from_str(&proof.name), &JsValue::from_f64(proof.modified)))?;
+    // Requests run in order, so the keys include the version just added
+    let keys = history.get_all_keys_with_key(versions_range(&proof.name)?.as_ref())?;
+    let keys_ = keys.clone();
+    when_done(&keys, "success", move |succeeded| {
+        if !succeeded {
+            return;
+        }
+        if let Ok(keys) = keys_.result() {
+            let keys = js_sys::Array::from(&keys);
+            // Keys are sorted, so the oldest versions come first
+            for key in keys.iter().take(keys.length().saturating_sub(HISTORY_LIMIT) as usize) {
+                let _ = history.delete(&key);
+            }
+        }
+    });
+    Ok(())
+}
+
+impl IndexedDbStore {
+    /// The kept versions of the proof with this name, most recent first
+    pub fn versions(&self, name: &str, done: Callback<Result<Vec<StoredProof>, StoreError>>) {
+        let name = name.to_string();
+        with_request(move |db| db.transaction_with_str(HISTORY_STORE)?.object_store(HISTORY_STORE)?.get_all_with_key(versions_range(&name)?.as_ref()), |versions| Ok(StoredProof::list_from_js(&versions)), done);
+    }
+
+    /// Contents of the version of the proof with this name that was saved at
+    /// `modified`
+    pub fn load_version(&self, name: &str, modified: f64, done: Callback<Result<Vec<u8>, StoreError>>) {
+        let key = js_sys::Array::of2(&JsValue::from_str(name), &JsValue::from_f64(modified));
+        let name = name.to_string();
+        with_request(move |db| db.transaction_with_str(HISTORY_STORE)?.object_store(HISTORY_STORE)?.get(&key), move |version| json_field(&version, "data").and_then(|data| data.as_string()).map(String::into_bytes).ok_or(StoreError::NotFound(name)), done);
+    }
+
+    /// Problem manifests saved with `IndexedDbStore::save_problem_set()`, as
+    /// their names and text, by name
+    pub fn problem_sets(&self, done: Callback<Result<Vec<(String, String)>, StoreError>>) {
+        with_request(
+            |db| db.transaction_with_str(PROBLEM_SET_STORE)?.object_store(PROBLEM_SET_STORE)?.get_all(),
+            |sets| {
+                if !js_sys::Array::is_array(&sets) {
+                    return Ok(vec![]);
+                }
+                let mut sets = js_sys::Array::from(&sets).iter().filter_map(|set| Some((json_field(&set, "name")?.as_string()?, json_field(&set, "manifest")?.as_string()?))).collect::<Vec<_>>();
+                sets.sort();
+                Ok(sets)
+            },
+            done,
+        );
+    }
+
+    /// Save a problem manifest, replacing any saved with the same name
+    pub fn save_problem_set(&self, name: &str, manifest: &str, done: Callback<Result<(), StoreError>>) {
+        let set = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&set, &JsValue::from_str("name"), &JsValue::from_str(name));
+        let _ = js_sys::Reflect::set(&set, &JsValue::from_str("manifest"), &JsValue::from_str(manifest));
+        let key = JsValue::from_str(name);
+        with_request(move |db| db.transaction_with_str_and_mode(PROBLEM_SET_STORE, IdbTransactionMode::Readwrite)?.object_store(PROBLEM_SET_STORE)?.put_with_key(&set, &key), |_| Ok(()), done);
+    }
+}
+
+impl ProofStore for IndexedDbStore {
+    fn location(&self) -> String {
+        "this browser's IndexedDB".into()
+    }
+
+    fn list(&self, done: Callback<Result<Vec<StoredProof>, StoreError>>) {
+        with_request(|db| db.transaction_with_str(METADATA_STORE)?.object_store(METADATA_STORE)?.get_all(), |proofs| Ok(StoredProof::list_from_js(&proofs)), done);
+    }
+
+    fn load(&self, name: &str, done: Callback<Result<Vec<u8>, StoreError>>) {
+        let key = JsValue::from_str(name);
+        let name = name.to_string();
+        with_request(move |db| db.transaction_with_str(PROOF_STORE)?.object_store(PROOF_STORE)?.get(&key), move |data| data.as_string().map(String::into_bytes).ok_or(StoreError::NotFound(name)), done);
+    }
+
+    fn save(&self, name: &str, data: Vec<u8>, done: Callback<Result<StoredProof, StoreError>>) {
+        let proof = StoredProof::new(name, &data);
+        let metadata = proof.to_js();
+        let data = JsValue::from_str(&String::from_utf8_lossy(&data));
+        let key = JsValue::from_str(name);
+        open_database(move |db| {
+            let db = match db {
+                Ok(db) => db,
+                Err(err) => return done.emit(Err(err)),
+            };
+            // Every store is written in one transaction, so the metadata and
+            // history never disagree with the contents
+            let stores = [PROOF_STORE, METADATA_STORE, HISTORY_STORE].iter().map(|store| JsValue::from_str(store)).collect::<js_sys::Array>();
+            let transaction = db.transaction_with_str_sequence_and_mode(&stores, IdbTransactionMode::Readwrite).and_then(|transaction| {
+                transaction.object_store(PROOF_STORE)?.put_with_key(&data, &key)?;
+                transaction.object_store(METADATA_STORE)?.put_with_key(&metadata, &key)?;
+                add_version(transaction.object_store(HISTORY_STORE)?, &proof, &data)?;
+                Ok(transaction)
+            });
+            db.close();
+            let transaction = match transaction {
+                Ok(transaction) => transaction,
+                Err(err) => return done.emit(Err(StoreError::Failed(error_message(&err)))),
+            };
+            let transaction_ = transaction.clone();
+            when_done(&transaction, "complete", move |succeeded| {
+                let result = match transaction_.error() {
+                    _ if succeeded => Ok(proof),
+                    Some(err) => Err(StoreError::Failed(err.message())),
+                    None => Err(StoreError::Failed("Saving to IndexedDB failed".into())),
+                };
+                done.emit(result);
+            });
+        });
+    }
+}
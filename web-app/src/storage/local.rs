@@ -0,0 +1,70 @@
+//! Proofs kept in the browser's `localStorage`
+//!
+//! `localStorage` is small, typically 5MB for the whole app, but it works in
+//! every browser, even where IndexedDB doesn't.
+
+use super::error_message;
+use super::ProofStore;
+use super::StoreError;
+use super::StoredProof;
+use crate::util::local_storage;
+
+use yew::Callback;
+
+/// `localStorage` key of the list of saved proofs, with their metadata
+const INDEX_KEY: &str = "aris-proof-index";
+
+/// Prefix of the `localStorage` keys of saved proofs, followed by their names
+const PROOF_PREFIX: &str = "aris-proof:";
+
+/// Proofs kept in the browser's `localStorage`
+pub struct LocalStorageStore;
+
+fn storage() -> Result<web_sys::Storage, StoreError> {
+    local_storage().ok_or_else(|| StoreError::Unavailable("this browser doesn't allow local storage".into()))
+}
+
+fn read_index(storage: &web_sys::Storage) -> Vec<StoredProof> {
+    let text = storage.get_item(INDEX_KEY).ok().flatten();
+    text.and_then(|text| js_sys::JSON::parse(&text).ok()).map(|index| StoredProof::list_from_js(&index)).unwrap_or_default()
+}
+
+fn write_index(storage: &web_sys::Storage, index: &[StoredProof]) -> Result<(), StoreError> {
+    let index = index.iter().map(StoredProof::to_js).collect::<js_sys::Array>();
+    let text = js_sys::JSON::stringify(&index).map(String::from).map_err(|err| StoreError::Failed(error_message(&err)))?;
+    storage.set_item(INDEX_KEY, &text).map_err(|err| StoreError::Failed(error_message(&err)))
+}
+
+impl ProofStore for LocalStorageStore {
+    fn location(&self) -> String {
+        "this browser's local storage".into()
+    }
+
+    fn list(&self, done: Callback<Result<Vec<StoredProof>, StoreError>>) {
+        done.emit(storage().map(|storage| read_index(&storage)));
+    }
+
+    fn load(&self, name: &str, done: Callback<Result<Vec<u8>, StoreError>>) {
+        let result = storage().and_then(|storage| match storage.get_item(&format!("{PROOF_PREFIX}{name}")) {
+            Ok(Some(text)) => Ok(text.into_bytes()),
+            Ok(None) => Err(StoreError::NotFound(name.to_string())),
+            Err(err) => Err(StoreError::Failed(error_message(&err))),
+        });
+        done.emit(result);
+    }
+
+    fn save(&self, name: &str, data: Vec<u8>, done: Callback<Result<StoredProof, StoreError>>) {
+        let proof = StoredProof::new(name, &data);
+        let result = storage().and_then(|storage| {
+            // Saving fails when the proof doesn't fit in what's left of the
+            // quota, and then the index is left as it was
+            storage.set_item(&format!("{PROOF_PREFIX}{name}"), &String::from_utf8_lossy(&data)).map_err(|err| StoreError::Failed(format!("The proof doesn't fit in local storage: {}", error_message(&err))))?;
+            let mut index = read_index(&storage);
+            index.retain(|other| other.name != name);
+            index.insert(0, proof.clone());
+            write_index(&storage, &index)?;
+            Ok(proof)
+        });
+        done.emit(result);
+    }
+}
@@ -0,0 +1,134 @@
+//! Places proofs are saved to and opened from
+//!
+//! Every way of keeping proofs implements `ProofStore`, so the app saves and
+//! opens proofs the same way wherever they're kept. Which store is used is one
+//! of the user's preferences.
+
+mod indexed_db;
+mod local;
+mod remote;
+
+pub use indexed_db::IndexedDbStore;
+pub use local::LocalStorageStore;
+pub use remote::RemoteStore;
+
+use crate::preferences::Preferences;
+use crate::preferences::StorageBackend;
+use crate::util::json_field;
+use crate::util::P;
+
+use aris::proofs::xml_interop;
+
+use std::fmt;
+
+use wasm_bindgen::JsValue;
+use yew::Callback;
+
+/// A saved proof, without its contents
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredProof {
+    pub name: String,
+
+    /// When the proof was last saved, in milliseconds since the Unix epoch
+    pub modified: f64,
+
+    /// Size of the saved proof, in bytes
+    pub size: usize,
+
+    /// Author from the proof's metadata
+    pub author: Option<String>,
+}
+
+/// Error saving or opening a proof
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoreError {
+    /// There's no saved proof with this name
+    NotFound(String),
+    /// The store can't be used, such as when the browser doesn't support it
+    Unavailable(String),
+    /// Saving or opening a proof failed
+    Failed(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::NotFound(name) => write!(f, "There's no saved proof named {name:?}."),
+            StoreError::Unavailable(reason) => write!(f, "Proofs can't be saved here: {reason}"),
+            StoreError::Failed(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+/// Somewhere proofs can be saved to and opened from. Most stores work
+/// asynchronously, so results are handed to callbacks, which may be called
+/// before the method returns.
+pub trait ProofStore {
+    /// Where the store keeps proofs, for showing to the user, like
+    /// `"this browser"`
+    fn location(&self) -> String;
+
+    /// The saved proofs, most recently saved first
+    fn list(&self, done: Callback<Result<Vec<StoredProof>, StoreError>>);
+
+    /// Contents of the saved proof with this name
+    fn load(&self, name: &str, done: Callback<Result<Vec<u8>, StoreError>>);
+
+    /// Save a proof under `name`, replacing any saved with the same name
+    fn save(&self, name: &str, data: Vec<u8>, done: Callback<Result<StoredProof, StoreError>>);
+}
+
+/// The store picked in the user's preferences
+pub fn current(preferences: &Preferences) -> Box<dyn ProofStore> {
+    match preferences.storage {
+        StorageBackend::LocalStorage => Box::new(LocalStorageStore),
+        StorageBackend::IndexedDb => Box::new(IndexedDbStore),
+        StorageBackend::Remote => Box::new(RemoteStore::new(&preferences.storage_url)),
+    }
+}
+
+impl StoredProof {
+    /// Metadata of a proof about to be saved, with these contents
+    fn new(name: &str, data: &[u8]) -> Self {
+        let author = xml_interop::proof_from_xml::<P, _>(data).ok().and_then(|(_, metadata)| metadata.author);
+        StoredProof { name: name.to_string(), modified: js_sys::Date::now(), size: data.len(), author }
+    }
+
+    /// Write the metadata as a JavaScript object, like
+    /// `{"name": "hw1.bram", "modified": 1700000000000, "size": 2048, "author": "Alice"}`
+    fn to_js(&self) -> JsValue {
+        let object = js_sys::Object::new();
+        let set = |field: &str, value: JsValue| {
+            let _ = js_sys::Reflect::set(&object, &JsValue::from_str(field), &value);
+        };
+        set("name", JsValue::from_str(&self.name));
+        set("modified", JsValue::from_f64(self.modified));
+        set("size", JsValue::from_f64(self.size as f64));
+        set("author", self.author.as_deref().map(JsValue::from_str).unwrap_or(JsValue::NULL));
+        object.into()
+    }
+
+    /// Read metadata written by `StoredProof::to_js()`
+    fn from_js(value: &JsValue) -> Option<Self> {
+        let name = json_field(value, "name")?.as_string()?;
+        let modified = json_field(value, "modified").and_then(|modified| modified.as_f64()).unwrap_or(0.0);
+        let size = json_field(value, "size").and_then(|size| size.as_f64()).unwrap_or(0.0) as usize;
+        let author = json_field(value, "author").and_then(|author| author.as_string());
+        Some(StoredProof { name, modified, size, author })
+    }
+
+    /// Read a list of metadata, skipping entries that aren't metadata
+    fn list_from_js(value: &JsValue) -> Vec<Self> {
+        if !js_sys::Array::is_array(value) {
+            return vec![];
+        }
+        let mut proofs = js_sys::Array::from(value).iter().filter_map(|proof| Self::from_js(&proof)).collect::<Vec<_>>();
+        proofs.sort_by(|a, b| b.modified.total_cmp(&a.modified));
+        proofs
+    }
+}
+
+/// Message of a JavaScript error, for stores' errors
+fn error_message(err: &JsValue) -> String {
+    json_field(err, "message").and_then(|message| message.as_string()).or_else(|| err.as_string()).unwrap_or_else(|| "unknown error".into())
+}
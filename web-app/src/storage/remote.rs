@@ -0,0 +1,121 @@
+//! Proofs kept on a server, such as a course's
+//!
+//! The server is given by a base URL, and has to answer these requests:
+//!
+//! + `GET {base}/proofs` with a JSON list of the saved proofs' metadata, like
+//!   `[{"name": "hw1.bram", "modified": 1700000000000, "size": 2048, "author": "Alice"}]`,
+//!   where `modified` is in milliseconds since the Unix epoch
+//! + `GET {base}/proofs/{name}` with the contents of the proof, or status 404
+//!   if there's none with that name
+//! + `PUT {base}/proofs/{name}` with the contents of the proof as the body,
+//!   saving it
+//!
+//! Proof names in URLs are percent-encoded. Requests include cookies, so the
+//! server can tell which user is saving.
+
+use super::ProofStore;
+use super::StoreError;
+use super::StoredProof;
+use crate::offline;
+
+use gloo::net::http::Request;
+use gloo::net::http::Response;
+use web_sys::RequestCredentials;
+use yew::platform::spawn_local;
+use yew::Callback;
+
+/// Proofs kept on a server
+pub struct RemoteStore {
+    /// URL the paths of requests are relative to, without a trailing slash
+    base_url: String,
+}
+
+impl RemoteStore {
+    pub fn new(base_url: &str) -> Self {
+        Self { base_url: base_url.trim().trim_end_matches('/').to_string() }
+    }
+
+    fn proof_url(&self, name: &str) -> String {
+        format!("{}/proofs/{}", self.base_url, String::from(js_sys::encode_uri_component(name)))
+    }
+
+    /// Check the server is configured, so requests don't go to the app's own
+    /// server by accident
+    fn check_configured(&self) -> Result<(), StoreError> {
+        if self.base_url.is_empty() {
+            Err(StoreError::Unavailable("no server is set in the settings".into()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Fail on responses with an error status, such as 404 for a missing proof
+fn check_status(response: Response, name: &str) -> Result<Response, StoreError> {
+    match response.status() {
+        404 => Err(StoreError::NotFound(name.to_string())),
+        _ if response.ok() => Ok(response),
+        status => Err(StoreError::Failed(format!("The server responded with {} {}", status, response.status_text()))),
+    }
+}
+
+fn request_failed(err: gloo::net::Error) -> StoreError {
+    if !offline::is_online() {
+        return StoreError::Unavailable("you're offline. Proofs can still be saved in this browser, picked in the settings.".into());
+    }
+    StoreError::Failed(format!("Couldn't reach the server: {err}"))
+}
+
+impl ProofStore for RemoteStore {
+    fn location(&self) -> String {
+        self.base_url.clone()
+    }
+
+    fn list(&self, done: Callback<Result<Vec<StoredProof>, StoreError>>) {
+        if let Err(err) = self.check_configured() {
+            return done.emit(Err(err));
+        }
+        let url = format!("{}/proofs", self.base_url);
+        spawn_local(async move {
+            let result = async {
+                let response = Request::get(&url).credentials(RequestCredentials::Include).send().await.map_err(request_failed)?;
+                let text = check_status(response, "")?.text().await.map_err(request_failed)?;
+                let proofs = js_sys::JSON::parse(&text).map_err(|_| StoreError::Failed("The server's list of proofs isn't valid JSON".into()))?;
+                Ok::<_, StoreError>(StoredProof::list_from_js(&proofs))
+            };
+            done.emit(result.await);
+        });
+    }
+
+    fn load(&self, name: &str, done: Callback<Result<Vec<u8>, StoreError>>) {
+        if let Err(err) = self.check_configured() {
+            return done.emit(Err(err));
+        }
+        let url = self.proof_url(name);
+        let name = name.to_string();
+        spawn_local(async move {
+            let result = async {
+                let response = Request::get(&url).credentials(RequestCredentials::Include).send().await.map_err(request_failed)?;
+                check_status(response, &name)?.binary().await.map_err(request_failed)
+            };
+            done.emit(result.await);
+        });
+    }
+
+    fn save(&self, name: &str, data: Vec<u8>, done: Callback<Result<StoredProof, StoreError>>) {
+        if let Err(err) = self.check_configured() {
+            return done.emit(Err(err));
+        }
+        let url = self.proof_url(name);
+        let proof = StoredProof::new(name, &data);
+        spawn_local(async move {
+            let result = async {
+                let request = Request::put(&url).credentials(RequestCredentials::Include).header("Content-Type", "application/xml").body(String::from_utf8_lossy(&data).into_owned()).map_err(request_failed)?;
+                let response = request.send().await.map_err(request_failed)?;
+                check_status(response, &proof.name)?;
+                Ok::<_, StoreError>(proof)
+            };
+            done.emit(result.await);
+        });
+    }
+}
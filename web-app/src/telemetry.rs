@@ -0,0 +1,175 @@
+//! Live progress reports for instructor dashboards
+//!
+//! During lab sessions, instructors and TAs can watch how students are doing
+//! on an instructor dashboard. Students opt in by opening the app with a
+//! `telemetry` query parameter naming a WebSocket relay, like
+//! `?telemetry=wss://lab.example.edu/aris`. Their proofs then report their
+//! progress to the relay, which forwards the reports to every dashboard
+//! connected to it.
+//!
+//! Messages are JSON objects. Dashboards announce themselves to the relay with
+//! `{"type": "dashboard"}`, and proofs send reports like:
+//!
+//! ```json
+//! {
+//!     "type": "report",
+//!     "client": "k3x9q2",
+//!     "proof": "proof-2",
+//!     "problem": "(P → Q), P ⊢ Q",
+//!     "lines": 3,
+//!     "correct": 2,
+//!     "incorrect": 1,
+//!     "unparsed": 0,
+//!     "gaps": 0,
+//!     "errors": ["does-not-occur"],
+//!     "warnings": ["restates"],
+//!     "solved": false
+//! }
+//! ```
+//!
+//! Reports are anonymized: `client` is picked at random on every visit, and
+//! errors are only reported by kind, like `"does-not-occur"`, not with the
+//! student's formulas. `proof` tells apart the proofs a client has open.
+
+use crate::util::json_field;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use gloo::events::EventListener;
+use wasm_bindgen::JsValue;
+use web_sys::WebSocket;
+
+/// Query parameter naming the relay to report to
+const RELAY_PARAMETER: &str = "telemetry";
+
+/// Message dashboards announce themselves to the relay with
+pub const DASHBOARD_HELLO: &str = r#"{"type":"dashboard"}"#;
+
+/// Progress of a proof, as reported to dashboards
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Identifies the proof among the ones the client has open
+    pub proof: String,
+    /// The premises and goals of the proof, like `"(P → Q), P ⊢ Q"`
+    pub problem: String,
+    /// Number of lines that aren't blank
+    pub lines: u32,
+    pub correct: u32,
+    pub incorrect: u32,
+    /// Number of lines that don't parse
+    pub unparsed: u32,
+    /// Number of steps admitted without proof, to be filled in later
+    pub gaps: u32,
+    /// Codes of the errors of incorrect lines, as given by
+    /// `ProofCheckError::code()`
+    pub errors: Vec<String>,
+    /// Codes of the warnings about correct lines, as given by `Lint::code()`
+    pub warnings: Vec<String>,
+    pub solved: bool,
+}
+
+/// Connection to the relay, shared by every proof
+struct Connection {
+    socket: WebSocket,
+
+    /// The latest report of each proof, waiting for the socket to open
+    queued: BTreeMap<String, String>,
+
+    /// Handle for sending queued reports once the socket opens
+    #[allow(dead_code)]
+    open_listener: EventListener,
+}
+
+thread_local! {
+    static CLIENT_ID: String = random_id();
+    static CONNECTION: RefCell<Option<Connection>> = const { RefCell::new(None) };
+}
+
+fn random_id() -> String {
+    const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    (0..6).map(|_| DIGITS[(js_sys::Math::random() * DIGITS.len() as f64) as usize % DIGITS.len()] as char).collect()
+}
+
+/// URL of the relay the user opted into reporting to, if any
+pub fn relay_url() -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    web_sys::UrlSearchParams::new_with_str(&search).ok()?.get(RELAY_PARAMETER).filter(|url| !url.is_empty())
+}
+
+impl Report {
+    /// Write the report as a JSON message from this client
+    fn to_json(&self, client: &str) -> String {
+        let object = js_sys::Object::new();
+        let set = |field: &str, value: JsValue| {
+            let _ = js_sys::Reflect::set(&object, &JsValue::from_str(field), &value);
+        };
+        set("type", JsValue::from_str("report"));
+        set("client", JsValue::from_str(client));
+        set("proof", JsValue::from_str(&self.proof));
+        set("problem", JsValue::from_str(&self.problem));
+        set("lines", JsValue::from(self.lines));
+        set("correct", JsValue::from(self.correct));
+        set("incorrect", JsValue::from(self.incorrect));
+        set("unparsed", JsValue::from(self.unparsed));
+        set("gaps", JsValue::from(self.gaps));
+        set("errors", self.errors.iter().map(|code| JsValue::from_str(code)).collect::<js_sys::Array>().into());
+        set("warnings", self.warnings.iter().map(|code| JsValue::from_str(code)).collect::<js_sys::Array>().into());
+        set("solved", JsValue::from_bool(self.solved));
+        js_sys::JSON::stringify(&object).map(String::from).unwrap_or_default()
+    }
+
+    /// Read a report message, returning the client that sent it along with
+    /// the report. Returns `None` for other messages.
+    pub fn from_json(text: &str) -> Option<(String, Report)> {
+        let object = js_sys::JSON::parse(text).ok()?;
+        if json_field(&object, "type")?.as_string()? != "report" {
+            return None;
+        }
+        let string = |field: &str| json_field(&object, field).and_then(|value| value.as_string()).unwrap_or_default();
+        let count = |field: &str| json_field(&object, field).and_then(|value| value.as_f64()).map(|n| n as u32).unwrap_or(0);
+        let codes = |field: &str| json_field(&object, field).filter(js_sys::Array::is_array).map(|codes| js_sys::Array::from(&codes).iter().filter_map(|code| code.as_string()).collect()).unwrap_or_default();
+        let report = Report { proof: string("proof"), problem: string("problem"), lines: count("lines"), correct: count("correct"), incorrect: count("incorrect"), unparsed: count("unparsed"), gaps: count("gaps"), errors: codes("errors"), warnings: codes("warnings"), solved: json_field(&object, "solved").and_then(|value| value.as_bool()).unwrap_or(false) };
+        Some((json_field(&object, "client")?.as_string()?, report))
+    }
+}
+
+/// Send a report to the relay, if the user opted into it. Connects to the
+/// relay if there's no connection, or if the last one closed.
+pub fn send(report: &Report) {
+    let url = match relay_url() {
+        Some(url) => url,
+        None => return,
+    };
+    let message = CLIENT_ID.with(|client| report.to_json(client));
+    CONNECTION.with(|connection| {
+        let mut connection = connection.borrow_mut();
+        let closed = connection.as_ref().map(|connection| connection.socket.ready_state() >= WebSocket::CLOSING).unwrap_or(true);
+        if closed {
+            *connection = connect(&url);
+        }
+        if let Some(connection) = connection.as_mut() {
+            if connection.socket.ready_state() == WebSocket::OPEN {
+                // Reports are only for watching live, so one that fails to
+                // send is superseded by the next
+                let _ = connection.socket.send_with_str(&message);
+            } else {
+                connection.queued.insert(report.proof.clone(), message);
+            }
+        }
+    });
+}
+
+fn connect(url: &str) -> Option<Connection> {
+    let socket = WebSocket::new(url).ok()?;
+    let open_listener = EventListener::once(&socket, "open", |_| {
+        CONNECTION.with(|connection| {
+            if let Some(connection) = connection.borrow_mut().as_mut() {
+                for message in std::mem::take(&mut connection.queued).into_values() {
+                    let _ = connection.socket.send_with_str(&message);
+                }
+            }
+        })
+    });
+    Some(Connection { socket, queued: BTreeMap::new(), open_listener })
+}
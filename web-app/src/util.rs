@@ -1,34 +1,28 @@
 use aris::expr::Expr;
 use aris::proofs::pooledproof::PooledProof;
-use aris::proofs::PjRef;
-use aris::proofs::Proof;
 
-use std::collections::HashMap;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
 
-use frunk_core::coproduct::Coproduct;
 use frunk_core::HList;
 
 // yew doesn't seem to allow Components to be generic over <P: Proof>, so fix a proof type P at the module level
 pub type P = PooledProof<HList![Expr]>;
 
-pub fn calculate_lineinfo<P: Proof>(output: &mut HashMap<PjRef<P>, (usize, usize)>, prf: &<P as Proof>::Subproof, line: &mut usize, depth: &mut usize) {
-    for prem in prf.premises() {
-        output.insert(Coproduct::inject(prem.clone()), (*line, *depth));
-        *line += 1;
-    }
-    for lineref in prf.lines() {
-        use Coproduct::{Inl, Inr};
-        match lineref {
-            Inl(r) => {
-                output.insert(Coproduct::inject(r), (*line, *depth));
-                *line += 1;
-            }
-            Inr(Inl(sr)) => {
-                *depth += 1;
-                calculate_lineinfo::<P>(output, &prf.lookup_subproof(&sr).unwrap(), line, depth);
-                *depth -= 1;
-            }
-            Inr(Inr(void)) => match void {},
-        }
-    }
+/// A new ID for a component's elements, like `proof-3`, that no other
+/// component on the page has. IDs are numbered in the order they're made, so a
+/// page that creates the same components gets the same IDs every time.
+pub fn unique_id(prefix: &str) -> String {
+    static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+    format!("{prefix}-{}", NEXT_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// The browser's `localStorage`, if it's available
+pub fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Field of a parsed JSON object, if it's there and isn't `null`
+pub fn json_field(object: &wasm_bindgen::JsValue, field: &str) -> Option<wasm_bindgen::JsValue> {
+    js_sys::Reflect::get(object, &wasm_bindgen::JsValue::from_str(field)).ok().filter(|value| !value.is_undefined() && !value.is_null())
 }
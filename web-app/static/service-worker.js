@@ -0,0 +1,77 @@
+// Service worker letting Aris run offline, such as in lecture halls with bad
+// Wi-Fi. It's registered by `offline::register_service_worker()` in the web
+// app.
+//
+// Every file the app needs is cached when the worker is installed. Requests
+// for those files are answered from the cache right away, and the cached copy
+// is refreshed from the network in the background, so a rebuilt app is picked
+// up on the next visit. Every other request, such as to a course's proof
+// store, goes to the network as usual.
+
+// Increase this whenever `APP_FILES` changes, so old caches are deleted
+const CACHE_NAME = "aris-v1";
+
+const APP_FILES = [
+  "./",
+  "index.html",
+  "manifest.webmanifest",
+  "icon.svg",
+  "styles.css",
+  "dark-theme.css",
+  "vendored/bootstrap.min.css",
+  "vendored/bootstrap.bundle.min.js",
+  "vendored/jquery-3.2.1.slim.min.js",
+  "pkg/aris_web_app.js",
+  "pkg/aris_web_app_bg.wasm",
+];
+
+self.addEventListener("install", (event) => {
+  event.waitUntil(
+    caches
+      .open(CACHE_NAME)
+      .then((cache) => cache.addAll(APP_FILES))
+      .then(() => self.skipWaiting())
+  );
+});
+
+self.addEventListener("activate", (event) => {
+  event.waitUntil(
+    caches
+      .keys()
+      .then((names) =>
+        Promise.all(
+          names
+            .filter((name) => name !== CACHE_NAME)
+            .map((name) => caches.delete(name))
+        )
+      )
+      .then(() => self.clients.claim())
+  );
+});
+
+self.addEventListener("fetch", (event) => {
+  const request = event.request;
+  if (request.method !== "GET") {
+    return;
+  }
+  event.respondWith(
+    caches.open(CACHE_NAME).then((cache) =>
+      // Ignore the query, so `./?telemetry=...` still opens offline
+      cache.match(request, { ignoreSearch: true }).then((cached) => {
+        if (!cached) {
+          return fetch(request);
+        }
+        const refreshed = fetch(request)
+          .then((response) => {
+            if (response.ok) {
+              return cache.put(request, response);
+            }
+          })
+          .catch(() => undefined);
+        // Keep the worker alive until the cache is refreshed
+        event.waitUntil(refreshed);
+        return cached;
+      })
+    )
+  );
+});